@@ -1,9 +1,12 @@
 //! ZK Key Generation Tool for NONOS.
 //!
-//! Generates Groth16 proving and verifying keys for the identity circuit.
+//! Generates Groth16 proving and verifying keys for the identity,
+//! note-mixer spend and credential range circuits.
 //!
 //! Usage:
 //!   cargo run --bin zk-keygen -- generate --output ./keys
+//!   cargo run --bin zk-keygen -- generate --output ./keys --circuit spend
+//!   cargo run --bin zk-keygen -- generate --output ./keys --circuit credential
 //!   cargo run --bin zk-keygen -- verify --vk ./keys/identity.vk.bin
 
 use ark_bn254::{Bn254, Fr};
@@ -20,6 +23,7 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_snark::SNARK;
 use ark_std::rand::thread_rng;
 use clap::{Parser, Subcommand};
+use nonos_crypto::{RangeKeys, SpendKeys, RANGE_VALUE_BITS, SPEND_MERKLE_DEPTH};
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::PathBuf;
@@ -44,7 +48,7 @@ enum Commands {
         #[arg(short, long, default_value = "./zk-keys")]
         output: PathBuf,
 
-        /// Circuit type to generate keys for.
+        /// Circuit type to generate keys for (identity, spend, credential).
         #[arg(short, long, default_value = "identity")]
         circuit: String,
     },
@@ -187,6 +191,8 @@ fn generate_keys(output_dir: &PathBuf, circuit: &str) -> Result<(), Box<dyn std:
 
     match circuit {
         "identity" => generate_identity_keys(output_dir)?,
+        "spend" => generate_spend_keys(output_dir)?,
+        "credential" => generate_credential_keys(output_dir)?,
         _ => {
             eprintln!("Unknown circuit type: {}", circuit);
             std::process::exit(1);
@@ -268,6 +274,109 @@ fn generate_identity_keys(output_dir: &PathBuf) -> Result<(), Box<dyn std::error
     Ok(())
 }
 
+fn generate_spend_keys(output_dir: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Generating note mixer spend circuit keys...");
+    println!("This may take several minutes.");
+    println!();
+
+    let mut rng = thread_rng();
+
+    println!("Running trusted setup (circuit-specific)...");
+    let keys = SpendKeys::generate(&mut rng)?;
+    println!("Setup complete.");
+    println!();
+
+    let pk_bytes = keys.proving_key_bytes()?;
+    let pk_path = output_dir.join("spend.pk.bin");
+    File::create(&pk_path)?.write_all(&pk_bytes)?;
+    println!("Proving key: {} ({} bytes)", pk_path.display(), pk_bytes.len());
+
+    let vk_bytes = keys.verifying_key_bytes()?;
+    let vk_path = output_dir.join("spend.vk.bin");
+    File::create(&vk_path)?.write_all(&vk_bytes)?;
+    println!("Verifying key: {} ({} bytes)", vk_path.display(), vk_bytes.len());
+
+    let vk_hash = compute_vk_hash(&vk_bytes);
+    let hash_path = output_dir.join("spend.vk.hash");
+    writeln!(File::create(&hash_path)?, "{}", vk_hash)?;
+    println!("VK hash: {}", vk_hash);
+
+    let meta_path = output_dir.join("spend.meta.json");
+    let metadata = serde_json::json!({
+        "circuit": "spend",
+        "version": CIRCUIT_VERSION,
+        "merkle_depth": SPEND_MERKLE_DEPTH,
+        "public_inputs": ["merkle_root", "nullifier", "recipient", "amount", "asset", "fee"],
+        "vk_hash": vk_hash,
+        "pk_size": pk_bytes.len(),
+        "vk_size": vk_bytes.len(),
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+    });
+    serde_json::to_writer_pretty(&mut File::create(&meta_path)?, &metadata)?;
+    println!("Metadata: {}", meta_path.display());
+
+    println!();
+    println!("Key generation complete!");
+    println!();
+    println!("To use these keys:");
+    println!("  1. Copy spend.vk.bin to <data_dir>/zk-keys/ on every mixer node");
+    println!("  2. Copy spend.pk.bin to wallets that need to generate spend proofs");
+    println!("  3. Verify the VK hash matches: {}", vk_hash);
+
+    Ok(())
+}
+
+fn generate_credential_keys(output_dir: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Generating credential range circuit keys...");
+    println!();
+
+    let mut rng = thread_rng();
+
+    println!("Running trusted setup (circuit-specific)...");
+    let keys = RangeKeys::generate(&mut rng)?;
+    println!("Setup complete.");
+    println!();
+
+    let pk_bytes = keys.proving_key_bytes()?;
+    let pk_path = output_dir.join("credential.pk.bin");
+    File::create(&pk_path)?.write_all(&pk_bytes)?;
+    println!("Proving key: {} ({} bytes)", pk_path.display(), pk_bytes.len());
+
+    let vk_bytes = keys.verifying_key_bytes()?;
+    let vk_path = output_dir.join("credential.vk.bin");
+    File::create(&vk_path)?.write_all(&vk_bytes)?;
+    println!("Verifying key: {} ({} bytes)", vk_path.display(), vk_bytes.len());
+
+    let vk_hash = compute_vk_hash(&vk_bytes);
+    let hash_path = output_dir.join("credential.vk.hash");
+    writeln!(File::create(&hash_path)?, "{}", vk_hash)?;
+    println!("VK hash: {}", vk_hash);
+
+    let meta_path = output_dir.join("credential.meta.json");
+    let metadata = serde_json::json!({
+        "circuit": "credential",
+        "version": CIRCUIT_VERSION,
+        "value_bits": RANGE_VALUE_BITS,
+        "public_inputs": ["commitment", "kind", "threshold", "context"],
+        "vk_hash": vk_hash,
+        "pk_size": pk_bytes.len(),
+        "vk_size": vk_bytes.len(),
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+    });
+    serde_json::to_writer_pretty(&mut File::create(&meta_path)?, &metadata)?;
+    println!("Metadata: {}", meta_path.display());
+
+    println!();
+    println!("Key generation complete!");
+    println!();
+    println!("To use these keys:");
+    println!("  1. Copy credential.vk.bin to <data_dir>/zk-keys/ on every node verifying credential proofs");
+    println!("  2. Copy credential.pk.bin to <data_dir>/zk-keys/ on nodes of credential holders");
+    println!("  3. Verify the VK hash matches: {}", vk_hash);
+
+    Ok(())
+}
+
 fn verify_key(vk_path: &PathBuf, expected_hash: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
     println!("Verifying key: {}", vk_path.display());
 
@@ -303,18 +412,35 @@ fn show_info(keys_dir: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
     println!("Directory: {}", keys_dir.display());
     println!();
 
-    let meta_path = keys_dir.join("identity.meta.json");
-    if meta_path.exists() {
+    let mut found = false;
+    for (circuit, label) in [
+        ("identity", "Identity Circuit"),
+        ("spend", "Spend Circuit"),
+        ("credential", "Credential Range Circuit"),
+    ] {
+        let meta_path = keys_dir.join(format!("{}.meta.json", circuit));
+        if !meta_path.exists() {
+            continue;
+        }
+        found = true;
+
         let meta_content = fs::read_to_string(&meta_path)?;
         let metadata: serde_json::Value = serde_json::from_str(&meta_content)?;
-        println!("Identity Circuit:");
+        println!("{}:", label);
         println!("  Version: {}", metadata["version"]);
-        println!("  Merkle depth: {}", metadata["merkle_depth"]);
+        if let Some(depth) = metadata.get("merkle_depth") {
+            println!("  Merkle depth: {}", depth);
+        }
+        if let Some(bits) = metadata.get("value_bits") {
+            println!("  Value bits: {}", bits);
+        }
         println!("  VK hash: {}", metadata["vk_hash"]);
         println!("  PK size: {} bytes", metadata["pk_size"]);
         println!("  VK size: {} bytes", metadata["vk_size"]);
         println!("  Generated: {}", metadata["generated_at"]);
-    } else {
+    }
+
+    if !found {
         println!("No keys found. Run 'zk-keygen generate' first.");
     }
 
@@ -0,0 +1,348 @@
+//! BIP-32 hierarchical deterministic derivation for secp256k1.
+//!
+//! Provides the standard Ethereum BIP-44 path `m/44'/60'/0'/0/i` so that a
+//! BIP-39 mnemonic yields the same addresses as MetaMask, Ledger and other
+//! wallets. Extended keys serialize to the usual `xprv` Base58Check form.
+
+use hmac::{Hmac, Mac};
+use nonos_types::{NonosError, NonosResult};
+use ripemd::Ripemd160;
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256, Sha512};
+use zeroize::Zeroize;
+
+type HmacSha512 = Hmac<Sha512>;
+
+thread_local! {
+    static SECP256K1_CTX: Secp256k1<secp256k1::SignOnly> = Secp256k1::signing_only();
+}
+
+/// First index of the hardened child range.
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// Ethereum BIP-44 external chain: `m/44'/60'/0'/0`.
+pub const ETH_BIP44_ACCOUNT_PATH: &str = "m/44'/60'/0'/0";
+
+const MASTER_HMAC_KEY: &[u8] = b"Bitcoin seed";
+const XPRV_VERSION: [u8; 4] = [0x04, 0x88, 0xAD, 0xE4];
+const EXTENDED_KEY_LEN: usize = 78;
+
+/// Full derivation path for Ethereum account `index`.
+pub fn eth_bip44_path(index: u32) -> String {
+    format!("{}/{}", ETH_BIP44_ACCOUNT_PATH, index)
+}
+
+/// Parses a path such as `m/44'/60'/0'/0/0` into child indices.
+pub fn parse_derivation_path(path: &str) -> NonosResult<Vec<u32>> {
+    let mut segments = path.split('/');
+    if segments.next() != Some("m") {
+        return Err(NonosError::Crypto(format!("Derivation path must start with 'm': {}", path)));
+    }
+
+    segments
+        .map(|segment| {
+            let (number, hardened) = match segment.strip_suffix('\'').or_else(|| segment.strip_suffix('h')) {
+                Some(n) => (n, true),
+                None => (segment, false),
+            };
+            let index: u32 = number
+                .parse()
+                .map_err(|_| NonosError::Crypto(format!("Invalid path segment: {}", segment)))?;
+            if index >= HARDENED_OFFSET {
+                return Err(NonosError::Crypto(format!("Path index out of range: {}", segment)));
+            }
+            Ok(if hardened { index + HARDENED_OFFSET } else { index })
+        })
+        .collect()
+}
+
+/// BIP-32 extended private key.
+#[derive(Clone)]
+pub struct ExtendedPrivateKey {
+    private_key: [u8; 32],
+    chain_code: [u8; 32],
+    depth: u8,
+    parent_fingerprint: [u8; 4],
+    child_number: u32,
+}
+
+impl ExtendedPrivateKey {
+    /// Master key from a BIP-39 seed (16 to 64 bytes).
+    pub fn from_seed(seed: &[u8]) -> NonosResult<Self> {
+        if seed.len() < 16 || seed.len() > 64 {
+            return Err(NonosError::Crypto(format!("Invalid BIP-32 seed length: {}", seed.len())));
+        }
+
+        let mut i = hmac_sha512(MASTER_HMAC_KEY, &[seed]);
+        let result = Self::from_hmac_output(&i, 0, [0u8; 4], 0);
+        i.zeroize();
+        result
+    }
+
+    fn from_hmac_output(
+        i: &[u8; 64],
+        depth: u8,
+        parent_fingerprint: [u8; 4],
+        child_number: u32,
+    ) -> NonosResult<Self> {
+        let mut private_key = [0u8; 32];
+        let mut chain_code = [0u8; 32];
+        private_key.copy_from_slice(&i[..32]);
+        chain_code.copy_from_slice(&i[32..]);
+
+        SecretKey::from_slice(&private_key)
+            .map_err(|_| NonosError::InvalidKey("Derived key is not a valid secp256k1 scalar".into()))?;
+
+        Ok(Self {
+            private_key,
+            chain_code,
+            depth,
+            parent_fingerprint,
+            child_number,
+        })
+    }
+
+    /// Derives the child at `index`; indices at or above
+    /// [`HARDENED_OFFSET`] are hardened.
+    pub fn derive_child(&self, index: u32) -> NonosResult<Self> {
+        let secret = self.secret_key()?;
+        let public = SECP256K1_CTX.with(|ctx| PublicKey::from_secret_key(ctx, &secret).serialize());
+
+        let mut i = if index >= HARDENED_OFFSET {
+            hmac_sha512(&self.chain_code, &[&[0u8], &self.private_key, &index.to_be_bytes()])
+        } else {
+            hmac_sha512(&self.chain_code, &[&public, &index.to_be_bytes()])
+        };
+
+        let mut il = [0u8; 32];
+        il.copy_from_slice(&i[..32]);
+        let tweak = Scalar::from_be_bytes(il)
+            .map_err(|_| NonosError::InvalidKey("Derived tweak out of range".into()));
+        il.zeroize();
+
+        let child_secret = secret
+            .add_tweak(&tweak?)
+            .map_err(|_| NonosError::InvalidKey("Derived child key is invalid".into()))?;
+        i[..32].copy_from_slice(&child_secret.secret_bytes());
+
+        let depth = self.depth
+            .checked_add(1)
+            .ok_or_else(|| NonosError::Crypto("Maximum derivation depth exceeded".into()))?;
+        let result = Self::from_hmac_output(&i, depth, fingerprint(&public), index);
+        i.zeroize();
+        result
+    }
+
+    /// Derives a descendant from a path relative to this key's root, e.g.
+    /// `m/44'/60'/0'/0`.
+    pub fn derive_path(&self, path: &str) -> NonosResult<Self> {
+        parse_derivation_path(path)?
+            .into_iter()
+            .try_fold(self.clone(), |key, index| key.derive_child(index))
+    }
+
+    /// Raw 32-byte secp256k1 private key.
+    pub fn private_key(&self) -> [u8; 32] {
+        self.private_key
+    }
+
+    /// Compressed SEC1 public key.
+    pub fn public_key(&self) -> NonosResult<[u8; 33]> {
+        let secret = self.secret_key()?;
+        Ok(SECP256K1_CTX.with(|ctx| PublicKey::from_secret_key(ctx, &secret).serialize()))
+    }
+
+    /// Depth in the tree (0 for the master key).
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Child number this key was derived at.
+    pub fn child_number(&self) -> u32 {
+        self.child_number
+    }
+
+    /// Serializes to the Base58Check `xprv` format.
+    pub fn to_base58(&self) -> String {
+        let mut data = Vec::with_capacity(EXTENDED_KEY_LEN + 4);
+        data.extend_from_slice(&XPRV_VERSION);
+        data.push(self.depth);
+        data.extend_from_slice(&self.parent_fingerprint);
+        data.extend_from_slice(&self.child_number.to_be_bytes());
+        data.extend_from_slice(&self.chain_code);
+        data.push(0);
+        data.extend_from_slice(&self.private_key);
+
+        let checksum = double_sha256(&data);
+        data.extend_from_slice(&checksum[..4]);
+
+        let encoded = bs58::encode(&data).into_string();
+        data.zeroize();
+        encoded
+    }
+
+    /// Parses a Base58Check `xprv` string.
+    pub fn from_base58(encoded: &str) -> NonosResult<Self> {
+        let mut data = bs58::decode(encoded.trim())
+            .into_vec()
+            .map_err(|e| NonosError::InvalidKey(format!("Invalid extended key encoding: {}", e)))?;
+
+        let result = Self::decode_payload(&data);
+        data.zeroize();
+        result
+    }
+
+    fn decode_payload(data: &[u8]) -> NonosResult<Self> {
+        if data.len() != EXTENDED_KEY_LEN + 4 {
+            return Err(NonosError::InvalidKey(format!("Invalid extended key length: {}", data.len())));
+        }
+
+        let (payload, checksum) = data.split_at(EXTENDED_KEY_LEN);
+        if double_sha256(payload)[..4] != *checksum {
+            return Err(NonosError::InvalidKey("Extended key checksum mismatch".into()));
+        }
+        if payload[..4] != XPRV_VERSION {
+            return Err(NonosError::InvalidKey("Not an xprv extended private key".into()));
+        }
+        if payload[45] != 0 {
+            return Err(NonosError::InvalidKey("Malformed extended private key".into()));
+        }
+
+        let mut i = [0u8; 64];
+        i[..32].copy_from_slice(&payload[46..78]);
+        i[32..].copy_from_slice(&payload[13..45]);
+
+        let mut parent_fingerprint = [0u8; 4];
+        parent_fingerprint.copy_from_slice(&payload[5..9]);
+        let child_number = u32::from_be_bytes([payload[9], payload[10], payload[11], payload[12]]);
+
+        let result = Self::from_hmac_output(&i, payload[4], parent_fingerprint, child_number);
+        i.zeroize();
+        result
+    }
+
+    fn secret_key(&self) -> NonosResult<SecretKey> {
+        SecretKey::from_slice(&self.private_key).map_err(|e| NonosError::InvalidKey(e.to_string()))
+    }
+}
+
+impl Drop for ExtendedPrivateKey {
+    fn drop(&mut self) {
+        self.private_key.zeroize();
+        self.chain_code.zeroize();
+    }
+}
+
+impl std::fmt::Debug for ExtendedPrivateKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExtendedPrivateKey")
+            .field("depth", &self.depth)
+            .field("child_number", &self.child_number)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Derives the private key for Ethereum account `index` at
+/// `m/44'/60'/0'/0/index` from a BIP-39 seed.
+pub fn derive_bip44_eth_key(seed: &[u8], index: u32) -> NonosResult<[u8; 32]> {
+    let master = ExtendedPrivateKey::from_seed(seed)?;
+    Ok(master.derive_path(&eth_bip44_path(index))?.private_key())
+}
+
+fn hmac_sha512(key: &[u8], parts: &[&[u8]]) -> [u8; 64] {
+    let mut mac = HmacSha512::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+fn double_sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(data)).into()
+}
+
+fn fingerprint(public_key: &[u8; 33]) -> [u8; 4] {
+    let hash = Ripemd160::digest(Sha256::digest(public_key));
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{derive_eth_address_from_private, mnemonic_to_seed};
+    use nonos_types::Secp256k1PrivateKey;
+
+    const TEST_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+    #[test]
+    fn test_bip32_vector1_master() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedPrivateKey::from_seed(&seed).unwrap();
+        assert_eq!(
+            master.to_base58(),
+            "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi"
+        );
+    }
+
+    #[test]
+    fn test_bip32_vector1_chain() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedPrivateKey::from_seed(&seed).unwrap();
+        let child = master.derive_path("m/0'/1/2'/2/1000000000").unwrap();
+        assert_eq!(
+            child.to_base58(),
+            "xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76"
+        );
+    }
+
+    #[test]
+    fn test_bip44_matches_reference_wallets() {
+        let seed = mnemonic_to_seed(TEST_MNEMONIC, "").unwrap();
+
+        let key0 = derive_bip44_eth_key(&seed, 0).unwrap();
+        assert_eq!(
+            hex::encode(key0),
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+        );
+
+        let address0 = derive_eth_address_from_private(&Secp256k1PrivateKey::from_bytes(key0)).unwrap();
+        assert_eq!(hex::encode(address0.as_bytes()), "f39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+
+        let key1 = derive_bip44_eth_key(&seed, 1).unwrap();
+        let address1 = derive_eth_address_from_private(&Secp256k1PrivateKey::from_bytes(key1)).unwrap();
+        assert_eq!(hex::encode(address1.as_bytes()), "70997970c51812dc3a010c7d01b50e0d17dc79c8");
+    }
+
+    #[test]
+    fn test_xprv_roundtrip() {
+        let seed = mnemonic_to_seed(TEST_MNEMONIC, "").unwrap();
+        let account = ExtendedPrivateKey::from_seed(&seed)
+            .unwrap()
+            .derive_path(ETH_BIP44_ACCOUNT_PATH)
+            .unwrap();
+
+        let encoded = account.to_base58();
+        let decoded = ExtendedPrivateKey::from_base58(&encoded).unwrap();
+        assert_eq!(decoded.to_base58(), encoded);
+        assert_eq!(decoded.depth(), 4);
+        assert_eq!(
+            decoded.derive_child(0).unwrap().private_key(),
+            derive_bip44_eth_key(&seed, 0).unwrap()
+        );
+
+        let mut corrupted = encoded.into_bytes();
+        corrupted[20] = if corrupted[20] == b'a' { b'b' } else { b'a' };
+        assert!(ExtendedPrivateKey::from_base58(std::str::from_utf8(&corrupted).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_parse_derivation_path() {
+        assert_eq!(
+            parse_derivation_path("m/44'/60'/0'/0/7").unwrap(),
+            vec![44 + HARDENED_OFFSET, 60 + HARDENED_OFFSET, HARDENED_OFFSET, 0, 7]
+        );
+        assert!(parse_derivation_path("44'/60'").is_err());
+        assert!(parse_derivation_path("m/abc").is_err());
+        assert!(parse_derivation_path("m/2147483648").is_err());
+    }
+}
@@ -0,0 +1,479 @@
+//! EIP-712 typed structured data hashing.
+//!
+//! Parses the `eth_signTypedData_v4` JSON document and produces the digest
+//! passed to [`crate::sign_message`]: `keccak256(0x1901 || domainSeparator || hashStruct(message))`.
+
+use crate::{keccak256, typed_data_hash};
+use nonos_types::{NonosError, NonosResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Name of the domain struct type.
+pub const EIP712_DOMAIN_TYPE: &str = "EIP712Domain";
+
+/// Domain fields in the order defined by EIP-712, used when a document
+/// omits an explicit `EIP712Domain` type.
+const DOMAIN_FIELDS: [(&str, &str); 5] = [
+    ("name", "string"),
+    ("version", "string"),
+    ("chainId", "uint256"),
+    ("verifyingContract", "address"),
+    ("salt", "bytes32"),
+];
+
+/// A member of a struct type.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypedDataField {
+    /// Member name.
+    pub name: String,
+    /// Solidity type, e.g. `uint256`, `Person[]`.
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+/// An `eth_signTypedData_v4` document.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    /// Struct type definitions, keyed by type name.
+    pub types: BTreeMap<String, Vec<TypedDataField>>,
+    /// Type of `message`.
+    pub primary_type: String,
+    /// Domain values hashed under `EIP712Domain`.
+    pub domain: Value,
+    /// Message values hashed under `primary_type`.
+    pub message: Value,
+}
+
+impl TypedData {
+    /// Parses a typed-data JSON document.
+    pub fn from_json(json: &str) -> NonosResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| NonosError::Serialization(format!("Invalid typed data: {}", e)))
+    }
+
+    /// Digest to sign: `keccak256(0x1901 || domainSeparator || hashStruct(message))`.
+    pub fn signing_hash(&self) -> NonosResult<[u8; 32]> {
+        let domain_separator = self.domain_separator()?;
+        let struct_hash = self.hash_struct(&self.primary_type, &self.message)?;
+        Ok(typed_data_hash(&domain_separator, &struct_hash))
+    }
+
+    /// `hashStruct(EIP712Domain, domain)`.
+    pub fn domain_separator(&self) -> NonosResult<[u8; 32]> {
+        if self.types.contains_key(EIP712_DOMAIN_TYPE) {
+            return self.hash_struct(EIP712_DOMAIN_TYPE, &self.domain);
+        }
+
+        let domain = self.domain.as_object()
+            .ok_or_else(|| typed_data_error("domain must be an object"))?;
+        let fields = DOMAIN_FIELDS
+            .iter()
+            .filter(|(name, _)| domain.contains_key(*name))
+            .map(|(name, field_type)| TypedDataField {
+                name: name.to_string(),
+                field_type: field_type.to_string(),
+            })
+            .collect();
+
+        let mut with_domain = self.clone();
+        with_domain.types.insert(EIP712_DOMAIN_TYPE.to_string(), fields);
+        with_domain.hash_struct(EIP712_DOMAIN_TYPE, &self.domain)
+    }
+
+    /// `keccak256(typeHash || encodeData(value))`.
+    pub fn hash_struct(&self, type_name: &str, value: &Value) -> NonosResult<[u8; 32]> {
+        Ok(keccak256(&self.encode_data(type_name, value)?))
+    }
+
+    /// `keccak256(encodeType(type_name))`.
+    pub fn type_hash(&self, type_name: &str) -> NonosResult<[u8; 32]> {
+        Ok(keccak256(self.encode_type(type_name)?.as_bytes()))
+    }
+
+    /// The primary type followed by its referenced struct types in
+    /// alphabetical order, e.g. `Mail(Person from,Person to,string contents)Person(string name,address wallet)`.
+    pub fn encode_type(&self, type_name: &str) -> NonosResult<String> {
+        let mut deps = BTreeSet::new();
+        self.collect_dependencies(type_name, &mut deps)?;
+        deps.remove(type_name);
+
+        let mut encoded = String::new();
+        for name in std::iter::once(type_name).chain(deps.iter().map(String::as_str)) {
+            let fields = self.struct_fields(name)?;
+            let members: Vec<String> = fields
+                .iter()
+                .map(|f| format!("{} {}", f.field_type, f.name))
+                .collect();
+            encoded.push_str(&format!("{}({})", name, members.join(",")));
+        }
+        Ok(encoded)
+    }
+
+    fn struct_fields(&self, type_name: &str) -> NonosResult<&[TypedDataField]> {
+        self.types
+            .get(type_name)
+            .map(Vec::as_slice)
+            .ok_or_else(|| typed_data_error(&format!("undefined struct type '{}'", type_name)))
+    }
+
+    fn collect_dependencies(&self, type_name: &str, deps: &mut BTreeSet<String>) -> NonosResult<()> {
+        if deps.contains(type_name) {
+            return Ok(());
+        }
+        deps.insert(type_name.to_string());
+
+        for field in self.struct_fields(type_name)? {
+            let base = base_type(&field.field_type);
+            if self.types.contains_key(base) {
+                self.collect_dependencies(base, deps)?;
+            } else if !is_atomic_type(base) {
+                return Err(typed_data_error(&format!(
+                    "unknown type '{}' for field '{}'", field.field_type, field.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_data(&self, type_name: &str, value: &Value) -> NonosResult<Vec<u8>> {
+        let object = value.as_object().ok_or_else(|| {
+            typed_data_error(&format!("value for '{}' must be an object", type_name))
+        })?;
+
+        let fields = self.struct_fields(type_name)?;
+        let mut encoded = Vec::with_capacity(32 * (fields.len() + 1));
+        encoded.extend_from_slice(&self.type_hash(type_name)?);
+
+        for field in fields {
+            let field_value = object.get(&field.name).ok_or_else(|| {
+                typed_data_error(&format!("missing value for '{}.{}'", type_name, field.name))
+            })?;
+            encoded.extend_from_slice(&self.encode_field(&field.field_type, field_value)?);
+        }
+        Ok(encoded)
+    }
+
+    fn encode_field(&self, field_type: &str, value: &Value) -> NonosResult<[u8; 32]> {
+        if let Some((element_type, length)) = split_array_type(field_type)? {
+            let items = value.as_array().ok_or_else(|| {
+                typed_data_error(&format!("value for '{}' must be an array", field_type))
+            })?;
+            if let Some(expected) = length {
+                if items.len() != expected {
+                    return Err(typed_data_error(&format!(
+                        "'{}' expects {} elements, got {}", field_type, expected, items.len()
+                    )));
+                }
+            }
+
+            let mut encoded = Vec::with_capacity(32 * items.len());
+            for item in items {
+                encoded.extend_from_slice(&self.encode_field(element_type, item)?);
+            }
+            return Ok(keccak256(&encoded));
+        }
+
+        if self.types.contains_key(field_type) {
+            if value.is_null() {
+                return Ok([0u8; 32]);
+            }
+            return self.hash_struct(field_type, value);
+        }
+
+        encode_atomic(field_type, value)
+    }
+}
+
+fn typed_data_error(message: &str) -> NonosError {
+    NonosError::Serialization(format!("Invalid typed data: {}", message))
+}
+
+/// Strips all array suffixes: `Person[][3]` -> `Person`.
+fn base_type(field_type: &str) -> &str {
+    field_type.split('[').next().unwrap_or(field_type)
+}
+
+/// Splits the outermost array dimension: `uint8[2][]` -> (`uint8[2]`, None).
+fn split_array_type(field_type: &str) -> NonosResult<Option<(&str, Option<usize>)>> {
+    if !field_type.ends_with(']') {
+        return Ok(None);
+    }
+
+    let open = field_type.rfind('[')
+        .ok_or_else(|| typed_data_error(&format!("malformed array type '{}'", field_type)))?;
+    let size = &field_type[open + 1..field_type.len() - 1];
+    let length = if size.is_empty() {
+        None
+    } else {
+        Some(size.parse().map_err(|_| {
+            typed_data_error(&format!("malformed array type '{}'", field_type))
+        })?)
+    };
+    Ok(Some((&field_type[..open], length)))
+}
+
+fn is_atomic_type(field_type: &str) -> bool {
+    match field_type {
+        "address" | "bool" | "string" | "bytes" => true,
+        _ => parse_bytes_size(field_type).is_some() || parse_int_type(field_type).is_some(),
+    }
+}
+
+fn parse_bytes_size(field_type: &str) -> Option<usize> {
+    let size: usize = field_type.strip_prefix("bytes")?.parse().ok()?;
+    (1..=32).contains(&size).then_some(size)
+}
+
+/// Returns `(signed, bits)` for `intN` / `uintN`.
+fn parse_int_type(field_type: &str) -> Option<(bool, u32)> {
+    let (signed, bits) = match field_type.strip_prefix("uint") {
+        Some(bits) => (false, bits),
+        None => (true, field_type.strip_prefix("int")?),
+    };
+    let bits: u32 = if bits.is_empty() { 256 } else { bits.parse().ok()? };
+    (bits % 8 == 0 && (8..=256).contains(&bits)).then_some((signed, bits))
+}
+
+fn encode_atomic(field_type: &str, value: &Value) -> NonosResult<[u8; 32]> {
+    let mut word = [0u8; 32];
+
+    match field_type {
+        "string" => {
+            let s = value.as_str().ok_or_else(|| typed_data_error("string value expected"))?;
+            return Ok(keccak256(s.as_bytes()));
+        }
+        "bytes" => return Ok(keccak256(&decode_hex_value(value)?)),
+        "bool" => {
+            let b = match value {
+                Value::Bool(b) => *b,
+                Value::String(s) if s == "true" || s == "false" => s == "true",
+                _ => return Err(typed_data_error("bool value expected")),
+            };
+            word[31] = b as u8;
+            return Ok(word);
+        }
+        "address" => {
+            let bytes = decode_hex_value(value)?;
+            if bytes.len() != 20 {
+                return Err(typed_data_error("address must be 20 bytes"));
+            }
+            word[12..].copy_from_slice(&bytes);
+            return Ok(word);
+        }
+        _ => {}
+    }
+
+    if let Some(size) = parse_bytes_size(field_type) {
+        let bytes = decode_hex_value(value)?;
+        if bytes.len() != size {
+            return Err(typed_data_error(&format!("{} expects {} bytes, got {}", field_type, size, bytes.len())));
+        }
+        word[..size].copy_from_slice(&bytes);
+        return Ok(word);
+    }
+
+    if let Some((signed, bits)) = parse_int_type(field_type) {
+        return encode_integer(value, signed, bits);
+    }
+
+    Err(typed_data_error(&format!("unknown type '{}'", field_type)))
+}
+
+fn decode_hex_value(value: &Value) -> NonosResult<Vec<u8>> {
+    let s = value.as_str().ok_or_else(|| typed_data_error("hex string expected"))?;
+    let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+    hex::decode(digits).map_err(|e| typed_data_error(&format!("invalid hex '{}': {}", s, e)))
+}
+
+/// Encodes a JSON number, decimal string or `0x` hex string as a 256-bit
+/// two's-complement word, rejecting values outside `intN`/`uintN`.
+fn encode_integer(value: &Value, signed: bool, bits: u32) -> NonosResult<[u8; 32]> {
+    let text = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.trim().to_string(),
+        _ => return Err(typed_data_error("integer value expected")),
+    };
+
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.as_str()),
+    };
+    if negative && !signed {
+        return Err(typed_data_error(&format!("negative value for uint{}", bits)));
+    }
+
+    let magnitude = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex_digits) => parse_radix(hex_digits, 16)?,
+        None => parse_radix(digits, 10)?,
+    };
+
+    // Largest magnitude allowed: 2^bits - 1 unsigned, 2^(bits-1) - 1 positive,
+    // 2^(bits-1) negative.
+    let limit_bits = if signed { bits - 1 } else { bits };
+    let limit = power_of_two(limit_bits);
+    let in_range = match limit {
+        None => true,
+        Some(limit) if negative => magnitude <= limit,
+        Some(limit) => magnitude < limit,
+    };
+    if !in_range {
+        return Err(typed_data_error(&format!(
+            "value {} out of range for {}int{}", text, if signed { "" } else { "u" }, bits
+        )));
+    }
+
+    Ok(if negative { negate(magnitude) } else { magnitude })
+}
+
+fn parse_radix(digits: &str, radix: u32) -> NonosResult<[u8; 32]> {
+    if digits.is_empty() {
+        return Err(typed_data_error("empty integer"));
+    }
+
+    let mut word = [0u8; 32];
+    for c in digits.chars() {
+        let digit = c.to_digit(radix)
+            .ok_or_else(|| typed_data_error(&format!("invalid integer '{}'", digits)))?;
+
+        let mut carry = digit;
+        for byte in word.iter_mut().rev() {
+            let v = (*byte as u32) * radix + carry;
+            *byte = v as u8;
+            carry = v >> 8;
+        }
+        if carry != 0 {
+            return Err(typed_data_error(&format!("integer '{}' exceeds 256 bits", digits)));
+        }
+    }
+    Ok(word)
+}
+
+/// `2^bits` as a big-endian word, or `None` when it does not fit (bits = 256).
+fn power_of_two(bits: u32) -> Option<[u8; 32]> {
+    if bits >= 256 {
+        return None;
+    }
+    let mut word = [0u8; 32];
+    word[31 - (bits / 8) as usize] = 1 << (bits % 8);
+    Some(word)
+}
+
+fn negate(word: [u8; 32]) -> [u8; 32] {
+    let mut out = word.map(|b| !b);
+    for byte in out.iter_mut().rev() {
+        let (v, overflow) = byte.overflowing_add(1);
+        *byte = v;
+        if !overflow {
+            break;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIL_EXAMPLE: &str = r#"{
+        "types": {
+            "EIP712Domain": [
+                {"name": "name", "type": "string"},
+                {"name": "version", "type": "string"},
+                {"name": "chainId", "type": "uint256"},
+                {"name": "verifyingContract", "type": "address"}
+            ],
+            "Person": [
+                {"name": "name", "type": "string"},
+                {"name": "wallet", "type": "address"}
+            ],
+            "Mail": [
+                {"name": "from", "type": "Person"},
+                {"name": "to", "type": "Person"},
+                {"name": "contents", "type": "string"}
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        },
+        "message": {
+            "from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+            "to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+            "contents": "Hello, Bob!"
+        }
+    }"#;
+
+    #[test]
+    fn test_eip712_mail_example() {
+        let typed = TypedData::from_json(MAIL_EXAMPLE).unwrap();
+
+        assert_eq!(
+            typed.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            hex::encode(typed.type_hash("Mail").unwrap()),
+            "a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2"
+        );
+        assert_eq!(
+            hex::encode(typed.domain_separator().unwrap()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            hex::encode(typed.hash_struct("Mail", &typed.message).unwrap()),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        assert_eq!(
+            hex::encode(typed.signing_hash().unwrap()),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+    }
+
+    #[test]
+    fn test_eip712_implicit_domain_type() {
+        let explicit = TypedData::from_json(MAIL_EXAMPLE).unwrap();
+        let mut implicit = explicit.clone();
+        implicit.types.remove(EIP712_DOMAIN_TYPE);
+
+        assert_eq!(implicit.signing_hash().unwrap(), explicit.signing_hash().unwrap());
+    }
+
+    #[test]
+    fn test_eip712_rejects_malformed_documents() {
+        let mut typed = TypedData::from_json(MAIL_EXAMPLE).unwrap();
+        typed.message["contents"] = Value::Null;
+        assert!(typed.signing_hash().is_err());
+
+        let mut typed = TypedData::from_json(MAIL_EXAMPLE).unwrap();
+        typed.types.get_mut("Person").unwrap()[1].field_type = "Wallet".into();
+        assert!(typed.signing_hash().is_err());
+
+        let mut typed = TypedData::from_json(MAIL_EXAMPLE).unwrap();
+        typed.message["from"]["wallet"] = Value::String("0x1234".into());
+        assert!(typed.signing_hash().is_err());
+    }
+
+    #[test]
+    fn test_encode_integer() {
+        let word = encode_integer(&Value::from(-1), true, 8).unwrap();
+        assert_eq!(word, [0xff; 32]);
+
+        let word = encode_integer(&Value::String("0x0100".into()), false, 16).unwrap();
+        assert_eq!(&word[30..], &[0x01, 0x00]);
+
+        assert!(encode_integer(&Value::from(256), false, 8).is_err());
+        assert!(encode_integer(&Value::from(128), true, 8).is_err());
+        assert!(encode_integer(&Value::from(-128), true, 8).is_ok());
+        assert!(encode_integer(&Value::from(-1), false, 256).is_err());
+        assert!(encode_integer(
+            &Value::String("115792089237316195423570985008687907853269984665640564039457584007913129639935".into()),
+            false,
+            256,
+        ).is_ok());
+    }
+}
@@ -0,0 +1,208 @@
+//! ERC-5564 stealth addresses, scheme 1 (SECP256k1 with view tags).
+//!
+//! Unlike the BLAKE3 scheme in [`crate::stealth`], this is the scheme used by
+//! the on-chain `ERC5564Announcer`, so payments made by any compliant wallet
+//! can be detected. Scanning only needs the [`StealthViewingKey`]; the spend
+//! private key is required solely to derive the stealth private key.
+
+use crate::{derive_eth_address, derive_public_key, generate_private_key, keccak256, StealthKeyPair, StealthMetaAddress};
+use nonos_types::{EthAddress, NonosError, NonosResult, Secp256k1PrivateKey, Secp256k1PublicKey};
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+
+/// Scheme ID for SECP256k1 with view tags.
+pub const ERC5564_SCHEME_ID: u64 = 1;
+
+/// Canonical `ERC5564Announcer` deployment (same address on every chain).
+pub const ERC5564_ANNOUNCER: &str = "0x55649E01B5Df198D18D95b5cc5051630cfD45564";
+
+/// Canonical ERC-6538 stealth meta-address registry (same address on every
+/// chain).
+pub const ERC6538_REGISTRY: &str = "0x6538E6bf4B0eBd30A8Ea093027Ac2422ce5d6538";
+
+/// `Announcement(uint256,address,address,bytes,bytes)` event signature.
+pub const ERC5564_ANNOUNCEMENT_EVENT: &str = "Announcement(uint256,address,address,bytes,bytes)";
+
+/// The keys needed to detect payments without being able to spend them.
+#[derive(Clone)]
+pub struct StealthViewingKey {
+    pub view_private: Secp256k1PrivateKey,
+    pub spend_public: Secp256k1PublicKey,
+}
+
+impl StealthViewingKey {
+    pub fn new(view_private: Secp256k1PrivateKey, spend_public: Secp256k1PublicKey) -> Self {
+        Self {
+            view_private,
+            spend_public,
+        }
+    }
+}
+
+impl StealthKeyPair {
+    pub fn viewing_key(&self) -> StealthViewingKey {
+        StealthViewingKey::new(self.view_private.clone(), self.spend_public)
+    }
+}
+
+/// A freshly generated stealth address and the data its sender announces.
+#[derive(Clone, Debug)]
+pub struct Erc5564StealthAddress {
+    pub stealth_address: EthAddress,
+    pub ephemeral_pubkey: Secp256k1PublicKey,
+    pub view_tag: u8,
+}
+
+/// Topic 0 of the `Announcement` event.
+pub fn erc5564_announcement_topic() -> [u8; 32] {
+    keccak256(ERC5564_ANNOUNCEMENT_EVENT.as_bytes())
+}
+
+/// Generates a stealth address for `recipient_meta` as a sender would.
+pub fn generate_erc5564_stealth_address(
+    recipient_meta: &StealthMetaAddress,
+) -> NonosResult<Erc5564StealthAddress> {
+    let ephemeral_private = generate_private_key();
+    let ephemeral_pubkey = derive_public_key(&ephemeral_private)?;
+
+    let hashed_secret = hashed_shared_secret(&ephemeral_private, &recipient_meta.view_pubkey.0)?;
+    let stealth_pubkey = stealth_public_key(&recipient_meta.spend_pubkey, &hashed_secret)?;
+
+    Ok(Erc5564StealthAddress {
+        stealth_address: derive_eth_address(&stealth_pubkey)?,
+        ephemeral_pubkey,
+        view_tag: hashed_secret[0],
+    })
+}
+
+/// Checks whether an announcement is addressed to `key`.
+///
+/// `ephemeral_pubkey` may be compressed or uncompressed. `metadata` is the
+/// announced metadata, whose first byte is the view tag; announcements with
+/// a mismatching tag are rejected after a single ECDH, before any point
+/// addition.
+pub fn check_erc5564_announcement(
+    key: &StealthViewingKey,
+    ephemeral_pubkey: &[u8],
+    metadata: &[u8],
+    stealth_address: &EthAddress,
+) -> NonosResult<bool> {
+    let hashed_secret = hashed_shared_secret(&key.view_private, ephemeral_pubkey)?;
+
+    if let Some(view_tag) = metadata.first() {
+        if *view_tag != hashed_secret[0] {
+            return Ok(false);
+        }
+    }
+
+    let stealth_pubkey = stealth_public_key(&key.spend_public, &hashed_secret)?;
+    Ok(derive_eth_address(&stealth_pubkey)? == *stealth_address)
+}
+
+/// Derives the private key controlling the stealth address announced with
+/// `ephemeral_pubkey`.
+pub fn derive_erc5564_stealth_private_key(
+    keypair: &StealthKeyPair,
+    ephemeral_pubkey: &[u8],
+) -> NonosResult<Secp256k1PrivateKey> {
+    let hashed_secret = hashed_shared_secret(&keypair.view_private, ephemeral_pubkey)?;
+
+    let spend_sk = SecretKey::from_slice(&keypair.spend_private.0)
+        .map_err(|e| NonosError::InvalidKey(e.to_string()))?;
+    let tweak = Scalar::from_be_bytes(hashed_secret)
+        .map_err(|_| NonosError::Crypto("Stealth tweak out of range".into()))?;
+
+    let stealth_sk = spend_sk.add_tweak(&tweak)
+        .map_err(|e| NonosError::Crypto(e.to_string()))?;
+
+    Ok(Secp256k1PrivateKey::from_bytes(stealth_sk.secret_bytes()))
+}
+
+/// `keccak256` of the compressed ECDH point, as specified by scheme 1.
+fn hashed_shared_secret(private_key: &Secp256k1PrivateKey, public_key: &[u8]) -> NonosResult<[u8; 32]> {
+    let secret = SecretKey::from_slice(&private_key.0)
+        .map_err(|e| NonosError::InvalidKey(e.to_string()))?;
+    let public = PublicKey::from_slice(public_key)
+        .map_err(|e| NonosError::InvalidKey(e.to_string()))?;
+
+    let shared = public.mul_tweak(&Secp256k1::verification_only(), &Scalar::from(secret))
+        .map_err(|e| NonosError::Crypto(e.to_string()))?;
+
+    Ok(keccak256(&shared.serialize()))
+}
+
+fn stealth_public_key(spend_pubkey: &Secp256k1PublicKey, hashed_secret: &[u8; 32]) -> NonosResult<Secp256k1PublicKey> {
+    let spend = PublicKey::from_slice(&spend_pubkey.0)
+        .map_err(|e| NonosError::InvalidKey(e.to_string()))?;
+    let tweak = Scalar::from_be_bytes(*hashed_secret)
+        .map_err(|_| NonosError::Crypto("Stealth tweak out of range".into()))?;
+
+    let stealth = spend.add_exp_tweak(&Secp256k1::verification_only(), &tweak)
+        .map_err(|e| NonosError::Crypto(e.to_string()))?;
+
+    Ok(Secp256k1PublicKey::from_bytes(stealth.serialize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_announcement_topic() {
+        assert_eq!(
+            hex::encode(erc5564_announcement_topic()),
+            "5f0eab8057630ba7676c49b4f21a0231414e79474595be8e4c432fbf6bf0f4e7"
+        );
+    }
+
+    #[test]
+    fn test_generate_check_and_derive() {
+        let keypair = StealthKeyPair::generate().unwrap();
+        let announced = generate_erc5564_stealth_address(&keypair.meta_address()).unwrap();
+
+        assert!(check_erc5564_announcement(
+            &keypair.viewing_key(),
+            &announced.ephemeral_pubkey.0,
+            &[announced.view_tag, 0xee],
+            &announced.stealth_address,
+        ).unwrap());
+
+        let private = derive_erc5564_stealth_private_key(&keypair, &announced.ephemeral_pubkey.0).unwrap();
+        assert_eq!(crate::derive_eth_address_from_private(&private).unwrap(), announced.stealth_address);
+    }
+
+    #[test]
+    fn test_uncompressed_ephemeral_key() {
+        let keypair = StealthKeyPair::generate().unwrap();
+        let announced = generate_erc5564_stealth_address(&keypair.meta_address()).unwrap();
+
+        let uncompressed = PublicKey::from_slice(&announced.ephemeral_pubkey.0).unwrap().serialize_uncompressed();
+        assert!(check_erc5564_announcement(
+            &keypair.viewing_key(),
+            &uncompressed,
+            &[],
+            &announced.stealth_address,
+        ).unwrap());
+    }
+
+    #[test]
+    fn test_rejects_other_recipients() {
+        let keypair = StealthKeyPair::generate().unwrap();
+        let other = StealthKeyPair::generate().unwrap();
+        let announced = generate_erc5564_stealth_address(&other.meta_address()).unwrap();
+
+        assert!(!check_erc5564_announcement(
+            &keypair.viewing_key(),
+            &announced.ephemeral_pubkey.0,
+            &[],
+            &announced.stealth_address,
+        ).unwrap());
+
+        let wrong_tag = announced.view_tag.wrapping_add(1);
+        assert!(!check_erc5564_announcement(
+            &other.viewing_key(),
+            &announced.ephemeral_pubkey.0,
+            &[wrong_tag],
+            &announced.stealth_address,
+        ).unwrap());
+    }
+}
@@ -0,0 +1,422 @@
+//! Ethereum Web3 Secret Storage (keystore V3).
+//!
+//! Interoperable with geth, Foundry (`cast wallet`) and MetaMask JSON
+//! imports: AES-128-CTR with a scrypt or PBKDF2-HMAC-SHA256 derived key and
+//! a Keccak-256 MAC over `derived_key[16..32] || ciphertext`.
+
+use crate::{derive_eth_address_from_private, keccak256, random_bytes};
+use aes::Aes128;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use nonos_types::{NonosError, NonosResult, Secp256k1PrivateKey, SECP256K1_PRIVATE_KEY_SIZE};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+/// Keystore format version written and accepted.
+pub const KEYSTORE_VERSION: u32 = 3;
+
+const CIPHER: &str = "aes-128-ctr";
+const PBKDF2_PRF: &str = "hmac-sha256";
+const DKLEN: u32 = 32;
+const SALT_SIZE: usize = 32;
+const IV_SIZE: usize = 16;
+
+// Upper bounds applied when decrypting untrusted files, well above what
+// geth, Foundry and MetaMask write.
+const MAX_SCRYPT_LOG_N: u8 = 20;
+/// Memory for scrypt's `128 * r * n` byte table; geth's default uses all of it.
+const MAX_SCRYPT_MEMORY: u128 = 256 * 1024 * 1024;
+/// Bytes mixed over all `p` lanes, `128 * r * n * p`.
+const MAX_SCRYPT_WORK: u128 = 4 * MAX_SCRYPT_MEMORY;
+const MAX_PBKDF2_ROUNDS: u32 = 10_000_000;
+
+/// A keystore V3 document.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeystoreV3 {
+    /// Encrypted key material. geth writes `crypto`; some older tools `Crypto`.
+    #[serde(alias = "Crypto")]
+    pub crypto: KeystoreCrypto,
+    /// Random UUID identifying the file.
+    pub id: String,
+    /// Always [`KEYSTORE_VERSION`].
+    pub version: u32,
+    /// Lowercase hex address without `0x`, if present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+/// The `crypto` section of a keystore.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeystoreCrypto {
+    /// Cipher name; only `aes-128-ctr` is supported.
+    pub cipher: String,
+    /// Cipher parameters.
+    pub cipherparams: KeystoreCipherParams,
+    /// Encrypted private key.
+    #[serde(with = "hex_serde")]
+    pub ciphertext: Vec<u8>,
+    /// KDF name and parameters.
+    #[serde(flatten)]
+    pub kdf: KeystoreKdfParams,
+    /// Keccak-256 MAC.
+    #[serde(with = "hex_serde")]
+    pub mac: Vec<u8>,
+}
+
+/// AES-CTR parameters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeystoreCipherParams {
+    /// 16-byte initial counter.
+    #[serde(with = "hex_serde")]
+    pub iv: Vec<u8>,
+}
+
+/// KDF parameters as serialized in the `kdf` / `kdfparams` fields.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kdf", content = "kdfparams", rename_all = "lowercase")]
+pub enum KeystoreKdfParams {
+    /// scrypt parameters.
+    Scrypt(ScryptKdfParams),
+    /// PBKDF2-HMAC-SHA256 parameters.
+    Pbkdf2(Pbkdf2KdfParams),
+}
+
+/// scrypt `kdfparams`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScryptKdfParams {
+    /// Derived key length.
+    pub dklen: u32,
+    /// CPU/memory cost; a power of two.
+    pub n: u32,
+    /// Block size.
+    pub r: u32,
+    /// Parallelism.
+    pub p: u32,
+    /// Salt.
+    #[serde(with = "hex_serde")]
+    pub salt: Vec<u8>,
+}
+
+/// PBKDF2 `kdfparams`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pbkdf2KdfParams {
+    /// Iteration count.
+    pub c: u32,
+    /// Derived key length.
+    pub dklen: u32,
+    /// Pseudo-random function; only `hmac-sha256` is supported.
+    pub prf: String,
+    /// Salt.
+    #[serde(with = "hex_serde")]
+    pub salt: Vec<u8>,
+}
+
+/// KDF to use when writing a keystore.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeystoreKdf {
+    /// scrypt with cost `2^log_n`.
+    Scrypt {
+        /// log2 of the CPU/memory cost.
+        log_n: u8,
+        /// Block size.
+        r: u32,
+        /// Parallelism.
+        p: u32,
+    },
+    /// PBKDF2-HMAC-SHA256.
+    Pbkdf2 {
+        /// Iteration count.
+        iterations: u32,
+    },
+}
+
+impl KeystoreKdf {
+    /// geth's standard scrypt parameters (n = 2^18, r = 8, p = 1).
+    pub fn scrypt() -> Self {
+        Self::Scrypt { log_n: 18, r: 8, p: 1 }
+    }
+
+    /// PBKDF2 with 262144 rounds, as in the Web3 Secret Storage spec.
+    pub fn pbkdf2() -> Self {
+        Self::Pbkdf2 { iterations: 262_144 }
+    }
+}
+
+impl Default for KeystoreKdf {
+    fn default() -> Self {
+        Self::scrypt()
+    }
+}
+
+impl KeystoreV3 {
+    /// Parses a keystore JSON file.
+    pub fn from_json(json: &str) -> NonosResult<Self> {
+        let keystore: Self = serde_json::from_str(json)
+            .map_err(|e| NonosError::Serialization(format!("Invalid keystore: {}", e)))?;
+        if keystore.version != KEYSTORE_VERSION {
+            return Err(NonosError::Serialization(format!(
+                "Unsupported keystore version: {}", keystore.version
+            )));
+        }
+        Ok(keystore)
+    }
+
+    /// Serializes to pretty-printed JSON.
+    pub fn to_json(&self) -> NonosResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| NonosError::Serialization(e.to_string()))
+    }
+}
+
+/// Encrypts `private_key` into a keystore V3 document.
+pub fn encrypt_keystore(
+    private_key: &Secp256k1PrivateKey,
+    password: &[u8],
+    kdf: KeystoreKdf,
+) -> NonosResult<KeystoreV3> {
+    let address = derive_eth_address_from_private(private_key)?;
+
+    let salt = random_bytes::<SALT_SIZE>().to_vec();
+    let iv = random_bytes::<IV_SIZE>();
+
+    let kdf_params = match kdf {
+        KeystoreKdf::Scrypt { log_n, r, p } => KeystoreKdfParams::Scrypt(ScryptKdfParams {
+            dklen: DKLEN,
+            n: 1u32.checked_shl(log_n as u32)
+                .ok_or_else(|| NonosError::Crypto(format!("Invalid scrypt cost: 2^{}", log_n)))?,
+            r,
+            p,
+            salt,
+        }),
+        KeystoreKdf::Pbkdf2 { iterations } => KeystoreKdfParams::Pbkdf2(Pbkdf2KdfParams {
+            c: iterations,
+            dklen: DKLEN,
+            prf: PBKDF2_PRF.to_string(),
+            salt,
+        }),
+    };
+
+    let mut derived = derive_keystore_key(password, &kdf_params, u8::MAX)?;
+
+    let mut ciphertext = private_key.as_bytes().to_vec();
+    apply_aes_ctr(&derived[..16], &iv, &mut ciphertext)?;
+    let mac = keystore_mac(&derived, &ciphertext);
+    derived.zeroize();
+
+    Ok(KeystoreV3 {
+        crypto: KeystoreCrypto {
+            cipher: CIPHER.to_string(),
+            cipherparams: KeystoreCipherParams { iv: iv.to_vec() },
+            ciphertext,
+            kdf: kdf_params,
+            mac: mac.to_vec(),
+        },
+        id: uuid::Uuid::new_v4().to_string(),
+        version: KEYSTORE_VERSION,
+        address: Some(hex::encode(address.as_bytes())),
+    })
+}
+
+/// Decrypts a keystore V3 document, checking the MAC and, when present,
+/// the recorded address.
+pub fn decrypt_keystore(keystore: &KeystoreV3, password: &[u8]) -> NonosResult<Secp256k1PrivateKey> {
+    let crypto = &keystore.crypto;
+    if crypto.cipher != CIPHER {
+        return Err(NonosError::Crypto(format!("Unsupported keystore cipher: {}", crypto.cipher)));
+    }
+    if crypto.cipherparams.iv.len() != IV_SIZE {
+        return Err(NonosError::Crypto("Invalid keystore IV length".into()));
+    }
+    if crypto.ciphertext.len() != SECP256K1_PRIVATE_KEY_SIZE {
+        return Err(NonosError::Crypto("Invalid keystore ciphertext length".into()));
+    }
+
+    let mut derived = derive_keystore_key(password, &crypto.kdf, MAX_SCRYPT_LOG_N)?;
+
+    let mac = keystore_mac(&derived, &crypto.ciphertext);
+    if !bool::from(mac.as_slice().ct_eq(&crypto.mac)) {
+        derived.zeroize();
+        return Err(NonosError::Crypto("Keystore MAC mismatch: wrong password or corrupted file".into()));
+    }
+
+    let mut plaintext = crypto.ciphertext.clone();
+    let result = apply_aes_ctr(&derived[..16], &crypto.cipherparams.iv, &mut plaintext);
+    derived.zeroize();
+    result?;
+
+    let mut key_bytes = [0u8; SECP256K1_PRIVATE_KEY_SIZE];
+    key_bytes.copy_from_slice(&plaintext);
+    plaintext.zeroize();
+    let private_key = Secp256k1PrivateKey::from_bytes(key_bytes);
+    key_bytes.zeroize();
+
+    let address = derive_eth_address_from_private(&private_key)?;
+    if let Some(expected) = &keystore.address {
+        let expected = expected.strip_prefix("0x").unwrap_or(expected);
+        if !expected.eq_ignore_ascii_case(&hex::encode(address.as_bytes())) {
+            return Err(NonosError::Crypto("Keystore address does not match decrypted key".into()));
+        }
+    }
+
+    Ok(private_key)
+}
+
+fn derive_keystore_key(password: &[u8], params: &KeystoreKdfParams, max_log_n: u8) -> NonosResult<Vec<u8>> {
+    match params {
+        KeystoreKdfParams::Scrypt(p) => {
+            check_dklen(p.dklen)?;
+            if p.n < 2 || !p.n.is_power_of_two() {
+                return Err(NonosError::Crypto(format!("Invalid scrypt n: {}", p.n)));
+            }
+            let log_n = p.n.trailing_zeros() as u8;
+            let memory = 128 * p.r as u128 * p.n as u128;
+            if log_n > max_log_n || memory > MAX_SCRYPT_MEMORY || memory * p.p as u128 > MAX_SCRYPT_WORK {
+                return Err(NonosError::Crypto("scrypt parameters exceed supported limits".into()));
+            }
+
+            // RFC 7914 requires n < 2^(16r); files violating it are rejected,
+            // as they are by Foundry.
+            let scrypt_params = scrypt::Params::new(log_n, p.r, p.p)
+                .map_err(|e| NonosError::Crypto(format!("Invalid scrypt parameters: {}", e)))?;
+            let mut derived = vec![0u8; p.dklen as usize];
+            scrypt::scrypt(password, &p.salt, &scrypt_params, &mut derived)
+                .map_err(|e| NonosError::Crypto(format!("scrypt failed: {}", e)))?;
+            Ok(derived)
+        }
+        KeystoreKdfParams::Pbkdf2(p) => {
+            check_dklen(p.dklen)?;
+            if p.prf != PBKDF2_PRF {
+                return Err(NonosError::Crypto(format!("Unsupported PBKDF2 PRF: {}", p.prf)));
+            }
+            if p.c == 0 || p.c > MAX_PBKDF2_ROUNDS {
+                return Err(NonosError::Crypto(format!("Invalid PBKDF2 iteration count: {}", p.c)));
+            }
+
+            let mut derived = vec![0u8; p.dklen as usize];
+            pbkdf2::pbkdf2_hmac::<Sha256>(password, &p.salt, p.c, &mut derived);
+            Ok(derived)
+        }
+    }
+}
+
+fn check_dklen(dklen: u32) -> NonosResult<()> {
+    if !(DKLEN..=64).contains(&dklen) {
+        return Err(NonosError::Crypto(format!("Invalid keystore dklen: {}", dklen)));
+    }
+    Ok(())
+}
+
+fn apply_aes_ctr(key: &[u8], iv: &[u8], data: &mut [u8]) -> NonosResult<()> {
+    let mut cipher = Aes128Ctr::new_from_slices(key, iv)
+        .map_err(|_| NonosError::Crypto("Invalid AES-CTR key or IV length".into()))?;
+    cipher.apply_keystream(data);
+    Ok(())
+}
+
+fn keystore_mac(derived: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut input = Vec::with_capacity(16 + ciphertext.len());
+    input.extend_from_slice(&derived[16..32]);
+    input.extend_from_slice(ciphertext);
+    let mac = keccak256(&input);
+    input.zeroize();
+    mac
+}
+
+mod hex_serde {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&hex::encode(data))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s.strip_prefix("0x").unwrap_or(&s)).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC_PRIVATE_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+    #[test]
+    fn test_decrypt_spec_pbkdf2_vector() {
+        let keystore = KeystoreV3::from_json(r#"{
+            "crypto": {
+                "cipher": "aes-128-ctr",
+                "cipherparams": {"iv": "6087dab2f9fdbbfaddc31a909735c1e6"},
+                "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+                "kdf": "pbkdf2",
+                "kdfparams": {
+                    "c": 262144,
+                    "dklen": 32,
+                    "prf": "hmac-sha256",
+                    "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+                },
+                "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+            },
+            "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+            "version": 3
+        }"#).unwrap();
+
+        let key = decrypt_keystore(&keystore, b"testpassword").unwrap();
+        assert_eq!(key.to_hex(), SPEC_PRIVATE_KEY);
+        assert!(decrypt_keystore(&keystore, b"wrongpassword").is_err());
+    }
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        let private_key = Secp256k1PrivateKey::from_hex(SPEC_PRIVATE_KEY).unwrap();
+
+        for kdf in [
+            KeystoreKdf::Scrypt { log_n: 10, r: 8, p: 1 },
+            KeystoreKdf::Pbkdf2 { iterations: 1024 },
+        ] {
+            let keystore = encrypt_keystore(&private_key, b"hunter2", kdf).unwrap();
+            let json = keystore.to_json().unwrap();
+
+            let parsed = KeystoreV3::from_json(&json).unwrap();
+            assert_eq!(decrypt_keystore(&parsed, b"hunter2").unwrap().to_hex(), SPEC_PRIVATE_KEY);
+            assert!(decrypt_keystore(&parsed, b"hunter3").is_err());
+
+            let mut tampered = parsed.clone();
+            tampered.address = Some("00".repeat(20));
+            assert!(decrypt_keystore(&tampered, b"hunter2").is_err());
+        }
+    }
+
+    #[test]
+    fn test_rejects_excessive_scrypt_cost() {
+        let private_key = Secp256k1PrivateKey::from_hex(SPEC_PRIVATE_KEY).unwrap();
+        let mut keystore = encrypt_keystore(
+            &private_key,
+            b"pw",
+            KeystoreKdf::Scrypt { log_n: 4, r: 8, p: 1 },
+        ).unwrap();
+
+        if let KeystoreKdfParams::Scrypt(ref mut params) = keystore.crypto.kdf {
+            params.n = 1 << 30;
+        }
+        assert!(decrypt_keystore(&keystore, b"pw").is_err());
+
+        for (n, r, p) in [(1 << 20, 1 << 10, 1), (1 << 18, 8, 1 << 10)] {
+            if let KeystoreKdfParams::Scrypt(ref mut params) = keystore.crypto.kdf {
+                params.n = n;
+                params.r = r;
+                params.p = p;
+            }
+            let err = decrypt_keystore(&keystore, b"pw").unwrap_err();
+            assert!(err.to_string().contains("exceed supported limits"));
+        }
+    }
+}
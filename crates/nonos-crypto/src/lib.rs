@@ -12,6 +12,12 @@ pub mod poseidon;
 pub mod poseidon_canonical;
 pub mod mnemonic;
 pub mod zk_proofs;
+pub mod spend_proofs;
+pub mod range_proofs;
+pub mod bip32;
+pub mod eip712;
+pub mod keystore;
+pub mod erc5564;
 
 pub use blake3_ops::*;
 pub use secp256k1_ops::*;
@@ -33,6 +39,7 @@ pub use poseidon_canonical::{
     poseidon_hash1_field,
     fr_to_bytes as canonical_fr_to_bytes,
     bytes_to_fr as canonical_bytes_to_fr,
+    is_canonical_fr,
     poseidon_hash2 as canonical_hash2,
     poseidon_hash as canonical_hash,
     poseidon_commitment as canonical_commitment,
@@ -42,6 +49,12 @@ pub use poseidon_canonical::{
 };
 pub use mnemonic::*;
 pub use zk_proofs::*;
+pub use spend_proofs::*;
+pub use range_proofs::*;
+pub use bip32::*;
+pub use eip712::*;
+pub use keystore::*;
+pub use erc5564::*;
 
 pub fn random_bytes<const N: usize>() -> [u8; N] {
     use rand::RngCore;
@@ -102,6 +102,13 @@ pub fn bytes_to_fr(bytes: &[u8; 32]) -> Fr {
     Fr::from_le_bytes_mod_order(bytes)
 }
 
+/// Whether `bytes` is the canonical encoding of a field element, that is
+/// below the field order. Any other encoding aliases a smaller value under
+/// [`bytes_to_fr`].
+pub fn is_canonical_fr(bytes: &[u8; 32]) -> bool {
+    fr_to_bytes(&bytes_to_fr(bytes)) == *bytes
+}
+
 /// Hash two 32-byte arrays.
 pub fn poseidon_hash2(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
     let result = poseidon_hash2_fields(bytes_to_fr(left), bytes_to_fr(right));
@@ -214,6 +221,11 @@ impl PoseidonMerkleTree {
         index
     }
 
+    /// Drop every leaf from `len` on, undoing later inserts.
+    pub fn truncate(&mut self, len: usize) {
+        self.leaves.truncate(len);
+    }
+
     /// Get current root.
     pub fn root(&self) -> [u8; 32] {
         fr_to_bytes(&self.root_field())
@@ -225,16 +237,11 @@ impl PoseidonMerkleTree {
             return self.zero_values[self.depth];
         }
 
-        let target_len = 1usize << self.depth;
+        // Only the populated prefix is hashed; fully empty subtrees are
+        // covered by the precomputed zero values.
         let mut level: Vec<Fr> = self.leaves.clone();
 
-        // Pad to full level with zero leaves
-        while level.len() < target_len {
-            level.push(self.zero_values[0]);
-        }
-
-        let mut depth_idx = 0;
-        while level.len() > 1 {
+        for depth_idx in 0..self.depth {
             let mut next_level = Vec::with_capacity(level.len() / 2);
             for chunk in level.chunks(2) {
                 let left = chunk[0];
@@ -246,7 +253,6 @@ impl PoseidonMerkleTree {
                 next_level.push(poseidon_hash2_fields(left, right));
             }
             level = next_level;
-            depth_idx += 1;
         }
 
         level[0]
@@ -262,18 +268,11 @@ impl PoseidonMerkleTree {
 
     /// Get Merkle proof as field elements.
     pub fn proof_field(&self, index: usize) -> Vec<(Fr, bool)> {
-        let mut proof = Vec::new();
-        let target_len = 1usize << self.depth;
-
+        let mut proof = Vec::with_capacity(self.depth);
         let mut level: Vec<Fr> = self.leaves.clone();
-        while level.len() < target_len {
-            level.push(self.zero_values[0]);
-        }
-
         let mut idx = index;
-        let mut depth_idx = 0;
 
-        while level.len() > 1 {
+        for depth_idx in 0..self.depth {
             let sibling_idx = if idx % 2 == 0 { idx + 1 } else { idx - 1 };
             let is_left = idx % 2 == 0;
 
@@ -297,7 +296,6 @@ impl PoseidonMerkleTree {
             }
             level = next_level;
             idx /= 2;
-            depth_idx += 1;
         }
 
         proof
@@ -472,6 +470,25 @@ mod tests {
         assert!(!PoseidonMerkleTree::verify_proof(&leaf1, &proof1, &wrong_root));
     }
 
+    #[test]
+    fn test_merkle_root_matches_padded_tree() {
+        let mut tree = PoseidonMerkleTree::new(2);
+        let leaves = [[0x11; 32], [0x22; 32], [0x33; 32]];
+        for leaf in &leaves {
+            tree.insert(*leaf);
+        }
+
+        // Fully padded depth-2 tree: the fourth leaf is the zero leaf H(0)
+        let zero_leaf = poseidon_hash1_field(Fr::from(0u64));
+        let left = poseidon_hash2_fields(bytes_to_fr(&leaves[0]), bytes_to_fr(&leaves[1]));
+        let right = poseidon_hash2_fields(bytes_to_fr(&leaves[2]), zero_leaf);
+        let expected = fr_to_bytes(&poseidon_hash2_fields(left, right));
+
+        assert_eq!(tree.root(), expected);
+        assert_eq!(tree.proof(2).len(), 2);
+        assert!(PoseidonMerkleTree::verify_proof(&leaves[2], &tree.proof(2), &expected));
+    }
+
     #[test]
     fn test_empty_tree() {
         let tree = PoseidonMerkleTree::new(4);
@@ -0,0 +1,351 @@
+//! Groth16 range circuit for numeric credentials.
+//!
+//! Proves that the value under a credential commitment
+//! `H(kind, value, salt)` is at least a public threshold without revealing
+//! it. The issuer signs the commitment, so a verifier that trusts the issuer
+//! learns only that the value it attested clears the threshold. The value
+//! is shown to be `threshold + diff` with `diff` decomposed into
+//! [`RANGE_VALUE_BITS`] bits, which rules out wrapping around the field.
+//!
+//! Public input order: `[commitment, kind, threshold, context]`.
+
+use crate::poseidon_canonical::{bytes_to_fr, canonical_config, fr_to_bytes, poseidon_hash_fields};
+use ark_bn254::{Bn254, Fr};
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::{alloc::AllocVar, boolean::Boolean, eq::EqGadget, fields::fp::FpVar};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use nonos_types::{NonosError, NonosResult};
+
+/// Width of the gap between value and threshold the circuit can express.
+pub const RANGE_VALUE_BITS: usize = 64;
+
+const ISSUANCE_DOMAIN: &[u8] = b"nonos-credential-issuance-v1";
+
+/// [`credential_commitment`] as a field element.
+pub fn credential_commitment_fr(kind: u64, value: u64, salt: Fr) -> Fr {
+    poseidon_hash_fields(&[Fr::from(kind), Fr::from(value), salt])
+}
+
+/// Commitment an issuer signs to attest `value` for a credential of `kind`.
+pub fn credential_commitment(kind: u64, value: u64, salt: &[u8; 32]) -> [u8; 32] {
+    fr_to_bytes(&credential_commitment_fr(kind, value, bytes_to_fr(salt)))
+}
+
+/// The message an issuer signs over a credential commitment.
+pub fn credential_issuance_message(kind: u64, commitment: &[u8; 32]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(ISSUANCE_DOMAIN.len() + 8 + 32);
+    message.extend_from_slice(ISSUANCE_DOMAIN);
+    message.extend_from_slice(&kind.to_be_bytes());
+    message.extend_from_slice(commitment);
+    message
+}
+
+/// What a range proof shows to its verifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangePublicInputs {
+    /// Commitment to the credential value, as signed by the issuer.
+    pub commitment: [u8; 32],
+    /// Credential kind the commitment was made for.
+    pub kind: u64,
+    /// Smallest value the proof accepts.
+    pub threshold: u64,
+    /// Binds the proof to a verifier's challenge so it cannot be replayed
+    /// elsewhere.
+    pub context: [u8; 32],
+}
+
+impl RangePublicInputs {
+    /// The inputs in circuit order.
+    pub fn to_field_elements(&self) -> Vec<Fr> {
+        vec![
+            bytes_to_fr(&self.commitment),
+            Fr::from(self.kind),
+            Fr::from(self.threshold),
+            bytes_to_fr(&self.context),
+        ]
+    }
+}
+
+/// The holder's side of a range proof: the opening of the commitment and
+/// the claim to prove about it.
+pub struct RangeProofInput {
+    /// Credential kind the commitment was made for.
+    pub kind: u64,
+    /// The attested value, kept secret.
+    pub value: u64,
+    /// Salt of the commitment, kept secret.
+    pub salt: [u8; 32],
+    /// Smallest value to prove `value` reaches.
+    pub threshold: u64,
+    /// Verifier binding, see [`RangePublicInputs::context`].
+    pub context: [u8; 32],
+}
+
+impl RangeProofInput {
+    /// The public inputs a proof of this input is verified against.
+    pub fn public_inputs(&self) -> RangePublicInputs {
+        RangePublicInputs {
+            commitment: credential_commitment(self.kind, self.value, &self.salt),
+            kind: self.kind,
+            threshold: self.threshold,
+            context: self.context,
+        }
+    }
+}
+
+/// The range circuit, with its witness when proving.
+#[derive(Clone)]
+pub struct RangeCircuit {
+    value: Option<Fr>,
+    salt: Option<Fr>,
+    diff_bits: Vec<Option<bool>>,
+    commitment: Option<Fr>,
+    kind: Option<Fr>,
+    threshold: Option<Fr>,
+    context: Option<Fr>,
+}
+
+impl RangeCircuit {
+    /// The circuit with `input` assigned. Fails if the value is below the
+    /// threshold, as no proof exists then.
+    pub fn new(input: &RangeProofInput) -> NonosResult<Self> {
+        let diff = input.value.checked_sub(input.threshold).ok_or_else(|| {
+            NonosError::Crypto("Credential value is below the threshold".into())
+        })?;
+        let public = input.public_inputs();
+
+        Ok(Self {
+            value: Some(Fr::from(input.value)),
+            salt: Some(bytes_to_fr(&input.salt)),
+            diff_bits: (0..RANGE_VALUE_BITS).map(|i| Some((diff >> i) & 1 == 1)).collect(),
+            commitment: Some(bytes_to_fr(&public.commitment)),
+            kind: Some(Fr::from(public.kind)),
+            threshold: Some(Fr::from(public.threshold)),
+            context: Some(bytes_to_fr(&public.context)),
+        })
+    }
+
+    /// The circuit without assignments, for key generation.
+    pub fn empty() -> Self {
+        Self {
+            value: None,
+            salt: None,
+            diff_bits: vec![None; RANGE_VALUE_BITS],
+            commitment: None,
+            kind: None,
+            threshold: None,
+            context: None,
+        }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for RangeCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let value = FpVar::new_witness(cs.clone(), || {
+            self.value.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let salt = FpVar::new_witness(cs.clone(), || {
+            self.salt.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let mut diff_bits = Vec::with_capacity(RANGE_VALUE_BITS);
+        for bit in &self.diff_bits {
+            diff_bits.push(Boolean::new_witness(cs.clone(), || {
+                bit.ok_or(SynthesisError::AssignmentMissing)
+            })?);
+        }
+
+        let commitment = FpVar::new_input(cs.clone(), || {
+            self.commitment.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let kind = FpVar::new_input(cs.clone(), || {
+            self.kind.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let threshold = FpVar::new_input(cs.clone(), || {
+            self.threshold.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        // The context takes no part in the relation. Groth16 verification
+        // fixes every public input, so a proof for one context does not
+        // verify for another.
+        let _context = FpVar::new_input(cs.clone(), || {
+            self.context.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let mut sponge = PoseidonSpongeVar::new(cs.clone(), canonical_config());
+        sponge.absorb(&vec![kind, value.clone(), salt])?;
+        let computed = sponge.squeeze_field_elements(1)?;
+        computed[0].enforce_equal(&commitment)?;
+
+        // value = threshold + diff with diff < 2^RANGE_VALUE_BITS.
+        let diff = Boolean::le_bits_to_fp_var(&diff_bits)?;
+        (threshold + diff).enforce_equal(&value)?;
+
+        Ok(())
+    }
+}
+
+/// Keys for the range circuit, as produced by `zk-keygen`.
+pub struct RangeKeys {
+    /// Needed by credential holders to prove.
+    pub proving_key: ProvingKey<Bn254>,
+    /// Needed by anyone checking proofs.
+    pub verifying_key: VerifyingKey<Bn254>,
+}
+
+impl RangeKeys {
+    /// Runs the circuit-specific trusted setup. Whoever runs it learns
+    /// the toxic waste, so production keys come from a ceremony.
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> NonosResult<Self> {
+        let (proving_key, verifying_key) =
+            Groth16::<Bn254>::circuit_specific_setup(RangeCircuit::empty(), rng)
+                .map_err(|e| NonosError::Crypto(format!("Range circuit setup failed: {}", e)))?;
+        Ok(Self { proving_key, verifying_key })
+    }
+
+    /// The proving key, compressed.
+    pub fn proving_key_bytes(&self) -> NonosResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.proving_key
+            .serialize_compressed(&mut bytes)
+            .map_err(|e| NonosError::Serialization(e.to_string()))?;
+        Ok(bytes)
+    }
+
+    /// The verifying key, compressed.
+    pub fn verifying_key_bytes(&self) -> NonosResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.verifying_key
+            .serialize_compressed(&mut bytes)
+            .map_err(|e| NonosError::Serialization(e.to_string()))?;
+        Ok(bytes)
+    }
+}
+
+/// Reads a proving key written by [`RangeKeys::proving_key_bytes`].
+pub fn load_range_proving_key(bytes: &[u8]) -> NonosResult<ProvingKey<Bn254>> {
+    ProvingKey::<Bn254>::deserialize_compressed(bytes)
+        .map_err(|e| NonosError::InvalidKey(format!("Invalid range proving key: {}", e)))
+}
+
+/// Reads and prepares a verifying key written by
+/// [`RangeKeys::verifying_key_bytes`].
+pub fn load_range_verifying_key(bytes: &[u8]) -> NonosResult<PreparedVerifyingKey<Bn254>> {
+    let vk = VerifyingKey::<Bn254>::deserialize_compressed(bytes)
+        .map_err(|e| NonosError::InvalidKey(format!("Invalid range verifying key: {}", e)))?;
+    Groth16::<Bn254>::process_vk(&vk)
+        .map_err(|e| NonosError::InvalidKey(format!("Range verifying key processing failed: {}", e)))
+}
+
+/// Returns the compressed Groth16 proof together with the public inputs it
+/// commits to.
+pub fn prove_range<R: RngCore + CryptoRng>(
+    proving_key: &ProvingKey<Bn254>,
+    input: &RangeProofInput,
+    rng: &mut R,
+) -> NonosResult<(Vec<u8>, RangePublicInputs)> {
+    let circuit = RangeCircuit::new(input)?;
+    let proof = Groth16::<Bn254>::prove(proving_key, circuit, rng)
+        .map_err(|e| NonosError::Crypto(format!("Range proof generation failed: {}", e)))?;
+
+    let mut bytes = Vec::new();
+    proof
+        .serialize_compressed(&mut bytes)
+        .map_err(|e| NonosError::Serialization(e.to_string()))?;
+
+    Ok((bytes, input.public_inputs()))
+}
+
+/// Checks a proof from [`prove_range`] against `public`. A proof that does
+/// not decode is reported as invalid rather than as an error.
+pub fn verify_range(
+    verifying_key: &PreparedVerifyingKey<Bn254>,
+    public: &RangePublicInputs,
+    proof_bytes: &[u8],
+) -> NonosResult<bool> {
+    let proof = match Proof::<Bn254>::deserialize_compressed(proof_bytes) {
+        Ok(proof) => proof,
+        Err(_) => return Ok(false),
+    };
+
+    Groth16::<Bn254>::verify_with_processed_vk(verifying_key, &public.to_field_elements(), &proof)
+        .map_err(|e| NonosError::Crypto(format!("Range verification error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::thread_rng;
+
+    fn input(value: u64, threshold: u64) -> RangeProofInput {
+        RangeProofInput { kind: 1, value, salt: [0x3c; 32], threshold, context: [0x42; 32] }
+    }
+
+    fn satisfied(circuit: RangeCircuit) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_range_circuit_satisfied() {
+        assert!(satisfied(RangeCircuit::new(&input(25, 18)).unwrap()));
+        assert!(satisfied(RangeCircuit::new(&input(18, 18)).unwrap()));
+        assert!(satisfied(RangeCircuit::new(&input(u64::MAX, 0)).unwrap()));
+    }
+
+    #[test]
+    fn test_range_circuit_rejects_value_below_threshold() {
+        assert!(RangeCircuit::new(&input(17, 18)).is_err());
+
+        // A prover claiming a larger threshold than its value allows cannot
+        // satisfy the circuit with any gap it makes up.
+        let mut circuit = RangeCircuit::new(&input(25, 18)).unwrap();
+        circuit.threshold = Some(Fr::from(30u64));
+        assert!(!satisfied(circuit));
+
+        let mut wrapped = RangeCircuit::new(&input(25, 18)).unwrap();
+        wrapped.threshold = Some(Fr::from(30u64));
+        wrapped.diff_bits = vec![Some(true); RANGE_VALUE_BITS];
+        assert!(!satisfied(wrapped));
+    }
+
+    #[test]
+    fn test_range_circuit_binds_commitment() {
+        let mut circuit = RangeCircuit::new(&input(25, 18)).unwrap();
+        circuit.commitment = Some(bytes_to_fr(&credential_commitment(1, 26, &[0x3c; 32])));
+        assert!(!satisfied(circuit));
+
+        let mut circuit = RangeCircuit::new(&input(25, 18)).unwrap();
+        circuit.kind = Some(Fr::from(2u64));
+        assert!(!satisfied(circuit));
+    }
+
+    #[test]
+    fn test_range_proof_binds_public_inputs() {
+        let mut rng = thread_rng();
+        let keys = RangeKeys::generate(&mut rng).unwrap();
+        let pvk = load_range_verifying_key(&keys.verifying_key_bytes().unwrap()).unwrap();
+
+        let (proof, public) = prove_range(&keys.proving_key, &input(25, 18), &mut rng).unwrap();
+        assert!(verify_range(&pvk, &public, &proof).unwrap());
+
+        let mut raised = public.clone();
+        raised.threshold = 21;
+        assert!(!verify_range(&pvk, &raised, &proof).unwrap());
+
+        let mut replayed = public.clone();
+        replayed.context = [0x43; 32];
+        assert!(!verify_range(&pvk, &replayed, &proof).unwrap());
+
+        let mut other_kind = public.clone();
+        other_kind.kind = 2;
+        assert!(!verify_range(&pvk, &other_kind, &proof).unwrap());
+
+        assert!(!verify_range(&pvk, &public, &[0u8; 16]).unwrap());
+    }
+}
@@ -0,0 +1,402 @@
+//! Groth16 spend circuit for the NONOS note mixer.
+//!
+//! Proves knowledge of a note whose canonical Poseidon commitment
+//! `H(secret, amount, asset, randomness)` is a leaf under an accepted Merkle
+//! root, and that the public nullifier equals `H(secret, commitment)`.
+//! Recipient and fee are bound as public inputs so a relayer cannot redirect
+//! the spend.
+//!
+//! Public input order: `[merkle_root, nullifier, recipient, amount, asset, fee]`.
+
+use crate::poseidon_canonical::{
+    bytes_to_fr, canonical_config, fr_to_bytes, is_canonical_fr, poseidon_hash_fields,
+};
+use ark_bn254::{Bn254, Fr};
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::fp::FpVar,
+    select::CondSelectGadget,
+};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use nonos_types::{NonosError, NonosResult};
+
+pub const SPEND_MERKLE_DEPTH: usize = 20;
+
+pub type NoteAsset = [u8; 8];
+
+pub fn asset_to_fr(asset: &NoteAsset) -> Fr {
+    let mut bytes = [0u8; 32];
+    bytes[..8].copy_from_slice(asset);
+    bytes_to_fr(&bytes)
+}
+
+pub fn note_commitment_fr(secret: Fr, amount: u128, asset: &NoteAsset, randomness: Fr) -> Fr {
+    poseidon_hash_fields(&[secret, Fr::from(amount), asset_to_fr(asset), randomness])
+}
+
+pub fn note_commitment(
+    secret: &[u8; 32],
+    amount: u128,
+    asset: &NoteAsset,
+    randomness: &[u8; 32],
+) -> [u8; 32] {
+    fr_to_bytes(&note_commitment_fr(
+        bytes_to_fr(secret),
+        amount,
+        asset,
+        bytes_to_fr(randomness),
+    ))
+}
+
+pub fn note_nullifier(secret: &[u8; 32], commitment: &[u8; 32]) -> [u8; 32] {
+    fr_to_bytes(&poseidon_hash_fields(&[bytes_to_fr(secret), bytes_to_fr(commitment)]))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpendPublicInputs {
+    pub merkle_root: [u8; 32],
+    pub nullifier: [u8; 32],
+    pub recipient: [u8; 32],
+    pub amount: u128,
+    pub asset: NoteAsset,
+    pub fee: u128,
+}
+
+impl SpendPublicInputs {
+    /// Whether the root, nullifier and recipient are canonical field
+    /// encodings. A proof verifies under every encoding of the same
+    /// element, so others would let a spend be replayed under a fresh
+    /// nullifier or redirected to different recipient bytes.
+    pub fn is_canonical(&self) -> bool {
+        [&self.merkle_root, &self.nullifier, &self.recipient].into_iter().all(is_canonical_fr)
+    }
+
+    pub fn to_field_elements(&self) -> Vec<Fr> {
+        vec![
+            bytes_to_fr(&self.merkle_root),
+            bytes_to_fr(&self.nullifier),
+            bytes_to_fr(&self.recipient),
+            Fr::from(self.amount),
+            asset_to_fr(&self.asset),
+            Fr::from(self.fee),
+        ]
+    }
+}
+
+pub struct SpendProofInput {
+    pub secret: [u8; 32],
+    pub randomness: [u8; 32],
+    pub amount: u128,
+    pub asset: NoteAsset,
+    /// Sibling path as returned by `PoseidonMerkleTree::proof`: `(sibling, is_left)`.
+    pub merkle_path: Vec<([u8; 32], bool)>,
+    pub merkle_root: [u8; 32],
+    pub recipient: [u8; 32],
+    pub fee: u128,
+}
+
+impl SpendProofInput {
+    pub fn public_inputs(&self) -> SpendPublicInputs {
+        let commitment = note_commitment(&self.secret, self.amount, &self.asset, &self.randomness);
+        SpendPublicInputs {
+            merkle_root: self.merkle_root,
+            nullifier: note_nullifier(&self.secret, &commitment),
+            recipient: self.recipient,
+            amount: self.amount,
+            asset: self.asset,
+            fee: self.fee,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SpendCircuit {
+    secret: Option<Fr>,
+    randomness: Option<Fr>,
+    merkle_path: Vec<Option<Fr>>,
+    path_is_right: Vec<Option<bool>>,
+    merkle_root: Option<Fr>,
+    nullifier: Option<Fr>,
+    recipient: Option<Fr>,
+    amount: Option<Fr>,
+    asset: Option<Fr>,
+    fee: Option<Fr>,
+}
+
+impl SpendCircuit {
+    pub fn new(input: &SpendProofInput) -> NonosResult<Self> {
+        if input.merkle_path.len() != SPEND_MERKLE_DEPTH {
+            return Err(NonosError::Crypto(format!(
+                "Merkle path must have {} levels, got {}",
+                SPEND_MERKLE_DEPTH,
+                input.merkle_path.len()
+            )));
+        }
+
+        let public = input.public_inputs();
+
+        Ok(Self {
+            secret: Some(bytes_to_fr(&input.secret)),
+            randomness: Some(bytes_to_fr(&input.randomness)),
+            merkle_path: input.merkle_path.iter().map(|(s, _)| Some(bytes_to_fr(s))).collect(),
+            path_is_right: input.merkle_path.iter().map(|(_, is_left)| Some(!is_left)).collect(),
+            merkle_root: Some(bytes_to_fr(&public.merkle_root)),
+            nullifier: Some(bytes_to_fr(&public.nullifier)),
+            recipient: Some(bytes_to_fr(&public.recipient)),
+            amount: Some(Fr::from(public.amount)),
+            asset: Some(asset_to_fr(&public.asset)),
+            fee: Some(Fr::from(public.fee)),
+        })
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            secret: None,
+            randomness: None,
+            merkle_path: vec![None; SPEND_MERKLE_DEPTH],
+            path_is_right: vec![None; SPEND_MERKLE_DEPTH],
+            merkle_root: None,
+            nullifier: None,
+            recipient: None,
+            amount: None,
+            asset: None,
+            fee: None,
+        }
+    }
+}
+
+fn poseidon_gadget(
+    cs: ConstraintSystemRef<Fr>,
+    inputs: &[FpVar<Fr>],
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut sponge = PoseidonSpongeVar::new(cs, canonical_config());
+    sponge.absorb(&inputs)?;
+    let output = sponge.squeeze_field_elements(1)?;
+    Ok(output[0].clone())
+}
+
+impl ConstraintSynthesizer<Fr> for SpendCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let secret = FpVar::new_witness(cs.clone(), || {
+            self.secret.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let randomness = FpVar::new_witness(cs.clone(), || {
+            self.randomness.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let mut path = Vec::with_capacity(SPEND_MERKLE_DEPTH);
+        for sibling in &self.merkle_path {
+            path.push(FpVar::new_witness(cs.clone(), || {
+                sibling.ok_or(SynthesisError::AssignmentMissing)
+            })?);
+        }
+        let mut is_right = Vec::with_capacity(SPEND_MERKLE_DEPTH);
+        for bit in &self.path_is_right {
+            is_right.push(Boolean::new_witness(cs.clone(), || {
+                bit.ok_or(SynthesisError::AssignmentMissing)
+            })?);
+        }
+
+        let merkle_root = FpVar::new_input(cs.clone(), || {
+            self.merkle_root.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let nullifier = FpVar::new_input(cs.clone(), || {
+            self.nullifier.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        // Recipient and fee take no part in the relation. Groth16 verification
+        // fixes every public input, so a proof for one recipient and fee
+        // does not verify for another.
+        let _recipient = FpVar::new_input(cs.clone(), || {
+            self.recipient.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let amount = FpVar::new_input(cs.clone(), || {
+            self.amount.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let asset = FpVar::new_input(cs.clone(), || {
+            self.asset.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let _fee = FpVar::new_input(cs.clone(), || {
+            self.fee.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let commitment = poseidon_gadget(
+            cs.clone(),
+            &[secret.clone(), amount, asset, randomness],
+        )?;
+
+        let mut current = commitment.clone();
+        for (sibling, right) in path.iter().zip(is_right.iter()) {
+            let left_node = FpVar::conditionally_select(right, sibling, &current)?;
+            let right_node = FpVar::conditionally_select(right, &current, sibling)?;
+            current = poseidon_gadget(cs.clone(), &[left_node, right_node])?;
+        }
+        current.enforce_equal(&merkle_root)?;
+
+        let computed_nullifier = poseidon_gadget(cs.clone(), &[secret, commitment])?;
+        computed_nullifier.enforce_equal(&nullifier)?;
+
+        Ok(())
+    }
+}
+
+pub struct SpendKeys {
+    pub proving_key: ProvingKey<Bn254>,
+    pub verifying_key: VerifyingKey<Bn254>,
+}
+
+impl SpendKeys {
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> NonosResult<Self> {
+        let (proving_key, verifying_key) =
+            Groth16::<Bn254>::circuit_specific_setup(SpendCircuit::empty(), rng)
+                .map_err(|e| NonosError::Crypto(format!("Spend circuit setup failed: {}", e)))?;
+        Ok(Self { proving_key, verifying_key })
+    }
+
+    pub fn proving_key_bytes(&self) -> NonosResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.proving_key
+            .serialize_compressed(&mut bytes)
+            .map_err(|e| NonosError::Serialization(e.to_string()))?;
+        Ok(bytes)
+    }
+
+    pub fn verifying_key_bytes(&self) -> NonosResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.verifying_key
+            .serialize_compressed(&mut bytes)
+            .map_err(|e| NonosError::Serialization(e.to_string()))?;
+        Ok(bytes)
+    }
+}
+
+pub fn load_spend_proving_key(bytes: &[u8]) -> NonosResult<ProvingKey<Bn254>> {
+    ProvingKey::<Bn254>::deserialize_compressed(bytes)
+        .map_err(|e| NonosError::InvalidKey(format!("Invalid spend proving key: {}", e)))
+}
+
+pub fn load_spend_verifying_key(bytes: &[u8]) -> NonosResult<PreparedVerifyingKey<Bn254>> {
+    let vk = VerifyingKey::<Bn254>::deserialize_compressed(bytes)
+        .map_err(|e| NonosError::InvalidKey(format!("Invalid spend verifying key: {}", e)))?;
+    Groth16::<Bn254>::process_vk(&vk)
+        .map_err(|e| NonosError::InvalidKey(format!("Spend verifying key processing failed: {}", e)))
+}
+
+/// Returns the compressed Groth16 proof together with the public inputs it
+/// commits to.
+pub fn prove_spend<R: RngCore + CryptoRng>(
+    proving_key: &ProvingKey<Bn254>,
+    input: &SpendProofInput,
+    rng: &mut R,
+) -> NonosResult<(Vec<u8>, SpendPublicInputs)> {
+    let circuit = SpendCircuit::new(input)?;
+    let proof = Groth16::<Bn254>::prove(proving_key, circuit, rng)
+        .map_err(|e| NonosError::Crypto(format!("Spend proof generation failed: {}", e)))?;
+
+    let mut bytes = Vec::new();
+    proof
+        .serialize_compressed(&mut bytes)
+        .map_err(|e| NonosError::Serialization(e.to_string()))?;
+
+    Ok((bytes, input.public_inputs()))
+}
+
+pub fn verify_spend(
+    verifying_key: &PreparedVerifyingKey<Bn254>,
+    public: &SpendPublicInputs,
+    proof_bytes: &[u8],
+) -> NonosResult<bool> {
+    if !public.is_canonical() {
+        return Ok(false);
+    }
+    let proof = match Proof::<Bn254>::deserialize_compressed(proof_bytes) {
+        Ok(proof) => proof,
+        Err(_) => return Ok(false),
+    };
+
+    Groth16::<Bn254>::verify_with_processed_vk(verifying_key, &public.to_field_elements(), &proof)
+        .map_err(|e| NonosError::Crypto(format!("Spend verification error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poseidon_canonical::PoseidonMerkleTree;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::thread_rng;
+
+    const ASSET: NoteAsset = [0x01, 0, 0, 0, 0, 0, 0, 0];
+
+    fn deposit(tree: &mut PoseidonMerkleTree, secret: [u8; 32], amount: u128) -> SpendProofInput {
+        let randomness = [0x5a; 32];
+        let commitment = note_commitment(&secret, amount, &ASSET, &randomness);
+        let index = tree.insert(commitment);
+
+        SpendProofInput {
+            secret,
+            randomness,
+            amount,
+            asset: ASSET,
+            merkle_path: tree.proof(index),
+            merkle_root: tree.root(),
+            recipient: [0x17; 32],
+            fee: 10,
+        }
+    }
+
+    #[test]
+    fn test_spend_circuit_satisfied() {
+        let mut tree = PoseidonMerkleTree::new(SPEND_MERKLE_DEPTH);
+        deposit(&mut tree, [0x01; 32], 500);
+        let input = deposit(&mut tree, [0x02; 32], 1000);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        SpendCircuit::new(&input).unwrap().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_spend_circuit_rejects_wrong_root() {
+        let mut tree = PoseidonMerkleTree::new(SPEND_MERKLE_DEPTH);
+        let mut input = deposit(&mut tree, [0x03; 32], 1000);
+        input.merkle_root = [0x09; 32];
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        SpendCircuit::new(&input).unwrap().generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_spend_proof_binds_public_inputs() {
+        let mut rng = thread_rng();
+        let keys = SpendKeys::generate(&mut rng).unwrap();
+        let pvk = load_spend_verifying_key(&keys.verifying_key_bytes().unwrap()).unwrap();
+
+        let mut tree = PoseidonMerkleTree::new(SPEND_MERKLE_DEPTH);
+        let input = deposit(&mut tree, [0x04; 32], 1000);
+
+        let (proof, public) = prove_spend(&keys.proving_key, &input, &mut rng).unwrap();
+        assert!(verify_spend(&pvk, &public, &proof).unwrap());
+
+        let mut redirected = public.clone();
+        redirected.recipient = [0x16; 32];
+        assert!(!verify_spend(&pvk, &redirected, &proof).unwrap());
+
+        let mut inflated = public.clone();
+        inflated.amount += 1;
+        assert!(!verify_spend(&pvk, &inflated, &proof).unwrap());
+
+        let mut other_nullifier = public.clone();
+        other_nullifier.nullifier[0] ^= 0x01;
+        assert!(!verify_spend(&pvk, &other_nullifier, &proof).unwrap());
+
+        assert!(!verify_spend(&pvk, &public, &[0u8; 16]).unwrap());
+    }
+}
@@ -26,31 +26,65 @@ impl StealthMetaAddress {
         }
     }
 
+    /// Encodes as an ERC-5564 meta-address for Ethereum mainnet,
+    /// `st:eth:0x<spend><view>`.
     pub fn encode(&self) -> String {
-        format!(
-            "st:eth:0x{}{}",
-            self.spend_pubkey.to_hex(),
-            self.view_pubkey.to_hex()
-        )
+        self.encode_for_chain("eth")
     }
 
+    /// Encodes with an EIP-3770 chain short name, e.g. `st:base:0x...`.
+    pub fn encode_for_chain(&self, chain: &str) -> String {
+        format!("st:{}:0x{}", chain, hex::encode(self.to_bytes()))
+    }
+
+    /// Decodes `st:<chain>:0x<spend><view>` for any chain short name, with
+    /// compressed or uncompressed keys.
     pub fn decode(s: &str) -> NonosResult<Self> {
-        let s = s
-            .strip_prefix("st:eth:0x")
-            .ok_or_else(|| NonosError::InvalidAddress("Invalid stealth meta-address prefix".into()))?;
-
-        if s.len() != 132 {
-            return Err(NonosError::InvalidAddress(
-                "Invalid stealth meta-address length".into(),
-            ));
+        let invalid_prefix = || NonosError::InvalidAddress("Invalid stealth meta-address prefix".into());
+
+        let rest = s.strip_prefix("st:").ok_or_else(invalid_prefix)?;
+        let (chain, keys) = rest.split_once(':').ok_or_else(invalid_prefix)?;
+        if chain.is_empty() || !chain.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(invalid_prefix());
         }
+        let keys = keys.strip_prefix("0x").ok_or_else(invalid_prefix)?;
 
-        let spend_pubkey = Secp256k1PublicKey::from_hex(&s[..66])?;
-        let view_pubkey = Secp256k1PublicKey::from_hex(&s[66..])?;
+        let bytes = hex::decode(keys)
+            .map_err(|e| NonosError::InvalidAddress(format!("Invalid stealth meta-address: {}", e)))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// The raw form stored by the ERC-6538 registry: spend key followed by
+    /// view key, both compressed.
+    pub fn to_bytes(&self) -> [u8; 66] {
+        let mut bytes = [0u8; 66];
+        bytes[..33].copy_from_slice(&self.spend_pubkey.0);
+        bytes[33..].copy_from_slice(&self.view_pubkey.0);
+        bytes
+    }
+
+    /// Parses registry bytes holding two compressed (66 bytes) or two
+    /// uncompressed (130 bytes) keys.
+    pub fn from_bytes(bytes: &[u8]) -> NonosResult<Self> {
+        let key_len = match bytes.len() {
+            66 => 33,
+            130 => 65,
+            _ => {
+                return Err(NonosError::InvalidAddress(
+                    "Invalid stealth meta-address length".into(),
+                ))
+            }
+        };
+
+        let compress = |key: &[u8]| -> NonosResult<Secp256k1PublicKey> {
+            let key = PublicKey::from_slice(key)
+                .map_err(|e| NonosError::InvalidAddress(format!("Invalid stealth meta-address key: {}", e)))?;
+            Ok(Secp256k1PublicKey::from_bytes(key.serialize()))
+        };
 
         Ok(Self {
-            spend_pubkey,
-            view_pubkey,
+            spend_pubkey: compress(&bytes[..key_len])?,
+            view_pubkey: compress(&bytes[key_len..])?,
         })
     }
 
@@ -230,6 +264,31 @@ mod tests {
         assert_eq!(meta.view_pubkey, decoded.view_pubkey);
     }
 
+    #[test]
+    fn test_stealth_meta_address_registry_bytes() {
+        let meta = StealthKeyPair::generate().unwrap().meta_address();
+
+        let bytes = meta.to_bytes();
+        let decoded = StealthMetaAddress::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.spend_pubkey, meta.spend_pubkey);
+        assert_eq!(decoded.view_pubkey, meta.view_pubkey);
+
+        let mut uncompressed = Vec::new();
+        for key in [&meta.spend_pubkey, &meta.view_pubkey] {
+            uncompressed.extend_from_slice(&PublicKey::from_slice(&key.0).unwrap().serialize_uncompressed());
+        }
+        let decoded = StealthMetaAddress::from_bytes(&uncompressed).unwrap();
+        assert_eq!(decoded.view_pubkey, meta.view_pubkey);
+
+        let base = meta.encode_for_chain("base");
+        assert!(base.starts_with("st:base:0x"));
+        assert_eq!(StealthMetaAddress::decode(&base).unwrap().spend_pubkey, meta.spend_pubkey);
+
+        assert!(StealthMetaAddress::from_bytes(&bytes[..65]).is_err());
+        assert!(StealthMetaAddress::decode("st::0x00").is_err());
+        assert!(StealthMetaAddress::decode(&base.replace("st:", "sx:")).is_err());
+    }
+
     #[test]
     fn test_stealth_address_generation_and_scanning() {
         let recipient_keypair = StealthKeyPair::generate().unwrap();
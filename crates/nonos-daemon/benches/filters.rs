@@ -0,0 +1,85 @@
+//! Compile time and matching throughput of the tracker filter engine.
+//!
+//! Run with `cargo bench -p nonos-daemon --bench filters`. Set
+//! `EASYLIST_PATH` and `EASYPRIVACY_PATH` to local copies of those lists to
+//! benchmark them as well; a synthetic list of similar shape always runs.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nonos_daemon::privacy::{FilterEngine, FilterRequest, RequestType, BUILTIN_RULES};
+
+const SYNTHETIC_RULES: usize = 50_000;
+const URL_CORPUS: usize = 10_000;
+
+fn synthetic_list() -> String {
+    let mut list = String::from("[Adblock Plus 2.0]\n! Synthetic list\n");
+    for i in 0..SYNTHETIC_RULES {
+        let rule = match i % 10 {
+            0..=5 => format!("||tracker{}.example^\n", i),
+            6 => format!("/ads/banner{}/*\n", i),
+            7 => format!("||cdn{}.net/pixel^$third-party,image\n", i),
+            8 => format!("example{}.org##.ad-slot\n", i),
+            _ => format!("@@||tracker{}.example/allowed^\n", i - 3),
+        };
+        list.push_str(&rule);
+    }
+    list
+}
+
+fn url_corpus() -> Vec<FilterRequest> {
+    (0..URL_CORPUS)
+        .map(|i| {
+            let url = match i % 4 {
+                0 => format!("https://tracker{}.example/collect?id={}", i * 7 % SYNTHETIC_RULES, i),
+                1 => format!("https://site{}.com/static/app.{}.js", i, i % 13),
+                2 => format!("https://cdn{}.net/pixel?u={}", i * 3 % SYNTHETIC_RULES, i),
+                _ => format!("https://news{}.org/articles/{}/index.html?utm_source=feed", i % 97, i),
+            };
+            FilterRequest::new(&url, Some("https://publisher.com/"), RequestType::Image)
+                .expect("absolute URL")
+        })
+        .collect()
+}
+
+fn lists() -> Vec<(String, String)> {
+    let mut lists = vec![
+        ("builtin".to_string(), BUILTIN_RULES.to_string()),
+        ("synthetic".to_string(), synthetic_list()),
+    ];
+    for var in ["EASYLIST_PATH", "EASYPRIVACY_PATH"] {
+        match std::env::var(var) {
+            Ok(path) => match std::fs::read_to_string(&path) {
+                Ok(text) => lists.push((var.trim_end_matches("_PATH").to_ascii_lowercase(), text)),
+                Err(e) => eprintln!("Skipping {}: {}", path, e),
+            },
+            Err(_) => eprintln!("{} not set, skipping", var),
+        }
+    }
+    lists
+}
+
+fn bench_filters(c: &mut Criterion) {
+    let lists = lists();
+    let requests = url_corpus();
+
+    let mut compile = c.benchmark_group("filters_compile");
+    compile.sample_size(10);
+    for (name, text) in &lists {
+        compile.bench_with_input(BenchmarkId::from_parameter(name), text, |b, text| {
+            b.iter(|| FilterEngine::compile([text.as_str()]));
+        });
+    }
+    compile.finish();
+
+    let mut check = c.benchmark_group("filters_check");
+    check.throughput(Throughput::Elements(requests.len() as u64));
+    for (name, text) in &lists {
+        let engine = FilterEngine::compile([text.as_str()]);
+        check.bench_with_input(BenchmarkId::from_parameter(name), &engine, |b, engine| {
+            b.iter(|| requests.iter().filter(|request| engine.check(request).is_blocked()).count());
+        });
+    }
+    check.finish();
+}
+
+criterion_group!(benches, bench_filters);
+criterion_main!(benches);
@@ -0,0 +1,75 @@
+//! Server and client cost of two-server PIR across database sizes.
+//!
+//! Run with `cargo bench -p nonos-daemon --bench pir`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use nonos_crypto::blake3_hash;
+use nonos_daemon::privacy::{PirDatabase, PirParams, PirRetrieval};
+
+const SLOTS_PER_BUCKET: u16 = 4;
+const MAX_ITEM_SIZE: u32 = 1024;
+const BUCKET_COUNTS: [u32; 4] = [256, 1024, 4096, 8192];
+
+fn database(buckets: u32) -> (PirDatabase, Vec<u8>) {
+    let params = PirParams { buckets, slots_per_bucket: SLOTS_PER_BUCKET, max_item_size: MAX_ITEM_SIZE };
+    let blobs: Vec<Vec<u8>> = (0..buckets * SLOTS_PER_BUCKET as u32 / 2)
+        .map(|i| {
+            let mut blob = vec![0u8; MAX_ITEM_SIZE as usize];
+            blob[..4].copy_from_slice(&i.to_le_bytes());
+            blob
+        })
+        .collect();
+    let wanted = blobs[blobs.len() / 2].clone();
+    (PirDatabase::build(params, blobs).expect("valid parameters"), wanted)
+}
+
+fn bench_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pir_build");
+    group.sample_size(10);
+    for buckets in BUCKET_COUNTS {
+        group.bench_with_input(BenchmarkId::from_parameter(buckets), &buckets, |b, &buckets| {
+            b.iter(|| database(buckets));
+        });
+    }
+    group.finish();
+}
+
+fn bench_answer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pir_answer");
+    for buckets in BUCKET_COUNTS {
+        let (database, wanted) = database(buckets);
+        let (_, [query, _]) = PirRetrieval::new(&database.info(), blake3_hash(&wanted).0)
+            .expect("valid parameters");
+
+        group.throughput(Throughput::Bytes(
+            (database.params().bucket_size() * buckets as usize) as u64,
+        ));
+        group.bench_with_input(BenchmarkId::from_parameter(buckets), &query, |b, query| {
+            b.iter(|| database.answer(query).expect("query matches database"));
+        });
+    }
+    group.finish();
+}
+
+fn bench_client(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pir_client");
+    for buckets in BUCKET_COUNTS {
+        let (database, wanted) = database(buckets);
+        let commitment = blake3_hash(&wanted).0;
+        let (retrieval, [left, right]) = PirRetrieval::new(&database.info(), commitment)
+            .expect("valid parameters");
+        let left = database.answer(&left).expect("query matches database");
+        let right = database.answer(&right).expect("query matches database");
+
+        group.bench_with_input(BenchmarkId::new("query", buckets), &buckets, |b, _| {
+            b.iter(|| PirRetrieval::new(&database.info(), commitment).expect("valid parameters"));
+        });
+        group.bench_with_input(BenchmarkId::new("reconstruct", buckets), &buckets, |b, _| {
+            b.iter(|| retrieval.reconstruct(&left, &right).expect("answers are consistent"));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_build, bench_answer, bench_client);
+criterion_main!(benches);
@@ -1,6 +1,7 @@
 use super::handlers::{send_error_response, send_response};
 use crate::services::BlockchainService;
 use nonos_types::NonosResult;
+use nonos_wallet::{FeeEstimate, FeeSpeed};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::net::TcpStream;
@@ -58,6 +59,8 @@ pub struct NonceResponse {
 pub struct GasPriceResponse {
     pub gas_price: String,
     pub gas_price_wei: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fees: Option<FeeEstimate>,
 }
 
 #[derive(Debug, Serialize)]
@@ -245,12 +248,19 @@ pub async fn blockchain_get_gas_price(
     };
 
     let bc = bc.read().await;
-    match bc.get_gas_price().await {
+    let fees = bc.get_fee_estimate().await.ok();
+    let price = match &fees {
+        Some(estimate) => Ok(estimate.gas_price(FeeSpeed::Normal)),
+        None => bc.get_gas_price().await,
+    };
+
+    match price {
         Ok(price) => {
             let gwei = price as f64 / 1e9;
             let response = GasPriceResponse {
                 gas_price: format!("{:.2} gwei", gwei),
                 gas_price_wei: price.to_string(),
+                fees,
             };
             let json = serde_json::to_string(&response).unwrap();
             send_response(stream, 200, "application/json", &json).await
@@ -14,6 +14,10 @@ pub async fn serve_dashboard(stream: &mut TcpStream) -> NonosResult<()> {
 pub async fn serve_status(stream: &mut TcpStream, node: &Arc<RwLock<Node>>) -> NonosResult<()> {
     let node = node.read().await;
     let metrics = node.metrics().await;
+    let network_stats = match node.network() {
+        Some(network) => Some(network.read().await.stats()),
+        None => None,
+    };
 
     let response = StatusResponse {
         node_id: metrics.node_id.to_string(),
@@ -27,6 +31,10 @@ pub async fn serve_status(stream: &mut TcpStream, node: &Arc<RwLock<Node>>) -> N
         staked_nox: metrics.staked.raw as f64 / 1e18,
         pending_rewards: metrics.pending_rewards.raw as f64 / 1e18,
         streak_days: metrics.streak,
+        reachability: network_stats.as_ref().map(|s| s.reachability).unwrap_or_default(),
+        public_address: network_stats.as_ref().and_then(|s| s.public_address.clone()),
+        relay_reservations: network_stats.as_ref().map_or(0, |s| s.relay_reservations),
+        hole_punch_successes: network_stats.as_ref().map_or(0, |s| s.hole_punch_successes),
     };
 
     let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
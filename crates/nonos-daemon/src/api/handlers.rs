@@ -196,6 +196,25 @@ async fn route_request(
         ("GET", "/api/privacy/stats") => serve_privacy_stats(stream, privacy).await,
         ("POST", "/api/privacy/tracking/check") => tracking_check(stream, privacy, body).await,
         ("POST", "/api/privacy/tracking/block") => tracking_block(stream, privacy, body).await,
+        ("GET", "/api/privacy/filter-lists") => filter_lists(stream, privacy).await,
+        ("POST", "/api/privacy/filter-lists/add") => filter_lists_add(stream, privacy, body).await,
+        ("POST", "/api/privacy/filter-lists/remove") => filter_lists_remove(stream, privacy, body).await,
+        ("POST", "/api/privacy/filter-lists/publish") => filter_lists_publish(stream, node, privacy, body).await,
+        ("POST", "/api/privacy/oracle/vote") => oracle_vote(stream, node, privacy, body).await,
+        ("GET", p) if p.starts_with("/api/privacy/oracle/score/") => {
+            oracle_score(stream, privacy, &p["/api/privacy/oracle/score/".len()..]).await
+        }
+        ("POST", "/api/privacy/mixnet/send") => mixnet_send(stream, node, body).await,
+        ("POST", "/api/privacy/mixnet/receive") => mixnet_receive(stream, node).await,
+        ("POST", "/api/privacy/pir/store") => pir_store(stream, privacy, body).await,
+        ("POST", "/api/privacy/pir/fetch") => pir_fetch(stream, node, body).await,
+        ("POST", "/api/privacy/credentials/store") => credentials_store(stream, privacy, body).await,
+        ("POST", "/api/privacy/credentials/prove") => credentials_prove(stream, privacy, body).await,
+        ("POST", "/api/privacy/credentials/verify") => credentials_verify(stream, privacy, body).await,
+        ("GET", "/api/privacy/vault") => vault_list(stream, privacy).await,
+        ("POST", "/api/privacy/vault/store") => vault_store(stream, node, privacy, body).await,
+        ("POST", "/api/privacy/vault/fetch") => vault_fetch(stream, node, privacy, body).await,
+        ("POST", "/api/privacy/vault/remove") => vault_remove(stream, node, privacy, body).await,
         ("POST", "/api/privacy/identity/register") => identity_register(stream, privacy, body).await,
         ("GET", "/api/privacy/identity/root") => identity_root(stream, privacy).await,
         ("POST", "/api/privacy/zk/register") => zk_identity_register(stream, privacy, body).await,
@@ -204,6 +223,8 @@ async fn route_request(
         ("GET", "/api/privacy/mixer/status") => mixer_status(stream, privacy).await,
         ("POST", "/api/privacy/mixer/deposit") => mixer_deposit(stream, privacy, body).await,
         ("POST", "/api/privacy/mixer/spend") => mixer_spend(stream, privacy, body).await,
+        ("POST", "/api/privacy/stealth/init") => stealth_init(stream, privacy, body).await,
+        ("GET", "/api/privacy/stealth/payments") => stealth_payments(stream, privacy).await,
         ("GET", "/api/staking/info") => staking_info(stream, contract_client, staker_address).await,
         ("GET", "/api/staking/balance") => staking_balance(stream, contract_client, staker_address).await,
         ("GET", "/api/staking/tier") => staking_tier(stream, contract_client, staker_address).await,
@@ -222,6 +243,9 @@ async fn route_request(
         ("GET", "/api/v1/node/services") => serve_node_services(stream, node).await,
         ("GET", "/api/v1/node/network") => serve_node_network(stream, node).await,
         ("GET", "/api/v1/node/peers") => serve_node_peers(stream, node).await,
+        ("GET", p) if p.starts_with("/api/v1/node/peers/") => {
+            serve_node_peer(stream, node, &p["/api/v1/node/peers/".len()..]).await
+        }
         ("GET", "/api/v1/node/metrics") => serve_node_metrics(stream, node, metrics).await,
         ("GET", "/api/v1/node/rewards") => serve_node_rewards(stream, node, contract_client, reward_tracker, staker_address).await,
         ("GET", "/api/v1/node/config") => serve_node_config(stream, node).await,
@@ -4,6 +4,7 @@ use crate::metrics::NodeMetricsCollector;
 use crate::rewards::RewardTracker;
 use crate::services::{ServiceState, ServiceType};
 use crate::Node;
+use libp2p::PeerId;
 use nonos_types::{EthAddress, NonosResult};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -115,6 +116,7 @@ pub async fn serve_node_services(
             (ServiceType::QualityOracle, "quality_oracle"),
             (ServiceType::Bootstrap, "bootstrap"),
             (ServiceType::Cache, "cache"),
+            (ServiceType::Mixnet, "mixnet"),
         ];
 
         for (service_type, name) in service_types {
@@ -210,6 +212,9 @@ pub async fn serve_node_peers(
         net.peers()
             .iter()
             .map(|p| PeerSummary {
+                gossipsub_score: p.id.parse::<PeerId>().ok()
+                    .and_then(|id| net.peer_store().get(&id))
+                    .and_then(|entry| entry.gossipsub_score),
                 peer_id: p.id.clone(),
                 state: "connected".to_string(),
                 addresses: p.addresses.clone(),
@@ -248,6 +253,65 @@ pub async fn serve_node_peers(
     send_json_response(stream, 200, &response).await
 }
 
+pub async fn serve_node_peer(
+    stream: &mut TcpStream,
+    node: &Arc<RwLock<Node>>,
+    peer_id: &str,
+) -> NonosResult<()> {
+    let Ok(peer) = peer_id.parse::<PeerId>() else {
+        let response: ApiResponse<PeerSummary> = ApiResponse::error(ApiErrorResponse::new(
+            "invalid_peer_id",
+            &format!("Invalid peer ID: {}", peer_id),
+        ));
+        return send_json_response(stream, 400, &response).await;
+    };
+
+    let node = node.read().await;
+    let summary = if let Some(ref network) = node.network() {
+        let net = network.read().await;
+        let connection = net.get_peer(&peer);
+        match (net.peer_store().get(&peer), connection) {
+            (Some(entry), Some(info)) => Some(PeerSummary {
+                state: "connected".to_string(),
+                addresses: info.addresses,
+                latency_ms: info.latency_ms.or(entry.latency_ms),
+                ..PeerSummary::from(&entry)
+            }),
+            (Some(entry), None) => Some(PeerSummary::from(&entry)),
+            (None, Some(info)) => Some(PeerSummary {
+                peer_id: info.id,
+                state: "connected".to_string(),
+                addresses: info.addresses,
+                latency_ms: info.latency_ms,
+                quality_score: 1.0,
+                penalty_score: 0,
+                is_banned: false,
+                ban_remaining_secs: None,
+                role_hint: None,
+                protocol_version: info.protocol_version,
+                last_seen: (chrono::Utc::now() - info.connected_at).num_seconds(),
+                messages_received: 0,
+                is_bootstrap: false,
+                gossipsub_score: None,
+            }),
+            (None, None) => None,
+        }
+    } else {
+        None
+    };
+
+    match summary {
+        Some(summary) => send_json_response(stream, 200, &ApiResponse::success(summary)).await,
+        None => {
+            let response: ApiResponse<PeerSummary> = ApiResponse::error(ApiErrorResponse::new(
+                "not_found",
+                &format!("Unknown peer: {}", peer_id),
+            ));
+            send_json_response(stream, 404, &response).await
+        }
+    }
+}
+
 pub async fn serve_node_metrics(
     stream: &mut TcpStream,
     node: &Arc<RwLock<Node>>,
@@ -102,6 +102,8 @@ pub struct PeerSummary {
     pub last_seen: i64,
     pub messages_received: u64,
     pub is_bootstrap: bool,
+    #[serde(default)]
+    pub gossipsub_score: Option<f64>,
 }
 
 impl From<&PeerEntry> for PeerSummary {
@@ -120,6 +122,7 @@ impl From<&PeerEntry> for PeerSummary {
             last_seen: entry.last_seen,
             messages_received: entry.messages_received,
             is_bootstrap: entry.is_bootstrap,
+            gossipsub_score: entry.gossipsub_score,
         }
     }
 }
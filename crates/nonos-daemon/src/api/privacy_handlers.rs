@@ -1,10 +1,18 @@
 use super::handlers::send_response;
 use super::responses::*;
-use crate::privacy::{Note, SpendRequest, ASSET_ETH};
-use crate::PrivacyServiceManager;
-use nonos_types::NonosResult;
+use crate::p2p::{FilterListChunkData, VaultClient};
+use crate::privacy::{
+    AssetId, BundleOutcome, Note, RequestType, SpendRequest, ASSET_ETH, ASSET_NOX, CHUNK_BYTES, DEFAULT_PIR_PARAMS,
+};
+use crate::rewards::current_epoch;
+use crate::storage::StoredVaultedCookie;
+use crate::{Node, PrivacyServiceManager};
+use nonos_crypto::StealthViewingKey;
+use nonos_types::{Ed25519PublicKey, NodeId, NonosResult, Secp256k1PrivateKey, Secp256k1PublicKey};
+use rand::seq::SliceRandom;
 use std::sync::Arc;
 use tokio::net::TcpStream;
+use tokio::sync::RwLock;
 
 pub async fn serve_privacy_stats(
     stream: &mut TcpStream,
@@ -75,11 +83,18 @@ pub async fn tracking_check(
         }
     };
 
-    let blocked = p.tracking_blocker.should_block_domain(&req.domain).await;
+    let (blocked, rule) = match &req.url {
+        Some(url) => {
+            let request_type = req.request_type.unwrap_or(RequestType::Other);
+            p.tracking_blocker.should_block_request(url, req.source_url.as_deref(), request_type).await
+        }
+        None => (p.tracking_blocker.should_block_domain(&req.domain).await, None),
+    };
 
     let response = TrackingCheckResponse {
         blocked,
         domain: req.domain,
+        rule,
     };
 
     let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
@@ -371,7 +386,15 @@ pub async fn mixer_deposit(
         }
     };
 
-    let mut note = Note::new(secret, amount, ASSET_ETH, randomness);
+    let asset = match parse_asset(&req.asset) {
+        Ok(a) => a,
+        Err(e) => {
+            let err = format!(r#"{{"error":"Invalid asset: {}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+
+    let mut note = Note::new(secret, amount, asset, randomness);
 
     match p.note_mixer.deposit(&mut note).await {
         Ok(index) => {
@@ -432,6 +455,13 @@ pub async fn mixer_spend(
         }
     };
 
+    let amount: u128 = match req.amount.parse() {
+        Ok(a) => a,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid amount"}"#).await;
+        }
+    };
+
     let fee: u128 = match req.fee.parse() {
         Ok(f) => f,
         Err(_) => {
@@ -439,6 +469,14 @@ pub async fn mixer_spend(
         }
     };
 
+    let asset = match parse_asset(&req.asset) {
+        Ok(a) => a,
+        Err(e) => {
+            let err = format!(r#"{{"error":"Invalid asset: {}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+
     let proof = match hex::decode(req.proof.strip_prefix("0x").unwrap_or(&req.proof)) {
         Ok(p) => p,
         Err(e) => {
@@ -451,6 +489,8 @@ pub async fn mixer_spend(
         merkle_root,
         nullifier,
         recipient,
+        amount,
+        asset,
         fee,
         merkle_path: vec![],
         proof,
@@ -473,6 +513,722 @@ pub async fn mixer_spend(
     }
 }
 
+pub async fn stealth_init(
+    stream: &mut TcpStream,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: StealthInitRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+
+    let view_private = match parse_hex_32(&req.view_private_key) {
+        Ok(k) => Secp256k1PrivateKey::from_bytes(k),
+        Err(e) => {
+            let err = format!(r#"{{"error":"Invalid view private key: {}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+
+    let spend_public = match Secp256k1PublicKey::from_hex(&req.spend_public_key) {
+        Ok(k) => k,
+        Err(e) => {
+            let err = format!(r#"{{"error":"Invalid spend public key: {}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+
+    match p.stealth_scanner.initialize_viewing_key(StealthViewingKey::new(view_private, spend_public)).await {
+        Ok(meta_address) => {
+            let response = StealthInitResponse {
+                success: true,
+                meta_address,
+            };
+            let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+            send_response(stream, 200, "application/json", &json).await
+        }
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            send_response(stream, 400, "application/json", &err).await
+        }
+    }
+}
+
+pub async fn stealth_payments(
+    stream: &mut TcpStream,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let scanner = &p.stealth_scanner;
+    let (_, scanned) = scanner.stats();
+    let payments = scanner.payments().await.into_iter().map(|payment| {
+        let a = payment.announcement;
+        StealthPaymentInfo {
+            stealth_address: a.stealth_address.to_hex(),
+            caller: a.caller.to_hex(),
+            ephemeral_pubkey: hex::encode(&a.ephemeral_pubkey),
+            metadata: hex::encode(&a.metadata),
+            block_number: a.block_number,
+            tx_hash: hex::encode(a.tx_hash),
+            log_index: a.log_index,
+            detected_at: payment.detected_at.to_rfc3339(),
+        }
+    }).collect();
+
+    let response = StealthPaymentsResponse {
+        initialized: scanner.is_initialized().await,
+        checkpoint: scanner.checkpoint().unwrap_or(None),
+        announcements_scanned: scanned,
+        payments,
+    };
+
+    let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+    send_response(stream, 200, "application/json", &json).await
+}
+
+pub async fn filter_lists(
+    stream: &mut TcpStream,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let response = FilterListsResponse { subscriptions: p.filter_lists.subscriptions().await };
+    let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+    send_response(stream, 200, "application/json", &json).await
+}
+
+pub async fn filter_lists_add(
+    stream: &mut TcpStream,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: FilterListAddRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+
+    let maintainer = match parse_hex_32(&req.maintainer) {
+        Ok(key) => Ed25519PublicKey::from_bytes(key),
+        Err(e) => {
+            let err = format!(r#"{{"error":"Invalid maintainer key: {}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+
+    match p.filter_lists.subscribe(&req.name, maintainer).await {
+        Ok(()) => {
+            let response = FilterListChangeResponse { success: true, name: req.name };
+            let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+            send_response(stream, 200, "application/json", &json).await
+        }
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            send_response(stream, 400, "application/json", &err).await
+        }
+    }
+}
+
+pub async fn filter_lists_remove(
+    stream: &mut TcpStream,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: FilterListRemoveRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+
+    match p.filter_lists.unsubscribe(&req.name).await {
+        Ok(success) => {
+            let response = FilterListChangeResponse { success, name: req.name };
+            let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+            send_response(stream, 200, "application/json", &json).await
+        }
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            send_response(stream, 500, "application/json", &err).await
+        }
+    }
+}
+
+/// Gossips one chunk of a signed bundle, and applies it locally as gossip
+/// is not delivered back to its publisher. A `catch_up` chunk is only
+/// taken locally, for the node to republish later.
+pub async fn filter_lists_publish(
+    stream: &mut TcpStream,
+    node: &Arc<RwLock<Node>>,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: FilterListPublishRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+
+    let bundle_id = match parse_hex_32(&req.bundle_id) {
+        Ok(id) => id,
+        Err(e) => {
+            let err = format!(r#"{{"error":"Invalid bundle ID: {}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+    let maintainer = match parse_hex_32(&req.maintainer) {
+        Ok(key) => Ed25519PublicKey::from_bytes(key),
+        Err(e) => {
+            let err = format!(r#"{{"error":"Invalid maintainer key: {}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+    let (data, signature) = match (hex::decode(&req.data), hex::decode(&req.signature)) {
+        (Ok(data), Ok(signature)) if data.len() <= CHUNK_BYTES => (data, signature),
+        _ => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid chunk data"}"#).await;
+        }
+    };
+    let chunk = FilterListChunkData {
+        name: req.name,
+        maintainer,
+        version: req.version,
+        bundle_id,
+        index: req.index,
+        total: req.total,
+        signature,
+        data,
+    };
+    if let Err(e) = chunk.verify() {
+        let err = format!(r#"{{"error":"{}"}}"#, e);
+        return send_response(stream, 400, "application/json", &err).await;
+    }
+
+    if !req.catch_up {
+        let Some(network) = node.read().await.network() else {
+            return send_response(stream, 503, "application/json", r#"{"error":"P2P network not available"}"#).await;
+        };
+        let published = network.read().await.publish_filter_list_chunk(chunk.clone()).await;
+        if let Err(e) = published {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            return send_response(stream, 500, "application/json", &err).await;
+        }
+    }
+
+    let applied_version = match p.filter_lists.ingest_chunk(chunk).await {
+        Ok(Some(BundleOutcome::Applied { version })) => Some(version),
+        Ok(Some(BundleOutcome::NotSubscribed)) if req.catch_up => {
+            let err = r#"{"error":"This node must subscribe to the list to keep it for catch-up"}"#;
+            return send_response(stream, 409, "application/json", err).await;
+        }
+        Ok(_) => None,
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+
+    let response = FilterListPublishResponse { published: !req.catch_up, applied_version };
+    let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+    send_response(stream, 200, "application/json", &json).await
+}
+
+/// Analyses a domain, counts the result as this node's vote for the
+/// current epoch and gossips it.
+pub async fn oracle_vote(
+    stream: &mut TcpStream,
+    node: &Arc<RwLock<Node>>,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: OracleVoteRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+
+    let Some(network) = node.read().await.network() else {
+        return send_response(stream, 503, "application/json", r#"{"error":"P2P network not available"}"#).await;
+    };
+
+    let epoch = current_epoch().0;
+    let vote = match p.privacy_oracle.cast_vote(&req.domain, req.content.as_deref(), epoch).await {
+        Ok(vote) => vote,
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+    if let Err(e) = network.read().await.publish_oracle_vote(vote.clone()).await {
+        let err = format!(r#"{{"error":"{}"}}"#, e);
+        return send_response(stream, 500, "application/json", &err).await;
+    }
+
+    let response = OracleVoteResponse {
+        consensus: p.privacy_oracle.get_score(&vote.domain).await,
+        domain: vote.domain,
+        epoch,
+        score: vote.score,
+    };
+    let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+    send_response(stream, 200, "application/json", &json).await
+}
+
+pub async fn oracle_score(
+    stream: &mut TcpStream,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    domain: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    match p.privacy_oracle.get_score(domain).await {
+        Some(score) => {
+            let json = serde_json::to_string(&score).unwrap_or_else(|_| "{}".to_string());
+            send_response(stream, 200, "application/json", &json).await
+        }
+        None => send_response(stream, 404, "application/json", r#"{"error":"No votes for domain"}"#).await,
+    }
+}
+
+fn vault_entry(cookie: StoredVaultedCookie) -> VaultCookieEntry {
+    VaultCookieEntry {
+        holders: cookie.holders.iter().map(hex::encode).collect(),
+        cookie_id: cookie.cookie_id,
+        generation: cookie.generation,
+        threshold: cookie.threshold,
+        updated_at: cookie.updated_at,
+    }
+}
+
+pub async fn mixnet_send(
+    stream: &mut TcpStream,
+    node: &Arc<RwLock<Node>>,
+    body: &str,
+) -> NonosResult<()> {
+    let req: MixnetSendRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+
+    let destination = match hex::decode(req.destination.trim_start_matches("0x")).ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+    {
+        Some(bytes) => NodeId::from_bytes(bytes),
+        None => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid destination node ID"}"#).await;
+        }
+    };
+    let Ok(payload) = hex::decode(req.payload.trim_start_matches("0x")) else {
+        return send_response(stream, 400, "application/json", r#"{"error":"Invalid payload hex"}"#).await;
+    };
+
+    let services = node.read().await.services();
+    let mixnet = match services {
+        Some(services) => services.read().await.mixnet(),
+        None => None,
+    };
+    let Some(mixnet) = mixnet else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Mixnet not running"}"#).await;
+    };
+
+    match mixnet.send_to(&payload, destination).await {
+        Ok(()) => send_response(stream, 200, "application/json", r#"{"sent":true}"#).await,
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            send_response(stream, 400, "application/json", &err).await
+        }
+    }
+}
+
+pub async fn mixnet_receive(
+    stream: &mut TcpStream,
+    node: &Arc<RwLock<Node>>,
+) -> NonosResult<()> {
+    let Some(services) = node.read().await.services() else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Mixnet not running"}"#).await;
+    };
+
+    let messages = services.write().await.drain_mix_deliveries();
+    let response = MixnetMessagesResponse {
+        messages: messages.iter().map(hex::encode).collect(),
+    };
+    let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+    send_response(stream, 200, "application/json", &json).await
+}
+
+pub async fn pir_store(
+    stream: &mut TcpStream,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: PirStoreRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+    let Ok(content) = hex::decode(req.content.trim_start_matches("0x")) else {
+        return send_response(stream, 400, "application/json", r#"{"error":"Invalid content hex"}"#).await;
+    };
+    if content.len() > DEFAULT_PIR_PARAMS.max_item_size as usize {
+        let err = format!(r#"{{"error":"Content exceeds {} bytes"}}"#, DEFAULT_PIR_PARAMS.max_item_size);
+        return send_response(stream, 400, "application/json", &err).await;
+    }
+
+    match p.content.store(&content, req.ttl_secs).await {
+        Ok(commitment) => {
+            let response = PirStoreResponse { commitment: hex::encode(commitment) };
+            let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+            send_response(stream, 200, "application/json", &json).await
+        }
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            send_response(stream, 500, "application/json", &err).await
+        }
+    }
+}
+
+pub async fn pir_fetch(
+    stream: &mut TcpStream,
+    node: &Arc<RwLock<Node>>,
+    body: &str,
+) -> NonosResult<()> {
+    let req: PirFetchRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+    let Some(commitment) = hex::decode(req.commitment.trim_start_matches("0x")).ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+    else {
+        return send_response(stream, 400, "application/json", r#"{"error":"Invalid commitment"}"#).await;
+    };
+
+    let client = match node.read().await.network() {
+        Some(network) => network.read().await.pir_client(),
+        None => None,
+    };
+    let Some(client) = client else {
+        return send_response(stream, 503, "application/json", r#"{"error":"P2P network not available"}"#).await;
+    };
+
+    match client.fetch_from_known_nodes(commitment).await {
+        Ok(Some(content)) => {
+            let response = PirFetchResponse { commitment: hex::encode(commitment), content: hex::encode(content) };
+            let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+            send_response(stream, 200, "application/json", &json).await
+        }
+        Ok(None) => send_response(stream, 404, "application/json", r#"{"error":"Content not found"}"#).await,
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            send_response(stream, 502, "application/json", &err).await
+        }
+    }
+}
+
+/// Keeps a numeric credential after checking the issuer's signature over
+/// its commitment.
+pub async fn credentials_store(
+    stream: &mut TcpStream,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: CredentialStoreRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+    let (salt, issuer) = match (parse_hex_32(&req.salt), parse_hex_32(&req.issuer)) {
+        (Ok(salt), Ok(issuer)) => (salt, Ed25519PublicKey::from_bytes(issuer)),
+        (Err(e), _) | (_, Err(e)) => {
+            let err = format!(r#"{{"error":"Invalid salt or issuer: {}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+    let signature: [u8; 64] = match hex::decode(req.signature.trim_start_matches("0x")).map(<[u8; 64]>::try_from) {
+        Ok(Ok(signature)) => signature,
+        _ => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid signature"}"#).await;
+        }
+    };
+
+    let credential_type = req.credential_type.to_string();
+    match p.credentials
+        .store_issued_credential(req.credential_type, req.value, salt, &issuer, &signature, req.expiry_secs)
+        .await
+    {
+        Ok(commitment) => {
+            let response = CredentialStoreResponse { credential_type, commitment: hex::encode(commitment) };
+            let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+            send_response(stream, 200, "application/json", &json).await
+        }
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            send_response(stream, 400, "application/json", &err).await
+        }
+    }
+}
+
+/// Proves a held credential meets its threshold, answering the verifier's
+/// challenge if one is given.
+pub async fn credentials_prove(
+    stream: &mut TcpStream,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: CredentialProveRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+    let challenge = match req.challenge.as_deref().map(parse_hex_32).transpose() {
+        Ok(challenge) => challenge.unwrap_or_else(nonos_crypto::random_bytes::<32>),
+        Err(e) => {
+            let err = format!(r#"{{"error":"Invalid challenge: {}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+
+    match p.credentials.create_proof_with_challenge(&req.credential_type, challenge, req.ttl_secs).await {
+        Ok(proof) => {
+            let json = serde_json::to_string(&proof).unwrap_or_else(|_| "{}".to_string());
+            send_response(stream, 200, "application/json", &json).await
+        }
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            send_response(stream, 400, "application/json", &err).await
+        }
+    }
+}
+
+/// Checks another holder's range proof against an issuer the caller
+/// trusts.
+pub async fn credentials_verify(
+    stream: &mut TcpStream,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: CredentialVerifyRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+    let issuer = match parse_hex_32(&req.issuer) {
+        Ok(key) => Ed25519PublicKey::from_bytes(key),
+        Err(e) => {
+            let err = format!(r#"{{"error":"Invalid issuer key: {}"}}"#, e);
+            return send_response(stream, 400, "application/json", &err).await;
+        }
+    };
+
+    let response = CredentialVerifyResponse { valid: p.credentials.verify_proof(&req.proof, &issuer) };
+    let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+    send_response(stream, 200, "application/json", &json).await
+}
+
+async fn vault_client(node: &Arc<RwLock<Node>>) -> Option<VaultClient> {
+    let network = node.read().await.network()?;
+    let client = network.read().await.vault_client();
+    client
+}
+
+pub async fn vault_list(
+    stream: &mut TcpStream,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let mut cookies = p.cookie_vault.cookies().await;
+    cookies.sort_by(|a, b| a.cookie_id.cmp(&b.cookie_id));
+    let response = VaultListResponse { cookies: cookies.into_iter().map(vault_entry).collect() };
+    let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+    send_response(stream, 200, "application/json", &json).await
+}
+
+pub async fn vault_store(
+    stream: &mut TcpStream,
+    node: &Arc<RwLock<Node>>,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: VaultStoreRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+
+    let Some(client) = vault_client(node).await else {
+        return send_response(stream, 503, "application/json", r#"{"error":"P2P network not available"}"#).await;
+    };
+
+    let mut holders = client.known_holders();
+    holders.shuffle(&mut rand::thread_rng());
+    match p.cookie_vault.distribute(&req.cookie_id, req.value.as_bytes(), &holders, &client).await {
+        Ok(cookie) => {
+            let json = serde_json::to_string(&vault_entry(cookie)).unwrap_or_else(|_| "{}".to_string());
+            send_response(stream, 200, "application/json", &json).await
+        }
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            send_response(stream, 400, "application/json", &err).await
+        }
+    }
+}
+
+pub async fn vault_fetch(
+    stream: &mut TcpStream,
+    node: &Arc<RwLock<Node>>,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: VaultCookieRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+
+    if p.cookie_vault.cookie(&req.cookie_id).await.is_none() {
+        return send_response(stream, 404, "application/json", r#"{"error":"Unknown cookie"}"#).await;
+    }
+    let Some(client) = vault_client(node).await else {
+        return send_response(stream, 503, "application/json", r#"{"error":"P2P network not available"}"#).await;
+    };
+
+    let value = match p.cookie_vault.collect(&req.cookie_id, &client).await {
+        Ok(value) => value,
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            return send_response(stream, 502, "application/json", &err).await;
+        }
+    };
+    let response = VaultFetchResponse {
+        cookie_id: req.cookie_id,
+        value: String::from_utf8_lossy(&value).into_owned(),
+    };
+    let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
+    send_response(stream, 200, "application/json", &json).await
+}
+
+pub async fn vault_remove(
+    stream: &mut TcpStream,
+    node: &Arc<RwLock<Node>>,
+    privacy: &Option<Arc<PrivacyServiceManager>>,
+    body: &str,
+) -> NonosResult<()> {
+    let Some(p) = privacy else {
+        return send_response(stream, 503, "application/json", r#"{"error":"Privacy services not available"}"#).await;
+    };
+
+    let req: VaultCookieRequest = match serde_json::from_str(body) {
+        Ok(r) => r,
+        Err(_) => {
+            return send_response(stream, 400, "application/json", r#"{"error":"Invalid JSON"}"#).await;
+        }
+    };
+
+    // Without the network the holders keep their shares, which are useless
+    // once the sealed cookie is gone.
+    let removed = match vault_client(node).await {
+        Some(client) => p.cookie_vault.forget(&req.cookie_id, &client).await,
+        None => p.cookie_vault.remove_cookie(&req.cookie_id).await.map(|cookie| cookie.is_some()),
+    };
+    match removed {
+        Ok(removed) => {
+            let json = serde_json::json!({ "removed": removed }).to_string();
+            send_response(stream, 200, "application/json", &json).await
+        }
+        Err(e) => {
+            let err = format!(r#"{{"error":"{}"}}"#, e);
+            send_response(stream, 500, "application/json", &err).await
+        }
+    }
+}
+
+/// Accepts a pool by name (`eth`, `nox`) or as its 8-byte id in hex.
+fn parse_asset(s: &str) -> Result<AssetId, String> {
+    let asset = match s.to_ascii_lowercase().as_str() {
+        "eth" => ASSET_ETH,
+        "nox" => ASSET_NOX,
+        other => {
+            let bytes = hex::decode(other.strip_prefix("0x").unwrap_or(other)).map_err(|e| e.to_string())?;
+            bytes
+                .try_into()
+                .map_err(|bytes: Vec<u8>| format!("Expected 8 bytes, got {}", bytes.len()))?
+        }
+    };
+    if asset != ASSET_ETH && asset != ASSET_NOX {
+        return Err(format!("Unsupported asset {}", hex::encode(asset)));
+    }
+    Ok(asset)
+}
+
 fn parse_hex_32(s: &str) -> Result<[u8; 32], String> {
     let s = s.strip_prefix("0x").unwrap_or(s);
     let bytes = hex::decode(s).map_err(|e| e.to_string())?;
@@ -1,3 +1,5 @@
+use crate::p2p::Reachability;
+use crate::privacy::{CredentialProof, CredentialType, DomainPrivacyScore, RequestType, SubscriptionInfo};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize)]
@@ -13,6 +15,10 @@ pub struct StatusResponse {
     pub staked_nox: f64,
     pub pending_rewards: f64,
     pub streak_days: u32,
+    pub reachability: Reachability,
+    pub public_address: Option<String>,
+    pub relay_reservations: u64,
+    pub hole_punch_successes: u64,
 }
 
 #[derive(Serialize)]
@@ -116,12 +122,22 @@ pub struct PrivacyStatsResponse {
 #[derive(Deserialize)]
 pub struct TrackingCheckRequest {
     pub domain: String,
+    /// Full request URL; when given it is checked instead of the bare domain.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// URL of the page making the request, for first/third-party and
+    /// `domain=` options.
+    #[serde(default)]
+    pub source_url: Option<String>,
+    #[serde(default)]
+    pub request_type: Option<RequestType>,
 }
 
 #[derive(Serialize)]
 pub struct TrackingCheckResponse {
     pub blocked: bool,
     pub domain: String,
+    pub rule: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -135,6 +151,183 @@ pub struct TrackingBlockResponse {
     pub domain: String,
 }
 
+#[derive(Serialize)]
+pub struct FilterListsResponse {
+    pub subscriptions: Vec<SubscriptionInfo>,
+}
+
+#[derive(Deserialize)]
+pub struct FilterListAddRequest {
+    pub name: String,
+    /// Hex-encoded Ed25519 key of the list's maintainer.
+    pub maintainer: String,
+}
+
+#[derive(Deserialize)]
+pub struct FilterListRemoveRequest {
+    pub name: String,
+}
+
+#[derive(Serialize)]
+pub struct FilterListChangeResponse {
+    pub success: bool,
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct FilterListPublishRequest {
+    pub name: String,
+    /// Hex-encoded Ed25519 key of the list's maintainer.
+    pub maintainer: String,
+    pub version: u64,
+    pub bundle_id: String,
+    pub index: u16,
+    pub total: u16,
+    /// Hex-encoded maintainer signature over the chunk header and data.
+    pub signature: String,
+    /// Hex-encoded chunk payload.
+    pub data: String,
+    /// Keep the bundle for this node to gossip to nodes catching up later,
+    /// rather than publishing it now.
+    #[serde(default)]
+    pub catch_up: bool,
+}
+
+#[derive(Serialize)]
+pub struct FilterListPublishResponse {
+    pub published: bool,
+    /// Version installed locally, once the chunk completed a bundle.
+    pub applied_version: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct OracleVoteRequest {
+    pub domain: String,
+    /// Page content to analyse along with the domain.
+    pub content: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct OracleVoteResponse {
+    pub domain: String,
+    pub epoch: u64,
+    pub score: u8,
+    /// Network consensus including this vote.
+    pub consensus: Option<DomainPrivacyScore>,
+}
+
+#[derive(Serialize)]
+pub struct VaultCookieEntry {
+    pub cookie_id: String,
+    pub generation: u32,
+    pub threshold: u8,
+    /// Node IDs holding the key shares, hex encoded.
+    pub holders: Vec<String>,
+    pub updated_at: i64,
+}
+
+#[derive(Serialize)]
+pub struct VaultListResponse {
+    pub cookies: Vec<VaultCookieEntry>,
+}
+
+#[derive(Deserialize)]
+pub struct VaultStoreRequest {
+    pub cookie_id: String,
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+pub struct VaultCookieRequest {
+    pub cookie_id: String,
+}
+
+#[derive(Serialize)]
+pub struct VaultFetchResponse {
+    pub cookie_id: String,
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+pub struct MixnetSendRequest {
+    /// Hex node ID of the exit that delivers the payload.
+    pub destination: String,
+    /// Hex payload.
+    pub payload: String,
+}
+
+#[derive(Serialize)]
+pub struct MixnetMessagesResponse {
+    /// Hex payloads that exited the mixnet here, oldest first.
+    pub messages: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PirStoreRequest {
+    /// Hex content.
+    pub content: String,
+    pub ttl_secs: u64,
+}
+
+#[derive(Serialize)]
+pub struct PirStoreResponse {
+    /// Hex commitment the content is fetched by.
+    pub commitment: String,
+}
+
+#[derive(Deserialize)]
+pub struct PirFetchRequest {
+    pub commitment: String,
+}
+
+#[derive(Serialize)]
+pub struct PirFetchResponse {
+    pub commitment: String,
+    /// Hex content.
+    pub content: String,
+}
+
+#[derive(Deserialize)]
+pub struct CredentialStoreRequest {
+    pub credential_type: CredentialType,
+    /// The number the issuer attested.
+    pub value: u64,
+    /// Hex salt the issuer committed to the value under.
+    pub salt: String,
+    /// Hex Ed25519 key of the issuer.
+    pub issuer: String,
+    /// Hex issuer signature over the commitment.
+    pub signature: String,
+    pub expiry_secs: u64,
+}
+
+#[derive(Serialize)]
+pub struct CredentialStoreResponse {
+    pub credential_type: String,
+    /// Hex commitment the issuer signed.
+    pub commitment: String,
+}
+
+#[derive(Deserialize)]
+pub struct CredentialProveRequest {
+    pub credential_type: CredentialType,
+    /// Hex challenge from the verifier; a random one if absent.
+    pub challenge: Option<String>,
+    pub ttl_secs: u64,
+}
+
+#[derive(Deserialize)]
+pub struct CredentialVerifyRequest {
+    pub proof: CredentialProof,
+    /// Hex Ed25519 key of the issuer the verifier trusts.
+    pub issuer: String,
+}
+
+#[derive(Serialize)]
+pub struct CredentialVerifyResponse {
+    pub valid: bool,
+}
+
 #[derive(Deserialize)]
 pub struct IdentityRegisterRequest {
     pub commitment: String,
@@ -202,7 +395,9 @@ pub struct NoteSpendRequest {
     pub merkle_root: String,
     pub nullifier: String,
     pub recipient: String,
+    pub amount: String,
     pub fee: String,
+    pub asset: String,
     pub proof: String,
 }
 
@@ -223,6 +418,38 @@ pub struct MixerStatusResponse {
     pub failed_spends: u64,
 }
 
+#[derive(Deserialize)]
+pub struct StealthInitRequest {
+    pub view_private_key: String,
+    pub spend_public_key: String,
+}
+
+#[derive(Serialize)]
+pub struct StealthInitResponse {
+    pub success: bool,
+    pub meta_address: String,
+}
+
+#[derive(Serialize)]
+pub struct StealthPaymentInfo {
+    pub stealth_address: String,
+    pub caller: String,
+    pub ephemeral_pubkey: String,
+    pub metadata: String,
+    pub block_number: u64,
+    pub tx_hash: String,
+    pub log_index: u64,
+    pub detected_at: String,
+}
+
+#[derive(Serialize)]
+pub struct StealthPaymentsResponse {
+    pub initialized: bool,
+    pub checkpoint: Option<u64>,
+    pub announcements_scanned: u64,
+    pub payments: Vec<StealthPaymentInfo>,
+}
+
 #[derive(Serialize)]
 pub struct StakingInfoResponse {
     pub available: bool,
@@ -14,10 +14,15 @@ fn test_status_response_serialization() {
         staked_nox: 100.0,
         pending_rewards: 5.5,
         streak_days: 7,
+        reachability: crate::p2p::Reachability::Private,
+        public_address: None,
+        relay_reservations: 1,
+        hole_punch_successes: 0,
     };
 
     let json = serde_json::to_string(&response).unwrap();
     assert!(json.contains("\"node_id\":\"test\""));
+    assert!(json.contains("\"reachability\":\"private\""));
 }
 
 #[test]
@@ -43,3 +48,23 @@ fn test_privacy_stats_serialization() {
     let json = serde_json::to_string(&response).unwrap();
     assert!(json.contains("\"tracking_block_rate\":20.0"));
 }
+
+#[tokio::test]
+async fn test_credential_proof_request_round_trip() {
+    use crate::privacy::{CredentialManager, CredentialType};
+
+    let holder = CredentialManager::new();
+    holder.store_credential(CredentialType::AgeOver(18), &[25], 3600).await.unwrap();
+    let proof = holder.create_proof(&CredentialType::AgeOver(18), 300).await.unwrap();
+
+    // The proof `credentials prove` prints is what `credentials verify` posts.
+    let body = serde_json::json!({ "proof": proof, "issuer": "00".repeat(32) }).to_string();
+    let request: CredentialVerifyRequest = serde_json::from_str(&body).unwrap();
+    assert_eq!(request.proof.commitment, proof.commitment);
+    assert_eq!(request.proof.challenge, proof.challenge);
+
+    let request: CredentialProveRequest =
+        serde_json::from_str(r#"{"credential_type":{"AgeOver":18},"ttl_secs":300}"#).unwrap();
+    assert_eq!(request.credential_type, CredentialType::AgeOver(18));
+    assert!(request.challenge.is_none());
+}
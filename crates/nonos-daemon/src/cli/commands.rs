@@ -96,6 +96,19 @@ pub enum Commands {
         action: MixerAction,
     },
 
+    #[command(about = "Manage filter-list subscriptions")]
+    Filters {
+        #[command(subcommand)]
+        action: FiltersAction,
+    },
+
+    #[command(about = "Issue, hold and prove numeric credentials")]
+    #[command(long_about = "Issue, hold and prove numeric credentials.\n\nCredentials are given as age-over:<years>, stake-over:<amount> or account-age-over:<days>. Proving and verifying need the credential range keys from zk-keygen in <data_dir>/zk-keys/.")]
+    Credentials {
+        #[command(subcommand)]
+        action: CredentialsAction,
+    },
+
     #[command(about = "Launch TUI dashboard")]
     Dash {
         #[arg(long, default_value = "matrix", help = "Dashboard theme (matrix, dark, light)")]
@@ -108,6 +121,13 @@ pub enum Commands {
         action: StakeAction,
     },
 
+    #[command(about = "Manage wallets and keystores")]
+    #[command(long_about = "Import and export Ethereum keystore V3 files.\n\nKeystore passwords are read from --password-file, NONOS_KEYSTORE_PASSWORD or stdin. Imported keys are stored encrypted with NONOS_WALLET_PASSWORD, or with a wallet password prompted for separately.")]
+    Wallet {
+        #[command(subcommand)]
+        action: WalletAction,
+    },
+
     #[command(about = "Manage rewards")]
     Rewards {
         #[command(subcommand)]
@@ -242,6 +262,93 @@ pub enum MixerAction {
     },
 }
 
+#[derive(Subcommand)]
+pub enum FiltersAction {
+    #[command(about = "List subscribed filter lists")]
+    List,
+    #[command(about = "Subscribe to a filter list")]
+    Add {
+        #[arg(help = "List name")]
+        name: String,
+        #[arg(long, help = "Maintainer's Ed25519 public key (hex)")]
+        maintainer: String,
+    },
+    #[command(about = "Unsubscribe from a filter list")]
+    Remove {
+        #[arg(help = "List name")]
+        name: String,
+    },
+    #[command(about = "Generate a maintainer signing key")]
+    Keygen {
+        #[arg(long, short, help = "Output file for the private key")]
+        output: PathBuf,
+    },
+    #[command(about = "Sign and publish a filter list version")]
+    Publish {
+        #[arg(long, help = "List name")]
+        name: String,
+        #[arg(long, help = "Maintainer private key file")]
+        key: PathBuf,
+        #[arg(long, help = "Version number, higher than the last published")]
+        version: u64,
+        #[arg(long, help = "Filter list file")]
+        list: PathBuf,
+        #[arg(long, help = "Previous version of the list, to publish a diff against")]
+        base: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CredentialsAction {
+    #[command(about = "Generate an issuer signing key")]
+    Keygen {
+        #[arg(long, short, help = "Output file for the private key")]
+        output: PathBuf,
+    },
+    #[command(about = "Sign a holder's credential value as its issuer")]
+    Issue {
+        #[arg(help = "Credential, e.g. age-over:18")]
+        credential: String,
+        #[arg(long, help = "The holder's value, e.g. their age")]
+        value: u64,
+        #[arg(long, help = "Issuer private key file")]
+        key: PathBuf,
+    },
+    #[command(about = "Keep an issued credential on this node")]
+    Store {
+        #[arg(help = "Credential, e.g. age-over:18")]
+        credential: String,
+        #[arg(long, help = "The value the issuer attested")]
+        value: u64,
+        #[arg(long, help = "Salt from the issuer (hex)")]
+        salt: String,
+        #[arg(long, help = "Issuer's Ed25519 public key (hex)")]
+        issuer: String,
+        #[arg(long, help = "Issuer's signature (hex)")]
+        signature: String,
+        #[arg(long, default_value = "31536000", help = "Seconds the credential is kept")]
+        expiry: u64,
+    },
+    #[command(about = "Prove a held credential without revealing its value")]
+    Prove {
+        #[arg(help = "Credential, e.g. age-over:18")]
+        credential: String,
+        #[arg(long, help = "Verifier's challenge (hex)")]
+        challenge: Option<String>,
+        #[arg(long, default_value = "300", help = "Seconds the proof is valid")]
+        ttl: u64,
+        #[arg(long, short, help = "Output file for the proof")]
+        output: Option<PathBuf>,
+    },
+    #[command(about = "Verify a credential proof")]
+    Verify {
+        #[arg(help = "Proof file")]
+        proof: PathBuf,
+        #[arg(long, help = "Trusted issuer's Ed25519 public key (hex)")]
+        issuer: String,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum StakeAction {
     #[command(about = "Show staking status")]
@@ -265,6 +372,42 @@ pub enum StakeAction {
     Tiers,
 }
 
+#[derive(Subcommand)]
+pub enum WalletAction {
+    #[command(about = "List stored wallets")]
+    List,
+    #[command(about = "Import a keystore V3 file (geth, Foundry, MetaMask)")]
+    ImportKeystore {
+        #[arg(help = "Path to keystore JSON file")]
+        file: PathBuf,
+        #[arg(long, help = "Name for the imported wallet")]
+        name: Option<String>,
+        #[arg(long, value_name = "FILE", help = "File containing the keystore password")]
+        password_file: Option<PathBuf>,
+        #[arg(long, help = "Use the imported key as the node reward key")]
+        reward: bool,
+    },
+    #[command(about = "Export a wallet account as a keystore V3 file")]
+    ExportKeystore {
+        #[arg(help = "Wallet ID (defaults to the reward wallet)")]
+        id: Option<String>,
+        #[arg(long, short, help = "Output file path")]
+        output: Option<PathBuf>,
+        #[arg(long, default_value = "0", help = "Account index")]
+        account: u32,
+        #[arg(long, default_value = "scrypt", help = "Key derivation function")]
+        kdf: KeystoreKdfArg,
+        #[arg(long, value_name = "FILE", help = "File containing the keystore password")]
+        password_file: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum KeystoreKdfArg {
+    Scrypt,
+    Pbkdf2,
+}
+
 #[derive(Subcommand)]
 pub enum RewardsAction {
     #[command(about = "Show rewards status")]
@@ -0,0 +1,148 @@
+use super::commands::{CredentialsAction, OutputFormat};
+use super::filters::{api_request, read_file, read_signing_key, write_signing_key};
+use nonos_crypto::{
+    credential_commitment, credential_issuance_message, ed25519_derive_public, ed25519_sign, random_bytes,
+};
+use nonos_daemon::CredentialType;
+use nonos_types::{NonosError, NonosResult};
+use std::path::PathBuf;
+
+pub async fn handle_credentials(action: CredentialsAction, format: &OutputFormat) -> NonosResult<()> {
+    let api_port = std::env::var("NONOS_API_PORT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(8420u16);
+    let base_url = format!("http://127.0.0.1:{}/api/privacy/credentials", api_port);
+
+    match action {
+        CredentialsAction::Keygen { output } => {
+            let public_key = write_signing_key(&output)?;
+            match format {
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                        "issuer": public_key.to_hex(),
+                        "output": output,
+                    })).unwrap());
+                }
+                OutputFormat::Text => {
+                    println!("\x1b[38;5;46m[+]\x1b[0m Issuer key written to {}", output.display());
+                    println!("Public key: \x1b[38;5;51m{}\x1b[0m", public_key.to_hex());
+                }
+            }
+        }
+        CredentialsAction::Issue { credential, value, key } => {
+            let credential_type = parse_credential(&credential)?;
+            let (kind, threshold) = credential_type.range_claim().expect("parsed credentials are numeric");
+            if value < threshold {
+                return Err(NonosError::Config(format!("{} does not meet {}", value, credential)));
+            }
+
+            let key = read_signing_key(&key)?;
+            let salt = random_bytes::<32>();
+            let commitment = credential_commitment(kind, value, &salt);
+            let signature = ed25519_sign(&key, &credential_issuance_message(kind, &commitment));
+            let issuer = ed25519_derive_public(&key).to_hex();
+
+            match format {
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                        "credential": credential,
+                        "value": value,
+                        "salt": hex::encode(salt),
+                        "issuer": issuer,
+                        "signature": hex::encode(signature),
+                    })).unwrap());
+                }
+                OutputFormat::Text => {
+                    println!("\x1b[38;5;46m[+]\x1b[0m Issued {} for value {}", credential, value);
+                    println!("Give the holder:");
+                    println!(
+                        "  nonos credentials store {} --value {} --salt {} --issuer {} --signature {}",
+                        credential, value, hex::encode(salt), issuer, hex::encode(signature)
+                    );
+                }
+            }
+        }
+        CredentialsAction::Store { credential, value, salt, issuer, signature, expiry } => {
+            let request = reqwest::Client::new()
+                .post(format!("{}/store", base_url))
+                .json(&serde_json::json!({
+                    "credential_type": parse_credential(&credential)?,
+                    "value": value,
+                    "salt": salt,
+                    "issuer": issuer,
+                    "signature": signature,
+                    "expiry_secs": expiry,
+                }));
+            if let Some(body) = api_request(request).await {
+                match format {
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&body).unwrap()),
+                    OutputFormat::Text => {
+                        println!("\x1b[38;5;46m[+]\x1b[0m Stored \x1b[38;5;51m{}\x1b[0m", credential);
+                        let commitment = body.get("commitment").and_then(|c| c.as_str()).unwrap_or("-");
+                        println!("Commitment: \x1b[38;5;245m{}\x1b[0m", commitment);
+                    }
+                }
+            }
+        }
+        CredentialsAction::Prove { credential, challenge, ttl, output } => {
+            let request = reqwest::Client::new()
+                .post(format!("{}/prove", base_url))
+                .json(&serde_json::json!({
+                    "credential_type": parse_credential(&credential)?,
+                    "challenge": challenge,
+                    "ttl_secs": ttl,
+                }));
+            let Some(proof) = api_request(request).await else {
+                return Ok(());
+            };
+            let json = serde_json::to_string_pretty(&proof).unwrap();
+            match output {
+                Some(path) => {
+                    write_proof(&path, &json)?;
+                    if matches!(format, OutputFormat::Text) {
+                        println!("\x1b[38;5;46m[+]\x1b[0m Proof of {} written to {}", credential, path.display());
+                    }
+                }
+                None => println!("{}", json),
+            }
+        }
+        CredentialsAction::Verify { proof, issuer } => {
+            let proof: serde_json::Value = serde_json::from_str(&read_file(&proof)?)
+                .map_err(|e| NonosError::Serialization(format!("Invalid proof file: {}", e)))?;
+            let request = reqwest::Client::new()
+                .post(format!("{}/verify", base_url))
+                .json(&serde_json::json!({ "proof": proof, "issuer": issuer }));
+            if let Some(body) = api_request(request).await {
+                let valid = body.get("valid").and_then(|v| v.as_bool()).unwrap_or(false);
+                match format {
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&body).unwrap()),
+                    OutputFormat::Text if valid => println!("\x1b[38;5;46m[+]\x1b[0m Proof is valid"),
+                    OutputFormat::Text => println!("\x1b[38;5;196m[-]\x1b[0m Proof is invalid"),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses `age-over:<years>`, `stake-over:<amount>` or
+/// `account-age-over:<days>`, the credentials the range circuit proves.
+fn parse_credential(s: &str) -> NonosResult<CredentialType> {
+    let invalid = || NonosError::Config(format!(
+        "Invalid credential {:?}: expected age-over:<n>, stake-over:<n> or account-age-over:<n>", s
+    ));
+    let (name, threshold) = s.split_once(':').ok_or_else(invalid)?;
+    match name {
+        "age-over" => threshold.parse().map(CredentialType::AgeOver).map_err(|_| invalid()),
+        "stake-over" => threshold.parse().map(CredentialType::StakeOver).map_err(|_| invalid()),
+        "account-age-over" => threshold.parse().map(CredentialType::AccountAgeOver).map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+fn write_proof(path: &PathBuf, json: &str) -> NonosResult<()> {
+    std::fs::write(path, json)
+        .map_err(|e| NonosError::Storage(format!("Failed to write {}: {}", path.display(), e)))
+}
@@ -0,0 +1,259 @@
+use super::commands::{FiltersAction, OutputFormat};
+use nonos_crypto::generate_ed25519_keypair;
+use nonos_daemon::{canonical_rules, rules_hash, BundleBody, FilterListBundle, FilterListChunkData};
+use nonos_types::{Ed25519PrivateKey, Ed25519PublicKey, NonosError, NonosResult};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Pause between published chunks, keeping a large bundle well inside
+/// the per-peer gossip rate limits.
+const CHUNK_PACING: Duration = Duration::from_millis(100);
+
+pub async fn handle_filters(action: FiltersAction, format: &OutputFormat) -> NonosResult<()> {
+    let api_port = std::env::var("NONOS_API_PORT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(8420u16);
+    let base_url = format!("http://127.0.0.1:{}/api/privacy/filter-lists", api_port);
+
+    match action {
+        FiltersAction::List => {
+            let Some(body) = api_request(reqwest::Client::new().get(&base_url)).await else {
+                return Ok(());
+            };
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&body).unwrap()),
+                OutputFormat::Text => print_subscriptions(&body),
+            }
+        }
+        FiltersAction::Add { name, maintainer } => {
+            let request = reqwest::Client::new()
+                .post(format!("{}/add", base_url))
+                .json(&serde_json::json!({ "name": name, "maintainer": maintainer }));
+            if let Some(body) = api_request(request).await {
+                match format {
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&body).unwrap()),
+                    OutputFormat::Text => {
+                        println!("\x1b[38;5;46m[+]\x1b[0m Subscribed to \x1b[38;5;51m{}\x1b[0m", name);
+                        println!("\x1b[38;5;245mThe list is applied once a full copy reaches this node; nodes gossip theirs about hourly.\x1b[0m");
+                    }
+                }
+            }
+        }
+        FiltersAction::Remove { name } => {
+            let request = reqwest::Client::new()
+                .post(format!("{}/remove", base_url))
+                .json(&serde_json::json!({ "name": name }));
+            if let Some(body) = api_request(request).await {
+                let removed = body.get("success").and_then(|s| s.as_bool()).unwrap_or(false);
+                match format {
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&body).unwrap()),
+                    OutputFormat::Text if removed => {
+                        println!("\x1b[38;5;46m[+]\x1b[0m Unsubscribed from \x1b[38;5;51m{}\x1b[0m", name);
+                    }
+                    OutputFormat::Text => println!("\x1b[38;5;226m[!]\x1b[0m Not subscribed to {}", name),
+                }
+            }
+        }
+        FiltersAction::Keygen { output } => keygen(&output, format)?,
+        FiltersAction::Publish { name, key, version, list, base } => {
+            publish(&base_url, &name, &key, version, &list, base.as_deref(), format).await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn keygen(output: &PathBuf, format: &OutputFormat) -> NonosResult<()> {
+    let public_key = write_signing_key(output)?;
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                "maintainer": public_key.to_hex(),
+                "output": output,
+            })).unwrap());
+        }
+        OutputFormat::Text => {
+            println!("\x1b[38;5;46m[+]\x1b[0m Maintainer key written to {}", output.display());
+            println!("Public key: \x1b[38;5;51m{}\x1b[0m", public_key.to_hex());
+        }
+    }
+    Ok(())
+}
+
+async fn publish(
+    base_url: &str,
+    name: &str,
+    key_path: &Path,
+    version: u64,
+    list_path: &Path,
+    base_path: Option<&Path>,
+    format: &OutputFormat,
+) -> NonosResult<()> {
+    let key = read_signing_key(key_path)?;
+
+    let rules = canonical_rules(&read_file(list_path)?);
+    let body = match base_path {
+        Some(_) if version < 2 => {
+            return Err(NonosError::Config("A diff needs a version after the first".into()));
+        }
+        Some(path) => BundleBody::diff(version - 1, &read_file(path)?, &rules),
+        None => BundleBody::Full { rules: rules.clone() },
+    };
+    let bundle = FilterListBundle::sign(name, version, body, rules_hash(&rules), &key)?;
+    let chunks = bundle.to_chunks(&key)?;
+    let bundle_id = hex::encode(chunks[0].bundle_id);
+
+    let client = reqwest::Client::new();
+    let mut applied_version = None;
+    // Nodes that miss the diff catch up from full copies gossiped again
+    // later, so the local node needs one of this version to start with.
+    if base_path.is_some() {
+        let body = BundleBody::Full { rules: rules.clone() };
+        let full = FilterListBundle::sign(name, version, body, rules_hash(&rules), &key)?;
+        let Some(applied) = post_chunks(&client, base_url, &full.to_chunks(&key)?, true).await else {
+            return Ok(());
+        };
+        applied_version = applied;
+    }
+    let Some(applied) = post_chunks(&client, base_url, &chunks, false).await else {
+        return Ok(());
+    };
+    applied_version = applied.or(applied_version);
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                "name": name,
+                "version": version,
+                "maintainer": bundle.maintainer.to_hex(),
+                "bundle_id": bundle_id,
+                "chunks": chunks.len(),
+                "rules": rules.lines().count(),
+                "applied_locally": applied_version == Some(version),
+            })).unwrap());
+        }
+        OutputFormat::Text => {
+            let kind = if base_path.is_some() { "diff" } else { "full list" };
+            println!(
+                "\x1b[38;5;46m[+]\x1b[0m Published {} version {} ({}, {} rules) in {} chunks",
+                name, version, kind, rules.lines().count(), chunks.len()
+            );
+            println!("Bundle: \x1b[38;5;245m{}\x1b[0m", bundle_id);
+        }
+    }
+    Ok(())
+}
+
+/// Posts `chunks` to the daemon, to gossip or, with `catch_up`, only to
+/// keep. Returns the version applied locally, or `None` on failure.
+async fn post_chunks(
+    client: &reqwest::Client,
+    base_url: &str,
+    chunks: &[FilterListChunkData],
+    catch_up: bool,
+) -> Option<Option<u64>> {
+    let mut applied_version = None;
+    for (i, chunk) in chunks.iter().enumerate() {
+        if i > 0 && !catch_up {
+            tokio::time::sleep(CHUNK_PACING).await;
+        }
+        let request = client.post(format!("{}/publish", base_url)).json(&serde_json::json!({
+            "name": chunk.name,
+            "maintainer": chunk.maintainer.to_hex(),
+            "version": chunk.version,
+            "bundle_id": hex::encode(chunk.bundle_id),
+            "index": chunk.index,
+            "total": chunk.total,
+            "signature": hex::encode(&chunk.signature),
+            "data": hex::encode(&chunk.data),
+            "catch_up": catch_up,
+        }));
+        let body = api_request(request).await?;
+        applied_version = body.get("applied_version").and_then(|v| v.as_u64()).or(applied_version);
+    }
+    Some(applied_version)
+}
+
+/// Writes a new Ed25519 private key to `output`, readable only by its
+/// owner, and returns the public key.
+pub(super) fn write_signing_key(output: &Path) -> NonosResult<Ed25519PublicKey> {
+    if output.exists() {
+        return Err(NonosError::Config(format!("{} already exists", output.display())));
+    }
+
+    let (private_key, public_key) = generate_ed25519_keypair();
+    std::fs::write(output, hex::encode(private_key.as_bytes()))
+        .map_err(|e| NonosError::Storage(format!("Failed to write key: {}", e)))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(output, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| NonosError::Storage(format!("Failed to set permissions: {}", e)))?;
+    }
+    Ok(public_key)
+}
+
+pub(super) fn read_signing_key(path: &Path) -> NonosResult<Ed25519PrivateKey> {
+    let key_hex = read_file(path)?;
+    let key_bytes: [u8; 32] = hex::decode(key_hex.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| NonosError::Config(format!("Invalid key in {}", path.display())))?;
+    Ok(Ed25519PrivateKey::from_bytes(key_bytes))
+}
+
+fn print_subscriptions(body: &serde_json::Value) {
+    println!("\x1b[38;5;46mFilter List Subscriptions\x1b[0m");
+    println!("\x1b[38;5;245m{}\x1b[0m", "═".repeat(50));
+
+    let subscriptions = body.get("subscriptions").and_then(|s| s.as_array()).cloned().unwrap_or_default();
+    if subscriptions.is_empty() {
+        println!("\x1b[38;5;245mNo subscriptions\x1b[0m");
+        return;
+    }
+
+    for list in subscriptions {
+        let field = |name: &str| list.get(name).cloned().unwrap_or_default();
+        let version = field("version").as_u64().unwrap_or(0);
+        println!("  \x1b[38;5;46m●\x1b[0m \x1b[38;5;51m{}\x1b[0m", field("name").as_str().unwrap_or("unknown"));
+        println!("    Maintainer: {}", field("maintainer").as_str().unwrap_or("-"));
+        if version == 0 {
+            println!("    Version:    \x1b[38;5;226mwaiting for first bundle\x1b[0m");
+        } else {
+            println!("    Version:    {} ({} rules)", version, field("rules").as_u64().unwrap_or(0));
+        }
+    }
+}
+
+/// Sends `request` with the API token, if one is set, and returns the JSON
+/// body. Failures are reported and yield `None`.
+pub(super) async fn api_request(request: reqwest::RequestBuilder) -> Option<serde_json::Value> {
+    let request = match std::env::var("NONOS_API_TOKEN") {
+        Ok(token) if !token.is_empty() => request.bearer_auth(token),
+        _ => request,
+    };
+
+    match request.send().await {
+        Ok(response) => {
+            let success = response.status().is_success();
+            let body: serde_json::Value = response.json().await.unwrap_or_default();
+            if success {
+                return Some(body);
+            }
+            let message = body.get("error").and_then(|e| e.as_str()).unwrap_or("Request failed");
+            println!("\x1b[38;5;196m[-]\x1b[0m {}", message);
+            None
+        }
+        Err(_) => {
+            println!("\x1b[38;5;245mDaemon not running\x1b[0m");
+            None
+        }
+    }
+}
+
+pub(super) fn read_file(path: &Path) -> NonosResult<String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| NonosError::Storage(format!("Failed to read {}: {}", path.display(), e)))
+}
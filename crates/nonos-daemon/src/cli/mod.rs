@@ -4,7 +4,10 @@ pub mod init;
 pub mod identity;
 pub mod stake;
 pub mod rewards;
+pub mod wallet;
 pub mod mixer;
+pub mod filters;
+pub mod credentials;
 pub mod info;
 pub mod checks;
 pub mod peers;
@@ -19,7 +22,10 @@ pub use init::init_node;
 pub use identity::handle_identity;
 pub use stake::handle_stake;
 pub use rewards::handle_rewards;
+pub use wallet::handle_wallet;
 pub use mixer::handle_mixer;
+pub use filters::handle_filters;
+pub use credentials::handle_credentials;
 pub use info::{show_info, show_status, show_version};
 pub use checks::run_checks;
 pub use peers::{handle_peers, show_stats};
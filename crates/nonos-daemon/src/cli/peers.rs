@@ -38,7 +38,23 @@ pub async fn handle_peers(
             }
         }
         Some(PeersAction::Show { peer_id }) => {
-            println!("Peer details for \x1b[38;5;51m{}\x1b[0m (not yet implemented)", peer_id);
+            let url = format!("http://127.0.0.1:{}/api/v1/node/peers/{}", api_port, peer_id);
+            match reqwest::get(&url).await {
+                Ok(response) => {
+                    let body: serde_json::Value = response.json().await.unwrap_or_default();
+                    match (format, body.get("data")) {
+                        (OutputFormat::Json, _) => println!("{}", serde_json::to_string_pretty(&body).unwrap()),
+                        (OutputFormat::Text, Some(peer)) if !peer.is_null() => print_peer(peer),
+                        (OutputFormat::Text, _) => {
+                            let message = body.pointer("/error/message")
+                                .and_then(|m| m.as_str())
+                                .unwrap_or("Unknown peer");
+                            println!("\x1b[38;5;196m{}\x1b[0m", message);
+                        }
+                    }
+                }
+                Err(_) => println!("\x1b[38;5;245mDaemon not running\x1b[0m"),
+            }
         }
         Some(PeersAction::Ban { peer_id }) => {
             println!("Banning peer \x1b[38;5;196m{}\x1b[0m (not yet implemented)", peer_id);
@@ -50,6 +66,36 @@ pub async fn handle_peers(
     Ok(())
 }
 
+fn print_peer(peer: &serde_json::Value) {
+    let field = |name: &str| match peer.get(name) {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Null) | None => "-".to_string(),
+        Some(value) => value.to_string(),
+    };
+
+    println!("\x1b[38;5;46mPeer {}\x1b[0m", field("peer_id"));
+    println!("\x1b[38;5;245m{}\x1b[0m", "═".repeat(50));
+    println!("State:           {}", field("state"));
+    if let Some(addresses) = peer.get("addresses").and_then(|a| a.as_array()) {
+        for address in addresses {
+            println!("Address:         {}", address.as_str().unwrap_or("unknown"));
+        }
+    }
+    println!("Latency (ms):    {}", field("latency_ms"));
+    println!("Quality score:   {}", field("quality_score"));
+    println!("Penalty score:   {}", field("penalty_score"));
+    match peer.get("gossipsub_score").and_then(|s| s.as_f64()) {
+        Some(score) => println!("Gossipsub score: \x1b[38;5;51m{:.2}\x1b[0m", score),
+        None => println!("Gossipsub score: -"),
+    }
+    if peer.get("is_banned").and_then(|b| b.as_bool()).unwrap_or(false) {
+        println!("Banned:          \x1b[38;5;196myes\x1b[0m ({}s remaining)", field("ban_remaining_secs"));
+    }
+    println!("Role:            {}", field("role_hint"));
+    println!("Protocol:        {}", field("protocol_version"));
+    println!("Messages:        {}", field("messages_received"));
+}
+
 pub async fn show_stats(_data_dir: &PathBuf, format: &OutputFormat) -> NonosResult<()> {
     let api_port = std::env::var("NONOS_API_PORT")
         .ok()
@@ -1,12 +1,19 @@
 use super::commands::{RewardsAction, OutputFormat};
-use super::utils::load_contract_config;
+use super::utils::{load_contract_config, load_fee_speed, resolve_wallet_key};
 use nonos_daemon::ContractClient;
 use nonos_types::{EthAddress, NonosResult};
+use std::path::{Path, PathBuf};
 use tracing::error;
 
-pub async fn handle_rewards(action: RewardsAction, format: &OutputFormat) -> NonosResult<()> {
+pub async fn handle_rewards(
+    action: RewardsAction,
+    config_path: &PathBuf,
+    data_dir: &Path,
+    format: &OutputFormat,
+) -> NonosResult<()> {
     let contract_config = load_contract_config()?;
     let mut client = ContractClient::new(contract_config);
+    client.set_fee_speed(load_fee_speed()?);
 
     if let Err(e) = client.connect().await {
         error!("Failed to connect to network: {}", e);
@@ -14,7 +21,7 @@ pub async fn handle_rewards(action: RewardsAction, format: &OutputFormat) -> Non
         return Ok(());
     }
 
-    let wallet_key = std::env::var("NONOS_WALLET_KEY").ok();
+    let wallet_key = resolve_wallet_key(config_path, data_dir)?;
     let wallet_address = if let Some(ref key) = wallet_key {
         client.set_wallet(key).await.ok().map(|a| EthAddress(a.0))
     } else {
@@ -40,7 +47,7 @@ pub async fn handle_rewards(action: RewardsAction, format: &OutputFormat) -> Non
                     }
                 }
             } else {
-                println!("\x1b[38;5;245mNo wallet configured. Set NONOS_WALLET_KEY environment variable.\x1b[0m");
+                println!("\x1b[38;5;245mNo wallet configured. Set NONOS_WALLET_KEY or import a keystore with 'nonos wallet import-keystore --reward'.\x1b[0m");
             }
         }
         RewardsAction::Claim => {
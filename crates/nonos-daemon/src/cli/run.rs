@@ -1,13 +1,14 @@
-use super::utils::print_banner;
+use super::utils::{load_contract_config, print_banner, resolve_wallet_key};
 use nonos_daemon::{
     Node, NodeConfig, NodeStorage, ServiceManager, ServiceConfig,
-    PrivacyServiceManager, ApiServer,
+    ApiServer, ContractClient, PrivacyServiceManager, VoterBinding,
 };
-use nonos_types::NonosResult;
-use std::path::PathBuf;
+use nonos_daemon::services::QualityAttestations;
+use nonos_types::{NodeId, NonosResult, Secp256k1PrivateKey};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{info, error};
+use tracing::{info, error, warn};
 
 pub async fn run_node(
     config_path: &PathBuf,
@@ -54,17 +55,21 @@ pub async fn run_node(
     let metrics_collector = node.metrics_collector();
 
     let mut service_manager = ServiceManager::new();
-    if config.services.health_beacon || config.services.quality_oracle {
+    if config.services.health_beacon {
         let network = node.network().ok_or_else(|| {
             nonos_types::NonosError::Internal("Network not initialized".into())
         })?;
 
         let service_config = ServiceConfig {
             health_beacon: config.services.health_beacon,
-            quality_oracle: config.services.quality_oracle,
+            // The node runs the quality oracle itself, next to the epoch
+            // settlement that consumes its attestations.
+            quality_oracle: false,
             bootstrap: config.services.bootstrap,
             cache: config.services.cache,
             cache_size_mb: config.services.cache_size_mb,
+            // The node runs the mixnet relay itself, so the API can reach it.
+            mixnet: false,
             ..Default::default()
         };
 
@@ -79,9 +84,15 @@ pub async fn run_node(
     }
 
     let node = Arc::new(RwLock::new(node));
-    let privacy_manager = Arc::new(PrivacyServiceManager::new(node_id));
-    privacy_manager.start_all().await?;
+    let privacy_manager = node.read().await.privacy().ok_or_else(|| {
+        nonos_types::NonosError::Internal("Privacy services not initialized".into())
+    })?;
     info!("Privacy services started (ZK Identity, Cache Mixing, Tracking Blocker)");
+    let attestations = match node.read().await.services() {
+        Some(services) => Some(services.read().await.attestations()),
+        None => None,
+    };
+    configure_stake_weights(&privacy_manager, attestations.as_deref(), node_id, config_path, data_dir).await;
 
     let api_addr: std::net::SocketAddr = format!("{}:{}", config.api.bind_address, config.api.port)
         .parse()
@@ -128,6 +139,65 @@ pub async fn run_node(
     Ok(())
 }
 
+/// Weighs privacy oracle votes and latency attestations by on-chain stake
+/// and, when a wallet key is available, lets this node vote and attest
+/// with that wallet's stake. Neither is fatal: without them the node still
+/// relays votes and attestations but counts none.
+async fn configure_stake_weights(
+    privacy: &PrivacyServiceManager,
+    attestations: Option<&QualityAttestations>,
+    node_id: NodeId,
+    config_path: &Path,
+    data_dir: &Path,
+) {
+    let mut client = match load_contract_config() {
+        Ok(contract_config) => ContractClient::new(contract_config),
+        Err(e) => {
+            warn!("Privacy oracle votes and attestations will not be counted: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = client.connect().await {
+        warn!("Privacy oracle votes and attestations will not be counted: {}", e);
+        return;
+    }
+    let client = Arc::new(client);
+    privacy.privacy_oracle.set_stake_lookup(client.clone());
+    if let Some(attestations) = attestations {
+        attestations.set_stake_lookup(client);
+    }
+
+    let key = if config_path.exists() {
+        resolve_wallet_key(&config_path.to_path_buf(), data_dir)
+    } else {
+        Ok(std::env::var("NONOS_WALLET_KEY").ok())
+    };
+    let binding = key.and_then(|key| match key {
+        Some(key) => {
+            let key = Secp256k1PrivateKey::from_hex(key.strip_prefix("0x").unwrap_or(&key))?;
+            VoterBinding::sign(&key, &node_id).map(Some)
+        }
+        None => Ok(None),
+    });
+    match binding {
+        Ok(Some(binding)) => {
+            let staker = binding.staker;
+            if let Some(attestations) = attestations {
+                match attestations.set_binding(&node_id, binding.clone()) {
+                    Ok(()) => info!("Latency attestations weighted by the stake of {}", staker),
+                    Err(e) => warn!("Latency attestations will not be counted: {}", e),
+                }
+            }
+            match privacy.privacy_oracle.set_voter(node_id, binding) {
+                Ok(()) => info!("Privacy oracle votes cast with the stake of {}", staker),
+                Err(e) => warn!("Privacy oracle voting disabled: {}", e),
+            }
+        }
+        Ok(None) => info!("No wallet key configured, privacy oracle voting disabled"),
+        Err(e) => warn!("Privacy oracle voting disabled: {}", e),
+    }
+}
+
 fn print_ready_message(node_id: nonos_types::NodeId, api_addr: std::net::SocketAddr) {
     println!();
     println!("\x1b[38;5;46m╔══════════════════════════════════════════════════════════════╗\x1b[0m");
@@ -1,13 +1,20 @@
 use super::commands::{StakeAction, OutputFormat};
-use super::utils::load_contract_config;
+use super::utils::{load_contract_config, load_fee_speed, resolve_wallet_key};
 use nonos_daemon::ContractClient;
 use nonos_types::{EthAddress, NodeTier, TokenAmount, NOX_DECIMALS};
 use nonos_types::NonosResult;
+use std::path::{Path, PathBuf};
 use tracing::error;
 
-pub async fn handle_stake(action: StakeAction, format: &OutputFormat) -> NonosResult<()> {
+pub async fn handle_stake(
+    action: StakeAction,
+    config_path: &PathBuf,
+    data_dir: &Path,
+    format: &OutputFormat,
+) -> NonosResult<()> {
     let contract_config = load_contract_config()?;
     let mut client = ContractClient::new(contract_config);
+    client.set_fee_speed(load_fee_speed()?);
 
     if let Err(e) = client.connect().await {
         error!("Failed to connect to network: {}", e);
@@ -16,7 +23,7 @@ pub async fn handle_stake(action: StakeAction, format: &OutputFormat) -> NonosRe
         return Ok(());
     }
 
-    let wallet_key = std::env::var("NONOS_WALLET_KEY").ok();
+    let wallet_key = resolve_wallet_key(config_path, data_dir)?;
     let wallet_address = if let Some(ref key) = wallet_key {
         client.set_wallet(key).await.ok().map(|a| EthAddress(a.0))
     } else {
@@ -49,7 +56,7 @@ pub async fn handle_stake(action: StakeAction, format: &OutputFormat) -> NonosRe
                     }
                 }
             } else {
-                println!("\x1b[38;5;245mNo wallet configured. Set NONOS_WALLET_KEY environment variable.\x1b[0m");
+                println!("\x1b[38;5;245mNo wallet configured. Set NONOS_WALLET_KEY or import a keystore with 'nonos wallet import-keystore --reward'.\x1b[0m");
             }
         }
         StakeAction::Deposit { amount } => {
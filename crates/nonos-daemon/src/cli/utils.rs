@@ -1,10 +1,12 @@
 use super::commands::Cli;
-use nonos_daemon::ContractConfig;
+use nonos_daemon::{ContractConfig, NodeConfig};
 use nonos_daemon::contracts::{
     USE_SEPOLIA, NOX_TOKEN_SEPOLIA, NOX_TOKEN_MAINNET,
     NOX_STAKING_CONTRACT_SEPOLIA, NOX_STAKING_CONTRACT_MAINNET,
 };
-use nonos_types::{EthAddress, NonosResult};
+use nonos_types::{EthAddress, NonosResult, WalletId};
+use nonos_wallet::{FeeSpeed, FileWalletStorage, WalletStorage};
+use std::path::{Path, PathBuf};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter, layer::SubscriberExt};
 
 const BUILD_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -119,6 +121,14 @@ pub fn load_contract_config() -> NonosResult<ContractConfig> {
     })
 }
 
+/// Fee tier for staking and reward transactions, from `NONOS_FEE_SPEED`.
+pub fn load_fee_speed() -> NonosResult<FeeSpeed> {
+    match std::env::var("NONOS_FEE_SPEED") {
+        Ok(speed) => speed.parse(),
+        Err(_) => Ok(FeeSpeed::default()),
+    }
+}
+
 pub fn parse_eth_address(s: &str) -> NonosResult<EthAddress> {
     let s = s.strip_prefix("0x").unwrap_or(s);
     if s.len() != 40 {
@@ -135,3 +145,24 @@ pub fn parse_eth_address(s: &str) -> NonosResult<EthAddress> {
     }
     Ok(EthAddress(addr))
 }
+
+pub fn wallet_storage(data_dir: &Path) -> NonosResult<FileWalletStorage> {
+    FileWalletStorage::new(data_dir.join("wallets"))
+}
+
+/// Signing key for stake and rewards commands: `NONOS_WALLET_KEY`, or the
+/// reward wallet imported with `nonos wallet import-keystore --reward`,
+/// unlocked with `NONOS_WALLET_PASSWORD`.
+pub fn resolve_wallet_key(config_path: &PathBuf, data_dir: &Path) -> NonosResult<Option<String>> {
+    if let Ok(key) = std::env::var("NONOS_WALLET_KEY") {
+        return Ok(Some(key));
+    }
+
+    let config = NodeConfig::load(config_path)?;
+    let (Some(id), Ok(password)) = (config.rewards.reward_wallet, std::env::var("NONOS_WALLET_PASSWORD")) else {
+        return Ok(None);
+    };
+
+    let wallet = wallet_storage(data_dir)?.load_wallet(&WalletId::from_str(&id)?, &password)?;
+    Ok(Some(wallet.get_account_private_key(0)?))
+}
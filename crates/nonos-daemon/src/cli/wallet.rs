@@ -0,0 +1,225 @@
+use super::commands::{KeystoreKdfArg, OutputFormat, WalletAction};
+use super::utils::wallet_storage;
+use nonos_crypto::{KeystoreKdf, KeystoreV3};
+use nonos_daemon::NodeConfig;
+use nonos_types::{NonosError, NonosResult, WalletId};
+use nonos_wallet::{FileWalletStorage, WalletStorage};
+use std::path::{Path, PathBuf};
+
+pub fn handle_wallet(
+    action: WalletAction,
+    config_path: &PathBuf,
+    data_dir: &Path,
+    format: &OutputFormat,
+) -> NonosResult<()> {
+    let storage = wallet_storage(data_dir)?;
+
+    match action {
+        WalletAction::List => list_wallets(&storage, config_path, format)?,
+        WalletAction::ImportKeystore { file, name, password_file, reward } => {
+            import_keystore(&storage, config_path, &file, name, password_file, reward, format)?
+        }
+        WalletAction::ExportKeystore { id, output, account, kdf, password_file } => {
+            export_keystore(&storage, config_path, id, output, account, kdf, password_file, format)?
+        }
+    }
+
+    Ok(())
+}
+
+fn list_wallets(storage: &FileWalletStorage, config_path: &PathBuf, format: &OutputFormat) -> NonosResult<()> {
+    let reward_wallet = NodeConfig::load(config_path).ok().and_then(|c| c.rewards.reward_wallet);
+
+    let mut wallets = Vec::new();
+    for id in storage.list_wallets()? {
+        wallets.push(storage.load_metadata(&id)?);
+    }
+    wallets.sort_by_key(|w| w.created_at);
+
+    match format {
+        OutputFormat::Json => {
+            let list: Vec<_> = wallets.iter().map(|w| serde_json::json!({
+                "id": w.id.to_string(),
+                "name": w.name,
+                "address": w.address.to_string(),
+                "derivation": w.derivation,
+                "created_at": w.created_at.to_rfc3339(),
+                "reward": reward_wallet.as_deref() == Some(w.id.to_string().as_str()),
+            })).collect();
+            println!("{}", serde_json::to_string_pretty(&list).unwrap());
+        }
+        OutputFormat::Text => {
+            if wallets.is_empty() {
+                println!("\x1b[38;5;245mNo wallets found. Import a keystore with:\x1b[0m");
+                println!("  \x1b[38;5;51mnonos wallet import-keystore <FILE> --reward\x1b[0m");
+            } else {
+                println!("\x1b[38;5;46mWallets\x1b[0m");
+                println!("\x1b[38;5;245m{}\x1b[0m", "═".repeat(90));
+                println!("{:<38} {:<16} {:<44} {:<10}", "ID", "Name", "Address", "Derivation");
+                println!("{}", "-".repeat(90));
+
+                for w in &wallets {
+                    let id = w.id.to_string();
+                    let marker = if reward_wallet.as_deref() == Some(id.as_str()) {
+                        " \x1b[38;5;226m(reward)\x1b[0m"
+                    } else {
+                        ""
+                    };
+                    println!(
+                        "{:<38} {:<16} {:<44} {:<10}{}",
+                        id, w.name, w.address.to_string(), format!("{:?}", w.derivation).to_lowercase(), marker
+                    );
+                }
+                println!("\x1b[38;5;245m{}\x1b[0m", "═".repeat(90));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn import_keystore(
+    storage: &FileWalletStorage,
+    config_path: &PathBuf,
+    file: &PathBuf,
+    name: Option<String>,
+    password_file: Option<PathBuf>,
+    reward: bool,
+    format: &OutputFormat,
+) -> NonosResult<()> {
+    if !file.exists() {
+        return Err(NonosError::Config(format!("File not found: {:?}", file)));
+    }
+
+    let json = std::fs::read_to_string(file)
+        .map_err(|e| NonosError::Config(format!("Failed to read keystore: {}", e)))?;
+    let keystore = KeystoreV3::from_json(&json)?;
+
+    let keystore_password = read_password(password_file.as_ref(), "NONOS_KEYSTORE_PASSWORD", "Keystore password: ")?;
+    let wallet_password = match std::env::var("NONOS_WALLET_PASSWORD") {
+        Ok(password) => password,
+        Err(_) => prompt_password("Wallet password for the imported key: ")?,
+    };
+
+    let name = name.unwrap_or_else(|| {
+        file.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "imported".to_string())
+    });
+
+    let metadata = storage.import_keystore(name, &keystore, &keystore_password, &wallet_password)?;
+
+    if reward {
+        let mut config = NodeConfig::load(config_path)?;
+        config.rewards.reward_address = metadata.address;
+        config.rewards.reward_wallet = Some(metadata.id.to_string());
+        config.save(config_path)?;
+    }
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                "id": metadata.id.to_string(),
+                "name": metadata.name,
+                "address": metadata.address.to_string(),
+                "reward": reward,
+            })).unwrap());
+        }
+        OutputFormat::Text => {
+            println!("\x1b[38;5;46m[+]\x1b[0m Keystore imported as wallet '{}'", metadata.name);
+            println!("    ID:      \x1b[38;5;226m{}\x1b[0m", metadata.id);
+            println!("    Address: \x1b[38;5;51m{}\x1b[0m", metadata.address);
+            if reward {
+                println!("\x1b[38;5;46m[+]\x1b[0m Reward key set in {:?}", config_path);
+                println!("\x1b[38;5;245mSet NONOS_WALLET_PASSWORD so stake and rewards commands can unlock it.\x1b[0m");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn export_keystore(
+    storage: &FileWalletStorage,
+    config_path: &PathBuf,
+    id: Option<String>,
+    output: Option<PathBuf>,
+    account: u32,
+    kdf: KeystoreKdfArg,
+    password_file: Option<PathBuf>,
+    format: &OutputFormat,
+) -> NonosResult<()> {
+    let id = match id {
+        Some(id) => id,
+        None => NodeConfig::load(config_path)?.rewards.reward_wallet.ok_or_else(|| {
+            NonosError::Config("No wallet ID given and no reward wallet configured".into())
+        })?,
+    };
+    let id = WalletId::from_str(&id)?;
+
+    let wallet_password = match std::env::var("NONOS_WALLET_PASSWORD") {
+        Ok(password) => password,
+        Err(_) => prompt_password("Wallet password: ")?,
+    };
+    let keystore_password = read_password(password_file.as_ref(), "NONOS_KEYSTORE_PASSWORD", "Keystore password: ")?;
+
+    let kdf = match kdf {
+        KeystoreKdfArg::Scrypt => KeystoreKdf::scrypt(),
+        KeystoreKdfArg::Pbkdf2 => KeystoreKdf::pbkdf2(),
+    };
+
+    let keystore = storage.export_keystore(&id, &wallet_password, account, &keystore_password, kdf)?;
+    let json = keystore.to_json()?;
+
+    let Some(output_path) = output else {
+        println!("{}", json);
+        return Ok(());
+    };
+
+    std::fs::write(&output_path, &json)
+        .map_err(|e| NonosError::Storage(format!("Failed to write keystore: {}", e)))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&output_path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| NonosError::Storage(format!("Failed to set permissions: {}", e)))?;
+    }
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                "id": id.to_string(),
+                "account": account,
+                "address": keystore.address,
+                "output": output_path,
+            })).unwrap());
+        }
+        OutputFormat::Text => {
+            println!("\x1b[38;5;46m[+]\x1b[0m Keystore exported to {:?}", output_path);
+            println!("\x1b[38;5;196mStore this file and its password securely!\x1b[0m");
+        }
+    }
+
+    Ok(())
+}
+
+fn read_password(password_file: Option<&PathBuf>, env_var: &str, prompt: &str) -> NonosResult<String> {
+    if let Some(path) = password_file {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| NonosError::Config(format!("Failed to read password file: {}", e)))?;
+        return Ok(contents.trim_end_matches(['\r', '\n']).to_string());
+    }
+
+    if let Ok(password) = std::env::var(env_var) {
+        return Ok(password);
+    }
+
+    prompt_password(prompt)
+}
+
+fn prompt_password(prompt: &str) -> NonosResult<String> {
+    rpassword::prompt_password(prompt)
+        .map_err(|e| NonosError::Config(format!("Failed to read password: {}", e)))
+}
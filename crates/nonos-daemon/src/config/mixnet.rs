@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// Lowest non-zero cover traffic rate, one packet every ~17 minutes.
+/// Slower rates hide nothing and their intervals stop being representable.
+pub const MIN_COVER_RATE: f64 = 0.001;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MixingMode {
+    /// Batch packets until the pool fills (or the oldest has waited
+    /// `max_delay_ms`), then shuffle and release them with uniform jitter.
+    Threshold,
+    /// Loopix-style continuous-time mixing: every packet is held for an
+    /// independent, exponentially distributed delay.
+    #[default]
+    Poisson,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MixingConfig {
+    pub mode: MixingMode,
+    /// Threshold mode: packets collected before a flush.
+    pub pool_size: usize,
+    /// Threshold mode: jitter bound and longest wait for the pool to fill.
+    pub max_delay_ms: u64,
+    /// Poisson mode: mean per-packet delay.
+    pub mean_delay_ms: u64,
+    /// Loop cover packets sent per second, routed back to this node; 0
+    /// disables them, otherwise at least [`MIN_COVER_RATE`].
+    pub loop_cover_rate: f64,
+    /// Drop cover packets sent per second, discarded by their exit; 0
+    /// disables them, otherwise at least [`MIN_COVER_RATE`].
+    pub drop_cover_rate: f64,
+    /// Lifetime of a mix key. Packets are accepted under the current and
+    /// previous key, and replay tags are kept only as long as their key.
+    pub key_rotation_secs: u64,
+}
+
+impl Default for MixingConfig {
+    fn default() -> Self {
+        Self {
+            mode: MixingMode::Poisson,
+            pool_size: 5,
+            max_delay_ms: 500,
+            mean_delay_ms: 200,
+            loop_cover_rate: 0.2,
+            drop_cover_rate: 0.2,
+            key_rotation_secs: 60 * 60,
+        }
+    }
+}
@@ -2,24 +2,30 @@ mod anyone;
 mod api;
 mod constants;
 mod logging;
+mod mixnet;
 mod network;
 mod node;
 mod rate_limit;
 mod rewards;
 mod security;
 mod services;
+mod stealth;
+mod tracking;
 mod types;
 
 pub use anyone::{AnyoneNetworkConfig, SecurityLevel};
 pub use api::ApiConfig;
 pub use constants::*;
 pub use logging::LoggingConfig;
+pub use mixnet::{MixingConfig, MixingMode, MIN_COVER_RATE};
 pub use network::NetworkConfig;
 pub use node::{NodeConfig, RedactedConfig};
 pub use rate_limit::RateLimitConfig;
 pub use rewards::RewardsConfig;
 pub use security::SecurityConfig;
 pub use services::ServicesConfig;
+pub use stealth::StealthConfig;
+pub use tracking::TrackingConfig;
 pub use types::*;
 
 #[cfg(test)]
@@ -63,6 +69,27 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_invalid_mixing_config() {
+        let mut config = NodeConfig::default();
+        config.services.mixnet_mixing.mode = MixingMode::Threshold;
+        config.services.mixnet_mixing.pool_size = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = NodeConfig::default();
+        config.services.mixnet_mixing.loop_cover_rate = -1.0;
+        assert!(config.validate().is_err());
+
+        let mut config = NodeConfig::default();
+        config.services.mixnet_mixing.drop_cover_rate = 1e-20;
+        assert!(config.validate().is_err());
+
+        let mut config = NodeConfig::default();
+        config.services.mixnet_mixing.loop_cover_rate = 0.0;
+        config.services.mixnet_mixing.drop_cover_rate = MIN_COVER_RATE;
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_invalid_multiaddr() {
         let mut config = NodeConfig::default();
@@ -78,6 +105,9 @@ mod tests {
         assert_eq!(NodeRole::Backbone.max_peers(), 500);
         assert!(!NodeRole::Local.serves_bootstrap());
         assert!(NodeRole::Relay.serves_bootstrap());
+        assert!(!NodeRole::Local.serves_relay());
+        assert!(NodeRole::Relay.serves_relay());
+        assert!(NodeRole::Backbone.serves_relay());
         assert!(NodeRole::Backbone.high_availability());
     }
 
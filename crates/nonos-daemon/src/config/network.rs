@@ -16,6 +16,11 @@ pub struct NetworkConfig {
     pub max_pending_dials: u32,
     pub ban_threshold: u8,
     pub ban_duration_secs: u64,
+    /// Listen and dial over QUIC alongside TCP.
+    pub enable_quic: bool,
+    /// UDP port for QUIC. Defaults to the node's P2P port.
+    pub quic_port: Option<u16>,
+    pub prefer_quic: bool,
 }
 
 impl Default for NetworkConfig {
@@ -32,6 +37,9 @@ impl Default for NetworkConfig {
             max_pending_dials: 16,
             ban_threshold: 80,
             ban_duration_secs: 3600,
+            enable_quic: true,
+            quic_port: None,
+            prefer_quic: true,
         }
     }
 }
@@ -39,6 +47,7 @@ impl Default for NetworkConfig {
 impl NetworkConfig {
     pub const OFFICIAL_BOOTSTRAP_NODES: &'static [&'static str] = &[
         // Netherlands - Primary bootstrap node
+        "/ip4/150.40.127.8/udp/9432/quic-v1/p2p/12D3KooWBjicitncMksUfrxrvuR6ZfmTHt3MrCmVxrMbpHV2YZoP",
         "/ip4/150.40.127.8/tcp/9432/p2p/12D3KooWBjicitncMksUfrxrvuR6ZfmTHt3MrCmVxrMbpHV2YZoP",
     ];
 
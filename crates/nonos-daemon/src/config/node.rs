@@ -8,11 +8,14 @@ use super::anyone::AnyoneNetworkConfig;
 use super::api::ApiConfig;
 use super::constants::DEFAULT_P2P_PORT;
 use super::logging::LoggingConfig;
+use super::mixnet::{MixingMode, MIN_COVER_RATE};
 use super::network::NetworkConfig;
 use super::rate_limit::RateLimitConfig;
 use super::rewards::RewardsConfig;
 use super::security::SecurityConfig;
 use super::services::ServicesConfig;
+use super::stealth::StealthConfig;
+use super::tracking::TrackingConfig;
 use super::types::{BootstrapMode, LogLevel, NodeRole, SecurityWarning, WarningSeverity};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -27,6 +30,8 @@ pub struct NodeConfig {
     pub network: NetworkConfig,
     pub anyone: AnyoneNetworkConfig,
     pub rewards: RewardsConfig,
+    pub stealth: StealthConfig,
+    pub tracking: TrackingConfig,
     pub security: SecurityConfig,
     pub logging: LoggingConfig,
     pub api: ApiConfig,
@@ -49,6 +54,8 @@ impl Default for NodeConfig {
             network: NetworkConfig::default(),
             anyone: AnyoneNetworkConfig::default(),
             rewards: RewardsConfig::default(),
+            stealth: StealthConfig::default(),
+            tracking: TrackingConfig::default(),
             security: SecurityConfig::default(),
             logging: LoggingConfig::default(),
             api: ApiConfig::default(),
@@ -229,6 +236,33 @@ impl NodeConfig {
             ));
         }
 
+        if self.stealth.enabled && self.stealth.batch_blocks == 0 {
+            return Err(NonosError::Config(
+                "Stealth scan batch size must be at least 1 block".into(),
+            ));
+        }
+
+        let mixing = &self.services.mixnet_mixing;
+        if mixing.mode == MixingMode::Threshold && mixing.pool_size == 0 {
+            return Err(NonosError::Config(
+                "Mixnet pool size must be at least 1 packet".into(),
+            ));
+        }
+
+        if mixing.key_rotation_secs == 0 {
+            return Err(NonosError::Config(
+                "Mixnet key rotation interval must be at least 1 second".into(),
+            ));
+        }
+
+        let valid_rate = |rate: f64| rate == 0.0 || (rate.is_finite() && rate >= MIN_COVER_RATE);
+        if !valid_rate(mixing.loop_cover_rate) || !valid_rate(mixing.drop_cover_rate) {
+            return Err(NonosError::Config(format!(
+                "Mixnet cover traffic rates must be 0 or at least {} packets per second",
+                MIN_COVER_RATE
+            )));
+        }
+
         if self.network.max_message_size < 1024 {
             return Err(NonosError::Config(
                 "Max message size must be at least 1024 bytes".into(),
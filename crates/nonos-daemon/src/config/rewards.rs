@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 pub struct RewardsConfig {
     pub contract: EthAddress,
     pub reward_address: EthAddress,
+    pub reward_wallet: Option<String>,
     pub auto_claim: bool,
     pub auto_claim_threshold: u64,
     pub rpc_url: Option<String>,
@@ -16,6 +17,7 @@ impl Default for RewardsConfig {
         Self {
             contract: EthAddress::zero(),
             reward_address: EthAddress::zero(),
+            reward_wallet: None,
             auto_claim: false,
             auto_claim_threshold: 100,
             rpc_url: None,
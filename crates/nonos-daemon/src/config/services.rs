@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use super::constants::DEFAULT_BOOTSTRAP_PORT;
+use super::mixnet::MixingConfig;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
@@ -13,6 +14,9 @@ pub struct ServicesConfig {
     pub cache: bool,
     pub cache_size_mb: u32,
     pub cache_max_age_secs: u64,
+    pub mixnet: bool,
+    pub mixnet_announce_interval_secs: u64,
+    pub mixnet_mixing: MixingConfig,
 }
 
 impl Default for ServicesConfig {
@@ -27,6 +31,9 @@ impl Default for ServicesConfig {
             cache: false,
             cache_size_mb: 1024,
             cache_max_age_secs: 86400,
+            mixnet: false,
+            mixnet_announce_interval_secs: 300,
+            mixnet_mixing: MixingConfig::default(),
         }
     }
 }
@@ -0,0 +1,37 @@
+use nonos_crypto::ERC5564_ANNOUNCER;
+use nonos_types::EthAddress;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StealthConfig {
+    pub enabled: bool,
+    /// ERC-5564 announcer contract whose `Announcement` logs are scanned.
+    pub announcer: EthAddress,
+    pub chain_id: u64,
+    /// Overrides the public endpoints for `chain_id`.
+    pub rpc_url: Option<String>,
+    /// First block to scan when no checkpoint exists; 0 starts at the
+    /// current head.
+    pub start_block: u64,
+    pub batch_blocks: u64,
+    /// Blocks to stay behind the head so reorged announcements are not
+    /// checkpointed.
+    pub confirmations: u64,
+    pub scan_interval_secs: u64,
+}
+
+impl Default for StealthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            announcer: EthAddress::from_hex(ERC5564_ANNOUNCER).unwrap_or_default(),
+            chain_id: 1,
+            rpc_url: None,
+            start_block: 0,
+            batch_blocks: 2_000,
+            confirmations: 12,
+            scan_interval_secs: 30,
+        }
+    }
+}
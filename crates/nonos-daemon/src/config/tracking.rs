@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackingConfig {
+    /// Filter lists in Adblock Plus / uBlock Origin syntax, such as
+    /// EasyList and EasyPrivacy, loaded at startup.
+    pub filter_lists: Vec<PathBuf>,
+    /// Keep the small built-in rule set alongside the loaded lists.
+    pub builtin_rules: bool,
+}
+
+impl Default for TrackingConfig {
+    fn default() -> Self {
+        Self {
+            filter_lists: Vec::new(),
+            builtin_rules: true,
+        }
+    }
+}
@@ -26,10 +26,18 @@ impl NodeRole {
         }
     }
 
+    /// Whether the node answers bootstrap requests from joining peers.
     pub fn serves_bootstrap(&self) -> bool {
         matches!(self, NodeRole::Relay | NodeRole::Backbone)
     }
 
+    /// Whether the node runs a circuit relay server for peers behind NAT.
+    /// The nodes peers join through are the ones expected to stay publicly
+    /// reachable, so this is the same set as [`NodeRole::serves_bootstrap`].
+    pub fn serves_relay(&self) -> bool {
+        self.serves_bootstrap()
+    }
+
     pub fn high_availability(&self) -> bool {
         matches!(self, NodeRole::Backbone)
     }
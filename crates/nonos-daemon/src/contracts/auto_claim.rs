@@ -1,6 +1,7 @@
 use super::client::ContractClient;
 use ethers::types::H256;
 use nonos_types::{EthAddress, NonosResult, TokenAmount};
+use nonos_wallet::FeeSpeed;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
@@ -54,7 +55,8 @@ impl AutoClaimManager {
 
             drop(client);
             let client = self.client.read().await;
-            let (tx_hash, _) = client.claim_rewards().await?;
+            // Claims are not time-sensitive, so pay the slow tier.
+            let (tx_hash, _) = client.claim_rewards_with_speed(FeeSpeed::Slow).await?;
 
             *self.last_claim.write().await = Some(chrono::Utc::now());
 
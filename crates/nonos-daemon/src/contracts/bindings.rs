@@ -33,3 +33,12 @@ abigen!(
         event Approval(address indexed owner, address indexed spender, uint256 value)
     ]"#
 );
+
+abigen!(
+    ERC6538Registry,
+    r#"[
+        function registerKeys(uint256 schemeId, bytes stealthMetaAddress) external
+        function stealthMetaAddressOf(address registrant, uint256 schemeId) external view returns (bytes)
+        event StealthMetaAddressSet(address indexed registrant, uint256 indexed schemeId, bytes stealthMetaAddress)
+    ]"#
+);
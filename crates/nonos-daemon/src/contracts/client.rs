@@ -1,27 +1,38 @@
-use super::bindings::{NoxStaking, NoxToken};
+use super::bindings::{ERC6538Registry, NoxStaking, NoxToken};
 use super::config::ContractConfig;
 use ethers::{
+    contract::builders::ContractCall,
     middleware::SignerMiddleware,
     providers::{Http, Middleware, Provider},
     signers::{LocalWallet, Signer},
-    types::{Address, H256, U256},
+    types::{transaction::eip2718::TypedTransaction, Address, H256, U256},
+};
+use nonos_crypto::{
+    generate_erc5564_stealth_address, Erc5564StealthAddress, StealthMetaAddress,
+    ERC5564_SCHEME_ID, ERC6538_REGISTRY,
 };
 use nonos_types::{
     EthAddress, NodeId, NodeTier, NonosError, NonosResult,
     TokenAmount, NOX_DECIMALS,
 };
+use nonos_wallet::{FeeOracle, FeeSpeed};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::info;
+use tracing::{info, warn};
+
+type SignerClient = SignerMiddleware<Provider<Http>, LocalWallet>;
 
 pub struct ContractClient {
     provider_url: String,
     provider: Option<Arc<Provider<Http>>>,
-    signer: Option<Arc<SignerMiddleware<Provider<Http>, LocalWallet>>>,
+    signer: Option<Arc<SignerClient>>,
     staking_address: Address,
     token_address: Address,
+    stealth_registry: Address,
     connected: Arc<RwLock<bool>>,
     chain_id: u64,
+    fee_oracle: Arc<FeeOracle>,
+    fee_speed: FeeSpeed,
 }
 
 impl ContractClient {
@@ -35,9 +46,47 @@ impl ContractClient {
             signer: None,
             staking_address,
             token_address,
+            stealth_registry: ERC6538_REGISTRY.parse().expect("valid registry address"),
             connected: Arc::new(RwLock::new(false)),
             chain_id: config.chain_id,
+            fee_oracle: FeeOracle::shared(),
+            fee_speed: FeeSpeed::default(),
+        }
+    }
+
+    /// Overrides the canonical ERC-6538 registry, e.g. on a testnet fork.
+    pub fn set_stealth_registry(&mut self, registry: &EthAddress) {
+        self.stealth_registry = Address::from_slice(&registry.0);
+    }
+
+    /// Fee tier used for staking and claiming transactions.
+    pub fn set_fee_speed(&mut self, speed: FeeSpeed) {
+        self.fee_speed = speed;
+    }
+
+    /// Prices an EIP-1559 contract call from `eth_feeHistory`. If the
+    /// estimate fails, ethers' own fee filling is left in place.
+    async fn priced<D>(
+        &self,
+        mut call: ContractCall<SignerClient, D>,
+        speed: FeeSpeed,
+    ) -> ContractCall<SignerClient, D> {
+        let Some(provider) = self.provider.as_ref() else {
+            return call;
+        };
+
+        match self.fee_oracle.estimate(self.chain_id, provider.as_ref()).await {
+            Ok(estimate) => {
+                let tier = estimate.tier(speed);
+                if let TypedTransaction::Eip1559(tx) = &mut call.tx {
+                    tx.max_fee_per_gas = Some(U256::from(tier.max_fee_per_gas));
+                    tx.max_priority_fee_per_gas = Some(U256::from(tier.max_priority_fee_per_gas));
+                }
+            }
+            Err(e) => warn!("Fee estimation failed, using provider defaults: {}", e),
         }
+
+        call
     }
 
     pub async fn connect(&mut self) -> NonosResult<()> {
@@ -160,7 +209,7 @@ impl ContractClient {
         info!("Approving {} NOX for staking", amount.to_decimal());
 
         let token = NoxToken::new(self.token_address, signer.clone());
-        let call = token.approve(self.staking_address, U256::from(amount.raw));
+        let call = self.priced(token.approve(self.staking_address, U256::from(amount.raw)), self.fee_speed).await;
         let pending = call.send().await
             .map_err(|e| NonosError::Contract(format!("Failed to approve: {}", e)))?;
 
@@ -179,7 +228,7 @@ impl ContractClient {
         info!("Staking {} NOX", amount.to_decimal());
 
         let staking = NoxStaking::new(self.staking_address, signer.clone());
-        let call = staking.stake(U256::from(amount.raw));
+        let call = self.priced(staking.stake(U256::from(amount.raw)), self.fee_speed).await;
         let pending = call.send().await
             .map_err(|e| NonosError::Contract(format!("Failed to stake: {}", e)))?;
 
@@ -198,7 +247,7 @@ impl ContractClient {
         info!("Unstaking {} NOX", amount.to_decimal());
 
         let staking = NoxStaking::new(self.staking_address, signer.clone());
-        let call = staking.unstake(U256::from(amount.raw));
+        let call = self.priced(staking.unstake(U256::from(amount.raw)), self.fee_speed).await;
         let pending = call.send().await
             .map_err(|e| NonosError::Contract(format!("Failed to unstake: {}", e)))?;
 
@@ -211,13 +260,17 @@ impl ContractClient {
     }
 
     pub async fn claim_rewards(&self) -> NonosResult<(H256, TokenAmount)> {
+        self.claim_rewards_with_speed(self.fee_speed).await
+    }
+
+    pub async fn claim_rewards_with_speed(&self, speed: FeeSpeed) -> NonosResult<(H256, TokenAmount)> {
         let signer = self.signer.as_ref()
             .ok_or_else(|| NonosError::Wallet("No wallet configured".into()))?;
 
         info!("Claiming rewards");
 
         let staking = NoxStaking::new(self.staking_address, signer.clone());
-        let call = staking.claim_rewards();
+        let call = self.priced(staking.claim_rewards(), speed).await;
         let pending = call.send().await
             .map_err(|e| NonosError::Contract(format!("Failed to claim: {}", e)))?;
 
@@ -239,7 +292,7 @@ impl ContractClient {
 
         let tier_index = tier.to_index();
         let staking = NoxStaking::new(self.staking_address, signer.clone());
-        let call = staking.set_tier(tier_index);
+        let call = self.priced(staking.set_tier(tier_index), self.fee_speed).await;
         let pending = call.send().await
             .map_err(|e| NonosError::Contract(format!("Failed to set tier: {}", e)))?;
 
@@ -259,7 +312,7 @@ impl ContractClient {
 
         let node_bytes: [u8; 32] = node_id.0;
         let staking = NoxStaking::new(self.staking_address, signer.clone());
-        let call = staking.register_node(node_bytes);
+        let call = self.priced(staking.register_node(node_bytes), self.fee_speed).await;
         let pending = call.send().await
             .map_err(|e| NonosError::Contract(format!("Failed to register node: {}", e)))?;
 
@@ -302,4 +355,68 @@ impl ContractClient {
 
         Ok(TokenAmount::from_raw(stake.as_u128(), NOX_DECIMALS))
     }
+
+    /// Looks up the scheme-1 stealth meta-address `registrant` published in
+    /// the ERC-6538 registry; `None` if they never registered.
+    pub async fn get_stealth_meta_address(
+        &self,
+        registrant: &EthAddress,
+    ) -> NonosResult<Option<StealthMetaAddress>> {
+        let provider = self.provider.as_ref()
+            .ok_or_else(|| NonosError::Network("Not connected".into()))?;
+
+        let registry = ERC6538Registry::new(self.stealth_registry, provider.clone());
+        let registrant = Address::from_slice(&registrant.0);
+
+        let bytes = registry
+            .stealth_meta_address_of(registrant, U256::from(ERC5564_SCHEME_ID))
+            .call()
+            .await
+            .map_err(|e| NonosError::Contract(format!("Failed to get stealth meta-address: {}", e)))?;
+
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+
+        StealthMetaAddress::from_bytes(&bytes).map(Some)
+    }
+
+    /// Publishes `meta` for the configured wallet so senders can pay it by
+    /// address.
+    pub async fn register_stealth_keys(&self, meta: &StealthMetaAddress) -> NonosResult<H256> {
+        let signer = self.signer.as_ref()
+            .ok_or_else(|| NonosError::Wallet("No wallet configured".into()))?;
+
+        info!("Registering stealth meta-address: {}", meta.encode());
+
+        let registry = ERC6538Registry::new(self.stealth_registry, signer.clone());
+        let call = registry.register_keys(
+            U256::from(ERC5564_SCHEME_ID),
+            meta.to_bytes().to_vec().into(),
+        );
+        let call = self.priced(call, self.fee_speed).await;
+        let pending = call.send().await
+            .map_err(|e| NonosError::Contract(format!("Failed to register stealth keys: {}", e)))?;
+
+        let receipt = pending.await
+            .map_err(|e| NonosError::Contract(format!("Register keys transaction failed: {}", e)))?
+            .ok_or_else(|| NonosError::Contract("No receipt for register keys".into()))?;
+
+        info!("Stealth keys registered: {:?}", receipt.transaction_hash);
+        Ok(receipt.transaction_hash)
+    }
+
+    /// Generates a fresh ERC-5564 stealth address for a registered user.
+    pub async fn generate_stealth_address(
+        &self,
+        registrant: &EthAddress,
+    ) -> NonosResult<Erc5564StealthAddress> {
+        let meta = self.get_stealth_meta_address(registrant).await?
+            .ok_or_else(|| NonosError::Contract(format!(
+                "{} has no registered stealth meta-address",
+                registrant
+            )))?;
+
+        generate_erc5564_stealth_address(&meta)
+    }
 }
@@ -4,7 +4,7 @@ mod client;
 mod auto_claim;
 mod rpc;
 
-pub use bindings::{NoxStaking, NoxToken};
+pub use bindings::{ERC6538Registry, NoxStaking, NoxToken};
 pub use config::{
     ContractConfig,
     NOX_TOKEN_MAINNET, NOX_STAKING_VAULT, NOX_TOKEN_IMPLEMENTATION,
@@ -1,4 +1,6 @@
+use async_trait::async_trait;
 use nonos_types::{NonosError, NonosResult};
+use nonos_wallet::JsonRpcTransport;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -281,6 +283,50 @@ impl RpcProvider {
     }
 }
 
+/// Sends each request to the current endpoint, counting transport failures
+/// towards failover. JSON-RPC errors are returned as-is since the endpoint
+/// itself answered.
+#[async_trait]
+impl JsonRpcTransport for RpcProvider {
+    async fn request(&self, method: &str, params: serde_json::Value) -> NonosResult<serde_json::Value> {
+        let url = self.get_url().await?;
+        let client = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .map_err(|e| NonosError::Network(format!("Failed to build RPC client: {}", e)))?;
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1
+        });
+
+        let response = match client.post(&url).json(&request).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                self.report_failure().await;
+                return Err(NonosError::Network(format!("RPC request to {} failed: {}", url, e)));
+            }
+        };
+
+        let json: serde_json::Value = match response.json().await {
+            Ok(json) => json,
+            Err(e) => {
+                self.report_failure().await;
+                return Err(NonosError::Network(format!("Failed to parse RPC response: {}", e)));
+            }
+        };
+        self.report_success().await;
+
+        if let Some(error) = json.get("error") {
+            return Err(NonosError::Network(format!("RPC error: {}", error)));
+        }
+
+        Ok(json.get("result").cloned().unwrap_or(serde_json::Value::Null))
+    }
+}
+
 impl Clone for RpcProvider {
     fn clone(&self) -> Self {
         Self {
@@ -38,3 +38,19 @@ fn test_contract_client_creation() {
     let config = ContractConfig::default();
     let _client = ContractClient::new(config);
 }
+
+#[test]
+fn test_erc6538_calldata_matches_wallet_encoder() {
+    use ethers::abi::AbiEncode;
+    use ethers::types::U256;
+    use nonos_crypto::{StealthKeyPair, ERC5564_SCHEME_ID};
+    use nonos_wallet::Erc6538Encoder;
+
+    let meta = StealthKeyPair::generate().unwrap().meta_address();
+    let call = bindings::RegisterKeysCall {
+        scheme_id: U256::from(ERC5564_SCHEME_ID),
+        stealth_meta_address: meta.to_bytes().to_vec().into(),
+    };
+
+    assert_eq!(call.encode(), Erc6538Encoder::register_keys(ERC5564_SCHEME_ID, &meta));
+}
@@ -1,4 +1,6 @@
+use async_trait::async_trait;
 use nonos_types::{NonosError, NonosResult};
+use nonos_wallet::JsonRpcTransport;
 use reqwest::{Client, Proxy};
 use std::sync::Arc;
 use std::time::Duration;
@@ -170,6 +172,13 @@ impl RpcClient {
     }
 }
 
+#[async_trait]
+impl JsonRpcTransport for RpcClient {
+    async fn request(&self, method: &str, params: serde_json::Value) -> NonosResult<serde_json::Value> {
+        self.call(method, params).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,4 +194,36 @@ mod tests {
         let client = ProxiedHttpClient::new();
         assert!(!client.is_proxy_configured().await);
     }
+
+    #[tokio::test]
+    async fn test_rpc_client_drives_nonce_manager() {
+        use axum::{routing::post, Json, Router};
+        use nonos_types::EthAddress;
+        use nonos_wallet::NonceManager;
+
+        async fn mock_node(Json(request): Json<serde_json::Value>) -> Json<serde_json::Value> {
+            let result = match request["method"].as_str() {
+                Some("eth_getTransactionCount") if request["params"][1] == "pending" => serde_json::json!("0x2a"),
+                Some("eth_getTransactionCount") => serde_json::json!("0x29"),
+                _ => serde_json::Value::Null,
+            };
+            Json(serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, Router::new().route("/", post(mock_node))).await.unwrap();
+        });
+
+        let mut manager = NonceManager::new(RpcClient::new(ProxiedHttpClient::new(), url));
+        let address = EthAddress::from_bytes([1; 20]);
+
+        assert_eq!(manager.transaction_count(&address, "latest").await.unwrap(), 41);
+        assert_eq!(manager.reserve_nonce(&address).await.unwrap(), 42);
+        assert_eq!(manager.reserve_nonce(&address).await.unwrap(), 43);
+
+        let receipt = manager.transport().request("eth_getTransactionReceipt", serde_json::json!(["0x00"])).await;
+        assert!(receipt.unwrap().is_null());
+    }
 }
@@ -17,7 +17,7 @@ pub mod geo;
 pub mod http_client;
 
 pub use node::{Node, CheckResult, DiagnosticReport};
-pub use p2p::{P2pNetwork, PeerInfo, NetworkStats, NetworkEvent, P2pMessage, topics};
+pub use p2p::{P2pNetwork, PeerInfo, NetworkStats, NetworkEvent, P2pMessage, FilterListChunkData, topics};
 pub use metrics::{NodeMetricsCollector, PrometheusExporter};
 pub use rewards::RewardTracker;
 pub use config::{NodeConfig, ServicesConfig, NetworkConfig, RewardsConfig, ApiConfig, AnyoneNetworkConfig, SecurityLevel};
@@ -27,13 +27,15 @@ pub use storage::{NodeStorage, StorageConfig};
 pub use services::{ServiceManager, ServiceConfig, ServiceType, ServiceState};
 pub use privacy::{
     PrivacyServiceManager, PrivacyStats, ZkIdentityService, CacheMixingService,
-    TrackingBlockerService, StealthScannerService,
+    TrackingBlockerService, StealthScannerService, FilterEngine, FilterRequest, RequestType,
+    FilterListSubscriptions, FilterListBundle, BundleBody, canonical_rules, rules_hash,
     AdvancedPrivacyManager, AdvancedPrivacyStats, ZkSessionManager, ZkSessionProof,
-    MixnetProcessor, OnionPacket, MixnetKeypair, MixNode, DecryptedLayer, PooledRequest,
-    PrivateContentRetrieval, CachedContent,
-    PrivacyOracle, DomainPrivacyScore, CookieBehavior, StealthSession, StealthSessionManager,
+    MixnetProcessor, SphinxPacket, MixnetKeypair, MixNode, ProcessedPacket, PooledRequest, MixnetStats,
+    PrivateContentRetrieval, CachedContent, PirDatabase, PirParams, PirRetrieval,
+    PrivacyOracle, DomainPrivacyScore, CookieBehavior, StakeLookup, VoteOutcome, VoterBinding,
+    StealthSession, StealthSessionManager,
     CredentialManager, CredentialType, CredentialProof, FingerprintNormalizer,
-    NormalizedRequest, DistributedCookieVault, SecretShare,
+    NormalizedRequest, DistributedCookieVault, SecretShare, HeldShare, ShareTransport,
     ZkCredentialSystem, ZkCredential, ZkCredentialType, ZkCredentialProof,
     ZkPublicInputs, MerkleProof, MERKLE_DEPTH,
 };
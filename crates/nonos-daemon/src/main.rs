@@ -3,7 +3,7 @@ mod cli;
 use clap::Parser;
 use cli::{
     Cli, Commands, init_logging, run_node, init_node,
-    handle_identity, handle_mixer, handle_stake, handle_rewards,
+    handle_identity, handle_mixer, handle_filters, handle_credentials, handle_stake, handle_rewards, handle_wallet,
     show_info, show_status, handle_config, run_checks, show_stats,
     handle_peers, generate_systemd, stop_node, restart_node, reload_node,
     show_version, launch_dashboard,
@@ -50,14 +50,23 @@ async fn main() -> NonosResult<()> {
         Commands::Mixer { action } => {
             handle_mixer(action, &data_dir, &cli.format).await?;
         }
+        Commands::Filters { action } => {
+            handle_filters(action, &cli.format).await?;
+        }
+        Commands::Credentials { action } => {
+            handle_credentials(action, &cli.format).await?;
+        }
         Commands::Dash { theme } => {
             launch_dashboard(&data_dir, &theme).await?;
         }
         Commands::Stake { action } => {
-            handle_stake(action, &cli.format).await?;
+            handle_stake(action, &config_path, &data_dir, &cli.format).await?;
+        }
+        Commands::Wallet { action } => {
+            handle_wallet(action, &config_path, &data_dir, &cli.format)?;
         }
         Commands::Rewards { action } => {
-            handle_rewards(action, &cli.format).await?;
+            handle_rewards(action, &config_path, &data_dir, &cli.format).await?;
         }
         Commands::Stats => {
             show_stats(&data_dir, &cli.format).await?;
@@ -1,3 +1,4 @@
+use crate::p2p::Reachability;
 use crate::{
     NodeConfig, NodeStorage, P2pNetwork, RewardTracker, NodeMetricsCollector,
     ServiceManager, ServiceConfig, PrivacyServiceManager, StorageConfig, ProxiedHttpClient,
@@ -11,13 +12,13 @@ use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
-use tracing::info;
+use tracing::{info, warn};
 
 #[cfg(unix)]
 use nix::libc;
 
 pub struct Node {
-    identity: NodeIdentity,
+    identity: Arc<NodeIdentity>,
     config: NodeConfig,
     status: Arc<RwLock<NodeStatus>>,
     network: Option<Arc<RwLock<P2pNetwork>>>,
@@ -36,7 +37,7 @@ impl Node {
         let identity = NodeIdentity::generate();
 
         Ok(Self {
-            identity,
+            identity: Arc::new(identity),
             config,
             status: Arc::new(RwLock::new(NodeStatus::Stopped)),
             network: None,
@@ -53,7 +54,7 @@ impl Node {
 
     pub fn with_identity(config: NodeConfig, identity: NodeIdentity) -> Self {
         Self {
-            identity,
+            identity: Arc::new(identity),
             config,
             status: Arc::new(RwLock::new(NodeStatus::Stopped)),
             network: None,
@@ -119,12 +120,54 @@ impl Node {
             keypair
         };
 
-        let mut network = P2pNetwork::with_keypair(keypair, self.config.port, self.config.max_connections);
+        let network_config = crate::p2p::NetworkConfig {
+            port: self.config.port,
+            max_connections: self.config.max_connections,
+            enable_quic: self.config.network.enable_quic,
+            quic_port: self.config.network.quic_port,
+            prefer_quic: self.config.network.prefer_quic,
+            ..Default::default()
+        };
+        let mut network = P2pNetwork::with_keypair_and_config(keypair, network_config);
+        network.set_identity(self.identity.clone());
+        network.set_node_role(self.config.role);
+        if let Some(storage) = &self.storage {
+            network.set_storage(storage.clone());
+        }
         network.start().await?;
         let network_arc = Arc::new(RwLock::new(network));
         self.network = Some(network_arc.clone());
 
-        let privacy = PrivacyServiceManager::new(self.id());
+        let storage = self.storage.as_ref()
+            .ok_or_else(|| NonosError::Internal("Storage not initialized".into()))?
+            .clone();
+        let privacy = PrivacyServiceManager::with_storage(self.id(), storage, &self.config.stealth)?;
+        let spend_vk_path = data_dir.join("zk-keys").join("spend.vk.bin");
+        if spend_vk_path.exists() {
+            let vk_bytes = std::fs::read(&spend_vk_path)
+                .map_err(|e| NonosError::Storage(format!("Failed to read spend verifying key: {}", e)))?;
+            privacy.note_mixer.load_verifying_key(&vk_bytes).await?;
+        }
+        let credential_pk_path = data_dir.join("zk-keys").join("credential.pk.bin");
+        if credential_pk_path.exists() {
+            let pk_bytes = std::fs::read(&credential_pk_path)
+                .map_err(|e| NonosError::Storage(format!("Failed to read credential proving key: {}", e)))?;
+            privacy.credentials.load_range_proving_key(&pk_bytes)?;
+        }
+        let credential_vk_path = data_dir.join("zk-keys").join("credential.vk.bin");
+        if credential_vk_path.exists() {
+            let vk_bytes = std::fs::read(&credential_vk_path)
+                .map_err(|e| NonosError::Storage(format!("Failed to read credential verifying key: {}", e)))?;
+            privacy.credentials.load_range_verifying_key(&vk_bytes)?;
+        }
+        if !self.config.tracking.builtin_rules {
+            privacy.tracking_blocker.remove_filter_list(crate::privacy::BUILTIN_LIST).await;
+        }
+        for path in &self.config.tracking.filter_lists {
+            if let Err(e) = privacy.tracking_blocker.load_filter_list(path).await {
+                warn!("Skipping filter list: {}", e);
+            }
+        }
         privacy.start_all().await?;
         self.privacy = Some(Arc::new(privacy));
 
@@ -153,18 +196,30 @@ impl Node {
             cache: self.config.services.cache,
             bootstrap_port: 9735,
             cache_size_mb: self.config.services.cache_size_mb,
+            mixnet: self.config.services.mixnet,
+            mixnet_announce_interval_secs: self.config.services.mixnet_announce_interval_secs,
+            mixnet_mixing: self.config.services.mixnet_mixing.clone(),
             beacon_interval_secs: 60,
-            quality_interval_secs: 300,
+            quality_interval_secs: self.config.services.quality_oracle_interval_secs,
         };
 
         let mut manager = ServiceManager::new();
         manager.start_all(
             self.id(),
-            network,
+            network.clone(),
             self.metrics.clone(),
             storage,
             service_config,
         ).await?;
+        if self.config.services.quality_oracle {
+            manager.start_epoch_settlement(self.id(), self.rewards.clone()).await;
+        }
+        if let Some(privacy) = &self.privacy {
+            manager.start_filter_list_sync(network.clone(), privacy.filter_lists.clone()).await;
+            manager.start_oracle_vote_sync(network.clone(), privacy.privacy_oracle.clone()).await;
+            manager.start_cookie_vault_maintenance(network.clone(), privacy.cookie_vault.clone()).await;
+            manager.start_pir_server(network, privacy.content.clone()).await;
+        }
 
         self.services = Some(Arc::new(RwLock::new(manager)));
 
@@ -325,6 +380,7 @@ impl Node {
 
         report.add_check("Port reachability", self.check_port_reachable().await);
         report.add_check("Peer connectivity", self.check_peer_connectivity().await);
+        report.add_check("NAT reachability", self.check_nat_reachability().await);
         report.add_check("Disk space", self.check_disk_space());
         report.add_check("Memory usage", self.check_memory_usage());
         report.add_check("Clock sync", self.check_time_sync().await);
@@ -378,6 +434,26 @@ impl Node {
         }
     }
 
+    async fn check_nat_reachability(&self) -> CheckResult {
+        let Some(ref network) = self.network else {
+            return CheckResult::Fail("Network not initialized".into());
+        };
+
+        let stats = network.read().await.stats();
+        match stats.reachability {
+            Reachability::Public => CheckResult::Pass(format!(
+                "Publicly reachable at {}",
+                stats.public_address.as_deref().unwrap_or("unknown address")
+            )),
+            Reachability::Private if stats.relay_reservations > 0 => CheckResult::Warn(format!(
+                "Behind NAT, reachable via {} relay(s)",
+                stats.relay_reservations
+            )),
+            Reachability::Private => CheckResult::Fail("Behind NAT with no relay reservation".into()),
+            Reachability::Unknown => CheckResult::Warn("Reachability not yet determined".into()),
+        }
+    }
+
     fn check_disk_space(&self) -> CheckResult {
         let data_dir = &self.config.data_dir;
 
@@ -1,15 +1,30 @@
+use super::mixnet::{MixnetAck, MixnetCodec};
+use super::pir::{PirCodec, PirRequest, PirResponse};
+use super::probe::{ProbeCodec, ProbeNonce};
+use super::record_store::PersistentRecordStore;
+use super::vault::{VaultCodec, VaultRequest, VaultResponse};
+use crate::privacy::SphinxPacket;
 use libp2p::{
-    gossipsub, identify, kad, ping,
-    swarm::NetworkBehaviour,
+    autonat, dcutr, gossipsub, identify, kad, ping, relay, request_response,
+    swarm::{behaviour::toggle::Toggle, NetworkBehaviour},
 };
 
 #[derive(NetworkBehaviour)]
 #[behaviour(to_swarm = "NonosBehaviourEvent")]
 pub struct NonosBehaviour {
-    pub kademlia: kad::Behaviour<kad::store::MemoryStore>,
+    pub kademlia: kad::Behaviour<PersistentRecordStore>,
     pub gossipsub: gossipsub::Behaviour,
     pub identify: identify::Behaviour,
     pub ping: ping::Behaviour,
+    pub mixnet: request_response::Behaviour<MixnetCodec>,
+    pub autonat: autonat::Behaviour,
+    pub relay_client: relay::client::Behaviour,
+    /// Circuit relay server, only enabled for roles that opt into relaying.
+    pub relay: Toggle<relay::Behaviour>,
+    pub dcutr: dcutr::Behaviour,
+    pub probe: request_response::Behaviour<ProbeCodec>,
+    pub pir: request_response::Behaviour<PirCodec>,
+    pub vault: request_response::Behaviour<VaultCodec>,
 }
 
 #[derive(Debug)]
@@ -18,6 +33,14 @@ pub enum NonosBehaviourEvent {
     Gossipsub(gossipsub::Event),
     Identify(identify::Event),
     Ping(ping::Event),
+    Mixnet(request_response::Event<SphinxPacket, MixnetAck>),
+    Autonat(autonat::Event),
+    RelayClient(relay::client::Event),
+    Relay(relay::Event),
+    Dcutr(dcutr::Event),
+    Probe(request_response::Event<ProbeNonce, ProbeNonce>),
+    Pir(request_response::Event<PirRequest, PirResponse>),
+    Vault(request_response::Event<VaultRequest, VaultResponse>),
 }
 
 impl From<kad::Event> for NonosBehaviourEvent {
@@ -43,3 +66,51 @@ impl From<ping::Event> for NonosBehaviourEvent {
         NonosBehaviourEvent::Ping(event)
     }
 }
+
+impl From<request_response::Event<SphinxPacket, MixnetAck>> for NonosBehaviourEvent {
+    fn from(event: request_response::Event<SphinxPacket, MixnetAck>) -> Self {
+        NonosBehaviourEvent::Mixnet(event)
+    }
+}
+
+impl From<autonat::Event> for NonosBehaviourEvent {
+    fn from(event: autonat::Event) -> Self {
+        NonosBehaviourEvent::Autonat(event)
+    }
+}
+
+impl From<relay::client::Event> for NonosBehaviourEvent {
+    fn from(event: relay::client::Event) -> Self {
+        NonosBehaviourEvent::RelayClient(event)
+    }
+}
+
+impl From<relay::Event> for NonosBehaviourEvent {
+    fn from(event: relay::Event) -> Self {
+        NonosBehaviourEvent::Relay(event)
+    }
+}
+
+impl From<dcutr::Event> for NonosBehaviourEvent {
+    fn from(event: dcutr::Event) -> Self {
+        NonosBehaviourEvent::Dcutr(event)
+    }
+}
+
+impl From<request_response::Event<ProbeNonce, ProbeNonce>> for NonosBehaviourEvent {
+    fn from(event: request_response::Event<ProbeNonce, ProbeNonce>) -> Self {
+        NonosBehaviourEvent::Probe(event)
+    }
+}
+
+impl From<request_response::Event<PirRequest, PirResponse>> for NonosBehaviourEvent {
+    fn from(event: request_response::Event<PirRequest, PirResponse>) -> Self {
+        NonosBehaviourEvent::Pir(event)
+    }
+}
+
+impl From<request_response::Event<VaultRequest, VaultResponse>> for NonosBehaviourEvent {
+    fn from(event: request_response::Event<VaultRequest, VaultResponse>) -> Self {
+        NonosBehaviourEvent::Vault(event)
+    }
+}
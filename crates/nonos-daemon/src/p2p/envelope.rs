@@ -0,0 +1,301 @@
+//! Signed wire envelope for gossiped [`P2pMessage`]s.
+//!
+//! Layout (integers big-endian):
+//!
+//! ```text
+//! version (1) | type (1) | timestamp ms (8) | signer key (32) | sender length (1) | sender peer ID | payload | signature (64)
+//! ```
+//!
+//! The Ed25519 signature covers everything before it, prefixed with a
+//! domain separator. The signer's `NodeId` is the BLAKE3 hash of its key,
+//! as for [`NodeIdentity::node_id`], and must match any node ID the payload
+//! claims. The sender is the libp2p peer the signer publishes from and must
+//! match the gossip source, which gossipsub authenticates, so a node ID is
+//! only ever bound to a peer that holds its key.
+
+use super::messages::{MessageType, P2pMessage};
+use super::peer_store::PenaltyReason;
+use nonos_crypto::{blake3_hash, ed25519_verify, NodeIdentity};
+use libp2p::PeerId;
+use nonos_types::{Ed25519PublicKey, NodeId, NonosError, NonosResult, ED25519_SIGNATURE_SIZE};
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+pub const ENVELOPE_VERSION: u8 = 2;
+
+/// How far in the past an envelope's timestamp may be.
+pub const REPLAY_WINDOW_MS: i64 = 5 * 60 * 1000;
+
+/// How far in the future an envelope's timestamp may be.
+pub const MAX_CLOCK_SKEW_MS: i64 = 30 * 1000;
+
+const SIGNING_DOMAIN: &[u8] = b"nonos-p2p-envelope";
+const HEADER_SIZE: usize = 1 + 1 + 8 + 32 + 1;
+const MIN_ENVELOPE_SIZE: usize = HEADER_SIZE + ED25519_SIGNATURE_SIZE;
+
+/// Why an inbound envelope was rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvelopeError {
+    Malformed(String),
+    UnsupportedVersion(u8),
+    BadSignature,
+    SignerMismatch,
+    SenderMismatch,
+    Expired,
+    FromFuture,
+    Replayed,
+}
+
+impl EnvelopeError {
+    /// Penalty for the peer that authored the envelope.
+    pub fn penalty(&self) -> PenaltyReason {
+        match self {
+            EnvelopeError::Malformed(_) | EnvelopeError::UnsupportedVersion(_) => {
+                PenaltyReason::MalformedMessage
+            }
+            EnvelopeError::BadSignature | EnvelopeError::SignerMismatch | EnvelopeError::SenderMismatch => {
+                PenaltyReason::ProtocolViolation
+            }
+            EnvelopeError::Expired | EnvelopeError::FromFuture | EnvelopeError::Replayed => {
+                PenaltyReason::InvalidData
+            }
+        }
+    }
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::Malformed(reason) => write!(f, "malformed envelope: {}", reason),
+            EnvelopeError::UnsupportedVersion(version) => write!(f, "unsupported envelope version {}", version),
+            EnvelopeError::BadSignature => write!(f, "invalid envelope signature"),
+            EnvelopeError::SignerMismatch => write!(f, "payload node ID does not match signer"),
+            EnvelopeError::SenderMismatch => write!(f, "envelope sender does not match gossip source"),
+            EnvelopeError::Expired => write!(f, "envelope timestamp outside replay window"),
+            EnvelopeError::FromFuture => write!(f, "envelope timestamp in the future"),
+            EnvelopeError::Replayed => write!(f, "envelope already seen"),
+        }
+    }
+}
+
+impl From<EnvelopeError> for NonosError {
+    fn from(err: EnvelopeError) -> Self {
+        match err {
+            EnvelopeError::BadSignature => NonosError::InvalidSignature(err.to_string()),
+            _ => NonosError::Network(err.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SignedEnvelope {
+    pub version: u8,
+    pub message_type: MessageType,
+    pub timestamp_ms: i64,
+    pub signer: Ed25519PublicKey,
+    pub sender: PeerId,
+    pub payload: Vec<u8>,
+    pub signature: [u8; ED25519_SIGNATURE_SIZE],
+}
+
+impl SignedEnvelope {
+    /// Signs `message` for publication from the local peer `sender`.
+    pub fn seal(message: &P2pMessage, identity: &NodeIdentity, sender: PeerId) -> NonosResult<Self> {
+        Self::seal_at(message, identity, sender, chrono::Utc::now().timestamp_millis())
+    }
+
+    pub fn seal_at(
+        message: &P2pMessage,
+        identity: &NodeIdentity,
+        sender: PeerId,
+        timestamp_ms: i64,
+    ) -> NonosResult<Self> {
+        if let Some(claimed) = message.claimed_node_id() {
+            if claimed != identity.node_id() {
+                return Err(NonosError::Network(
+                    "Refusing to sign a message for another node".into(),
+                ));
+            }
+        }
+
+        let mut envelope = Self {
+            version: ENVELOPE_VERSION,
+            message_type: message.message_type(),
+            timestamp_ms,
+            signer: *identity.public_key(),
+            sender,
+            payload: message.encode()?,
+            signature: [0u8; ED25519_SIGNATURE_SIZE],
+        };
+        envelope.signature = identity.sign(&envelope.signed_bytes()).bytes;
+        Ok(envelope)
+    }
+
+    pub fn signer_id(&self) -> NodeId {
+        NodeId::from_bytes(blake3_hash(&self.signer.0).0)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MIN_ENVELOPE_SIZE + 64 + self.payload.len());
+        self.write_header(&mut bytes);
+        bytes.extend_from_slice(&self.payload);
+        bytes.extend_from_slice(&self.signature);
+        bytes
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, EnvelopeError> {
+        if data.len() < MIN_ENVELOPE_SIZE {
+            return Err(EnvelopeError::Malformed(format!("{} bytes is too short", data.len())));
+        }
+        if data[0] != ENVELOPE_VERSION {
+            return Err(EnvelopeError::UnsupportedVersion(data[0]));
+        }
+
+        let message_type = MessageType::try_from(data[1])
+            .map_err(|e| EnvelopeError::Malformed(e.to_string()))?;
+        let timestamp_ms = i64::from_be_bytes(data[2..10].try_into().expect("8 bytes"));
+        let signer = Ed25519PublicKey::from_bytes(data[10..42].try_into().expect("32 bytes"));
+
+        let signature_start = data.len() - ED25519_SIGNATURE_SIZE;
+        let payload_start = HEADER_SIZE + data[42] as usize;
+        if payload_start > signature_start {
+            return Err(EnvelopeError::Malformed("sender overruns envelope".into()));
+        }
+        let sender = PeerId::from_bytes(&data[HEADER_SIZE..payload_start])
+            .map_err(|e| EnvelopeError::Malformed(format!("invalid sender: {}", e)))?;
+        let signature = data[signature_start..].try_into().expect("64 bytes");
+
+        Ok(Self {
+            version: data[0],
+            message_type,
+            timestamp_ms,
+            signer,
+            sender,
+            payload: data[payload_start..signature_start].to_vec(),
+            signature,
+        })
+    }
+
+    /// Checks the signature and returns the payload, which is guaranteed to
+    /// be of the declared type and, where it names a node, from the signer.
+    pub fn open(&self) -> Result<P2pMessage, EnvelopeError> {
+        let valid = ed25519_verify(&self.signer, &self.signed_bytes(), &self.signature)
+            .map_err(|e| EnvelopeError::Malformed(e.to_string()))?;
+        if !valid {
+            return Err(EnvelopeError::BadSignature);
+        }
+
+        let message = P2pMessage::decode(&self.payload)
+            .map_err(|e| EnvelopeError::Malformed(e.to_string()))?;
+        if message.message_type() != self.message_type {
+            return Err(EnvelopeError::Malformed("payload does not match message type".into()));
+        }
+        if message.claimed_node_id().is_some_and(|claimed| claimed != self.signer_id()) {
+            return Err(EnvelopeError::SignerMismatch);
+        }
+        // Anyone may relay a chunk, but only its list's maintainer may
+        // publish one.
+        if let P2pMessage::FilterListChunk(chunk) = &message {
+            if chunk.verify().is_err() {
+                return Err(EnvelopeError::BadSignature);
+            }
+        }
+
+        Ok(message)
+    }
+
+    fn write_header(&self, out: &mut Vec<u8>) {
+        out.push(self.version);
+        out.push(self.message_type as u8);
+        out.extend_from_slice(&self.timestamp_ms.to_be_bytes());
+        out.extend_from_slice(&self.signer.0);
+        let sender = self.sender.to_bytes();
+        out.push(sender.len() as u8);
+        out.extend_from_slice(&sender);
+    }
+
+    fn signed_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SIGNING_DOMAIN.len() + HEADER_SIZE + 64 + self.payload.len());
+        bytes.extend_from_slice(SIGNING_DOMAIN);
+        self.write_header(&mut bytes);
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+}
+
+/// Rejects envelopes outside the timestamp window and signatures already
+/// seen within it. Entries are kept just long enough that a replay would
+/// fail the timestamp check once they are forgotten.
+pub struct ReplayGuard {
+    seen: HashSet<[u8; 32]>,
+    order: VecDeque<([u8; 32], i64)>,
+}
+
+impl ReplayGuard {
+    pub fn new() -> Self {
+        Self {
+            seen: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn check(&mut self, envelope: &SignedEnvelope, now_ms: i64) -> Result<(), EnvelopeError> {
+        self.prune(now_ms);
+
+        if envelope.timestamp_ms < now_ms - REPLAY_WINDOW_MS {
+            return Err(EnvelopeError::Expired);
+        }
+        if envelope.timestamp_ms > now_ms + MAX_CLOCK_SKEW_MS {
+            return Err(EnvelopeError::FromFuture);
+        }
+
+        let key = blake3_hash(&envelope.signature).0;
+        if !self.seen.insert(key) {
+            return Err(EnvelopeError::Replayed);
+        }
+        self.order.push_back((key, now_ms));
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    fn prune(&mut self, now_ms: i64) {
+        let retention = REPLAY_WINDOW_MS + MAX_CLOCK_SKEW_MS;
+        while let Some(&(key, seen_at)) = self.order.front() {
+            if now_ms - seen_at <= retention {
+                break;
+            }
+            self.order.pop_front();
+            self.seen.remove(&key);
+        }
+    }
+}
+
+impl Default for ReplayGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parses, authenticates and replay-checks a gossiped message published
+/// by `source`.
+pub fn verify_envelope(
+    data: &[u8],
+    source: &PeerId,
+    guard: &mut ReplayGuard,
+    now_ms: i64,
+) -> Result<(NodeId, P2pMessage), EnvelopeError> {
+    let envelope = SignedEnvelope::from_bytes(data)?;
+    let message = envelope.open()?;
+    if envelope.sender != *source {
+        return Err(EnvelopeError::SenderMismatch);
+    }
+    guard.check(&envelope, now_ms)?;
+    Ok((envelope.signer_id(), message))
+}
@@ -1,4 +1,5 @@
-use nonos_types::NodeId;
+use crate::privacy::{CookieBehavior, VoterBinding};
+use nonos_types::{Ed25519PublicKey, NodeId, NonosError, NonosResult};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -8,15 +9,82 @@ pub enum P2pMessage {
     BootstrapRequest,
     BootstrapResponse(Vec<String>),
     NodeAnnouncement(NodeAnnouncementData),
+    LatencyAttestation(LatencyAttestationData),
+    FilterListChunk(FilterListChunkData),
+    OracleVote(Box<OracleVoteData>),
+}
+
+/// Wire tag of a [`P2pMessage`], carried in the envelope header so a
+/// payload cannot be reinterpreted as a different message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MessageType {
+    HealthBeacon = 1,
+    QualityReport = 2,
+    BootstrapRequest = 3,
+    BootstrapResponse = 4,
+    NodeAnnouncement = 5,
+    LatencyAttestation = 6,
+    FilterListChunk = 7,
+    OracleVote = 8,
+}
+
+impl TryFrom<u8> for MessageType {
+    type Error = NonosError;
+
+    fn try_from(value: u8) -> NonosResult<Self> {
+        match value {
+            1 => Ok(MessageType::HealthBeacon),
+            2 => Ok(MessageType::QualityReport),
+            3 => Ok(MessageType::BootstrapRequest),
+            4 => Ok(MessageType::BootstrapResponse),
+            5 => Ok(MessageType::NodeAnnouncement),
+            6 => Ok(MessageType::LatencyAttestation),
+            7 => Ok(MessageType::FilterListChunk),
+            8 => Ok(MessageType::OracleVote),
+            other => Err(NonosError::Serialization(format!("Unknown message type: {}", other))),
+        }
+    }
 }
 
 impl P2pMessage {
-    pub fn encode(&self) -> Vec<u8> {
-        serde_json::to_vec(self).unwrap_or_default()
+    pub fn message_type(&self) -> MessageType {
+        match self {
+            P2pMessage::HealthBeacon(_) => MessageType::HealthBeacon,
+            P2pMessage::QualityReport(_) => MessageType::QualityReport,
+            P2pMessage::BootstrapRequest => MessageType::BootstrapRequest,
+            P2pMessage::BootstrapResponse(_) => MessageType::BootstrapResponse,
+            P2pMessage::NodeAnnouncement(_) => MessageType::NodeAnnouncement,
+            P2pMessage::LatencyAttestation(_) => MessageType::LatencyAttestation,
+            P2pMessage::FilterListChunk(_) => MessageType::FilterListChunk,
+            P2pMessage::OracleVote(_) => MessageType::OracleVote,
+        }
     }
 
-    pub fn decode(data: &[u8]) -> Option<Self> {
-        serde_json::from_slice(data).ok()
+    /// The node the message claims to speak for, which must be the signer.
+    pub fn claimed_node_id(&self) -> Option<NodeId> {
+        match self {
+            P2pMessage::HealthBeacon(data) => Some(data.node_id),
+            P2pMessage::QualityReport(data) => Some(data.node_id),
+            P2pMessage::NodeAnnouncement(data) => Some(data.node_id),
+            P2pMessage::LatencyAttestation(data) => Some(data.node_id),
+            P2pMessage::OracleVote(data) => Some(data.voter),
+            P2pMessage::BootstrapRequest
+            | P2pMessage::BootstrapResponse(_)
+            | P2pMessage::FilterListChunk(_) => None,
+        }
+    }
+
+    /// Binary payload encoding; see [`super::SignedEnvelope`] for the wire
+    /// format that wraps it.
+    pub fn encode(&self) -> NonosResult<Vec<u8>> {
+        bincode::serialize(self)
+            .map_err(|e| NonosError::Serialization(format!("Failed to encode message: {}", e)))
+    }
+
+    pub fn decode(data: &[u8]) -> NonosResult<Self> {
+        bincode::deserialize(data)
+            .map_err(|e| NonosError::Serialization(format!("Failed to decode message: {}", e)))
     }
 }
 
@@ -48,4 +116,59 @@ pub struct NodeAnnouncementData {
     pub staked_amount: String,
     pub services: Vec<String>,
     pub addresses: Vec<String>,
+    /// X25519 key for Sphinx packets, if the node runs a mix relay.
+    pub mix_public_key: Option<[u8; 32]>,
+}
+
+/// Results of probes one node ran against others, signed by the prober
+/// through the envelope.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LatencyAttestationData {
+    /// The prober.
+    pub node_id: NodeId,
+    pub epoch: u64,
+    pub probes: Vec<ProbeResult>,
+    /// Ties the prober to the stake its results are weighted by; results
+    /// without one are not counted.
+    pub binding: Option<VoterBinding>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProbeResult {
+    pub target: NodeId,
+    /// Round-trip time, or `None` if the target did not answer.
+    pub rtt_ms: Option<u32>,
+}
+
+/// One piece of a signed filter-list bundle. Any node may relay it, but
+/// only chunks whose header the list's maintainer signed are accepted, so
+/// no one else can occupy the slots bundles are reassembled in.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FilterListChunkData {
+    pub name: String,
+    pub maintainer: Ed25519PublicKey,
+    pub version: u64,
+    /// BLAKE3 hash of the whole encoded bundle.
+    pub bundle_id: [u8; 32],
+    pub index: u16,
+    pub total: u16,
+    /// The maintainer's signature over the header and the hash of `data`.
+    pub signature: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+/// A node's privacy score for a domain, counted once per staker, domain and
+/// epoch and weighted by the stake of the staker that `binding` ties the
+/// voter to.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OracleVoteData {
+    /// The node casting the vote, which must sign the envelope.
+    pub voter: NodeId,
+    pub binding: VoterBinding,
+    pub epoch: u64,
+    pub domain: String,
+    pub score: u8,
+    pub trackers_detected: Vec<String>,
+    pub fingerprinting: Vec<String>,
+    pub cookie_behavior: CookieBehavior,
 }
@@ -0,0 +1,117 @@
+use super::types::NetworkCommand;
+use crate::privacy::{MixNode, SphinxPacket, SPHINX_PACKET_SIZE};
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use libp2p::{request_response, PeerId, StreamProtocol};
+use nonos_types::{NodeId, NonosError, NonosResult};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+pub const MIXNET_PROTOCOL: StreamProtocol = StreamProtocol::new("/nonos/mixnet/1.0.0");
+
+const ACK_BYTE: u8 = 0x01;
+
+/// Acknowledges that a packet was received; says nothing about whether it
+/// was valid, so the sender learns nothing about the rest of the path.
+#[derive(Clone, Copy, Debug)]
+pub struct MixnetAck;
+
+/// Carries exactly one [`SPHINX_PACKET_SIZE`] packet per stream.
+#[derive(Clone, Default)]
+pub struct MixnetCodec;
+
+#[async_trait]
+impl request_response::Codec for MixnetCodec {
+    type Protocol = StreamProtocol;
+    type Request = SphinxPacket;
+    type Response = MixnetAck;
+
+    async fn read_request<T>(&mut self, _: &StreamProtocol, io: &mut T) -> io::Result<SphinxPacket>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let mut bytes = vec![0u8; SPHINX_PACKET_SIZE];
+        io.read_exact(&mut bytes).await?;
+        SphinxPacket::from_bytes(&bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    async fn read_response<T>(&mut self, _: &StreamProtocol, io: &mut T) -> io::Result<MixnetAck>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let mut byte = [0u8; 1];
+        io.read_exact(&mut byte).await?;
+        if byte[0] != ACK_BYTE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid mixnet ack"));
+        }
+        Ok(MixnetAck)
+    }
+
+    async fn write_request<T>(&mut self, _: &StreamProtocol, io: &mut T, packet: SphinxPacket) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        io.write_all(&packet.to_bytes()).await?;
+        io.close().await
+    }
+
+    async fn write_response<T>(&mut self, _: &StreamProtocol, io: &mut T, _: MixnetAck) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        io.write_all(&[ACK_BYTE]).await?;
+        io.close().await
+    }
+}
+
+/// How long a mix node is routed through after its last announcement:
+/// three missed announcements at the default interval.
+pub const MIX_ROUTE_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// An announced mix node and the peer that hosts it.
+#[derive(Clone, Debug)]
+pub struct MixRoute {
+    pub peer_id: PeerId,
+    pub node: MixNode,
+    pub last_seen: Instant,
+}
+
+pub type MixRoutes = Arc<RwLock<HashMap<NodeId, MixRoute>>>;
+
+/// Forgets mix nodes not announced within `ttl`, returning how many.
+pub(crate) fn prune_mix_routes(routes: &MixRoutes, ttl: Duration) -> usize {
+    let mut routes = routes.write();
+    let before = routes.len();
+    routes.retain(|_, route| route.last_seen.elapsed() < ttl);
+    before - routes.len()
+}
+
+/// Hands packets for a `NodeId` to the swarm. Synchronous, so it can be
+/// used from `MixnetProcessor` callbacks.
+#[derive(Clone)]
+pub struct MixPacketSender {
+    command_tx: mpsc::Sender<NetworkCommand>,
+    routes: MixRoutes,
+}
+
+impl MixPacketSender {
+    pub(crate) fn new(command_tx: mpsc::Sender<NetworkCommand>, routes: MixRoutes) -> Self {
+        Self { command_tx, routes }
+    }
+
+    pub fn send(&self, next_hop: &NodeId, packet: SphinxPacket) -> NonosResult<()> {
+        let peer = self.routes.read()
+            .get(next_hop)
+            .map(|route| route.peer_id)
+            .ok_or_else(|| NonosError::Network(format!("No route to mix node {}", next_hop)))?;
+
+        self.command_tx
+            .try_send(NetworkCommand::SendMixPacket { peer, packet })
+            .map_err(|e| NonosError::Network(format!("Failed to queue mix packet: {}", e)))
+    }
+}
@@ -1,13 +1,44 @@
 mod behaviour;
+mod envelope;
 mod messages;
+mod mixnet;
+mod nat;
+mod pir;
+mod probe;
 mod network;
 mod peer_store;
+mod record_store;
+mod scoring;
 mod swarm;
 mod types;
+mod vault;
 
 pub use behaviour::{NonosBehaviour, NonosBehaviourEvent};
-pub use messages::{HealthBeaconData, NodeAnnouncementData, P2pMessage, QualityReportData};
-pub use network::{NetworkConfig, P2pNetwork};
+pub use envelope::{
+    verify_envelope, EnvelopeError, ReplayGuard, SignedEnvelope, ENVELOPE_VERSION,
+    MAX_CLOCK_SKEW_MS, REPLAY_WINDOW_MS,
+};
+pub use messages::{
+    FilterListChunkData, HealthBeaconData, LatencyAttestationData, MessageType, NodeAnnouncementData, OracleVoteData,
+    P2pMessage, ProbeResult, QualityReportData,
+};
+pub use mixnet::{MixPacketSender, MixRoute, MixnetAck, MixnetCodec, MIXNET_PROTOCOL};
+pub use nat::{circuit_address, MAX_RELAY_RESERVATIONS};
+pub use pir::{
+    PirClient, PirCodec, PirRequest, PirResponse, ServedPirDatabase, PIR_PROTOCOL, PIR_REQUEST_TIMEOUT,
+};
+pub use vault::{
+    VaultClient, VaultCodec, VaultRequest, VaultResponse, VaultShareStore, VAULT_PROTOCOL, VAULT_REQUEST_TIMEOUT,
+};
+pub use probe::{NodePeers, NodeProber, ProbeNonce, PROBE_PROTOCOL, PROBE_TIMEOUT};
+pub use network::{
+    address_transport, quic_address_for, rank_addresses, AddressTransport, NetworkConfig, P2pNetwork,
+};
+pub use record_store::{PersistentRecordStore, RecordStoreConfig};
+pub use scoring::{
+    application_score, peer_score_params, peer_score_thresholds, protocol_score,
+    GOSSIP_THRESHOLD, GRAYLIST_THRESHOLD, PUBLISH_THRESHOLD,
+};
 pub use peer_store::{
     new_shared_peer_store, PeerEntry, PeerState, PeerStore, PeerStoreStats,
     PenaltyReason, SharedPeerStore, DEFAULT_BAN_DURATION, MAX_PENALTY_SCORE,
@@ -16,7 +47,7 @@ pub use peer_store::{
 pub use types::{
     BackoffStrategy, BanEntry, CircuitBreaker, CircuitState, ConnectionState,
     ConnectionTracker, NetworkCommand, NetworkEvent, NetworkStats, NetworkStatsSnapshot,
-    PeerInfo, RateLimitReason, RateLimiter,
+    PeerInfo, RateLimitReason, RateLimiter, Reachability,
 };
 
 pub mod topics {
@@ -25,6 +56,8 @@ pub mod topics {
     pub const PEER_DISCOVERY: &str = "nonos/peers";
     pub const NODE_ANNOUNCEMENTS: &str = "nonos/announcements";
     pub const PRIVACY_COORD: &str = "nonos/privacy";
+    pub const FILTER_LISTS: &str = "nonos/filter-lists";
+    pub const PRIVACY_ORACLE: &str = "nonos/privacy-oracle";
 }
 
 #[cfg(test)]
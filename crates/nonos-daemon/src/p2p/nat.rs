@@ -0,0 +1,118 @@
+//! Relay selection for nodes that AutoNAT has found to be unreachable.
+//!
+//! Peers that advertise the circuit relay v2 hop protocol in identify are
+//! remembered as candidates. While the node is private it listens on a
+//! `/p2p-circuit` address through up to [`MAX_RELAY_RESERVATIONS`] of them,
+//! which makes it dialable via the relay and lets DCUtR upgrade those
+//! relayed connections to direct ones.
+
+use libp2p::core::transport::ListenerId;
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
+use std::collections::{HashMap, HashSet};
+
+pub const MAX_RELAY_RESERVATIONS: usize = 2;
+
+pub(crate) struct RelayManager {
+    candidates: HashMap<PeerId, Multiaddr>,
+    listeners: HashMap<ListenerId, PeerId>,
+    accepted: HashSet<PeerId>,
+}
+
+impl RelayManager {
+    pub fn new() -> Self {
+        Self {
+            candidates: HashMap::new(),
+            listeners: HashMap::new(),
+            accepted: HashSet::new(),
+        }
+    }
+
+    /// Records a peer that offers relaying, reachable at one of `addrs`.
+    /// Returns false if none of the addresses is usable for a reservation.
+    pub fn add_candidate<'a>(&mut self, peer: PeerId, addrs: impl IntoIterator<Item = &'a Multiaddr>) -> bool {
+        match addrs.into_iter().find(|addr| is_relay_dialable(addr)) {
+            Some(addr) => {
+                self.candidates.insert(peer, addr.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_candidate(&mut self, peer: &PeerId) {
+        self.candidates.remove(peer);
+    }
+
+    /// Circuit addresses to listen on to bring the number of relays in
+    /// use up to [`MAX_RELAY_RESERVATIONS`].
+    pub fn wanted_circuits(&self) -> Vec<(PeerId, Multiaddr)> {
+        let in_use: HashSet<&PeerId> = self.listeners.values().collect();
+        let missing = MAX_RELAY_RESERVATIONS.saturating_sub(in_use.len());
+
+        self.candidates.iter()
+            .filter(|(peer, _)| !in_use.contains(peer))
+            .take(missing)
+            .map(|(peer, addr)| (*peer, circuit_address(addr, *peer)))
+            .collect()
+    }
+
+    pub fn listening(&mut self, listener: ListenerId, relay: PeerId) {
+        self.listeners.insert(listener, relay);
+    }
+
+    /// Marks the reservation on `relay` as accepted. Returns false for a
+    /// renewal of a reservation that was already counted.
+    pub fn accepted(&mut self, relay: PeerId) -> bool {
+        self.accepted.insert(relay)
+    }
+
+    /// Forgets a closed circuit listener, returning the relay it used.
+    pub fn listener_closed(&mut self, listener: ListenerId) -> Option<PeerId> {
+        let relay = self.listeners.remove(&listener)?;
+        if !self.listeners.values().any(|peer| *peer == relay) {
+            self.accepted.remove(&relay);
+        }
+        Some(relay)
+    }
+
+    /// Takes every circuit listener, for when the node no longer needs
+    /// relays.
+    pub fn drain_listeners(&mut self) -> Vec<ListenerId> {
+        self.accepted.clear();
+        self.listeners.drain().map(|(listener, _)| listener).collect()
+    }
+
+    pub fn reservation_count(&self) -> usize {
+        self.accepted.len()
+    }
+
+    pub fn candidate_count(&self) -> usize {
+        self.candidates.len()
+    }
+}
+
+impl Default for RelayManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `<relay addr>/p2p/<relay>/p2p-circuit`
+pub fn circuit_address(relay_addr: &Multiaddr, relay: PeerId) -> Multiaddr {
+    let mut addr: Multiaddr = relay_addr.iter()
+        .filter(|protocol| !matches!(protocol, Protocol::P2p(_)))
+        .collect();
+    addr.push(Protocol::P2p(relay));
+    addr.push(Protocol::P2pCircuit);
+    addr
+}
+
+fn is_relay_dialable(addr: &Multiaddr) -> bool {
+    addr.iter().all(|protocol| match protocol {
+        Protocol::Ip4(ip) => !ip.is_loopback() && !ip.is_unspecified(),
+        Protocol::Ip6(ip) => !ip.is_loopback() && !ip.is_unspecified(),
+        Protocol::P2pCircuit => false,
+        _ => true,
+    })
+}
@@ -1,3 +1,4 @@
+use crate::p2p::record_store::RecordStoreConfig;
 use std::time::Duration;
 use super::constants::{DEFAULT_MESSAGES_PER_SEC, DEFAULT_BYTES_PER_SEC, MAX_MESSAGE_SIZE, MAX_PEERS};
 
@@ -14,6 +15,19 @@ pub struct NetworkConfig {
     pub dial_timeout: Duration,
     pub bootstrap_on_start: bool,
     pub custom_bootstrap_nodes: Vec<String>,
+    pub record_store: RecordStoreConfig,
+    /// Listen and dial over QUIC in addition to TCP.
+    pub enable_quic: bool,
+    /// UDP port for QUIC. Defaults to the TCP port number.
+    pub quic_port: Option<u16>,
+    /// Try a peer's QUIC addresses before its TCP ones.
+    pub prefer_quic: bool,
+}
+
+impl NetworkConfig {
+    pub fn quic_port(&self) -> u16 {
+        self.quic_port.unwrap_or(self.port)
+    }
 }
 
 impl Default for NetworkConfig {
@@ -30,6 +44,10 @@ impl Default for NetworkConfig {
             dial_timeout: Duration::from_secs(10),
             bootstrap_on_start: true,
             custom_bootstrap_nodes: Vec::new(),
+            record_store: RecordStoreConfig::default(),
+            enable_quic: true,
+            quic_port: None,
+            prefer_quic: true,
         }
     }
 }
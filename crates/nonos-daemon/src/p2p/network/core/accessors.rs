@@ -3,7 +3,10 @@ use super::network::P2pNetwork;
 use crate::config::{BootstrapMode, NodeRole};
 use crate::p2p::peer_store::SharedPeerStore;
 use libp2p::PeerId;
+use crate::storage::NodeStorage;
+use nonos_crypto::NodeIdentity;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
 impl P2pNetwork {
     pub fn local_peer_id(&self) -> &PeerId {
@@ -26,6 +29,12 @@ impl P2pNetwork {
         self.node_role
     }
 
+    /// Sets the node's role. Takes effect on the next [`P2pNetwork::start`],
+    /// which enables the relay server for roles that serve one.
+    pub fn set_node_role(&mut self, role: NodeRole) {
+        self.node_role = role;
+    }
+
     pub fn bootstrap_mode(&self) -> &BootstrapMode {
         &self.bootstrap_mode
     }
@@ -33,4 +42,19 @@ impl P2pNetwork {
     pub fn peer_store(&self) -> &SharedPeerStore {
         &self.peer_store
     }
+
+    /// Sets the identity that signs outgoing [`crate::p2p::P2pMessage`]s.
+    pub fn set_identity(&mut self, identity: Arc<NodeIdentity>) {
+        self.identity = Some(identity);
+    }
+
+    pub fn identity(&self) -> Option<&Arc<NodeIdentity>> {
+        self.identity.as_ref()
+    }
+
+    /// Sets the storage that persists the DHT record store. Takes effect on
+    /// the next [`P2pNetwork::start`].
+    pub fn set_storage(&mut self, storage: Arc<NodeStorage>) {
+        self.storage = Some(storage);
+    }
 }
@@ -2,7 +2,9 @@ use super::super::config::{
     CIRCUIT_BREAKER_FAILURE_THRESHOLD, CIRCUIT_BREAKER_RESET_TIMEOUT,
     CIRCUIT_BREAKER_SUCCESS_THRESHOLD, MIN_PEERS,
 };
-use super::super::helpers::extract_peer_id;
+use super::super::helpers::{
+    address_transport, extract_peer_id, quic_address_for, rank_addresses, AddressTransport,
+};
 use super::network::P2pNetwork;
 use crate::p2p::types::{CircuitBreaker, NetworkCommand, PeerInfo};
 use libp2p::{Multiaddr, PeerId};
@@ -16,6 +18,10 @@ impl P2pNetwork {
             .parse()
             .map_err(|e| NonosError::Network(format!("Invalid multiaddress: {}", e)))?;
 
+        if !self.config.enable_quic && address_transport(&multiaddr) == AddressTransport::Quic {
+            return Err(NonosError::Network("QUIC transport is disabled".into()));
+        }
+
         if let Some(peer_id) = extract_peer_id(&multiaddr) {
             if self.is_banned(&peer_id) {
                 return Err(NonosError::Network(format!("Peer {} is banned", peer_id)));
@@ -47,8 +53,16 @@ impl P2pNetwork {
             .connection_attempts
             .fetch_add(1, Ordering::Relaxed);
 
+        let command = match extract_peer_id(&multiaddr) {
+            Some(peer) => NetworkCommand::Dial {
+                peer,
+                addresses: self.dial_addresses(&peer, multiaddr),
+            },
+            None => NetworkCommand::Connect(multiaddr),
+        };
+
         if let Some(tx) = &self.command_tx {
-            tx.send(NetworkCommand::Connect(multiaddr))
+            tx.send(command)
                 .await
                 .map_err(|e| NonosError::Network(format!("Failed to send connect command: {}", e)))?;
         }
@@ -56,6 +70,26 @@ impl P2pNetwork {
         Ok(format!("connecting_to_{}", address))
     }
 
+    /// Addresses to dial `peer` on, best first: the requested address, its
+    /// QUIC counterpart and any addresses already known for the peer,
+    /// ordered by the configured transport preference.
+    pub fn dial_addresses(&self, peer: &PeerId, requested: Multiaddr) -> Vec<Multiaddr> {
+        let mut candidates = Vec::new();
+        if self.config.enable_quic {
+            candidates.extend(quic_address_for(&requested));
+        }
+        candidates.push(requested);
+
+        if let Some(info) = self.peers.read().get(peer) {
+            candidates.extend(info.addresses.iter().filter_map(|addr| addr.parse().ok()));
+        }
+        if let Some(entry) = self.peer_store.get(peer) {
+            candidates.extend(entry.addresses.iter().filter_map(|addr| addr.parse().ok()));
+        }
+
+        rank_addresses(candidates, self.config.enable_quic, self.config.prefer_quic)
+    }
+
     pub async fn disconnect(&self, peer_id: &str) {
         if let Ok(peer) = peer_id.parse::<PeerId>() {
             if let Some(tx) = &self.command_tx {
@@ -44,6 +44,10 @@ impl P2pNetwork {
             dial_timeout: Duration::from_secs(node_config.network.dial_timeout_secs),
             bootstrap_on_start: node_config.network.bootstrap_mode != BootstrapMode::None,
             custom_bootstrap_nodes: node_config.network.custom_bootstrap_peers.clone(),
+            record_store: Default::default(),
+            enable_quic: node_config.network.enable_quic,
+            quic_port: node_config.network.quic_port,
+            prefer_quic: node_config.network.prefer_quic,
         };
 
         Self {
@@ -68,6 +72,15 @@ impl P2pNetwork {
                     .with_max_attempts(10),
             )),
             started_at: None,
+            mix_routes: Arc::new(RwLock::new(HashMap::new())),
+            mix_packet_rx: Arc::new(RwLock::new(None)),
+            node_peers: Arc::new(RwLock::new(HashMap::new())),
+            attestation_rx: Arc::new(RwLock::new(None)),
+            filter_list_rx: Arc::new(RwLock::new(None)),
+            oracle_vote_rx: Arc::new(RwLock::new(None)),
+            pir_database: Arc::new(RwLock::new(None)),
+            identity: None,
+            storage: None,
         }
     }
 
@@ -99,6 +112,15 @@ impl P2pNetwork {
                     .with_max_attempts(10),
             )),
             started_at: None,
+            mix_routes: Arc::new(RwLock::new(HashMap::new())),
+            mix_packet_rx: Arc::new(RwLock::new(None)),
+            node_peers: Arc::new(RwLock::new(HashMap::new())),
+            attestation_rx: Arc::new(RwLock::new(None)),
+            filter_list_rx: Arc::new(RwLock::new(None)),
+            oracle_vote_rx: Arc::new(RwLock::new(None)),
+            pir_database: Arc::new(RwLock::new(None)),
+            identity: None,
+            storage: None,
         }
     }
 
@@ -140,6 +162,15 @@ impl P2pNetwork {
                     .with_max_attempts(10),
             )),
             started_at: None,
+            mix_routes: Arc::new(RwLock::new(HashMap::new())),
+            mix_packet_rx: Arc::new(RwLock::new(None)),
+            node_peers: Arc::new(RwLock::new(HashMap::new())),
+            attestation_rx: Arc::new(RwLock::new(None)),
+            filter_list_rx: Arc::new(RwLock::new(None)),
+            oracle_vote_rx: Arc::new(RwLock::new(None)),
+            pir_database: Arc::new(RwLock::new(None)),
+            identity: None,
+            storage: None,
         }
     }
 
@@ -176,6 +207,15 @@ impl P2pNetwork {
                     .with_max_attempts(10),
             )),
             started_at: None,
+            mix_routes: Arc::new(RwLock::new(HashMap::new())),
+            mix_packet_rx: Arc::new(RwLock::new(None)),
+            node_peers: Arc::new(RwLock::new(HashMap::new())),
+            attestation_rx: Arc::new(RwLock::new(None)),
+            filter_list_rx: Arc::new(RwLock::new(None)),
+            oracle_vote_rx: Arc::new(RwLock::new(None)),
+            pir_database: Arc::new(RwLock::new(None)),
+            identity: None,
+            storage: None,
         }
     }
 }
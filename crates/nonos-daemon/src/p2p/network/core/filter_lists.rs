@@ -0,0 +1,19 @@
+use super::network::P2pNetwork;
+use crate::p2p::messages::{FilterListChunkData, P2pMessage};
+use crate::p2p::topics;
+use nonos_types::NonosResult;
+use tokio::sync::mpsc;
+
+impl P2pNetwork {
+    /// Filter-list bundle chunks gossiped by other nodes. Can only be taken
+    /// once per start.
+    pub fn take_filter_list_chunks(&self) -> Option<mpsc::Receiver<FilterListChunkData>> {
+        self.filter_list_rx.write().take()
+    }
+
+    /// Gossips one chunk of a filter-list bundle. Callers should pace a
+    /// bundle's chunks so relays stay within their peers' rate limits.
+    pub async fn publish_filter_list_chunk(&self, chunk: FilterListChunkData) -> NonosResult<()> {
+        self.publish_message(topics::FILTER_LISTS, &P2pMessage::FilterListChunk(chunk)).await
+    }
+}
@@ -1,105 +1,107 @@
 use super::super::config::PROTOCOL_VERSION;
 use super::network::P2pNetwork;
 use crate::p2p::behaviour::NonosBehaviour;
-use crate::p2p::swarm::run_swarm;
+use crate::p2p::mixnet::{MixnetCodec, MIXNET_PROTOCOL};
+use crate::p2p::pir::{PirCodec, PIR_PROTOCOL, PIR_REQUEST_TIMEOUT};
+use crate::p2p::probe::{ProbeCodec, PROBE_PROTOCOL, PROBE_TIMEOUT};
+use crate::p2p::record_store::PersistentRecordStore;
+use crate::p2p::scoring::{peer_score_params, peer_score_thresholds};
+use crate::p2p::swarm::{run_swarm, SwarmContext};
 use crate::p2p::topics;
 use crate::p2p::types::NetworkCommand;
-use libp2p::{gossipsub, identify, kad, noise, ping, tcp, yamux, SwarmBuilder};
+use crate::p2p::vault::{VaultCodec, VaultShareStore, VAULT_PROTOCOL, VAULT_REQUEST_TIMEOUT};
+use libp2p::{
+    autonat, dcutr, gossipsub, identify, identity, kad, noise, ping, relay, request_response, tcp,
+    yamux, SwarmBuilder,
+};
 use nonos_types::{NonosError, NonosResult};
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tracing::info;
 
+const MIXNET_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MIX_PACKET_QUEUE: usize = 1024;
+const ATTESTATION_QUEUE: usize = 256;
+const FILTER_LIST_QUEUE: usize = 256;
+const ORACLE_VOTE_QUEUE: usize = 1024;
+
 impl P2pNetwork {
     pub async fn start(&mut self) -> NonosResult<()> {
         if self.running.load(Ordering::Relaxed) {
             return Ok(());
         }
 
-        info!("Starting P2P network on port {}", self.config.port);
+        info!("Starting P2P network on port {} (role: {})", self.config.port, self.node_role);
 
-        let swarm = SwarmBuilder::with_existing_identity(self.local_key.clone())
+        let builder = SwarmBuilder::with_existing_identity(self.local_key.clone())
             .with_tokio()
             .with_tcp(
                 tcp::Config::default(),
                 noise::Config::new,
                 yamux::Config::default,
             )
-            .map_err(|e| NonosError::Network(format!("Failed to create transport: {}", e)))?
-            .with_behaviour(|key| {
-                let store = kad::store::MemoryStore::new(key.public().to_peer_id());
-                #[allow(deprecated)]
-                let kademlia_config = kad::Config::default();
-                let kademlia = kad::Behaviour::with_config(
-                    key.public().to_peer_id(),
-                    store,
-                    kademlia_config,
-                );
-
-                let gossipsub_config = gossipsub::ConfigBuilder::default()
-                    .heartbeat_interval(Duration::from_secs(10))
-                    .validation_mode(gossipsub::ValidationMode::Strict)
-                    .max_transmit_size(self.config.max_message_size)
-                    .message_id_fn(|msg| {
-                        let hash = blake3::hash(&msg.data);
-                        gossipsub::MessageId::from(hash.as_bytes().to_vec())
-                    })
-                    .build()
-                    .expect("Valid gossipsub config");
-
-                let gossipsub = gossipsub::Behaviour::new(
-                    gossipsub::MessageAuthenticity::Signed(key.clone()),
-                    gossipsub_config,
-                )
-                .expect("Valid gossipsub behaviour");
-
-                let identify = identify::Behaviour::new(identify::Config::new(
-                    PROTOCOL_VERSION.to_string(),
-                    key.public(),
-                ));
-
-                let ping = ping::Behaviour::new(ping::Config::new());
-
-                Ok(NonosBehaviour {
-                    kademlia,
-                    gossipsub,
-                    identify,
-                    ping,
+            .map_err(|e| NonosError::Network(format!("Failed to create transport: {}", e)))?;
+
+        // The QUIC transport is only added when enabled, so a node with QUIC
+        // off never binds or dials UDP.
+        let swarm = if self.config.enable_quic {
+            builder
+                .with_quic_config(|mut quic| {
+                    quic.handshake_timeout = self.config.dial_timeout;
+                    quic
                 })
-            })
-            .map_err(|e| NonosError::Network(format!("Failed to create behaviour: {}", e)))?
-            .with_swarm_config(|c| c.with_idle_connection_timeout(self.config.idle_timeout))
-            .build();
+                .with_relay_client(noise::Config::new, yamux::Config::default)
+                .map_err(|e| NonosError::Network(format!("Failed to create relay transport: {}", e)))?
+                .with_behaviour(|key, relay_client| self.build_behaviour(key, relay_client))
+                .map_err(|e| NonosError::Network(format!("Failed to create behaviour: {}", e)))?
+                .with_swarm_config(|c| c.with_idle_connection_timeout(self.config.idle_timeout))
+                .build()
+        } else {
+            builder
+                .with_relay_client(noise::Config::new, yamux::Config::default)
+                .map_err(|e| NonosError::Network(format!("Failed to create relay transport: {}", e)))?
+                .with_behaviour(|key, relay_client| self.build_behaviour(key, relay_client))
+                .map_err(|e| NonosError::Network(format!("Failed to create behaviour: {}", e)))?
+                .with_swarm_config(|c| c.with_idle_connection_timeout(self.config.idle_timeout))
+                .build()
+        };
 
         let (command_tx, command_rx) = mpsc::channel::<NetworkCommand>(256);
         let (event_tx, event_rx) = mpsc::channel(256);
+        let (mix_packet_tx, mix_packet_rx) = mpsc::channel(MIX_PACKET_QUEUE);
+        let (attestation_tx, attestation_rx) = mpsc::channel(ATTESTATION_QUEUE);
+        let (filter_list_tx, filter_list_rx) = mpsc::channel(FILTER_LIST_QUEUE);
+        let (oracle_vote_tx, oracle_vote_rx) = mpsc::channel(ORACLE_VOTE_QUEUE);
 
         self.command_tx = Some(command_tx.clone());
         *self.event_rx.write() = Some(event_rx);
-
-        let peers = self.peers.clone();
-        let banned_peers = self.banned_peers.clone();
-        let stats = self.stats.clone();
-        let running = self.running.clone();
-        let rate_limiters = self.rate_limiters.clone();
-        let config = self.config.clone();
-        let port = self.config.port;
-
-        tokio::spawn(async move {
-            run_swarm(
-                swarm,
-                command_rx,
-                event_tx,
-                peers,
-                banned_peers,
-                stats,
-                running,
-                port,
-                rate_limiters,
-                config,
-            ).await;
-        });
+        *self.mix_packet_rx.write() = Some(mix_packet_rx);
+        *self.attestation_rx.write() = Some(attestation_rx);
+        *self.filter_list_rx.write() = Some(filter_list_rx);
+        *self.oracle_vote_rx.write() = Some(oracle_vote_rx);
+
+        let ctx = SwarmContext {
+            event_tx,
+            peers: self.peers.clone(),
+            banned_peers: self.banned_peers.clone(),
+            stats: self.stats.clone(),
+            running: self.running.clone(),
+            rate_limiters: self.rate_limiters.clone(),
+            config: self.config.clone(),
+            mix_routes: self.mix_routes.clone(),
+            mix_packet_tx,
+            peer_store: self.peer_store.clone(),
+            node_peers: self.node_peers.clone(),
+            attestation_tx,
+            filter_list_tx,
+            oracle_vote_tx,
+        };
+        let pir_database = self.pir_database.clone();
+        let vault_shares = Arc::new(VaultShareStore::open(self.storage.clone()));
+
+        tokio::spawn(run_swarm(swarm, command_rx, ctx, pir_database, vault_shares));
 
         self.running.store(true, Ordering::Relaxed);
         self.started_at = Some(Instant::now());
@@ -108,6 +110,8 @@ impl P2pNetwork {
         self.subscribe(topics::QUALITY_REPORTS).await?;
         self.subscribe(topics::PEER_DISCOVERY).await?;
         self.subscribe(topics::NODE_ANNOUNCEMENTS).await?;
+        self.subscribe(topics::FILTER_LISTS).await?;
+        self.subscribe(topics::PRIVACY_ORACLE).await?;
 
         if self.config.bootstrap_on_start {
             self.bootstrap().await?;
@@ -117,6 +121,114 @@ impl P2pNetwork {
         Ok(())
     }
 
+    fn build_behaviour(&self, key: &identity::Keypair, relay_client: relay::client::Behaviour) -> NonosBehaviour {
+        let local_peer_id = key.public().to_peer_id();
+        let dht = &self.config.record_store;
+        let store = PersistentRecordStore::open(
+            local_peer_id,
+            self.storage.clone(),
+            dht.clone(),
+        );
+
+        // Publication is driven by the swarm loop so that records
+        // loaded from disk are re-published soon after a restart.
+        #[allow(deprecated)]
+        let mut kademlia_config = kad::Config::default();
+        kademlia_config
+            .set_max_packet_size(dht.max_value_bytes * 2)
+            .set_record_ttl(Some(dht.record_ttl))
+            .set_replication_interval(Some(dht.replication_interval))
+            .set_publication_interval(None)
+            .set_provider_record_ttl(Some(dht.provider_ttl))
+            .set_provider_publication_interval(None);
+        let kademlia = kad::Behaviour::with_config(
+            local_peer_id,
+            store,
+            kademlia_config,
+        );
+
+        let gossipsub_config = gossipsub::ConfigBuilder::default()
+            .heartbeat_interval(Duration::from_secs(10))
+            .validation_mode(gossipsub::ValidationMode::Strict)
+            // Messages are only forwarded once the swarm has checked
+            // their envelope, so invalid ones count against the sender.
+            .validate_messages()
+            .max_transmit_size(self.config.max_message_size)
+            .message_id_fn(|msg| {
+                let hash = blake3::hash(&msg.data);
+                gossipsub::MessageId::from(hash.as_bytes().to_vec())
+            })
+            .build()
+            .expect("Valid gossipsub config");
+
+        let mut gossipsub = gossipsub::Behaviour::new(
+            gossipsub::MessageAuthenticity::Signed(key.clone()),
+            gossipsub_config,
+        )
+        .expect("Valid gossipsub behaviour");
+        gossipsub
+            .with_peer_score(peer_score_params(), peer_score_thresholds())
+            .expect("Valid gossipsub peer score parameters");
+
+        let identify = identify::Behaviour::new(identify::Config::new(
+            PROTOCOL_VERSION.to_string(),
+            key.public(),
+        ));
+
+        let ping = ping::Behaviour::new(ping::Config::new());
+
+        let mixnet = request_response::Behaviour::with_codec(
+            MixnetCodec,
+            [(MIXNET_PROTOCOL, request_response::ProtocolSupport::Full)],
+            request_response::Config::default()
+                .with_request_timeout(MIXNET_REQUEST_TIMEOUT),
+        );
+
+        let autonat = autonat::Behaviour::new(local_peer_id, autonat::Config::default());
+
+        let relay = self.node_role.serves_relay()
+            .then(|| relay::Behaviour::new(local_peer_id, relay::Config::default()))
+            .into();
+
+        let dcutr = dcutr::Behaviour::new(local_peer_id);
+
+        let probe = request_response::Behaviour::with_codec(
+            ProbeCodec,
+            [(PROBE_PROTOCOL, request_response::ProtocolSupport::Full)],
+            request_response::Config::default()
+                .with_request_timeout(PROBE_TIMEOUT),
+        );
+
+        let pir = request_response::Behaviour::with_codec(
+            PirCodec,
+            [(PIR_PROTOCOL, request_response::ProtocolSupport::Full)],
+            request_response::Config::default()
+                .with_request_timeout(PIR_REQUEST_TIMEOUT),
+        );
+
+        let vault = request_response::Behaviour::with_codec(
+            VaultCodec,
+            [(VAULT_PROTOCOL, request_response::ProtocolSupport::Full)],
+            request_response::Config::default()
+                .with_request_timeout(VAULT_REQUEST_TIMEOUT),
+        );
+
+        NonosBehaviour {
+            kademlia,
+            gossipsub,
+            identify,
+            ping,
+            mixnet,
+            autonat,
+            relay_client,
+            relay,
+            dcutr,
+            probe,
+            pir,
+            vault,
+        }
+    }
+
     pub async fn shutdown(&mut self) {
         info!("Shutting down P2P network");
 
@@ -1,4 +1,6 @@
 use super::network::P2pNetwork;
+use crate::p2p::envelope::SignedEnvelope;
+use crate::p2p::messages::P2pMessage;
 use crate::p2p::types::NetworkCommand;
 use nonos_types::{NonosError, NonosResult};
 use std::sync::atomic::Ordering;
@@ -34,4 +36,31 @@ impl P2pNetwork {
     pub async fn publish(&self, topic: &str, message: &[u8]) -> NonosResult<()> {
         self.broadcast(topic, message).await
     }
+
+    /// Stores a record in the DHT as its publisher. It is kept in the local
+    /// record store across restarts and re-published until replaced.
+    pub async fn put_dht_record(&self, key: &[u8], value: Vec<u8>) -> NonosResult<()> {
+        let max_value_bytes = self.config.record_store.max_value_bytes;
+        if value.len() > max_value_bytes {
+            return Err(NonosError::Network(format!(
+                "DHT record too large: {} bytes (max: {})",
+                value.len(),
+                max_value_bytes
+            )));
+        }
+
+        let tx = self.command_tx.as_ref()
+            .ok_or_else(|| NonosError::Network("P2P network not started".into()))?;
+        tx.send(NetworkCommand::PutRecord { key: key.to_vec(), value })
+            .await
+            .map_err(|e| NonosError::Network(format!("Failed to put DHT record: {}", e)))
+    }
+
+    /// Signs `message` with the node identity and publishes the envelope.
+    pub async fn publish_message(&self, topic: &str, message: &P2pMessage) -> NonosResult<()> {
+        let identity = self.identity.as_ref()
+            .ok_or_else(|| NonosError::Network("No node identity to sign messages with".into()))?;
+        let envelope = SignedEnvelope::seal(message, identity, self.local_peer_id)?;
+        self.broadcast(topic, &envelope.to_bytes()).await
+    }
 }
@@ -0,0 +1,24 @@
+use super::network::P2pNetwork;
+use crate::p2p::mixnet::MixPacketSender;
+use crate::privacy::{MixNode, SphinxPacket};
+use tokio::sync::mpsc;
+
+impl P2pNetwork {
+    /// Sender for packets addressed by mix `NodeId`; `None` until started.
+    pub fn mix_packet_sender(&self) -> Option<MixPacketSender> {
+        self.command_tx
+            .as_ref()
+            .map(|tx| MixPacketSender::new(tx.clone(), self.mix_routes.clone()))
+    }
+
+    /// Packets received over the mixnet protocol. Can only be taken once
+    /// per start.
+    pub fn take_mix_packets(&self) -> Option<mpsc::Receiver<SphinxPacket>> {
+        self.mix_packet_rx.write().take()
+    }
+
+    /// Mix nodes learned from node announcements.
+    pub fn mix_nodes(&self) -> Vec<MixNode> {
+        self.mix_routes.read().values().map(|route| route.node.clone()).collect()
+    }
+}
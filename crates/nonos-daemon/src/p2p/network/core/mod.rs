@@ -11,6 +11,12 @@ mod rate_limiting;
 mod peer_tracking;
 mod bootstrap;
 mod accessors;
+mod mixnet;
+mod probing;
+mod pir;
+mod filter_lists;
+mod oracle_votes;
+mod vault;
 #[cfg(test)]
 mod tests;
 
@@ -1,10 +1,17 @@
 use crate::config::{BootstrapMode, NodeRole};
+use crate::p2p::messages::{FilterListChunkData, LatencyAttestationData, OracleVoteData};
+use crate::p2p::mixnet::MixRoutes;
+use crate::p2p::pir::ServedPirDatabase;
+use crate::p2p::probe::NodePeers;
 use crate::p2p::peer_store::SharedPeerStore;
+use crate::privacy::SphinxPacket;
+use crate::storage::NodeStorage;
 use crate::p2p::types::{
     BackoffStrategy, BanEntry, CircuitBreaker, NetworkCommand, NetworkEvent, NetworkStats,
     PeerInfo, RateLimiter,
 };
 use libp2p::PeerId;
+use nonos_crypto::NodeIdentity;
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::atomic::AtomicBool;
@@ -33,4 +40,13 @@ pub struct P2pNetwork {
     pub(crate) circuit_breakers: Arc<RwLock<HashMap<PeerId, CircuitBreaker>>>,
     pub(crate) bootstrap_backoff: Arc<RwLock<BackoffStrategy>>,
     pub(crate) started_at: Option<Instant>,
+    pub(crate) mix_routes: MixRoutes,
+    pub(crate) mix_packet_rx: Arc<RwLock<Option<mpsc::Receiver<SphinxPacket>>>>,
+    pub(crate) node_peers: NodePeers,
+    pub(crate) attestation_rx: Arc<RwLock<Option<mpsc::Receiver<LatencyAttestationData>>>>,
+    pub(crate) filter_list_rx: Arc<RwLock<Option<mpsc::Receiver<FilterListChunkData>>>>,
+    pub(crate) oracle_vote_rx: Arc<RwLock<Option<mpsc::Receiver<OracleVoteData>>>>,
+    pub(crate) pir_database: ServedPirDatabase,
+    pub(crate) identity: Option<Arc<NodeIdentity>>,
+    pub(crate) storage: Option<Arc<NodeStorage>>,
 }
@@ -0,0 +1,17 @@
+use super::network::P2pNetwork;
+use crate::p2p::messages::{OracleVoteData, P2pMessage};
+use crate::p2p::topics;
+use nonos_types::NonosResult;
+use tokio::sync::mpsc;
+
+impl P2pNetwork {
+    /// Privacy oracle votes gossiped by other nodes. Can only be taken once
+    /// per start.
+    pub fn take_oracle_votes(&self) -> Option<mpsc::Receiver<OracleVoteData>> {
+        self.oracle_vote_rx.write().take()
+    }
+
+    pub async fn publish_oracle_vote(&self, vote: OracleVoteData) -> NonosResult<()> {
+        self.publish_message(topics::PRIVACY_ORACLE, &P2pMessage::OracleVote(Box::new(vote))).await
+    }
+}
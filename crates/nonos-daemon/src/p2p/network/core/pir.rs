@@ -0,0 +1,23 @@
+use super::network::P2pNetwork;
+use crate::p2p::pir::PirClient;
+use crate::privacy::{PirDatabase, PirDatabaseInfo};
+use std::sync::Arc;
+
+impl P2pNetwork {
+    /// Handle for private retrieval from peers; `None` until started.
+    pub fn pir_client(&self) -> Option<PirClient> {
+        self.command_tx
+            .as_ref()
+            .map(|tx| PirClient::new(tx.clone(), self.node_peers.clone()))
+    }
+
+    /// Serves `database` to PIR clients in place of any previous one, or
+    /// stops serving with `None`.
+    pub fn serve_pir_database(&self, database: Option<PirDatabase>) {
+        *self.pir_database.write() = database.map(Arc::new);
+    }
+
+    pub fn served_pir_database(&self) -> Option<PirDatabaseInfo> {
+        self.pir_database.read().as_ref().map(|database| database.info())
+    }
+}
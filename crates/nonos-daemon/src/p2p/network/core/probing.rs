@@ -0,0 +1,26 @@
+use super::network::P2pNetwork;
+use crate::p2p::messages::LatencyAttestationData;
+use crate::p2p::probe::NodeProber;
+use libp2p::PeerId;
+use nonos_types::NodeId;
+use tokio::sync::mpsc;
+
+impl P2pNetwork {
+    /// Handle for probing other nodes; `None` until started.
+    pub fn node_prober(&self) -> Option<NodeProber> {
+        self.command_tx
+            .as_ref()
+            .map(|tx| NodeProber::new(tx.clone(), self.node_peers.clone()))
+    }
+
+    /// Latency attestations gossiped by other nodes, already verified
+    /// against their signer. Can only be taken once per start.
+    pub fn take_attestations(&self) -> Option<mpsc::Receiver<LatencyAttestationData>> {
+        self.attestation_rx.write().take()
+    }
+
+    /// The peer that last spoke for `node` in a signed message.
+    pub fn node_peer(&self, node: &NodeId) -> Option<PeerId> {
+        self.node_peers.read().get(node).copied()
+    }
+}
@@ -0,0 +1,12 @@
+use super::network::P2pNetwork;
+use crate::p2p::vault::VaultClient;
+
+impl P2pNetwork {
+    /// Handle for keeping cookie vault shares with other nodes; `None`
+    /// until started.
+    pub fn vault_client(&self) -> Option<VaultClient> {
+        self.command_tx
+            .as_ref()
+            .map(|tx| VaultClient::new(tx.clone(), self.node_peers.clone()))
+    }
+}
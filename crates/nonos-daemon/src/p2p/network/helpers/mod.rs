@@ -1,5 +1,7 @@
 mod extract_peer_id;
 mod humanize_duration;
+mod transport_address;
 
 pub use extract_peer_id::*;
 pub use humanize_duration::*;
+pub use transport_address::*;
@@ -0,0 +1,63 @@
+use libp2p::multiaddr::Protocol;
+use libp2p::Multiaddr;
+
+/// The transport a multiaddr dials over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressTransport {
+    Quic,
+    Tcp,
+    /// Relayed, DNS-only or otherwise not a direct transport address.
+    Other,
+}
+
+pub fn address_transport(addr: &Multiaddr) -> AddressTransport {
+    let mut transport = AddressTransport::Other;
+    for protocol in addr.iter() {
+        match protocol {
+            Protocol::P2pCircuit => return AddressTransport::Other,
+            Protocol::QuicV1 => transport = AddressTransport::Quic,
+            Protocol::Tcp(_) => transport = AddressTransport::Tcp,
+            _ => {}
+        }
+    }
+    transport
+}
+
+/// The QUIC counterpart of a TCP address, assuming the peer serves QUIC on
+/// the same port number, as nodes do by default.
+pub fn quic_address_for(tcp_addr: &Multiaddr) -> Option<Multiaddr> {
+    if address_transport(tcp_addr) != AddressTransport::Tcp {
+        return None;
+    }
+
+    Some(tcp_addr.iter()
+        .map(|protocol| match protocol {
+            Protocol::Tcp(port) => Protocol::Udp(port),
+            other => other,
+        })
+        .flat_map(|protocol| {
+            let quic = matches!(protocol, Protocol::Udp(_)).then_some(Protocol::QuicV1);
+            std::iter::once(protocol).chain(quic)
+        })
+        .collect())
+}
+
+/// Orders addresses for dialling: QUIC before TCP when `prefer_quic`, QUIC
+/// dropped entirely when it is disabled. Duplicates are removed and the
+/// relative order within a transport is kept.
+pub fn rank_addresses(addrs: Vec<Multiaddr>, quic_enabled: bool, prefer_quic: bool) -> Vec<Multiaddr> {
+    let mut ranked: Vec<Multiaddr> = Vec::with_capacity(addrs.len());
+    for addr in addrs {
+        if !ranked.contains(&addr) {
+            ranked.push(addr);
+        }
+    }
+
+    ranked.retain(|addr| quic_enabled || address_transport(addr) != AddressTransport::Quic);
+    ranked.sort_by_key(|addr| match (address_transport(addr), prefer_quic) {
+        (AddressTransport::Quic, true) | (AddressTransport::Tcp, false) => 0,
+        (AddressTransport::Quic, false) | (AddressTransport::Tcp, true) => 1,
+        (AddressTransport::Other, _) => 2,
+    });
+    ranked
+}
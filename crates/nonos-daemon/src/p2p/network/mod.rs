@@ -6,3 +6,4 @@ pub use config::NetworkConfig;
 pub use core::P2pNetwork;
 pub(crate) use config::get_bootstrap_nodes;
 pub(crate) use helpers::extract_peer_id;
+pub use helpers::{address_transport, quic_address_for, rank_addresses, AddressTransport};
@@ -31,6 +31,9 @@ pub struct PeerEntry {
     pub sideline_expires_at: Option<i64>,
     pub connection_count: u32,
     pub consecutive_failures: u32,
+    /// Last gossipsub score observed for this peer, if it has been scored.
+    #[serde(default)]
+    pub gossipsub_score: Option<f64>,
 }
 
 impl Default for PeerEntry {
@@ -60,6 +63,7 @@ impl Default for PeerEntry {
             sideline_expires_at: None,
             connection_count: 0,
             consecutive_failures: 0,
+            gossipsub_score: None,
         }
     }
 }
@@ -128,6 +132,7 @@ impl PeerEntry {
             PenaltyReason::InvalidData => 15,
             PenaltyReason::Spam => 25,
             PenaltyReason::ConnectionAbuse => 20,
+            PenaltyReason::LowGossipScore => 10,
         };
 
         self.penalty_score = (self.penalty_score + penalty).min(MAX_PENALTY_SCORE);
@@ -26,6 +26,7 @@ pub enum PenaltyReason {
     InvalidData,
     Spam,
     ConnectionAbuse,
+    LowGossipScore,
 }
 
 impl std::fmt::Display for PenaltyReason {
@@ -38,6 +39,7 @@ impl std::fmt::Display for PenaltyReason {
             PenaltyReason::InvalidData => write!(f, "invalid_data"),
             PenaltyReason::Spam => write!(f, "spam"),
             PenaltyReason::ConnectionAbuse => write!(f, "connection_abuse"),
+            PenaltyReason::LowGossipScore => write!(f, "low_gossip_score"),
         }
     }
 }
@@ -0,0 +1,300 @@
+//! Private content retrieval between peers.
+//!
+//! Carries the two-server XOR PIR scheme from [`crate::privacy::PirDatabase`]
+//! over `/nonos/pir/1.0.0`. A node serves at most one database; clients ask
+//! known nodes for its description, pair up nodes serving the same digest,
+//! and send each one of a pair its half of the query.
+
+use super::probe::NodePeers;
+use super::types::NetworkCommand;
+use crate::privacy::{PirDatabase, PirDatabaseInfo, PirQuery, PirRetrieval, MAX_PIR_DATABASE_BYTES};
+use async_trait::async_trait;
+use futures::future::join_all;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use libp2p::request_response::{self, OutboundRequestId, ResponseChannel};
+use libp2p::{PeerId, StreamProtocol};
+use nonos_types::{NonosError, NonosResult};
+use parking_lot::RwLock;
+use rand::seq::SliceRandom;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Semaphore};
+use tracing::debug;
+
+pub const PIR_PROTOCOL: StreamProtocol = StreamProtocol::new("/nonos/pir/1.0.0");
+
+pub const PIR_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Queries answered at once; further queries are refused until one ends.
+const MAX_CONCURRENT_ANSWERS: usize = 4;
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+const MAX_RESPONSE_SIZE: usize = MAX_PIR_DATABASE_BYTES + 1024;
+/// Nodes asked which database they serve when looking for servers.
+const MAX_SURVEYED_SERVERS: usize = 16;
+
+/// The database this node serves, if any.
+pub type ServedPirDatabase = Arc<RwLock<Option<Arc<PirDatabase>>>>;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum PirRequest {
+    Info,
+    Query(PirQuery),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum PirResponse {
+    /// The served database, or `None` if the node serves none.
+    Info(Option<PirDatabaseInfo>),
+    Answer(Vec<u8>),
+    /// The query was for another database, malformed, or the node is busy.
+    Refused,
+}
+
+/// Length-prefixed bincode in both directions.
+#[derive(Clone, Default)]
+pub struct PirCodec;
+
+#[async_trait]
+impl request_response::Codec for PirCodec {
+    type Protocol = StreamProtocol;
+    type Request = PirRequest;
+    type Response = PirResponse;
+
+    async fn read_request<T>(&mut self, _: &StreamProtocol, io: &mut T) -> io::Result<PirRequest>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_framed(io, MAX_REQUEST_SIZE).await
+    }
+
+    async fn read_response<T>(&mut self, _: &StreamProtocol, io: &mut T) -> io::Result<PirResponse>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_framed(io, MAX_RESPONSE_SIZE).await
+    }
+
+    async fn write_request<T>(&mut self, _: &StreamProtocol, io: &mut T, request: PirRequest) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_framed(io, &request).await
+    }
+
+    async fn write_response<T>(&mut self, _: &StreamProtocol, io: &mut T, response: PirResponse) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_framed(io, &response).await
+    }
+}
+
+pub(super) async fn read_framed<T, M>(io: &mut T, max_size: usize) -> io::Result<M>
+where
+    T: AsyncRead + Unpin + Send,
+    M: DeserializeOwned,
+{
+    let mut length = [0u8; 4];
+    io.read_exact(&mut length).await?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > max_size {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Message too large"));
+    }
+
+    let mut bytes = vec![0u8; length];
+    io.read_exact(&mut bytes).await?;
+    bincode::deserialize(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+pub(super) async fn write_framed<T, M>(io: &mut T, message: &M) -> io::Result<()>
+where
+    T: AsyncWrite + Unpin + Send,
+    M: Serialize,
+{
+    let bytes = bincode::serialize(message)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    let length = u32::try_from(bytes.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Message too large"))?;
+    io.write_all(&length.to_be_bytes()).await?;
+    io.write_all(&bytes).await?;
+    io.close().await
+}
+
+/// Fetches content from pairs of peers without revealing which item is
+/// wanted. Holds no lock on the network.
+#[derive(Clone)]
+pub struct PirClient {
+    command_tx: mpsc::Sender<NetworkCommand>,
+    node_peers: NodePeers,
+}
+
+impl PirClient {
+    pub(crate) fn new(command_tx: mpsc::Sender<NetworkCommand>, node_peers: NodePeers) -> Self {
+        Self { command_tx, node_peers }
+    }
+
+    /// Retrieves the content with `commitment` from any two known nodes
+    /// serving the same database, trying databases served by more nodes
+    /// first. Returns `None` if no such database holds it.
+    pub async fn fetch_from_known_nodes(&self, commitment: [u8; 32]) -> NonosResult<Option<Vec<u8>>> {
+        let mut servers: Vec<PeerId> = self.node_peers.read().values().copied().collect();
+        servers.shuffle(&mut rand::thread_rng());
+        servers.truncate(MAX_SURVEYED_SERVERS);
+
+        let infos = join_all(servers.iter().map(|peer| async move {
+            (*peer, self.database_info(*peer).await.ok().flatten())
+        })).await;
+        let pairs = server_pairs(infos);
+        if pairs.is_empty() {
+            return Err(NonosError::Network("No two known nodes serve the same PIR database".into()));
+        }
+
+        for pair in pairs {
+            match self.fetch(commitment, pair).await {
+                Ok(Some(content)) => return Ok(Some(content)),
+                Ok(None) => {}
+                Err(e) => debug!("PIR fetch from {} and {} failed: {}", pair[0], pair[1], e),
+            }
+        }
+        Ok(None)
+    }
+
+    /// The database `peer` serves, if any.
+    pub async fn database_info(&self, peer: PeerId) -> NonosResult<Option<PirDatabaseInfo>> {
+        match self.request(peer, PirRequest::Info).await? {
+            PirResponse::Info(info) => Ok(info),
+            _ => Err(NonosError::Network(format!("Unexpected PIR response from {}", peer))),
+        }
+    }
+
+    /// Retrieves the content with `commitment` from two servers holding the
+    /// same database. Neither learns which item was fetched unless they
+    /// collude. Returns `None` if the database does not hold it.
+    pub async fn fetch(&self, commitment: [u8; 32], servers: [PeerId; 2]) -> NonosResult<Option<Vec<u8>>> {
+        if servers[0] == servers[1] {
+            return Err(NonosError::Network("PIR needs two distinct servers".into()));
+        }
+
+        let (left, right) = tokio::join!(self.database_info(servers[0]), self.database_info(servers[1]));
+        let info = match (left?, right?) {
+            (Some(left), Some(right)) if left == right => left,
+            (Some(_), Some(_)) => {
+                return Err(NonosError::Network("PIR servers hold different databases".into()));
+            }
+            _ => return Err(NonosError::Network("PIR server has no database".into())),
+        };
+
+        let (retrieval, [left, right]) = PirRetrieval::new(&info, commitment)?;
+        let (left, right) = tokio::join!(
+            self.request(servers[0], PirRequest::Query(left)),
+            self.request(servers[1], PirRequest::Query(right)),
+        );
+        match (left?, right?) {
+            (PirResponse::Answer(left), PirResponse::Answer(right)) => retrieval.reconstruct(&left, &right),
+            _ => Err(NonosError::Network("PIR query refused".into())),
+        }
+    }
+
+    async fn request(&self, peer: PeerId, request: PirRequest) -> NonosResult<PirResponse> {
+        let (reply, response) = oneshot::channel();
+        self.command_tx
+            .send(NetworkCommand::Pir { peer, request, reply })
+            .await
+            .map_err(|_| NonosError::Network("P2P network not running".into()))?;
+
+        tokio::time::timeout(PIR_REQUEST_TIMEOUT * 2, response)
+            .await
+            .map_err(|_| NonosError::Network(format!("PIR request to {} timed out", peer)))?
+            .map_err(|_| NonosError::Network("P2P network stopped".into()))?
+    }
+}
+
+/// One pair of servers per distinct database that at least two of
+/// `servers` report, databases served by more of them first.
+pub(crate) fn server_pairs(servers: Vec<(PeerId, Option<PirDatabaseInfo>)>) -> Vec<[PeerId; 2]> {
+    let mut databases: Vec<(PirDatabaseInfo, Vec<PeerId>)> = Vec::new();
+    for (peer, info) in servers {
+        let Some(info) = info else { continue };
+        match databases.iter_mut().find(|(served, _)| *served == info) {
+            Some((_, peers)) => peers.push(peer),
+            None => databases.push((info, vec![peer])),
+        }
+    }
+    databases.sort_by_key(|(_, peers)| std::cmp::Reverse(peers.len()));
+    databases.into_iter()
+        .filter_map(|(_, peers)| (peers.len() >= 2).then(|| [peers[0], peers[1]]))
+        .collect()
+}
+
+pub(crate) type PirAnswer = (ResponseChannel<PirResponse>, PirResponse);
+
+/// The swarm's side of PIR: outstanding requests it sent, and answers to
+/// inbound queries, computed off the swarm task and handed back through
+/// `answer_tx`.
+pub(crate) struct PirExchange {
+    database: ServedPirDatabase,
+    answer_tx: mpsc::UnboundedSender<PirAnswer>,
+    answering: Arc<Semaphore>,
+    pending: HashMap<OutboundRequestId, oneshot::Sender<NonosResult<PirResponse>>>,
+}
+
+impl PirExchange {
+    pub fn new(database: ServedPirDatabase, answer_tx: mpsc::UnboundedSender<PirAnswer>) -> Self {
+        Self {
+            database,
+            answer_tx,
+            answering: Arc::new(Semaphore::new(MAX_CONCURRENT_ANSWERS)),
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn sent(&mut self, request: OutboundRequestId, reply: oneshot::Sender<NonosResult<PirResponse>>) {
+        self.pending.insert(request, reply);
+    }
+
+    pub fn answered(&mut self, request: OutboundRequestId, response: PirResponse) {
+        if let Some(reply) = self.pending.remove(&request) {
+            let _ = reply.send(Ok(response));
+        }
+    }
+
+    pub fn failed(&mut self, request: OutboundRequestId, error: String) {
+        if let Some(reply) = self.pending.remove(&request) {
+            let _ = reply.send(Err(NonosError::Network(format!("PIR request failed: {}", error))));
+        }
+    }
+
+    /// Answers `request` on a blocking thread; the response comes back
+    /// through `answer_tx`.
+    pub fn serve(&self, channel: ResponseChannel<PirResponse>, request: PirRequest) {
+        let database = self.database.read().clone();
+        let query = match request {
+            PirRequest::Info => {
+                let _ = self.answer_tx.send((channel, PirResponse::Info(database.map(|db| db.info()))));
+                return;
+            }
+            PirRequest::Query(query) => query,
+        };
+
+        let (Some(database), Ok(permit)) = (database, self.answering.clone().try_acquire_owned()) else {
+            let _ = self.answer_tx.send((channel, PirResponse::Refused));
+            return;
+        };
+
+        let answer_tx = self.answer_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let response = match database.answer(&query) {
+                Ok(answer) => PirResponse::Answer(answer),
+                Err(e) => {
+                    debug!("Refusing PIR query: {}", e);
+                    PirResponse::Refused
+                }
+            };
+            drop(permit);
+            let _ = answer_tx.send((channel, response));
+        });
+    }
+}
@@ -0,0 +1,161 @@
+//! Latency probes between nodes.
+//!
+//! A probe is a random nonce that the remote end echoes back over
+//! `/nonos/probe/1.0.0`; the swarm times the round trip. Probes are
+//! addressed by `NodeId`, resolved through the node-to-peer mapping learned
+//! from verified gossip.
+
+use super::types::NetworkCommand;
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use libp2p::request_response::OutboundRequestId;
+use libp2p::{request_response, PeerId, StreamProtocol};
+use nonos_types::NodeId;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+
+pub const PROBE_PROTOCOL: StreamProtocol = StreamProtocol::new("/nonos/probe/1.0.0");
+
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const NONCE_SIZE: usize = 32;
+
+/// Which peer speaks for each node, learned from signed messages.
+pub type NodePeers = Arc<RwLock<HashMap<NodeId, PeerId>>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProbeNonce(pub [u8; NONCE_SIZE]);
+
+impl ProbeNonce {
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+}
+
+/// Request and response are both a bare nonce.
+#[derive(Clone, Default)]
+pub struct ProbeCodec;
+
+#[async_trait]
+impl request_response::Codec for ProbeCodec {
+    type Protocol = StreamProtocol;
+    type Request = ProbeNonce;
+    type Response = ProbeNonce;
+
+    async fn read_request<T>(&mut self, _: &StreamProtocol, io: &mut T) -> io::Result<ProbeNonce>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_nonce(io).await
+    }
+
+    async fn read_response<T>(&mut self, _: &StreamProtocol, io: &mut T) -> io::Result<ProbeNonce>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_nonce(io).await
+    }
+
+    async fn write_request<T>(&mut self, _: &StreamProtocol, io: &mut T, nonce: ProbeNonce) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        io.write_all(&nonce.0).await?;
+        io.close().await
+    }
+
+    async fn write_response<T>(&mut self, _: &StreamProtocol, io: &mut T, nonce: ProbeNonce) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        io.write_all(&nonce.0).await?;
+        io.close().await
+    }
+}
+
+async fn read_nonce<T>(io: &mut T) -> io::Result<ProbeNonce>
+where
+    T: AsyncRead + Unpin + Send,
+{
+    let mut nonce = [0u8; NONCE_SIZE];
+    io.read_exact(&mut nonce).await?;
+    Ok(ProbeNonce(nonce))
+}
+
+/// Probes nodes by `NodeId` through the swarm. Holds no lock on the
+/// network, so a probe can be awaited for its full timeout.
+#[derive(Clone)]
+pub struct NodeProber {
+    command_tx: mpsc::Sender<NetworkCommand>,
+    node_peers: NodePeers,
+}
+
+impl NodeProber {
+    pub(crate) fn new(command_tx: mpsc::Sender<NetworkCommand>, node_peers: NodePeers) -> Self {
+        Self { command_tx, node_peers }
+    }
+
+    /// Nodes whose peer is known, and so can be probed.
+    pub fn known_nodes(&self) -> Vec<NodeId> {
+        self.node_peers.read().keys().copied().collect()
+    }
+
+    /// Round-trip time to `node`, or `None` if it is unknown, unreachable
+    /// or answers incorrectly.
+    pub async fn probe(&self, node: &NodeId) -> Option<Duration> {
+        let peer = self.node_peers.read().get(node).copied()?;
+        let (reply, response) = oneshot::channel();
+        self.command_tx.send(NetworkCommand::Probe { peer, reply }).await.ok()?;
+
+        // The request timeout normally fires first; this covers the swarm
+        // shutting down with the probe in flight.
+        tokio::time::timeout(PROBE_TIMEOUT * 2, response).await.ok()?.ok()?
+    }
+}
+
+struct PendingProbe {
+    nonce: ProbeNonce,
+    sent_at: Instant,
+    reply: oneshot::Sender<Option<Duration>>,
+}
+
+/// Outstanding probes sent by the swarm. Each resolves to the round-trip
+/// time, or `None` if the peer failed to echo the nonce.
+pub(crate) struct ProbeTracker {
+    pending: HashMap<OutboundRequestId, PendingProbe>,
+}
+
+impl ProbeTracker {
+    pub fn new() -> Self {
+        Self { pending: HashMap::new() }
+    }
+
+    pub fn sent(&mut self, request: OutboundRequestId, nonce: ProbeNonce, reply: oneshot::Sender<Option<Duration>>) {
+        self.pending.insert(request, PendingProbe { nonce, sent_at: Instant::now(), reply });
+    }
+
+    /// Resolves a probe with the echoed nonce. A wrong echo counts as a
+    /// failure.
+    pub fn answered(&mut self, request: OutboundRequestId, echoed: ProbeNonce) {
+        if let Some(probe) = self.pending.remove(&request) {
+            let rtt = (probe.nonce == echoed).then(|| probe.sent_at.elapsed());
+            let _ = probe.reply.send(rtt);
+        }
+    }
+
+    pub fn failed(&mut self, request: OutboundRequestId) {
+        if let Some(probe) = self.pending.remove(&request) {
+            let _ = probe.reply.send(None);
+        }
+    }
+}
+
+impl Default for ProbeTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,333 @@
+use crate::storage::{NodeStorage, StoredDhtProvider, StoredDhtRecord};
+use libp2p::kad::store::{self, MemoryStore, MemoryStoreConfig, RecordStore};
+use libp2p::kad::{ProviderRecord, Record, RecordKey, K_VALUE};
+use libp2p::{Multiaddr, PeerId};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+#[derive(Clone, Debug)]
+pub struct RecordStoreConfig {
+    pub max_records: usize,
+    pub max_value_bytes: usize,
+    /// Upper bound on the summed size of all stored values.
+    pub max_total_bytes: usize,
+    pub max_providers_per_key: usize,
+    pub max_provided_keys: usize,
+    pub record_ttl: Duration,
+    pub provider_ttl: Duration,
+    /// How often stored records are re-replicated to the closest peers.
+    pub replication_interval: Duration,
+    /// How often records and provider entries this node published are
+    /// re-published, which also refreshes their TTL.
+    pub publication_interval: Duration,
+}
+
+impl Default for RecordStoreConfig {
+    fn default() -> Self {
+        Self {
+            max_records: 4096,
+            max_value_bytes: 64 * 1024,
+            max_total_bytes: 64 * 1024 * 1024,
+            max_providers_per_key: K_VALUE.get(),
+            max_provided_keys: 1024,
+            record_ttl: Duration::from_secs(48 * 60 * 60),
+            provider_ttl: Duration::from_secs(48 * 60 * 60),
+            replication_interval: Duration::from_secs(60 * 60),
+            publication_interval: Duration::from_secs(22 * 60 * 60),
+        }
+    }
+}
+
+/// A Kademlia record store that writes through to `NodeStorage`, so DHT
+/// records and provider entries survive a restart.
+///
+/// Lookups are served from an in-memory [`MemoryStore`] that is rebuilt
+/// from disk on open, dropping anything that expired while the node was
+/// down. Without storage it behaves like a plain `MemoryStore` with the
+/// same limits.
+pub struct PersistentRecordStore {
+    inner: MemoryStore,
+    storage: Option<Arc<NodeStorage>>,
+    config: RecordStoreConfig,
+    total_bytes: usize,
+    provider_keys: HashSet<RecordKey>,
+}
+
+impl PersistentRecordStore {
+    pub fn open(local_id: PeerId, storage: Option<Arc<NodeStorage>>, config: RecordStoreConfig) -> Self {
+        let inner = MemoryStore::with_config(local_id, MemoryStoreConfig {
+            max_records: config.max_records,
+            // MemoryStore rejects values of exactly `max_value_bytes`.
+            max_value_bytes: config.max_value_bytes + 1,
+            max_providers_per_key: config.max_providers_per_key,
+            max_provided_keys: config.max_provided_keys,
+        });
+
+        let mut store = Self {
+            inner,
+            storage: None,
+            config,
+            total_bytes: 0,
+            provider_keys: HashSet::new(),
+        };
+        if let Some(storage) = storage {
+            store.load(&storage);
+            store.storage = Some(storage);
+        }
+        store
+    }
+
+    pub fn in_memory(local_id: PeerId, config: RecordStoreConfig) -> Self {
+        Self::open(local_id, None, config)
+    }
+
+    pub fn config(&self) -> &RecordStoreConfig {
+        &self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.records().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.records().next().is_none()
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Removes expired records and provider entries.
+    pub fn prune_expired(&mut self) {
+        let now = Instant::now();
+
+        let expired: Vec<RecordKey> = self.inner.records()
+            .filter(|record| record.is_expired(now))
+            .map(|record| record.key.clone())
+            .collect();
+        for key in &expired {
+            self.remove(key);
+        }
+
+        let expired_providers: Vec<(RecordKey, PeerId)> = self.provider_keys.iter()
+            .flat_map(|key| self.inner.providers(key))
+            .filter(|provider| provider.is_expired(now))
+            .map(|provider| (provider.key, provider.provider))
+            .collect();
+        for (key, provider) in &expired_providers {
+            self.remove_provider(key, provider);
+        }
+
+        if !expired.is_empty() || !expired_providers.is_empty() {
+            debug!(
+                "Pruned {} expired DHT records and {} provider entries",
+                expired.len(), expired_providers.len()
+            );
+        }
+    }
+
+    fn load(&mut self, storage: &NodeStorage) {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let (mut loaded, mut dropped) = (0usize, 0usize);
+
+        match storage.load_dht_records() {
+            Ok(records) => {
+                for stored in records {
+                    let expired = stored.expires_at_ms.is_some_and(|at| at <= now_ms);
+                    let record = (!expired).then(|| record_from_stored(&stored)).flatten();
+                    match record.map(|record| self.insert_record(record)) {
+                        Some(Ok(())) => loaded += 1,
+                        _ => {
+                            dropped += 1;
+                            let _ = storage.remove_dht_record(&stored.key);
+                        }
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to load DHT records: {}", e),
+        }
+
+        match storage.load_dht_providers() {
+            Ok(providers) => {
+                for stored in providers {
+                    let expired = stored.expires_at_ms.is_some_and(|at| at <= now_ms);
+                    let provider = (!expired).then(|| provider_from_stored(&stored)).flatten();
+                    match provider.map(|provider| self.insert_provider(provider)) {
+                        Some(Ok(())) => loaded += 1,
+                        _ => {
+                            dropped += 1;
+                            let _ = storage.remove_dht_provider(&stored.key, &stored.provider);
+                        }
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to load DHT providers: {}", e),
+        }
+
+        debug!("Loaded {} DHT entries from storage, dropped {} expired or invalid", loaded, dropped);
+    }
+
+    /// Inserts into memory, enforcing the total size limit.
+    fn insert_record(&mut self, record: Record) -> store::Result<()> {
+        if record.value.len() > self.config.max_value_bytes {
+            return Err(store::Error::ValueTooLarge);
+        }
+
+        let replaced = self.inner.get(&record.key).map_or(0, |r| r.value.len());
+        let new_total = self.total_bytes - replaced + record.value.len();
+        if new_total > self.config.max_total_bytes {
+            return Err(store::Error::MaxRecords);
+        }
+
+        self.inner.put(record)?;
+        self.total_bytes = new_total;
+        Ok(())
+    }
+
+    fn insert_provider(&mut self, record: ProviderRecord) -> store::Result<()> {
+        let key = record.key.clone();
+        self.inner.add_provider(record)?;
+        self.provider_keys.insert(key);
+        Ok(())
+    }
+}
+
+impl RecordStore for PersistentRecordStore {
+    type RecordsIter<'a> = <MemoryStore as RecordStore>::RecordsIter<'a>;
+    type ProvidedIter<'a> = <MemoryStore as RecordStore>::ProvidedIter<'a>;
+
+    fn get(&self, k: &RecordKey) -> Option<Cow<'_, Record>> {
+        self.inner.get(k)
+    }
+
+    fn put(&mut self, r: Record) -> store::Result<()> {
+        let stored = record_to_stored(&r);
+        self.insert_record(r)?;
+
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.store_dht_record(&stored) {
+                warn!("Failed to persist DHT record: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, k: &RecordKey) {
+        if let Some(record) = self.inner.get(k) {
+            self.total_bytes -= record.value.len();
+        }
+        self.inner.remove(k);
+
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.remove_dht_record(k.as_ref()) {
+                warn!("Failed to remove persisted DHT record: {}", e);
+            }
+        }
+    }
+
+    fn records(&self) -> Self::RecordsIter<'_> {
+        self.inner.records()
+    }
+
+    fn add_provider(&mut self, record: ProviderRecord) -> store::Result<()> {
+        self.insert_provider(record.clone())?;
+
+        // The memory store silently ignores providers for full keys.
+        if let Some(storage) = &self.storage {
+            if self.inner.providers(&record.key).contains(&record) {
+                if let Err(e) = storage.store_dht_provider(&provider_to_stored(&record)) {
+                    warn!("Failed to persist DHT provider: {}", e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn providers(&self, key: &RecordKey) -> Vec<ProviderRecord> {
+        self.inner.providers(key)
+    }
+
+    fn provided(&self) -> Self::ProvidedIter<'_> {
+        self.inner.provided()
+    }
+
+    fn remove_provider(&mut self, k: &RecordKey, p: &PeerId) {
+        self.inner.remove_provider(k, p);
+        if self.inner.providers(k).is_empty() {
+            self.provider_keys.remove(k);
+        }
+
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.remove_dht_provider(k.as_ref(), &p.to_bytes()) {
+                warn!("Failed to remove persisted DHT provider: {}", e);
+            }
+        }
+    }
+}
+
+fn to_unix_ms(expires: Option<Instant>) -> Option<i64> {
+    let now = Instant::now();
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    expires.map(|at| {
+        if at >= now {
+            now_ms.saturating_add((at - now).as_millis() as i64)
+        } else {
+            now_ms.saturating_sub((now - at).as_millis() as i64)
+        }
+    })
+}
+
+fn to_instant(expires_at_ms: Option<i64>) -> Option<Instant> {
+    let now = Instant::now();
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    expires_at_ms.map(|at| {
+        let remaining = at.saturating_sub(now_ms).max(0) as u64;
+        now + Duration::from_millis(remaining)
+    })
+}
+
+fn record_to_stored(record: &Record) -> StoredDhtRecord {
+    StoredDhtRecord {
+        key: record.key.to_vec(),
+        value: record.value.clone(),
+        publisher: record.publisher.map(|p| p.to_bytes()),
+        expires_at_ms: to_unix_ms(record.expires),
+    }
+}
+
+fn record_from_stored(stored: &StoredDhtRecord) -> Option<Record> {
+    let publisher = match &stored.publisher {
+        Some(bytes) => Some(PeerId::from_bytes(bytes).ok()?),
+        None => None,
+    };
+
+    Some(Record {
+        key: RecordKey::new(&stored.key),
+        value: stored.value.clone(),
+        publisher,
+        expires: to_instant(stored.expires_at_ms),
+    })
+}
+
+fn provider_to_stored(record: &ProviderRecord) -> StoredDhtProvider {
+    StoredDhtProvider {
+        key: record.key.to_vec(),
+        provider: record.provider.to_bytes(),
+        addresses: record.addresses.iter().map(|a| a.to_vec()).collect(),
+        expires_at_ms: to_unix_ms(record.expires),
+    }
+}
+
+fn provider_from_stored(stored: &StoredDhtProvider) -> Option<ProviderRecord> {
+    Some(ProviderRecord {
+        key: RecordKey::new(&stored.key),
+        provider: PeerId::from_bytes(&stored.provider).ok()?,
+        expires: to_instant(stored.expires_at_ms),
+        addresses: stored.addresses.iter()
+            .filter_map(|bytes| Multiaddr::try_from(bytes.clone()).ok())
+            .collect(),
+    })
+}
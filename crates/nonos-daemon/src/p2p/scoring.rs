@@ -0,0 +1,127 @@
+//! Gossipsub peer scoring and its link to the `PeerStore` reputation.
+//!
+//! Each topic is scored on first message deliveries, invalid messages and,
+//! for the health beacon topic that every node publishes to, mesh delivery
+//! deficits. The peer store feeds in through the application-specific
+//! score, so locally banned peers fall below the graylist threshold; in the
+//! other direction a peer whose own gossip behaviour drags it below the
+//! graylist threshold is penalised in the peer store.
+
+use super::peer_store::{PeerEntry, MAX_PENALTY_SCORE};
+use super::topics;
+use libp2p::gossipsub::{
+    score_parameter_decay, IdentTopic, PeerScoreParams, PeerScoreThresholds, TopicScoreParams,
+};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+pub const GOSSIP_THRESHOLD: f64 = -10.0;
+pub const PUBLISH_THRESHOLD: f64 = -50.0;
+pub const GRAYLIST_THRESHOLD: f64 = -80.0;
+pub const ACCEPT_PX_THRESHOLD: f64 = 10.0;
+pub const OPPORTUNISTIC_GRAFT_THRESHOLD: f64 = 5.0;
+
+pub const APP_SPECIFIC_WEIGHT: f64 = 1.0;
+/// Application score of a banned peer, low enough to graylist it alone.
+pub const BANNED_APPLICATION_SCORE: f64 = GRAYLIST_THRESHOLD - 20.0;
+/// Application score of a sidelined peer: below the publish threshold, so
+/// we stop sending it our own messages, but still above graylisting.
+pub const SIDELINED_APPLICATION_SCORE: f64 = PUBLISH_THRESHOLD - 10.0;
+/// Worst application score reachable through penalties short of a ban.
+const MAX_PENALTY_APPLICATION_SCORE: f64 = -40.0;
+
+/// How often gossipsub and peer store scores are synchronised.
+pub const SCORE_SYNC_INTERVAL: Duration = Duration::from_secs(30);
+
+const TOPIC_SCORE_CAP: f64 = 50.0;
+
+pub fn peer_score_thresholds() -> PeerScoreThresholds {
+    PeerScoreThresholds {
+        gossip_threshold: GOSSIP_THRESHOLD,
+        publish_threshold: PUBLISH_THRESHOLD,
+        graylist_threshold: GRAYLIST_THRESHOLD,
+        accept_px_threshold: ACCEPT_PX_THRESHOLD,
+        opportunistic_graft_threshold: OPPORTUNISTIC_GRAFT_THRESHOLD,
+    }
+}
+
+pub fn peer_score_params() -> PeerScoreParams {
+    let topics: HashMap<_, _> = [
+        (topics::NODE_ANNOUNCEMENTS, topic_params(1.0, false)),
+        (topics::HEALTH_BEACON, topic_params(0.5, true)),
+        (topics::QUALITY_REPORTS, topic_params(0.5, false)),
+        (topics::PRIVACY_COORD, topic_params(0.5, false)),
+        (topics::FILTER_LISTS, topic_params(0.25, false)),
+        (topics::PRIVACY_ORACLE, topic_params(0.25, false)),
+        (topics::PEER_DISCOVERY, topic_params(0.25, false)),
+    ]
+    .into_iter()
+    .map(|(topic, params)| (IdentTopic::new(topic).hash(), params))
+    .collect();
+
+    PeerScoreParams {
+        topics,
+        topic_score_cap: TOPIC_SCORE_CAP,
+        app_specific_weight: APP_SPECIFIC_WEIGHT,
+        ip_colocation_factor_weight: -5.0,
+        ip_colocation_factor_threshold: 10.0,
+        ip_colocation_factor_whitelist: HashSet::new(),
+        behaviour_penalty_weight: -10.0,
+        behaviour_penalty_threshold: 6.0,
+        behaviour_penalty_decay: score_parameter_decay(Duration::from_secs(600)),
+        ..Default::default()
+    }
+}
+
+/// Score parameters for one topic. `track_mesh_deliveries` penalises mesh
+/// peers that stop forwarding; only worth enabling on topics with steady
+/// traffic, or quiet periods would count against honest peers.
+fn topic_params(topic_weight: f64, track_mesh_deliveries: bool) -> TopicScoreParams {
+    let (mesh_weight, mesh_threshold) = if track_mesh_deliveries { (-5.0, 1.0) } else { (0.0, 0.0) };
+    let mesh_decay = score_parameter_decay(Duration::from_secs(600));
+
+    TopicScoreParams {
+        topic_weight,
+        time_in_mesh_weight: 0.1,
+        time_in_mesh_quantum: Duration::from_secs(60),
+        time_in_mesh_cap: 60.0,
+        first_message_deliveries_weight: 1.0,
+        first_message_deliveries_decay: score_parameter_decay(Duration::from_secs(600)),
+        first_message_deliveries_cap: 10.0,
+        mesh_message_deliveries_weight: mesh_weight,
+        mesh_message_deliveries_decay: mesh_decay,
+        mesh_message_deliveries_cap: 20.0,
+        mesh_message_deliveries_threshold: mesh_threshold,
+        mesh_message_deliveries_window: Duration::from_secs(2),
+        mesh_message_deliveries_activation: Duration::from_secs(300),
+        mesh_failure_penalty_weight: mesh_weight,
+        mesh_failure_penalty_decay: mesh_decay,
+        invalid_message_deliveries_weight: -20.0,
+        invalid_message_deliveries_decay: score_parameter_decay(Duration::from_secs(3600)),
+    }
+}
+
+/// The peer store's view of a peer, expressed as a gossipsub
+/// application-specific score.
+pub fn application_score(entry: &PeerEntry) -> f64 {
+    if entry.is_banned() {
+        return BANNED_APPLICATION_SCORE;
+    }
+    if entry.is_sidelined() {
+        return SIDELINED_APPLICATION_SCORE;
+    }
+    let penalty = entry.penalty_score.clamp(0, MAX_PENALTY_SCORE) as f64 / MAX_PENALTY_SCORE as f64;
+    penalty * MAX_PENALTY_APPLICATION_SCORE
+}
+
+/// The part of a gossipsub score earned on the wire, i.e. without the
+/// application score we supplied. Penalising on the full score would let a
+/// peer store penalty feed back into itself.
+pub fn protocol_score(gossipsub_score: f64, application_score: f64) -> f64 {
+    gossipsub_score - application_score * APP_SPECIFIC_WEIGHT
+}
+
+/// Whether a peer's own gossip behaviour warrants a peer store penalty.
+pub fn should_penalize(protocol_score: f64) -> bool {
+    protocol_score < GRAYLIST_THRESHOLD
+}
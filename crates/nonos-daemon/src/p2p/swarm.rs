@@ -1,19 +1,41 @@
 use super::behaviour::{NonosBehaviour, NonosBehaviourEvent};
-use super::network::{extract_peer_id, get_bootstrap_nodes, NetworkConfig};
+use super::envelope::{verify_envelope, ReplayGuard};
+use super::messages::{
+    FilterListChunkData, LatencyAttestationData, NodeAnnouncementData, OracleVoteData, P2pMessage,
+};
+use super::mixnet::{prune_mix_routes, MixRoute, MixRoutes, MixnetAck, MIX_ROUTE_TTL};
+use super::nat::RelayManager;
+use super::network::{
+    address_transport, extract_peer_id, get_bootstrap_nodes, AddressTransport, NetworkConfig,
+};
+use super::peer_store::{PenaltyReason, SharedPeerStore};
+use super::pir::{PirExchange, ServedPirDatabase};
+use super::probe::{NodePeers, ProbeNonce, ProbeTracker};
+use super::scoring::{
+    application_score, protocol_score, should_penalize, BANNED_APPLICATION_SCORE,
+    SCORE_SYNC_INTERVAL,
+};
+use super::topics;
+use super::vault::{VaultExchange, VaultShareStore};
 use super::types::{
     BanEntry, MessageViolation, NetworkCommand, NetworkEvent, NetworkStats, PeerInfo,
-    RateLimitReason, RateLimiter,
+    RateLimitReason, RateLimiter, Reachability,
 };
+use crate::privacy::{MixNode, SphinxPacket, SPHINX_PACKET_SIZE};
 use futures::StreamExt;
+use libp2p::kad::store::RecordStore;
 use libp2p::{
-    gossipsub, identify, kad, ping,
-    swarm::SwarmEvent,
+    autonat, dcutr, gossipsub, identify, kad, ping, relay, request_response,
+    swarm::{dial_opts::DialOpts, SwarmEvent},
     Multiaddr, PeerId, Swarm,
 };
+use nonos_types::{NodeId, NonosError};
 use parking_lot::RwLock;
 use std::collections::HashMap;
+use std::num::NonZeroU8;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
@@ -22,19 +44,48 @@ const DEFAULT_BYTES_PER_SEC: u64 = 1024 * 1024;
 const MAX_MESSAGE_SIZE: usize = 64 * 1024;
 const SPAM_MESSAGE_THRESHOLD: u64 = 1000;
 const SPAM_TIME_WINDOW_SECS: i64 = 60;
+/// Lets bootstrap fill the routing table before stored records are re-published.
+const DHT_REPUBLISH_DELAY: Duration = Duration::from_secs(120);
+const DHT_PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+const MIX_ROUTE_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Handles and channels the swarm loop shares with the rest of the node.
+pub(crate) struct SwarmContext {
+    pub event_tx: mpsc::Sender<NetworkEvent>,
+    pub peers: Arc<RwLock<HashMap<PeerId, PeerInfo>>>,
+    pub banned_peers: Arc<RwLock<HashMap<PeerId, BanEntry>>>,
+    pub stats: Arc<NetworkStats>,
+    pub running: Arc<AtomicBool>,
+    pub rate_limiters: Arc<RwLock<HashMap<PeerId, RateLimiter>>>,
+    pub config: NetworkConfig,
+    pub mix_routes: MixRoutes,
+    pub mix_packet_tx: mpsc::Sender<SphinxPacket>,
+    pub peer_store: SharedPeerStore,
+    pub node_peers: NodePeers,
+    pub attestation_tx: mpsc::Sender<LatencyAttestationData>,
+    pub filter_list_tx: mpsc::Sender<FilterListChunkData>,
+    pub oracle_vote_tx: mpsc::Sender<OracleVoteData>,
+}
+
+/// Per-protocol state owned by the swarm loop.
+struct SwarmState {
+    global_rate_limiter: RateLimiter,
+    replay_guard: ReplayGuard,
+    relays: RelayManager,
+    probes: ProbeTracker,
+    pir: PirExchange,
+    vault: VaultExchange,
+}
 
 pub(crate) async fn run_swarm(
     mut swarm: Swarm<NonosBehaviour>,
     mut command_rx: mpsc::Receiver<NetworkCommand>,
-    event_tx: mpsc::Sender<NetworkEvent>,
-    peers: Arc<RwLock<HashMap<PeerId, PeerInfo>>>,
-    banned_peers: Arc<RwLock<HashMap<PeerId, BanEntry>>>,
-    stats: Arc<NetworkStats>,
-    running: Arc<AtomicBool>,
-    port: u16,
-    rate_limiters: Arc<RwLock<HashMap<PeerId, RateLimiter>>>,
-    config: NetworkConfig,
+    ctx: SwarmContext,
+    pir_database: ServedPirDatabase,
+    vault_shares: Arc<VaultShareStore>,
 ) {
+    let config = &ctx.config;
+    let port = config.port;
     let listen_addr: Multiaddr = format!("/ip4/0.0.0.0/tcp/{}", port)
         .parse()
         .expect("Valid listen address");
@@ -45,6 +96,18 @@ pub(crate) async fn run_swarm(
     }
     info!("Listening on {}", listen_addr);
 
+    if config.enable_quic {
+        let quic_addr: Multiaddr = format!("/ip4/0.0.0.0/udp/{}/quic-v1", config.quic_port())
+            .parse()
+            .expect("Valid QUIC listen address");
+
+        // TCP alone is enough to take part in the network.
+        match swarm.listen_on(quic_addr.clone()) {
+            Ok(_) => info!("Listening on {}", quic_addr),
+            Err(e) => warn!("Failed to listen on {}: {}", quic_addr, e),
+        }
+    }
+
     let bootstrap_nodes = if !config.custom_bootstrap_nodes.is_empty() {
         config.custom_bootstrap_nodes.clone()
     } else {
@@ -53,47 +116,74 @@ pub(crate) async fn run_swarm(
 
     for addr_str in bootstrap_nodes {
         if let Ok(addr) = addr_str.parse::<Multiaddr>() {
+            if !config.enable_quic && address_transport(&addr) == AddressTransport::Quic {
+                continue;
+            }
             if let Some(peer_id) = extract_peer_id(&addr) {
+                swarm.behaviour_mut().autonat.add_server(peer_id, Some(addr.clone()));
                 swarm.behaviour_mut().kademlia.add_address(&peer_id, addr);
             }
         }
     }
 
-    let mut global_rate_limiter = RateLimiter::new(
-        config.messages_per_sec,
-        config.bytes_per_sec,
+    let (pir_answer_tx, mut pir_answer_rx) = mpsc::unbounded_channel();
+    let (vault_answer_tx, mut vault_answer_rx) = mpsc::unbounded_channel();
+    let mut state = SwarmState {
+        global_rate_limiter: RateLimiter::new(config.messages_per_sec, config.bytes_per_sec),
+        replay_guard: ReplayGuard::new(),
+        relays: RelayManager::new(),
+        probes: ProbeTracker::new(),
+        pir: PirExchange::new(pir_database, pir_answer_tx),
+        vault: VaultExchange::new(vault_shares, vault_answer_tx),
+    };
+
+    let mut dht_republish = tokio::time::interval_at(
+        tokio::time::Instant::now() + DHT_REPUBLISH_DELAY,
+        config.record_store.publication_interval,
     );
+    let mut dht_prune = tokio::time::interval(DHT_PRUNE_INTERVAL);
+    let mut score_sync = tokio::time::interval(SCORE_SYNC_INTERVAL);
+    let mut mix_route_prune = tokio::time::interval(MIX_ROUTE_PRUNE_INTERVAL);
 
     loop {
         tokio::select! {
             Some(cmd) = command_rx.recv() => {
-                handle_command(
-                    cmd,
-                    &mut swarm,
-                    &peers,
-                    &banned_peers,
-                    &stats,
-                    &running,
-                    &rate_limiters,
-                    &mut global_rate_limiter,
-                ).await;
-
-                if !running.load(Ordering::Relaxed) {
+                handle_command(cmd, &mut swarm, &ctx, &mut state).await;
+
+                if !ctx.running.load(Ordering::Relaxed) {
                     break;
                 }
             }
 
+            _ = dht_republish.tick() => {
+                republish_local_records(&mut swarm);
+            }
+
+            _ = dht_prune.tick() => {
+                swarm.behaviour_mut().kademlia.store_mut().prune_expired();
+            }
+
+            _ = score_sync.tick() => {
+                sync_peer_scores(&mut swarm, &ctx.peer_store, &ctx.banned_peers, &ctx.stats);
+            }
+
+            _ = mix_route_prune.tick() => {
+                let pruned = prune_mix_routes(&ctx.mix_routes, MIX_ROUTE_TTL);
+                if pruned > 0 {
+                    debug!("Forgot {} mix nodes that stopped announcing", pruned);
+                }
+            }
+
+            Some((channel, response)) = pir_answer_rx.recv() => {
+                let _ = swarm.behaviour_mut().pir.send_response(channel, response);
+            }
+
+            Some((channel, response)) = vault_answer_rx.recv() => {
+                let _ = swarm.behaviour_mut().vault.send_response(channel, response);
+            }
+
             event = swarm.select_next_some() => {
-                handle_swarm_event(
-                    event,
-                    &event_tx,
-                    &peers,
-                    &banned_peers,
-                    &stats,
-                    &mut swarm,
-                    &rate_limiters,
-                    &config,
-                ).await;
+                handle_swarm_event(event, &mut swarm, &ctx, &mut state).await;
             }
         }
     }
@@ -104,17 +194,16 @@ pub(crate) async fn run_swarm(
 async fn handle_command(
     cmd: NetworkCommand,
     swarm: &mut Swarm<NonosBehaviour>,
-    peers: &Arc<RwLock<HashMap<PeerId, PeerInfo>>>,
-    banned_peers: &Arc<RwLock<HashMap<PeerId, BanEntry>>>,
-    stats: &Arc<NetworkStats>,
-    running: &Arc<AtomicBool>,
-    rate_limiters: &Arc<RwLock<HashMap<PeerId, RateLimiter>>>,
-    global_rate_limiter: &mut RateLimiter,
+    ctx: &SwarmContext,
+    state: &mut SwarmState,
 ) {
+    let SwarmContext { peers, banned_peers, stats, running, rate_limiters, config, .. } = ctx;
+    let SwarmState { global_rate_limiter, probes, pir, vault, .. } = state;
+
     match cmd {
         NetworkCommand::Connect(addr) => {
             if let Some(peer_id) = extract_peer_id(&addr) {
-                if is_banned(&banned_peers, &peer_id) {
+                if is_banned(banned_peers, &peer_id) {
                     warn!("Refusing to connect to banned peer: {}", peer_id);
                     return;
                 }
@@ -128,6 +217,26 @@ async fn handle_command(
             }
         }
 
+        NetworkCommand::Dial { peer, addresses } => {
+            if is_banned(banned_peers, &peer) {
+                warn!("Refusing to connect to banned peer: {}", peer);
+                return;
+            }
+
+            stats.connection_attempts.fetch_add(1, Ordering::Relaxed);
+
+            let mut opts = DialOpts::peer_id(peer).addresses(addresses);
+            // Addresses arrive ranked QUIC first; dialling them one at a
+            // time only falls back to TCP once QUIC has failed.
+            if config.prefer_quic {
+                opts = opts.override_dial_concurrency_factor(NonZeroU8::MIN);
+            }
+            if let Err(e) = swarm.dial(opts.build()) {
+                warn!("Failed to dial {}: {}", peer, e);
+                stats.connection_failures.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
         NetworkCommand::Disconnect(peer) => {
             let _ = swarm.disconnect_peer_id(peer);
             peers.write().remove(&peer);
@@ -170,7 +279,7 @@ async fn handle_command(
         }
 
         NetworkCommand::AddAddress(peer, addr) => {
-            if is_banned(&banned_peers, &peer) {
+            if is_banned(banned_peers, &peer) {
                 return;
             }
             swarm.behaviour_mut().kademlia.add_address(&peer, addr);
@@ -184,10 +293,19 @@ async fn handle_command(
             }
         }
 
+        NetworkCommand::PutRecord { key, value } => {
+            let mut record = kad::Record::new(key, value);
+            record.publisher = Some(*swarm.local_peer_id());
+            if let Err(e) = swarm.behaviour_mut().kademlia.put_record(record, kad::Quorum::One) {
+                warn!("Failed to put DHT record: {:?}", e);
+            }
+        }
+
         NetworkCommand::BanPeer(peer, _duration) => {
             let _ = swarm.disconnect_peer_id(peer);
             peers.write().remove(&peer);
             rate_limiters.write().remove(&peer);
+            swarm.behaviour_mut().gossipsub.set_application_score(&peer, BANNED_APPLICATION_SCORE);
             swarm.behaviour_mut().gossipsub.blacklist_peer(&peer);
         }
 
@@ -200,6 +318,47 @@ async fn handle_command(
             debug!("Updated global rate limit: {} msg/s, {} bytes/s", messages_per_sec, bytes_per_sec);
         }
 
+        NetworkCommand::SendMixPacket { peer, packet } => {
+            if is_banned(banned_peers, &peer) {
+                warn!("Refusing to send mix packet to banned peer: {}", peer);
+                return;
+            }
+
+            swarm.behaviour_mut().mixnet.send_request(&peer, packet);
+            stats.bytes_sent.fetch_add(SPHINX_PACKET_SIZE as u64, Ordering::Relaxed);
+        }
+
+        NetworkCommand::Probe { peer, reply } => {
+            if is_banned(banned_peers, &peer) {
+                let _ = reply.send(None);
+                return;
+            }
+
+            let nonce = ProbeNonce::random();
+            let request = swarm.behaviour_mut().probe.send_request(&peer, nonce);
+            probes.sent(request, nonce, reply);
+        }
+
+        NetworkCommand::Pir { peer, request, reply } => {
+            if is_banned(banned_peers, &peer) {
+                let _ = reply.send(Err(NonosError::Network(format!("Peer {} is banned", peer))));
+                return;
+            }
+
+            let request = swarm.behaviour_mut().pir.send_request(&peer, request);
+            pir.sent(request, reply);
+        }
+
+        NetworkCommand::Vault { peer, request, reply } => {
+            if is_banned(banned_peers, &peer) {
+                let _ = reply.send(Err(NonosError::Network(format!("Peer {} is banned", peer))));
+                return;
+            }
+
+            let request = swarm.behaviour_mut().vault.send_request(&peer, request);
+            vault.sent(request, reply);
+        }
+
         NetworkCommand::Shutdown => {
             info!("Received shutdown command");
             running.store(false, Ordering::Relaxed);
@@ -209,21 +368,35 @@ async fn handle_command(
 
 async fn handle_swarm_event(
     event: SwarmEvent<NonosBehaviourEvent>,
-    event_tx: &mpsc::Sender<NetworkEvent>,
-    peers: &Arc<RwLock<HashMap<PeerId, PeerInfo>>>,
-    banned_peers: &Arc<RwLock<HashMap<PeerId, BanEntry>>>,
-    stats: &Arc<NetworkStats>,
     swarm: &mut Swarm<NonosBehaviour>,
-    rate_limiters: &Arc<RwLock<HashMap<PeerId, RateLimiter>>>,
-    config: &NetworkConfig,
+    ctx: &SwarmContext,
+    state: &mut SwarmState,
 ) {
+    let SwarmContext {
+        event_tx,
+        peers,
+        banned_peers,
+        stats,
+        rate_limiters,
+        config,
+        mix_routes,
+        mix_packet_tx,
+        peer_store,
+        node_peers,
+        attestation_tx,
+        filter_list_tx,
+        oracle_vote_tx,
+        ..
+    } = ctx;
+    let SwarmState { replay_guard, relays, probes, pir, vault, .. } = state;
+
     match event {
         SwarmEvent::NewListenAddr { address, .. } => {
             info!("Listening on {}", address);
         }
 
         SwarmEvent::ConnectionEstablished { peer_id, endpoint, num_established, .. } => {
-            if is_banned(&banned_peers, &peer_id) {
+            if is_banned(banned_peers, &peer_id) {
                 warn!("Disconnecting banned peer that connected: {}", peer_id);
                 let _ = swarm.disconnect_peer_id(peer_id);
                 return;
@@ -249,9 +422,21 @@ async fn handle_swarm_event(
             let _ = event_tx.send(NetworkEvent::PeerConnected(peer_id)).await;
         }
 
+        SwarmEvent::ListenerClosed { listener_id, reason, .. } => {
+            if let Some(relay) = relays.listener_closed(listener_id) {
+                info!("Relay circuit listener via {} closed: {:?}", relay, reason);
+                stats.relay_reservations.store(relays.reservation_count() as u64, Ordering::Relaxed);
+
+                if stats.reachability() == Reachability::Private {
+                    reserve_relays(swarm, relays);
+                }
+            }
+        }
+
         SwarmEvent::ConnectionClosed { peer_id, num_established, cause, .. } => {
             if num_established == 0 {
                 info!("Disconnected from peer: {} (cause: {:?})", peer_id, cause);
+                relays.remove_candidate(&peer_id);
                 peers.write().remove(&peer_id);
                 rate_limiters.write().remove(&peer_id);
                 stats.peer_count.fetch_sub(1, Ordering::Relaxed);
@@ -280,9 +465,10 @@ async fn handle_swarm_event(
             message,
             message_id,
         })) => {
-            if is_banned(&banned_peers, &propagation_source) {
+            if is_banned(banned_peers, &propagation_source) {
                 debug!("Ignoring message from banned peer: {}", propagation_source);
                 stats.messages_dropped.fetch_add(1, Ordering::Relaxed);
+                report_validation(swarm, &message_id, &propagation_source, gossipsub::MessageAcceptance::Ignore);
                 return;
             }
 
@@ -294,6 +480,7 @@ async fn handle_swarm_event(
                     propagation_source, message.data.len(), MAX_MESSAGE_SIZE
                 );
                 stats.messages_dropped.fetch_add(1, Ordering::Relaxed);
+                report_validation(swarm, &message_id, &propagation_source, gossipsub::MessageAcceptance::Reject);
 
                 let should_ban = {
                     let mut peers_lock = peers.write();
@@ -338,6 +525,7 @@ async fn handle_swarm_event(
                 if let Err(reason) = rate_limit_result {
                     stats.rate_limit_hits.fetch_add(1, Ordering::Relaxed);
                     stats.messages_dropped.fetch_add(1, Ordering::Relaxed);
+                    report_validation(swarm, &message_id, &propagation_source, gossipsub::MessageAcceptance::Ignore);
 
                     let should_ban = {
                         let mut peers_lock = peers.write();
@@ -417,17 +605,200 @@ async fn handle_swarm_event(
                     banned_peers.write().insert(propagation_source, ban);
                     stats.banned_peers.fetch_add(1, Ordering::Relaxed);
                     let _ = swarm.disconnect_peer_id(propagation_source);
+                    report_validation(swarm, &message_id, &propagation_source, gossipsub::MessageAcceptance::Ignore);
                     return;
                 }
             }
 
+            let author = message.source.unwrap_or(propagation_source);
+            let now_ms = chrono::Utc::now().timestamp_millis();
+            let (signer, p2p_message) = match verify_envelope(&message.data, &author, replay_guard, now_ms) {
+                Ok(verified) => verified,
+                Err(e) => {
+                    warn!("Rejected message {} from {}: {}", message_id, author, e);
+                    stats.messages_dropped.fetch_add(1, Ordering::Relaxed);
+                    report_validation(swarm, &message_id, &propagation_source, gossipsub::MessageAcceptance::Reject);
+                    penalize_author(peer_store, banned_peers, stats, swarm, author, e.penalty());
+                    return;
+                }
+            };
+            report_validation(swarm, &message_id, &propagation_source, gossipsub::MessageAcceptance::Accept);
+
+            // The envelope was sealed for `author`, so the signer runs that
+            // peer and probes, PIR queries and vault shares can be sent to it.
+            if p2p_message.claimed_node_id().is_some() {
+                node_peers.write().insert(signer, author);
+            }
+
+            match p2p_message {
+                P2pMessage::NodeAnnouncement(ref announcement) if topic == topics::NODE_ANNOUNCEMENTS => {
+                    record_mix_route(mix_routes, signer, author, announcement);
+                }
+                P2pMessage::LatencyAttestation(ref attestation)
+                    if topic == topics::QUALITY_REPORTS && attestation_tx.try_send(attestation.clone()).is_err() =>
+                {
+                    debug!("Attestation queue full, dropping attestation from {}", signer);
+                }
+                P2pMessage::FilterListChunk(ref chunk)
+                    if topic == topics::FILTER_LISTS && filter_list_tx.try_send(chunk.clone()).is_err() =>
+                {
+                    debug!("Filter list queue full, dropping chunk from {}", author);
+                }
+                P2pMessage::OracleVote(ref vote)
+                    if topic == topics::PRIVACY_ORACLE && oracle_vote_tx.try_send((**vote).clone()).is_err() =>
+                {
+                    debug!("Oracle vote queue full, dropping vote from {}", signer);
+                }
+                _ => {}
+            }
+
             let _ = event_tx.send(NetworkEvent::Message {
                 topic,
                 source: propagation_source,
-                data: message.data,
+                signer,
+                message: p2p_message,
             }).await;
         }
 
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Mixnet(request_response::Event::Message {
+            peer,
+            message: request_response::Message::Request { request, channel, .. },
+        })) => {
+            if is_banned(banned_peers, &peer) {
+                stats.messages_dropped.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+
+            let _ = swarm.behaviour_mut().mixnet.send_response(channel, MixnetAck);
+
+            if config.enable_rate_limiting {
+                let rate_limit_result = rate_limiters.write()
+                    .entry(peer)
+                    .or_insert_with(|| RateLimiter::new(DEFAULT_MESSAGES_PER_SEC, DEFAULT_BYTES_PER_SEC))
+                    .check_message(SPHINX_PACKET_SIZE as u64);
+
+                if let Err(reason) = rate_limit_result {
+                    stats.rate_limit_hits.fetch_add(1, Ordering::Relaxed);
+                    stats.messages_dropped.fetch_add(1, Ordering::Relaxed);
+                    let _ = event_tx.send(NetworkEvent::RateLimited { peer, reason }).await;
+                    return;
+                }
+            }
+
+            stats.messages_received.fetch_add(1, Ordering::Relaxed);
+            stats.bytes_received.fetch_add(SPHINX_PACKET_SIZE as u64, Ordering::Relaxed);
+
+            if mix_packet_tx.try_send(request).is_err() {
+                debug!("Mix packet queue full, dropping packet from {}", peer);
+                stats.messages_dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Mixnet(request_response::Event::Message {
+            peer,
+            message: request_response::Message::Response { .. },
+        })) => {
+            debug!("Mix packet delivered to {}", peer);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Mixnet(request_response::Event::OutboundFailure {
+            peer,
+            error,
+            ..
+        })) => {
+            warn!("Failed to deliver mix packet to {}: {}", peer, error);
+
+            if let Some(info) = peers.write().get_mut(&peer) {
+                info.record_failure();
+            }
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Mixnet(request_response::Event::InboundFailure {
+            peer,
+            error,
+            ..
+        })) => {
+            debug!("Inbound mix packet from {} failed: {}", peer, error);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Probe(request_response::Event::Message {
+            peer,
+            message: request_response::Message::Request { request, channel, .. },
+        })) => {
+            if is_banned(banned_peers, &peer) {
+                return;
+            }
+            let _ = swarm.behaviour_mut().probe.send_response(channel, request);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Probe(request_response::Event::Message {
+            message: request_response::Message::Response { request_id, response },
+            ..
+        })) => {
+            probes.answered(request_id, response);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Probe(request_response::Event::OutboundFailure {
+            peer,
+            request_id,
+            error,
+        })) => {
+            debug!("Probe to {} failed: {}", peer, error);
+            probes.failed(request_id);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Pir(request_response::Event::Message {
+            peer,
+            message: request_response::Message::Request { request, channel, .. },
+        })) => {
+            if is_banned(banned_peers, &peer) {
+                return;
+            }
+            pir.serve(channel, request);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Pir(request_response::Event::Message {
+            message: request_response::Message::Response { request_id, response },
+            ..
+        })) => {
+            pir.answered(request_id, response);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Pir(request_response::Event::OutboundFailure {
+            peer,
+            request_id,
+            error,
+        })) => {
+            debug!("PIR request to {} failed: {}", peer, error);
+            pir.failed(request_id, error.to_string());
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Vault(request_response::Event::Message {
+            peer,
+            message: request_response::Message::Request { request, channel, .. },
+        })) => {
+            if is_banned(banned_peers, &peer) {
+                return;
+            }
+            vault.serve(peer, channel, request);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Vault(request_response::Event::Message {
+            message: request_response::Message::Response { request_id, response },
+            ..
+        })) => {
+            vault.answered(request_id, response);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Vault(request_response::Event::OutboundFailure {
+            peer,
+            request_id,
+            error,
+        })) => {
+            debug!("Vault request to {} failed: {}", peer, error);
+            vault.failed(request_id, error.to_string());
+        }
+
         SwarmEvent::Behaviour(NonosBehaviourEvent::Gossipsub(gossipsub::Event::Subscribed {
             peer_id,
             topic,
@@ -454,7 +825,7 @@ async fn handle_swarm_event(
             is_new_peer,
             ..
         })) => {
-            if is_banned(&banned_peers, &peer) {
+            if is_banned(banned_peers, &peer) {
                 return;
             }
 
@@ -528,11 +899,22 @@ async fn handle_swarm_event(
                 peer_info.addresses = info.listen_addrs.iter().map(|a| a.to_string()).collect();
             }
 
-            for addr in info.listen_addrs {
-                if !is_banned(&banned_peers, &peer_id) {
-                    swarm.behaviour_mut().kademlia.add_address(&peer_id, addr);
+            if is_banned(banned_peers, &peer_id) {
+                return;
+            }
+
+            if info.protocols.contains(&relay::HOP_PROTOCOL_NAME)
+                && relays.add_candidate(peer_id, &info.listen_addrs)
+            {
+                debug!("Peer {} offers circuit relaying", peer_id);
+                if stats.reachability() == Reachability::Private {
+                    reserve_relays(swarm, relays);
                 }
             }
+
+            for addr in info.listen_addrs {
+                swarm.behaviour_mut().kademlia.add_address(&peer_id, addr);
+            }
         }
 
         SwarmEvent::Behaviour(NonosBehaviourEvent::Identify(identify::Event::Sent { peer_id, .. })) => {
@@ -547,10 +929,178 @@ async fn handle_swarm_event(
             debug!("Identify error with {}: {:?}", peer_id, error);
         }
 
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Autonat(autonat::Event::StatusChanged { old, new })) => {
+            let reachability = Reachability::from(&new);
+            let public_address = match &new {
+                autonat::NatStatus::Public(addr) => Some(addr.to_string()),
+                _ => None,
+            };
+            info!("NAT status changed from {:?} to {:?}", old, new);
+            stats.set_reachability(reachability, public_address);
+
+            match reachability {
+                Reachability::Private => reserve_relays(swarm, relays),
+                Reachability::Public => {
+                    for listener in relays.drain_listeners() {
+                        swarm.remove_listener(listener);
+                    }
+                    stats.relay_reservations.store(0, Ordering::Relaxed);
+                }
+                Reachability::Unknown => {}
+            }
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Autonat(event)) => {
+            debug!("AutoNAT: {:?}", event);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::RelayClient(relay::client::Event::ReservationReqAccepted {
+            relay_peer_id,
+            ..
+        })) if relays.accepted(relay_peer_id) => {
+            info!("Reserved relay circuit via {}", relay_peer_id);
+            stats.relay_reservations.store(relays.reservation_count() as u64, Ordering::Relaxed);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::RelayClient(event)) => {
+            debug!("Relay client: {:?}", event);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Relay(relay::Event::CircuitReqAccepted {
+            src_peer_id,
+            dst_peer_id,
+        })) => {
+            debug!("Relaying circuit from {} to {}", src_peer_id, dst_peer_id);
+            stats.relayed_circuits.fetch_add(1, Ordering::Relaxed);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Relay(relay::Event::CircuitClosed {
+            src_peer_id,
+            dst_peer_id,
+            ..
+        })) => {
+            debug!("Relayed circuit from {} to {} closed", src_peer_id, dst_peer_id);
+            let _ = stats.relayed_circuits.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Relay(event)) => {
+            debug!("Relay server: {:?}", event);
+        }
+
+        SwarmEvent::Behaviour(NonosBehaviourEvent::Dcutr(dcutr::Event { remote_peer_id, result })) => {
+            match result {
+                Ok(_) => {
+                    info!("Hole punched direct connection to {}", remote_peer_id);
+                    stats.hole_punch_successes.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => {
+                    debug!("Hole punch to {} failed: {}", remote_peer_id, e);
+                    stats.hole_punch_failures.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
         _ => {}
     }
 }
 
+/// Listens on circuit addresses through known relays until the node holds
+/// enough reservations to be reachable while behind NAT.
+fn reserve_relays(swarm: &mut Swarm<NonosBehaviour>, relays: &mut RelayManager) {
+    let wanted = relays.wanted_circuits();
+    if wanted.is_empty() && relays.reservation_count() == 0 {
+        debug!("Behind NAT with no relay candidates among {} known", relays.candidate_count());
+    }
+
+    for (relay, circuit) in wanted {
+        match swarm.listen_on(circuit.clone()) {
+            Ok(listener) => {
+                debug!("Requesting relay reservation on {}", circuit);
+                relays.listening(listener, relay);
+            }
+            Err(e) => warn!("Failed to listen via relay {}: {}", relay, e),
+        }
+    }
+}
+
+/// Re-publishes the records and provider entries this node originated,
+/// refreshing their TTL across the network.
+fn republish_local_records(swarm: &mut Swarm<NonosBehaviour>) {
+    let local_peer_id = *swarm.local_peer_id();
+    let kademlia = &mut swarm.behaviour_mut().kademlia;
+
+    let store = kademlia.store_mut();
+    let records: Vec<kad::Record> = store.records()
+        .filter(|record| record.publisher == Some(local_peer_id))
+        .map(|record| kad::Record { expires: None, ..record.into_owned() })
+        .collect();
+    let provided: Vec<kad::RecordKey> = store.provided()
+        .map(|provider| provider.key.clone())
+        .collect();
+
+    if records.is_empty() && provided.is_empty() {
+        return;
+    }
+    info!("Re-publishing {} DHT records and {} provider keys", records.len(), provided.len());
+
+    for record in records {
+        if let Err(e) = kademlia.put_record(record, kad::Quorum::One) {
+            warn!("Failed to re-publish DHT record: {:?}", e);
+        }
+    }
+    for key in provided {
+        if let Err(e) = kademlia.start_providing(key) {
+            warn!("Failed to re-announce DHT provider key: {:?}", e);
+        }
+    }
+}
+
+/// Pushes peer store reputation into gossipsub as the application score,
+/// and records the resulting gossipsub scores in the peer store. Peers
+/// whose own gossip behaviour puts them below the graylist threshold are
+/// penalised.
+fn sync_peer_scores(
+    swarm: &mut Swarm<NonosBehaviour>,
+    peer_store: &SharedPeerStore,
+    banned_peers: &Arc<RwLock<HashMap<PeerId, BanEntry>>>,
+    stats: &Arc<NetworkStats>,
+) {
+    let connected: Vec<PeerId> = swarm.connected_peers().copied().collect();
+
+    for peer in connected {
+        let entry = peer_store.get_or_create(peer);
+        let app_score = if is_banned(banned_peers, &peer) {
+            BANNED_APPLICATION_SCORE
+        } else {
+            application_score(&entry)
+        };
+        swarm.behaviour_mut().gossipsub.set_application_score(&peer, app_score);
+
+        let Some(score) = swarm.behaviour().gossipsub.peer_score(&peer) else {
+            continue;
+        };
+        peer_store.update(&peer, |entry| entry.gossipsub_score = Some(score));
+
+        if should_penalize(protocol_score(score, app_score)) && !is_banned(banned_peers, &peer) {
+            debug!("Peer {} has a gossipsub score of {:.2}", peer, score);
+            penalize_author(peer_store, banned_peers, stats, swarm, peer, PenaltyReason::LowGossipScore);
+        }
+    }
+}
+
+fn report_validation(
+    swarm: &mut Swarm<NonosBehaviour>,
+    message_id: &gossipsub::MessageId,
+    propagation_source: &PeerId,
+    acceptance: gossipsub::MessageAcceptance,
+) {
+    if let Err(e) = swarm.behaviour_mut().gossipsub
+        .report_message_validation_result(message_id, propagation_source, acceptance)
+    {
+        debug!("Failed to report validation of message {}: {:?}", message_id, e);
+    }
+}
+
 fn is_banned(banned_peers: &Arc<RwLock<HashMap<PeerId, BanEntry>>>, peer_id: &PeerId) -> bool {
     if let Some(ban) = banned_peers.read().get(peer_id) {
         if !ban.is_expired() {
@@ -560,6 +1110,55 @@ fn is_banned(banned_peers: &Arc<RwLock<HashMap<PeerId, BanEntry>>>, peer_id: &Pe
     false
 }
 
+/// Applies a peer store penalty to the author of a rejected message and,
+/// if that pushes it over the ban threshold, bans and disconnects it.
+fn penalize_author(
+    peer_store: &SharedPeerStore,
+    banned_peers: &Arc<RwLock<HashMap<PeerId, BanEntry>>>,
+    stats: &Arc<NetworkStats>,
+    swarm: &mut Swarm<NonosBehaviour>,
+    author: PeerId,
+    reason: PenaltyReason,
+) {
+    peer_store.get_or_create(author);
+    peer_store.apply_penalty(&author, reason);
+
+    let Some(ban_duration) = peer_store.get(&author).and_then(|entry| entry.ban_remaining()) else {
+        return;
+    };
+    if is_banned(banned_peers, &author) {
+        return;
+    }
+
+    warn!("Auto-banning peer {} for invalid messages", author);
+    let ban = BanEntry::new(author, ban_duration, "invalid_messages");
+    banned_peers.write().insert(author, ban);
+    stats.banned_peers.fetch_add(1, Ordering::Relaxed);
+    let _ = swarm.disconnect_peer_id(author);
+}
+
+/// Learns the peer hosting an announced mix node, keyed by the announcing
+/// node's ID so Sphinx next hops can be dialled. Only a node's own
+/// announcement counts, and `peer_id` must be the peer its envelope was
+/// sealed for, so nobody can route another node's packets to themselves.
+fn record_mix_route(mix_routes: &MixRoutes, signer: NodeId, peer_id: PeerId, announcement: &NodeAnnouncementData) {
+    let Some(public_key) = announcement.mix_public_key else {
+        return;
+    };
+    if announcement.node_id != signer {
+        return;
+    }
+
+    let node = MixNode {
+        node_id: announcement.node_id,
+        public_key,
+        address: announcement.addresses.first().cloned().unwrap_or_else(|| peer_id.to_string()),
+    };
+
+    debug!("Learned mix node {} at {}", announcement.node_id, peer_id);
+    mix_routes.write().insert(announcement.node_id, MixRoute { peer_id, node, last_seen: Instant::now() });
+}
+
 fn is_spam_behavior(info: &PeerInfo) -> bool {
     if info.message_count > SPAM_MESSAGE_THRESHOLD {
         if let Some(last_msg_ts) = info.last_message_at {
@@ -587,4 +1186,57 @@ mod tests {
         info.last_message_at = Some(chrono::Utc::now().timestamp());
         assert!(is_spam_behavior(&info));
     }
+
+    #[test]
+    fn test_record_mix_route() {
+        let routes: MixRoutes = Arc::new(RwLock::new(HashMap::new()));
+        let peer = PeerId::random();
+        let node_id = NodeId::from_bytes([5u8; 32]);
+
+        let mut announcement = NodeAnnouncementData {
+            node_id,
+            tier: String::new(),
+            staked_amount: String::new(),
+            services: Vec::new(),
+            addresses: Vec::new(),
+            mix_public_key: None,
+        };
+        record_mix_route(&routes, node_id, peer, &announcement);
+        assert!(routes.read().is_empty());
+
+        announcement.mix_public_key = Some([1u8; 32]);
+        record_mix_route(&routes, NodeId::from_bytes([6u8; 32]), PeerId::random(), &announcement);
+        assert!(routes.read().is_empty());
+
+        record_mix_route(&routes, node_id, peer, &announcement);
+
+        let route = routes.read().get(&node_id).cloned().unwrap();
+        assert_eq!(route.peer_id, peer);
+        assert_eq!(route.node.public_key, [1u8; 32]);
+    }
+
+    #[test]
+    fn test_prune_mix_routes() {
+        let routes: MixRoutes = Arc::new(RwLock::new(HashMap::new()));
+        let node = |byte: u8| MixNode {
+            node_id: NodeId::from_bytes([byte; 32]),
+            public_key: [byte; 32],
+            address: String::new(),
+        };
+        let now = Instant::now();
+        routes.write().insert(NodeId::from_bytes([1; 32]), MixRoute {
+            peer_id: PeerId::random(),
+            node: node(1),
+            last_seen: now,
+        });
+        routes.write().insert(NodeId::from_bytes([2; 32]), MixRoute {
+            peer_id: PeerId::random(),
+            node: node(2),
+            last_seen: now - Duration::from_secs(120),
+        });
+
+        assert_eq!(prune_mix_routes(&routes, Duration::from_secs(60)), 1);
+        assert!(routes.read().contains_key(&NodeId::from_bytes([1; 32])));
+        assert_eq!(prune_mix_routes(&routes, Duration::from_secs(60)), 0);
+    }
 }
@@ -21,14 +21,17 @@ async fn test_message_encoding() {
     };
 
     let msg = P2pMessage::HealthBeacon(beacon);
-    let encoded = msg.encode();
-    let decoded = P2pMessage::decode(&encoded);
+    let encoded = msg.encode().unwrap();
+    let decoded = P2pMessage::decode(&encoded).unwrap();
 
-    assert!(decoded.is_some());
-    if let Some(P2pMessage::HealthBeacon(data)) = decoded {
-        assert_eq!(data.uptime_secs, 3600);
-        assert_eq!(data.version, "1.0.0");
+    match decoded {
+        P2pMessage::HealthBeacon(data) => {
+            assert_eq!(data.uptime_secs, 3600);
+            assert_eq!(data.version, "1.0.0");
+        }
+        other => panic!("unexpected message: {:?}", other),
     }
+    assert!(P2pMessage::decode(b"{\"BootstrapRequest\":null}").is_err());
 }
 
 #[tokio::test]
@@ -47,3 +50,617 @@ async fn test_peer_info() {
 
     assert_eq!(info.latency_ms, Some(50));
 }
+
+#[tokio::test]
+async fn test_mixnet_codec_roundtrip() {
+    use crate::privacy::{build_sphinx_packet, MixnetKeypair, SPHINX_PACKET_SIZE};
+    use futures::io::Cursor;
+    use libp2p::request_response::Codec;
+
+    let keypair = MixnetKeypair::generate();
+    let path = vec![(NodeId::from_bytes([7u8; 32]), *keypair.public_key())];
+    let packet = build_sphinx_packet(b"over the wire", &path).unwrap();
+
+    let mut codec = MixnetCodec;
+    let mut wire = Cursor::new(Vec::new());
+    codec.write_request(&MIXNET_PROTOCOL, &mut wire, packet.clone()).await.unwrap();
+    assert_eq!(wire.get_ref().len(), SPHINX_PACKET_SIZE);
+
+    wire.set_position(0);
+    let received = codec.read_request(&MIXNET_PROTOCOL, &mut wire).await.unwrap();
+    assert_eq!(received.to_bytes(), packet.to_bytes());
+
+    let mut truncated = Cursor::new(packet.to_bytes()[..100].to_vec());
+    assert!(codec.read_request(&MIXNET_PROTOCOL, &mut truncated).await.is_err());
+}
+
+#[tokio::test]
+async fn test_pir_codec_roundtrip() {
+    use crate::privacy::{PirDatabase, PirParams, PirRetrieval};
+    use futures::io::Cursor;
+    use libp2p::request_response::Codec;
+
+    let params = PirParams { buckets: 16, slots_per_bucket: 2, max_item_size: 64 };
+    let database = PirDatabase::build(params, vec![b"served over pir".to_vec()]).unwrap();
+    let commitment = nonos_crypto::blake3_hash(b"served over pir").0;
+    let (_, [query, _]) = PirRetrieval::new(&database.info(), commitment).unwrap();
+
+    let mut codec = PirCodec;
+    let mut wire = Cursor::new(Vec::new());
+    codec.write_request(&PIR_PROTOCOL, &mut wire, PirRequest::Query(query.clone())).await.unwrap();
+    wire.set_position(0);
+    match codec.read_request(&PIR_PROTOCOL, &mut wire).await.unwrap() {
+        PirRequest::Query(received) => assert_eq!(received, query),
+        other => panic!("unexpected request {:?}", other),
+    }
+
+    let mut wire = Cursor::new(Vec::new());
+    codec.write_response(&PIR_PROTOCOL, &mut wire, PirResponse::Info(Some(database.info()))).await.unwrap();
+    wire.set_position(0);
+    match codec.read_response(&PIR_PROTOCOL, &mut wire).await.unwrap() {
+        PirResponse::Info(info) => assert_eq!(info, Some(database.info())),
+        other => panic!("unexpected response {:?}", other),
+    }
+
+    let mut oversized = Cursor::new(u32::MAX.to_be_bytes().to_vec());
+    assert!(codec.read_request(&PIR_PROTOCOL, &mut oversized).await.is_err());
+}
+
+#[test]
+fn test_pir_servers_are_paired_by_database() {
+    use super::pir::server_pairs;
+    use libp2p::PeerId;
+    use crate::privacy::{PirDatabase, PirParams};
+
+    let params = PirParams { buckets: 16, slots_per_bucket: 2, max_item_size: 64 };
+    let common = PirDatabase::build(params, vec![b"common".to_vec()]).unwrap().info();
+    let other = PirDatabase::build(params, vec![b"other".to_vec()]).unwrap().info();
+    let peers: Vec<PeerId> = (0..5).map(|_| PeerId::random()).collect();
+
+    let pairs = server_pairs(vec![
+        (peers[0], Some(other)),
+        (peers[1], Some(common)),
+        (peers[2], None),
+        (peers[3], Some(common)),
+        (peers[4], Some(common)),
+    ]);
+    assert_eq!(pairs, vec![[peers[1], peers[3]]]);
+
+    assert!(server_pairs(vec![(peers[0], Some(common)), (peers[1], Some(other))]).is_empty());
+}
+
+async fn held_share(cookie_id: &str) -> crate::privacy::HeldShare {
+    let vault = crate::privacy::DistributedCookieVault::new(2, 3).unwrap();
+    let nodes = [[1u8; 32], [2u8; 32], [3u8; 32]];
+    let shares = vault.split_cookie(cookie_id, b"session=abc", &nodes).await.unwrap();
+    let cookie = vault.cookie(cookie_id).await.unwrap();
+    crate::privacy::HeldShare {
+        cookie_id: cookie_id.to_string(),
+        generation: cookie.generation,
+        index: shares[0].index,
+        value: shares[0].value.clone(),
+        commitments: cookie.commitments,
+    }
+}
+
+#[tokio::test]
+async fn test_vault_codec_roundtrip() {
+    use futures::io::Cursor;
+    use libp2p::request_response::Codec;
+
+    let share = held_share("c").await;
+    let mut codec = VaultCodec;
+    let mut wire = Cursor::new(Vec::new());
+    codec.write_request(&VAULT_PROTOCOL, &mut wire, VaultRequest::Store(share.clone())).await.unwrap();
+    wire.set_position(0);
+    match codec.read_request(&VAULT_PROTOCOL, &mut wire).await.unwrap() {
+        VaultRequest::Store(received) => assert_eq!(received, share),
+        other => panic!("unexpected request {:?}", other),
+    }
+
+    let mut wire = Cursor::new(Vec::new());
+    codec.write_response(&VAULT_PROTOCOL, &mut wire, VaultResponse::Share(Some(share.clone()))).await.unwrap();
+    wire.set_position(0);
+    match codec.read_response(&VAULT_PROTOCOL, &mut wire).await.unwrap() {
+        VaultResponse::Share(received) => assert_eq!(received, Some(share)),
+        other => panic!("unexpected response {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_vault_share_store() {
+    let storage = std::sync::Arc::new(crate::storage::NodeStorage::in_memory().unwrap());
+    let store = VaultShareStore::open(Some(storage.clone()));
+    let owner = libp2p::PeerId::random();
+    let other = libp2p::PeerId::random();
+    let share = held_share("c").await;
+    let fetch = VaultRequest::Fetch { cookie_id: "c".into(), generation: share.generation };
+
+    assert!(matches!(store.handle(owner, VaultRequest::Store(share.clone())), VaultResponse::Stored));
+    match store.handle(owner, fetch.clone()) {
+        VaultResponse::Share(held) => assert_eq!(held, Some(share.clone())),
+        other => panic!("unexpected response {:?}", other),
+    }
+    // Only the peer that stored a share gets it back.
+    assert!(matches!(store.handle(other, fetch.clone()), VaultResponse::Share(None)));
+
+    // Shares that do not match their commitments are refused.
+    let mut corrupted = share.clone();
+    corrupted.cookie_id = "d".into();
+    corrupted.index += 1;
+    assert!(matches!(store.handle(owner, VaultRequest::Store(corrupted)), VaultResponse::Refused(_)));
+
+    // Held shares survive a restart.
+    let reopened = VaultShareStore::open(Some(storage.clone()));
+    assert_eq!(reopened.len(), 1);
+    assert!(matches!(reopened.handle(owner, fetch.clone()), VaultResponse::Share(Some(_))));
+
+    let release = VaultRequest::Release { cookie_id: "c".into(), generation: share.generation };
+    assert!(matches!(reopened.handle(other, release.clone()), VaultResponse::Released));
+    assert_eq!(reopened.len(), 1);
+    assert!(matches!(reopened.handle(owner, release), VaultResponse::Released));
+    assert!(reopened.is_empty());
+    assert!(VaultShareStore::open(Some(storage)).is_empty());
+}
+
+#[test]
+fn test_node_announcement_mix_key() {
+    let announcement = NodeAnnouncementData {
+        node_id: NodeId::from_bytes([3u8; 32]),
+        tier: String::new(),
+        staked_amount: String::new(),
+        services: vec!["mixnet".to_string()],
+        addresses: Vec::new(),
+        mix_public_key: Some([9u8; 32]),
+    };
+
+    let encoded = P2pMessage::NodeAnnouncement(announcement.clone()).encode().unwrap();
+    match P2pMessage::decode(&encoded) {
+        Ok(P2pMessage::NodeAnnouncement(data)) => assert_eq!(data.mix_public_key, Some([9u8; 32])),
+        other => panic!("unexpected message: {:?}", other),
+    }
+
+    let without_key = NodeAnnouncementData { mix_public_key: None, ..announcement };
+    let encoded = P2pMessage::NodeAnnouncement(without_key).encode().unwrap();
+    match P2pMessage::decode(&encoded) {
+        Ok(P2pMessage::NodeAnnouncement(data)) => assert!(data.mix_public_key.is_none()),
+        other => panic!("unexpected message: {:?}", other),
+    }
+}
+
+fn signed_beacon(identity: &nonos_crypto::NodeIdentity, sender: libp2p::PeerId, timestamp_ms: i64) -> SignedEnvelope {
+    let beacon = P2pMessage::HealthBeacon(HealthBeaconData {
+        node_id: identity.node_id(),
+        timestamp: chrono::Utc::now(),
+        uptime_secs: 60,
+        version: "1.0.0".to_string(),
+        peer_count: 3,
+        cpu_usage: None,
+        memory_usage: None,
+    });
+    SignedEnvelope::seal_at(&beacon, identity, sender, timestamp_ms).unwrap()
+}
+
+#[test]
+fn test_envelope_roundtrip() {
+    let identity = nonos_crypto::NodeIdentity::generate();
+    let peer = libp2p::PeerId::random();
+    let now = chrono::Utc::now().timestamp_millis();
+    let bytes = signed_beacon(&identity, peer, now).to_bytes();
+
+    assert_eq!(bytes[0], ENVELOPE_VERSION);
+    assert_eq!(bytes[1], MessageType::HealthBeacon as u8);
+
+    let mut guard = ReplayGuard::new();
+    let (signer, message) = verify_envelope(&bytes, &peer, &mut guard, now).unwrap();
+    assert_eq!(signer, identity.node_id());
+    assert!(matches!(message, P2pMessage::HealthBeacon(data) if data.uptime_secs == 60));
+
+    let request = SignedEnvelope::seal(&P2pMessage::BootstrapRequest, &identity, peer).unwrap();
+    let opened = SignedEnvelope::from_bytes(&request.to_bytes()).unwrap();
+    assert_eq!(opened.sender, peer);
+    assert!(matches!(opened.open().unwrap(), P2pMessage::BootstrapRequest));
+}
+
+#[test]
+fn test_envelope_rejects_tampering_and_forgery() {
+    let identity = nonos_crypto::NodeIdentity::generate();
+    let peer = libp2p::PeerId::random();
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut guard = ReplayGuard::new();
+
+    let mut bytes = signed_beacon(&identity, peer, now).to_bytes();
+    let payload_byte = bytes.len() - 70;
+    bytes[payload_byte] ^= 0x01;
+    assert_eq!(verify_envelope(&bytes, &peer, &mut guard, now).unwrap_err(), EnvelopeError::BadSignature);
+
+    let mut bytes = signed_beacon(&identity, peer, now).to_bytes();
+    bytes[0] = ENVELOPE_VERSION + 1;
+    assert_eq!(
+        verify_envelope(&bytes, &peer, &mut guard, now).unwrap_err(),
+        EnvelopeError::UnsupportedVersion(ENVELOPE_VERSION + 1)
+    );
+
+    assert!(matches!(
+        verify_envelope(&[ENVELOPE_VERSION; 16], &peer, &mut guard, now),
+        Err(EnvelopeError::Malformed(_))
+    ));
+
+    // A beacon claiming another node's ID, validly signed by an attacker.
+    let victim = nonos_crypto::NodeIdentity::generate();
+    let attacker = nonos_crypto::NodeIdentity::generate();
+    let mut forged = signed_beacon(&victim, peer, now);
+    forged.signer = *attacker.public_key();
+    let mut signed = b"nonos-p2p-envelope".to_vec();
+    signed.extend_from_slice(&forged.to_bytes()[..forged.to_bytes().len() - 64]);
+    forged.signature = attacker.sign(&signed).bytes;
+    assert_eq!(
+        verify_envelope(&forged.to_bytes(), &peer, &mut guard, now).unwrap_err(),
+        EnvelopeError::SignerMismatch
+    );
+    assert_eq!(EnvelopeError::SignerMismatch.penalty(), PenaltyReason::ProtocolViolation);
+
+    let beacon = P2pMessage::decode(&forged.payload).unwrap();
+    assert!(SignedEnvelope::seal(&beacon, &attacker, peer).is_err());
+}
+
+#[test]
+fn test_envelope_binds_sender_peer() {
+    let identity = nonos_crypto::NodeIdentity::generate();
+    let peer = libp2p::PeerId::random();
+    let attacker = libp2p::PeerId::random();
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut guard = ReplayGuard::new();
+
+    // Republishing someone else's envelope from another peer must not
+    // bind their node ID to that peer.
+    let envelope = signed_beacon(&identity, peer, now);
+    assert_eq!(
+        verify_envelope(&envelope.to_bytes(), &attacker, &mut guard, now).unwrap_err(),
+        EnvelopeError::SenderMismatch
+    );
+    assert_eq!(EnvelopeError::SenderMismatch.penalty(), PenaltyReason::ProtocolViolation);
+
+    let mut rebound = envelope.clone();
+    rebound.sender = attacker;
+    assert_eq!(
+        verify_envelope(&rebound.to_bytes(), &attacker, &mut guard, now).unwrap_err(),
+        EnvelopeError::BadSignature
+    );
+
+    assert!(verify_envelope(&envelope.to_bytes(), &peer, &mut guard, now).is_ok());
+}
+
+#[test]
+fn test_envelope_rejects_unsigned_filter_list_chunks() {
+    use crate::privacy::{BundleBody, FilterListBundle};
+
+    let identity = nonos_crypto::NodeIdentity::generate();
+    let peer = libp2p::PeerId::random();
+    let (key, _) = nonos_crypto::generate_ed25519_keypair();
+    let body = BundleBody::Full { rules: "||ads.example^".into() };
+    let bundle = FilterListBundle::sign("community", 1, body, [0; 32], &key).unwrap();
+    let chunk = bundle.to_chunks(&key).unwrap().remove(0);
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut guard = ReplayGuard::new();
+
+    // Any node may relay the maintainer's chunk.
+    let relayed = SignedEnvelope::seal_at(&P2pMessage::FilterListChunk(chunk.clone()), &identity, peer, now).unwrap();
+    assert!(verify_envelope(&relayed.to_bytes(), &peer, &mut guard, now).is_ok());
+
+    // A relayed chunk whose data is not the maintainer's is rejected, and
+    // its author penalised, before it reaches the assembler.
+    let mut garbage = chunk.clone();
+    garbage.data[0] ^= 1;
+    let garbage = SignedEnvelope::seal_at(&P2pMessage::FilterListChunk(garbage), &identity, peer, now).unwrap();
+    let rejected = verify_envelope(&garbage.to_bytes(), &peer, &mut guard, now).unwrap_err();
+    assert_eq!(rejected, EnvelopeError::BadSignature);
+    assert_eq!(rejected.penalty(), PenaltyReason::ProtocolViolation);
+
+    let mut forged = chunk;
+    forged.bundle_id = [7; 32];
+    let forged = SignedEnvelope::seal_at(&P2pMessage::FilterListChunk(forged), &identity, peer, now).unwrap();
+    assert_eq!(
+        verify_envelope(&forged.to_bytes(), &peer, &mut guard, now).unwrap_err(),
+        EnvelopeError::BadSignature
+    );
+}
+
+#[test]
+fn test_replay_guard_window() {
+    let identity = nonos_crypto::NodeIdentity::generate();
+    let peer = libp2p::PeerId::random();
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut guard = ReplayGuard::new();
+
+    let envelope = signed_beacon(&identity, peer, now);
+    assert!(guard.check(&envelope, now).is_ok());
+    assert_eq!(guard.check(&envelope, now + 1_000), Err(EnvelopeError::Replayed));
+
+    let stale = signed_beacon(&identity, peer, now - REPLAY_WINDOW_MS - 1);
+    assert_eq!(guard.check(&stale, now), Err(EnvelopeError::Expired));
+
+    let future = signed_beacon(&identity, peer, now + MAX_CLOCK_SKEW_MS + 1);
+    assert_eq!(guard.check(&future, now), Err(EnvelopeError::FromFuture));
+
+    let later = now + REPLAY_WINDOW_MS + MAX_CLOCK_SKEW_MS + 1;
+    assert_eq!(guard.check(&envelope, later), Err(EnvelopeError::Expired));
+    assert!(guard.is_empty());
+}
+
+fn dht_record(key: &[u8], value_len: usize, ttl: Option<std::time::Duration>) -> libp2p::kad::Record {
+    libp2p::kad::Record {
+        key: libp2p::kad::RecordKey::new(&key),
+        value: vec![0xab; value_len],
+        publisher: None,
+        expires: ttl.map(|ttl| std::time::Instant::now() + ttl),
+    }
+}
+
+#[test]
+fn test_record_store_survives_reopen() {
+    use crate::NodeStorage;
+    use libp2p::kad::store::RecordStore;
+    use libp2p::kad::{ProviderRecord, RecordKey};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    let storage = Arc::new(NodeStorage::in_memory().unwrap());
+    let local = libp2p::PeerId::random();
+    let remote = libp2p::PeerId::random();
+    let hour = Some(Duration::from_secs(3600));
+
+    {
+        let mut store = PersistentRecordStore::open(local, Some(storage.clone()), RecordStoreConfig::default());
+        let mut published = dht_record(b"mix-directory", 128, hour);
+        published.publisher = Some(local);
+        store.put(published).unwrap();
+        store.put(dht_record(b"stale", 16, Some(Duration::ZERO))).unwrap();
+        store.put(dht_record(b"removed", 16, hour)).unwrap();
+        store.remove(&RecordKey::new(b"removed"));
+
+        for provider in [local, remote] {
+            store.add_provider(ProviderRecord {
+                key: RecordKey::new(b"identity-root"),
+                provider,
+                expires: Some(Instant::now() + Duration::from_secs(3600)),
+                addresses: vec!["/ip4/10.0.0.1/tcp/9432".parse().unwrap()],
+            }).unwrap();
+        }
+    }
+
+    let store = PersistentRecordStore::open(local, Some(storage), RecordStoreConfig::default());
+    let record = store.get(&RecordKey::new(b"mix-directory")).unwrap();
+    assert_eq!(record.value.len(), 128);
+    assert_eq!(record.publisher, Some(local));
+    assert!(record.expires.unwrap() > Instant::now() + Duration::from_secs(3500));
+
+    assert!(store.get(&RecordKey::new(b"stale")).is_none());
+    assert!(store.get(&RecordKey::new(b"removed")).is_none());
+    assert_eq!(store.len(), 1);
+    assert_eq!(store.total_bytes(), 128);
+
+    let providers = store.providers(&RecordKey::new(b"identity-root"));
+    assert_eq!(providers.len(), 2);
+    assert_eq!(providers[0].addresses.len(), 1);
+    assert_eq!(store.provided().count(), 1);
+}
+
+#[test]
+fn test_record_store_limits() {
+    use libp2p::kad::store::{Error, RecordStore};
+
+    let config = RecordStoreConfig {
+        max_records: 3,
+        max_value_bytes: 100,
+        max_total_bytes: 150,
+        ..Default::default()
+    };
+    let mut store = PersistentRecordStore::in_memory(libp2p::PeerId::random(), config);
+
+    assert!(store.put(dht_record(b"a", 100, None)).is_ok());
+    assert!(matches!(store.put(dht_record(b"b", 101, None)), Err(Error::ValueTooLarge)));
+    assert!(matches!(store.put(dht_record(b"b", 60, None)), Err(Error::MaxRecords)));
+    assert!(store.put(dht_record(b"a", 90, None)).is_ok());
+    assert!(store.put(dht_record(b"b", 60, None)).is_ok());
+    assert_eq!(store.total_bytes(), 150);
+
+    assert!(store.put(dht_record(b"c", 0, None)).is_ok());
+    assert!(matches!(store.put(dht_record(b"d", 0, None)), Err(Error::MaxRecords)));
+}
+
+#[test]
+fn test_record_store_prunes_expired() {
+    use crate::NodeStorage;
+    use libp2p::kad::store::RecordStore;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let storage = Arc::new(NodeStorage::in_memory().unwrap());
+    let local = libp2p::PeerId::random();
+    let mut store = PersistentRecordStore::open(local, Some(storage.clone()), RecordStoreConfig::default());
+
+    store.put(dht_record(b"short", 8, Some(Duration::from_millis(1)))).unwrap();
+    store.put(dht_record(b"long", 8, Some(Duration::from_secs(3600)))).unwrap();
+    std::thread::sleep(Duration::from_millis(5));
+
+    store.prune_expired();
+    assert_eq!(store.len(), 1);
+    assert_eq!(store.total_bytes(), 8);
+    assert_eq!(storage.load_dht_records().unwrap().len(), 1);
+}
+
+#[test]
+fn test_reachability_from_nat_status() {
+    use libp2p::autonat::NatStatus;
+
+    let addr: libp2p::Multiaddr = "/ip4/203.0.113.7/tcp/9432".parse().unwrap();
+    assert_eq!(Reachability::from(&NatStatus::Public(addr)), Reachability::Public);
+    assert_eq!(Reachability::from(&NatStatus::Private), Reachability::Private);
+    assert_eq!(Reachability::from(&NatStatus::Unknown), Reachability::Unknown);
+
+    let stats = NetworkStats::new();
+    assert_eq!(stats.snapshot().reachability, Reachability::Unknown);
+
+    stats.set_reachability(Reachability::Public, Some("/ip4/203.0.113.7/tcp/9432".into()));
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.reachability, Reachability::Public);
+    assert_eq!(snapshot.public_address.as_deref(), Some("/ip4/203.0.113.7/tcp/9432"));
+    assert_eq!(serde_json::to_value(snapshot.reachability).unwrap(), "public");
+}
+
+#[test]
+fn test_relay_manager_reserves_up_to_limit() {
+    use super::nat::RelayManager;
+    use libp2p::core::transport::ListenerId;
+    use libp2p::{Multiaddr, PeerId};
+
+    let mut relays = RelayManager::new();
+    let loopback: Multiaddr = "/ip4/127.0.0.1/tcp/9432".parse().unwrap();
+    assert!(!relays.add_candidate(PeerId::random(), [&loopback]));
+
+    let peers: Vec<PeerId> = (0..3).map(|_| PeerId::random()).collect();
+    for (i, peer) in peers.iter().enumerate() {
+        let addr: Multiaddr = format!("/ip4/198.51.100.{}/tcp/9432", i + 1).parse().unwrap();
+        assert!(relays.add_candidate(*peer, [&loopback, &addr]));
+    }
+
+    let wanted = relays.wanted_circuits();
+    assert_eq!(wanted.len(), MAX_RELAY_RESERVATIONS);
+    for (relay, circuit) in &wanted {
+        assert!(circuit.to_string().ends_with(&format!("/p2p/{}/p2p-circuit", relay)));
+    }
+
+    let (first, _) = wanted[0];
+    let listener = ListenerId::next();
+    relays.listening(listener, first);
+    assert!(relays.accepted(first));
+    assert!(!relays.accepted(first));
+    assert_eq!(relays.reservation_count(), 1);
+    assert!(relays.wanted_circuits().iter().all(|(relay, _)| *relay != first));
+
+    assert_eq!(relays.listener_closed(listener), Some(first));
+    assert_eq!(relays.reservation_count(), 0);
+}
+
+#[test]
+fn test_circuit_address_replaces_peer_id() {
+    use libp2p::PeerId;
+
+    let relay = PeerId::random();
+    let other = PeerId::random();
+    let addr: libp2p::Multiaddr = format!("/ip4/198.51.100.1/tcp/9432/p2p/{}", other).parse().unwrap();
+    assert_eq!(
+        circuit_address(&addr, relay).to_string(),
+        format!("/ip4/198.51.100.1/tcp/9432/p2p/{}/p2p-circuit", relay)
+    );
+}
+
+#[test]
+fn test_quic_address_for_tcp() {
+    let tcp: libp2p::Multiaddr = "/ip4/198.51.100.1/tcp/9432".parse().unwrap();
+    let quic = quic_address_for(&tcp).unwrap();
+    assert_eq!(quic.to_string(), "/ip4/198.51.100.1/udp/9432/quic-v1");
+    assert_eq!(address_transport(&quic), AddressTransport::Quic);
+    assert!(quic_address_for(&quic).is_none());
+}
+
+#[test]
+fn test_rank_addresses_prefers_quic() {
+    let tcp: libp2p::Multiaddr = "/ip4/198.51.100.1/tcp/9432".parse().unwrap();
+    let quic: libp2p::Multiaddr = "/ip4/198.51.100.1/udp/9432/quic-v1".parse().unwrap();
+    let dns: libp2p::Multiaddr = "/dns4/boot.example.org".parse().unwrap();
+    let addrs = vec![dns.clone(), tcp.clone(), quic.clone(), tcp.clone()];
+
+    assert_eq!(rank_addresses(addrs.clone(), true, true), vec![quic.clone(), tcp.clone(), dns.clone()]);
+    assert_eq!(rank_addresses(addrs.clone(), true, false), vec![tcp.clone(), quic, dns.clone()]);
+    assert_eq!(rank_addresses(addrs, false, true), vec![tcp, dns]);
+}
+
+#[test]
+fn test_dial_addresses_follow_quic_config() {
+    let peer = libp2p::PeerId::random();
+    let requested: libp2p::Multiaddr = format!("/ip4/198.51.100.1/tcp/9432/p2p/{}", peer).parse().unwrap();
+
+    let network = P2pNetwork::new(9432, 10);
+    let addrs = network.dial_addresses(&peer, requested.clone());
+    assert_eq!(addrs.len(), 2);
+    assert_eq!(address_transport(&addrs[0]), AddressTransport::Quic);
+
+    let network = P2pNetwork::with_config(NetworkConfig {
+        enable_quic: false,
+        ..Default::default()
+    });
+    assert_eq!(network.dial_addresses(&peer, requested.clone()), vec![requested]);
+}
+
+#[test]
+fn test_official_bootstrap_nodes_carry_both_transports() {
+    let nodes: Vec<libp2p::Multiaddr> = crate::config::NetworkConfig::OFFICIAL_BOOTSTRAP_NODES
+        .iter()
+        .map(|addr| addr.parse().unwrap())
+        .collect();
+
+    assert!(nodes.iter().any(|addr| address_transport(addr) == AddressTransport::Quic));
+    assert!(nodes.iter().any(|addr| address_transport(addr) == AddressTransport::Tcp));
+}
+
+#[test]
+fn test_peer_score_params_cover_all_topics() {
+    let params = peer_score_params();
+    assert!(params.validate().is_ok());
+    assert!(peer_score_thresholds().validate().is_ok());
+
+    for topic in [
+        topics::HEALTH_BEACON,
+        topics::QUALITY_REPORTS,
+        topics::PEER_DISCOVERY,
+        topics::NODE_ANNOUNCEMENTS,
+        topics::PRIVACY_COORD,
+    ] {
+        let hash = libp2p::gossipsub::IdentTopic::new(topic).hash();
+        let topic_params = params.topics.get(&hash).expect("topic is scored");
+        assert!(topic_params.invalid_message_deliveries_weight < 0.0);
+        assert!(topic_params.first_message_deliveries_weight > 0.0);
+    }
+
+    let beacon = &params.topics[&libp2p::gossipsub::IdentTopic::new(topics::HEALTH_BEACON).hash()];
+    assert!(beacon.mesh_message_deliveries_weight < 0.0);
+}
+
+#[test]
+fn test_application_score_follows_peer_store() {
+    let mut entry = PeerEntry::new(libp2p::PeerId::random());
+    assert_eq!(application_score(&entry), 0.0);
+
+    entry.apply_penalty(PenaltyReason::ProtocolViolation);
+    let penalized = application_score(&entry);
+    assert!(penalized < 0.0 && penalized > PUBLISH_THRESHOLD);
+
+    entry.sideline(SIDELINE_COOLDOWN);
+    let sidelined = application_score(&entry);
+    assert!(sidelined < PUBLISH_THRESHOLD && sidelined > GRAYLIST_THRESHOLD);
+
+    entry.ban(DEFAULT_BAN_DURATION, "test");
+    assert!(application_score(&entry) < GRAYLIST_THRESHOLD);
+}
+
+#[test]
+fn test_protocol_score_excludes_application_score() {
+    let app_score = application_score(&{
+        let mut entry = PeerEntry::new(libp2p::PeerId::random());
+        entry.ban(DEFAULT_BAN_DURATION, "test");
+        entry
+    });
+
+    // A banned peer that behaves on the wire is not penalised again.
+    assert_eq!(protocol_score(app_score + 5.0, app_score), 5.0);
+    assert!(protocol_score(GRAYLIST_THRESHOLD - 1.0, 0.0) < GRAYLIST_THRESHOLD);
+}
+
+#[test]
+fn test_low_gossip_score_penalty() {
+    let mut entry = PeerEntry::new(libp2p::PeerId::random());
+    assert_eq!(entry.apply_penalty(PenaltyReason::LowGossipScore), 10);
+    assert_eq!(PenaltyReason::LowGossipScore.to_string(), "low_gossip_score");
+}
@@ -1,11 +1,19 @@
+use crate::p2p::messages::P2pMessage;
+use crate::p2p::pir::{PirRequest, PirResponse};
+use crate::p2p::vault::{VaultRequest, VaultResponse};
+use crate::privacy::SphinxPacket;
 use libp2p::{Multiaddr, PeerId};
+use nonos_types::{NodeId, NonosResult};
 use std::time::Duration;
+use tokio::sync::oneshot;
 
 use super::rate_limit::RateLimitReason;
 
 #[derive(Debug)]
 pub enum NetworkCommand {
     Connect(Multiaddr),
+    /// Dials a peer over the given addresses, in order of preference.
+    Dial { peer: PeerId, addresses: Vec<Multiaddr> },
     Disconnect(PeerId),
     Publish { topic: String, data: Vec<u8> },
     Subscribe(String),
@@ -16,13 +24,20 @@ pub enum NetworkCommand {
     BanPeer(PeerId, Duration),
     UnbanPeer(PeerId),
     SetRateLimit { messages_per_sec: u32, bytes_per_sec: u64 },
+    SendMixPacket { peer: PeerId, packet: SphinxPacket },
+    PutRecord { key: Vec<u8>, value: Vec<u8> },
+    /// Times a nonce echo from `peer`; replies `None` if it fails.
+    Probe { peer: PeerId, reply: oneshot::Sender<Option<Duration>> },
+    Pir { peer: PeerId, request: PirRequest, reply: oneshot::Sender<NonosResult<PirResponse>> },
+    Vault { peer: PeerId, request: VaultRequest, reply: oneshot::Sender<NonosResult<VaultResponse>> },
 }
 
 #[derive(Debug, Clone)]
 pub enum NetworkEvent {
     PeerConnected(PeerId),
     PeerDisconnected(PeerId),
-    Message { topic: String, source: PeerId, data: Vec<u8> },
+    /// A gossiped message whose envelope signature has been verified.
+    Message { topic: String, source: PeerId, signer: NodeId, message: P2pMessage },
     PeerDiscovered(PeerId, Vec<Multiaddr>),
     PingResult { peer: PeerId, rtt: Duration },
     Error(String),
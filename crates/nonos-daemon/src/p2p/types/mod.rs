@@ -5,6 +5,7 @@ mod commands;
 mod connection;
 mod peer_info;
 mod rate_limit;
+mod reachability;
 mod stats;
 mod violations;
 
@@ -15,6 +16,7 @@ pub use commands::{NetworkCommand, NetworkEvent};
 pub use connection::{ConnectionState, ConnectionTracker};
 pub use peer_info::PeerInfo;
 pub use rate_limit::{RateLimitReason, RateLimiter};
+pub use reachability::Reachability;
 pub use stats::{NetworkStats, NetworkStatsSnapshot};
 pub use violations::MessageViolation;
 
@@ -0,0 +1,48 @@
+use libp2p::autonat::NatStatus;
+use serde::{Deserialize, Serialize};
+
+/// Whether other peers can dial this node directly, as determined by
+/// AutoNAT probes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum Reachability {
+    #[default]
+    Unknown = 0,
+    Public = 1,
+    Private = 2,
+}
+
+impl Reachability {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Reachability::Public,
+            2 => Reachability::Private,
+            _ => Reachability::Unknown,
+        }
+    }
+
+    pub fn is_public(&self) -> bool {
+        matches!(self, Reachability::Public)
+    }
+}
+
+impl From<&NatStatus> for Reachability {
+    fn from(status: &NatStatus) -> Self {
+        match status {
+            NatStatus::Public(_) => Reachability::Public,
+            NatStatus::Private => Reachability::Private,
+            NatStatus::Unknown => Reachability::Unknown,
+        }
+    }
+}
+
+impl std::fmt::Display for Reachability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reachability::Unknown => write!(f, "unknown"),
+            Reachability::Public => write!(f, "public"),
+            Reachability::Private => write!(f, "private"),
+        }
+    }
+}
@@ -1,5 +1,7 @@
+use super::reachability::Reachability;
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 use std::time::Instant;
 
 pub struct NetworkStats {
@@ -15,6 +17,14 @@ pub struct NetworkStats {
     pub rate_limit_hits: AtomicU64,
     pub banned_peers: AtomicU64,
     pub circuit_breaker_trips: AtomicU64,
+    /// Active reservations this node holds on circuit relays.
+    pub relay_reservations: AtomicU64,
+    /// Circuits this node is relaying for other peers.
+    pub relayed_circuits: AtomicU64,
+    pub hole_punch_successes: AtomicU64,
+    pub hole_punch_failures: AtomicU64,
+    reachability: AtomicU8,
+    public_address: RwLock<Option<String>>,
     started_at: Instant,
 }
 
@@ -39,6 +49,12 @@ impl NetworkStats {
             rate_limit_hits: AtomicU64::new(0),
             banned_peers: AtomicU64::new(0),
             circuit_breaker_trips: AtomicU64::new(0),
+            relay_reservations: AtomicU64::new(0),
+            relayed_circuits: AtomicU64::new(0),
+            hole_punch_successes: AtomicU64::new(0),
+            hole_punch_failures: AtomicU64::new(0),
+            reachability: AtomicU8::new(Reachability::Unknown as u8),
+            public_address: RwLock::new(None),
             started_at: Instant::now(),
         }
     }
@@ -47,6 +63,19 @@ impl NetworkStats {
         self.started_at.elapsed()
     }
 
+    pub fn reachability(&self) -> Reachability {
+        Reachability::from_u8(self.reachability.load(Ordering::Relaxed))
+    }
+
+    pub fn public_address(&self) -> Option<String> {
+        self.public_address.read().clone()
+    }
+
+    pub fn set_reachability(&self, reachability: Reachability, public_address: Option<String>) {
+        self.reachability.store(reachability as u8, Ordering::Relaxed);
+        *self.public_address.write() = public_address;
+    }
+
     pub fn snapshot(&self) -> NetworkStatsSnapshot {
         NetworkStatsSnapshot {
             peer_count: self.peer_count.load(Ordering::Relaxed),
@@ -61,6 +90,12 @@ impl NetworkStats {
             rate_limit_hits: self.rate_limit_hits.load(Ordering::Relaxed),
             banned_peers: self.banned_peers.load(Ordering::Relaxed),
             circuit_breaker_trips: self.circuit_breaker_trips.load(Ordering::Relaxed),
+            reachability: self.reachability(),
+            public_address: self.public_address(),
+            relay_reservations: self.relay_reservations.load(Ordering::Relaxed),
+            relayed_circuits: self.relayed_circuits.load(Ordering::Relaxed),
+            hole_punch_successes: self.hole_punch_successes.load(Ordering::Relaxed),
+            hole_punch_failures: self.hole_punch_failures.load(Ordering::Relaxed),
             uptime_secs: self.started_at.elapsed().as_secs(),
         }
     }
@@ -80,5 +115,11 @@ pub struct NetworkStatsSnapshot {
     pub rate_limit_hits: u64,
     pub banned_peers: u64,
     pub circuit_breaker_trips: u64,
+    pub reachability: Reachability,
+    pub public_address: Option<String>,
+    pub relay_reservations: u64,
+    pub relayed_circuits: u64,
+    pub hole_punch_successes: u64,
+    pub hole_punch_failures: u64,
     pub uptime_secs: u64,
 }
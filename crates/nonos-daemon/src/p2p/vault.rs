@@ -0,0 +1,304 @@
+//! Holding cookie vault key shares for other nodes.
+//!
+//! Carries [`crate::privacy::DistributedCookieVault`] shares over
+//! `/nonos/vault/1.0.0`. A holder checks every share against its
+//! commitments before keeping it, and only ever returns or drops a share
+//! at the request of the peer that stored it.
+
+use super::pir::{read_framed, write_framed};
+use super::probe::NodePeers;
+use super::types::NetworkCommand;
+use crate::privacy::{HeldShare, ShareTransport, MAX_COOKIE_ID_LEN};
+use crate::storage::{NodeStorage, StoredVaultShare};
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncWrite};
+use libp2p::request_response::{self, OutboundRequestId, ResponseChannel};
+use libp2p::{PeerId, StreamProtocol};
+use nonos_types::{NodeId, NonosError, NonosResult};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, warn};
+
+pub const VAULT_PROTOCOL: StreamProtocol = StreamProtocol::new("/nonos/vault/1.0.0");
+
+pub const VAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+const MAX_MESSAGE_SIZE: usize = 16 * 1024;
+/// Shares kept for any one peer.
+const MAX_SHARES_PER_OWNER: usize = 256;
+/// Shares kept for all peers together.
+const MAX_HELD_SHARES: usize = 16 * 1024;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum VaultRequest {
+    Store(HeldShare),
+    Fetch { cookie_id: String, generation: u32 },
+    Release { cookie_id: String, generation: u32 },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum VaultResponse {
+    Stored,
+    /// The share the requesting peer stored, if this node still holds it.
+    Share(Option<HeldShare>),
+    Released,
+    Refused(String),
+}
+
+/// Length-prefixed bincode in both directions.
+#[derive(Clone, Default)]
+pub struct VaultCodec;
+
+#[async_trait]
+impl request_response::Codec for VaultCodec {
+    type Protocol = StreamProtocol;
+    type Request = VaultRequest;
+    type Response = VaultResponse;
+
+    async fn read_request<T>(&mut self, _: &StreamProtocol, io: &mut T) -> io::Result<VaultRequest>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_framed(io, MAX_MESSAGE_SIZE).await
+    }
+
+    async fn read_response<T>(&mut self, _: &StreamProtocol, io: &mut T) -> io::Result<VaultResponse>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_framed(io, MAX_MESSAGE_SIZE).await
+    }
+
+    async fn write_request<T>(&mut self, _: &StreamProtocol, io: &mut T, request: VaultRequest) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_framed(io, &request).await
+    }
+
+    async fn write_response<T>(&mut self, _: &StreamProtocol, io: &mut T, response: VaultResponse) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_framed(io, &response).await
+    }
+}
+
+type ShareKey = (PeerId, String, u32);
+
+/// The shares this node holds for other nodes, persisted when storage is
+/// available.
+pub struct VaultShareStore {
+    shares: RwLock<HashMap<ShareKey, HeldShare>>,
+    storage: Option<Arc<NodeStorage>>,
+}
+
+impl VaultShareStore {
+    /// Loads the shares kept in `storage`, if any.
+    pub fn open(storage: Option<Arc<NodeStorage>>) -> Self {
+        let mut shares = HashMap::new();
+        if let Some(storage) = &storage {
+            match storage.load_vault_shares() {
+                Ok(stored) => {
+                    for share in stored {
+                        let Ok(owner) = PeerId::from_bytes(&share.owner) else {
+                            continue;
+                        };
+                        shares.insert(
+                            (owner, share.cookie_id.clone(), share.generation),
+                            HeldShare {
+                                cookie_id: share.cookie_id,
+                                generation: share.generation,
+                                index: share.index,
+                                value: share.value,
+                                commitments: share.commitments,
+                            },
+                        );
+                    }
+                    debug!("Loaded {} held vault shares from storage", shares.len());
+                }
+                Err(e) => warn!("Failed to load held vault shares: {}", e),
+            }
+        }
+        Self { shares: RwLock::new(shares), storage }
+    }
+
+    pub fn len(&self) -> usize {
+        self.shares.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shares.read().is_empty()
+    }
+
+    /// Answers `request` from `owner`.
+    pub fn handle(&self, owner: PeerId, request: VaultRequest) -> VaultResponse {
+        let result = match request {
+            VaultRequest::Store(share) => self.store(owner, share).map(|_| VaultResponse::Stored),
+            VaultRequest::Fetch { cookie_id, generation } => {
+                let share = self.shares.read().get(&(owner, cookie_id, generation)).cloned();
+                Ok(VaultResponse::Share(share))
+            }
+            VaultRequest::Release { cookie_id, generation } => {
+                self.release(owner, cookie_id, generation).map(|_| VaultResponse::Released)
+            }
+        };
+        result.unwrap_or_else(|e| VaultResponse::Refused(e.to_string()))
+    }
+
+    fn store(&self, owner: PeerId, share: HeldShare) -> NonosResult<()> {
+        if share.cookie_id.is_empty() || share.cookie_id.len() > MAX_COOKIE_ID_LEN {
+            return Err(NonosError::Config("Invalid cookie ID".into()));
+        }
+        share.verify()?;
+
+        let key = (owner, share.cookie_id.clone(), share.generation);
+        let mut shares = self.shares.write();
+        if !shares.contains_key(&key) {
+            if shares.len() >= MAX_HELD_SHARES {
+                return Err(NonosError::Internal("Holding too many shares".into()));
+            }
+            if shares.keys().filter(|(peer, _, _)| *peer == owner).count() >= MAX_SHARES_PER_OWNER {
+                return Err(NonosError::Internal("Holding too many shares for this peer".into()));
+            }
+        }
+
+        if let Some(storage) = &self.storage {
+            storage.store_vault_share(&StoredVaultShare {
+                owner: owner.to_bytes(),
+                cookie_id: share.cookie_id.clone(),
+                generation: share.generation,
+                index: share.index,
+                value: share.value.clone(),
+                commitments: share.commitments.clone(),
+            })?;
+        }
+        shares.insert(key, share);
+        Ok(())
+    }
+
+    fn release(&self, owner: PeerId, cookie_id: String, generation: u32) -> NonosResult<()> {
+        if let Some(storage) = &self.storage {
+            storage.remove_vault_share(&owner.to_bytes(), &cookie_id, generation)?;
+        }
+        self.shares.write().remove(&(owner, cookie_id, generation));
+        Ok(())
+    }
+}
+
+/// Delivers cookie vault shares to other nodes and fetches them back.
+/// Holds no lock on the network.
+#[derive(Clone)]
+pub struct VaultClient {
+    command_tx: mpsc::Sender<NetworkCommand>,
+    node_peers: NodePeers,
+}
+
+impl VaultClient {
+    pub(crate) fn new(command_tx: mpsc::Sender<NetworkCommand>, node_peers: NodePeers) -> Self {
+        Self { command_tx, node_peers }
+    }
+
+    /// Nodes whose peer is known, and so can hold shares.
+    pub fn known_holders(&self) -> Vec<[u8; 32]> {
+        self.node_peers.read().keys().map(|node| node.0).collect()
+    }
+
+    async fn request(&self, holder: &[u8; 32], request: VaultRequest) -> NonosResult<VaultResponse> {
+        let peer = self.node_peers.read().get(&NodeId::from_bytes(*holder)).copied()
+            .ok_or_else(|| NonosError::Network(format!("No known peer for holder {}", hex::encode(holder))))?;
+
+        let (reply, response) = oneshot::channel();
+        self.command_tx
+            .send(NetworkCommand::Vault { peer, request, reply })
+            .await
+            .map_err(|_| NonosError::Network("P2P network not running".into()))?;
+
+        let response = tokio::time::timeout(VAULT_REQUEST_TIMEOUT * 2, response)
+            .await
+            .map_err(|_| NonosError::Network(format!("Vault request to {} timed out", peer)))?
+            .map_err(|_| NonosError::Network("P2P network stopped".into()))??;
+        match response {
+            VaultResponse::Refused(reason) => {
+                Err(NonosError::Network(format!("Vault request refused by {}: {}", peer, reason)))
+            }
+            response => Ok(response),
+        }
+    }
+}
+
+#[async_trait]
+impl ShareTransport for VaultClient {
+    async fn store(&self, holder: &[u8; 32], share: HeldShare) -> NonosResult<()> {
+        match self.request(holder, VaultRequest::Store(share)).await? {
+            VaultResponse::Stored => Ok(()),
+            _ => Err(NonosError::Network("Unexpected vault response".into())),
+        }
+    }
+
+    async fn fetch(&self, holder: &[u8; 32], cookie_id: &str, generation: u32) -> NonosResult<Option<HeldShare>> {
+        let request = VaultRequest::Fetch { cookie_id: cookie_id.to_string(), generation };
+        match self.request(holder, request).await? {
+            VaultResponse::Share(share) => Ok(share),
+            _ => Err(NonosError::Network("Unexpected vault response".into())),
+        }
+    }
+
+    async fn release(&self, holder: &[u8; 32], cookie_id: &str, generation: u32) -> NonosResult<()> {
+        let request = VaultRequest::Release { cookie_id: cookie_id.to_string(), generation };
+        match self.request(holder, request).await? {
+            VaultResponse::Released => Ok(()),
+            _ => Err(NonosError::Network("Unexpected vault response".into())),
+        }
+    }
+}
+
+pub(crate) type VaultAnswer = (ResponseChannel<VaultResponse>, VaultResponse);
+
+/// The swarm's side of the vault protocol: outstanding requests it sent,
+/// and answers to inbound ones, computed off the swarm task since storing
+/// a share checks it and writes it to disk.
+pub(crate) struct VaultExchange {
+    store: Arc<VaultShareStore>,
+    answer_tx: mpsc::UnboundedSender<VaultAnswer>,
+    pending: HashMap<OutboundRequestId, oneshot::Sender<NonosResult<VaultResponse>>>,
+}
+
+impl VaultExchange {
+    pub fn new(store: Arc<VaultShareStore>, answer_tx: mpsc::UnboundedSender<VaultAnswer>) -> Self {
+        Self { store, answer_tx, pending: HashMap::new() }
+    }
+
+    pub fn sent(&mut self, request: OutboundRequestId, reply: oneshot::Sender<NonosResult<VaultResponse>>) {
+        self.pending.insert(request, reply);
+    }
+
+    pub fn answered(&mut self, request: OutboundRequestId, response: VaultResponse) {
+        if let Some(reply) = self.pending.remove(&request) {
+            let _ = reply.send(Ok(response));
+        }
+    }
+
+    pub fn failed(&mut self, request: OutboundRequestId, error: String) {
+        if let Some(reply) = self.pending.remove(&request) {
+            let _ = reply.send(Err(NonosError::Network(format!("Vault request failed: {}", error))));
+        }
+    }
+
+    pub fn serve(&self, peer: PeerId, channel: ResponseChannel<VaultResponse>, request: VaultRequest) {
+        let store = self.store.clone();
+        let answer_tx = self.answer_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let response = store.handle(peer, request);
+            if let VaultResponse::Refused(reason) = &response {
+                debug!("Refusing vault request from {}: {}", peer, reason);
+            }
+            let _ = answer_tx.send((channel, response));
+        });
+    }
+}
@@ -0,0 +1,110 @@
+//! Feldman verifiable secret sharing over the BN254 scalar field.
+//!
+//! The dealer publishes `a_j·G` for each coefficient `a_j` of the sharing
+//! polynomial; a share `s` at `x` is valid iff `s·G = Σ x^j·(a_j·G)`. The
+//! commitment to `a_0` reveals nothing about the secret beyond its discrete
+//! log, so the secret must be uniformly random, as a vault key is.
+
+use ark_bn254::{Fr, G1Affine, G1Projective};
+use ark_ec::{CurveGroup, Group};
+use ark_ff::{Field, One, UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::thread_rng;
+use nonos_types::{NonosError, NonosResult};
+
+pub(super) struct Dealing {
+    /// The share for index `i + 1` at position `i`.
+    pub shares: Vec<Fr>,
+    pub commitments: Vec<G1Affine>,
+}
+
+/// Splits `secret` into `count` shares, any `threshold` of which recover it.
+pub(super) fn deal(secret: Fr, threshold: u8, count: u8) -> Dealing {
+    let mut rng = thread_rng();
+    let mut coefficients = vec![secret];
+    coefficients.extend((1..threshold).map(|_| Fr::rand(&mut rng)));
+
+    let generator = G1Projective::generator();
+    let commitments = G1Projective::normalize_batch(
+        &coefficients.iter().map(|a| generator * a).collect::<Vec<_>>(),
+    );
+    let shares = (1..=count).map(|x| eval_poly(&coefficients, Fr::from(x))).collect();
+    Dealing { shares, commitments }
+}
+
+fn eval_poly(coefficients: &[Fr], x: Fr) -> Fr {
+    coefficients.iter().rev().fold(Fr::zero(), |acc, a| acc * x + a)
+}
+
+/// Whether `share` is the value at `index` of the polynomial committed to.
+pub(super) fn verify(commitments: &[G1Affine], index: u8, share: &Fr) -> bool {
+    if index == 0 || commitments.is_empty() {
+        return false;
+    }
+    let x = Fr::from(index);
+    let expected = commitments.iter().rev()
+        .fold(G1Projective::zero(), |acc, c| acc * x + c);
+    G1Projective::generator() * share == expected
+}
+
+/// Lagrange interpolation of the polynomial through `points` at zero.
+pub(super) fn interpolate_at_zero(points: &[(u8, Fr)]) -> Option<Fr> {
+    let mut secret = Fr::zero();
+    for (i, &(xi, yi)) in points.iter().enumerate() {
+        let xi = Fr::from(xi);
+        let mut basis = Fr::one();
+        for (j, &(xj, _)) in points.iter().enumerate() {
+            if i != j {
+                let xj = Fr::from(xj);
+                basis *= xj * (xj - xi).inverse()?;
+            }
+        }
+        secret += yi * basis;
+    }
+    Some(secret)
+}
+
+pub(super) fn random_scalar() -> Fr {
+    Fr::rand(&mut thread_rng())
+}
+
+pub(super) fn encode_scalar(scalar: &Fr) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(32);
+    scalar.serialize_compressed(&mut bytes).expect("Serializing to a Vec cannot fail");
+    bytes
+}
+
+/// Rejects encodings that are not a canonical field element.
+pub(super) fn decode_scalar(bytes: &[u8]) -> NonosResult<Fr> {
+    Fr::deserialize_compressed(bytes)
+        .map_err(|e| NonosError::Crypto(format!("Invalid share value: {}", e)))
+}
+
+pub(super) fn encode_commitments(commitments: &[G1Affine]) -> Vec<Vec<u8>> {
+    commitments.iter()
+        .map(|point| {
+            let mut bytes = Vec::with_capacity(32);
+            point.serialize_compressed(&mut bytes).expect("Serializing to a Vec cannot fail");
+            bytes
+        })
+        .collect()
+}
+
+/// Rejects points that are not on the curve.
+pub(super) fn decode_commitments(encoded: &[Vec<u8>]) -> NonosResult<Vec<G1Affine>> {
+    encoded.iter()
+        .map(|bytes| {
+            G1Affine::deserialize_compressed(bytes.as_slice())
+                .map_err(|e| NonosError::Crypto(format!("Invalid share commitment: {}", e)))
+        })
+        .collect()
+}
+
+/// Checks an encoded share against encoded commitments.
+pub(super) fn verify_encoded(commitments: &[Vec<u8>], index: u8, value: &[u8]) -> NonosResult<Fr> {
+    let share = decode_scalar(value)?;
+    if !verify(&decode_commitments(commitments)?, index, &share) {
+        return Err(NonosError::Crypto(format!("Share {} does not match its commitments", index)));
+    }
+    Ok(share)
+}
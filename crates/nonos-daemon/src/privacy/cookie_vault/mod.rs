@@ -1,6 +1,8 @@
-mod gf256;
+mod feldman;
+mod transport;
 mod types;
 mod vault;
 
-pub use types::SecretShare;
-pub use vault::DistributedCookieVault;
+pub use transport::ShareTransport;
+pub use types::{HeldShare, SecretShare};
+pub use vault::{DistributedCookieVault, MAX_COOKIE_ID_LEN};
@@ -0,0 +1,15 @@
+use super::HeldShare;
+use async_trait::async_trait;
+use nonos_types::NonosResult;
+
+/// How shares reach the nodes that hold them and come back.
+#[async_trait]
+pub trait ShareTransport: Send + Sync {
+    async fn store(&self, holder: &[u8; 32], share: HeldShare) -> NonosResult<()>;
+
+    /// The share `holder` keeps of `generation` of `cookie_id`, if any.
+    async fn fetch(&self, holder: &[u8; 32], cookie_id: &str, generation: u32) -> NonosResult<Option<HeldShare>>;
+
+    /// Asks `holder` to drop its share of `generation` of `cookie_id`.
+    async fn release(&self, holder: &[u8; 32], cookie_id: &str, generation: u32) -> NonosResult<()>;
+}
@@ -1,8 +1,33 @@
+use super::feldman;
+use nonos_types::NonosResult;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SecretShare {
     pub index: u8,
+    /// The holder's share of the cookie's key, an encoded BN254 scalar.
     pub value: Vec<u8>,
     pub node_id: [u8; 32],
 }
+
+/// A share as delivered to its holder, with the commitments it verifies
+/// against.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeldShare {
+    pub cookie_id: String,
+    /// Shares of different generations never combine.
+    pub generation: u32,
+    pub index: u8,
+    pub value: Vec<u8>,
+    /// Compressed BN254 G1 commitments to the sharing polynomial's
+    /// coefficients.
+    pub commitments: Vec<Vec<u8>>,
+}
+
+impl HeldShare {
+    /// Checks the share against its own commitments. Whether those are the
+    /// cookie's commitments is up to the cookie's owner to check.
+    pub fn verify(&self) -> NonosResult<()> {
+        feldman::verify_encoded(&self.commitments, self.index, &self.value).map(|_| ())
+    }
+}
@@ -1,18 +1,45 @@
+use ark_bn254::Fr;
+use futures::stream::{FuturesUnordered, StreamExt};
 use nonos_crypto::random_bytes;
 use nonos_types::{NonosError, NonosResult};
-use std::collections::HashMap;
+use rand::seq::SliceRandom;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::info;
+use tracing::{info, warn};
 
-use super::gf256;
-use super::types::SecretShare;
+use super::feldman;
+use super::transport::ShareTransport;
+use super::types::{HeldShare, SecretShare};
+use crate::storage::{NodeStorage, StoredVaultedCookie};
 
-#[derive(Debug)]
+/// Longest cookie ID the vault accepts.
+pub const MAX_COOKIE_ID_LEN: usize = 128;
+
+const SEALING_KEY_CONTEXT: &str = "nonos cookie vault sealing key v1";
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+
+/// Keeps cookies split across other nodes. Each cookie is sealed under a
+/// fresh key and only the key is shared, with Feldman commitments so that
+/// a share a holder corrupts is caught rather than silently breaking
+/// reconstruction. The vault keeps the sealed cookie and the commitments,
+/// neither of which reveals the cookie.
 pub struct DistributedCookieVault {
     threshold: u8,
     total_shares: u8,
-    shares: Arc<RwLock<HashMap<String, Vec<SecretShare>>>>,
+    cookies: Arc<RwLock<HashMap<String, StoredVaultedCookie>>>,
+    storage: Option<Arc<NodeStorage>>,
+}
+
+impl std::fmt::Debug for DistributedCookieVault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DistributedCookieVault")
+            .field("threshold", &self.threshold)
+            .field("total_shares", &self.total_shares)
+            .field("persistent", &self.storage.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 impl DistributedCookieVault {
@@ -29,10 +56,30 @@ impl DistributedCookieVault {
         Ok(Self {
             threshold,
             total_shares,
-            shares: Arc::new(RwLock::new(HashMap::new())),
+            cookies: Arc::new(RwLock::new(HashMap::new())),
+            storage: None,
         })
     }
 
+    /// Like [`DistributedCookieVault::new`], but vaulted cookies are
+    /// persisted to `storage` and brought back by
+    /// [`DistributedCookieVault::restore`].
+    pub fn with_storage(threshold: u8, total_shares: u8, storage: Arc<NodeStorage>) -> NonosResult<Self> {
+        Ok(Self { storage: Some(storage), ..Self::new(threshold, total_shares)? })
+    }
+
+    pub async fn restore(&self) -> NonosResult<usize> {
+        let Some(storage) = &self.storage else {
+            return Ok(0);
+        };
+        let stored = storage.load_vaulted_cookies()?;
+        let mut cookies = self.cookies.write().await;
+        for cookie in stored {
+            cookies.insert(cookie.cookie_id.clone(), cookie);
+        }
+        Ok(cookies.len())
+    }
+
     pub fn threshold(&self) -> u8 {
         self.threshold
     }
@@ -41,12 +88,32 @@ impl DistributedCookieVault {
         self.total_shares
     }
 
+    /// Seals `cookie_value` and splits its key among the first
+    /// `total_shares` of `node_ids`. Only the sealed cookie and the
+    /// commitments are kept; handing out the shares is up to the caller.
     pub async fn split_cookie(
         &self,
         cookie_id: &str,
         cookie_value: &[u8],
         node_ids: &[[u8; 32]],
     ) -> NonosResult<Vec<SecretShare>> {
+        let previous = self.cookie(cookie_id).await;
+        let generation = previous.as_ref().map_or(1, |cookie| cookie.generation + 1);
+        let (cookie, shares) = self.deal(cookie_id, cookie_value, node_ids, generation)?;
+        self.commit(cookie, previous.map(|cookie| cookie.generation)).await?;
+        Ok(shares)
+    }
+
+    fn deal(
+        &self,
+        cookie_id: &str,
+        cookie_value: &[u8],
+        node_ids: &[[u8; 32]],
+        generation: u32,
+    ) -> NonosResult<(StoredVaultedCookie, Vec<SecretShare>)> {
+        if cookie_id.is_empty() || cookie_id.len() > MAX_COOKIE_ID_LEN {
+            return Err(NonosError::Config(format!("Invalid cookie ID length: {}", cookie_id.len())));
+        }
         if node_ids.len() < self.total_shares as usize {
             return Err(NonosError::Internal(format!(
                 "Not enough nodes for sharing: need {}, got {}",
@@ -54,32 +121,31 @@ impl DistributedCookieVault {
                 node_ids.len()
             )));
         }
+        let holders = node_ids[..self.total_shares as usize].to_vec();
+        if holders.iter().collect::<HashSet<_>>().len() != holders.len() {
+            return Err(NonosError::Config("Each share needs a different node".into()));
+        }
 
-        let mut shares: Vec<SecretShare> = (0..self.total_shares)
-            .map(|i| SecretShare {
-                index: i + 1,
-                value: Vec::with_capacity(cookie_value.len()),
-                node_id: node_ids[i as usize],
+        let key = feldman::random_scalar();
+        let sealed = seal(&key, cookie_id, cookie_value)?;
+        let dealing = feldman::deal(key, self.threshold, self.total_shares);
+
+        let shares = dealing.shares.iter().zip(&holders).enumerate()
+            .map(|(i, (share, node_id))| SecretShare {
+                index: i as u8 + 1,
+                value: feldman::encode_scalar(share),
+                node_id: *node_id,
             })
             .collect();
-
-        for &secret_byte in cookie_value {
-            let mut coefficients = vec![secret_byte];
-            let random = random_bytes::<32>();
-            for i in 1..self.threshold as usize {
-                coefficients.push(random[i % 32]);
-            }
-
-            for share in &mut shares {
-                let y = gf256::eval_poly(&coefficients, share.index);
-                share.value.push(y);
-            }
-        }
-
-        {
-            let mut storage = self.shares.write().await;
-            storage.insert(cookie_id.to_string(), shares.clone());
-        }
+        let cookie = StoredVaultedCookie {
+            cookie_id: cookie_id.to_string(),
+            generation,
+            threshold: self.threshold,
+            holders,
+            commitments: feldman::encode_commitments(&dealing.commitments),
+            sealed,
+            updated_at: chrono::Utc::now().timestamp(),
+        };
 
         info!(
             "Split cookie '{}' ({} bytes) into {} shares (threshold: {})",
@@ -89,9 +155,35 @@ impl DistributedCookieVault {
             self.threshold
         );
 
-        Ok(shares)
+        Ok((cookie, shares))
+    }
+
+    /// Records `cookie`, provided the cookie is still at `previous`
+    /// generation; a concurrent re-share wins otherwise.
+    async fn commit(&self, cookie: StoredVaultedCookie, previous: Option<u32>) -> NonosResult<()> {
+        let mut cookies = self.cookies.write().await;
+        let current = cookies.get(&cookie.cookie_id).map(|cookie| cookie.generation);
+        if current != previous {
+            return Err(NonosError::Internal(format!(
+                "Cookie '{}' was re-shared concurrently", cookie.cookie_id
+            )));
+        }
+        if let Some(storage) = &self.storage {
+            storage.store_vaulted_cookie(&cookie)?;
+        }
+        cookies.insert(cookie.cookie_id.clone(), cookie);
+        Ok(())
+    }
+
+    /// Checks `share` against the commitments of `cookie_id`.
+    pub async fn verify_share(&self, cookie_id: &str, share: &SecretShare) -> NonosResult<()> {
+        let cookie = self.cookie(cookie_id).await
+            .ok_or_else(|| NonosError::Crypto(format!("Unknown cookie '{}'", cookie_id)))?;
+        feldman::verify_encoded(&cookie.commitments, share.index, &share.value).map(|_| ())
     }
 
+    /// Recovers the cookie from any `threshold` of `shares` that verify.
+    /// Shares that fail verification are skipped.
     pub async fn reconstruct_cookie(
         &self,
         cookie_id: &str,
@@ -105,14 +197,7 @@ impl DistributedCookieVault {
             )));
         }
 
-        let value_len = shares[0].value.len();
-        if !shares.iter().all(|s| s.value.len() == value_len) {
-            return Err(NonosError::Crypto(
-                "Share value lengths do not match".to_string(),
-            ));
-        }
-
-        let mut seen_indices = std::collections::HashSet::new();
+        let mut seen_indices = HashSet::new();
         for share in shares {
             if share.index == 0 {
                 return Err(NonosError::Crypto(
@@ -127,20 +212,9 @@ impl DistributedCookieVault {
             }
         }
 
-        let mut secret = Vec::with_capacity(value_len);
-
-        for byte_idx in 0..value_len {
-            let points: Vec<(u8, u8)> = shares
-                .iter()
-                .take(self.threshold as usize)
-                .map(|s| (s.index, s.value[byte_idx]))
-                .collect();
-
-            let secret_byte = gf256::interpolate_at_zero(&points).ok_or_else(|| {
-                NonosError::Crypto("Interpolation failed - possible duplicate share indices".into())
-            })?;
-            secret.push(secret_byte);
-        }
+        let cookie = self.cookie(cookie_id).await
+            .ok_or_else(|| NonosError::Crypto(format!("Unknown cookie '{}'", cookie_id)))?;
+        let secret = open_cookie(&cookie, shares)?;
 
         info!(
             "Reconstructed cookie '{}' ({} bytes) from {} shares",
@@ -152,26 +226,293 @@ impl DistributedCookieVault {
         Ok(secret)
     }
 
-    pub async fn get_shares(&self, cookie_id: &str) -> Option<Vec<SecretShare>> {
-        self.shares.read().await.get(cookie_id).cloned()
+    /// Splits `cookie_value` among the first `total_shares` of `holders`
+    /// and delivers each holder its share. Holders keep each generation
+    /// apart, so the earlier sharing stays usable until every holder has
+    /// accepted the new one; its holders are then asked to drop theirs.
+    pub async fn distribute(
+        &self,
+        cookie_id: &str,
+        cookie_value: &[u8],
+        holders: &[[u8; 32]],
+        transport: &dyn ShareTransport,
+    ) -> NonosResult<StoredVaultedCookie> {
+        let previous = self.cookie(cookie_id).await;
+        let generation = previous.as_ref().map_or(1, |cookie| cookie.generation + 1);
+        let (cookie, shares) = self.deal(cookie_id, cookie_value, holders, generation)?;
+
+        let deliveries: FuturesUnordered<_> = shares.iter()
+            .map(|share| async {
+                let held = HeldShare {
+                    cookie_id: cookie.cookie_id.clone(),
+                    generation,
+                    index: share.index,
+                    value: share.value.clone(),
+                    commitments: cookie.commitments.clone(),
+                };
+                (share.node_id, transport.store(&share.node_id, held).await)
+            })
+            .collect();
+        let results: Vec<_> = deliveries.collect().await;
+
+        let refused = results.iter().find_map(|(holder, result)| {
+            result.as_ref().err().map(|e| (holder, e.to_string()))
+        });
+        if let Some((holder, error)) = refused {
+            let delivered: Vec<_> = results.iter()
+                .filter(|(_, result)| result.is_ok())
+                .map(|(holder, _)| *holder)
+                .collect();
+            release_all(transport, &delivered, cookie_id, generation).await;
+            return Err(NonosError::Network(format!(
+                "Holder {} did not take its share: {}", hex::encode(holder), error
+            )));
+        }
+
+        if let Err(e) = self.commit(cookie.clone(), previous.as_ref().map(|cookie| cookie.generation)).await {
+            release_all(transport, &cookie.holders, cookie_id, generation).await;
+            return Err(e);
+        }
+        if let Some(previous) = previous {
+            release_all(transport, &previous.holders, cookie_id, previous.generation).await;
+        }
+        Ok(cookie)
     }
 
-    pub async fn remove_shares(&self, cookie_id: &str) -> Option<Vec<SecretShare>> {
-        self.shares.write().await.remove(cookie_id)
+    /// Recovers a distributed cookie from the first `threshold` holders
+    /// that return a share matching its commitments.
+    pub async fn collect(&self, cookie_id: &str, transport: &dyn ShareTransport) -> NonosResult<Vec<u8>> {
+        let cookie = self.cookie(cookie_id).await
+            .ok_or_else(|| NonosError::Crypto(format!("Unknown cookie '{}'", cookie_id)))?;
+        let (shares, _) = gather(&cookie, transport, cookie.threshold as usize).await;
+        open_cookie(&cookie, &shares)
+    }
+
+    /// Re-shares a distributed cookie among `holders` under a fresh key, so
+    /// shares left with former holders are of no further use.
+    pub async fn refresh(
+        &self,
+        cookie_id: &str,
+        holders: &[[u8; 32]],
+        transport: &dyn ShareTransport,
+    ) -> NonosResult<StoredVaultedCookie> {
+        let value = self.collect(cookie_id, transport).await?;
+        self.distribute(cookie_id, &value, holders, transport).await
+    }
+
+    /// Checks every holder of every distributed cookie and re-shares the
+    /// cookies that lost holders, replacing those with `candidates`.
+    /// Returns how many cookies were re-shared.
+    pub async fn maintain(&self, transport: &dyn ShareTransport, candidates: &[[u8; 32]]) -> usize {
+        let mut refreshed = 0;
+        for cookie in self.cookies().await {
+            let (shares, failed) = gather(&cookie, transport, cookie.holders.len()).await;
+            if failed.is_empty() {
+                continue;
+            }
+
+            let mut replacements: Vec<[u8; 32]> = candidates.iter()
+                .filter(|node| !cookie.holders.contains(node))
+                .copied()
+                .collect();
+            replacements.shuffle(&mut rand::thread_rng());
+            if replacements.len() < failed.len() {
+                warn!(
+                    "Cookie '{}' lost {} holders but only {} replacements are known",
+                    cookie.cookie_id, failed.len(), replacements.len()
+                );
+                continue;
+            }
+
+            let value = match open_cookie(&cookie, &shares) {
+                Ok(value) => value,
+                Err(e) => {
+                    warn!("Cannot re-share cookie '{}': {}", cookie.cookie_id, e);
+                    continue;
+                }
+            };
+            let holders: Vec<[u8; 32]> = cookie.holders.iter()
+                .filter(|holder| !failed.contains(holder))
+                .copied()
+                .chain(replacements.into_iter().take(failed.len()))
+                .collect();
+            match self.distribute(&cookie.cookie_id, &value, &holders, transport).await {
+                Ok(_) => {
+                    info!("Re-shared cookie '{}' after losing {} holders", cookie.cookie_id, failed.len());
+                    refreshed += 1;
+                }
+                Err(e) => warn!("Failed to re-share cookie '{}': {}", cookie.cookie_id, e),
+            }
+        }
+        refreshed
+    }
+
+    /// Drops a distributed cookie and asks its holders to drop their shares.
+    pub async fn forget(&self, cookie_id: &str, transport: &dyn ShareTransport) -> NonosResult<bool> {
+        let Some(cookie) = self.remove_cookie(cookie_id).await? else {
+            return Ok(false);
+        };
+        release_all(transport, &cookie.holders, cookie_id, cookie.generation).await;
+        Ok(true)
+    }
+
+    pub async fn cookie(&self, cookie_id: &str) -> Option<StoredVaultedCookie> {
+        self.cookies.read().await.get(cookie_id).cloned()
+    }
+
+    pub async fn cookies(&self) -> Vec<StoredVaultedCookie> {
+        self.cookies.read().await.values().cloned().collect()
+    }
+
+    /// Drops the record of `cookie_id`. Its holders keep their shares.
+    pub async fn remove_cookie(&self, cookie_id: &str) -> NonosResult<Option<StoredVaultedCookie>> {
+        if let Some(storage) = &self.storage {
+            storage.remove_vaulted_cookie(cookie_id)?;
+        }
+        Ok(self.cookies.write().await.remove(cookie_id))
     }
 
     pub async fn stored_cookie_count(&self) -> usize {
-        self.shares.read().await.len()
+        self.cookies.read().await.len()
     }
 
     pub async fn clear(&self) {
-        self.shares.write().await.clear();
+        self.cookies.write().await.clear();
     }
 }
 
+/// Fetches shares of `cookie` until `wanted` of them verify. Returns the
+/// valid shares and the holders that returned none or a bad one.
+async fn gather(
+    cookie: &StoredVaultedCookie,
+    transport: &dyn ShareTransport,
+    wanted: usize,
+) -> (Vec<SecretShare>, Vec<[u8; 32]>) {
+    let mut fetches: FuturesUnordered<_> = cookie.holders.iter().enumerate()
+        .map(|(i, holder)| async move {
+            (i as u8 + 1, *holder, transport.fetch(holder, &cookie.cookie_id, cookie.generation).await)
+        })
+        .collect();
+
+    let mut valid = Vec::new();
+    let mut failed = Vec::new();
+    while let Some((index, holder, fetched)) = fetches.next().await {
+        let checked = fetched.and_then(|held| {
+            let held = held.ok_or_else(|| NonosError::Crypto("No share held".into()))?;
+            if held.generation != cookie.generation || held.index != index || held.commitments != cookie.commitments {
+                return Err(NonosError::Crypto(format!(
+                    "Share is for generation {} index {}", held.generation, held.index
+                )));
+            }
+            feldman::verify_encoded(&cookie.commitments, index, &held.value)?;
+            Ok(held.value)
+        });
+        match checked {
+            Ok(value) => {
+                valid.push(SecretShare { index, value, node_id: holder });
+                if valid.len() >= wanted {
+                    break;
+                }
+            }
+            Err(e) => {
+                warn!("Holder {} of cookie '{}': {}", hex::encode(holder), cookie.cookie_id, e);
+                failed.push(holder);
+            }
+        }
+    }
+    (valid, failed)
+}
+
+async fn release_all(transport: &dyn ShareTransport, holders: &[[u8; 32]], cookie_id: &str, generation: u32) {
+    let releases: FuturesUnordered<_> = holders.iter()
+        .map(|holder| async move {
+            if let Err(e) = transport.release(holder, cookie_id, generation).await {
+                warn!("Holder {} did not drop its share of '{}': {}", hex::encode(holder), cookie_id, e);
+            }
+        })
+        .collect();
+    releases.collect::<Vec<_>>().await;
+}
+
+/// Recovers the key from the first `threshold` of `shares` that verify and
+/// opens the sealed cookie with it.
+fn open_cookie(cookie: &StoredVaultedCookie, shares: &[SecretShare]) -> NonosResult<Vec<u8>> {
+    let commitments = feldman::decode_commitments(&cookie.commitments)?;
+    let mut points: Vec<(u8, Fr)> = Vec::with_capacity(cookie.threshold as usize);
+    let mut rejected = 0;
+    for share in shares {
+        if points.len() == cookie.threshold as usize {
+            break;
+        }
+        match feldman::decode_scalar(&share.value) {
+            Ok(value) if !points.iter().any(|(x, _)| *x == share.index)
+                && feldman::verify(&commitments, share.index, &value) => {
+                points.push((share.index, value));
+            }
+            _ => rejected += 1,
+        }
+    }
+    if points.len() < cookie.threshold as usize {
+        return Err(NonosError::Crypto(format!(
+            "Insufficient valid shares: need {}, got {} ({} failed verification)",
+            cookie.threshold,
+            points.len(),
+            rejected
+        )));
+    }
+
+    let key = feldman::interpolate_at_zero(&points)
+        .ok_or_else(|| NonosError::Crypto("Interpolation failed - possible duplicate share indices".into()))?;
+    open(&key, &cookie.cookie_id, &cookie.sealed)
+}
+
+fn sealing_key(key: &Fr) -> [u8; 32] {
+    blake3::derive_key(SEALING_KEY_CONTEXT, &feldman::encode_scalar(key))
+}
+
+fn seal(key: &Fr, cookie_id: &str, value: &[u8]) -> NonosResult<Vec<u8>> {
+    use aes_gcm::{
+        aead::{Aead, KeyInit, Payload},
+        Aes256Gcm, Nonce,
+    };
+
+    let cipher = Aes256Gcm::new_from_slice(&sealing_key(key))
+        .map_err(|e| NonosError::Crypto(e.to_string()))?;
+    let nonce_bytes = random_bytes::<NONCE_SIZE>();
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), Payload { msg: value, aad: cookie_id.as_bytes() })
+        .map_err(|e| NonosError::Crypto(format!("Encryption failed: {}", e)))?;
+
+    let mut sealed = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+    sealed.extend_from_slice(&nonce_bytes);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+fn open(key: &Fr, cookie_id: &str, sealed: &[u8]) -> NonosResult<Vec<u8>> {
+    use aes_gcm::{
+        aead::{Aead, KeyInit, Payload},
+        Aes256Gcm, Nonce,
+    };
+
+    if sealed.len() < NONCE_SIZE + TAG_SIZE {
+        return Err(NonosError::Crypto("Sealed cookie too short".into()));
+    }
+    let cipher = Aes256Gcm::new_from_slice(&sealing_key(key))
+        .map_err(|e| NonosError::Crypto(e.to_string()))?;
+    cipher
+        .decrypt(
+            Nonce::from_slice(&sealed[..NONCE_SIZE]),
+            Payload { msg: &sealed[NONCE_SIZE..], aad: cookie_id.as_bytes() },
+        )
+        .map_err(|e| NonosError::Crypto(format!("Decryption failed: {}", e)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
 
     fn make_nodes(count: u8) -> Vec<[u8; 32]> {
         (0..count)
@@ -366,4 +707,179 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Threshold"));
     }
+
+    type HeldShares = HashMap<([u8; 32], String, u32), HeldShare>;
+
+    /// Holders that keep shares in memory; `down` holders fail every request.
+    #[derive(Default)]
+    struct MemoryHolders {
+        shares: Mutex<HeldShares>,
+        down: Mutex<HashSet<[u8; 32]>>,
+    }
+
+    impl MemoryHolders {
+        fn check(&self, holder: &[u8; 32]) -> NonosResult<()> {
+            if self.down.lock().unwrap().contains(holder) {
+                return Err(NonosError::Network("Holder unreachable".into()));
+            }
+            Ok(())
+        }
+
+        fn held(&self, holder: &[u8; 32], cookie_id: &str) -> Option<HeldShare> {
+            self.shares.lock().unwrap().iter()
+                .filter(|((h, c, _), _)| h == holder && c == cookie_id)
+                .max_by_key(|((_, _, generation), _)| *generation)
+                .map(|(_, share)| share.clone())
+        }
+    }
+
+    #[async_trait]
+    impl ShareTransport for MemoryHolders {
+        async fn store(&self, holder: &[u8; 32], share: HeldShare) -> NonosResult<()> {
+            self.check(holder)?;
+            share.verify()?;
+            self.shares.lock().unwrap().insert((*holder, share.cookie_id.clone(), share.generation), share);
+            Ok(())
+        }
+
+        async fn fetch(&self, holder: &[u8; 32], cookie_id: &str, generation: u32) -> NonosResult<Option<HeldShare>> {
+            self.check(holder)?;
+            Ok(self.shares.lock().unwrap().get(&(*holder, cookie_id.to_string(), generation)).cloned())
+        }
+
+        async fn release(&self, holder: &[u8; 32], cookie_id: &str, generation: u32) -> NonosResult<()> {
+            self.check(holder)?;
+            self.shares.lock().unwrap()
+                .remove(&(*holder, cookie_id.to_string(), generation));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_share_detected() {
+        let vault = DistributedCookieVault::new(3, 5).unwrap();
+        let cookie = b"session=abc";
+        let mut shares = vault.split_cookie("c", cookie, &make_nodes(5)).await.unwrap();
+
+        shares[0].value = feldman::encode_scalar(&feldman::random_scalar());
+        assert!(vault.verify_share("c", &shares[0]).await.is_err());
+        assert!(vault.verify_share("c", &shares[1]).await.is_ok());
+
+        // The bad share is skipped when enough good ones remain...
+        assert_eq!(vault.reconstruct_cookie("c", &shares).await.unwrap(), cookie.to_vec());
+        // ...and reported rather than yielding a wrong cookie otherwise.
+        let err = vault.reconstruct_cookie("c", &shares[0..3]).await.unwrap_err();
+        assert!(err.to_string().contains("failed verification"));
+    }
+
+    #[tokio::test]
+    async fn test_split_rejects_repeated_holders() {
+        let vault = DistributedCookieVault::new(2, 3).unwrap();
+        let nodes = [[1u8; 32], [2u8; 32], [1u8; 32]];
+        assert!(vault.split_cookie("c", b"v", &nodes).await.is_err());
+        assert!(vault.split_cookie(&"x".repeat(MAX_COOKIE_ID_LEN + 1), b"v", &make_nodes(3)).await.is_err());
+        assert_eq!(vault.stored_cookie_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_distribute_and_collect() {
+        let vault = DistributedCookieVault::new(3, 5).unwrap();
+        let holders = MemoryHolders::default();
+        let nodes = make_nodes(5);
+
+        let cookie = vault.distribute("c", b"session=abc", &nodes, &holders).await.unwrap();
+        assert_eq!(cookie.generation, 1);
+        assert_eq!(holders.shares.lock().unwrap().len(), 5);
+        assert!(!cookie.sealed.windows(11).any(|w| w == b"session=abc"));
+
+        // Two holders going away still leaves enough.
+        holders.down.lock().unwrap().extend([nodes[0], nodes[3]]);
+        assert_eq!(vault.collect("c", &holders).await.unwrap(), b"session=abc".to_vec());
+
+        // A holder tampering with its share is outvoted by the commitments.
+        holders.down.lock().unwrap().remove(&nodes[0]);
+        holders.shares.lock().unwrap().get_mut(&(nodes[1], "c".to_string(), 1)).unwrap().value =
+            feldman::encode_scalar(&feldman::random_scalar());
+        assert_eq!(vault.collect("c", &holders).await.unwrap(), b"session=abc".to_vec());
+
+        holders.down.lock().unwrap().insert(nodes[2]);
+        assert!(vault.collect("c", &holders).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_distribute_failure_leaves_previous_sharing() {
+        let vault = DistributedCookieVault::new(2, 3).unwrap();
+        let holders = MemoryHolders::default();
+        let nodes = make_nodes(4);
+        vault.distribute("c", b"v1", &nodes[..3], &holders).await.unwrap();
+
+        holders.down.lock().unwrap().insert(nodes[3]);
+        assert!(vault.distribute("c", b"v2", &nodes[1..], &holders).await.is_err());
+        assert_eq!(vault.cookie("c").await.unwrap().generation, 1);
+        assert_eq!(vault.collect("c", &holders).await.unwrap(), b"v1".to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_retires_old_shares() {
+        let vault = DistributedCookieVault::new(2, 3).unwrap();
+        let holders = MemoryHolders::default();
+        let nodes = make_nodes(5);
+        vault.distribute("c", b"secret", &nodes[..3], &holders).await.unwrap();
+        let departed = holders.held(&nodes[0], "c").unwrap();
+
+        let cookie = vault.refresh("c", &nodes[1..4], &holders).await.unwrap();
+        assert_eq!(cookie.generation, 2);
+        assert_eq!(cookie.holders, nodes[1..4].to_vec());
+        assert!(holders.held(&nodes[0], "c").is_none());
+        assert_eq!(holders.held(&nodes[1], "c").unwrap().generation, 2);
+
+        // A share kept by a departed holder does not verify against the new key.
+        let stale = SecretShare { index: departed.index, value: departed.value, node_id: nodes[0] };
+        assert!(vault.verify_share("c", &stale).await.is_err());
+        assert_eq!(vault.collect("c", &holders).await.unwrap(), b"secret".to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_maintain_replaces_lost_holders() {
+        let vault = DistributedCookieVault::new(2, 3).unwrap();
+        let holders = MemoryHolders::default();
+        let nodes = make_nodes(6);
+        vault.distribute("c", b"secret", &nodes[..3], &holders).await.unwrap();
+
+        assert_eq!(vault.maintain(&holders, &nodes).await, 0);
+        assert_eq!(vault.cookie("c").await.unwrap().generation, 1);
+
+        holders.down.lock().unwrap().insert(nodes[1]);
+        assert_eq!(vault.maintain(&holders, &nodes).await, 1);
+        let cookie = vault.cookie("c").await.unwrap();
+        assert_eq!(cookie.generation, 2);
+        assert!(!cookie.holders.contains(&nodes[1]));
+        assert!(cookie.holders.contains(&nodes[0]) && cookie.holders.contains(&nodes[2]));
+        assert_eq!(vault.collect("c", &holders).await.unwrap(), b"secret".to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_forget_releases_shares() {
+        let vault = DistributedCookieVault::new(2, 3).unwrap();
+        let holders = MemoryHolders::default();
+        vault.distribute("c", b"secret", &make_nodes(3), &holders).await.unwrap();
+
+        assert!(vault.forget("c", &holders).await.unwrap());
+        assert!(!vault.forget("c", &holders).await.unwrap());
+        assert!(holders.shares.lock().unwrap().is_empty());
+        assert_eq!(vault.stored_cookie_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_restore_from_storage() {
+        let storage = Arc::new(NodeStorage::in_memory().unwrap());
+        let holders = MemoryHolders::default();
+
+        let vault = DistributedCookieVault::with_storage(2, 3, storage.clone()).unwrap();
+        vault.distribute("c", b"secret", &make_nodes(3), &holders).await.unwrap();
+
+        let restored = DistributedCookieVault::with_storage(2, 3, storage).unwrap();
+        assert_eq!(restored.restore().await.unwrap(), 1);
+        assert_eq!(restored.collect("c", &holders).await.unwrap(), b"secret".to_vec());
+    }
 }
@@ -25,6 +25,15 @@ pub fn compute_proof_mac(
     blake3_derive_key("nonos-credential-mac", &[&key.0[..], &mac_input[..]].concat()).0
 }
 
+/// Binds a range proof to its challenge and expiry, so neither can be
+/// changed without invalidating it.
+pub fn range_proof_context(challenge: &[u8; 32], expires_at: u64) -> [u8; 32] {
+    let mut input = Vec::with_capacity(40);
+    input.extend_from_slice(challenge);
+    input.extend_from_slice(&expires_at.to_le_bytes());
+    blake3_derive_key("nonos-credential-range-context", &input).0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,16 +1,25 @@
-use nonos_crypto::random_bytes;
-use nonos_types::{NonosError, NonosResult};
+use ark_bn254::Bn254;
+use ark_groth16::{PreparedVerifyingKey, ProvingKey};
+use nonos_crypto::{
+    credential_commitment, credential_issuance_message, ed25519_verify, load_range_proving_key,
+    load_range_verifying_key, prove_range, random_bytes, verify_range, RangeProofInput,
+    RangePublicInputs,
+};
+use nonos_types::{Ed25519PublicKey, NonosError, NonosResult};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tracing::info;
 
-use super::helpers::{compute_commitment, compute_proof_mac};
+use super::helpers::{compute_commitment, compute_proof_mac, range_proof_context};
 use super::types::{CredentialInfo, CredentialProof, CredentialType, StoredCredential};
 
 pub struct CredentialManager {
     credentials: Arc<RwLock<HashMap<CredentialType, StoredCredential>>>,
     issued_proofs: Arc<RwLock<Vec<CredentialProof>>>,
     master_secret: [u8; 32],
+    range_proving_key: parking_lot::RwLock<Option<Arc<ProvingKey<Bn254>>>>,
+    range_verifying_key: parking_lot::RwLock<Option<PreparedVerifyingKey<Bn254>>>,
 }
 
 impl CredentialManager {
@@ -19,6 +28,8 @@ impl CredentialManager {
             credentials: Arc::new(RwLock::new(HashMap::new())),
             issued_proofs: Arc::new(RwLock::new(Vec::new())),
             master_secret: random_bytes::<32>(),
+            range_proving_key: parking_lot::RwLock::new(None),
+            range_verifying_key: parking_lot::RwLock::new(None),
         }
     }
 
@@ -27,9 +38,27 @@ impl CredentialManager {
             credentials: Arc::new(RwLock::new(HashMap::new())),
             issued_proofs: Arc::new(RwLock::new(Vec::new())),
             master_secret,
+            range_proving_key: parking_lot::RwLock::new(None),
+            range_verifying_key: parking_lot::RwLock::new(None),
         }
     }
 
+    /// Loads the credential range circuit proving key from `zk-keygen`,
+    /// needed to prove numeric credentials from an issuer.
+    pub fn load_range_proving_key(&self, pk_bytes: &[u8]) -> NonosResult<()> {
+        *self.range_proving_key.write() = Some(Arc::new(load_range_proving_key(pk_bytes)?));
+        info!("Loaded credential range proving key");
+        Ok(())
+    }
+
+    /// Loads the credential range circuit verifying key from `zk-keygen`,
+    /// needed to verify range proofs.
+    pub fn load_range_verifying_key(&self, vk_bytes: &[u8]) -> NonosResult<()> {
+        *self.range_verifying_key.write() = Some(load_range_verifying_key(vk_bytes)?);
+        info!("Loaded credential range verifying key");
+        Ok(())
+    }
+
     pub async fn store_credential(
         &self,
         credential_type: CredentialType,
@@ -51,6 +80,7 @@ impl CredentialManager {
             salt,
             issuer: None,
             signature: None,
+            range_value: None,
             created_at: now,
             expires_at: now + expiry_secs,
         };
@@ -82,6 +112,54 @@ impl CredentialManager {
             salt,
             issuer: Some(issuer),
             signature: Some(signature),
+            range_value: None,
+            created_at: now,
+            expires_at: now + expiry_secs,
+        };
+
+        self.credentials.write().await.insert(credential_type, credential);
+        Ok(commitment)
+    }
+
+    /// Stores a numeric credential whose `value` an issuer attested by
+    /// signing its commitment under `salt`. Proofs of it show the value
+    /// meets the credential's threshold without revealing it.
+    pub async fn store_issued_credential(
+        &self,
+        credential_type: CredentialType,
+        value: u64,
+        salt: [u8; 32],
+        issuer: &Ed25519PublicKey,
+        signature: &[u8; 64],
+        expiry_secs: u64,
+    ) -> NonosResult<[u8; 32]> {
+        let (kind, threshold) = credential_type.range_claim().ok_or_else(|| {
+            NonosError::Config(format!("{} is not a numeric credential", credential_type))
+        })?;
+        if value < threshold {
+            return Err(NonosError::Config(format!(
+                "Credential value does not meet {}", credential_type
+            )));
+        }
+
+        let commitment = credential_commitment(kind, value, &salt);
+        if !ed25519_verify(issuer, &credential_issuance_message(kind, &commitment), signature)? {
+            return Err(NonosError::Crypto("Invalid issuer signature".into()));
+        }
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let credential = StoredCredential {
+            credential_type: credential_type.clone(),
+            value: value.to_le_bytes().to_vec(),
+            commitment,
+            salt,
+            issuer: Some(issuer.0),
+            signature: Some(signature.to_vec()),
+            range_value: Some(value),
             created_at: now,
             expires_at: now + expiry_secs,
         };
@@ -107,17 +185,27 @@ impl CredentialManager {
         credential_type: &CredentialType,
         proof_ttl_secs: u64,
     ) -> NonosResult<CredentialProof> {
-        let creds = self.credentials.read().await;
-        let credential = creds
+        self.create_proof_with_challenge(credential_type, random_bytes::<32>(), proof_ttl_secs).await
+    }
+
+    /// Like [`CredentialManager::create_proof`], but answering a verifier's
+    /// `challenge` so the proof cannot be replayed to another verifier.
+    /// Range proofs are generated on the blocking pool.
+    pub async fn create_proof_with_challenge(
+        &self,
+        credential_type: &CredentialType,
+        challenge: [u8; 32],
+        proof_ttl_secs: u64,
+    ) -> NonosResult<CredentialProof> {
+        let credential = self.credentials.read().await
             .get(credential_type)
+            .cloned()
             .ok_or_else(|| NonosError::Internal("Credential not found".into()))?;
 
         if credential.is_expired() {
             return Err(NonosError::Internal("Credential has expired".into()));
         }
 
-        let challenge = random_bytes::<32>();
-
         let proof_mac = compute_proof_mac(
             &self.master_secret,
             &credential.salt,
@@ -130,29 +218,91 @@ impl CredentialManager {
             .unwrap_or_default()
             .as_secs();
 
+        let expires_at = now + proof_ttl_secs;
+
+        let range_proof = match (credential_type.range_claim(), credential.range_value) {
+            (Some((kind, threshold)), Some(value)) => {
+                let proving_key = self.range_proving_key.read().clone()
+                    .ok_or_else(|| NonosError::Crypto("Range proving key not loaded".into()))?;
+                let input = RangeProofInput {
+                    kind,
+                    value,
+                    salt: credential.salt,
+                    threshold,
+                    context: range_proof_context(&challenge, expires_at),
+                };
+                let (proof, _) = tokio::task::spawn_blocking(move || {
+                    prove_range(&proving_key, &input, &mut ark_std::rand::thread_rng())
+                })
+                .await
+                .map_err(|e| NonosError::Internal(format!("Range proving failed: {}", e)))??;
+                Some(proof)
+            }
+            _ => None,
+        };
+
         let proof = CredentialProof {
             credential_type: credential_type.clone(),
             commitment: credential.commitment,
             proof_mac,
             challenge,
             created_at: now,
-            expires_at: now + proof_ttl_secs,
-            issuer_signature: credential.signature.clone(),
+            expires_at,
+            issuer_signature: credential.signature,
+            issuer: credential.issuer,
+            range_proof,
         };
 
-        drop(creds);
         self.issued_proofs.write().await.push(proof.clone());
 
         Ok(proof)
     }
 
-    pub fn verify_proof(
+    /// Checks a proof of a numeric credential from any holder: that
+    /// `issuer` signed the commitment and that the committed value meets the
+    /// credential's threshold. Neither the value nor this manager's secret
+    /// is needed.
+    pub fn verify_proof(&self, proof: &CredentialProof, issuer: &Ed25519PublicKey) -> bool {
+        if proof.is_expired() || proof.issuer != Some(issuer.0) {
+            return false;
+        }
+        let Some((kind, threshold)) = proof.credential_type.range_claim() else {
+            return false;
+        };
+        let (Some(signature), Some(range_proof)) = (&proof.issuer_signature, &proof.range_proof) else {
+            return false;
+        };
+        let Ok(signature) = <[u8; 64]>::try_from(signature.as_slice()) else {
+            return false;
+        };
+        let message = credential_issuance_message(kind, &proof.commitment);
+        if !ed25519_verify(issuer, &message, &signature).unwrap_or(false) {
+            return false;
+        }
+
+        let verifying_key = self.range_verifying_key.read();
+        let Some(verifying_key) = verifying_key.as_ref() else {
+            return false;
+        };
+        let public = RangePublicInputs {
+            commitment: proof.commitment,
+            kind,
+            threshold,
+            context: range_proof_context(&proof.challenge, proof.expires_at),
+        };
+        verify_range(verifying_key, &public, range_proof).unwrap_or(false)
+    }
+
+    /// Checks a proof this manager created against the credential's
+    /// opening. Its MAC convinces no one else, so numeric credentials are
+    /// refused here and only accepted through [`CredentialManager::verify_proof`].
+    pub fn verify_opening(
         &self,
         proof: &CredentialProof,
         value: &[u8],
         salt: &[u8; 32],
     ) -> bool {
-        if proof.is_expired() {
+        if proof.is_expired() || proof.credential_type.range_claim().is_some() {
             return false;
         }
 
@@ -221,6 +371,41 @@ impl Default for CredentialManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nonos_crypto::{ed25519_sign, generate_ed25519_keypair, RangeKeys};
+    use std::sync::OnceLock;
+
+    /// Range circuit key bytes, generated once for the whole module.
+    fn range_keys() -> &'static (Vec<u8>, Vec<u8>) {
+        static KEYS: OnceLock<(Vec<u8>, Vec<u8>)> = OnceLock::new();
+        KEYS.get_or_init(|| {
+            let keys = RangeKeys::generate(&mut ark_std::rand::thread_rng()).unwrap();
+            (keys.proving_key_bytes().unwrap(), keys.verifying_key_bytes().unwrap())
+        })
+    }
+
+    fn prover() -> CredentialManager {
+        let manager = CredentialManager::new();
+        manager.load_range_proving_key(&range_keys().0).unwrap();
+        manager
+    }
+
+    fn verifier() -> CredentialManager {
+        let manager = CredentialManager::new();
+        manager.load_range_verifying_key(&range_keys().1).unwrap();
+        manager
+    }
+
+    fn issue(
+        credential_type: &CredentialType,
+        value: u64,
+    ) -> (Ed25519PublicKey, [u8; 32], [u8; 64]) {
+        let (issuer_sk, issuer_pk) = generate_ed25519_keypair();
+        let (kind, _) = credential_type.range_claim().unwrap();
+        let salt = random_bytes::<32>();
+        let commitment = credential_commitment(kind, value, &salt);
+        let signature = ed25519_sign(&issuer_sk, &credential_issuance_message(kind, &commitment));
+        (issuer_pk, salt, signature)
+    }
 
     #[tokio::test]
     async fn test_store_and_check_credential() {
@@ -363,4 +548,120 @@ mod tests {
 
         assert_eq!(proof.issuer_signature, Some(signature));
     }
+
+    #[tokio::test]
+    async fn test_issued_credential_range_proof() {
+        let holder = prover();
+        let credential_type = CredentialType::AgeOver(18);
+        let (issuer, salt, signature) = issue(&credential_type, 25);
+
+        holder
+            .store_issued_credential(credential_type.clone(), 25, salt, &issuer, &signature, 3600)
+            .await
+            .unwrap();
+
+        let proof = holder
+            .create_proof_with_challenge(&credential_type, [0x42; 32], 300)
+            .await
+            .unwrap();
+        assert!(proof.range_proof.is_some());
+        assert_eq!(proof.issuer, Some(issuer.0));
+
+        let verifier = verifier();
+        assert!(verifier.verify_proof(&proof, &issuer));
+
+        let (_, other_issuer) = generate_ed25519_keypair();
+        assert!(!verifier.verify_proof(&proof, &other_issuer));
+
+        let mut raised = proof.clone();
+        raised.credential_type = CredentialType::AgeOver(21);
+        assert!(!verifier.verify_proof(&raised, &issuer));
+
+        let mut extended = proof.clone();
+        extended.expires_at += 3600;
+        assert!(!verifier.verify_proof(&extended, &issuer));
+
+        let mut replayed = proof.clone();
+        replayed.challenge = [0x43; 32];
+        assert!(!verifier.verify_proof(&replayed, &issuer));
+
+        let mut swapped = proof;
+        swapped.commitment[0] ^= 1;
+        assert!(!verifier.verify_proof(&swapped, &issuer));
+    }
+
+    #[tokio::test]
+    async fn test_issued_credential_rejects_bad_issuance() {
+        let manager = CredentialManager::new();
+        let credential_type = CredentialType::StakeOver(1000);
+        let (issuer, salt, signature) = issue(&credential_type, 1500);
+
+        let wrong_value = manager
+            .store_issued_credential(credential_type.clone(), 1600, salt, &issuer, &signature, 3600)
+            .await;
+        assert!(wrong_value.is_err());
+
+        let below = issue(&credential_type, 999);
+        let below_threshold = manager
+            .store_issued_credential(credential_type.clone(), 999, below.1, &below.0, &below.2, 3600)
+            .await;
+        assert!(below_threshold.is_err());
+
+        let not_numeric = manager
+            .store_issued_credential(CredentialType::MemberOf("premium".into()), 1500, salt, &issuer, &signature, 3600)
+            .await;
+        assert!(not_numeric.is_err());
+
+        assert!(!manager.has_credential(&credential_type).await);
+    }
+
+    #[tokio::test]
+    async fn test_range_proof_requires_proving_key() {
+        let manager = CredentialManager::new();
+        let credential_type = CredentialType::AccountAgeOver(30);
+        let (issuer, salt, signature) = issue(&credential_type, 90);
+
+        manager
+            .store_issued_credential(credential_type.clone(), 90, salt, &issuer, &signature, 3600)
+            .await
+            .unwrap();
+
+        assert!(manager.create_proof(&credential_type, 300).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unissued_proof_does_not_verify() {
+        let manager = CredentialManager::new();
+        let value = 1500u64.to_le_bytes();
+        manager
+            .store_credential(CredentialType::StakeOver(1000), &value, 3600)
+            .await
+            .unwrap();
+
+        let proof = manager
+            .create_proof(&CredentialType::StakeOver(1000), 300)
+            .await
+            .unwrap();
+        assert!(proof.range_proof.is_none());
+
+        let (_, issuer) = generate_ed25519_keypair();
+        assert!(!verifier().verify_proof(&proof, &issuer));
+
+        // Not even the holder accepts a MAC for a numeric credential.
+        let salt = manager.credentials.read().await[&CredentialType::StakeOver(1000)].salt;
+        assert!(!manager.verify_opening(&proof, &value, &salt));
+    }
+
+    #[tokio::test]
+    async fn test_verify_opening() {
+        let manager = CredentialManager::new();
+        let credential_type = CredentialType::MemberOf("premium".into());
+        manager.store_credential(credential_type.clone(), b"gold", 3600).await.unwrap();
+
+        let proof = manager.create_proof(&credential_type, 300).await.unwrap();
+        let salt = manager.credentials.read().await[&credential_type].salt;
+        assert!(manager.verify_opening(&proof, b"gold", &salt));
+        assert!(!manager.verify_opening(&proof, b"silver", &salt));
+        assert!(!CredentialManager::new().verify_opening(&proof, b"gold", &salt));
+    }
 }
@@ -10,6 +10,19 @@ pub enum CredentialType {
     Custom(String),
 }
 
+impl CredentialType {
+    /// The range circuit's kind tag and the threshold to prove, for
+    /// credentials that claim a number is at least some value.
+    pub fn range_claim(&self) -> Option<(u64, u64)> {
+        match self {
+            CredentialType::AgeOver(age) => Some((1, u64::from(*age))),
+            CredentialType::StakeOver(amount) => Some((2, *amount)),
+            CredentialType::AccountAgeOver(days) => Some((3, u64::from(*days))),
+            _ => None,
+        }
+    }
+}
+
 impl std::fmt::Display for CredentialType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -31,6 +44,10 @@ pub struct StoredCredential {
     pub salt: [u8; 32],
     pub issuer: Option<[u8; 32]>,
     pub signature: Option<Vec<u8>>,
+    /// The number an issuer attested, for credentials that can be proven
+    /// with the range circuit.
+    #[serde(default)]
+    pub range_value: Option<u64>,
     pub created_at: u64,
     pub expires_at: u64,
 }
@@ -62,6 +79,13 @@ pub struct CredentialProof {
     pub created_at: u64,
     pub expires_at: u64,
     pub issuer_signature: Option<Vec<u8>>,
+    /// Ed25519 key of the issuer that signed the commitment.
+    #[serde(default)]
+    pub issuer: Option<[u8; 32]>,
+    /// Groth16 proof that the committed value meets the credential's
+    /// threshold, for numeric credentials from an issuer.
+    #[serde(default)]
+    pub range_proof: Option<Vec<u8>>,
 }
 
 impl CredentialProof {
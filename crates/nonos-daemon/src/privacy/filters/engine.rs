@@ -0,0 +1,214 @@
+use super::parser::{parse_line, NetworkFilter, ParsedLine};
+use super::pattern::is_token_char;
+use super::request::{host_suffixes, FilterRequest};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Tokens too common in URLs to narrow anything down; a filter only falls
+/// back to one when it has nothing better.
+const COMMON_TOKENS: &[&str] = &["http", "https", "www", "com", "net", "org", "js", "html", "php"];
+
+/// The outcome of checking a request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FilterDecision {
+    Allow,
+    Block { rule: String },
+    /// A blocking rule matched but an exception overrode it.
+    Exception { rule: String },
+}
+
+impl FilterDecision {
+    pub fn is_blocked(&self) -> bool {
+        matches!(self, FilterDecision::Block { .. })
+    }
+}
+
+/// What a compile made of its input.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct FilterListStats {
+    pub blocking: usize,
+    pub exceptions: usize,
+    pub removeparam: usize,
+    pub cosmetic_skipped: usize,
+    pub unsupported_skipped: usize,
+}
+
+/// Filters indexed for lookup: plain `||host^` filters by host, the rest by
+/// their rarest usable token, and anything without a token in a list that
+/// every request is checked against.
+#[derive(Default)]
+struct FilterIndex {
+    filters: Vec<NetworkFilter>,
+    by_host: HashMap<String, Vec<u32>>,
+    by_token: HashMap<String, Vec<u32>>,
+    untokenized: Vec<u32>,
+}
+
+impl FilterIndex {
+    fn insert(&mut self, filter: NetworkFilter, token_counts: &HashMap<String, usize>) {
+        let index = self.filters.len() as u32;
+        if let Some(host) = filter.pattern.plain_host() {
+            self.by_host.entry(host.to_string()).or_default().push(index);
+        } else {
+            let token = filter.pattern.tokens()
+                .into_iter()
+                .min_by_key(|token| {
+                    let common = COMMON_TOKENS.contains(&token.as_str());
+                    (common, token_counts.get(token).copied().unwrap_or(0), usize::MAX - token.len())
+                });
+            match token {
+                Some(token) => self.by_token.entry(token).or_default().push(index),
+                None => self.untokenized.push(index),
+            }
+        }
+        self.filters.push(filter);
+    }
+
+    /// The first filter matching `request`, or with `prefer_important` an
+    /// important one if any matches. Each filter sits in exactly one
+    /// bucket, so none is checked twice.
+    fn find<'a>(&'a self, request: &FilterRequest, tokens: &[&str], prefer_important: bool) -> Option<&'a NetworkFilter> {
+        let hosts = host_suffixes(request.hostname()).filter_map(|host| self.by_host.get(host));
+        let tokens = tokens.iter().filter_map(|token| self.by_token.get(*token));
+
+        let mut found: Option<&NetworkFilter> = None;
+        for index in hosts.chain(tokens).flatten().chain(&self.untokenized) {
+            let filter = &self.filters[*index as usize];
+            if !filter.matches(request) {
+                continue;
+            }
+            if !prefer_important || filter.important {
+                return Some(filter);
+            }
+            found.get_or_insert(filter);
+        }
+        found
+    }
+
+    fn len(&self) -> usize {
+        self.filters.len()
+    }
+}
+
+/// Compiled filter lists.
+#[derive(Default)]
+pub struct FilterEngine {
+    blocking: FilterIndex,
+    exceptions: FilterIndex,
+    removeparam: Vec<NetworkFilter>,
+    removeparam_exceptions: Vec<NetworkFilter>,
+    stats: FilterListStats,
+}
+
+impl FilterEngine {
+    /// Parses and indexes `lists`, each the text of one filter list.
+    pub fn compile<'a>(lists: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut stats = FilterListStats::default();
+        let mut filters = Vec::new();
+        for line in lists.into_iter().flat_map(str::lines) {
+            match parse_line(line) {
+                ParsedLine::Filter(filter) => filters.push(*filter),
+                ParsedLine::Cosmetic => stats.cosmetic_skipped += 1,
+                ParsedLine::Unsupported => stats.unsupported_skipped += 1,
+                ParsedLine::Ignored => {}
+            }
+        }
+
+        // Index each filter under the token fewest other filters use, so
+        // the buckets a URL touches stay short.
+        let mut token_counts: HashMap<String, usize> = HashMap::new();
+        for filter in &filters {
+            for token in filter.pattern.tokens() {
+                *token_counts.entry(token).or_default() += 1;
+            }
+        }
+
+        let mut engine = Self::default();
+        for filter in filters {
+            match (filter.exception, filter.removeparam.is_some()) {
+                (false, true) => engine.removeparam.push(filter),
+                (true, true) => engine.removeparam_exceptions.push(filter),
+                (true, false) => engine.exceptions.insert(filter, &token_counts),
+                (false, false) => engine.blocking.insert(filter, &token_counts),
+            }
+        }
+
+        stats.blocking = engine.blocking.len();
+        stats.exceptions = engine.exceptions.len();
+        stats.removeparam = engine.removeparam.len();
+        engine.stats = stats;
+        engine
+    }
+
+    pub fn stats(&self) -> FilterListStats {
+        self.stats
+    }
+
+    pub fn check(&self, request: &FilterRequest) -> FilterDecision {
+        let tokens = url_tokens(&request.lowered);
+        let Some(block) = self.blocking.find(request, &tokens, true) else {
+            return FilterDecision::Allow;
+        };
+        if block.important {
+            return FilterDecision::Block { rule: block.text.clone() };
+        }
+
+        match self.exceptions.find(request, &tokens, false) {
+            Some(exception) => FilterDecision::Exception { rule: exception.text.clone() },
+            None => FilterDecision::Block { rule: block.text.clone() },
+        }
+    }
+
+    /// The request URL without the query parameters `$removeparam` filters
+    /// strip from it, or `None` if nothing is removed.
+    pub fn strip_params(&self, request: &FilterRequest) -> Option<String> {
+        let url = request.url();
+        let (base, rest) = url.split_once('?')?;
+        let (query, fragment) = match rest.split_once('#') {
+            Some((query, fragment)) => (query, Some(fragment)),
+            None => (rest, None),
+        };
+
+        let rules: Vec<_> = self.removeparam.iter().filter(|filter| filter.matches(request)).collect();
+        if rules.is_empty() {
+            return None;
+        }
+        let exceptions: Vec<_> = self.removeparam_exceptions.iter().filter(|filter| filter.matches(request)).collect();
+
+        let removed = |name: &str| {
+            rules.iter().any(|rule| rule.removeparam.as_ref().is_some_and(|param| param.removes(name)))
+                && !exceptions.iter().any(|rule| rule.removeparam.as_ref().is_some_and(|param| param.removes(name)))
+        };
+
+        let params: Vec<&str> = query.split('&').filter(|param| !param.is_empty()).collect();
+        let kept: Vec<&str> = params.iter()
+            .copied()
+            .filter(|param| !removed(param.split('=').next().unwrap_or(param)))
+            .collect();
+        if kept.len() == params.len() {
+            return None;
+        }
+
+        let mut stripped = base.to_string();
+        if !kept.is_empty() {
+            stripped.push('?');
+            stripped.push_str(&kept.join("&"));
+        }
+        if let Some(fragment) = fragment {
+            stripped.push('#');
+            stripped.push_str(fragment);
+        }
+        Some(stripped)
+    }
+}
+
+/// Distinct runs of token characters in a lowercased URL.
+fn url_tokens(url: &str) -> Vec<&str> {
+    let mut tokens: Vec<&str> = url
+        .split(|c: char| !c.is_ascii() || !is_token_char(c as u8))
+        .filter(|token| !token.is_empty())
+        .collect();
+    tokens.sort_unstable();
+    tokens.dedup();
+    tokens
+}
@@ -0,0 +1,22 @@
+//! Adblock Plus / uBlock Origin network filter engine.
+//!
+//! Parses the network subset of the filter syntax (`||host^` anchors, `*`
+//! and `^` wildcards, `@@` exceptions, and the `$third-party`, type,
+//! `domain=`, `match-case`, `important` and `removeparam` options) and
+//! compiles it into an indexed matcher. Cosmetic filters, regex filters and
+//! options this engine cannot honour are skipped and counted.
+
+mod engine;
+mod parser;
+mod pattern;
+mod request;
+
+pub use engine::{FilterDecision, FilterEngine, FilterListStats};
+pub use request::{FilterRequest, RequestType};
+pub(crate) use request::host_suffixes;
+
+/// Rules used until, and alongside, lists loaded from disk.
+pub const BUILTIN_RULES: &str = include_str!("builtin.txt");
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,173 @@
+use super::pattern::Pattern;
+use super::request::{is_subdomain_of, FilterRequest, RequestType};
+
+/// What `$removeparam` strips.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum RemoveParam {
+    All,
+    Named(String),
+    /// `~name`: every parameter but this one.
+    AllExcept(String),
+}
+
+impl RemoveParam {
+    pub fn removes(&self, name: &str) -> bool {
+        match self {
+            RemoveParam::All => true,
+            RemoveParam::Named(param) => param == name,
+            RemoveParam::AllExcept(param) => param != name,
+        }
+    }
+}
+
+/// One parsed network filter.
+#[derive(Clone, Debug)]
+pub(crate) struct NetworkFilter {
+    pub text: String,
+    pub pattern: Pattern,
+    pub exception: bool,
+    pub important: bool,
+    third_party: Option<bool>,
+    include_types: u16,
+    exclude_types: u16,
+    include_domains: Vec<String>,
+    exclude_domains: Vec<String>,
+    pub removeparam: Option<RemoveParam>,
+}
+
+impl NetworkFilter {
+    pub fn matches(&self, request: &FilterRequest) -> bool {
+        self.options_match(request) && self.pattern.matches(request)
+    }
+
+    fn options_match(&self, request: &FilterRequest) -> bool {
+        if self.third_party.is_some_and(|third_party| third_party != request.third_party) {
+            return false;
+        }
+
+        let bit = request.request_type.bit();
+        if (self.include_types != 0 && self.include_types & bit == 0) || self.exclude_types & bit != 0 {
+            return false;
+        }
+
+        if self.include_domains.is_empty() && self.exclude_domains.is_empty() {
+            return true;
+        }
+        let Some(source) = request.source_host.as_deref() else {
+            return self.include_domains.is_empty();
+        };
+        if self.exclude_domains.iter().any(|domain| is_subdomain_of(source, domain)) {
+            return false;
+        }
+        self.include_domains.is_empty() || self.include_domains.iter().any(|domain| is_subdomain_of(source, domain))
+    }
+}
+
+/// How a line of a filter list was handled.
+#[derive(Debug)]
+pub(crate) enum ParsedLine {
+    Filter(Box<NetworkFilter>),
+    /// Blank lines, comments and list headers.
+    Ignored,
+    /// Element hiding and scriptlet rules, which only a browser can apply.
+    Cosmetic,
+    /// Regex filters and options this engine cannot honour. Skipping the
+    /// whole rule is safer than applying it more broadly than intended.
+    Unsupported,
+}
+
+pub(crate) fn parse_line(line: &str) -> ParsedLine {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('!') || line.starts_with('[') {
+        return ParsedLine::Ignored;
+    }
+    if ["##", "#@#", "#?#", "#$#", "#%#"].iter().any(|marker| line.contains(marker)) {
+        return ParsedLine::Cosmetic;
+    }
+
+    let (exception, rule) = match line.strip_prefix("@@") {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+
+    if is_regex(rule) {
+        return ParsedLine::Unsupported;
+    }
+    let (pattern, options) = match rule.rfind('$') {
+        Some(index) => (&rule[..index], Some(&rule[index + 1..])),
+        None => (rule, None),
+    };
+    if is_regex(pattern) {
+        return ParsedLine::Unsupported;
+    }
+
+    let mut filter = NetworkFilter {
+        text: line.to_string(),
+        pattern: Pattern::parse("", false),
+        exception,
+        important: false,
+        third_party: None,
+        include_types: 0,
+        exclude_types: 0,
+        include_domains: Vec::new(),
+        exclude_domains: Vec::new(),
+        removeparam: None,
+    };
+
+    let mut match_case = false;
+    for option in options.into_iter().flat_map(|options| options.split(',')) {
+        let option = option.trim();
+        let (negated, name) = match option.strip_prefix('~') {
+            Some(name) => (true, name),
+            None => (false, option),
+        };
+        let (name, value) = match name.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (name, None),
+        };
+
+        match (name, value) {
+            ("third-party" | "3p", None) => filter.third_party = Some(!negated),
+            ("first-party" | "1p", None) => filter.third_party = Some(negated),
+            ("match-case", None) if !negated => match_case = true,
+            ("important", None) if !negated => filter.important = true,
+            ("domain" | "from", Some(domains)) if !negated => {
+                for domain in domains.split('|').map(str::trim).filter(|domain| !domain.is_empty()) {
+                    match domain.strip_prefix('~') {
+                        Some(domain) => filter.exclude_domains.push(domain.to_ascii_lowercase()),
+                        None => filter.include_domains.push(domain.to_ascii_lowercase()),
+                    }
+                }
+            }
+            ("removeparam" | "queryprune", value) if !negated => {
+                filter.removeparam = Some(match value.unwrap_or("") {
+                    "" => RemoveParam::All,
+                    value if is_regex(value) => return ParsedLine::Unsupported,
+                    value => match value.strip_prefix('~') {
+                        Some(kept) => RemoveParam::AllExcept(kept.to_string()),
+                        None => RemoveParam::Named(value.to_string()),
+                    },
+                });
+            }
+            (name, None) => match RequestType::from_option(name) {
+                Some(request_type) if negated => filter.exclude_types |= request_type.bit(),
+                Some(request_type) => filter.include_types |= request_type.bit(),
+                None => return ParsedLine::Unsupported,
+            },
+            _ => return ParsedLine::Unsupported,
+        }
+    }
+
+    // A bare `$removeparam=...` applies to every URL; any other empty
+    // pattern would block everything, which no list means.
+    if pattern.is_empty() && filter.removeparam.is_none() {
+        return ParsedLine::Unsupported;
+    }
+
+    filter.pattern = Pattern::parse(pattern, match_case);
+    ParsedLine::Filter(Box::new(filter))
+}
+
+fn is_regex(text: &str) -> bool {
+    text.len() > 2 && text.starts_with('/') && text.ends_with('/')
+}
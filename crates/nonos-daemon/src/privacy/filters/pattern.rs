@@ -0,0 +1,180 @@
+use super::request::FilterRequest;
+
+/// Where the start of a pattern must sit in the URL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LeftAnchor {
+    None,
+    /// `|`: the start of the URL.
+    Start,
+    /// `||`: the start of the host or of one of its labels.
+    Hostname,
+}
+
+/// A network filter pattern: literal parts separated by `*`, where `^`
+/// inside a part matches one separator character or the end of the URL.
+#[derive(Clone, Debug)]
+pub(crate) struct Pattern {
+    parts: Vec<Vec<u8>>,
+    left: LeftAnchor,
+    right: bool,
+    match_case: bool,
+}
+
+impl Pattern {
+    pub fn parse(mut text: &str, match_case: bool) -> Self {
+        let left = if let Some(rest) = text.strip_prefix("||") {
+            text = rest;
+            LeftAnchor::Hostname
+        } else if let Some(rest) = text.strip_prefix('|') {
+            text = rest;
+            LeftAnchor::Start
+        } else {
+            LeftAnchor::None
+        };
+
+        let right = match text.strip_suffix('|') {
+            Some(rest) => {
+                text = rest;
+                true
+            }
+            None => false,
+        };
+
+        let text = if match_case { text.to_string() } else { text.to_ascii_lowercase() };
+        let mut parts: Vec<Vec<u8>> = text.split('*').map(|part| part.as_bytes().to_vec()).collect();
+        // Leading and trailing wildcards only cancel the adjacent anchor.
+        let left = if parts.len() > 1 && parts[0].is_empty() { LeftAnchor::None } else { left };
+        let right = right && !(parts.len() > 1 && parts.last().is_some_and(Vec::is_empty));
+        parts.retain(|part| !part.is_empty());
+
+        Self { parts, left, right, match_case }
+    }
+
+    /// The host a `||host^` or `||host` pattern names, if that is all the
+    /// pattern says, so the filter can be indexed by host.
+    pub fn plain_host(&self) -> Option<&str> {
+        if self.left != LeftAnchor::Hostname || self.right || self.parts.len() != 1 {
+            return None;
+        }
+        let part = self.parts[0].strip_suffix(b"^").unwrap_or(&self.parts[0]);
+        let host = std::str::from_utf8(part).ok()?;
+        let valid = !host.is_empty()
+            && host.bytes().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'.' || c == b'-');
+        valid.then_some(host)
+    }
+
+    /// Candidate index keys: runs of token characters that any matching
+    /// URL must contain as a whole token.
+    pub fn tokens(&self) -> Vec<String> {
+        let mut tokens = Vec::new();
+        let last = self.parts.len().saturating_sub(1);
+
+        for (index, part) in self.parts.iter().enumerate() {
+            let mut start = 0;
+            while start < part.len() {
+                if !is_token_char(part[start]) {
+                    start += 1;
+                    continue;
+                }
+                let mut end = start;
+                while end < part.len() && is_token_char(part[end]) {
+                    end += 1;
+                }
+
+                let bounded_left = start > 0 || (index == 0 && self.left != LeftAnchor::None);
+                let bounded_right = end < part.len() || (index == last && self.right);
+                if bounded_left && bounded_right {
+                    if let Ok(token) = std::str::from_utf8(&part[start..end]) {
+                        tokens.push(token.to_ascii_lowercase());
+                    }
+                }
+                start = end;
+            }
+        }
+        tokens
+    }
+
+    pub fn matches(&self, request: &FilterRequest) -> bool {
+        let text = if self.match_case { request.url.as_bytes() } else { request.lowered.as_bytes() };
+        let Some(first) = self.parts.first() else {
+            return true;
+        };
+
+        match self.left {
+            LeftAnchor::Start => self.matches_from(text, 0),
+            LeftAnchor::Hostname => {
+                let host = request.host.clone();
+                std::iter::once(host.start)
+                    .chain(host.clone().filter(|&i| text[i] == b'.').map(|i| i + 1))
+                    .filter(|&start| start < host.end)
+                    .any(|start| self.matches_from(text, start))
+            }
+            LeftAnchor::None if self.right => {
+                (0..=text.len()).any(|start| self.matches_from(text, start))
+            }
+            LeftAnchor::None => {
+                // Without a right anchor the earliest match of the first
+                // part leaves the most room for the rest.
+                find_part(first, text, 0).is_some_and(|(start, _)| self.matches_from(text, start))
+            }
+        }
+    }
+
+    /// Whether the pattern matches with its first part at `start`.
+    fn matches_from(&self, text: &[u8], start: usize) -> bool {
+        let Some(mut position) = match_part(&self.parts[0], text, start) else {
+            return false;
+        };
+        let Some((last, middle)) = self.parts[1..].split_last() else {
+            return !self.right || position == text.len();
+        };
+
+        for part in middle {
+            match find_part(part, text, position) {
+                Some((_, end)) => position = end,
+                None => return false,
+            }
+        }
+
+        if self.right {
+            (position..=text.len()).any(|start| match_part(last, text, start) == Some(text.len()))
+        } else {
+            find_part(last, text, position).is_some()
+        }
+    }
+}
+
+/// Characters that make up index tokens. Separators never are, so a token
+/// bounded by anything else in the pattern is bounded in the URL too.
+pub(crate) fn is_token_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'%'
+}
+
+/// What `^` matches: anything but a letter, digit or one of `_-.%`.
+fn is_separator(c: u8) -> bool {
+    !(c.is_ascii_alphanumeric() || matches!(c, b'_' | b'-' | b'.' | b'%'))
+}
+
+/// End of `part` matched at exactly `start`.
+fn match_part(part: &[u8], text: &[u8], start: usize) -> Option<usize> {
+    let mut position = start;
+    for (index, &c) in part.iter().enumerate() {
+        if c == b'^' {
+            if position == text.len() {
+                return part[index + 1..].iter().all(|&c| c == b'^').then_some(position);
+            }
+            if !is_separator(text[position]) {
+                return None;
+            }
+        } else if text.get(position) != Some(&c) {
+            return None;
+        }
+        position += 1;
+    }
+    Some(position)
+}
+
+/// Earliest match of `part` at or after `from`, as (start, end).
+fn find_part(part: &[u8], text: &[u8], from: usize) -> Option<(usize, usize)> {
+    (from..=text.len()).find_map(|start| match_part(part, text, start).map(|end| (start, end)))
+}
@@ -0,0 +1,173 @@
+use nonos_types::{NonosError, NonosResult};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// What a request loads, as named by filter type options.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestType {
+    Document,
+    Subdocument,
+    Script,
+    Stylesheet,
+    Image,
+    Font,
+    Media,
+    Object,
+    Xhr,
+    Websocket,
+    Ping,
+    Other,
+}
+
+impl RequestType {
+    pub(crate) fn bit(self) -> u16 {
+        1 << self as u16
+    }
+
+    /// The type named by a filter option, including uBO's short aliases.
+    pub(crate) fn from_option(name: &str) -> Option<Self> {
+        Some(match name {
+            "document" | "doc" => RequestType::Document,
+            "subdocument" | "frame" => RequestType::Subdocument,
+            "script" => RequestType::Script,
+            "stylesheet" | "css" => RequestType::Stylesheet,
+            "image" => RequestType::Image,
+            "font" => RequestType::Font,
+            "media" => RequestType::Media,
+            "object" | "object-subrequest" => RequestType::Object,
+            "xmlhttprequest" | "xhr" => RequestType::Xhr,
+            "websocket" => RequestType::Websocket,
+            "ping" | "beacon" => RequestType::Ping,
+            "other" => RequestType::Other,
+            _ => return None,
+        })
+    }
+}
+
+/// Second-level labels under which registrations happen one level deeper,
+/// e.g. `example.co.uk`. A short approximation of the public suffix list,
+/// enough to tell first from third parties on common sites.
+const MULTI_LABEL_SUFFIXES: &[&str] = &[
+    "ac", "co", "com", "edu", "gov", "net", "or", "org", "ne", "gob", "nic",
+];
+
+/// A request as the filter engine sees it.
+#[derive(Clone, Debug)]
+pub struct FilterRequest {
+    pub(crate) url: String,
+    /// `url` with ASCII lowercased, so byte offsets line up.
+    pub(crate) lowered: String,
+    pub(crate) host: Range<usize>,
+    pub(crate) source_host: Option<String>,
+    pub(crate) request_type: RequestType,
+    pub(crate) third_party: bool,
+}
+
+impl FilterRequest {
+    /// A request for `url` made by the page at `source_url`. Without a
+    /// source the request is treated as third-party.
+    pub fn new(url: &str, source_url: Option<&str>, request_type: RequestType) -> NonosResult<Self> {
+        let host = host_range(url)
+            .ok_or_else(|| NonosError::Config(format!("Not an absolute URL: {}", url)))?;
+        let lowered = url.to_ascii_lowercase();
+
+        let source_host = match source_url {
+            Some(source) => {
+                let range = host_range(source)
+                    .ok_or_else(|| NonosError::Config(format!("Not an absolute URL: {}", source)))?;
+                Some(source[range].to_ascii_lowercase())
+            }
+            None => None,
+        };
+
+        let third_party = source_host.as_deref()
+            .map_or(true, |source| registrable_domain(source) != registrable_domain(&lowered[host.clone()]));
+
+        Ok(Self {
+            url: url.to_string(),
+            lowered,
+            host,
+            source_host,
+            request_type,
+            third_party,
+        })
+    }
+
+    /// A bare request for the root of `domain`, for checks with no page
+    /// context.
+    pub fn for_domain(domain: &str) -> NonosResult<Self> {
+        let domain = domain.trim().trim_end_matches('.');
+        if domain.is_empty() || domain.contains(['/', '?', '#', ' ']) {
+            return Err(NonosError::Config(format!("Invalid domain: {}", domain)));
+        }
+        Self::new(&format!("https://{}/", domain), None, RequestType::Other)
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn hostname(&self) -> &str {
+        &self.lowered[self.host.clone()]
+    }
+
+    pub fn request_type(&self) -> RequestType {
+        self.request_type
+    }
+
+    pub fn is_third_party(&self) -> bool {
+        self.third_party
+    }
+}
+
+/// Byte range of the host in an absolute URL, without userinfo or port.
+fn host_range(url: &str) -> Option<Range<usize>> {
+    let start = url.find("://")? + 3;
+    let authority_end = url[start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |offset| start + offset);
+    let authority = &url[start..authority_end];
+
+    let host_start = authority.rfind('@').map_or(start, |at| start + at + 1);
+    let host = &url[host_start..authority_end];
+    let host_end = if host.starts_with('[') {
+        host.find(']').map(|close| host_start + close + 1)?
+    } else {
+        host.find(':').map_or(authority_end, |colon| host_start + colon)
+    };
+
+    (host_end > host_start).then_some(host_start..host_end)
+}
+
+/// The domain a site registers under, used to tell first from third
+/// parties.
+pub(crate) fn registrable_domain(host: &str) -> &str {
+    if host.starts_with('[') || host.parse::<std::net::Ipv4Addr>().is_ok() {
+        return host;
+    }
+
+    let labels: Vec<&str> = host.rsplitn(4, '.').collect();
+    let keep = match labels.as_slice() {
+        [tld, second, ..] if tld.len() == 2 && MULTI_LABEL_SUFFIXES.contains(second) => 3,
+        _ => 2,
+    };
+    if labels.len() <= keep {
+        return host;
+    }
+
+    let suffix_len: usize = labels[..keep].iter().map(|label| label.len() + 1).sum::<usize>() - 1;
+    &host[host.len() - suffix_len..]
+}
+
+/// Yields `host` and each parent domain, e.g. `a.b.c`, `b.c`, `c`.
+pub(crate) fn host_suffixes(host: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(host), |current| current.split_once('.').map(|(_, parent)| parent))
+}
+
+/// Whether `host` is `domain` or one of its subdomains.
+pub(crate) fn is_subdomain_of(host: &str, domain: &str) -> bool {
+    host.len() >= domain.len()
+        && host.ends_with(domain)
+        && (host.len() == domain.len() || host.as_bytes()[host.len() - domain.len() - 1] == b'.')
+}
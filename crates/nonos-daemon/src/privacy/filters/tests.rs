@@ -0,0 +1,141 @@
+use super::request::registrable_domain;
+use super::*;
+
+fn request(url: &str, source: Option<&str>, request_type: RequestType) -> FilterRequest {
+    FilterRequest::new(url, source, request_type).unwrap()
+}
+
+fn blocks(rules: &str, url: &str) -> bool {
+    FilterEngine::compile([rules])
+        .check(&request(url, None, RequestType::Other))
+        .is_blocked()
+}
+
+#[test]
+fn test_hostname_anchor() {
+    let rules = "||tracker.com^";
+    assert!(blocks(rules, "https://tracker.com/pixel.gif"));
+    assert!(blocks(rules, "https://cdn.tracker.com/a.js"));
+    assert!(blocks(rules, "http://tracker.com:8080/"));
+    assert!(!blocks(rules, "https://nottracker.com/"));
+    assert!(!blocks(rules, "https://tracker.com.example.org/"));
+    assert!(!blocks(rules, "https://example.com/?ref=tracker.com"));
+}
+
+#[test]
+fn test_wildcards_and_separators() {
+    assert!(blocks("/ads/*/banner^", "https://site.org/ads/top/banner?x=1"));
+    assert!(blocks("/ads/*/banner^", "https://site.org/ads/top/banner"));
+    assert!(!blocks("/ads/*/banner^", "https://site.org/ads/top/banners"));
+    assert!(blocks("||cdn.example.com/*/track.js", "https://cdn.example.com/v2/track.js"));
+    assert!(blocks("-pixel.", "https://shop.example.com/img/fb-pixel.png"));
+}
+
+#[test]
+fn test_start_and_end_anchors() {
+    assert!(blocks("|https://ads.", "https://ads.example.com/"));
+    assert!(!blocks("|https://ads.", "https://example.com/?u=https://ads.x"));
+    assert!(blocks(".gif|", "https://example.com/beacon.gif"));
+    assert!(!blocks(".gif|", "https://example.com/beacon.gif?x=1"));
+}
+
+#[test]
+fn test_match_case() {
+    assert!(blocks("/Track^", "https://example.com/track/1"));
+    assert!(!blocks("/Track^$match-case", "https://example.com/track/1"));
+    assert!(blocks("/Track^$match-case", "https://example.com/Track/1"));
+}
+
+#[test]
+fn test_third_party_option() {
+    let engine = FilterEngine::compile(["||social.com^$third-party"]);
+    let embedded = request("https://social.com/widget.js", Some("https://news.org/"), RequestType::Script);
+    let own_site = request("https://static.social.com/app.js", Some("https://www.social.com/"), RequestType::Script);
+    assert!(embedded.is_third_party());
+    assert!(!own_site.is_third_party());
+    assert!(engine.check(&embedded).is_blocked());
+    assert!(!engine.check(&own_site).is_blocked());
+}
+
+#[test]
+fn test_type_options() {
+    let engine = FilterEngine::compile(["||ads.net^$script,image", "||beacon.io^$~image"]);
+    let source = Some("https://site.com/");
+    assert!(engine.check(&request("https://ads.net/a.js", source, RequestType::Script)).is_blocked());
+    assert!(!engine.check(&request("https://ads.net/a.css", source, RequestType::Stylesheet)).is_blocked());
+    assert!(engine.check(&request("https://beacon.io/p", source, RequestType::Xhr)).is_blocked());
+    assert!(!engine.check(&request("https://beacon.io/p.png", source, RequestType::Image)).is_blocked());
+}
+
+#[test]
+fn test_domain_option() {
+    let engine = FilterEngine::compile(["/widget.js$domain=news.org|~sports.news.org"]);
+    let check = |source: &str| {
+        engine.check(&request("https://cdn.net/widget.js", Some(source), RequestType::Script)).is_blocked()
+    };
+    assert!(check("https://news.org/"));
+    assert!(check("https://world.news.org/story"));
+    assert!(!check("https://sports.news.org/"));
+    assert!(!check("https://blog.com/"));
+    assert!(!engine.check(&request("https://cdn.net/widget.js", None, RequestType::Script)).is_blocked());
+}
+
+#[test]
+fn test_exceptions_and_important() {
+    let engine = FilterEngine::compile(["||ads.com^\n@@||ads.com/allowed/"]);
+    assert!(engine.check(&request("https://ads.com/x", None, RequestType::Other)).is_blocked());
+    let decision = engine.check(&request("https://ads.com/allowed/x", None, RequestType::Other));
+    assert_eq!(decision, FilterDecision::Exception { rule: "@@||ads.com/allowed/".to_string() });
+
+    let engine = FilterEngine::compile(["||ads.com^$important\n@@||ads.com/allowed/"]);
+    assert!(engine.check(&request("https://ads.com/allowed/x", None, RequestType::Other)).is_blocked());
+}
+
+#[test]
+fn test_removeparam() {
+    let engine = FilterEngine::compile([
+        "$removeparam=utm_source\n$removeparam=fbclid\n@@||keep.org^$removeparam=utm_source\n||strict.com^$removeparam",
+    ]);
+    let strip = |url: &str| engine.strip_params(&request(url, None, RequestType::Document));
+
+    assert_eq!(
+        strip("https://a.com/p?id=1&utm_source=x&fbclid=y#top").as_deref(),
+        Some("https://a.com/p?id=1#top"),
+    );
+    assert_eq!(strip("https://a.com/p?utm_source=x").as_deref(), Some("https://a.com/p"));
+    assert_eq!(strip("https://a.com/p?id=1"), None);
+    assert_eq!(
+        strip("https://keep.org/?utm_source=x&fbclid=y").as_deref(),
+        Some("https://keep.org/?utm_source=x"),
+    );
+    assert_eq!(strip("https://strict.com/?a=1&b=2").as_deref(), Some("https://strict.com/"));
+}
+
+#[test]
+fn test_skipped_rules_are_counted() {
+    let stats = FilterEngine::compile([
+        "! comment\n[Adblock Plus 2.0]\nexample.com##.ad\n#@#.banner\n/ad[0-9]+/\n||x.com^$csp=script-src\n||y.com^\n@@||y.com/ok",
+    ]).stats();
+    assert_eq!(stats.blocking, 1);
+    assert_eq!(stats.exceptions, 1);
+    assert_eq!(stats.cosmetic_skipped, 2);
+    assert_eq!(stats.unsupported_skipped, 2);
+}
+
+#[test]
+fn test_builtin_rules() {
+    let engine = FilterEngine::compile([BUILTIN_RULES]);
+    assert!(engine.stats().blocking > 0);
+    assert!(engine.stats().removeparam > 0);
+    assert!(engine.check(&FilterRequest::for_domain("www.google-analytics.com").unwrap()).is_blocked());
+    assert!(!engine.check(&FilterRequest::for_domain("example.com").unwrap()).is_blocked());
+}
+
+#[test]
+fn test_registrable_domain() {
+    assert_eq!(registrable_domain("www.example.com"), "example.com");
+    assert_eq!(registrable_domain("a.b.example.co.uk"), "example.co.uk");
+    assert_eq!(registrable_domain("example.com"), "example.com");
+    assert_eq!(registrable_domain("localhost"), "localhost");
+    assert_eq!(registrable_domain("10.0.0.1"), "10.0.0.1");
+}
@@ -4,9 +4,13 @@ use subtle::ConstantTimeEq;
 
 const MAX_NULLIFIERS: usize = 1_000_000;
 
+/// In-memory nullifier set. Only sets created with `evicting` drop their
+/// oldest entries past `MAX_NULLIFIERS`, and those must be backed by a
+/// persistent record that callers check on a miss.
 pub struct BoundedNullifierSet {
     set: HashSet<ScopedNullifier>,
     order: VecDeque<ScopedNullifier>,
+    evict: bool,
     evicted: u64,
 }
 
@@ -15,10 +19,18 @@ impl BoundedNullifierSet {
         Self {
             set: HashSet::with_capacity(1024),
             order: VecDeque::with_capacity(1024),
+            evict: false,
             evicted: 0,
         }
     }
 
+    pub fn evicting() -> Self {
+        Self {
+            evict: true,
+            ..Self::new()
+        }
+    }
+
     pub fn contains(&self, nullifier: &[u8; 32], scope: &[u8; 32]) -> bool {
         let key = ScopedNullifier {
             nullifier: *nullifier,
@@ -45,7 +57,7 @@ impl BoundedNullifierSet {
             return false;
         }
 
-        while self.set.len() >= MAX_NULLIFIERS {
+        while self.evict && self.set.len() >= MAX_NULLIFIERS {
             if let Some(old) = self.order.pop_front() {
                 self.set.remove(&old);
                 self.evicted += 1;
@@ -1,5 +1,6 @@
 use super::nullifier::BoundedNullifierSet;
 use super::types::{IdentityCommitment, ScopedNullifier, VerificationResult};
+use crate::storage::{NodeStorage, PrivacyTree};
 use ark_bn254::{Bn254, Fr};
 use ark_groth16::{Groth16, PreparedVerifyingKey, Proof};
 use ark_serialize::CanonicalDeserialize;
@@ -28,6 +29,7 @@ pub struct ZkIdentityRegistry {
     registrations: AtomicU64,
     verifications_passed: AtomicU64,
     verifications_failed: AtomicU64,
+    storage: Option<Arc<NodeStorage>>,
 }
 
 impl ZkIdentityRegistry {
@@ -48,9 +50,71 @@ impl ZkIdentityRegistry {
             registrations: AtomicU64::new(0),
             verifications_passed: AtomicU64::new(0),
             verifications_failed: AtomicU64::new(0),
+            storage: None,
         }
     }
 
+    /// Rebuilds the registry from `storage` and persists every subsequent
+    /// registration and recorded nullifier to it.
+    pub fn with_storage(storage: Arc<NodeStorage>) -> NonosResult<Self> {
+        let state = storage.load_privacy_state(PrivacyTree::IdentityRegistry, MAX_ACCEPTED_ROOTS)?;
+
+        let mut tree = PoseidonMerkleTree::new(20);
+        let mut identities = HashMap::with_capacity(state.leaves.len());
+        for leaf in &state.leaves {
+            let identity: IdentityCommitment = bincode::deserialize(leaf)
+                .map_err(|e| NonosError::Storage(format!("Invalid stored identity: {}", e)))?;
+            let index = tree.insert(identity.commitment);
+            if index != identity.index {
+                return Err(NonosError::Storage(format!(
+                    "Stored identity index {} does not match tree position {}",
+                    identity.index, index
+                )));
+            }
+            identities.insert(identity.commitment, identity);
+        }
+
+        let root = tree.root();
+        state.check_root(PrivacyTree::IdentityRegistry, &root)?;
+
+        let mut accepted: VecDeque<[u8; 32]> = state.accepted_roots.iter().copied().collect();
+        if !accepted.contains(&root) {
+            accepted.push_back(root);
+        }
+
+        let mut nullifiers = BoundedNullifierSet::evicting();
+        for bytes in &state.nullifiers {
+            if bytes.len() != 64 {
+                return Err(NonosError::Storage("Invalid stored scoped nullifier".into()));
+            }
+            let mut nullifier = [0u8; 32];
+            let mut scope = [0u8; 32];
+            nullifier.copy_from_slice(&bytes[..32]);
+            scope.copy_from_slice(&bytes[32..]);
+            nullifiers.insert(nullifier, scope);
+        }
+
+        info!(
+            "ZK Identity Registry restored: {} identities, {} nullifiers",
+            identities.len(),
+            nullifiers.len()
+        );
+
+        Ok(Self {
+            registrations: AtomicU64::new(identities.len() as u64),
+            tree: Arc::new(RwLock::new(tree)),
+            accepted_roots: Arc::new(RwLock::new(accepted)),
+            current_root: Arc::new(RwLock::new(root)),
+            nullifiers: Arc::new(RwLock::new(nullifiers)),
+            identities: Arc::new(RwLock::new(identities)),
+            verifying_key: Arc::new(RwLock::new(None)),
+            production_mode: AtomicBool::new(false),
+            verifications_passed: AtomicU64::new(0),
+            verifications_failed: AtomicU64::new(0),
+            storage: Some(storage),
+        })
+    }
+
     pub fn set_production_mode(&self, enabled: bool) {
         self.production_mode.store(enabled, Ordering::SeqCst);
         if enabled {
@@ -92,14 +156,34 @@ impl ZkIdentityRegistry {
             }
         }
 
-        let index = {
+        let registered_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let (identity, new_root) = {
             let mut tree = self.tree.write().await;
-            tree.insert(commitment)
-        };
+            let index = tree.insert(commitment);
+            let root = tree.root();
+            let identity = IdentityCommitment {
+                commitment,
+                index,
+                registered_at,
+            };
+
+            if let Some(storage) = &self.storage {
+                let written = bincode::serialize(&identity)
+                    .map_err(|e| NonosError::Serialization(e.to_string()))
+                    .and_then(|leaf| {
+                        storage.append_privacy_leaf(PrivacyTree::IdentityRegistry, index as u64, &leaf, &root)
+                    });
+                if let Err(e) = written {
+                    tree.truncate(index);
+                    return Err(e);
+                }
+            }
 
-        let new_root = {
-            let tree = self.tree.read().await;
-            tree.root()
+            (identity, root)
         };
 
         {
@@ -115,15 +199,6 @@ impl ZkIdentityRegistry {
             *current = new_root;
         }
 
-        let identity = IdentityCommitment {
-            commitment,
-            index,
-            registered_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
-        };
-
         {
             let mut identities = self.identities.write().await;
             identities.insert(commitment, identity.clone());
@@ -163,7 +238,25 @@ impl ZkIdentityRegistry {
 
     pub async fn is_nullifier_used(&self, nullifier: &[u8; 32], scope: &[u8; 32]) -> bool {
         let nullifiers = self.nullifiers.read().await;
-        nullifiers.contains(nullifier, scope)
+        if nullifiers.contains(nullifier, scope) {
+            return true;
+        }
+        match self.stored_nullifier_used(nullifier, scope) {
+            Ok(used) => used,
+            Err(e) => {
+                error!("Treating nullifier as used: {}", e);
+                true
+            }
+        }
+    }
+
+    /// Checks the persisted nullifiers, which still hold entries evicted
+    /// from memory.
+    fn stored_nullifier_used(&self, nullifier: &[u8; 32], scope: &[u8; 32]) -> NonosResult<bool> {
+        match &self.storage {
+            Some(storage) => storage.has_privacy_nullifier(PrivacyTree::IdentityRegistry, &scoped_bytes(nullifier, scope)),
+            None => Ok(false),
+        }
     }
 
     pub async fn verify_proof(
@@ -208,7 +301,7 @@ impl ZkIdentityRegistry {
                             nullifier_recorded: false,
                         });
                     }
-                    self.record_nullifier(nullifier, scope).await;
+                    self.record_nullifier(nullifier, scope).await?;
                     self.verifications_passed.fetch_add(1, Ordering::Relaxed);
                     return Ok(VerificationResult {
                         valid: true,
@@ -235,7 +328,7 @@ impl ZkIdentityRegistry {
             .map_err(|e| NonosError::Internal(format!("Verification error: {}", e)))?;
 
         if valid {
-            self.record_nullifier(nullifier, scope).await;
+            self.record_nullifier(nullifier, scope).await?;
             self.verifications_passed.fetch_add(1, Ordering::Relaxed);
             Ok(VerificationResult {
                 valid: true,
@@ -252,9 +345,18 @@ impl ZkIdentityRegistry {
         }
     }
 
-    async fn record_nullifier(&self, nullifier: &[u8; 32], scope: &[u8; 32]) {
+    async fn record_nullifier(&self, nullifier: &[u8; 32], scope: &[u8; 32]) -> NonosResult<()> {
         let mut nullifiers = self.nullifiers.write().await;
+        if nullifiers.contains(nullifier, scope) || self.stored_nullifier_used(nullifier, scope)? {
+            return Ok(());
+        }
+
+        if let Some(storage) = &self.storage {
+            storage.append_privacy_nullifier(PrivacyTree::IdentityRegistry, &scoped_bytes(nullifier, scope))?;
+        }
+
         nullifiers.insert(*nullifier, *scope);
+        Ok(())
     }
 
     pub fn stats(&self) -> (u64, u64, u64) {
@@ -274,6 +376,13 @@ impl ZkIdentityRegistry {
     }
 }
 
+fn scoped_bytes(nullifier: &[u8; 32], scope: &[u8; 32]) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(nullifier);
+    bytes[32..].copy_from_slice(scope);
+    bytes
+}
+
 impl Default for ZkIdentityRegistry {
     fn default() -> Self {
         Self::new()
@@ -313,7 +422,7 @@ mod tests {
         let scope1 = [0x01; 32];
         let scope2 = [0x02; 32];
 
-        registry.record_nullifier(&nullifier, &scope1).await;
+        registry.record_nullifier(&nullifier, &scope1).await.unwrap();
         assert!(registry.is_nullifier_used(&nullifier, &scope1).await);
         assert!(!registry.is_nullifier_used(&nullifier, &scope2).await);
     }
@@ -346,6 +455,26 @@ mod tests {
         assert!(!r2.valid);
     }
 
+    #[tokio::test]
+    async fn test_state_survives_restart() {
+        let storage = Arc::new(crate::storage::NodeStorage::in_memory().unwrap());
+        let registry = ZkIdentityRegistry::with_storage(storage.clone()).unwrap();
+
+        let identity = registry.register_identity(&[0x11; 32], &[0x22; 32]).await.unwrap();
+        let root = registry.current_root().await;
+        let nullifier = [0xaa; 32];
+        let scope = [0x01; 32];
+        assert!(registry.verify_proof(&[], &root, &nullifier, &scope, None).await.unwrap().valid);
+        drop(registry);
+
+        let restored = ZkIdentityRegistry::with_storage(storage).unwrap();
+        assert_eq!(restored.current_root().await, root);
+        assert_eq!(restored.identity_count().await, 1);
+        assert!(restored.is_nullifier_used(&nullifier, &scope).await);
+        assert!(restored.get_proof(&identity.commitment).await.is_ok());
+        assert!(restored.register_identity(&[0x11; 32], &[0x22; 32]).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_merkle_proof_generation() {
         let registry = ZkIdentityRegistry::new();
@@ -1,37 +1,105 @@
 use super::{
     ZkIdentityService, CacheMixingService, TrackingBlockerService, StealthScannerService,
-    ZkIdentityRegistry, NoteMixer,
+    ZkIdentityRegistry, NoteMixer, FilterListSubscriptions, PrivacyOracle, DistributedCookieVault,
+    ZkSessionManager, ZkCredentialSystem, PrivateContentRetrieval, CredentialManager, DEFAULT_PIR_PARAMS,
 };
+use crate::config::StealthConfig;
+use crate::storage::NodeStorage;
+use nonos_crypto::random_bytes;
 use nonos_types::{NodeId, NonosResult};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tracing::{error, info};
 
+/// Shares needed to open a vaulted cookie, out of [`COOKIE_VAULT_SHARES`].
+const COOKIE_VAULT_THRESHOLD: u8 = 3;
+const COOKIE_VAULT_SHARES: u8 = 5;
+
 pub struct PrivacyServiceManager {
     pub zk_identity: Arc<ZkIdentityService>,
     pub cache_mixing: Arc<CacheMixingService>,
     pub tracking_blocker: Arc<TrackingBlockerService>,
+    pub filter_lists: Arc<FilterListSubscriptions>,
+    pub privacy_oracle: Arc<PrivacyOracle>,
     pub stealth_scanner: Arc<StealthScannerService>,
     pub identity_registry: Arc<ZkIdentityRegistry>,
     pub note_mixer: Arc<NoteMixer>,
+    pub cookie_vault: Arc<DistributedCookieVault>,
+    pub zk_sessions: Arc<ZkSessionManager>,
+    pub zk_credentials: Arc<ZkCredentialSystem>,
+    /// Numeric credentials this node holds and proves with the range
+    /// circuit, and checks proofs of for others.
+    pub credentials: Arc<CredentialManager>,
+    /// Content this node serves to PIR clients.
+    pub content: Arc<PrivateContentRetrieval>,
     shutdown: Arc<AtomicBool>,
 }
 
 impl PrivacyServiceManager {
     pub fn new(node_id: NodeId) -> Self {
+        let tracking_blocker = Arc::new(TrackingBlockerService::new(node_id));
         Self {
             zk_identity: Arc::new(ZkIdentityService::new(node_id)),
             cache_mixing: Arc::new(CacheMixingService::new(node_id, 10000)),
-            tracking_blocker: Arc::new(TrackingBlockerService::new(node_id)),
+            filter_lists: Arc::new(FilterListSubscriptions::new(tracking_blocker.clone())),
+            tracking_blocker,
+            privacy_oracle: Arc::new(PrivacyOracle::new()),
             stealth_scanner: Arc::new(StealthScannerService::new(node_id)),
             identity_registry: Arc::new(ZkIdentityRegistry::new()),
             note_mixer: Arc::new(NoteMixer::new()),
+            cookie_vault: Arc::new(
+                DistributedCookieVault::new(COOKIE_VAULT_THRESHOLD, COOKIE_VAULT_SHARES)
+                    .expect("Valid cookie vault threshold"),
+            ),
+            zk_sessions: Arc::new(ZkSessionManager::new()),
+            zk_credentials: Arc::new(ZkCredentialSystem::new(random_bytes::<32>())),
+            credentials: Arc::new(CredentialManager::new()),
+            content: Arc::new(PrivateContentRetrieval::new(
+                DEFAULT_PIR_PARAMS.buckets as usize * DEFAULT_PIR_PARAMS.slots_per_bucket as usize,
+            )),
             shutdown: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Like [`PrivacyServiceManager::new`], but the identity registry, note
+    /// mixer, ZK sessions and credentials, stealth scanner, filter-list
+    /// subscriptions and cookie vault are restored from and persisted to
+    /// `storage`. The stealth scanner only polls the chain when `stealth` is
+    /// enabled.
+    pub fn with_storage(node_id: NodeId, storage: Arc<NodeStorage>, stealth: &StealthConfig) -> NonosResult<Self> {
+        let stealth_scanner = if stealth.enabled {
+            StealthScannerService::from_config(node_id, stealth.clone(), storage.clone())?
+        } else {
+            StealthScannerService::new(node_id)
+        };
+
+        let base = Self::new(node_id);
+        Ok(Self {
+            identity_registry: Arc::new(ZkIdentityRegistry::with_storage(storage.clone())?),
+            filter_lists: Arc::new(FilterListSubscriptions::with_storage(base.tracking_blocker.clone(), storage.clone())),
+            note_mixer: Arc::new(NoteMixer::with_storage(storage.clone())?),
+            zk_sessions: Arc::new(ZkSessionManager::with_storage(storage.clone())?),
+            zk_credentials: Arc::new(ZkCredentialSystem::with_storage(storage.clone())?),
+            cookie_vault: Arc::new(DistributedCookieVault::with_storage(
+                COOKIE_VAULT_THRESHOLD,
+                COOKIE_VAULT_SHARES,
+                storage,
+            )?),
+            stealth_scanner: Arc::new(stealth_scanner),
+            ..base
+        })
+    }
+
     pub async fn start_all(&self) -> NonosResult<()> {
         info!("Starting NONOS privacy services");
+        let restored = self.filter_lists.restore().await?;
+        if restored > 0 {
+            info!("Restored {} filter list subscriptions", restored);
+        }
+        let vaulted = self.cookie_vault.restore().await?;
+        if vaulted > 0 {
+            info!("Restored {} vaulted cookies", vaulted);
+        }
         let shutdown = self.shutdown.clone();
 
         let zk = self.zk_identity.clone();
@@ -1,6 +1,5 @@
 use super::types::AssetId;
-use ark_bn254::Fr;
-use nonos_crypto::poseidon_canonical::{bytes_to_fr, fr_to_bytes, poseidon_hash_fields};
+use nonos_crypto::spend_proofs::{note_commitment, note_nullifier};
 use serde::{Deserialize, Serialize};
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
@@ -43,15 +42,7 @@ impl Note {
         asset: &AssetId,
         randomness: &[u8; 32],
     ) -> [u8; 32] {
-        let secret_fr = bytes_to_fr(secret);
-        let amount_fr = Fr::from(amount);
-        let mut asset_bytes = [0u8; 32];
-        asset_bytes[..8].copy_from_slice(asset);
-        let asset_fr = bytes_to_fr(&asset_bytes);
-        let randomness_fr = bytes_to_fr(randomness);
-
-        let result = poseidon_hash_fields(&[secret_fr, amount_fr, asset_fr, randomness_fr]);
-        fr_to_bytes(&result)
+        note_commitment(secret, amount, asset, randomness)
     }
 
     pub fn nullifier(&self) -> [u8; 32] {
@@ -59,8 +50,7 @@ impl Note {
     }
 
     pub fn compute_nullifier(secret: &[u8; 32], commitment: &[u8; 32]) -> [u8; 32] {
-        let result = poseidon_hash_fields(&[bytes_to_fr(secret), bytes_to_fr(commitment)]);
-        fr_to_bytes(&result)
+        note_nullifier(secret, commitment)
     }
 
     pub fn commitment(&self) -> [u8; 32] {
@@ -1,6 +1,12 @@
 use super::note::Note;
 use super::types::{AssetId, SpendRequest, SpendResult};
-use nonos_crypto::poseidon_canonical::{bytes_to_fr, fr_to_bytes, poseidon_hash_fields, PoseidonMerkleTree};
+use crate::storage::{NodeStorage, PrivacyTree};
+use ark_bn254::Bn254;
+use ark_groth16::PreparedVerifyingKey;
+use nonos_crypto::poseidon_canonical::{
+    bytes_to_fr, fr_to_bytes, is_canonical_fr, poseidon_hash_fields, PoseidonMerkleTree,
+};
+use nonos_crypto::spend_proofs::{load_spend_verifying_key, verify_spend, SpendPublicInputs, SPEND_MERKLE_DEPTH};
 use nonos_types::{NonosError, NonosResult};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
@@ -11,6 +17,7 @@ use tracing::{error, info, warn};
 const MAX_NULLIFIERS: usize = 1_000_000;
 const MAX_NOTES: usize = 1_048_576;
 const MAX_ACCEPTED_ROOTS: usize = 256;
+const STORED_LEAF_LEN: usize = 32 + 8 + 16;
 
 pub struct NoteMixer {
     tree: Arc<RwLock<PoseidonMerkleTree>>,
@@ -18,16 +25,18 @@ pub struct NoteMixer {
     nullifier_order: Arc<RwLock<VecDeque<[u8; 32]>>>,
     commitment_index: Arc<RwLock<HashMap<[u8; 32], usize>>>,
     accepted_roots: Arc<RwLock<VecDeque<[u8; 32]>>>,
+    verifying_key: Arc<RwLock<Option<PreparedVerifyingKey<Bn254>>>>,
     production_mode: AtomicBool,
     deposits: AtomicU64,
     spends: AtomicU64,
     failed_spends: AtomicU64,
     tvl: Arc<RwLock<HashMap<AssetId, u128>>>,
+    storage: Option<Arc<NodeStorage>>,
 }
 
 impl NoteMixer {
     pub fn new() -> Self {
-        let tree = PoseidonMerkleTree::new(20);
+        let tree = PoseidonMerkleTree::new(SPEND_MERKLE_DEPTH);
         let initial_root = tree.root();
         let mut accepted = VecDeque::new();
         accepted.push_back(initial_root);
@@ -38,14 +47,87 @@ impl NoteMixer {
             nullifier_order: Arc::new(RwLock::new(VecDeque::with_capacity(1024))),
             commitment_index: Arc::new(RwLock::new(HashMap::new())),
             accepted_roots: Arc::new(RwLock::new(accepted)),
+            verifying_key: Arc::new(RwLock::new(None)),
             production_mode: AtomicBool::new(false),
             deposits: AtomicU64::new(0),
             spends: AtomicU64::new(0),
             failed_spends: AtomicU64::new(0),
             tvl: Arc::new(RwLock::new(HashMap::new())),
+            storage: None,
         }
     }
 
+    /// Rebuilds the pool from `storage` and persists every subsequent
+    /// deposit and spend to it.
+    pub fn with_storage(storage: Arc<NodeStorage>) -> NonosResult<Self> {
+        let state = storage.load_privacy_state(PrivacyTree::NoteMixer, MAX_ACCEPTED_ROOTS)?;
+
+        let mut tree = PoseidonMerkleTree::new(SPEND_MERKLE_DEPTH);
+        let mut commitment_index = HashMap::with_capacity(state.leaves.len());
+        let mut tvl: HashMap<AssetId, u128> = HashMap::new();
+
+        for leaf in &state.leaves {
+            if leaf.len() != STORED_LEAF_LEN {
+                return Err(NonosError::Storage(format!("Invalid stored note length: {}", leaf.len())));
+            }
+            let mut commitment = [0u8; 32];
+            commitment.copy_from_slice(&leaf[..32]);
+            let mut asset = [0u8; 8];
+            asset.copy_from_slice(&leaf[32..40]);
+            let mut amount = [0u8; 16];
+            amount.copy_from_slice(&leaf[40..]);
+
+            let index = tree.insert(commitment);
+            commitment_index.insert(commitment, index);
+            let total = tvl.entry(asset).or_insert(0);
+            *total = total.saturating_add(u128::from_be_bytes(amount));
+        }
+
+        let root = tree.root();
+        state.check_root(PrivacyTree::NoteMixer, &root)?;
+
+        let mut accepted: VecDeque<[u8; 32]> = state.accepted_roots.iter().copied().collect();
+        if !accepted.contains(&root) {
+            accepted.push_back(root);
+        }
+
+        let mut nullifiers = HashSet::with_capacity(state.nullifiers.len().max(1024));
+        let mut order = VecDeque::with_capacity(state.nullifiers.len().max(1024));
+        for bytes in &state.nullifiers {
+            let nullifier: [u8; 32] = bytes.as_slice().try_into()
+                .map_err(|_| NonosError::Storage("Invalid stored nullifier".into()))?;
+            if nullifiers.insert(nullifier) {
+                order.push_back(nullifier);
+            }
+        }
+        while order.len() > MAX_NULLIFIERS {
+            if let Some(old) = order.pop_front() {
+                nullifiers.remove(&old);
+            }
+        }
+
+        info!(
+            "Note Mixer restored: {} notes, {} spent nullifiers",
+            commitment_index.len(),
+            nullifiers.len()
+        );
+
+        Ok(Self {
+            deposits: AtomicU64::new(commitment_index.len() as u64),
+            spends: AtomicU64::new(nullifiers.len() as u64),
+            tree: Arc::new(RwLock::new(tree)),
+            nullifiers: Arc::new(RwLock::new(nullifiers)),
+            nullifier_order: Arc::new(RwLock::new(order)),
+            commitment_index: Arc::new(RwLock::new(commitment_index)),
+            accepted_roots: Arc::new(RwLock::new(accepted)),
+            verifying_key: Arc::new(RwLock::new(None)),
+            production_mode: AtomicBool::new(false),
+            failed_spends: AtomicU64::new(0),
+            tvl: Arc::new(RwLock::new(tvl)),
+            storage: Some(storage),
+        })
+    }
+
     pub fn set_production_mode(&self, enabled: bool) {
         self.production_mode.store(enabled, Ordering::SeqCst);
         if enabled {
@@ -53,6 +135,17 @@ impl NoteMixer {
         }
     }
 
+    pub async fn load_verifying_key(&self, vk_bytes: &[u8]) -> NonosResult<()> {
+        let pvk = load_spend_verifying_key(vk_bytes)?;
+        *self.verifying_key.write().await = Some(pvk);
+        info!("Loaded note mixer spend verifying key");
+        Ok(())
+    }
+
+    pub async fn has_verifying_key(&self) -> bool {
+        self.verifying_key.read().await.is_some()
+    }
+
     pub async fn deposit(&self, note: &mut Note) -> NonosResult<usize> {
         {
             let index = self.commitment_index.read().await;
@@ -64,14 +157,23 @@ impl NoteMixer {
             }
         }
 
-        let tree_index = {
+        let (tree_index, new_root) = {
             let mut tree = self.tree.write().await;
-            tree.insert(note.commitment())
-        };
+            let index = tree.insert(note.commitment());
+            let root = tree.root();
+
+            if let Some(storage) = &self.storage {
+                let mut leaf = Vec::with_capacity(STORED_LEAF_LEN);
+                leaf.extend_from_slice(&note.commitment());
+                leaf.extend_from_slice(&note.public.asset);
+                leaf.extend_from_slice(&note.public.amount.to_be_bytes());
+                if let Err(e) = storage.append_privacy_leaf(PrivacyTree::NoteMixer, index as u64, &leaf, &root) {
+                    tree.truncate(index);
+                    return Err(e);
+                }
+            }
 
-        let new_root = {
-            let tree = self.tree.read().await;
-            tree.root()
+            (index, root)
         };
 
         {
@@ -121,10 +223,43 @@ impl NoteMixer {
     }
 
     pub async fn is_spent(&self, nullifier: &[u8; 32]) -> bool {
-        self.nullifiers.read().await.contains(nullifier)
+        if self.nullifiers.read().await.contains(nullifier) {
+            return true;
+        }
+        match self.stored_spent(nullifier) {
+            Ok(spent) => spent,
+            Err(e) => {
+                error!("Treating nullifier as spent: {}", e);
+                true
+            }
+        }
+    }
+
+    /// Checks the persisted nullifiers, which still hold entries evicted
+    /// from memory.
+    fn stored_spent(&self, nullifier: &[u8; 32]) -> NonosResult<bool> {
+        match &self.storage {
+            Some(storage) => storage.has_privacy_nullifier(PrivacyTree::NoteMixer, nullifier),
+            None => Ok(false),
+        }
     }
 
     pub async fn spend(&self, request: &SpendRequest) -> NonosResult<SpendResult> {
+        // The spent set holds raw bytes, so each field element must have
+        // exactly one accepted encoding.
+        let canonical = [&request.merkle_root, &request.nullifier, &request.recipient]
+            .into_iter()
+            .all(is_canonical_fr);
+        if !canonical {
+            self.failed_spends.fetch_add(1, Ordering::Relaxed);
+            warn!("Spend rejected: non-canonical field encoding");
+            return Ok(SpendResult {
+                success: false,
+                reason: Some("Non-canonical field encoding".into()),
+                tx_hash: None,
+            });
+        }
+
         if self.is_spent(&request.nullifier).await {
             self.failed_spends.fetch_add(1, Ordering::Relaxed);
             warn!("Spend rejected: nullifier already spent");
@@ -145,23 +280,74 @@ impl NoteMixer {
             });
         }
 
-        if self.production_mode.load(Ordering::SeqCst) && request.proof.is_empty() {
+        if request.fee > request.amount {
             self.failed_spends.fetch_add(1, Ordering::Relaxed);
-            error!("Production mode: proof required");
+            warn!("Spend rejected: fee exceeds note amount");
             return Ok(SpendResult {
                 success: false,
-                reason: Some("Proof required in production".into()),
+                reason: Some("Fee exceeds note amount".into()),
                 tx_hash: None,
             });
         }
 
+        let vk = self.verifying_key.read().await.clone();
+        match vk {
+            Some(vk) => {
+                let public = SpendPublicInputs {
+                    merkle_root: request.merkle_root,
+                    nullifier: request.nullifier,
+                    recipient: request.recipient,
+                    amount: request.amount,
+                    asset: request.asset,
+                    fee: request.fee,
+                };
+
+                if !verify_spend(&vk, &public, &request.proof)? {
+                    self.failed_spends.fetch_add(1, Ordering::Relaxed);
+                    warn!("Spend rejected: proof verification failed");
+                    return Ok(SpendResult {
+                        success: false,
+                        reason: Some("Invalid spend proof".into()),
+                        tx_hash: None,
+                    });
+                }
+            }
+            None if self.production_mode.load(Ordering::SeqCst) => {
+                self.failed_spends.fetch_add(1, Ordering::Relaxed);
+                error!("Production mode: spend verifying key not loaded, rejecting spend");
+                return Ok(SpendResult {
+                    success: false,
+                    reason: Some("Verifying key not loaded".into()),
+                    tx_hash: None,
+                });
+            }
+            None => {
+                warn!("Spend accepted without proof verification (dev mode, no verifying key)");
+            }
+        }
+
         {
             let mut nullifiers = self.nullifiers.write().await;
             let mut order = self.nullifier_order.write().await;
 
-            while nullifiers.len() >= MAX_NULLIFIERS {
-                if let Some(old) = order.pop_front() {
-                    nullifiers.remove(&old);
+            if nullifiers.contains(&request.nullifier) || self.stored_spent(&request.nullifier)? {
+                self.failed_spends.fetch_add(1, Ordering::Relaxed);
+                return Ok(SpendResult {
+                    success: false,
+                    reason: Some("Double-spend attempt".into()),
+                    tx_hash: None,
+                });
+            }
+
+            // Without storage the in-memory set is the only record of a
+            // spend, so it is only trimmed when persisted nullifiers back it.
+            if let Some(storage) = &self.storage {
+                storage.append_privacy_nullifier(PrivacyTree::NoteMixer, &request.nullifier)?;
+
+                while nullifiers.len() >= MAX_NULLIFIERS {
+                    if let Some(old) = order.pop_front() {
+                        nullifiers.remove(&old);
+                    }
                 }
             }
 
@@ -218,6 +404,7 @@ mod tests {
     use super::*;
     use super::super::note::Note;
     use super::super::types::ASSET_NOX;
+    use nonos_crypto::spend_proofs::{prove_spend, SpendKeys, SpendProofInput};
     use rand::RngCore;
 
     fn random_bytes<const N: usize>() -> [u8; N] {
@@ -257,7 +444,9 @@ mod tests {
         let request = SpendRequest {
             merkle_root: root,
             nullifier: note.nullifier(),
-            recipient: random_bytes(),
+            recipient: [0x12; 32],
+            amount: 1000,
+            asset: ASSET_NOX,
             fee: 10,
             merkle_path: proof,
             proof: vec![],
@@ -280,7 +469,9 @@ mod tests {
         let request = SpendRequest {
             merkle_root: root,
             nullifier: note.nullifier(),
-            recipient: random_bytes(),
+            recipient: [0x12; 32],
+            amount: 1000,
+            asset: ASSET_NOX,
             fee: 10,
             merkle_path: proof,
             proof: vec![],
@@ -299,7 +490,9 @@ mod tests {
         let request = SpendRequest {
             merkle_root: [0xff; 32],
             nullifier: note.nullifier(),
-            recipient: random_bytes(),
+            recipient: [0x12; 32],
+            amount: 1000,
+            asset: ASSET_NOX,
             fee: 10,
             merkle_path: vec![],
             proof: vec![],
@@ -307,4 +500,173 @@ mod tests {
 
         assert!(!mixer.spend(&request).await.unwrap().success);
     }
+
+    #[tokio::test]
+    async fn test_state_survives_restart() {
+        let storage = Arc::new(NodeStorage::in_memory().unwrap());
+        let mixer = NoteMixer::with_storage(storage.clone()).unwrap();
+
+        let mut note = Note::new(random_bytes(), 1000, ASSET_NOX, random_bytes());
+        mixer.deposit(&mut note).await.unwrap();
+        let mut other = Note::new(random_bytes(), 500, ASSET_NOX, random_bytes());
+        mixer.deposit(&mut other).await.unwrap();
+
+        let request = SpendRequest {
+            merkle_root: mixer.root().await,
+            nullifier: note.nullifier(),
+            recipient: [0x12; 32],
+            amount: 1000,
+            asset: ASSET_NOX,
+            fee: 10,
+            merkle_path: vec![],
+            proof: vec![],
+        };
+        assert!(mixer.spend(&request).await.unwrap().success);
+        let root = mixer.root().await;
+        drop(mixer);
+
+        let restored = NoteMixer::with_storage(storage).unwrap();
+        assert_eq!(restored.root().await, root);
+        assert_eq!(restored.note_count().await, 2);
+        assert_eq!(restored.tvl(&ASSET_NOX).await, 1500);
+        assert!(restored.is_root_accepted(&root).await);
+        assert!(restored.is_spent(&note.nullifier()).await);
+        assert!(!restored.spend(&request).await.unwrap().success);
+        assert!(restored.get_proof(&other.commitment()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_evicted_nullifier_still_spent() {
+        let storage = Arc::new(NodeStorage::in_memory().unwrap());
+        let mixer = NoteMixer::with_storage(storage).unwrap();
+        let mut note = Note::new(random_bytes(), 1000, ASSET_NOX, random_bytes());
+        mixer.deposit(&mut note).await.unwrap();
+
+        let request = SpendRequest {
+            merkle_root: mixer.root().await,
+            nullifier: note.nullifier(),
+            recipient: [0x12; 32],
+            amount: 1000,
+            asset: ASSET_NOX,
+            fee: 10,
+            merkle_path: vec![],
+            proof: vec![],
+        };
+        assert!(mixer.spend(&request).await.unwrap().success);
+
+        mixer.nullifiers.write().await.clear();
+        mixer.nullifier_order.write().await.clear();
+
+        assert!(mixer.is_spent(&note.nullifier()).await);
+        assert!(!mixer.spend(&request).await.unwrap().success);
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_root_mismatch() {
+        let storage = Arc::new(NodeStorage::in_memory().unwrap());
+        let mixer = NoteMixer::with_storage(storage.clone()).unwrap();
+        let mut note = Note::new(random_bytes(), 1000, ASSET_NOX, random_bytes());
+        mixer.deposit(&mut note).await.unwrap();
+
+        storage.store_privacy_root(PrivacyTree::NoteMixer, &[0xab; 32]).unwrap();
+        assert!(NoteMixer::with_storage(storage).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_production_requires_verifying_key() {
+        let mixer = NoteMixer::new();
+        mixer.set_production_mode(true);
+        let mut note = Note::new(random_bytes(), 1000, ASSET_NOX, random_bytes());
+        mixer.deposit(&mut note).await.unwrap();
+
+        let request = SpendRequest {
+            merkle_root: mixer.root().await,
+            nullifier: note.nullifier(),
+            recipient: [0x12; 32],
+            amount: 1000,
+            asset: ASSET_NOX,
+            fee: 10,
+            merkle_path: vec![],
+            proof: vec![0u8; 128],
+        };
+
+        let result = mixer.spend(&request).await.unwrap();
+        assert!(!result.success);
+        assert!(!mixer.is_spent(&note.nullifier()).await);
+    }
+
+    #[tokio::test]
+    async fn test_spend_proof_verification() {
+        let mut rng = ark_std::rand::thread_rng();
+        let keys = SpendKeys::generate(&mut rng).unwrap();
+
+        let mixer = NoteMixer::new();
+        mixer.set_production_mode(true);
+        mixer.load_verifying_key(&keys.verifying_key_bytes().unwrap()).await.unwrap();
+
+        let secret = random_bytes();
+        let randomness = random_bytes();
+        let mut note = Note::new(secret, 1000, ASSET_NOX, randomness);
+        mixer.deposit(&mut note).await.unwrap();
+
+        let input = SpendProofInput {
+            secret,
+            randomness,
+            amount: 1000,
+            asset: ASSET_NOX,
+            merkle_path: mixer.get_proof(&note.commitment()).await.unwrap(),
+            merkle_root: mixer.root().await,
+            recipient: [0x12; 32],
+            fee: 10,
+        };
+        let (proof, public) = prove_spend(&keys.proving_key, &input, &mut rng).unwrap();
+        assert_eq!(public.nullifier, note.nullifier());
+
+        let mut request = SpendRequest {
+            merkle_root: public.merkle_root,
+            nullifier: public.nullifier,
+            recipient: [0x13; 32],
+            amount: public.amount,
+            asset: public.asset,
+            fee: public.fee,
+            merkle_path: vec![],
+            proof,
+        };
+
+        let redirected = mixer.spend(&request).await.unwrap();
+        assert!(!redirected.success);
+        assert!(!mixer.is_spent(&note.nullifier()).await);
+
+        request.recipient = public.recipient;
+        assert!(mixer.spend(&request).await.unwrap().success);
+        assert!(!mixer.spend(&request).await.unwrap().success);
+
+        // The same proof under another encoding of the nullifier or
+        // recipient is still a replay.
+        let mut aliased = request.clone();
+        aliased.nullifier = plus_field_order(&request.nullifier);
+        assert!(!mixer.spend(&aliased).await.unwrap().success);
+        assert!(!mixer.is_spent(&aliased.nullifier).await);
+
+        let mut aliased = request;
+        aliased.recipient = plus_field_order(&public.recipient);
+        assert!(!mixer.spend(&aliased).await.unwrap().success);
+    }
+
+    /// `bytes + r` for the BN254 scalar field order `r`, a different
+    /// encoding of the same field element.
+    fn plus_field_order(bytes: &[u8; 32]) -> [u8; 32] {
+        use ark_ff::{BigInteger, PrimeField};
+
+        let order = ark_bn254::Fr::MODULUS.to_bytes_le();
+        let mut sum = [0u8; 32];
+        let mut carry = 0u16;
+        for i in 0..32 {
+            let digit = bytes[i] as u16 + order[i] as u16 + carry;
+            sum[i] = digit as u8;
+            carry = digit >> 8;
+        }
+        assert_eq!(carry, 0);
+        sum
+    }
 }
@@ -10,6 +10,8 @@ pub struct SpendRequest {
     pub merkle_root: [u8; 32],
     pub nullifier: [u8; 32],
     pub recipient: [u8; 32],
+    pub amount: u128,
+    pub asset: AssetId,
     pub fee: u128,
     pub merkle_path: Vec<([u8; 32], bool)>,
     pub proof: Vec<u8>,
@@ -1,16 +1,38 @@
+use crate::config::{MixingConfig, MixingMode};
 use nonos_crypto::{blake3_derive_key, random_bytes};
 use nonos_types::{Blake3Key, NonosError, NonosResult, NodeId};
+use rand::seq::SliceRandom;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, warn};
 
 const X25519_PUBLIC_KEY_SIZE: usize = 32;
-const NONCE_SIZE: usize = 12;
-const TAG_SIZE: usize = 16;
+const MAC_SIZE: usize = 32;
 const MAX_PAYLOAD_SIZE: usize = 8192;
 const ROUTING_INFO_SIZE: usize = 40;
+const HOP_INFO_SIZE: usize = ROUTING_INFO_SIZE + MAC_SIZE;
+const ROUTING_HEADER_SIZE: usize = MAX_HOPS * HOP_INFO_SIZE;
+const PAYLOAD_LENGTH_SIZE: usize = 2;
+const SPHINX_PAYLOAD_SIZE: usize = MAC_SIZE + PAYLOAD_LENGTH_SIZE + MAX_PAYLOAD_SIZE;
+
+/// Longest path a packet can describe. Shorter paths are padded, so the
+/// header never reveals the path length.
+pub const MAX_HOPS: usize = 5;
+
+/// Replay tags recorded under one mix key before it is rotated early, which
+/// bounds the replay set without ever refusing fresh packets.
+const MAX_REPLAY_TAGS: usize = 1 << 18;
+/// Bytes of the payload forming the hashed half of each LIONESS round.
+const LIONESS_LEFT_SIZE: usize = 32;
+
+/// Size of every Sphinx packet on the wire, at every hop.
+pub const SPHINX_PACKET_SIZE: usize =
+    X25519_PUBLIC_KEY_SIZE + ROUTING_HEADER_SIZE + MAC_SIZE + SPHINX_PAYLOAD_SIZE;
 
 #[derive(Clone)]
 pub struct MixnetKeypair {
@@ -78,24 +100,68 @@ pub struct MixNode {
     pub address: String,
 }
 
+/// A fixed-size Sphinx packet.
+///
+/// `alpha` is re-blinded and `beta` re-encrypted and shifted at every hop,
+/// with filler keeping it at [`MAX_HOPS`] entries, so neither the size nor
+/// any field of the packet links its incoming and outgoing forms or reveals
+/// a node's position in the path.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct OnionPacket {
-    pub ephemeral_public: [u8; X25519_PUBLIC_KEY_SIZE],
-    pub encrypted_payload: Vec<u8>,
-    pub request_id: [u8; 16],
+pub struct SphinxPacket {
+    pub alpha: [u8; X25519_PUBLIC_KEY_SIZE],
+    pub beta: Vec<u8>,
+    pub gamma: [u8; MAC_SIZE],
+    pub payload: Vec<u8>,
 }
 
-impl OnionPacket {
+impl SphinxPacket {
     pub fn is_valid(&self) -> bool {
-        self.encrypted_payload.len() >= NONCE_SIZE + TAG_SIZE + ROUTING_INFO_SIZE
+        self.beta.len() == ROUTING_HEADER_SIZE && self.payload.len() == SPHINX_PAYLOAD_SIZE
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SPHINX_PACKET_SIZE);
+        bytes.extend_from_slice(&self.alpha);
+        bytes.extend_from_slice(&self.beta);
+        bytes.extend_from_slice(&self.gamma);
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> NonosResult<Self> {
+        if bytes.len() != SPHINX_PACKET_SIZE {
+            return Err(NonosError::Crypto(format!(
+                "Sphinx packet must be {} bytes, got {}",
+                SPHINX_PACKET_SIZE,
+                bytes.len()
+            )));
+        }
+
+        let (alpha, rest) = bytes.split_at(X25519_PUBLIC_KEY_SIZE);
+        let (beta, rest) = rest.split_at(ROUTING_HEADER_SIZE);
+        let (gamma, payload) = rest.split_at(MAC_SIZE);
+
+        Ok(Self {
+            alpha: alpha.try_into().expect("split at key size"),
+            beta: beta.to_vec(),
+            gamma: gamma.try_into().expect("split at MAC size"),
+            payload: payload.to_vec(),
+        })
     }
 }
 
+/// What a mix node does with a packet after removing its layer.
 #[derive(Debug)]
-pub struct DecryptedLayer {
-    pub next_hop: Option<NodeId>,
-    pub payload: Vec<u8>,
-    pub is_exit: bool,
+pub enum SphinxAction {
+    Relay { next_hop: NodeId, packet: SphinxPacket },
+    Exit { payload: Vec<u8> },
+}
+
+#[derive(Debug)]
+pub struct ProcessedPacket {
+    /// Unique per packet and hop; a node must refuse a tag it has seen.
+    pub replay_tag: [u8; 32],
+    pub action: SphinxAction,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -147,218 +213,573 @@ impl RoutingInfo {
     }
 }
 
-fn derive_encryption_key(shared_secret: &[u8; 32], context: &[u8]) -> Blake3Key {
-    let mut input = Vec::with_capacity(32 + context.len());
-    input.extend_from_slice(shared_secret);
-    input.extend_from_slice(context);
-    blake3_derive_key("nonos-mixnet-layer-key", &input)
+struct HopSecrets {
+    header_key: Blake3Key,
+    mac_key: Blake3Key,
+    payload_cipher: Lioness,
+    payload_mac_key: Blake3Key,
+    replay_tag: [u8; 32],
 }
 
-fn aes_gcm_encrypt(key: &Blake3Key, plaintext: &[u8], aad: &[u8]) -> NonosResult<Vec<u8>> {
-    use aes_gcm::{
-        aead::{Aead, KeyInit, Payload},
-        Aes256Gcm, Nonce,
-    };
-
-    let cipher = Aes256Gcm::new_from_slice(&key.0)
-        .map_err(|e| NonosError::Crypto(e.to_string()))?;
+impl HopSecrets {
+    fn derive(shared_secret: &[u8; 32]) -> Self {
+        Self {
+            header_key: blake3_derive_key("nonos-sphinx-header-stream", shared_secret),
+            mac_key: blake3_derive_key("nonos-sphinx-header-mac", shared_secret),
+            payload_cipher: Lioness::derive(shared_secret),
+            payload_mac_key: blake3_derive_key("nonos-sphinx-payload-mac", shared_secret),
+            replay_tag: blake3_derive_key("nonos-sphinx-replay-tag", shared_secret).0,
+        }
+    }
+}
 
-    let nonce_bytes = random_bytes::<NONCE_SIZE>();
-    let nonce = Nonce::from_slice(&nonce_bytes);
+/// LIONESS over the Sphinx payload, with Blake3 as both the stream cipher
+/// and the keyed hash. Being a wide-block cipher, changing any bit of the
+/// ciphertext garbles the whole plaintext, so a relay that tags a payload
+/// cannot recognise it further along the path.
+struct Lioness {
+    keys: [Blake3Key; 4],
+}
 
-    let payload = Payload {
-        msg: plaintext,
-        aad,
-    };
+impl Lioness {
+    fn derive(shared_secret: &[u8; 32]) -> Self {
+        Self {
+            keys: [
+                blake3_derive_key("nonos-sphinx-lioness-1", shared_secret),
+                blake3_derive_key("nonos-sphinx-lioness-2", shared_secret),
+                blake3_derive_key("nonos-sphinx-lioness-3", shared_secret),
+                blake3_derive_key("nonos-sphinx-lioness-4", shared_secret),
+            ],
+        }
+    }
 
-    let ciphertext = cipher
-        .encrypt(nonce, payload)
-        .map_err(|e| NonosError::Crypto(format!("Encryption failed: {}", e)))?;
+    fn stream_round(key: &Blake3Key, left: &[u8], right: &mut [u8]) {
+        let mut round_key = key.0;
+        xor_in_place(&mut round_key, left);
+        xor_in_place(right, &keystream(&Blake3Key(round_key), right.len()));
+    }
 
-    let mut result = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
-    result.extend_from_slice(&nonce_bytes);
-    result.extend_from_slice(&ciphertext);
-    Ok(result)
-}
+    fn hash_round(key: &Blake3Key, left: &mut [u8], right: &[u8]) {
+        xor_in_place(left, &mac(key, right));
+    }
 
-fn aes_gcm_decrypt(key: &Blake3Key, encrypted: &[u8], aad: &[u8]) -> NonosResult<Vec<u8>> {
-    use aes_gcm::{
-        aead::{Aead, KeyInit, Payload},
-        Aes256Gcm, Nonce,
-    };
+    fn encrypt(&self, block: &mut [u8]) {
+        let (left, right) = block.split_at_mut(LIONESS_LEFT_SIZE);
+        Self::stream_round(&self.keys[0], left, right);
+        Self::hash_round(&self.keys[1], left, right);
+        Self::stream_round(&self.keys[2], left, right);
+        Self::hash_round(&self.keys[3], left, right);
+    }
 
-    if encrypted.len() < NONCE_SIZE + TAG_SIZE {
-        return Err(NonosError::Crypto("Encrypted data too short".into()));
+    fn decrypt(&self, block: &mut [u8]) {
+        let (left, right) = block.split_at_mut(LIONESS_LEFT_SIZE);
+        Self::hash_round(&self.keys[3], left, right);
+        Self::stream_round(&self.keys[2], left, right);
+        Self::hash_round(&self.keys[1], left, right);
+        Self::stream_round(&self.keys[0], left, right);
     }
+}
 
-    let cipher = Aes256Gcm::new_from_slice(&key.0)
-        .map_err(|e| NonosError::Crypto(e.to_string()))?;
+fn blinding_factor(alpha: &[u8; 32], shared_secret: &[u8; 32]) -> [u8; 32] {
+    let mut input = [0u8; 64];
+    input[..32].copy_from_slice(alpha);
+    input[32..].copy_from_slice(shared_secret);
+    blake3_derive_key("nonos-sphinx-blinding", &input).0
+}
 
-    let nonce = Nonce::from_slice(&encrypted[..NONCE_SIZE]);
-    let ciphertext = &encrypted[NONCE_SIZE..];
+fn keystream(key: &Blake3Key, len: usize) -> Vec<u8> {
+    let mut stream = vec![0u8; len];
+    blake3::Hasher::new_keyed(&key.0).finalize_xof().fill(&mut stream);
+    stream
+}
+
+fn xor_in_place(data: &mut [u8], stream: &[u8]) {
+    for (byte, key) in data.iter_mut().zip(stream) {
+        *byte ^= key;
+    }
+}
 
-    let payload = Payload {
-        msg: ciphertext,
-        aad,
-    };
+fn mac(key: &Blake3Key, data: &[u8]) -> [u8; MAC_SIZE] {
+    *blake3::keyed_hash(&key.0, data).as_bytes()
+}
 
-    cipher
-        .decrypt(nonce, payload)
-        .map_err(|e| NonosError::Crypto(format!("Decryption failed: {}", e)))
+fn verify_mac(key: &Blake3Key, data: &[u8], expected: &[u8; MAC_SIZE]) -> bool {
+    // `blake3::Hash` compares in constant time.
+    blake3::keyed_hash(&key.0, data) == blake3::Hash::from(*expected)
 }
 
-pub fn build_onion_packet(
+pub fn build_sphinx_packet(
     payload: &[u8],
     path: &[(NodeId, [u8; 32])],
-) -> NonosResult<OnionPacket> {
+) -> NonosResult<SphinxPacket> {
     if path.is_empty() {
         return Err(NonosError::Crypto("Path cannot be empty".into()));
     }
+    if path.len() > MAX_HOPS {
+        return Err(NonosError::Crypto(format!("Path exceeds {} hops", MAX_HOPS)));
+    }
     if payload.len() > MAX_PAYLOAD_SIZE {
         return Err(NonosError::Crypto("Payload too large".into()));
     }
 
-    let request_id = random_bytes::<16>();
-
-    let mut current_payload = payload.to_vec();
-    let mut current_ephemeral: Option<[u8; 32]> = None;
-
-    for (i, (_node_id, their_public)) in path.iter().enumerate().rev() {
-        let ephemeral = MixnetKeypair::generate();
-
-        let shared = ephemeral.diffie_hellman(their_public);
+    let ephemeral = MixnetKeypair::generate();
+    let mut alpha = *ephemeral.public_key();
+
+    // Hop i sees alpha blinded by every earlier hop, so its shared secret is
+    // its public key multiplied by the ephemeral secret and those factors.
+    let mut scalars = vec![ephemeral.secret];
+    let mut secrets = Vec::with_capacity(path.len());
+    for (_, node_public) in path {
+        let shared = scalars
+            .iter()
+            .fold(*node_public, |point, scalar| x25519_dalek::x25519(*scalar, point));
+        if shared == [0u8; 32] {
+            return Err(NonosError::Crypto("Invalid mix node public key".into()));
+        }
 
-        let layer_key = derive_encryption_key(&shared, &request_id);
+        let blinding = blinding_factor(&alpha, &shared);
+        alpha = x25519_dalek::x25519(blinding, alpha);
+        scalars.push(blinding);
+        secrets.push(HopSecrets::derive(&shared));
+    }
 
-        let routing = if i == path.len() - 1 {
-            RoutingInfo::exit_node()
-        } else {
-            RoutingInfo::relay(&path[i + 1].0)
-        };
+    // The filler is what the earlier hops shift into the end of the header;
+    // precomputing it lets every hop's MAC cover the whole header.
+    let exit = path.len() - 1;
+    let mut filler = Vec::with_capacity(exit * HOP_INFO_SIZE);
+    for hop in &secrets[..exit] {
+        filler.extend_from_slice(&[0u8; HOP_INFO_SIZE]);
+        let stream = keystream(&hop.header_key, ROUTING_HEADER_SIZE + HOP_INFO_SIZE);
+        let offset = stream.len() - filler.len();
+        xor_in_place(&mut filler, &stream[offset..]);
+    }
 
-        let mut layer_plaintext = Vec::with_capacity(
-            ROUTING_INFO_SIZE + X25519_PUBLIC_KEY_SIZE + current_payload.len(),
-        );
-        layer_plaintext.extend_from_slice(&routing.to_bytes());
-        if let Some(inner_ephemeral) = current_ephemeral {
-            layer_plaintext.extend_from_slice(&inner_ephemeral);
-        }
-        layer_plaintext.extend_from_slice(&current_payload);
+    let mut beta = vec![0u8; ROUTING_HEADER_SIZE - filler.len()];
+    beta[..ROUTING_INFO_SIZE].copy_from_slice(&RoutingInfo::exit_node().to_bytes());
+    let stream = keystream(&secrets[exit].header_key, beta.len());
+    xor_in_place(&mut beta, &stream);
+    beta.extend_from_slice(&filler);
+    let mut gamma = mac(&secrets[exit].mac_key, &beta);
+
+    for i in (0..exit).rev() {
+        let mut layer = Vec::with_capacity(ROUTING_HEADER_SIZE);
+        layer.extend_from_slice(&RoutingInfo::relay(&path[i + 1].0).to_bytes());
+        layer.extend_from_slice(&gamma);
+        layer.extend_from_slice(&beta[..ROUTING_HEADER_SIZE - HOP_INFO_SIZE]);
+        xor_in_place(&mut layer, &keystream(&secrets[i].header_key, ROUTING_HEADER_SIZE));
+
+        gamma = mac(&secrets[i].mac_key, &layer);
+        beta = layer;
+    }
 
-        let encrypted = aes_gcm_encrypt(&layer_key, &layer_plaintext, ephemeral.public_key())?;
+    let mut body = vec![0u8; SPHINX_PAYLOAD_SIZE];
+    let data_start = MAC_SIZE + PAYLOAD_LENGTH_SIZE;
+    body[MAC_SIZE..data_start].copy_from_slice(&(payload.len() as u16).to_le_bytes());
+    body[data_start..data_start + payload.len()].copy_from_slice(payload);
+    let tag = mac(&secrets[exit].payload_mac_key, &body[MAC_SIZE..]);
+    body[..MAC_SIZE].copy_from_slice(&tag);
 
-        current_payload = encrypted;
-        current_ephemeral = Some(*ephemeral.public_key());
+    for hop in secrets.iter().rev() {
+        hop.payload_cipher.encrypt(&mut body);
     }
 
-    Ok(OnionPacket {
-        ephemeral_public: current_ephemeral.expect("path not empty"),
-        encrypted_payload: current_payload,
-        request_id,
+    Ok(SphinxPacket {
+        alpha: *ephemeral.public_key(),
+        beta,
+        gamma,
+        payload: body,
     })
 }
 
-pub fn decrypt_onion_layer(
-    packet: &OnionPacket,
+/// Removes this node's layer. Header tampering is caught here; payload
+/// tampering garbles the whole payload and is caught at the exit, which
+/// checks the sender's payload MAC.
+pub fn process_sphinx_packet(
+    packet: &SphinxPacket,
     our_keypair: &MixnetKeypair,
-) -> NonosResult<DecryptedLayer> {
-    let shared = our_keypair.diffie_hellman(&packet.ephemeral_public);
-
-    let layer_key = derive_encryption_key(&shared, &packet.request_id);
+) -> NonosResult<ProcessedPacket> {
+    if !packet.is_valid() {
+        return Err(NonosError::Crypto("Invalid packet structure".into()));
+    }
 
-    let decrypted = aes_gcm_decrypt(&layer_key, &packet.encrypted_payload, &packet.ephemeral_public)?;
+    let shared = our_keypair.diffie_hellman(&packet.alpha);
+    if shared == [0u8; 32] {
+        return Err(NonosError::Crypto("Invalid ephemeral key".into()));
+    }
+    let secrets = HopSecrets::derive(&shared);
 
-    if decrypted.len() < ROUTING_INFO_SIZE {
-        return Err(NonosError::Crypto("Decrypted layer too short".into()));
+    if !verify_mac(&secrets.mac_key, &packet.beta, &packet.gamma) {
+        return Err(NonosError::Crypto("Header MAC mismatch".into()));
     }
 
+    let mut header = packet.beta.clone();
+    header.extend_from_slice(&[0u8; HOP_INFO_SIZE]);
+    xor_in_place(&mut header, &keystream(&secrets.header_key, ROUTING_HEADER_SIZE + HOP_INFO_SIZE));
+
     let mut routing_bytes = [0u8; ROUTING_INFO_SIZE];
-    routing_bytes.copy_from_slice(&decrypted[..ROUTING_INFO_SIZE]);
+    routing_bytes.copy_from_slice(&header[..ROUTING_INFO_SIZE]);
     let routing = RoutingInfo::from_bytes(&routing_bytes);
 
-    let payload = decrypted[ROUTING_INFO_SIZE..].to_vec();
+    let mut payload = packet.payload.clone();
+    secrets.payload_cipher.decrypt(&mut payload);
 
     if routing.is_exit() {
-        Ok(DecryptedLayer {
-            next_hop: None,
-            payload,
-            is_exit: true,
-        })
-    } else {
-        Ok(DecryptedLayer {
-            next_hop: Some(NodeId::from_bytes(routing.next_node)),
-            payload,
-            is_exit: false,
-        })
+        let mut tag = [0u8; MAC_SIZE];
+        tag.copy_from_slice(&payload[..MAC_SIZE]);
+        if !verify_mac(&secrets.payload_mac_key, &payload[MAC_SIZE..], &tag) {
+            return Err(NonosError::Crypto("Payload MAC mismatch".into()));
+        }
+
+        let data_start = MAC_SIZE + PAYLOAD_LENGTH_SIZE;
+        let len = u16::from_le_bytes([payload[MAC_SIZE], payload[MAC_SIZE + 1]]) as usize;
+        if len > MAX_PAYLOAD_SIZE {
+            return Err(NonosError::Crypto("Payload length out of range".into()));
+        }
+
+        return Ok(ProcessedPacket {
+            replay_tag: secrets.replay_tag,
+            action: SphinxAction::Exit {
+                payload: payload[data_start..data_start + len].to_vec(),
+            },
+        });
     }
+
+    let mut gamma = [0u8; MAC_SIZE];
+    gamma.copy_from_slice(&header[ROUTING_INFO_SIZE..HOP_INFO_SIZE]);
+    let blinding = blinding_factor(&packet.alpha, &shared);
+
+    Ok(ProcessedPacket {
+        replay_tag: secrets.replay_tag,
+        action: SphinxAction::Relay {
+            next_hop: NodeId::from_bytes(routing.next_node),
+            packet: SphinxPacket {
+                alpha: x25519_dalek::x25519(blinding, packet.alpha),
+                beta: header[HOP_INFO_SIZE..].to_vec(),
+                gamma,
+                payload,
+            },
+        },
+    })
+}
+
+/// Prefix of cover-packet payloads, which exits count and discard. Only the
+/// exit ever sees it; on the wire cover packets look like any other.
+const COVER_MARKER: &[u8; 15] = b"nonos-mix-cover";
+const COVER_LOOP: u8 = 0x01;
+const COVER_DROP: u8 = 0x02;
+/// Hops in the paths this node builds, the same for real and cover packets
+/// so the two cannot be told apart by route length.
+const PATH_HOPS: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoverKind {
+    /// Routed through the network and back to this node.
+    Loop,
+    /// Discarded by a random exit.
+    Drop,
 }
 
 #[derive(Clone)]
 pub struct PooledRequest {
-    pub packet: OnionPacket,
+    pub packet: SphinxPacket,
     pub arrival_time: std::time::Instant,
-    pub jitter_ms: u64,
 }
 
-pub struct MixnetProcessor {
+/// Traffic counters. Relayed packets cannot be told apart, so real and
+/// cover traffic are only separated where this node creates or exits them.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MixnetStats {
+    pub packets_received: u64,
+    pub packets_relayed: u64,
+    pub real_sent: u64,
+    pub real_delivered: u64,
+    pub loop_cover_sent: u64,
+    pub loop_cover_received: u64,
+    pub drop_cover_sent: u64,
+    pub drop_cover_received: u64,
+    pub replays_dropped: u64,
+    pub invalid_packets: u64,
+}
+
+#[derive(Default)]
+struct MixnetCounters {
+    packets_received: AtomicU64,
+    packets_relayed: AtomicU64,
+    real_sent: AtomicU64,
+    real_delivered: AtomicU64,
+    loop_cover_sent: AtomicU64,
+    loop_cover_received: AtomicU64,
+    drop_cover_sent: AtomicU64,
+    drop_cover_received: AtomicU64,
+    replays_dropped: AtomicU64,
+    invalid_packets: AtomicU64,
+}
+
+impl MixnetCounters {
+    fn snapshot(&self) -> MixnetStats {
+        MixnetStats {
+            packets_received: self.packets_received.load(Ordering::Relaxed),
+            packets_relayed: self.packets_relayed.load(Ordering::Relaxed),
+            real_sent: self.real_sent.load(Ordering::Relaxed),
+            real_delivered: self.real_delivered.load(Ordering::Relaxed),
+            loop_cover_sent: self.loop_cover_sent.load(Ordering::Relaxed),
+            loop_cover_received: self.loop_cover_received.load(Ordering::Relaxed),
+            drop_cover_sent: self.drop_cover_sent.load(Ordering::Relaxed),
+            drop_cover_received: self.drop_cover_received.load(Ordering::Relaxed),
+            replays_dropped: self.replays_dropped.load(Ordering::Relaxed),
+            invalid_packets: self.invalid_packets.load(Ordering::Relaxed),
+        }
+    }
+}
+
+type ForwardCallback = Box<dyn Fn(NodeId, SphinxPacket) + Send + Sync>;
+type ExitCallback = Box<dyn Fn(Vec<u8>) + Send + Sync>;
+
+/// Where processed packets go. Cloneable so delayed deliveries can run on
+/// their own tasks.
+#[derive(Clone, Default)]
+struct PacketSink {
+    forward_callback: Arc<RwLock<Option<ForwardCallback>>>,
+    exit_callback: Arc<RwLock<Option<ExitCallback>>>,
+    counters: Arc<MixnetCounters>,
+}
+
+impl PacketSink {
+    async fn forward(&self, next_hop: NodeId, packet: SphinxPacket) {
+        if let Some(ref callback) = *self.forward_callback.read().await {
+            callback(next_hop, packet);
+        }
+    }
+
+    async fn deliver(&self, action: SphinxAction) {
+        match action {
+            SphinxAction::Relay { next_hop, packet } => {
+                self.counters.packets_relayed.fetch_add(1, Ordering::Relaxed);
+                self.forward(next_hop, packet).await;
+            }
+            SphinxAction::Exit { payload } => match cover_kind(&payload) {
+                Some(CoverKind::Loop) => {
+                    self.counters.loop_cover_received.fetch_add(1, Ordering::Relaxed);
+                }
+                Some(CoverKind::Drop) => {
+                    self.counters.drop_cover_received.fetch_add(1, Ordering::Relaxed);
+                }
+                None => {
+                    self.counters.real_delivered.fetch_add(1, Ordering::Relaxed);
+                    if let Some(ref callback) = *self.exit_callback.read().await {
+                        callback(payload);
+                    }
+                }
+            },
+        }
+    }
+
+    /// Delivers after `delay` without holding up the caller.
+    async fn schedule(&self, action: SphinxAction, delay: Duration) {
+        if delay.is_zero() {
+            self.deliver(action).await;
+            return;
+        }
+
+        let sink = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            sink.deliver(action).await;
+        });
+    }
+}
+
+fn cover_kind(payload: &[u8]) -> Option<CoverKind> {
+    match payload.strip_prefix(COVER_MARKER.as_slice())?.first() {
+        Some(&COVER_LOOP) => Some(CoverKind::Loop),
+        Some(&COVER_DROP) => Some(CoverKind::Drop),
+        _ => None,
+    }
+}
+
+/// Samples an exponential delay with the given mean.
+fn exponential_delay(mean: Duration) -> Duration {
+    let uniform: f64 = rand::random();
+    mean.mul_f64(-(1.0 - uniform).ln())
+}
+
+/// Longest interval [`poisson_interval`] returns, however low the rate.
+pub const MAX_POISSON_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Time until the next event of a Poisson process with `rate` events per
+/// second, at most [`MAX_POISSON_INTERVAL`]; `None` if the rate is zero.
+pub fn poisson_interval(rate: f64) -> Option<Duration> {
+    if rate <= 0.0 || !rate.is_finite() {
+        return None;
+    }
+    let mean = Duration::try_from_secs_f64(1.0 / rate)
+        .map_or(MAX_POISSON_INTERVAL, |mean| mean.min(MAX_POISSON_INTERVAL));
+    Some(exponential_delay(mean).min(MAX_POISSON_INTERVAL))
+}
+
+/// A mix key and the replay tags seen under it. Tags only need to outlive
+/// their key: once it is retired, packets built for it no longer decrypt.
+struct KeyEpoch {
     keypair: MixnetKeypair,
+    seen_tags: HashSet<[u8; 32]>,
+}
+
+impl KeyEpoch {
+    fn new(keypair: MixnetKeypair) -> Self {
+        Self { keypair, seen_tags: HashSet::new() }
+    }
+}
+
+/// The current mix key and the one it replaced, which is still accepted so
+/// packets built just before a rotation are not lost.
+struct MixKeys {
+    current: KeyEpoch,
+    previous: Option<KeyEpoch>,
+    rotated_at: Instant,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ReplayCheck {
+    Fresh,
+    Replayed,
+    /// The packet's key was retired while it was being unwrapped.
+    Expired,
+}
+
+impl MixKeys {
+    fn new(keypair: MixnetKeypair, now: Instant) -> Self {
+        Self { current: KeyEpoch::new(keypair), previous: None, rotated_at: now }
+    }
+
+    /// Makes `keypair` current, dropping the previous key and its tags.
+    fn rotate(&mut self, keypair: MixnetKeypair, now: Instant) {
+        let retired = std::mem::replace(&mut self.current, KeyEpoch::new(keypair));
+        self.previous = Some(retired);
+        self.rotated_at = now;
+    }
+
+    fn rotation_due(&self, lifetime: Duration, now: Instant) -> bool {
+        now.saturating_duration_since(self.rotated_at) >= lifetime
+            || self.current.seen_tags.len() >= MAX_REPLAY_TAGS
+    }
+
+    /// Accepted keys, current first.
+    fn keypairs(&self) -> Vec<MixnetKeypair> {
+        std::iter::once(&self.current)
+            .chain(self.previous.as_ref())
+            .map(|epoch| epoch.keypair.clone())
+            .collect()
+    }
+
+    /// Records `tag` under the key whose public half is `public`.
+    fn check(&mut self, public: &[u8; 32], tag: [u8; 32]) -> ReplayCheck {
+        let epoch = std::iter::once(&mut self.current)
+            .chain(self.previous.as_mut())
+            .find(|epoch| epoch.keypair.public_key() == public);
+        match epoch {
+            None => ReplayCheck::Expired,
+            Some(epoch) => {
+                if epoch.seen_tags.insert(tag) {
+                    ReplayCheck::Fresh
+                } else {
+                    ReplayCheck::Replayed
+                }
+            }
+        }
+    }
+}
+
+pub struct MixnetProcessor {
+    keys: Arc<RwLock<MixKeys>>,
+    config: MixingConfig,
+    local_node: Arc<RwLock<Option<NodeId>>>,
     request_pool: Arc<RwLock<Vec<PooledRequest>>>,
-    min_pool_size: usize,
-    max_delay_ms: u64,
     known_nodes: Arc<RwLock<HashMap<NodeId, MixNode>>>,
-    forward_callback: Arc<RwLock<Option<Box<dyn Fn(NodeId, OnionPacket) + Send + Sync>>>>,
-    exit_callback: Arc<RwLock<Option<Box<dyn Fn(Vec<u8>) + Send + Sync>>>>,
+    sink: PacketSink,
 }
 
 impl MixnetProcessor {
     pub fn new() -> Self {
-        Self {
-            keypair: MixnetKeypair::generate(),
-            request_pool: Arc::new(RwLock::new(Vec::new())),
-            min_pool_size: 5,
-            max_delay_ms: 500,
-            known_nodes: Arc::new(RwLock::new(HashMap::new())),
-            forward_callback: Arc::new(RwLock::new(None)),
-            exit_callback: Arc::new(RwLock::new(None)),
-        }
+        Self::with_mixing(MixnetKeypair::generate(), MixingConfig::default())
     }
 
+    /// A threshold mix with no cover traffic.
     pub fn with_config(
         keypair: MixnetKeypair,
         min_pool_size: usize,
         max_delay_ms: u64,
     ) -> Self {
+        Self::with_mixing(keypair, MixingConfig {
+            mode: MixingMode::Threshold,
+            pool_size: min_pool_size,
+            max_delay_ms,
+            loop_cover_rate: 0.0,
+            drop_cover_rate: 0.0,
+            ..Default::default()
+        })
+    }
+
+    pub fn with_mixing(keypair: MixnetKeypair, config: MixingConfig) -> Self {
         Self {
-            keypair,
+            keys: Arc::new(RwLock::new(MixKeys::new(keypair, Instant::now()))),
+            config,
+            local_node: Arc::new(RwLock::new(None)),
             request_pool: Arc::new(RwLock::new(Vec::new())),
-            min_pool_size,
-            max_delay_ms,
             known_nodes: Arc::new(RwLock::new(HashMap::new())),
-            forward_callback: Arc::new(RwLock::new(None)),
-            exit_callback: Arc::new(RwLock::new(None)),
+            sink: PacketSink::default(),
         }
     }
 
-    pub fn public_key(&self) -> &[u8; 32] {
-        self.keypair.public_key()
+    /// The key senders should build packets for; changes on rotation.
+    pub async fn public_key(&self) -> [u8; 32] {
+        *self.keys.read().await.current.keypair.public_key()
+    }
+
+    /// Replaces the mix key with a fresh one. Packets for the key it
+    /// replaces are still accepted until the next rotation; packets for any
+    /// older key no longer decrypt, and its replay tags are forgotten.
+    pub async fn rotate_keys(&self) {
+        self.keys.write().await.rotate(MixnetKeypair::generate(), Instant::now());
+    }
+
+    /// Rotates once the key has lived `key_rotation_secs` or recorded
+    /// [`MAX_REPLAY_TAGS`] packets. Returns whether it did, so the new key
+    /// can be announced.
+    pub async fn rotate_keys_if_due(&self) -> bool {
+        let lifetime = Duration::from_secs(self.config.key_rotation_secs);
+        let mut keys = self.keys.write().await;
+        if !keys.rotation_due(lifetime, Instant::now()) {
+            return false;
+        }
+        keys.rotate(MixnetKeypair::generate(), Instant::now());
+        true
+    }
+
+    pub fn mixing_config(&self) -> &MixingConfig {
+        &self.config
+    }
+
+    /// This node's own mix identity, which loop cover packets return to.
+    pub async fn set_local_node(&self, node_id: NodeId) {
+        *self.local_node.write().await = Some(node_id);
     }
 
     pub async fn set_forward_callback<F>(&self, callback: F)
     where
-        F: Fn(NodeId, OnionPacket) + Send + Sync + 'static,
+        F: Fn(NodeId, SphinxPacket) + Send + Sync + 'static,
     {
-        *self.forward_callback.write().await = Some(Box::new(callback));
+        *self.sink.forward_callback.write().await = Some(Box::new(callback));
     }
 
     pub async fn set_exit_callback<F>(&self, callback: F)
     where
         F: Fn(Vec<u8>) + Send + Sync + 'static,
     {
-        *self.exit_callback.write().await = Some(Box::new(callback));
+        *self.sink.exit_callback.write().await = Some(Box::new(callback));
     }
 
     pub async fn add_node(&self, node: MixNode) {
-        self.known_nodes.write().await.insert(node.node_id.clone(), node);
+        self.known_nodes.write().await.insert(node.node_id, node);
     }
 
     pub async fn remove_node(&self, node_id: &NodeId) {
@@ -369,87 +790,111 @@ impl MixnetProcessor {
         self.known_nodes.read().await.values().cloned().collect()
     }
 
-    pub async fn process_packet(&self, packet: OnionPacket) -> NonosResult<()> {
+    pub async fn process_packet(&self, packet: SphinxPacket) -> NonosResult<()> {
         if !packet.is_valid() {
+            self.sink.counters.invalid_packets.fetch_add(1, Ordering::Relaxed);
             return Err(NonosError::Crypto("Invalid packet structure".into()));
         }
+        self.sink.counters.packets_received.fetch_add(1, Ordering::Relaxed);
 
-        let jitter = (random_bytes::<2>()[0] as u64 * self.max_delay_ms) / 255;
-        let pooled = PooledRequest {
-            packet,
-            arrival_time: std::time::Instant::now(),
-            jitter_ms: jitter,
-        };
+        match self.config.mode {
+            MixingMode::Poisson => {
+                if let Some(action) = self.unwrap_packet(&packet).await {
+                    let delay = exponential_delay(Duration::from_millis(self.config.mean_delay_ms));
+                    self.sink.schedule(action, delay).await;
+                }
+            }
+            MixingMode::Threshold => {
+                let mut pool = self.request_pool.write().await;
+                pool.push(PooledRequest {
+                    packet,
+                    arrival_time: std::time::Instant::now(),
+                });
+
+                if pool.len() >= self.config.pool_size {
+                    drop(pool);
+                    self.flush_pool().await?;
+                }
+            }
+        }
 
-        let mut pool = self.request_pool.write().await;
-        pool.push(pooled);
+        Ok(())
+    }
 
-        if pool.len() >= self.min_pool_size {
-            drop(pool);
-            self.flush_pool().await?;
+    /// Removes our layer under the current or previous key and rejects
+    /// replays; failures are counted and logged rather than returned, as
+    /// the sender never learns of them.
+    async fn unwrap_packet(&self, packet: &SphinxPacket) -> Option<SphinxAction> {
+        let keypairs = self.keys.read().await.keypairs();
+        let mut result = Err(NonosError::Crypto("No mix key".into()));
+        for keypair in &keypairs {
+            result = process_sphinx_packet(packet, keypair)
+                .map(|processed| (*keypair.public_key(), processed));
+            if result.is_ok() {
+                break;
+            }
         }
 
-        Ok(())
+        let (public, processed) = match result {
+            Ok(unwrapped) => unwrapped,
+            Err(e) => {
+                warn!("Failed to process Sphinx packet: {}", e);
+                self.sink.counters.invalid_packets.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+        };
+
+        match self.keys.write().await.check(&public, processed.replay_tag) {
+            ReplayCheck::Fresh => {}
+            ReplayCheck::Replayed => {
+                warn!("Dropping replayed Sphinx packet");
+                self.sink.counters.replays_dropped.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+            ReplayCheck::Expired => {
+                debug!("Dropping Sphinx packet for a retired mix key");
+                self.sink.counters.invalid_packets.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+        }
+
+        Some(processed.action)
     }
 
     async fn flush_pool(&self) -> NonosResult<()> {
-        let mut pool = self.request_pool.write().await;
-        if pool.is_empty() {
+        let mut requests: Vec<_> = self.request_pool.write().await.drain(..).collect();
+        if requests.is_empty() {
             return Ok(());
         }
 
-        debug!("Mixing {} requests in pool", pool.len());
+        debug!("Mixing {} requests in pool", requests.len());
+        requests.shuffle(&mut rand::thread_rng());
 
-        let rng_bytes = random_bytes::<32>();
-        for i in (1..pool.len()).rev() {
-            let j = (rng_bytes[i % 32] as usize) % (i + 1);
-            pool.swap(i, j);
+        for request in requests {
+            if let Some(action) = self.unwrap_packet(&request.packet).await {
+                let jitter = rand::thread_rng().gen_range(0..=self.config.max_delay_ms);
+                self.sink.schedule(action, Duration::from_millis(jitter)).await;
+            }
         }
 
-        let requests: Vec<_> = pool.drain(..).collect();
-        drop(pool);
+        Ok(())
+    }
 
-        for request in requests {
-            let elapsed = request.arrival_time.elapsed().as_millis() as u64;
-            let remaining_delay = request.jitter_ms.saturating_sub(elapsed);
-            if remaining_delay > 0 {
-                tokio::time::sleep(tokio::time::Duration::from_millis(remaining_delay)).await;
-            }
+    /// Threshold mode: flushes the pool once its oldest packet has waited
+    /// `max_delay_ms`, so a quiet node never holds packets indefinitely.
+    pub async fn flush_expired(&self) -> NonosResult<()> {
+        let max_wait = Duration::from_millis(self.config.max_delay_ms);
+        let expired = self.request_pool.read().await
+            .iter()
+            .any(|request| request.arrival_time.elapsed() >= max_wait);
 
-            match decrypt_onion_layer(&request.packet, &self.keypair) {
-                Ok(layer) => {
-                    if layer.is_exit {
-                        if let Some(ref callback) = *self.exit_callback.read().await {
-                            callback(layer.payload);
-                        }
-                    } else if let Some(next_hop) = layer.next_hop {
-                        if layer.payload.len() >= X25519_PUBLIC_KEY_SIZE {
-                            let mut ephemeral = [0u8; X25519_PUBLIC_KEY_SIZE];
-                            ephemeral.copy_from_slice(&layer.payload[..X25519_PUBLIC_KEY_SIZE]);
-                            let encrypted = layer.payload[X25519_PUBLIC_KEY_SIZE..].to_vec();
-
-                            let next_packet = OnionPacket {
-                                ephemeral_public: ephemeral,
-                                encrypted_payload: encrypted,
-                                request_id: request.packet.request_id,
-                            };
-
-                            if let Some(ref callback) = *self.forward_callback.read().await {
-                                callback(next_hop, next_packet);
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    warn!("Failed to decrypt onion layer: {}", e);
-                }
-            }
+        if expired {
+            self.flush_pool().await?;
         }
-
         Ok(())
     }
 
-    pub async fn create_packet(&self, payload: &[u8], path: &[NodeId]) -> NonosResult<OnionPacket> {
+    pub async fn create_packet(&self, payload: &[u8], path: &[NodeId]) -> NonosResult<SphinxPacket> {
         if path.is_empty() {
             return Err(NonosError::Crypto("Path cannot be empty".into()));
         }
@@ -460,11 +905,89 @@ impl MixnetProcessor {
         for node_id in path {
             let node = nodes.get(node_id)
                 .ok_or_else(|| NonosError::Crypto(format!("Unknown node: {:?}", node_id)))?;
-            path_with_keys.push((node.node_id.clone(), node.public_key));
+            path_with_keys.push((node.node_id, node.public_key));
         }
 
         drop(nodes);
-        build_onion_packet(payload, &path_with_keys)
+        build_sphinx_packet(payload, &path_with_keys)
+    }
+
+    /// Builds a packet for `path` and hands it to the first hop.
+    pub async fn send_packet(&self, payload: &[u8], path: &[NodeId]) -> NonosResult<()> {
+        let packet = self.create_packet(payload, path).await?;
+        self.sink.counters.real_sent.fetch_add(1, Ordering::Relaxed);
+        self.sink.forward(path[0], packet).await;
+        Ok(())
+    }
+
+    /// Sends `payload` to `destination` through random relays, so no single
+    /// relay sees both this node and the destination.
+    pub async fn send_to(&self, payload: &[u8], destination: NodeId) -> NonosResult<()> {
+        let local = *self.local_node.read().await;
+        let nodes = self.known_nodes.read().await;
+        if !nodes.contains_key(&destination) {
+            return Err(NonosError::Network(format!("Unknown mix node: {}", destination)));
+        }
+
+        let mut path: Vec<NodeId> = nodes.keys()
+            .filter(|node_id| **node_id != destination && Some(**node_id) != local)
+            .copied()
+            .collect();
+        drop(nodes);
+
+        path.shuffle(&mut rand::thread_rng());
+        path.truncate(PATH_HOPS - 1);
+        path.push(destination);
+        self.send_packet(payload, &path).await
+    }
+
+    /// Sends one cover packet over a random path of known nodes.
+    pub async fn send_cover_packet(&self, kind: CoverKind) -> NonosResult<()> {
+        let local = *self.local_node.read().await;
+        let mut candidates: Vec<MixNode> = self.known_nodes.read().await
+            .values()
+            .filter(|node| Some(node.node_id) != local)
+            .cloned()
+            .collect();
+        if candidates.is_empty() {
+            return Err(NonosError::Network("No mix nodes known for cover traffic".into()));
+        }
+
+        candidates.shuffle(&mut rand::thread_rng());
+        let mut path: Vec<(NodeId, [u8; 32])> = candidates
+            .iter()
+            .take(PATH_HOPS)
+            .map(|node| (node.node_id, node.public_key))
+            .collect();
+
+        let kind_byte = match kind {
+            CoverKind::Loop => {
+                let local = local.ok_or_else(|| {
+                    NonosError::Network("Local mix node not set for loop cover".into())
+                })?;
+                path.truncate(PATH_HOPS - 1);
+                path.push((local, self.public_key().await));
+                COVER_LOOP
+            }
+            CoverKind::Drop => COVER_DROP,
+        };
+
+        let mut payload = COVER_MARKER.to_vec();
+        payload.push(kind_byte);
+        let packet = build_sphinx_packet(&payload, &path)?;
+
+        let counter = match kind {
+            CoverKind::Loop => &self.sink.counters.loop_cover_sent,
+            CoverKind::Drop => &self.sink.counters.drop_cover_sent,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        self.sink.forward(path[0].0, packet).await;
+        Ok(())
+    }
+
+    pub fn stats(&self) -> MixnetStats {
+        self.sink.counters.snapshot()
     }
 
     pub async fn pool_size(&self) -> usize {
@@ -510,102 +1033,231 @@ mod tests {
         assert_eq!(shared_alice, shared_bob);
     }
 
-    #[test]
-    fn test_single_hop_onion() {
-        let exit_kp = MixnetKeypair::generate();
-        let exit_node = NodeId::from_bytes(random_bytes::<32>());
+    fn random_path(len: usize) -> (Vec<MixnetKeypair>, Vec<(NodeId, [u8; 32])>) {
+        let keypairs: Vec<_> = (0..len).map(|_| MixnetKeypair::generate()).collect();
+        let path = keypairs
+            .iter()
+            .map(|kp| (NodeId::from_bytes(random_bytes::<32>()), *kp.public_key()))
+            .collect();
+        (keypairs, path)
+    }
 
-        let path = vec![(exit_node.clone(), *exit_kp.public_key())];
+    #[test]
+    fn test_single_hop_packet() {
+        let (keypairs, path) = random_path(1);
         let payload = b"secret message";
 
-        let packet = build_onion_packet(payload, &path).unwrap();
+        let packet = build_sphinx_packet(payload, &path).unwrap();
         assert!(packet.is_valid());
+        assert_eq!(packet.to_bytes().len(), SPHINX_PACKET_SIZE);
 
-        let decrypted = decrypt_onion_layer(&packet, &exit_kp).unwrap();
-        assert!(decrypted.is_exit);
-        assert_eq!(decrypted.payload, payload);
+        let processed = process_sphinx_packet(&packet, &keypairs[0]).unwrap();
+        match processed.action {
+            SphinxAction::Exit { payload: received } => assert_eq!(received, payload),
+            other => panic!("expected exit, got {:?}", other),
+        }
     }
 
     #[test]
-    fn test_three_hop_onion() {
-        let entry_kp = MixnetKeypair::generate();
-        let middle_kp = MixnetKeypair::generate();
-        let exit_kp = MixnetKeypair::generate();
-
-        let entry_node = NodeId::from_bytes(random_bytes::<32>());
-        let middle_node = NodeId::from_bytes(random_bytes::<32>());
-        let exit_node = NodeId::from_bytes(random_bytes::<32>());
-
-        let path = vec![
-            (entry_node.clone(), *entry_kp.public_key()),
-            (middle_node.clone(), *middle_kp.public_key()),
-            (exit_node.clone(), *exit_kp.public_key()),
-        ];
-
-        let payload = b"three-hop secret";
-        let packet = build_onion_packet(payload, &path).unwrap();
-
-        let layer1 = decrypt_onion_layer(&packet, &entry_kp).unwrap();
-        assert!(!layer1.is_exit);
-        assert_eq!(layer1.next_hop, Some(middle_node.clone()));
-
-        let ephemeral1: [u8; 32] = layer1.payload[..32].try_into().unwrap();
-        let packet2 = OnionPacket {
-            ephemeral_public: ephemeral1,
-            encrypted_payload: layer1.payload[32..].to_vec(),
-            request_id: packet.request_id,
-        };
-
-        let layer2 = decrypt_onion_layer(&packet2, &middle_kp).unwrap();
-        assert!(!layer2.is_exit);
-        assert_eq!(layer2.next_hop, Some(exit_node.clone()));
+    fn test_multi_hop_packet_is_unlinkable() {
+        for hops in 2..=MAX_HOPS {
+            let (keypairs, path) = random_path(hops);
+            let payload = b"multi-hop secret";
+
+            let mut packet = build_sphinx_packet(payload, &path).unwrap();
+            let mut tags = HashSet::new();
+
+            for (i, keypair) in keypairs.iter().enumerate() {
+                let processed = process_sphinx_packet(&packet, keypair).unwrap();
+                assert!(tags.insert(processed.replay_tag));
+
+                match processed.action {
+                    SphinxAction::Relay { next_hop, packet: next } => {
+                        assert_eq!(next_hop, path[i + 1].0);
+                        assert_eq!(next.to_bytes().len(), SPHINX_PACKET_SIZE);
+                        assert_ne!(next.alpha, packet.alpha);
+                        assert_ne!(next.gamma, packet.gamma);
+                        assert_ne!(next.payload, packet.payload);
+                        packet = next;
+                    }
+                    SphinxAction::Exit { payload: received } => {
+                        assert_eq!(i, hops - 1);
+                        assert_eq!(received, payload);
+                    }
+                }
+            }
+        }
+    }
 
-        let ephemeral2: [u8; 32] = layer2.payload[..32].try_into().unwrap();
-        let packet3 = OnionPacket {
-            ephemeral_public: ephemeral2,
-            encrypted_payload: layer2.payload[32..].to_vec(),
-            request_id: packet.request_id,
-        };
+    #[test]
+    fn test_path_limits() {
+        let (_, path) = random_path(MAX_HOPS + 1);
+        assert!(build_sphinx_packet(b"too long", &path).is_err());
+        assert!(build_sphinx_packet(b"empty", &[]).is_err());
 
-        let layer3 = decrypt_onion_layer(&packet3, &exit_kp).unwrap();
-        assert!(layer3.is_exit);
-        assert_eq!(layer3.payload, payload);
+        let (_, path) = random_path(1);
+        assert!(build_sphinx_packet(&vec![0u8; MAX_PAYLOAD_SIZE + 1], &path).is_err());
     }
 
     #[test]
     fn test_wrong_key_fails() {
-        let real_kp = MixnetKeypair::generate();
-        let wrong_kp = MixnetKeypair::generate();
-        let node = NodeId::from_bytes(random_bytes::<32>());
-
-        let path = vec![(node, *real_kp.public_key())];
-        let packet = build_onion_packet(b"secret", &path).unwrap();
+        let (_, path) = random_path(1);
+        let packet = build_sphinx_packet(b"secret", &path).unwrap();
 
-        let result = decrypt_onion_layer(&packet, &wrong_kp);
+        let result = process_sphinx_packet(&packet, &MixnetKeypair::generate());
         assert!(result.is_err());
     }
 
     #[test]
-    fn test_aes_gcm_roundtrip() {
-        let key = Blake3Key::from_bytes(random_bytes::<32>());
-        let plaintext = b"test message for encryption";
-        let aad = b"additional data";
+    fn test_tampering_is_detected() {
+        let (keypairs, path) = random_path(2);
+        let packet = build_sphinx_packet(b"secret", &path).unwrap();
+
+        let mut header_tampered = packet.clone();
+        header_tampered.beta[100] ^= 1;
+        assert!(process_sphinx_packet(&header_tampered, &keypairs[0]).is_err());
+
+        let mut payload_tampered = packet;
+        payload_tampered.payload[500] ^= 1;
+        let relayed = match process_sphinx_packet(&payload_tampered, &keypairs[0]).unwrap().action {
+            SphinxAction::Relay { packet, .. } => packet,
+            other => panic!("expected relay, got {:?}", other),
+        };
+        assert!(process_sphinx_packet(&relayed, &keypairs[1]).is_err());
+    }
 
-        let encrypted = aes_gcm_encrypt(&key, plaintext, aad).unwrap();
-        let decrypted = aes_gcm_decrypt(&key, &encrypted, aad).unwrap();
+    #[test]
+    fn test_packet_bytes_roundtrip() {
+        let (keypairs, path) = random_path(3);
+        let packet = build_sphinx_packet(b"wire format", &path).unwrap();
 
-        assert_eq!(decrypted, plaintext);
+        let restored = SphinxPacket::from_bytes(&packet.to_bytes()).unwrap();
+        assert!(process_sphinx_packet(&restored, &keypairs[0]).is_ok());
+        assert!(SphinxPacket::from_bytes(&packet.to_bytes()[1..]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replayed_packet_is_dropped() {
+        let keypair = MixnetKeypair::generate();
+        let processor = MixnetProcessor::with_config(keypair.clone(), 1, 0);
+
+        let delivered = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = delivered.clone();
+        processor.set_exit_callback(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }).await;
+
+        let path = vec![(NodeId::from_bytes(random_bytes::<32>()), *keypair.public_key())];
+        let packet = build_sphinx_packet(b"once", &path).unwrap();
+
+        processor.process_packet(packet.clone()).await.unwrap();
+        processor.process_packet(packet).await.unwrap();
+
+        assert_eq!(delivered.load(std::sync::atomic::Ordering::SeqCst), 1);
     }
 
     #[test]
-    fn test_aes_gcm_wrong_aad_fails() {
-        let key = Blake3Key::from_bytes(random_bytes::<32>());
-        let plaintext = b"test message";
+    fn test_exponential_delay_mean() {
+        let mean = Duration::from_millis(200);
+        let samples = 20_000;
+        let total: Duration = (0..samples).map(|_| exponential_delay(mean)).sum();
+        let average = total.as_secs_f64() * 1000.0 / samples as f64;
+        assert!((170.0..230.0).contains(&average), "mean delay {}ms", average);
+
+        assert!(poisson_interval(0.0).is_none());
+        assert!(poisson_interval(f64::NAN).is_none());
+        assert!(poisson_interval(5.0).is_some());
+        assert!(poisson_interval(1e-20).unwrap() <= MAX_POISSON_INTERVAL);
+        assert!(poisson_interval(f64::MIN_POSITIVE).unwrap() <= MAX_POISSON_INTERVAL);
+    }
 
-        let encrypted = aes_gcm_encrypt(&key, plaintext, b"correct aad").unwrap();
-        let result = aes_gcm_decrypt(&key, &encrypted, b"wrong aad");
+    #[tokio::test]
+    async fn test_poisson_mode_delivers_after_delay() {
+        let keypair = MixnetKeypair::generate();
+        let processor = MixnetProcessor::with_mixing(keypair.clone(), MixingConfig {
+            mode: MixingMode::Poisson,
+            mean_delay_ms: 5,
+            ..Default::default()
+        });
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        processor.set_exit_callback(move |payload| {
+            let _ = tx.send(payload);
+        }).await;
+
+        let path = vec![(NodeId::from_bytes(random_bytes::<32>()), *keypair.public_key())];
+        let packet = build_sphinx_packet(b"poisson", &path).unwrap();
+        processor.process_packet(packet).await.unwrap();
 
-        assert!(result.is_err());
+        assert_eq!(processor.pool_size().await, 0);
+        let payload = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap();
+        assert_eq!(payload.unwrap(), b"poisson");
+        assert_eq!(processor.stats().real_delivered, 1);
+    }
+
+    #[tokio::test]
+    async fn test_threshold_flushes_expired_packets() {
+        let keypair = MixnetKeypair::generate();
+        let processor = MixnetProcessor::with_config(keypair.clone(), 10, 0);
+
+        let path = vec![(NodeId::from_bytes(random_bytes::<32>()), *keypair.public_key())];
+        let packet = build_sphinx_packet(b"lonely", &path).unwrap();
+        processor.process_packet(packet).await.unwrap();
+        assert_eq!(processor.pool_size().await, 1);
+
+        processor.flush_expired().await.unwrap();
+        assert_eq!(processor.pool_size().await, 0);
+        assert_eq!(processor.stats().real_delivered, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cover_packets_are_counted_not_delivered() {
+        let local = MixnetKeypair::generate();
+        let local_id = NodeId::from_bytes(random_bytes::<32>());
+        let processor = MixnetProcessor::with_config(local.clone(), 1, 0);
+        processor.set_local_node(local_id).await;
+
+        let hop = MixnetKeypair::generate();
+        let hop_id = NodeId::from_bytes(random_bytes::<32>());
+        processor.add_node(MixNode {
+            node_id: hop_id,
+            public_key: *hop.public_key(),
+            address: String::new(),
+        }).await;
+
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let outbox = sent.clone();
+        processor.set_forward_callback(move |next_hop, packet| {
+            outbox.lock().unwrap().push((next_hop, packet));
+        }).await;
+
+        let delivered = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = delivered.clone();
+        processor.set_exit_callback(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }).await;
+
+        processor.send_cover_packet(CoverKind::Loop).await.unwrap();
+        let (next_hop, packet) = sent.lock().unwrap().pop().unwrap();
+        assert_eq!(next_hop, hop_id);
+
+        let returned = match process_sphinx_packet(&packet, &hop).unwrap().action {
+            SphinxAction::Relay { next_hop, packet } => {
+                assert_eq!(next_hop, local_id);
+                packet
+            }
+            SphinxAction::Exit { .. } => panic!("loop cover exited early"),
+        };
+        processor.process_packet(returned).await.unwrap();
+
+        let stats = processor.stats();
+        assert_eq!(stats.loop_cover_sent, 1);
+        assert_eq!(stats.loop_cover_received, 1);
+        assert_eq!(stats.real_delivered, 0);
+        assert_eq!(delivered.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        processor.send_cover_packet(CoverKind::Drop).await.unwrap();
+        assert_eq!(processor.stats().drop_cover_sent, 1);
     }
 
     #[tokio::test]
@@ -624,6 +1276,32 @@ mod tests {
         assert_eq!(processor.pool_size().await, 0);
     }
 
+    #[tokio::test]
+    async fn test_send_to_routes_through_known_nodes() {
+        let processor = MixnetProcessor::with_config(MixnetKeypair::generate(), 1, 0);
+        let destination = NodeId::from_bytes(random_bytes::<32>());
+        assert!(processor.send_to(b"hello", destination).await.is_err());
+
+        for node_id in [destination, NodeId::from_bytes(random_bytes::<32>())] {
+            processor.add_node(MixNode {
+                node_id,
+                public_key: *MixnetKeypair::generate().public_key(),
+                address: String::new(),
+            }).await;
+        }
+
+        let first_hops = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = first_hops.clone();
+        processor.set_forward_callback(move |next_hop, _| {
+            recorded.lock().unwrap().push(next_hop);
+        }).await;
+
+        processor.send_to(b"hello", destination).await.unwrap();
+        assert_eq!(first_hops.lock().unwrap().len(), 1);
+        assert_ne!(first_hops.lock().unwrap()[0], destination);
+        assert_eq!(processor.stats().real_sent, 1);
+    }
+
     #[test]
     fn test_routing_info_serialization() {
         let node_id = NodeId::from_bytes(random_bytes::<32>());
@@ -641,4 +1319,65 @@ mod tests {
 
         assert!(restored_exit.is_exit());
     }
+
+    #[test]
+    fn test_replay_tags_are_scoped_to_key_epoch() {
+        let first = MixnetKeypair::generate();
+        let start = Instant::now();
+        let mut keys = MixKeys::new(first.clone(), start);
+
+        assert_eq!(keys.check(first.public_key(), [1; 32]), ReplayCheck::Fresh);
+        assert_eq!(keys.check(first.public_key(), [1; 32]), ReplayCheck::Replayed);
+        assert!(!keys.rotation_due(Duration::from_secs(60), start));
+        assert!(keys.rotation_due(Duration::from_secs(60), start + Duration::from_secs(60)));
+
+        let second = MixnetKeypair::generate();
+        keys.rotate(second.clone(), start);
+        assert_eq!(keys.check(first.public_key(), [1; 32]), ReplayCheck::Replayed);
+        assert_eq!(keys.check(second.public_key(), [1; 32]), ReplayCheck::Fresh);
+
+        keys.rotate(MixnetKeypair::generate(), start);
+        assert_eq!(keys.check(first.public_key(), [2; 32]), ReplayCheck::Expired);
+        assert_eq!(keys.keypairs().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_packets_for_retired_keys_are_dropped() {
+        let keypair = MixnetKeypair::generate();
+        let processor = MixnetProcessor::with_config(keypair.clone(), 1, 0);
+
+        let delivered = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = delivered.clone();
+        processor.set_exit_callback(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }).await;
+
+        let path = vec![(NodeId::from_bytes(random_bytes::<32>()), *keypair.public_key())];
+        processor.rotate_keys().await;
+        assert_ne!(processor.public_key().await, *keypair.public_key());
+        processor.process_packet(build_sphinx_packet(b"previous", &path).unwrap()).await.unwrap();
+        assert_eq!(delivered.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        processor.rotate_keys().await;
+        processor.process_packet(build_sphinx_packet(b"retired", &path).unwrap()).await.unwrap();
+        assert_eq!(delivered.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(processor.stats().invalid_packets, 1);
+    }
+
+    #[test]
+    fn test_payload_tampering_garbles_whole_block() {
+        let cipher = Lioness::derive(&random_bytes::<32>());
+        let plaintext = vec![0u8; SPHINX_PAYLOAD_SIZE];
+        let mut ciphertext = plaintext.clone();
+        cipher.encrypt(&mut ciphertext);
+
+        let mut restored = ciphertext.clone();
+        cipher.decrypt(&mut restored);
+        assert_eq!(restored, plaintext);
+
+        ciphertext[500] ^= 1;
+        cipher.decrypt(&mut ciphertext);
+        let changed = ciphertext.iter().zip(&plaintext).filter(|(a, b)| a != b).count();
+        assert!(changed > SPHINX_PAYLOAD_SIZE / 2);
+    }
 }
@@ -2,7 +2,9 @@ mod identity;
 mod mixer;
 mod zk_identity;
 mod cache_mixing;
+mod filters;
 mod tracking_blocker;
+mod subscriptions;
 mod stealth;
 mod manager;
 mod zk_sessions;
@@ -20,17 +22,33 @@ pub use identity::{ZkIdentityRegistry, IdentityCommitment, VerificationResult};
 pub use mixer::{NoteMixer, Note, SpendRequest, SpendResult, AssetId, ASSET_ETH, ASSET_NOX};
 pub use zk_identity::ZkIdentityService;
 pub use cache_mixing::CacheMixingService;
-pub use tracking_blocker::{TrackingBlockerService, TrackingBlockerStats};
+pub use filters::{FilterDecision, FilterEngine, FilterListStats, FilterRequest, RequestType, BUILTIN_RULES};
+pub use tracking_blocker::{TrackingBlockerService, TrackingBlockerStats, BUILTIN_LIST};
+pub use subscriptions::{
+    canonical_rules, rules_hash, BundleBody, BundleOutcome, ChunkAssembler, FilterListBundle,
+    FilterListSubscriptions, SubscriptionInfo, CHUNK_BYTES, MAX_BUNDLE_BYTES, REPUBLISH_INTERVAL,
+    SUBSCRIPTION_LIST_PREFIX,
+};
 pub use stealth::StealthScannerService;
 pub use manager::{PrivacyServiceManager, PrivacyStats};
 pub use zk_sessions::{ZkSessionManager, ZkSessionProof};
-pub use mixnet::{MixnetProcessor, MixnetKeypair, MixNode, OnionPacket, DecryptedLayer, PooledRequest, build_onion_packet, decrypt_onion_layer};
-pub use pir::{PrivateContentRetrieval, CachedContent, ContentMetadata, CacheStats};
-pub use oracle::{PrivacyOracle, DomainPrivacyScore, CookieBehavior};
+pub use mixnet::{
+    MixnetProcessor, MixnetKeypair, MixNode, SphinxPacket, SphinxAction, ProcessedPacket, PooledRequest,
+    MixnetStats, CoverKind, build_sphinx_packet, process_sphinx_packet, poisson_interval,
+    MAX_HOPS, MAX_POISSON_INTERVAL, SPHINX_PACKET_SIZE,
+};
+pub use pir::{
+    PrivateContentRetrieval, CachedContent, ContentMetadata, CacheStats, PirDatabase, PirDatabaseInfo,
+    PirParams, PirQuery, PirRetrieval, DEFAULT_PIR_PARAMS, MAX_PIR_DATABASE_BYTES,
+};
+pub use oracle::{
+    PrivacyOracle, DomainPrivacyScore, CookieBehavior, StakeLookup, VoteOutcome, VoterBinding,
+    normalize_domain, vote_nullifier,
+};
 pub use stealth_sessions::{StealthSession, StealthSessionManager};
 pub use credentials::{CredentialManager, CredentialType, CredentialProof, StoredCredential, CredentialInfo};
 pub use fingerprint::{FingerprintNormalizer, NormalizedRequest};
-pub use cookie_vault::{DistributedCookieVault, SecretShare};
+pub use cookie_vault::{DistributedCookieVault, HeldShare, SecretShare, ShareTransport, MAX_COOKIE_ID_LEN};
 pub use advanced::{AdvancedPrivacyManager, AdvancedPrivacyStats};
 pub use zk_credentials::{ZkCredentialSystem, ZkCredential, ZkCredentialType, ZkCredentialProof, ZkPublicInputs, MerkleProof, MERKLE_DEPTH};
 
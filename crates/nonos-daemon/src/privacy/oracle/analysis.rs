@@ -0,0 +1,95 @@
+use super::{CookieBehavior, DomainPrivacyScore, PrivacyOracle};
+
+impl PrivacyOracle {
+    pub(super) fn default_tracker_patterns() -> Vec<String> {
+        vec![
+            "google-analytics.com".into(),
+            "googletagmanager.com".into(),
+            "facebook.com/tr".into(),
+            "doubleclick.net".into(),
+            "amazon-adsystem.com".into(),
+            "hotjar.com".into(),
+            "fullstory.com".into(),
+            "segment.io".into(),
+            "mixpanel.com".into(),
+            "amplitude.com".into(),
+            "heapanalytics.com".into(),
+            "clarity.ms".into(),
+            "newrelic.com".into(),
+            "datadog-ci.com".into(),
+        ]
+    }
+
+    pub(super) fn default_fingerprint_patterns() -> Vec<String> {
+        vec![
+            "canvas.toDataURL".into(),
+            "WebGLRenderingContext".into(),
+            "AudioContext".into(),
+            "navigator.plugins".into(),
+            "navigator.mimeTypes".into(),
+            "screen.colorDepth".into(),
+            "getClientRects".into(),
+        ]
+    }
+
+    pub async fn analyze_domain(&self, domain: &str, page_content: Option<&str>) -> DomainPrivacyScore {
+        let mut trackers_detected = Vec::new();
+        let mut fingerprinting = Vec::new();
+        let mut score: i32 = 100;
+
+        for pattern in &self.tracker_patterns {
+            if domain.contains(pattern) || page_content.map(|c| c.contains(pattern)).unwrap_or(false) {
+                trackers_detected.push(pattern.clone());
+                score -= 10;
+            }
+        }
+
+        if let Some(content) = page_content {
+            for pattern in &self.fingerprint_patterns {
+                if content.contains(pattern) {
+                    fingerprinting.push(pattern.clone());
+                    score -= 5;
+                }
+            }
+        }
+
+        let cookie_behavior = self.analyze_cookie_behavior(page_content);
+        match cookie_behavior {
+            CookieBehavior::CrossSite => score -= 20,
+            CookieBehavior::Supercookies => score -= 30,
+            CookieBehavior::Persistent => score -= 10,
+            _ => {}
+        }
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        DomainPrivacyScore {
+            domain: domain.to_string(),
+            score: score.clamp(0, 100) as u8,
+            voter_count: 1,
+            trackers_detected,
+            fingerprinting,
+            cookie_behavior,
+            updated_at: now,
+            confidence: 0.0,
+        }
+    }
+
+    fn analyze_cookie_behavior(&self, content: Option<&str>) -> CookieBehavior {
+        if let Some(content) = content {
+            if content.contains("evercookie") || content.contains("localStorage.setItem") {
+                return CookieBehavior::Supercookies;
+            }
+            if content.contains("SameSite=None") {
+                return CookieBehavior::CrossSite;
+            }
+            if content.contains("document.cookie") {
+                return CookieBehavior::Persistent;
+            }
+        }
+        CookieBehavior::None
+    }
+}
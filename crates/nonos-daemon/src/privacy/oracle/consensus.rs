@@ -0,0 +1,120 @@
+use super::votes::RecordedVote;
+use super::{CookieBehavior, DomainPrivacyScore};
+
+/// Share of the total weight trimmed from each end of the score range
+/// before averaging, so a minority at either extreme cannot drag the
+/// consensus.
+pub const TRIM_FRACTION: f64 = 0.2;
+
+/// Total weight at which the stake half of the confidence reaches 0.5;
+/// 50,000 NOX staked at the Gold tier.
+pub const CONFIDENCE_WEIGHT: f64 = 100_000.0;
+
+/// Stake-weighted consensus over one domain's votes, each staker counted
+/// once with its latest vote. `None` if no vote carries weight.
+pub(super) fn consensus<'a>(
+    domain: &str,
+    votes: impl IntoIterator<Item = &'a RecordedVote>,
+) -> Option<DomainPrivacyScore> {
+    let mut latest: Vec<&RecordedVote> = Vec::new();
+    for vote in votes.into_iter().filter(|vote| vote.weight > 0.0) {
+        match latest.iter_mut().find(|seen| seen.staker == vote.staker) {
+            Some(seen) if seen.epoch < vote.epoch => *seen = vote,
+            Some(_) => {}
+            None => latest.push(vote),
+        }
+    }
+    if latest.is_empty() {
+        return None;
+    }
+
+    let total: f64 = latest.iter().map(|vote| vote.weight).sum();
+    let mut scores: Vec<(f64, f64)> = latest.iter().map(|vote| (vote.score as f64, vote.weight)).collect();
+    let score = trimmed_mean(&mut scores, TRIM_FRACTION);
+
+    let deviation = latest.iter()
+        .map(|vote| (vote.score as f64 - score).abs() * vote.weight)
+        .sum::<f64>() / total;
+    let agreement = (1.0 - deviation / 50.0).clamp(0.0, 1.0);
+    let confidence = total / (total + CONFIDENCE_WEIGHT) * agreement;
+
+    Some(DomainPrivacyScore {
+        domain: domain.to_string(),
+        score: score.round().clamp(0.0, 100.0) as u8,
+        voter_count: latest.len() as u32,
+        trackers_detected: majority_labels(&latest, total, |vote| &vote.trackers_detected),
+        fingerprinting: majority_labels(&latest, total, |vote| &vote.fingerprinting),
+        cookie_behavior: weighted_mode(&latest),
+        updated_at: latest.iter().map(|vote| vote.received_at).max().unwrap_or(0),
+        confidence,
+    })
+}
+
+/// Weighted mean of `(value, weight)` pairs after removing `trim` of the
+/// total weight from each end; a vote straddling a cut counts in part.
+pub fn trimmed_mean(values: &mut [(f64, f64)], trim: f64) -> f64 {
+    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let total: f64 = values.iter().map(|(_, weight)| weight).sum();
+    let (low, high) = (total * trim, total * (1.0 - trim));
+
+    let mut start = 0.0;
+    let mut kept_weight = 0.0;
+    let mut kept_sum = 0.0;
+    for &(value, weight) in values.iter() {
+        let end = start + weight;
+        let kept = (end.min(high) - start.max(low)).max(0.0);
+        kept_weight += kept;
+        kept_sum += kept * value;
+        start = end;
+    }
+
+    if kept_weight > 0.0 {
+        kept_sum / kept_weight
+    } else {
+        values.iter().map(|(value, weight)| value * weight).sum::<f64>() / total
+    }
+}
+
+/// Labels reported by voters holding more than half the weight.
+fn majority_labels<'a>(
+    votes: &[&'a RecordedVote],
+    total: f64,
+    labels: impl Fn(&'a RecordedVote) -> &'a Vec<String>,
+) -> Vec<String> {
+    let mut tallies: Vec<(&str, f64)> = Vec::new();
+    for vote in votes {
+        let mut seen: Vec<&str> = Vec::new();
+        for label in labels(vote) {
+            if seen.contains(&label.as_str()) {
+                continue;
+            }
+            seen.push(label);
+            match tallies.iter_mut().find(|(tallied, _)| *tallied == label) {
+                Some((_, weight)) => *weight += vote.weight,
+                None => tallies.push((label, vote.weight)),
+            }
+        }
+    }
+
+    let mut majority: Vec<String> = tallies.into_iter()
+        .filter(|(_, weight)| *weight * 2.0 > total)
+        .map(|(label, _)| label.to_string())
+        .collect();
+    majority.sort();
+    majority
+}
+
+/// The cookie behaviour with the most weight behind it.
+fn weighted_mode(votes: &[&RecordedVote]) -> CookieBehavior {
+    let mut tallies: Vec<(&CookieBehavior, f64)> = Vec::new();
+    for vote in votes {
+        match tallies.iter_mut().find(|(behavior, _)| **behavior == vote.cookie_behavior) {
+            Some((_, weight)) => *weight += vote.weight,
+            None => tallies.push((&vote.cookie_behavior, vote.weight)),
+        }
+    }
+    tallies.into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(behavior, _)| behavior.clone())
+        .unwrap_or_default()
+}
@@ -0,0 +1,24 @@
+//! Privacy oracle: stake-weighted network consensus on how much domains
+//! track their visitors.
+//!
+//! Nodes analyse the pages they see and gossip the result as a vote. A
+//! vote carries a [`VoterBinding`], the signature of the staker whose
+//! stake weighs it, and counts once per staker, domain and epoch, enforced
+//! with a scoped nullifier. Scores are a weighted mean with the extremes
+//! trimmed, so a node without stake, or with only a minority of it, cannot
+//! move the consensus.
+
+mod analysis;
+mod consensus;
+mod service;
+mod stake;
+mod types;
+mod votes;
+
+pub use service::{PrivacyOracle, VoteOutcome};
+pub use stake::StakeLookup;
+pub use types::{CookieBehavior, DomainPrivacyScore};
+pub use votes::{normalize_domain, vote_nullifier, VoterBinding};
+
+#[cfg(test)]
+mod tests;
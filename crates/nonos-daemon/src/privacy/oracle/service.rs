@@ -0,0 +1,359 @@
+use super::consensus::consensus;
+use super::stake::StakeLookup;
+use super::votes::{
+    normalize_domain, validate_vote, vote_nullifier, RecordedVote, VoterBinding, MAX_VOTE_LABELS,
+};
+use super::DomainPrivacyScore;
+use crate::p2p::OracleVoteData;
+use nonos_types::{EthAddress, NodeId, NonosError, NonosResult};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+
+/// Past epochs whose votes still count towards consensus.
+pub const RETAINED_EPOCHS: u64 = 1;
+
+/// Stakers with stake whose weight is remembered per epoch.
+pub const MAX_TRACKED_STAKERS: usize = 4096;
+
+/// Stakers found to have no stake, remembered per epoch so their votes do
+/// not cost another lookup. The least recently seen are forgotten first.
+pub const MAX_UNSTAKED_CACHED: usize = 4096;
+
+/// Stake lookups a voting node may cause per epoch, so one node cannot
+/// spend the shared budget below on its own.
+pub const MAX_LOOKUPS_PER_VOTER: usize = 4;
+
+/// Stake lookups that may be made at once across all voters. Node IDs cost
+/// nothing, so only a shared budget bounds the chain queries fresh ones can
+/// cause; once it is spent, votes from stakers not yet weighed are refused
+/// until it refills.
+pub const MAX_LOOKUP_BURST: f64 = 256.0;
+
+/// Rate at which the shared lookup budget refills, per second.
+pub const LOOKUPS_PER_SEC: f64 = 4.0;
+
+/// Voting nodes whose lookups are counted; the least recently seen are
+/// forgotten first.
+const MAX_TRACKED_VOTERS: usize = 4096;
+
+/// Domains a staker may vote on per epoch.
+pub const MAX_VOTES_PER_STAKER: usize = 1024;
+
+/// What became of a vote.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VoteOutcome {
+    Counted { weight: f64 },
+    /// The staker already voted on this domain this epoch.
+    Duplicate,
+    /// The staker has nothing staked.
+    Unweighted,
+}
+
+struct VoteBook {
+    /// Counted votes by domain, then nullifier.
+    domains: HashMap<String, HashMap<[u8; 32], RecordedVote>>,
+    /// Voting weight per staker and epoch, for stakers with stake.
+    weights: HashMap<(EthAddress, u64), f64>,
+    /// Stakers and epochs without stake.
+    unstaked: LruMap<(EthAddress, u64), ()>,
+    /// Stake lookups caused per voting node and epoch.
+    lookups: LruMap<(NodeId, u64), usize>,
+    /// Lookups left in the shared budget, as of `lookups_refilled`.
+    lookup_tokens: f64,
+    lookups_refilled: Instant,
+    /// Votes counted per staker and epoch.
+    counts: HashMap<(EthAddress, u64), usize>,
+}
+
+impl Default for VoteBook {
+    fn default() -> Self {
+        Self {
+            domains: HashMap::new(),
+            weights: HashMap::new(),
+            unstaked: LruMap::new(MAX_UNSTAKED_CACHED),
+            lookups: LruMap::new(MAX_TRACKED_VOTERS),
+            lookup_tokens: MAX_LOOKUP_BURST,
+            lookups_refilled: Instant::now(),
+            counts: HashMap::new(),
+        }
+    }
+}
+
+impl VoteBook {
+    fn prune(&mut self, current_epoch: u64) {
+        let live = |epoch: u64| epoch + RETAINED_EPOCHS >= current_epoch;
+        for votes in self.domains.values_mut() {
+            votes.retain(|_, vote| live(vote.epoch));
+        }
+        self.domains.retain(|_, votes| !votes.is_empty());
+        self.weights.retain(|(_, epoch), _| live(*epoch));
+        self.unstaked.retain(|(_, epoch)| live(*epoch));
+        self.lookups.retain(|(_, epoch)| live(*epoch));
+        self.counts.retain(|(_, epoch), _| live(*epoch));
+    }
+
+    /// Takes a lookup from the shared budget, if one is left.
+    fn take_lookup(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.lookups_refilled).as_secs_f64();
+        self.lookup_tokens = (self.lookup_tokens + elapsed * LOOKUPS_PER_SEC).min(MAX_LOOKUP_BURST);
+        self.lookups_refilled = now;
+        if self.lookup_tokens < 1.0 {
+            return false;
+        }
+        self.lookup_tokens -= 1.0;
+        true
+    }
+}
+
+/// A map that forgets its least recently used entry to make room.
+struct LruMap<K, V> {
+    entries: HashMap<K, (V, u64)>,
+    clock: u64,
+    capacity: usize,
+}
+
+impl<K: Eq + Hash + Clone, V> LruMap<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self { entries: HashMap::new(), clock: 0, capacity }
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.clock += 1;
+        let (value, used) = self.entries.get_mut(key)?;
+        *used = self.clock;
+        Some(value)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.clock += 1;
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            if let Some(oldest) = self.entries.iter().min_by_key(|(_, (_, used))| *used).map(|(key, _)| key.clone()) {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (value, self.clock));
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&K) -> bool) {
+        self.entries.retain(|key, _| keep(key));
+    }
+}
+
+/// Scores how much domains track their visitors. A node analyses the pages
+/// it sees and gossips the result as a vote; votes from across the network
+/// are weighted by the voter's stake, counted once per staker, domain and
+/// epoch, and combined into a trimmed consensus.
+pub struct PrivacyOracle {
+    pub(super) tracker_patterns: Vec<String>,
+    pub(super) fingerprint_patterns: Vec<String>,
+    votes: Arc<RwLock<VoteBook>>,
+    stake: parking_lot::RwLock<Option<Arc<dyn StakeLookup>>>,
+    voter: parking_lot::RwLock<Option<(NodeId, VoterBinding)>>,
+}
+
+impl PrivacyOracle {
+    pub fn new() -> Self {
+        Self {
+            tracker_patterns: Self::default_tracker_patterns(),
+            fingerprint_patterns: Self::default_fingerprint_patterns(),
+            votes: Arc::new(RwLock::new(VoteBook::default())),
+            stake: parking_lot::RwLock::new(None),
+            voter: parking_lot::RwLock::new(None),
+        }
+    }
+
+    /// Sets where vote weights are looked up. Until one is set, no vote
+    /// can be counted.
+    pub fn set_stake_lookup(&self, lookup: Arc<dyn StakeLookup>) {
+        *self.stake.write() = Some(lookup);
+    }
+
+    /// Lets this node cast votes as `node_id`, with the stake `binding`
+    /// ties it to.
+    pub fn set_voter(&self, node_id: NodeId, binding: VoterBinding) -> NonosResult<()> {
+        binding.verify(&node_id)?;
+        *self.voter.write() = Some((node_id, binding));
+        Ok(())
+    }
+
+    pub fn can_vote(&self) -> bool {
+        self.voter.read().is_some()
+    }
+
+    /// Checks and counts a vote from the network.
+    pub async fn submit_vote(&self, vote: &OracleVoteData, current_epoch: u64) -> NonosResult<VoteOutcome> {
+        if vote.epoch > current_epoch || vote.epoch + RETAINED_EPOCHS < current_epoch {
+            return Err(NonosError::Config(format!(
+                "Vote for epoch {} outside current epoch {}", vote.epoch, current_epoch
+            )));
+        }
+        validate_vote(vote)?;
+        vote.binding.verify(&vote.voter)?;
+
+        let staker = vote.binding.staker;
+        let nullifier = vote_nullifier(&staker, &vote.domain, vote.epoch);
+        let key = (staker, vote.epoch);
+
+        let known_weight = {
+            let mut book = self.votes.write().await;
+            if book.domains.get(&vote.domain).is_some_and(|votes| votes.contains_key(&nullifier)) {
+                return Ok(VoteOutcome::Duplicate);
+            }
+            match book.weights.get(&key) {
+                Some(weight) => Some(*weight),
+                None => book.unstaked.get_mut(&key).map(|_| 0.0),
+            }
+        };
+        let weight = match known_weight {
+            Some(weight) => weight,
+            None => self.lookup_weight(&staker, vote.epoch, &vote.voter).await?,
+        };
+        if weight <= 0.0 {
+            return Ok(VoteOutcome::Unweighted);
+        }
+
+        let mut book = self.votes.write().await;
+        book.prune(current_epoch);
+        if book.domains.get(&vote.domain).is_some_and(|votes| votes.contains_key(&nullifier)) {
+            return Ok(VoteOutcome::Duplicate);
+        }
+        let count = book.counts.entry(key).or_default();
+        if *count >= MAX_VOTES_PER_STAKER {
+            return Err(NonosError::Config(format!("{} has used its votes for epoch {}", staker, vote.epoch)));
+        }
+        *count += 1;
+
+        book.domains.entry(vote.domain.clone()).or_default().insert(nullifier, RecordedVote {
+            staker,
+            epoch: vote.epoch,
+            weight,
+            score: vote.score,
+            trackers_detected: vote.trackers_detected.clone(),
+            fingerprinting: vote.fingerprinting.clone(),
+            cookie_behavior: vote.cookie_behavior.clone(),
+            received_at: chrono::Utc::now().timestamp() as u64,
+        });
+        Ok(VoteOutcome::Counted { weight })
+    }
+
+    /// Looks up the weight of `staker`, whose binding `voter` presented,
+    /// charging the lookup to `voter` and the shared budget.
+    async fn lookup_weight(&self, staker: &EthAddress, epoch: u64, voter: &NodeId) -> NonosResult<f64> {
+        let lookup = self.stake.read().clone()
+            .ok_or_else(|| NonosError::Config("No stake source for oracle votes".into()))?;
+        {
+            let mut book = self.votes.write().await;
+            if book.weights.len() >= MAX_TRACKED_STAKERS {
+                return Err(NonosError::Config("Too many stakers voting this epoch".into()));
+            }
+            let voter_key = (*voter, epoch);
+            let lookups = book.lookups.get_mut(&voter_key).map_or(0, |count| *count);
+            if lookups >= MAX_LOOKUPS_PER_VOTER {
+                return Err(NonosError::Config(format!("{} has bound too many stakers in epoch {}", voter, epoch)));
+            }
+            if !book.take_lookup(Instant::now()) {
+                return Err(NonosError::Config("Stake lookup budget spent, try again later".into()));
+            }
+            book.lookups.insert(voter_key, lookups + 1);
+        }
+
+        let weight = lookup.voting_weight(staker).await?;
+        let mut book = self.votes.write().await;
+        if weight > 0.0 {
+            book.weights.insert((*staker, epoch), weight);
+        } else {
+            book.unstaked.insert((*staker, epoch), ());
+        }
+        Ok(weight)
+    }
+
+    /// Analyses `domain`, counts the result as this node's vote and returns
+    /// the vote for gossiping.
+    pub async fn cast_vote(
+        &self,
+        domain: &str,
+        page_content: Option<&str>,
+        epoch: u64,
+    ) -> NonosResult<OracleVoteData> {
+        let (voter, binding) = self.voter.read().clone()
+            .ok_or_else(|| NonosError::Config("No staker binding configured for oracle votes".into()))?;
+        let domain = normalize_domain(domain)
+            .ok_or_else(|| NonosError::Config(format!("Invalid domain: {:?}", domain)))?;
+
+        let analysis = self.analyze_domain(&domain, page_content).await;
+        let mut trackers_detected = analysis.trackers_detected;
+        let mut fingerprinting = analysis.fingerprinting;
+        trackers_detected.truncate(MAX_VOTE_LABELS);
+        fingerprinting.truncate(MAX_VOTE_LABELS);
+
+        let vote = OracleVoteData {
+            voter,
+            binding,
+            epoch,
+            domain,
+            score: analysis.score,
+            trackers_detected,
+            fingerprinting,
+            cookie_behavior: analysis.cookie_behavior,
+        };
+
+        // Gossip is not delivered back to its publisher, so our own vote is
+        // counted directly.
+        match self.submit_vote(&vote, epoch).await? {
+            VoteOutcome::Counted { .. } => Ok(vote),
+            VoteOutcome::Duplicate => Err(NonosError::Config(format!(
+                "Already voted on {} in epoch {}", vote.domain, epoch
+            ))),
+            VoteOutcome::Unweighted => Err(NonosError::Config(format!(
+                "{} has no stake to vote with", vote.binding.staker
+            ))),
+        }
+    }
+
+    /// The network's consensus on `domain`, if any staked node has voted
+    /// on it.
+    pub async fn get_score(&self, domain: &str) -> Option<DomainPrivacyScore> {
+        let domain = normalize_domain(domain)?;
+        let book = self.votes.read().await;
+        consensus(&domain, book.domains.get(&domain)?.values())
+    }
+
+    pub async fn get_all_scores(&self) -> Vec<DomainPrivacyScore> {
+        let book = self.votes.read().await;
+        book.domains.iter()
+            .filter_map(|(domain, votes)| consensus(domain, votes.values()))
+            .collect()
+    }
+
+    /// Drops votes and weights from epochs that no longer count.
+    pub async fn prune(&self, current_epoch: u64) {
+        self.votes.write().await.prune(current_epoch);
+    }
+
+    pub async fn score_count(&self) -> usize {
+        self.votes.read().await.domains.len()
+    }
+
+    pub async fn clear_scores(&self) {
+        let mut book = self.votes.write().await;
+        book.domains.clear();
+        book.counts.clear();
+    }
+
+    pub fn tracker_pattern_count(&self) -> usize {
+        self.tracker_patterns.len()
+    }
+
+    pub fn fingerprint_pattern_count(&self) -> usize {
+        self.fingerprint_patterns.len()
+    }
+}
+
+impl Default for PrivacyOracle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
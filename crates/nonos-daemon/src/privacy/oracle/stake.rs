@@ -0,0 +1,26 @@
+use crate::contracts::ContractClient;
+use async_trait::async_trait;
+use nonos_types::{EthAddress, NonosResult};
+
+/// Where oracle votes get their weight.
+#[async_trait]
+pub trait StakeLookup: Send + Sync {
+    /// Voting weight of `staker`; zero if it has nothing staked.
+    async fn voting_weight(&self, staker: &EthAddress) -> NonosResult<f64>;
+}
+
+/// Weight is the stake in NOX times the staker's tier multiplier. Tier
+/// multipliers grow with stake, so splitting a stake across addresses
+/// never adds weight.
+#[async_trait]
+impl StakeLookup for ContractClient {
+    async fn voting_weight(&self, staker: &EthAddress) -> NonosResult<f64> {
+        let stake = self.get_stake(staker).await?;
+        if stake.raw == 0 {
+            return Ok(0.0);
+        }
+        let tier = self.get_tier(staker).await?;
+        let nox = stake.raw as f64 / 10f64.powi(stake.decimals as i32);
+        Ok(nox * tier.multiplier())
+    }
+}
@@ -0,0 +1,302 @@
+use super::consensus::{trimmed_mean, TRIM_FRACTION};
+use super::service::{MAX_LOOKUPS_PER_VOTER, MAX_LOOKUP_BURST, RETAINED_EPOCHS};
+use super::*;
+use crate::p2p::OracleVoteData;
+use async_trait::async_trait;
+use nonos_crypto::{derive_eth_address_from_private, generate_private_key};
+use nonos_types::{EthAddress, NodeId, NonosResult, Secp256k1PrivateKey};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const EPOCH: u64 = 100;
+
+struct FixedStakes(HashMap<EthAddress, f64>);
+
+#[async_trait]
+impl StakeLookup for FixedStakes {
+    async fn voting_weight(&self, staker: &EthAddress) -> NonosResult<f64> {
+        Ok(self.0.get(staker).copied().unwrap_or(0.0))
+    }
+}
+
+struct Staker {
+    key: Secp256k1PrivateKey,
+    address: EthAddress,
+}
+
+fn staker() -> Staker {
+    let key = generate_private_key();
+    let address = derive_eth_address_from_private(&key).unwrap();
+    Staker { key, address }
+}
+
+fn oracle(stakes: &[(&Staker, f64)]) -> PrivacyOracle {
+    let oracle = PrivacyOracle::new();
+    let stakes = stakes.iter().map(|(staker, weight)| (staker.address, *weight)).collect();
+    oracle.set_stake_lookup(Arc::new(FixedStakes(stakes)));
+    oracle
+}
+
+fn vote(staker: &Staker, domain: &str, score: u8, epoch: u64) -> OracleVoteData {
+    let voter = NodeId::from_bytes(rand::random());
+    OracleVoteData {
+        voter,
+        binding: VoterBinding::sign(&staker.key, &voter).unwrap(),
+        epoch,
+        domain: domain.to_string(),
+        score,
+        trackers_detected: Vec::new(),
+        fingerprinting: Vec::new(),
+        cookie_behavior: CookieBehavior::None,
+    }
+}
+
+#[tokio::test]
+async fn test_privacy_oracle_tracker_detection() {
+    let oracle = PrivacyOracle::new();
+    let score = oracle.analyze_domain("google-analytics.com", None).await;
+    assert!(score.score < 100);
+    assert!(!score.trackers_detected.is_empty());
+}
+
+#[tokio::test]
+async fn test_privacy_oracle_fingerprint_detection() {
+    let oracle = PrivacyOracle::new();
+    let content = "function test() { canvas.toDataURL(); }";
+    let score = oracle.analyze_domain("example.com", Some(content)).await;
+    assert!(!score.fingerprinting.is_empty());
+}
+
+#[tokio::test]
+async fn test_cookie_behavior_detection() {
+    let oracle = PrivacyOracle::new();
+    let content = "SameSite=None; Secure";
+    let score = oracle.analyze_domain("example.com", Some(content)).await;
+    assert_eq!(score.cookie_behavior, CookieBehavior::CrossSite);
+}
+
+#[test]
+fn test_voter_binding() {
+    let staker = staker();
+    let node = NodeId::from_bytes(rand::random());
+    let binding = VoterBinding::sign(&staker.key, &node).unwrap();
+    assert_eq!(binding.staker, staker.address);
+    assert!(binding.verify(&node).is_ok());
+    assert!(binding.verify(&NodeId::from_bytes(rand::random())).is_err());
+
+    let mut claimed = binding.clone();
+    claimed.staker = EthAddress::from_bytes([7; 20]);
+    assert!(claimed.verify(&node).is_err());
+}
+
+#[test]
+fn test_vote_nullifier_scope() {
+    let a = staker();
+    let b = staker();
+    let n = vote_nullifier(&a.address, "example.com", EPOCH);
+    assert_eq!(n, vote_nullifier(&a.address, "example.com", EPOCH));
+    assert_ne!(n, vote_nullifier(&b.address, "example.com", EPOCH));
+    assert_ne!(n, vote_nullifier(&a.address, "example.org", EPOCH));
+    assert_ne!(n, vote_nullifier(&a.address, "example.com", EPOCH + 1));
+}
+
+#[tokio::test]
+async fn test_one_vote_per_staker_domain_epoch() {
+    let staker = staker();
+    let oracle = oracle(&[(&staker, 1_000.0)]);
+
+    let first = vote(&staker, "example.com", 90, EPOCH);
+    assert_eq!(oracle.submit_vote(&first, EPOCH).await.unwrap(), VoteOutcome::Counted { weight: 1_000.0 });
+    // Another node bound to the same stake does not get a second vote.
+    let second = vote(&staker, "example.com", 10, EPOCH);
+    assert_eq!(oracle.submit_vote(&second, EPOCH).await.unwrap(), VoteOutcome::Duplicate);
+    assert_eq!(oracle.get_score("example.com").await.unwrap().score, 90);
+
+    // The next epoch's vote replaces it rather than adding to it.
+    let next = vote(&staker, "example.com", 50, EPOCH + 1);
+    assert!(matches!(oracle.submit_vote(&next, EPOCH + 1).await.unwrap(), VoteOutcome::Counted { .. }));
+    let score = oracle.get_score("example.com").await.unwrap();
+    assert_eq!(score.score, 50);
+    assert_eq!(score.voter_count, 1);
+}
+
+#[tokio::test]
+async fn test_unstaked_votes_do_not_count() {
+    let staked = staker();
+    let oracle = oracle(&[(&staked, 10.0)]);
+
+    for _ in 0..50 {
+        let sybil = staker();
+        let outcome = oracle.submit_vote(&vote(&sybil, "example.com", 0, EPOCH), EPOCH).await.unwrap();
+        assert_eq!(outcome, VoteOutcome::Unweighted);
+    }
+    assert!(oracle.get_score("example.com").await.is_none());
+
+    oracle.submit_vote(&vote(&staked, "example.com", 70, EPOCH), EPOCH).await.unwrap();
+    assert_eq!(oracle.get_score("example.com").await.unwrap().score, 70);
+}
+
+#[tokio::test]
+async fn test_unstaked_bindings_do_not_crowd_out_stakers() {
+    let staked = staker();
+    let oracle = oracle(&[(&staked, 10.0)]);
+    oracle.submit_vote(&vote(&staked, "example.com", 70, EPOCH), EPOCH).await.unwrap();
+
+    // Fresh stakers, each bound by a fresh node, only cost lookups until
+    // the shared budget is spent...
+    let mut looked_up = 0;
+    for i in 0..2 * MAX_LOOKUP_BURST as usize {
+        match oracle.submit_vote(&vote(&staker(), &format!("site{}.example", i), 0, EPOCH), EPOCH).await {
+            Ok(outcome) => assert_eq!(outcome, VoteOutcome::Unweighted),
+            Err(_) => break,
+        }
+        looked_up += 1;
+    }
+    assert!(looked_up >= MAX_LOOKUP_BURST as usize);
+    assert!(looked_up < 2 * MAX_LOOKUP_BURST as usize);
+
+    // ...and stakers already weighed keep voting.
+    let outcome = oracle.submit_vote(&vote(&staked, "example.org", 70, EPOCH), EPOCH).await.unwrap();
+    assert_eq!(outcome, VoteOutcome::Counted { weight: 10.0 });
+}
+
+#[tokio::test]
+async fn test_stake_lookups_limited_per_voter() {
+    let staked = staker();
+    let oracle = oracle(&[(&staked, 10.0)]);
+    let voter = NodeId::from_bytes(rand::random());
+    let bound = |staker: &Staker, domain: &str| OracleVoteData {
+        binding: VoterBinding::sign(&staker.key, &voter).unwrap(),
+        voter,
+        ..vote(staker, domain, 50, EPOCH)
+    };
+
+    let sybils: Vec<Staker> = (0..MAX_LOOKUPS_PER_VOTER).map(|_| staker()).collect();
+    for sybil in &sybils {
+        let outcome = oracle.submit_vote(&bound(sybil, "example.com"), EPOCH).await.unwrap();
+        assert_eq!(outcome, VoteOutcome::Unweighted);
+    }
+    // Stakers already looked up cost nothing more.
+    let outcome = oracle.submit_vote(&bound(&sybils[0], "example.org"), EPOCH).await.unwrap();
+    assert_eq!(outcome, VoteOutcome::Unweighted);
+    assert!(oracle.submit_vote(&bound(&staker(), "example.com"), EPOCH).await.is_err());
+
+    // Other voters are unaffected.
+    let outcome = oracle.submit_vote(&vote(&staked, "example.com", 70, EPOCH), EPOCH).await.unwrap();
+    assert_eq!(outcome, VoteOutcome::Counted { weight: 10.0 });
+}
+
+#[tokio::test]
+async fn test_invalid_votes_rejected() {
+    let staker = staker();
+    let oracle = oracle(&[(&staker, 10.0)]);
+
+    assert!(oracle.submit_vote(&vote(&staker, "example.com", 50, EPOCH + 1), EPOCH).await.is_err());
+    assert!(oracle.submit_vote(&vote(&staker, "example.com", 50, EPOCH - 2), EPOCH).await.is_err());
+    assert!(oracle.submit_vote(&vote(&staker, "Example.com", 50, EPOCH), EPOCH).await.is_err());
+    assert!(oracle.submit_vote(&vote(&staker, "example.com", 101, EPOCH), EPOCH).await.is_err());
+
+    let mut forged = vote(&staker, "example.com", 50, EPOCH);
+    forged.voter = NodeId::from_bytes(rand::random());
+    assert!(oracle.submit_vote(&forged, EPOCH).await.is_err());
+    assert_eq!(oracle.score_count().await, 0);
+
+    // Without a stake source nothing can be weighed.
+    let unweighed = PrivacyOracle::new();
+    assert!(unweighed.submit_vote(&vote(&staker, "example.com", 50, EPOCH), EPOCH).await.is_err());
+}
+
+#[test]
+fn test_trimmed_mean() {
+    let mut values = [(80.0, 10.0), (80.0, 10.0), (80.0, 10.0), (80.0, 10.0), (0.0, 10.0)];
+    assert_eq!(trimmed_mean(&mut values, 0.2), 80.0);
+
+    let mut values = [(10.0, 1.0), (20.0, 1.0), (30.0, 1.0), (40.0, 1.0)];
+    assert!((trimmed_mean(&mut values, 0.25) - 25.0).abs() < 1e-9);
+
+    let mut single = [(42.0, 3.0)];
+    assert_eq!(trimmed_mean(&mut single, TRIM_FRACTION), 42.0);
+}
+
+#[tokio::test]
+async fn test_consensus_trims_outliers() {
+    let honest: Vec<Staker> = (0..4).map(|_| staker()).collect();
+    let outlier = staker();
+    let mut stakes: Vec<(&Staker, f64)> = honest.iter().map(|s| (s, 10_000.0)).collect();
+    stakes.push((&outlier, 10_000.0));
+    let oracle = oracle(&stakes);
+
+    for staker in &honest {
+        let mut v = vote(staker, "tracker.example", 20, EPOCH);
+        v.trackers_detected = vec!["doubleclick.net".into()];
+        v.cookie_behavior = CookieBehavior::CrossSite;
+        oracle.submit_vote(&v, EPOCH).await.unwrap();
+    }
+    let mut v = vote(&outlier, "tracker.example", 100, EPOCH);
+    v.trackers_detected = vec!["made-up.example".into()];
+    oracle.submit_vote(&v, EPOCH).await.unwrap();
+
+    let score = oracle.get_score("tracker.example").await.unwrap();
+    assert_eq!(score.score, 20);
+    assert_eq!(score.voter_count, 5);
+    assert_eq!(score.trackers_detected, vec!["doubleclick.net".to_string()]);
+    assert_eq!(score.cookie_behavior, CookieBehavior::CrossSite);
+    assert!(score.confidence > 0.0 && score.confidence < 1.0);
+}
+
+#[tokio::test]
+async fn test_confidence_grows_with_stake_and_agreement() {
+    let small = staker();
+    let large: Vec<Staker> = (0..3).map(|_| staker()).collect();
+    let mut stakes = vec![(&small, 1_000.0)];
+    stakes.extend(large.iter().map(|s| (s, 200_000.0)));
+    let oracle = oracle(&stakes);
+
+    oracle.submit_vote(&vote(&small, "a.example", 60, EPOCH), EPOCH).await.unwrap();
+    let thin = oracle.get_score("a.example").await.unwrap().confidence;
+
+    for staker in &large {
+        oracle.submit_vote(&vote(staker, "a.example", 60, EPOCH), EPOCH).await.unwrap();
+    }
+    let broad = oracle.get_score("a.example").await.unwrap().confidence;
+    assert!(broad > thin);
+    assert!(broad > 0.8);
+
+    oracle.submit_vote(&vote(&large[0], "b.example", 0, EPOCH), EPOCH).await.unwrap();
+    oracle.submit_vote(&vote(&large[1], "b.example", 100, EPOCH), EPOCH).await.unwrap();
+    let split = oracle.get_score("b.example").await.unwrap().confidence;
+    assert!(split < broad);
+}
+
+#[tokio::test]
+async fn test_cast_vote() {
+    let staker = staker();
+    let node = NodeId::from_bytes(rand::random());
+    let oracle = oracle(&[(&staker, 500.0)]);
+    assert!(oracle.cast_vote("example.com", None, EPOCH).await.is_err());
+
+    assert!(oracle.set_voter(NodeId::from_bytes(rand::random()), VoterBinding::sign(&staker.key, &node).unwrap()).is_err());
+    oracle.set_voter(node, VoterBinding::sign(&staker.key, &node).unwrap()).unwrap();
+
+    let vote = oracle.cast_vote("WWW.Google-Analytics.com.", None, EPOCH).await.unwrap();
+    assert_eq!(vote.voter, node);
+    assert_eq!(vote.domain, "www.google-analytics.com");
+    assert!(oracle.get_score("www.google-analytics.com").await.unwrap().score < 100);
+    assert!(oracle.cast_vote("www.google-analytics.com", None, EPOCH).await.is_err());
+
+    // A node receiving the gossiped vote counts it the same way.
+    let other = self::oracle(&[(&staker, 500.0)]);
+    assert!(matches!(other.submit_vote(&vote, EPOCH).await.unwrap(), VoteOutcome::Counted { .. }));
+}
+
+#[tokio::test]
+async fn test_prune_old_epochs() {
+    let staker = staker();
+    let oracle = oracle(&[(&staker, 10.0)]);
+    oracle.submit_vote(&vote(&staker, "example.com", 40, EPOCH), EPOCH).await.unwrap();
+
+    oracle.prune(EPOCH + RETAINED_EPOCHS).await;
+    assert!(oracle.get_score("example.com").await.is_some());
+    oracle.prune(EPOCH + RETAINED_EPOCHS + 1).await;
+    assert!(oracle.get_score("example.com").await.is_none());
+}
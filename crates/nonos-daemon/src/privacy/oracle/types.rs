@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DomainPrivacyScore {
+    pub domain: String,
+    pub score: u8,
+    pub voter_count: u32,
+    pub trackers_detected: Vec<String>,
+    pub fingerprinting: Vec<String>,
+    pub cookie_behavior: CookieBehavior,
+    pub updated_at: u64,
+    /// How far the score can be trusted, from 0 to 1: 0 for a local
+    /// analysis, rising with the stake behind a network consensus and how
+    /// closely its voters agree.
+    #[serde(default)]
+    pub confidence: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CookieBehavior {
+    #[default]
+    None,
+    SessionOnly,
+    Persistent,
+    CrossSite,
+    Supercookies,
+}
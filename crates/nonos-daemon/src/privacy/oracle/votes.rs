@@ -0,0 +1,118 @@
+use super::CookieBehavior;
+use crate::p2p::OracleVoteData;
+use nonos_crypto::{
+    blake3_hash, compute_scoped_nullifier, personal_message_hash, sign_personal_message, verify_signature,
+};
+use nonos_types::{
+    EcdsaSignature, EthAddress, NodeId, NonosError, NonosResult, Secp256k1PrivateKey,
+};
+use serde::{Deserialize, Serialize};
+
+/// Trackers or fingerprinting techniques a single vote may list.
+pub const MAX_VOTE_LABELS: usize = 32;
+const MAX_LABEL_LEN: usize = 128;
+const MAX_DOMAIN_LEN: usize = 253;
+
+const VOTER_DOMAIN: &[u8] = b"nonos-oracle-voter";
+const SCOPE_DOMAIN: &[u8] = b"nonos-oracle-vote-scope";
+
+/// A staker's authorisation for a node to vote with its stake: a
+/// personal-sign signature over the node ID, so it can be produced by any
+/// Ethereum wallet.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VoterBinding {
+    pub staker: EthAddress,
+    /// `r || s || v`.
+    pub signature: Vec<u8>,
+}
+
+impl VoterBinding {
+    pub fn sign(staker_key: &Secp256k1PrivateKey, voter: &NodeId) -> NonosResult<Self> {
+        let staker = nonos_crypto::derive_eth_address_from_private(staker_key)?;
+        let signature = sign_personal_message(staker_key, binding_message(voter).as_bytes())?;
+
+        let mut bytes = Vec::with_capacity(65);
+        bytes.extend_from_slice(&signature.r);
+        bytes.extend_from_slice(&signature.s);
+        bytes.push(signature.v);
+        Ok(Self { staker, signature: bytes })
+    }
+
+    /// Checks that the staker signed for `voter`.
+    pub fn verify(&self, voter: &NodeId) -> NonosResult<()> {
+        if self.signature.len() != 65 {
+            return Err(NonosError::InvalidSignature("Invalid voter binding length".into()));
+        }
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&self.signature[..32]);
+        s.copy_from_slice(&self.signature[32..64]);
+        let signature = EcdsaSignature::new(r, s, self.signature[64]);
+
+        let hash = personal_message_hash(binding_message(voter).as_bytes());
+        if !verify_signature(&signature, &hash, &self.staker)? {
+            return Err(NonosError::InvalidSignature(format!(
+                "Voter binding for {} not signed by {}", voter, self.staker
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn binding_message(voter: &NodeId) -> String {
+    format!("NONOS privacy oracle voter {}", hex::encode(voter.0))
+}
+
+/// The nullifier of `staker`'s vote on `domain` in `epoch`.
+///
+/// Votes are weighted by stake, so they are attributable by design and the
+/// staker address itself stands in for the identity secret: every node can
+/// recompute the nullifier, and every node a staker runs shares it, which
+/// is what limits the staker to one vote per domain and epoch.
+pub fn vote_nullifier(staker: &EthAddress, domain: &str, epoch: u64) -> [u8; 32] {
+    let mut secret = [0u8; 32];
+    secret[12..].copy_from_slice(&staker.0);
+    let commitment = blake3_hash(&[VOTER_DOMAIN, &staker.0[..]].concat()).0;
+    let scope = blake3_hash(&[SCOPE_DOMAIN, &epoch.to_le_bytes(), domain.as_bytes()].concat()).0;
+    compute_scoped_nullifier(&secret, &commitment, &scope)
+}
+
+/// `domain` in the form votes must carry: lowercase with no trailing dot.
+pub fn normalize_domain(domain: &str) -> Option<String> {
+    let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+    let valid = !domain.is_empty()
+        && domain.len() <= MAX_DOMAIN_LEN
+        && domain.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'.')
+        && !domain.split('.').any(str::is_empty);
+    valid.then_some(domain)
+}
+
+/// Checks a vote's contents, but not its binding or weight.
+pub(super) fn validate_vote(vote: &OracleVoteData) -> NonosResult<()> {
+    if normalize_domain(&vote.domain).as_deref() != Some(vote.domain.as_str()) {
+        return Err(NonosError::Config(format!("Vote for non-canonical domain {:?}", vote.domain)));
+    }
+    if vote.score > 100 {
+        return Err(NonosError::Config(format!("Vote score {} out of range", vote.score)));
+    }
+    let labels_ok = |labels: &[String]| {
+        labels.len() <= MAX_VOTE_LABELS && labels.iter().all(|label| label.len() <= MAX_LABEL_LEN)
+    };
+    if !labels_ok(&vote.trackers_detected) || !labels_ok(&vote.fingerprinting) {
+        return Err(NonosError::Config("Vote lists too many or too long labels".into()));
+    }
+    Ok(())
+}
+
+/// What a counted vote contributes to its domain's consensus.
+#[derive(Clone, Debug)]
+pub(super) struct RecordedVote {
+    pub staker: EthAddress,
+    pub epoch: u64,
+    pub weight: f64,
+    pub score: u8,
+    pub trackers_detected: Vec<String>,
+    pub fingerprinting: Vec<String>,
+    pub cookie_behavior: CookieBehavior,
+    pub received_at: u64,
+}
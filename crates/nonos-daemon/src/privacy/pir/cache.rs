@@ -1,3 +1,4 @@
+use super::xor::{PirDatabase, PirParams};
 use nonos_crypto::{blake3_hash, random_bytes};
 use nonos_types::{NonosError, NonosResult};
 use serde::{Deserialize, Serialize};
@@ -180,6 +181,21 @@ impl PrivateContentRetrieval {
         }
     }
 
+    /// Snapshot of the unexpired content as a PIR database, so peers can
+    /// fetch it without revealing which item they want. Nodes caching the
+    /// same content with the same parameters build identical databases.
+    pub async fn pir_database(&self, params: PirParams) -> NonosResult<PirDatabase> {
+        let cache = self.cache.read().await;
+        let mut blobs = Vec::with_capacity(cache.len());
+        for cached in cache.values().filter(|cached| !cached.is_expired()) {
+            let item_key = self.derive_item_key(&cached.commitment);
+            blobs.push(decrypt_content(&cached.encrypted_data, &item_key)?);
+        }
+        drop(cache);
+
+        PirDatabase::build(params, blobs)
+    }
+
     pub async fn list_commitments(&self) -> Vec<[u8; 32]> {
         self.cache.read().await.keys().copied().collect()
     }
@@ -406,6 +422,24 @@ mod tests {
         assert_eq!(stats.max_size, 100);
     }
 
+    #[tokio::test]
+    async fn test_pir_database_snapshot() {
+        let cache = PrivateContentRetrieval::new(100);
+        let commitment = cache.store(b"fetch me privately", 3600).await.unwrap();
+        cache.store(b"expired", 0).await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+
+        let params = PirParams { buckets: 8, slots_per_bucket: 2, max_item_size: 64 };
+        let database = cache.pir_database(params).await.unwrap();
+        assert_eq!(database.item_count(), 1);
+
+        let (retrieval, [left, right]) = super::super::PirRetrieval::new(&database.info(), commitment).unwrap();
+        let content = retrieval
+            .reconstruct(&database.answer(&left).unwrap(), &database.answer(&right).unwrap())
+            .unwrap();
+        assert_eq!(content.as_deref(), Some(b"fetch me privately".as_slice()));
+    }
+
     #[tokio::test]
     async fn test_deterministic_commitment() {
         let cache = PrivateContentRetrieval::new(100);
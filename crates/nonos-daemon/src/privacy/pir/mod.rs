@@ -0,0 +1,8 @@
+mod cache;
+mod xor;
+
+pub use cache::{PrivateContentRetrieval, CachedContent, ContentMetadata, CacheStats};
+pub use xor::{
+    PirDatabase, PirDatabaseInfo, PirParams, PirQuery, PirRetrieval, DEFAULT_PIR_PARAMS,
+    MAX_PIR_DATABASE_BYTES,
+};
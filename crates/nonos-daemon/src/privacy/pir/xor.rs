@@ -0,0 +1,361 @@
+//! Two-server XOR private information retrieval.
+//!
+//! Cached blobs are laid out in a database of fixed-size buckets, each
+//! holding a few fixed-size slots; a blob's bucket follows from its
+//! commitment alone. To fetch bucket `i` the client sends one server a
+//! uniformly random bucket selection and the other the same selection with
+//! bit `i` flipped. Each server XORs the selected buckets together, and the
+//! XOR of the two answers is bucket `i`. Either selection on its own is
+//! uniformly random, so a server learns nothing about which blob was
+//! wanted, provided the two servers do not collude.
+//!
+//! Both servers must hold byte-identical databases. The layout is a pure
+//! function of the parameters and the blob set, and [`PirDatabase::digest`]
+//! identifies it, so a client only pairs servers that report the same
+//! digest.
+
+use nonos_crypto::blake3_hash;
+use nonos_types::{NonosError, NonosResult};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const COMMITMENT_SIZE: usize = 32;
+const LENGTH_SIZE: usize = 4;
+const SLOT_HEADER_SIZE: usize = COMMITMENT_SIZE + LENGTH_SIZE;
+
+/// Upper bound on a database, which every query scans in full.
+pub const MAX_PIR_DATABASE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Shape of the databases nodes serve their cached content in, so that
+/// nodes caching the same content build identical databases: 16 MiB
+/// scanned per query.
+pub const DEFAULT_PIR_PARAMS: PirParams = PirParams {
+    buckets: 512,
+    slots_per_bucket: 4,
+    max_item_size: 8 * 1024,
+};
+
+/// Shape of a PIR database. Servers and clients must agree on it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PirParams {
+    pub buckets: u32,
+    pub slots_per_bucket: u16,
+    /// Largest blob that fits in a slot.
+    pub max_item_size: u32,
+}
+
+impl PirParams {
+    pub fn validate(&self) -> NonosResult<()> {
+        if self.buckets == 0 || self.slots_per_bucket == 0 || self.max_item_size == 0 {
+            return Err(NonosError::Config("PIR parameters must be non-zero".into()));
+        }
+        if self.database_size().map_or(true, |size| size > MAX_PIR_DATABASE_BYTES) {
+            return Err(NonosError::Config(format!(
+                "PIR database exceeds {} bytes", MAX_PIR_DATABASE_BYTES
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn slot_size(&self) -> usize {
+        SLOT_HEADER_SIZE + self.max_item_size as usize
+    }
+
+    pub fn bucket_size(&self) -> usize {
+        self.slot_size() * self.slots_per_bucket as usize
+    }
+
+    /// Length in bytes of a bucket selection.
+    pub fn selection_size(&self) -> usize {
+        (self.buckets as usize).div_ceil(8)
+    }
+
+    /// The bucket a blob with this commitment is stored in.
+    pub fn bucket_of(&self, commitment: &[u8; 32]) -> usize {
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&commitment[..8]);
+        (u64::from_le_bytes(prefix) % self.buckets as u64) as usize
+    }
+
+    fn database_size(&self) -> Option<usize> {
+        self.bucket_size().checked_mul(self.buckets as usize)
+    }
+}
+
+/// What a server tells clients about the database it serves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PirDatabaseInfo {
+    pub params: PirParams,
+    pub digest: [u8; 32],
+    pub items: u32,
+}
+
+/// One server's share of a retrieval.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PirQuery {
+    /// Digest of the database the selection was built for.
+    pub digest: [u8; 32],
+    /// One bit per bucket, least significant bit first.
+    pub selection: Vec<u8>,
+}
+
+/// A bucketed database of blobs that answers [`PirQuery`]s.
+pub struct PirDatabase {
+    info: PirDatabaseInfo,
+    data: Vec<u8>,
+}
+
+impl PirDatabase {
+    /// Lays out `blobs`, keyed by commitment. Blobs larger than a slot, or
+    /// that arrive at a full bucket in commitment order, are left out.
+    pub fn build(params: PirParams, blobs: impl IntoIterator<Item = Vec<u8>>) -> NonosResult<Self> {
+        params.validate()?;
+
+        let blobs: BTreeMap<[u8; 32], Vec<u8>> = blobs.into_iter()
+            .filter(|blob| blob.len() <= params.max_item_size as usize)
+            .map(|blob| (blake3_hash(&blob).0, blob))
+            .collect();
+
+        let bucket_size = params.bucket_size();
+        let slot_size = params.slot_size();
+        let mut data = vec![0u8; bucket_size * params.buckets as usize];
+        let mut fill = vec![0usize; params.buckets as usize];
+        let mut digest = blake3::Hasher::new();
+        digest.update(b"nonos-pir-database-v1");
+        digest.update(&params.buckets.to_le_bytes());
+        digest.update(&params.slots_per_bucket.to_le_bytes());
+        digest.update(&params.max_item_size.to_le_bytes());
+
+        let mut items = 0u32;
+        for (commitment, blob) in &blobs {
+            let bucket = params.bucket_of(commitment);
+            if fill[bucket] == params.slots_per_bucket as usize {
+                continue;
+            }
+
+            let offset = bucket * bucket_size + fill[bucket] * slot_size;
+            let slot = &mut data[offset..offset + slot_size];
+            slot[..COMMITMENT_SIZE].copy_from_slice(commitment);
+            slot[COMMITMENT_SIZE..SLOT_HEADER_SIZE].copy_from_slice(&(blob.len() as u32).to_le_bytes());
+            slot[SLOT_HEADER_SIZE..SLOT_HEADER_SIZE + blob.len()].copy_from_slice(blob);
+
+            fill[bucket] += 1;
+            items += 1;
+            digest.update(commitment);
+        }
+
+        Ok(Self {
+            info: PirDatabaseInfo { params, digest: *digest.finalize().as_bytes(), items },
+            data,
+        })
+    }
+
+    pub fn info(&self) -> PirDatabaseInfo {
+        self.info
+    }
+
+    pub fn params(&self) -> PirParams {
+        self.info.params
+    }
+
+    /// Identifies the layout; equal digests mean identical databases.
+    pub fn digest(&self) -> [u8; 32] {
+        self.info.digest
+    }
+
+    pub fn item_count(&self) -> usize {
+        self.info.items as usize
+    }
+
+    /// XOR of the buckets selected by `query`. Touches every bucket, so the
+    /// work done does not depend on the selection.
+    pub fn answer(&self, query: &PirQuery) -> NonosResult<Vec<u8>> {
+        let params = self.info.params;
+        if query.digest != self.info.digest {
+            return Err(NonosError::Storage("PIR query is for a different database".into()));
+        }
+        if query.selection.len() != params.selection_size() {
+            return Err(NonosError::Storage("PIR selection has the wrong length".into()));
+        }
+
+        let bucket_size = params.bucket_size();
+        let mut answer = vec![0u8; bucket_size];
+        for (bucket, chunk) in self.data.chunks_exact(bucket_size).enumerate() {
+            let mask = 0u8.wrapping_sub((query.selection[bucket / 8] >> (bucket % 8)) & 1);
+            for (out, byte) in answer.iter_mut().zip(chunk) {
+                *out ^= byte & mask;
+            }
+        }
+        Ok(answer)
+    }
+}
+
+/// Client side of one retrieval: produces the two queries and recovers the
+/// blob from the two answers.
+pub struct PirRetrieval {
+    params: PirParams,
+    commitment: [u8; 32],
+}
+
+impl PirRetrieval {
+    /// Queries for the blob with `commitment` from a database described by
+    /// `info`, one per server.
+    pub fn new(info: &PirDatabaseInfo, commitment: [u8; 32]) -> NonosResult<(Self, [PirQuery; 2])> {
+        let params = info.params;
+        params.validate()?;
+
+        let mut left = vec![0u8; params.selection_size()];
+        rand::thread_rng().fill_bytes(&mut left);
+        let spare_bits = left.len() * 8 - params.buckets as usize;
+        if spare_bits > 0 {
+            if let Some(last) = left.last_mut() {
+                *last &= 0xff >> spare_bits;
+            }
+        }
+
+        let bucket = params.bucket_of(&commitment);
+        let mut right = left.clone();
+        right[bucket / 8] ^= 1 << (bucket % 8);
+
+        let queries = [
+            PirQuery { digest: info.digest, selection: left },
+            PirQuery { digest: info.digest, selection: right },
+        ];
+        Ok((Self { params, commitment }, queries))
+    }
+
+    /// Combines the servers' answers. Returns `None` if the blob is not in
+    /// the database, and an error if the answers are malformed or do not
+    /// reconstruct a blob matching the commitment.
+    pub fn reconstruct(&self, left: &[u8], right: &[u8]) -> NonosResult<Option<Vec<u8>>> {
+        let bucket_size = self.params.bucket_size();
+        if left.len() != bucket_size || right.len() != bucket_size {
+            return Err(NonosError::Crypto("PIR answer has the wrong length".into()));
+        }
+
+        let bucket: Vec<u8> = left.iter().zip(right).map(|(a, b)| a ^ b).collect();
+        for slot in bucket.chunks_exact(self.params.slot_size()) {
+            if slot[..COMMITMENT_SIZE] != self.commitment {
+                continue;
+            }
+
+            let mut length = [0u8; LENGTH_SIZE];
+            length.copy_from_slice(&slot[COMMITMENT_SIZE..SLOT_HEADER_SIZE]);
+            let length = u32::from_le_bytes(length) as usize;
+            if length > self.params.max_item_size as usize {
+                return Err(NonosError::Crypto("PIR slot length out of range".into()));
+            }
+
+            let blob = slot[SLOT_HEADER_SIZE..SLOT_HEADER_SIZE + length].to_vec();
+            if blake3_hash(&blob).0 != self.commitment {
+                return Err(NonosError::Crypto("Content hash mismatch".into()));
+            }
+            return Ok(Some(blob));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> PirParams {
+        PirParams { buckets: 32, slots_per_bucket: 4, max_item_size: 128 }
+    }
+
+    fn blobs(count: usize) -> Vec<Vec<u8>> {
+        (0..count).map(|i| format!("cached blob number {}", i).into_bytes()).collect()
+    }
+
+    fn fetch(left: &PirDatabase, right: &PirDatabase, blob: &[u8]) -> NonosResult<Option<Vec<u8>>> {
+        let (retrieval, [a, b]) = PirRetrieval::new(&left.info(), blake3_hash(blob).0)?;
+        retrieval.reconstruct(&left.answer(&a)?, &right.answer(&b)?)
+    }
+
+    #[test]
+    fn test_retrieves_every_item() {
+        let items = blobs(40);
+        let left = PirDatabase::build(params(), items.clone()).unwrap();
+        let right = PirDatabase::build(params(), items.iter().rev().cloned()).unwrap();
+        assert_eq!(left.digest(), right.digest());
+        assert_eq!(left.item_count(), 40);
+
+        for blob in &items {
+            assert_eq!(fetch(&left, &right, blob).unwrap().as_deref(), Some(blob.as_slice()));
+        }
+    }
+
+    #[test]
+    fn test_missing_item() {
+        let database = PirDatabase::build(params(), blobs(5)).unwrap();
+        assert_eq!(fetch(&database, &database, b"not cached").unwrap(), None);
+    }
+
+    #[test]
+    fn test_queries_differ_only_in_target_bucket() {
+        let database = PirDatabase::build(params(), blobs(5)).unwrap();
+        let commitment = blake3_hash(b"cached blob number 3").0;
+        let (_, [left, right]) = PirRetrieval::new(&database.info(), commitment).unwrap();
+
+        let differing: Vec<usize> = (0..params().buckets as usize)
+            .filter(|bucket| (left.selection[bucket / 8] ^ right.selection[bucket / 8]) >> (bucket % 8) & 1 == 1)
+            .collect();
+        assert_eq!(differing, vec![params().bucket_of(&commitment)]);
+    }
+
+    #[test]
+    fn test_rejects_foreign_or_malformed_queries() {
+        let database = PirDatabase::build(params(), blobs(5)).unwrap();
+        let other = PirDatabase::build(params(), blobs(6)).unwrap();
+        assert_ne!(database.digest(), other.digest());
+
+        let (_, [query, _]) = PirRetrieval::new(&other.info(), [0u8; 32]).unwrap();
+        assert!(database.answer(&query).is_err());
+
+        let short = PirQuery { digest: database.digest(), selection: vec![0u8; 1] };
+        assert!(database.answer(&short).is_err());
+    }
+
+    #[test]
+    fn test_mismatched_answers_fail_verification() {
+        let items = blobs(40);
+        let left = PirDatabase::build(params(), items.clone()).unwrap();
+        let (retrieval, [a, b]) = PirRetrieval::new(&left.info(), blake3_hash(&items[0]).0).unwrap();
+
+        let mut tampered = left.answer(&b).unwrap();
+        let slot = (0..params().slots_per_bucket as usize)
+            .find(|slot| {
+                let answer = left.answer(&a).unwrap();
+                let offset = slot * params().slot_size();
+                answer.iter().zip(&tampered).skip(offset).take(COMMITMENT_SIZE)
+                    .map(|(x, y)| x ^ y)
+                    .eq(blake3_hash(&items[0]).0)
+            })
+            .unwrap();
+        tampered[slot * params().slot_size() + SLOT_HEADER_SIZE] ^= 0xff;
+
+        assert!(retrieval.reconstruct(&left.answer(&a).unwrap(), &tampered).is_err());
+        assert!(retrieval.reconstruct(&[0u8; 3], &tampered).is_err());
+    }
+
+    #[test]
+    fn test_oversized_and_overflowing_items_are_skipped() {
+        let tiny = PirParams { buckets: 1, slots_per_bucket: 2, max_item_size: 16 };
+        let database = PirDatabase::build(tiny, vec![
+            vec![1u8; 17],
+            b"first".to_vec(),
+            b"second".to_vec(),
+            b"third".to_vec(),
+        ]).unwrap();
+        assert_eq!(database.item_count(), 2);
+    }
+
+    #[test]
+    fn test_params_validation() {
+        assert!(PirParams { buckets: 0, ..params() }.validate().is_err());
+        assert!(PirParams { buckets: u32::MAX, ..params() }.validate().is_err());
+        assert!(params().validate().is_ok());
+    }
+}
@@ -1,15 +1,38 @@
-use nonos_crypto::{StealthKeyPair, StealthScanner};
-use nonos_types::{NodeId, NonosResult};
+use crate::config::StealthConfig;
+use crate::contracts::RpcProvider;
+use crate::storage::NodeStorage;
+use ethers::abi::{decode, ParamType};
+use nonos_crypto::{
+    check_erc5564_announcement, derive_public_key, erc5564_announcement_topic, StealthKeyPair,
+    StealthMetaAddress, StealthViewingKey, ERC5564_SCHEME_ID,
+};
+use nonos_types::{
+    EthAddress, NodeId, NonosError, NonosResult, Secp256k1PrivateKey, Secp256k1PublicKey,
+    StealthAnnouncement, StealthPayment,
+};
+use nonos_wallet::JsonRpcTransport;
+use serde_json::{json, Value};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
 use tokio::time::interval;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
+/// Secrets key for the persisted viewing key (view private || spend public).
+const VIEWING_KEY_SECRET: &str = "stealth_viewing_key";
+
+/// Scans ERC-5564 `Announcement` logs for payments to our stealth addresses.
+///
+/// Only the viewing key is held, so the daemon can detect payments but not
+/// spend them; the wallet derives the spending key for each payment.
 pub struct StealthScannerService {
     _node_id: NodeId,
-    scanner: Arc<RwLock<Option<StealthScanner>>>,
+    viewing_key: Arc<RwLock<Option<StealthViewingKey>>>,
+    config: StealthConfig,
+    rpc: Option<Arc<dyn JsonRpcTransport>>,
+    storage: Option<Arc<NodeStorage>>,
+    payments: RwLock<Vec<StealthPayment>>,
     detected_payments: AtomicU64,
     announcements_scanned: AtomicU64,
 }
@@ -18,22 +41,113 @@ impl StealthScannerService {
     pub fn new(node_id: NodeId) -> Self {
         Self {
             _node_id: node_id,
-            scanner: Arc::new(RwLock::new(None)),
+            viewing_key: Arc::new(RwLock::new(None)),
+            config: StealthConfig::default(),
+            rpc: None,
+            storage: None,
+            payments: RwLock::new(Vec::new()),
             detected_payments: AtomicU64::new(0),
             announcements_scanned: AtomicU64::new(0),
         }
     }
 
+    /// Scans `config.announcer` through `rpc`, checkpointing progress and
+    /// detected payments in `storage`. A viewing key stored by a previous
+    /// run is restored.
+    pub fn with_storage(
+        node_id: NodeId,
+        config: StealthConfig,
+        rpc: Arc<dyn JsonRpcTransport>,
+        storage: Arc<NodeStorage>,
+    ) -> NonosResult<Self> {
+        Self::restore(node_id, config, Some(rpc), storage)
+    }
+
+    fn restore(
+        node_id: NodeId,
+        config: StealthConfig,
+        rpc: Option<Arc<dyn JsonRpcTransport>>,
+        storage: Arc<NodeStorage>,
+    ) -> NonosResult<Self> {
+        let payments = storage.load_stealth_payments()?;
+        let viewing_key = storage
+            .load_secret(VIEWING_KEY_SECRET)?
+            .map(|bytes| decode_viewing_key(&bytes))
+            .transpose()?;
+
+        if !payments.is_empty() {
+            info!("Restored {} stealth payments", payments.len());
+        }
+
+        Ok(Self {
+            viewing_key: Arc::new(RwLock::new(viewing_key)),
+            config,
+            rpc,
+            storage: Some(storage),
+            detected_payments: AtomicU64::new(payments.len() as u64),
+            payments: RwLock::new(payments),
+            ..Self::new(node_id)
+        })
+    }
+
+    /// Like [`StealthScannerService::with_storage`], using the public
+    /// endpoints for `config.chain_id` unless `config.rpc_url` is set. A
+    /// chain with neither is not scanned.
+    pub fn from_config(node_id: NodeId, config: StealthConfig, storage: Arc<NodeStorage>) -> NonosResult<Self> {
+        let rpc = match (&config.rpc_url, config.chain_id) {
+            (Some(url), chain_id) => RpcProvider::custom(vec![url.clone()], chain_id),
+            (None, 1) => RpcProvider::mainnet(),
+            (None, 11155111) => RpcProvider::sepolia(),
+            (None, 8453) => RpcProvider::base(),
+            (None, 42161) => RpcProvider::arbitrum(),
+            (None, chain_id) => {
+                warn!(
+                    "No public RPC endpoints for chain {}; set stealth.rpc_url. Stealth scanning disabled",
+                    chain_id
+                );
+                return Self::restore(node_id, config, None, storage);
+            }
+        };
+
+        Self::with_storage(node_id, config, Arc::new(rpc), storage)
+    }
+
     pub async fn initialize(&self, keypair: StealthKeyPair) -> NonosResult<String> {
-        let meta_address = keypair.meta_address();
-        let encoded = meta_address.encode();
-        *self.scanner.write().await = Some(StealthScanner::new(keypair));
+        self.initialize_viewing_key(keypair.viewing_key()).await
+    }
+
+    /// Starts scanning for `key`, persisting it so scanning resumes after a
+    /// restart. Returns the encoded meta-address it receives payments on.
+    pub async fn initialize_viewing_key(&self, key: StealthViewingKey) -> NonosResult<String> {
+        let view_pubkey = derive_public_key(&key.view_private)?;
+        let encoded = StealthMetaAddress::new(key.spend_public, view_pubkey).encode();
+
+        if let Some(storage) = &self.storage {
+            let mut bytes = Vec::with_capacity(65);
+            bytes.extend_from_slice(&key.view_private.0);
+            bytes.extend_from_slice(&key.spend_public.0);
+            storage.store_secret(VIEWING_KEY_SECRET, &bytes)?;
+        }
+
+        *self.viewing_key.write().await = Some(key);
         info!("Stealth scanner initialized");
         Ok(encoded)
     }
 
     pub async fn is_initialized(&self) -> bool {
-        self.scanner.read().await.is_some()
+        self.viewing_key.read().await.is_some()
+    }
+
+    pub async fn payments(&self) -> Vec<StealthPayment> {
+        self.payments.read().await.clone()
+    }
+
+    /// Last block whose announcements have been scanned.
+    pub fn checkpoint(&self) -> NonosResult<Option<u64>> {
+        match &self.storage {
+            Some(storage) => storage.stealth_checkpoint(),
+            None => Ok(None),
+        }
     }
 
     pub fn stats(&self) -> (u64, u64) {
@@ -43,12 +157,104 @@ impl StealthScannerService {
         )
     }
 
+    /// Scans from the checkpoint up to `confirmations` blocks behind the
+    /// head, in batches of `batch_blocks`. Returns the number of payments
+    /// detected. Does nothing until a viewing key is set.
+    pub async fn scan(&self) -> NonosResult<usize> {
+        let (Some(rpc), Some(storage)) = (&self.rpc, &self.storage) else {
+            return Ok(0);
+        };
+        let Some(key) = self.viewing_key.read().await.clone() else {
+            return Ok(0);
+        };
+
+        let head = parse_quantity(&rpc.request("eth_blockNumber", json!([])).await?)?;
+        let safe_head = head.saturating_sub(self.config.confirmations);
+
+        let mut from = match storage.stealth_checkpoint()? {
+            Some(block) => block + 1,
+            None if self.config.start_block > 0 => self.config.start_block,
+            None => safe_head,
+        };
+
+        let topic = format!("0x{}", hex::encode(erc5564_announcement_topic()));
+        let scheme = format!("0x{:064x}", ERC5564_SCHEME_ID);
+        let mut detected = 0;
+
+        while from <= safe_head {
+            let to = from.saturating_add(self.config.batch_blocks.max(1) - 1).min(safe_head);
+
+            let logs = rpc
+                .request(
+                    "eth_getLogs",
+                    json!([{
+                        "address": self.config.announcer.to_hex(),
+                        "fromBlock": format!("0x{:x}", from),
+                        "toBlock": format!("0x{:x}", to),
+                        "topics": [topic, scheme],
+                    }]),
+                )
+                .await?;
+            let logs = logs
+                .as_array()
+                .ok_or_else(|| NonosError::Network("Invalid eth_getLogs response".into()))?;
+
+            let mut payments = Vec::new();
+            for log in logs {
+                if log["removed"].as_bool() == Some(true) {
+                    continue;
+                }
+                let announcement = match parse_announcement(log) {
+                    Ok(announcement) => announcement,
+                    Err(e) => {
+                        debug!("Skipping malformed announcement: {}", e);
+                        continue;
+                    }
+                };
+                self.announcements_scanned.fetch_add(1, Ordering::Relaxed);
+
+                let ours = check_erc5564_announcement(
+                    &key,
+                    &announcement.ephemeral_pubkey,
+                    &announcement.metadata,
+                    &announcement.stealth_address,
+                )
+                .unwrap_or(false);
+
+                if ours {
+                    info!(
+                        "Detected stealth payment to {} in block {}",
+                        announcement.stealth_address, announcement.block_number
+                    );
+                    payments.push(StealthPayment {
+                        announcement,
+                        detected_at: chrono::Utc::now(),
+                    });
+                }
+            }
+
+            storage.record_stealth_scan(&payments, to)?;
+            detected += payments.len();
+            self.detected_payments.fetch_add(payments.len() as u64, Ordering::Relaxed);
+            self.payments.write().await.extend(payments);
+
+            from = to + 1;
+        }
+
+        Ok(detected)
+    }
+
     pub async fn run(self: Arc<Self>, shutdown: Arc<AtomicBool>) -> NonosResult<()> {
         info!("Stealth Scanner started");
-        let mut ticker = interval(Duration::from_secs(30));
+        let mut ticker = interval(Duration::from_secs(self.config.scan_interval_secs.max(1)));
 
         while !shutdown.load(Ordering::Relaxed) {
             ticker.tick().await;
+
+            if let Err(e) = self.scan().await {
+                warn!("Stealth scan failed: {}", e);
+            }
+
             let (detected, scanned) = self.stats();
             debug!("Stealth scanner: {} detected, {} scanned", detected, scanned);
         }
@@ -57,3 +263,216 @@ impl StealthScannerService {
         Ok(())
     }
 }
+
+fn decode_viewing_key(bytes: &[u8]) -> NonosResult<StealthViewingKey> {
+    if bytes.len() != 65 {
+        return Err(NonosError::Storage("Invalid stored stealth viewing key".into()));
+    }
+
+    let mut view_private = [0u8; 32];
+    view_private.copy_from_slice(&bytes[..32]);
+    let mut spend_public = [0u8; 33];
+    spend_public.copy_from_slice(&bytes[32..]);
+
+    Ok(StealthViewingKey::new(
+        Secp256k1PrivateKey::from_bytes(view_private),
+        Secp256k1PublicKey::from_bytes(spend_public),
+    ))
+}
+
+fn parse_quantity(value: &Value) -> NonosResult<u64> {
+    let hex = value
+        .as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .ok_or_else(|| NonosError::Network(format!("Invalid quantity: {}", value)))?;
+    u64::from_str_radix(hex, 16).map_err(|e| NonosError::Network(format!("Invalid quantity: {}", e)))
+}
+
+fn parse_word(value: &Value) -> NonosResult<[u8; 32]> {
+    let bytes = value
+        .as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .and_then(|s| hex::decode(s).ok())
+        .ok_or_else(|| NonosError::Network(format!("Invalid log word: {}", value)))?;
+    bytes
+        .try_into()
+        .map_err(|_| NonosError::Network("Log word is not 32 bytes".into()))
+}
+
+fn topic_address(topic: &[u8; 32]) -> EthAddress {
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&topic[12..]);
+    EthAddress::from_bytes(address)
+}
+
+fn parse_announcement(log: &Value) -> NonosResult<StealthAnnouncement> {
+    let topics = log["topics"]
+        .as_array()
+        .filter(|topics| topics.len() == 4)
+        .ok_or_else(|| NonosError::Network("Announcement log must have 4 topics".into()))?;
+
+    let scheme = parse_word(&topics[1])?;
+    if scheme[..24].iter().any(|b| *b != 0) {
+        return Err(NonosError::Network("Scheme ID out of range".into()));
+    }
+    let mut scheme_id = [0u8; 8];
+    scheme_id.copy_from_slice(&scheme[24..]);
+
+    let data = log["data"]
+        .as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .and_then(|s| hex::decode(s).ok())
+        .ok_or_else(|| NonosError::Network("Invalid announcement data".into()))?;
+    let mut tokens = decode(&[ParamType::Bytes, ParamType::Bytes], &data)
+        .map_err(|e| NonosError::Network(format!("Failed to decode announcement: {}", e)))?
+        .into_iter();
+
+    let mut next_bytes = || {
+        tokens
+            .next()
+            .and_then(|token| token.into_bytes())
+            .ok_or_else(|| NonosError::Network("Invalid announcement data".into()))
+    };
+    let ephemeral_pubkey = next_bytes()?;
+    let metadata = next_bytes()?;
+
+    Ok(StealthAnnouncement {
+        scheme_id: u64::from_be_bytes(scheme_id),
+        stealth_address: topic_address(&parse_word(&topics[2])?),
+        caller: topic_address(&parse_word(&topics[3])?),
+        ephemeral_pubkey,
+        metadata,
+        block_number: parse_quantity(&log["blockNumber"])?,
+        tx_hash: parse_word(&log["transactionHash"])?,
+        log_index: parse_quantity(&log["logIndex"])?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ethers::abi::{encode, Token};
+    use nonos_crypto::generate_erc5564_stealth_address;
+    use std::sync::Mutex;
+
+    struct MockNode {
+        head: u64,
+        logs: Vec<Value>,
+        ranges: Mutex<Vec<(u64, u64)>>,
+    }
+
+    #[async_trait]
+    impl JsonRpcTransport for MockNode {
+        async fn request(&self, method: &str, params: Value) -> NonosResult<Value> {
+            match method {
+                "eth_blockNumber" => Ok(json!(format!("0x{:x}", self.head))),
+                "eth_getLogs" => {
+                    let from = parse_quantity(&params[0]["fromBlock"])?;
+                    let to = parse_quantity(&params[0]["toBlock"])?;
+                    self.ranges.lock().unwrap().push((from, to));
+
+                    let logs: Vec<Value> = self
+                        .logs
+                        .iter()
+                        .filter(|log| (from..=to).contains(&parse_quantity(&log["blockNumber"]).unwrap()))
+                        .cloned()
+                        .collect();
+                    Ok(json!(logs))
+                }
+                _ => Ok(Value::Null),
+            }
+        }
+    }
+
+    fn announcement_log(block: u64, stealth: &EthAddress, ephemeral: &[u8], metadata: &[u8]) -> Value {
+        let data = encode(&[Token::Bytes(ephemeral.to_vec()), Token::Bytes(metadata.to_vec())]);
+        json!({
+            "address": nonos_crypto::ERC5564_ANNOUNCER,
+            "topics": [
+                format!("0x{}", hex::encode(erc5564_announcement_topic())),
+                format!("0x{:064x}", ERC5564_SCHEME_ID),
+                format!("0x{:0>64}", hex::encode(stealth.0)),
+                format!("0x{:0>64}", "ab".repeat(20)),
+            ],
+            "data": format!("0x{}", hex::encode(data)),
+            "blockNumber": format!("0x{:x}", block),
+            "transactionHash": format!("0x{:064x}", block),
+            "logIndex": "0x0",
+            "removed": false,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_scan_detects_and_checkpoints() {
+        let keypair = StealthKeyPair::generate().unwrap();
+        let other = StealthKeyPair::generate().unwrap();
+        let ours = generate_erc5564_stealth_address(&keypair.meta_address()).unwrap();
+        let theirs = generate_erc5564_stealth_address(&other.meta_address()).unwrap();
+
+        let node = Arc::new(MockNode {
+            head: 130,
+            logs: vec![
+                announcement_log(105, &ours.stealth_address, &ours.ephemeral_pubkey.0, &[ours.view_tag]),
+                announcement_log(110, &theirs.stealth_address, &theirs.ephemeral_pubkey.0, &[theirs.view_tag]),
+                announcement_log(125, &ours.stealth_address, &ours.ephemeral_pubkey.0, &[ours.view_tag]),
+            ],
+            ranges: Mutex::new(Vec::new()),
+        });
+        let config = StealthConfig {
+            start_block: 100,
+            batch_blocks: 10,
+            confirmations: 10,
+            ..Default::default()
+        };
+        let storage = Arc::new(NodeStorage::in_memory().unwrap());
+
+        let service = StealthScannerService::with_storage(NodeId::from_bytes([1; 32]), config.clone(), node.clone(), storage.clone()).unwrap();
+        assert_eq!(service.scan().await.unwrap(), 0);
+        assert!(node.ranges.lock().unwrap().is_empty());
+
+        service.initialize(keypair).await.unwrap();
+        assert_eq!(service.scan().await.unwrap(), 1);
+        assert_eq!(*node.ranges.lock().unwrap(), vec![(100, 109), (110, 119), (120, 120)]);
+        assert_eq!(service.checkpoint().unwrap(), Some(120));
+        assert_eq!(service.stats(), (1, 2));
+
+        let payment = &service.payments().await[0];
+        assert_eq!(payment.announcement.stealth_address, ours.stealth_address);
+        assert_eq!(payment.announcement.block_number, 105);
+
+        let restored = StealthScannerService::with_storage(NodeId::from_bytes([1; 32]), config, node.clone(), storage).unwrap();
+        assert!(restored.is_initialized().await);
+        assert_eq!(restored.payments().await.len(), 1);
+        assert_eq!(restored.scan().await.unwrap(), 0);
+        assert_eq!(node.ranges.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_chain_without_rpc_is_not_scanned() {
+        let storage = Arc::new(NodeStorage::in_memory().unwrap());
+        let config = StealthConfig { chain_id: 999_999, rpc_url: None, ..Default::default() };
+        let scanner = StealthScannerService::from_config(NodeId::from_bytes([1; 32]), config, storage).unwrap();
+
+        scanner.initialize(StealthKeyPair::generate().unwrap()).await.unwrap();
+        assert_eq!(scanner.scan().await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_parse_announcement() {
+        let stealth = EthAddress::from_bytes([0x11; 20]);
+        let log = announcement_log(7, &stealth, &[2u8; 33], &[0x5a, 1, 2]);
+
+        let announcement = parse_announcement(&log).unwrap();
+        assert_eq!(announcement.scheme_id, ERC5564_SCHEME_ID);
+        assert_eq!(announcement.stealth_address, stealth);
+        assert_eq!(announcement.caller, EthAddress::from_bytes([0xab; 20]));
+        assert_eq!(announcement.ephemeral_pubkey, vec![2u8; 33]);
+        assert_eq!(announcement.view_tag(), Some(0x5a));
+        assert_eq!(announcement.block_number, 7);
+
+        let mut truncated = log.clone();
+        truncated["topics"].as_array_mut().unwrap().pop();
+        assert!(parse_announcement(&truncated).is_err());
+    }
+}
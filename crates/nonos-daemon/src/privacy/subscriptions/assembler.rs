@@ -0,0 +1,98 @@
+use super::bundle::{CHUNK_BYTES, MAX_BUNDLE_BYTES};
+use crate::p2p::FilterListChunkData;
+use nonos_crypto::blake3_hash;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Bundles being reassembled at once; the oldest is dropped for a new one.
+const MAX_PENDING_BUNDLES: usize = 8;
+/// How long a partly received bundle is kept.
+const PENDING_TTL: Duration = Duration::from_secs(300);
+/// Recently completed bundles whose late chunks are ignored.
+const COMPLETED_MEMORY: usize = 64;
+
+struct PendingBundle {
+    /// Data and maintainer signature of each chunk received.
+    chunks: Vec<Option<(Vec<u8>, Vec<u8>)>>,
+    received: usize,
+    started: Instant,
+}
+
+/// Reassembles gossiped chunks into encoded bundles. A bundle is only
+/// returned once its bytes hash to the ID every chunk carried.
+#[derive(Default)]
+pub struct ChunkAssembler {
+    pending: HashMap<[u8; 32], PendingBundle>,
+    completed: VecDeque<[u8; 32]>,
+}
+
+impl ChunkAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `chunk`, whose signature has been checked, returning the
+    /// encoded bundle and its chunk signatures if it was the last one
+    /// missing.
+    pub fn insert(&mut self, chunk: FilterListChunkData, now: Instant) -> Option<(Vec<u8>, Vec<Vec<u8>>)> {
+        let total = chunk.total as usize;
+        let index = chunk.index as usize;
+        // Every chunk but the last is full, as `to_chunks` produces them.
+        let size_ok = if index + 1 < total {
+            chunk.data.len() == CHUNK_BYTES
+        } else {
+            (1..=CHUNK_BYTES).contains(&chunk.data.len())
+        };
+        let well_formed = index < total && total <= MAX_BUNDLE_BYTES.div_ceil(CHUNK_BYTES) && size_ok;
+        if !well_formed || self.completed.contains(&chunk.bundle_id) {
+            return None;
+        }
+
+        if !self.pending.contains_key(&chunk.bundle_id) {
+            self.expire(now);
+            if self.pending.len() >= MAX_PENDING_BUNDLES {
+                if let Some(oldest) = self.pending.iter().min_by_key(|(_, pending)| pending.started).map(|(id, _)| *id) {
+                    self.pending.remove(&oldest);
+                }
+            }
+        }
+
+        let pending = self.pending.entry(chunk.bundle_id).or_insert_with(|| PendingBundle {
+            chunks: vec![None; total],
+            received: 0,
+            started: now,
+        });
+        if pending.chunks.len() != total || pending.chunks[index].is_some() {
+            return None;
+        }
+        pending.chunks[index] = Some((chunk.data, chunk.signature));
+        pending.received += 1;
+        if pending.received < total {
+            return None;
+        }
+
+        let pending = self.pending.remove(&chunk.bundle_id)?;
+        let (chunks, signatures): (Vec<Vec<u8>>, Vec<Vec<u8>>) = pending.chunks.into_iter().flatten().unzip();
+        let bytes = chunks.concat();
+        if blake3_hash(&bytes).0 != chunk.bundle_id {
+            debug!("Discarding filter list bundle {} with mismatched hash", hex::encode(chunk.bundle_id));
+            return None;
+        }
+
+        if self.completed.len() >= COMPLETED_MEMORY {
+            self.completed.pop_front();
+        }
+        self.completed.push_back(chunk.bundle_id);
+        Some((bytes, signatures))
+    }
+
+    /// Drops bundles that have been incomplete for too long.
+    pub fn expire(&mut self, now: Instant) {
+        self.pending.retain(|_, pending| now.duration_since(pending.started) < PENDING_TTL);
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
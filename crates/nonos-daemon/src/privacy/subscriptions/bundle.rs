@@ -0,0 +1,212 @@
+use crate::p2p::FilterListChunkData;
+use nonos_crypto::{blake3_hash, ed25519_derive_public, ed25519_sign, ed25519_verify};
+use nonos_types::{
+    Ed25519PrivateKey, Ed25519PublicKey, NonosError, NonosResult, ED25519_SIGNATURE_SIZE,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Largest encoded bundle accepted, and so the most a peer can make us
+/// buffer for one bundle.
+pub const MAX_BUNDLE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Payload bytes per gossiped chunk, leaving room for the envelope within
+/// the 64 KiB message limit.
+pub const CHUNK_BYTES: usize = 48 * 1024;
+
+const SIGNING_DOMAIN: &[u8] = b"nonos-filter-list-bundle";
+const CHUNK_SIGNING_DOMAIN: &[u8] = b"nonos-filter-list-chunks";
+
+/// A list's rules in canonical form: trimmed, non-empty, sorted and
+/// deduplicated. Rule order never changes what a list matches, so this lets
+/// a diff reproduce the maintainer's list exactly, hash included.
+pub fn canonical_rules(text: &str) -> String {
+    let rules: BTreeSet<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    rules.into_iter().collect::<Vec<_>>().join("\n")
+}
+
+/// BLAKE3 hash of a list in canonical form.
+pub fn rules_hash(canonical: &str) -> [u8; 32] {
+    blake3_hash(canonical.as_bytes()).0
+}
+
+/// What a bundle carries.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BundleBody {
+    /// The whole list.
+    Full { rules: String },
+    /// Changes against version `base_version`.
+    Diff { base_version: u64, added: Vec<String>, removed: Vec<String> },
+}
+
+impl BundleBody {
+    /// The diff that turns `old` into `new`.
+    pub fn diff(base_version: u64, old: &str, new: &str) -> Self {
+        let old = canonical_rules(old);
+        let new = canonical_rules(new);
+        let old: BTreeSet<&str> = old.lines().collect();
+        let new: BTreeSet<&str> = new.lines().collect();
+
+        BundleBody::Diff {
+            base_version,
+            added: new.difference(&old).map(|rule| rule.to_string()).collect(),
+            removed: old.difference(&new).map(|rule| rule.to_string()).collect(),
+        }
+    }
+
+    /// The canonical list after applying this body to `current`, the
+    /// canonical text of version `current_version`.
+    pub fn apply(&self, current: &str, current_version: u64) -> NonosResult<String> {
+        match self {
+            BundleBody::Full { rules } => Ok(canonical_rules(rules)),
+            BundleBody::Diff { base_version, added, removed } => {
+                if *base_version != current_version {
+                    return Err(NonosError::Config(format!(
+                        "Diff against version {} does not apply to version {}",
+                        base_version, current_version
+                    )));
+                }
+                let removed: BTreeSet<&str> = removed.iter().map(|rule| rule.trim()).collect();
+                let rules: BTreeSet<&str> = current.lines()
+                    .filter(|rule| !removed.contains(rule))
+                    .chain(added.iter().map(|rule| rule.trim()))
+                    .filter(|rule| !rule.is_empty())
+                    .collect();
+                Ok(rules.into_iter().collect::<Vec<_>>().join("\n"))
+            }
+        }
+    }
+}
+
+/// One version of a filter list, signed by its maintainer.
+///
+/// The signature covers the list name, maintainer key, version, body and
+/// the hash of the resulting canonical list, so a diff applied to the wrong
+/// base is caught as well as a forged one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FilterListBundle {
+    pub name: String,
+    pub maintainer: Ed25519PublicKey,
+    pub version: u64,
+    pub body: BundleBody,
+    pub content_hash: [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+impl FilterListBundle {
+    /// Signs `body` as `version` of list `name`. `content_hash` is the
+    /// [`rules_hash`] of the list the body produces.
+    pub fn sign(
+        name: &str,
+        version: u64,
+        body: BundleBody,
+        content_hash: [u8; 32],
+        key: &Ed25519PrivateKey,
+    ) -> NonosResult<Self> {
+        let mut bundle = Self {
+            name: name.to_string(),
+            maintainer: ed25519_derive_public(key),
+            version,
+            body,
+            content_hash,
+            signature: Vec::new(),
+        };
+        bundle.signature = ed25519_sign(key, &bundle.signed_bytes()?).to_vec();
+        Ok(bundle)
+    }
+
+    pub fn verify(&self) -> NonosResult<()> {
+        let signature: [u8; ED25519_SIGNATURE_SIZE] = self.signature.as_slice().try_into()
+            .map_err(|_| NonosError::InvalidSignature("Invalid filter list signature length".into()))?;
+        if !ed25519_verify(&self.maintainer, &self.signed_bytes()?, &signature)? {
+            return Err(NonosError::InvalidSignature(format!("Bad signature on filter list {}", self.name)));
+        }
+        Ok(())
+    }
+
+    fn signed_bytes(&self) -> NonosResult<Vec<u8>> {
+        let fields = (&self.name, &self.maintainer, self.version, &self.body, &self.content_hash);
+        let mut bytes = SIGNING_DOMAIN.to_vec();
+        bytes.extend(bincode::serialize(&fields)
+            .map_err(|e| NonosError::Serialization(format!("Failed to encode filter list bundle: {}", e)))?);
+        Ok(bytes)
+    }
+
+    pub fn encode(&self) -> NonosResult<Vec<u8>> {
+        let bytes = bincode::serialize(self)
+            .map_err(|e| NonosError::Serialization(format!("Failed to encode filter list bundle: {}", e)))?;
+        if bytes.len() > MAX_BUNDLE_BYTES {
+            return Err(NonosError::Config(format!(
+                "Filter list bundle is {} bytes (max: {})", bytes.len(), MAX_BUNDLE_BYTES
+            )));
+        }
+        Ok(bytes)
+    }
+
+    pub fn decode(bytes: &[u8]) -> NonosResult<Self> {
+        if bytes.len() > MAX_BUNDLE_BYTES {
+            return Err(NonosError::Serialization(format!("Filter list bundle too large: {} bytes", bytes.len())));
+        }
+        bincode::deserialize(bytes)
+            .map_err(|e| NonosError::Serialization(format!("Failed to decode filter list bundle: {}", e)))
+    }
+
+    /// Splits the encoded bundle into gossip chunks, identified by the
+    /// hash of the encoding and each signed with the maintainer's `key`.
+    pub fn to_chunks(&self, key: &Ed25519PrivateKey) -> NonosResult<Vec<FilterListChunkData>> {
+        if ed25519_derive_public(key) != self.maintainer {
+            return Err(NonosError::Config(format!("Key is not the maintainer of filter list {}", self.name)));
+        }
+        let bytes = self.encode()?;
+        let mut chunks = self.chunks_of(&bytes, &[]);
+        for chunk in &mut chunks {
+            chunk.signature = ed25519_sign(key, &chunk.signed_bytes()?).to_vec();
+        }
+        Ok(chunks)
+    }
+
+    /// Splits `bytes`, this bundle's encoding, into chunks carrying the
+    /// maintainer's chunk `signatures` in order, so a node can gossip a
+    /// bundle again without the maintainer's key.
+    pub fn chunks_of(&self, bytes: &[u8], signatures: &[Vec<u8>]) -> Vec<FilterListChunkData> {
+        let bundle_id = blake3_hash(bytes).0;
+        let total = bytes.len().div_ceil(CHUNK_BYTES) as u16;
+
+        bytes.chunks(CHUNK_BYTES)
+            .enumerate()
+            .map(|(index, data)| FilterListChunkData {
+                name: self.name.clone(),
+                maintainer: self.maintainer,
+                version: self.version,
+                bundle_id,
+                index: index as u16,
+                total,
+                signature: signatures.get(index).cloned().unwrap_or_default(),
+                data: data.to_vec(),
+            })
+            .collect()
+    }
+}
+
+impl FilterListChunkData {
+    /// Checks the maintainer's signature over the chunk header and data, so
+    /// a chunk relayed with any other data is rejected on its own. The
+    /// bundle the chunks reassemble into is verified separately.
+    pub fn verify(&self) -> NonosResult<()> {
+        let signature: [u8; ED25519_SIGNATURE_SIZE] = self.signature.as_slice().try_into()
+            .map_err(|_| NonosError::InvalidSignature("Invalid filter list chunk signature length".into()))?;
+        if !ed25519_verify(&self.maintainer, &self.signed_bytes()?, &signature)? {
+            return Err(NonosError::InvalidSignature(format!("Bad signature on filter list {} chunk", self.name)));
+        }
+        Ok(())
+    }
+
+    fn signed_bytes(&self) -> NonosResult<Vec<u8>> {
+        let data_hash = blake3_hash(&self.data).0;
+        let fields = (&self.name, &self.maintainer, self.version, &self.bundle_id, self.index, self.total, &data_hash);
+        let mut bytes = CHUNK_SIGNING_DOMAIN.to_vec();
+        bytes.extend(bincode::serialize(&fields)
+            .map_err(|e| NonosError::Serialization(format!("Failed to encode filter list chunk: {}", e)))?);
+        Ok(bytes)
+    }
+}
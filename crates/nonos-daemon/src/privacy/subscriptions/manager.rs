@@ -0,0 +1,293 @@
+use super::assembler::ChunkAssembler;
+use super::bundle::{rules_hash, BundleBody, FilterListBundle};
+use crate::p2p::FilterListChunkData;
+use crate::privacy::TrackingBlockerService;
+use crate::storage::{NodeStorage, StoredBundle, StoredFilterList};
+use nonos_types::{Ed25519PublicKey, NonosError, NonosResult};
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// Prefix of the tracking blocker list names subscriptions install under,
+/// so they cannot collide with lists loaded from disk.
+pub const SUBSCRIPTION_LIST_PREFIX: &str = "subscription:";
+const MAX_LIST_NAME_LEN: usize = 64;
+
+/// How often a full copy of each list's current version is gossiped again,
+/// plus up to a quarter more at random. Whichever node holding a copy is
+/// due first sends it; the others put theirs off when it arrives.
+pub const REPUBLISH_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// What became of a bundle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BundleOutcome {
+    Applied { version: u64 },
+    /// No subscription to this list from this maintainer.
+    NotSubscribed,
+    /// Not newer than the version already applied.
+    Stale { current: u64 },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SubscriptionInfo {
+    pub name: String,
+    pub maintainer: String,
+    pub version: u64,
+    pub rules: usize,
+    pub updated_at: Option<i64>,
+}
+
+/// Filter-list subscriptions: which maintainer each list is taken from,
+/// and the latest version received. Verified bundles are persisted and
+/// installed into the tracking blocker as they arrive.
+pub struct FilterListSubscriptions {
+    blocker: Arc<TrackingBlockerService>,
+    storage: Option<Arc<NodeStorage>>,
+    lists: RwLock<BTreeMap<String, StoredFilterList>>,
+    assembler: Mutex<ChunkAssembler>,
+    /// When each list's full copy is next due to be gossiped.
+    republish_at: Mutex<HashMap<String, Instant>>,
+}
+
+impl FilterListSubscriptions {
+    pub fn new(blocker: Arc<TrackingBlockerService>) -> Self {
+        Self {
+            blocker,
+            storage: None,
+            lists: RwLock::new(BTreeMap::new()),
+            assembler: Mutex::new(ChunkAssembler::new()),
+            republish_at: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Like [`FilterListSubscriptions::new`], but subscriptions and list
+    /// versions are persisted to `storage`; call
+    /// [`FilterListSubscriptions::restore`] to load them.
+    pub fn with_storage(blocker: Arc<TrackingBlockerService>, storage: Arc<NodeStorage>) -> Self {
+        Self { storage: Some(storage), ..Self::new(blocker) }
+    }
+
+    /// Loads persisted subscriptions and installs their lists. Returns the
+    /// number of subscriptions restored.
+    pub async fn restore(&self) -> NonosResult<usize> {
+        let Some(storage) = &self.storage else {
+            return Ok(0);
+        };
+
+        let stored = storage.load_filter_lists()?;
+        let mut lists = self.lists.write().await;
+        for list in stored {
+            if list.version > 0 {
+                self.blocker.set_filter_list(&list_key(&list.name), list.rules.clone()).await;
+            }
+            lists.insert(list.name.clone(), list);
+        }
+        Ok(lists.len())
+    }
+
+    /// Follows list `name` as published by `maintainer`. Subscribing to a
+    /// list already followed under another key starts it over.
+    pub async fn subscribe(&self, name: &str, maintainer: Ed25519PublicKey) -> NonosResult<()> {
+        validate_name(name)?;
+        let mut lists = self.lists.write().await;
+        if lists.get(name).is_some_and(|list| list.maintainer == maintainer.0) {
+            return Ok(());
+        }
+
+        let list = StoredFilterList {
+            name: name.to_string(),
+            maintainer: maintainer.0,
+            version: 0,
+            rules: String::new(),
+            updated_at: None,
+            full_bundle: None,
+        };
+        if let Some(storage) = &self.storage {
+            storage.store_filter_list(&list)?;
+        }
+        if lists.insert(name.to_string(), list).is_some() {
+            self.blocker.remove_filter_list(&list_key(name)).await;
+        }
+        self.republish_at.lock().remove(name);
+
+        info!("Subscribed to filter list {} from {}", name, maintainer.to_hex());
+        Ok(())
+    }
+
+    pub async fn unsubscribe(&self, name: &str) -> NonosResult<bool> {
+        let mut lists = self.lists.write().await;
+        if lists.remove(name).is_none() {
+            return Ok(false);
+        }
+        if let Some(storage) = &self.storage {
+            storage.remove_filter_list(name)?;
+        }
+        self.blocker.remove_filter_list(&list_key(name)).await;
+        self.republish_at.lock().remove(name);
+
+        info!("Unsubscribed from filter list {}", name);
+        Ok(true)
+    }
+
+    pub async fn subscriptions(&self) -> Vec<SubscriptionInfo> {
+        self.lists.read().await.values()
+            .map(|list| SubscriptionInfo {
+                name: list.name.clone(),
+                maintainer: hex::encode(list.maintainer),
+                version: list.version,
+                rules: list.rules.lines().count(),
+                updated_at: list.updated_at,
+            })
+            .collect()
+    }
+
+    /// Verifies `bundle` and, if it is a newer version of a subscribed
+    /// list, applies, persists and installs it.
+    pub async fn apply_bundle(&self, bundle: &FilterListBundle) -> NonosResult<BundleOutcome> {
+        self.apply(bundle, None).await
+    }
+
+    /// Applies `bundle` like [`FilterListSubscriptions::apply_bundle`]. A
+    /// full bundle of the resulting version also keeps `chunked`, its
+    /// encoding and chunk signature, to gossip again.
+    async fn apply(&self, bundle: &FilterListBundle, chunked: Option<StoredBundle>) -> NonosResult<BundleOutcome> {
+        let mut lists = self.lists.write().await;
+        let Some(list) = lists.get_mut(&bundle.name).filter(|list| list.maintainer == bundle.maintainer.0) else {
+            return Ok(BundleOutcome::NotSubscribed);
+        };
+        let is_full = matches!(bundle.body, BundleBody::Full { .. });
+        if bundle.version < list.version || (bundle.version == list.version && (!is_full || list.version == 0)) {
+            return Ok(BundleOutcome::Stale { current: list.version });
+        }
+
+        bundle.verify()?;
+        let rules = bundle.body.apply(&list.rules, list.version)?;
+        if rules_hash(&rules) != bundle.content_hash {
+            return Err(NonosError::Config(format!(
+                "Filter list {} version {} does not match its content hash", bundle.name, bundle.version
+            )));
+        }
+        if is_full {
+            self.defer_republish(&bundle.name);
+        }
+
+        if bundle.version == list.version {
+            // Someone else's copy of the version already applied: keep it
+            // to pass on if there is none yet.
+            if list.full_bundle.is_none() && chunked.is_some() && rules == list.rules {
+                let updated = StoredFilterList { full_bundle: chunked, ..list.clone() };
+                if let Some(storage) = &self.storage {
+                    storage.store_filter_list(&updated)?;
+                }
+                *list = updated;
+            }
+            return Ok(BundleOutcome::Stale { current: list.version });
+        }
+
+        let updated = StoredFilterList {
+            version: bundle.version,
+            rules,
+            updated_at: Some(chrono::Utc::now().timestamp()),
+            full_bundle: chunked.filter(|_| is_full),
+            ..list.clone()
+        };
+        if let Some(storage) = &self.storage {
+            storage.store_filter_list(&updated)?;
+        }
+        let stats = self.blocker.set_filter_list(&list_key(&updated.name), updated.rules.clone()).await;
+        *list = updated;
+
+        info!(
+            "Applied filter list {} version {} ({} blocking, {} exceptions)",
+            bundle.name, bundle.version, stats.blocking, stats.exceptions
+        );
+        Ok(BundleOutcome::Applied { version: bundle.version })
+    }
+
+    /// Adds a gossiped chunk; once its bundle is complete, decodes and
+    /// applies it. Only chunks the maintainer of a subscribed list signed,
+    /// for its current or a newer version, are reassembled.
+    pub async fn ingest_chunk(&self, chunk: FilterListChunkData) -> NonosResult<Option<BundleOutcome>> {
+        {
+            let lists = self.lists.read().await;
+            let Some(list) = lists.get(&chunk.name).filter(|list| list.maintainer == chunk.maintainer.0) else {
+                return Ok(Some(BundleOutcome::NotSubscribed));
+            };
+            if chunk.version < list.version {
+                return Ok(Some(BundleOutcome::Stale { current: list.version }));
+            }
+        }
+        chunk.verify()?;
+
+        let (name, maintainer, version) = (chunk.name.clone(), chunk.maintainer, chunk.version);
+        let Some((encoded, chunk_signatures)) = self.assembler.lock().insert(chunk, Instant::now()) else {
+            return Ok(None);
+        };
+        let bundle = FilterListBundle::decode(&encoded)?;
+        if bundle.name != name || bundle.maintainer != maintainer || bundle.version != version {
+            return Err(NonosError::InvalidSignature(format!(
+                "Filter list bundle does not match the chunks of {} version {}", name, version
+            )));
+        }
+        self.apply(&bundle, Some(StoredBundle { encoded, chunk_signatures })).await.map(Some)
+    }
+
+    /// Chunks of the full bundles this node keeps whose turn to be gossiped
+    /// again has come by `now`, so nodes that missed a version or
+    /// subscribed late can catch up.
+    pub async fn due_republications(&self, now: Instant) -> Vec<Vec<FilterListChunkData>> {
+        let lists = self.lists.read().await;
+        let mut republish_at = self.republish_at.lock();
+        let mut due = Vec::new();
+
+        for list in lists.values() {
+            let Some(stored) = &list.full_bundle else {
+                continue;
+            };
+            let next = republish_at.entry(list.name.clone()).or_insert_with(|| now + jittered(REPUBLISH_INTERVAL));
+            if now < *next {
+                continue;
+            }
+            *next = now + jittered(REPUBLISH_INTERVAL);
+
+            match FilterListBundle::decode(&stored.encoded) {
+                Ok(bundle) => due.push(bundle.chunks_of(&stored.encoded, &stored.chunk_signatures)),
+                Err(e) => warn!("Cannot republish filter list {}: {}", list.name, e),
+            }
+        }
+        due
+    }
+
+    /// Puts off gossiping list `name` again after a full copy of it went by.
+    fn defer_republish(&self, name: &str) {
+        self.republish_at.lock().insert(name.to_string(), Instant::now() + jittered(REPUBLISH_INTERVAL));
+    }
+
+    /// Drops partly received bundles that have stalled.
+    pub fn expire_pending(&self) {
+        self.assembler.lock().expire(Instant::now());
+    }
+}
+
+fn jittered(interval: Duration) -> Duration {
+    interval + interval.mul_f64(rand::thread_rng().gen_range(0.0..0.25))
+}
+
+fn list_key(name: &str) -> String {
+    format!("{}{}", SUBSCRIPTION_LIST_PREFIX, name)
+}
+
+fn validate_name(name: &str) -> NonosResult<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_LIST_NAME_LEN
+        && name.bytes().all(|c| c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b'.'));
+    if !valid {
+        return Err(NonosError::Config(format!("Invalid filter list name: {:?}", name)));
+    }
+    Ok(())
+}
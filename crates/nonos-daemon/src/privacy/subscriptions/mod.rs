@@ -0,0 +1,27 @@
+//! Filter-list subscriptions distributed over gossipsub.
+//!
+//! A maintainer publishes each version of a list as a [`FilterListBundle`]
+//! signed with their Ed25519 key, carrying either the whole list or a diff
+//! against the previous version. Bundles are split into chunks to fit the
+//! gossip message limit, each carrying the maintainer's signature over the
+//! bundle's header so forged chunks are rejected at gossip validation.
+//! Nodes follow a list by name and maintainer key, verify and persist each
+//! newer version, and hot-swap it into the tracking blocker.
+//!
+//! Nodes keep the latest full bundle of each list and gossip it again
+//! every [`REPUBLISH_INTERVAL`] or so, which is how nodes that missed a
+//! diff, or subscribed late, catch up. Maintainers publishing a diff hand
+//! their own node a full bundle of the same version to start this off.
+
+mod assembler;
+mod bundle;
+mod manager;
+
+pub use assembler::ChunkAssembler;
+pub use bundle::{canonical_rules, rules_hash, BundleBody, FilterListBundle, CHUNK_BYTES, MAX_BUNDLE_BYTES};
+pub use manager::{
+    BundleOutcome, FilterListSubscriptions, SubscriptionInfo, REPUBLISH_INTERVAL, SUBSCRIPTION_LIST_PREFIX,
+};
+
+#[cfg(test)]
+mod tests;
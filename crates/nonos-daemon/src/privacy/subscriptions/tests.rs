@@ -0,0 +1,253 @@
+use super::*;
+use crate::privacy::TrackingBlockerService;
+use crate::storage::NodeStorage;
+use nonos_crypto::generate_ed25519_keypair;
+use nonos_types::{Ed25519PrivateKey, NodeId};
+use std::sync::Arc;
+use std::time::Instant;
+
+async fn ingest_all(subs: &FilterListSubscriptions, chunks: Vec<crate::p2p::FilterListChunkData>) -> Option<BundleOutcome> {
+    let mut outcome = None;
+    for chunk in chunks {
+        outcome = subs.ingest_chunk(chunk).await.unwrap();
+    }
+    outcome
+}
+
+const V1: &str = "||tracker.example^\n||ads.example^\n";
+const V2: &str = "||tracker.example^\n||pixel.example^\n";
+
+fn full(name: &str, version: u64, rules: &str, key: &Ed25519PrivateKey) -> FilterListBundle {
+    let body = BundleBody::Full { rules: rules.to_string() };
+    FilterListBundle::sign(name, version, body, rules_hash(&canonical_rules(rules)), key).unwrap()
+}
+
+fn subscriptions(storage: Option<Arc<NodeStorage>>) -> (Arc<TrackingBlockerService>, FilterListSubscriptions) {
+    let blocker = Arc::new(TrackingBlockerService::new(NodeId::from_bytes(rand::random())));
+    let subs = match storage {
+        Some(storage) => FilterListSubscriptions::with_storage(blocker.clone(), storage),
+        None => FilterListSubscriptions::new(blocker.clone()),
+    };
+    (blocker, subs)
+}
+
+#[test]
+fn test_bundle_signature() {
+    let (key, _) = generate_ed25519_keypair();
+    let bundle = full("community", 1, V1, &key);
+    assert!(bundle.verify().is_ok());
+
+    let mut tampered = bundle.clone();
+    tampered.version = 2;
+    assert!(tampered.verify().is_err());
+
+    let mut tampered = bundle.clone();
+    tampered.body = BundleBody::Full { rules: V2.to_string() };
+    assert!(tampered.verify().is_err());
+
+    let (other, _) = generate_ed25519_keypair();
+    let mut forged = full("community", 1, V1, &other);
+    forged.maintainer = bundle.maintainer;
+    assert!(forged.verify().is_err());
+}
+
+#[test]
+fn test_diff_round_trip() {
+    let body = BundleBody::diff(1, V1, V2);
+    let v1 = canonical_rules(V1);
+    let v2 = body.apply(&v1, 1).unwrap();
+    assert_eq!(v2, canonical_rules(V2));
+    assert_eq!(rules_hash(&v2), rules_hash(&canonical_rules(V2)));
+
+    assert!(body.apply(&v1, 2).is_err());
+}
+
+#[test]
+fn test_chunks_reassemble() {
+    let (key, _) = generate_ed25519_keypair();
+    let rules: String = (0..20_000).map(|i| format!("||tracker{}.example^\n", i)).collect();
+    let bundle = full("big", 1, &rules, &key);
+    let mut chunks = bundle.to_chunks(&key).unwrap();
+    assert!(chunks.len() > 1);
+    assert!(chunks.iter().all(|chunk| chunk.data.len() <= CHUNK_BYTES));
+
+    chunks.reverse();
+    let mut assembler = ChunkAssembler::new();
+    let now = Instant::now();
+    let last = chunks.pop().unwrap();
+    for chunk in chunks.clone() {
+        assert!(assembler.insert(chunk, now).is_none());
+    }
+    // Duplicates are ignored while pending.
+    assert!(assembler.insert(chunks[0].clone(), now).is_none());
+    let (bytes, signatures) = assembler.insert(last.clone(), now).unwrap();
+    assert_eq!(signatures.len(), chunks.len() + 1);
+    assert_eq!(signatures[0], last.signature);
+    let decoded = FilterListBundle::decode(&bytes).unwrap();
+    assert_eq!(decoded.body, bundle.body);
+    assert!(decoded.verify().is_ok());
+
+    // A completed bundle is not reassembled again.
+    assert!(assembler.insert(last, now).is_none());
+    assert_eq!(assembler.pending(), 0);
+}
+
+#[test]
+fn test_assembler_rejects_bad_chunks() {
+    let (key, _) = generate_ed25519_keypair();
+    let mut chunk = full("small", 1, V1, &key).to_chunks(&key).unwrap().remove(0);
+    let mut assembler = ChunkAssembler::new();
+    let now = Instant::now();
+
+    let mut out_of_range = chunk.clone();
+    out_of_range.index = 1;
+    assert!(assembler.insert(out_of_range, now).is_none());
+    assert_eq!(assembler.pending(), 0);
+
+    let mut short = chunk.clone();
+    short.total = 2;
+    assert!(assembler.insert(short, now).is_none());
+    assert_eq!(assembler.pending(), 0);
+
+    chunk.data[0] ^= 1;
+    assert!(assembler.insert(chunk, now).is_none());
+}
+
+#[tokio::test]
+async fn test_subscription_applies_bundles() {
+    let (key, maintainer) = generate_ed25519_keypair();
+    let (blocker, subs) = subscriptions(None);
+
+    let v1 = full("community", 1, V1, &key);
+    assert_eq!(subs.apply_bundle(&v1).await.unwrap(), BundleOutcome::NotSubscribed);
+
+    subs.subscribe("community", maintainer).await.unwrap();
+    assert_eq!(subs.apply_bundle(&v1).await.unwrap(), BundleOutcome::Applied { version: 1 });
+    assert!(blocker.should_block_url("https://ads.example/x.js").await.0);
+    assert_eq!(subs.apply_bundle(&v1).await.unwrap(), BundleOutcome::Stale { current: 1 });
+
+    let v2 = BundleBody::diff(1, V1, V2);
+    let v2 = FilterListBundle::sign("community", 2, v2, rules_hash(&canonical_rules(V2)), &key).unwrap();
+    assert_eq!(subs.apply_bundle(&v2).await.unwrap(), BundleOutcome::Applied { version: 2 });
+    assert!(!blocker.should_block_url("https://ads.example/x.js").await.0);
+    assert!(blocker.should_block_url("https://pixel.example/p.gif").await.0);
+
+    // Another key's bundle for the same list is not taken.
+    let (other, _) = generate_ed25519_keypair();
+    let v3 = full("community", 3, "||example.org^", &other);
+    assert_eq!(subs.apply_bundle(&v3).await.unwrap(), BundleOutcome::NotSubscribed);
+
+    assert!(subs.unsubscribe("community").await.unwrap());
+    assert!(!blocker.should_block_url("https://pixel.example/p.gif").await.0);
+    assert!(subs.subscribe("bad name", maintainer).await.is_err());
+}
+
+#[tokio::test]
+async fn test_subscription_rejects_wrong_content() {
+    let (key, maintainer) = generate_ed25519_keypair();
+    let (_, subs) = subscriptions(None);
+    subs.subscribe("community", maintainer).await.unwrap();
+
+    let body = BundleBody::Full { rules: V1.to_string() };
+    let wrong = FilterListBundle::sign("community", 1, body, rules_hash(V2), &key).unwrap();
+    assert!(subs.apply_bundle(&wrong).await.is_err());
+
+    // A diff against a version we do not have fails too.
+    let body = BundleBody::diff(4, V1, V2);
+    let diff = FilterListBundle::sign("community", 5, body, rules_hash(&canonical_rules(V2)), &key).unwrap();
+    assert!(subs.apply_bundle(&diff).await.is_err());
+    assert_eq!(subs.subscriptions().await[0].version, 0);
+}
+
+#[tokio::test]
+async fn test_subscriptions_persist() {
+    let storage = Arc::new(NodeStorage::in_memory().unwrap());
+    let (key, maintainer) = generate_ed25519_keypair();
+    {
+        let (_, subs) = subscriptions(Some(storage.clone()));
+        subs.subscribe("community", maintainer).await.unwrap();
+        subs.ingest_chunk(full("community", 1, V1, &key).to_chunks(&key).unwrap().remove(0)).await.unwrap();
+    }
+
+    let (blocker, subs) = subscriptions(Some(storage));
+    assert_eq!(subs.restore().await.unwrap(), 1);
+    let info = subs.subscriptions().await;
+    assert_eq!(info[0].version, 1);
+    assert_eq!(info[0].rules, 2);
+    assert_eq!(info[0].maintainer, maintainer.to_hex());
+    assert!(blocker.should_block_url("https://tracker.example/").await.0);
+}
+
+#[tokio::test]
+async fn test_chunks_must_be_signed_by_maintainer() {
+    let (key, maintainer) = generate_ed25519_keypair();
+    let (other, _) = generate_ed25519_keypair();
+    let bundle = full("community", 1, V1, &key);
+    assert!(bundle.to_chunks(&other).is_err());
+
+    let chunk = bundle.to_chunks(&key).unwrap().remove(0);
+    assert!(chunk.verify().is_ok());
+
+    let mut unsigned = chunk.clone();
+    unsigned.signature.clear();
+    assert!(unsigned.verify().is_err());
+
+    // Moving a signature to another bundle ID breaks it.
+    let mut forged = chunk.clone();
+    forged.bundle_id[0] ^= 1;
+    assert!(forged.verify().is_err());
+
+    // So does a valid header relayed with other data, which would
+    // otherwise take the chunk's place and block the bundle.
+    let mut garbage = chunk.clone();
+    garbage.data[0] ^= 1;
+    assert!(garbage.verify().is_err());
+
+    let (_, subs) = subscriptions(None);
+    assert_eq!(subs.ingest_chunk(chunk.clone()).await.unwrap(), Some(BundleOutcome::NotSubscribed));
+    subs.subscribe("community", maintainer).await.unwrap();
+    assert!(subs.ingest_chunk(unsigned).await.is_err());
+    assert!(subs.ingest_chunk(forged).await.is_err());
+    assert!(subs.ingest_chunk(garbage).await.is_err());
+    assert_eq!(
+        subs.ingest_chunk(chunk).await.unwrap(),
+        Some(BundleOutcome::Applied { version: 1 })
+    );
+}
+
+#[tokio::test]
+async fn test_late_subscriber_catches_up() {
+    let (key, maintainer) = generate_ed25519_keypair();
+    let (_, early) = subscriptions(None);
+    early.subscribe("community", maintainer).await.unwrap();
+
+    let v1 = full("community", 1, V1, &key);
+    ingest_all(&early, v1.to_chunks(&key).unwrap()).await;
+    let body = BundleBody::diff(1, V1, V2);
+    let v2 = FilterListBundle::sign("community", 2, body, rules_hash(&canonical_rules(V2)), &key).unwrap();
+    assert_eq!(
+        ingest_all(&early, v2.to_chunks(&key).unwrap()).await,
+        Some(BundleOutcome::Applied { version: 2 })
+    );
+    // Only a full copy of the current version is passed on.
+    let later = Instant::now() + REPUBLISH_INTERVAL * 2;
+    assert!(early.due_republications(later).await.is_empty());
+
+    let v2_full = full("community", 2, V2, &key);
+    assert_eq!(
+        ingest_all(&early, v2_full.to_chunks(&key).unwrap()).await,
+        Some(BundleOutcome::Stale { current: 2 })
+    );
+    assert!(early.due_republications(Instant::now()).await.is_empty());
+    let due = early.due_republications(later).await;
+    assert_eq!(due.len(), 1);
+    assert!(early.due_republications(later).await.is_empty());
+
+    let (blocker, late) = subscriptions(None);
+    late.subscribe("community", maintainer).await.unwrap();
+    assert_eq!(
+        ingest_all(&late, due.into_iter().next().unwrap()).await,
+        Some(BundleOutcome::Applied { version: 2 })
+    );
+    assert!(blocker.should_block_url("https://pixel.example/p.gif").await.0);
+}
@@ -1,6 +1,9 @@
-use nonos_crypto::poseidon_hash;
-use nonos_types::{NodeId, NonosResult};
-use std::collections::HashSet;
+use super::filters::{
+    host_suffixes, FilterDecision, FilterEngine, FilterListStats, FilterRequest, RequestType, BUILTIN_RULES,
+};
+use nonos_types::{NodeId, NonosError, NonosResult};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -8,40 +11,23 @@ use tokio::sync::RwLock;
 use tokio::time::interval;
 use tracing::{debug, info};
 
-const KNOWN_TRACKERS: &[&str] = &[
-    "google-analytics.com", "googletagmanager.com", "facebook.com",
-    "connect.facebook.net", "doubleclick.net", "googlesyndication.com",
-    "googleadservices.com", "amazon-adsystem.com", "scorecardresearch.com",
-    "quantserve.com", "adsrvr.org", "criteo.com", "taboola.com",
-    "outbrain.com", "chartbeat.com", "mixpanel.com", "segment.io",
-    "amplitude.com", "hotjar.com", "fullstory.com", "mouseflow.com",
-    "crazyegg.com", "clarity.ms", "newrelic.com", "sentry.io",
-];
-
-const TRACKING_PATTERNS: &[&str] = &[
-    "google-analytics.com", "analytics.google.com", "/ga.js", "/gtag/",
-    "gtm.js", "analytics.js", "facebook.com/tr", "fbevents.js",
-    "connect.facebook", "pixel.facebook", "doubleclick.net",
-    "googlesyndication", "googleadservices", "adservice.google", "pagead",
-    "hotjar.com", "fullstory.com", "mouseflow.com", "clarity.ms",
-    "crazyegg.com", "logrocket.com", "hubspot.com", "marketo.com",
-    "pardot.com", "eloqua.com", "fingerprint", "fp.js", "fpjs",
-];
-
-const TRACKING_PARAMS: &[&str] = &[
-    "utm_source", "utm_medium", "utm_campaign", "utm_term", "utm_content",
-    "fbclid", "fb_action_ids", "fb_action_types", "fb_source", "fb_ref",
-    "gclid", "gclsrc", "dclid", "msclkid", "twclid", "mc_cid", "mc_eid",
-    "_hsenc", "_hsmi", "hsCtaTracking", "oly_enc_id", "oly_anon_id",
-    "vero_id", "nr_email_referer", "mkt_tok", "trk_contact", "trk_msg",
-];
+/// Name the built-in rules are listed under.
+pub const BUILTIN_LIST: &str = "builtin";
 
+/// Rule text that marks a filter as aimed at fingerprinting scripts, for
+/// the fingerprinting counter.
+const FINGERPRINTING_MARKERS: &[&str] = &["fingerprint", "fpjs", "fpcdn"];
+
+/// Blocks tracker requests and strips tracking parameters using filter
+/// lists in Adblock Plus / uBlock Origin syntax. Lists can be loaded and
+/// replaced at runtime; domains blocked or unblocked by hand take
+/// precedence over them.
 pub struct TrackingBlockerService {
     _node_id: NodeId,
-    blocked_domains: Arc<RwLock<HashSet<[u8; 32]>>>,
-    blocked_domain_strings: Arc<RwLock<HashSet<String>>>,
-    blocked_patterns: Arc<RwLock<Vec<String>>>,
-    blocked_params: Arc<RwLock<HashSet<String>>>,
+    lists: RwLock<BTreeMap<String, String>>,
+    engine: RwLock<Arc<FilterEngine>>,
+    blocked_domains: Arc<RwLock<HashSet<String>>>,
+    allowed_domains: Arc<RwLock<HashSet<String>>>,
     requests_blocked: AtomicU64,
     total_requests: AtomicU64,
     fingerprint_blocked: AtomicU64,
@@ -49,110 +35,146 @@ pub struct TrackingBlockerService {
 
 impl TrackingBlockerService {
     pub fn new(node_id: NodeId) -> Self {
-        let mut blocked_hashes = HashSet::new();
-        let mut blocked_strings = HashSet::new();
-
-        for domain in KNOWN_TRACKERS {
-            blocked_hashes.insert(poseidon_hash(domain.as_bytes()));
-            blocked_strings.insert(domain.to_string());
-        }
-
-        let patterns: Vec<String> = TRACKING_PATTERNS.iter().map(|s| s.to_string()).collect();
-        let params: HashSet<String> = TRACKING_PARAMS.iter().map(|s| s.to_string()).collect();
+        let mut lists = BTreeMap::new();
+        lists.insert(BUILTIN_LIST.to_string(), BUILTIN_RULES.to_string());
 
         Self {
             _node_id: node_id,
-            blocked_domains: Arc::new(RwLock::new(blocked_hashes)),
-            blocked_domain_strings: Arc::new(RwLock::new(blocked_strings)),
-            blocked_patterns: Arc::new(RwLock::new(patterns)),
-            blocked_params: Arc::new(RwLock::new(params)),
+            engine: RwLock::new(Arc::new(FilterEngine::compile([BUILTIN_RULES]))),
+            lists: RwLock::new(lists),
+            blocked_domains: Arc::new(RwLock::new(HashSet::new())),
+            allowed_domains: Arc::new(RwLock::new(HashSet::new())),
             requests_blocked: AtomicU64::new(0),
             total_requests: AtomicU64::new(0),
             fingerprint_blocked: AtomicU64::new(0),
         }
     }
 
-    pub async fn should_block_domain(&self, domain: &str) -> bool {
-        let domain_lower = domain.to_lowercase();
-        self.total_requests.fetch_add(1, Ordering::Relaxed);
+    /// Loads the filter list at `path`, replacing any list previously
+    /// loaded from it.
+    pub async fn load_filter_list(&self, path: impl AsRef<Path>) -> NonosResult<FilterListStats> {
+        let path = path.as_ref();
+        let rules = tokio::fs::read_to_string(path).await
+            .map_err(|e| NonosError::Storage(format!("Failed to read filter list {}: {}", path.display(), e)))?;
+        let stats = self.set_filter_list(&path.display().to_string(), rules).await;
+        info!(
+            "Loaded filter list {}: {} blocking, {} exceptions, {} removeparam, {} skipped",
+            path.display(), stats.blocking, stats.exceptions, stats.removeparam,
+            stats.cosmetic_skipped + stats.unsupported_skipped,
+        );
+        Ok(stats)
+    }
 
-        let domain_hash = poseidon_hash(domain_lower.as_bytes());
-        if self.blocked_domains.read().await.contains(&domain_hash) {
-            self.requests_blocked.fetch_add(1, Ordering::Relaxed);
-            return true;
-        }
+    /// Adds or replaces the list called `name` and recompiles. Returns the
+    /// stats for that list alone.
+    pub async fn set_filter_list(&self, name: &str, rules: String) -> FilterListStats {
+        let stats = FilterEngine::compile([rules.as_str()]).stats();
+        self.lists.write().await.insert(name.to_string(), rules);
+        self.recompile().await;
+        stats
+    }
 
-        for blocked in self.blocked_domain_strings.read().await.iter() {
-            if domain_lower.contains(blocked) || blocked.contains(&domain_lower) {
-                self.requests_blocked.fetch_add(1, Ordering::Relaxed);
-                return true;
-            }
+    pub async fn remove_filter_list(&self, name: &str) -> bool {
+        let removed = self.lists.write().await.remove(name).is_some();
+        if removed {
+            self.recompile().await;
         }
+        removed
+    }
 
-        for pattern in self.blocked_patterns.read().await.iter() {
-            if domain_lower.contains(&pattern.to_lowercase()) {
-                self.requests_blocked.fetch_add(1, Ordering::Relaxed);
-                return true;
-            }
+    pub async fn filter_lists(&self) -> Vec<String> {
+        self.lists.read().await.keys().cloned().collect()
+    }
+
+    async fn recompile(&self) {
+        let lists: Vec<String> = self.lists.read().await.values().cloned().collect();
+        let engine = tokio::task::spawn_blocking(move || {
+            FilterEngine::compile(lists.iter().map(String::as_str))
+        }).await;
+
+        match engine {
+            Ok(engine) => *self.engine.write().await = Arc::new(engine),
+            Err(e) => tracing::error!("Filter compilation failed: {}", e),
         }
+    }
 
-        false
+    pub async fn should_block_domain(&self, domain: &str) -> bool {
+        self.total_requests.fetch_add(1, Ordering::Relaxed);
+        let Ok(request) = FilterRequest::for_domain(domain) else {
+            return false;
+        };
+        self.decide(&request).await.is_blocked()
     }
 
+    /// Checks `url` with no page context, as a third-party request of an
+    /// unknown type.
     pub async fn should_block_url(&self, url: &str) -> (bool, Option<String>) {
-        let url_lower = url.to_lowercase();
-        self.total_requests.fetch_add(1, Ordering::Relaxed);
+        self.should_block_request(url, None, RequestType::Other).await
+    }
 
-        for pattern in self.blocked_patterns.read().await.iter() {
-            if url_lower.contains(&pattern.to_lowercase()) {
-                self.requests_blocked.fetch_add(1, Ordering::Relaxed);
-                return (true, Some(format!("Matched: {}", pattern)));
-            }
+    /// Checks a request for `url` of type `request_type` made by the page
+    /// at `source_url`. Returns whether to block it and the deciding rule.
+    pub async fn should_block_request(
+        &self,
+        url: &str,
+        source_url: Option<&str>,
+        request_type: RequestType,
+    ) -> (bool, Option<String>) {
+        self.total_requests.fetch_add(1, Ordering::Relaxed);
+        let Ok(request) = FilterRequest::new(url, source_url, request_type) else {
+            return (false, None);
+        };
+
+        match self.decide(&request).await {
+            FilterDecision::Block { rule } => (true, Some(format!("Matched: {}", rule))),
+            FilterDecision::Exception { rule } => (false, Some(format!("Allowed by: {}", rule))),
+            FilterDecision::Allow => (false, None),
         }
+    }
 
-        if url_lower.contains("fingerprint") ||
-           (url_lower.contains("canvas") && url_lower.contains("font")) ||
-           (url_lower.contains("webgl") && url_lower.contains("render")) {
-            self.fingerprint_blocked.fetch_add(1, Ordering::Relaxed);
-            return (true, Some("Fingerprinting detected".to_string()));
+    async fn decide(&self, request: &FilterRequest) -> FilterDecision {
+        let host = request.hostname();
+        if let Some(domain) = matching_domain(&*self.allowed_domains.read().await, host) {
+            return FilterDecision::Exception { rule: format!("@@||{}^", domain) };
+        }
+        if let Some(domain) = matching_domain(&*self.blocked_domains.read().await, host) {
+            self.requests_blocked.fetch_add(1, Ordering::Relaxed);
+            return FilterDecision::Block { rule: format!("||{}^", domain) };
         }
 
-        (false, None)
+        let decision = self.engine.read().await.check(request);
+        if let FilterDecision::Block { rule } = &decision {
+            self.requests_blocked.fetch_add(1, Ordering::Relaxed);
+            let rule = rule.to_ascii_lowercase();
+            if FINGERPRINTING_MARKERS.iter().any(|marker| rule.contains(marker)) {
+                self.fingerprint_blocked.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        decision
     }
 
     pub async fn strip_tracking_params(&self, url: &str) -> String {
-        if let Some(query_start) = url.find('?') {
-            let base = &url[..query_start];
-            let query = &url[query_start + 1..];
-            let blocked_params = self.blocked_params.read().await;
-
-            let cleaned: Vec<&str> = query.split('&')
-                .filter(|param| {
-                    param.split('=').next().map_or(true, |key| !blocked_params.contains(key))
-                })
-                .collect();
-
-            if cleaned.is_empty() {
-                base.to_string()
-            } else {
-                format!("{}?{}", base, cleaned.join("&"))
-            }
-        } else {
-            url.to_string()
-        }
+        let Ok(request) = FilterRequest::new(url, None, RequestType::Document) else {
+            return url.to_string();
+        };
+        self.engine.read().await
+            .strip_params(&request)
+            .unwrap_or_else(|| url.to_string())
     }
 
     pub async fn block_domain(&self, domain: &str) {
         let domain_lower = domain.to_lowercase();
-        self.blocked_domains.write().await.insert(poseidon_hash(domain_lower.as_bytes()));
-        self.blocked_domain_strings.write().await.insert(domain_lower);
+        self.allowed_domains.write().await.remove(&domain_lower);
+        self.blocked_domains.write().await.insert(domain_lower);
         info!("Added to blocklist: {}", domain);
     }
 
+    /// Removes `domain` from the manual blocklist and exempts it, and its
+    /// subdomains, from the filter lists.
     pub async fn unblock_domain(&self, domain: &str) {
         let domain_lower = domain.to_lowercase();
-        self.blocked_domains.write().await.remove(&poseidon_hash(domain_lower.as_bytes()));
-        self.blocked_domain_strings.write().await.remove(&domain_lower);
+        self.blocked_domains.write().await.remove(&domain_lower);
+        self.allowed_domains.write().await.insert(domain_lower);
         info!("Removed from blocklist: {}", domain);
     }
 
@@ -166,14 +188,17 @@ impl TrackingBlockerService {
 
     pub async fn detailed_stats(&self) -> TrackingBlockerStats {
         let (blocked, total, fingerprint) = self.stats();
+        let filters = self.engine.read().await.stats();
         TrackingBlockerStats {
             requests_blocked: blocked,
             total_requests: total,
             fingerprint_blocked: fingerprint,
             block_rate: if total > 0 { (blocked as f64 / total as f64) * 100.0 } else { 0.0 },
-            blocked_domains_count: self.blocked_domain_strings.read().await.len(),
-            blocked_patterns_count: self.blocked_patterns.read().await.len(),
-            blocked_params_count: self.blocked_params.read().await.len(),
+            blocked_domains_count: self.blocked_domains.read().await.len(),
+            blocked_patterns_count: filters.blocking,
+            blocked_params_count: filters.removeparam,
+            exception_rules_count: filters.exceptions,
+            filter_lists_count: self.lists.read().await.len(),
         }
     }
 
@@ -193,6 +218,11 @@ impl TrackingBlockerService {
     }
 }
 
+/// The entry in `domains` that `host` is, or is a subdomain of.
+fn matching_domain<'a>(domains: &'a HashSet<String>, host: &str) -> Option<&'a str> {
+    host_suffixes(host).find_map(|suffix| domains.get(suffix).map(String::as_str))
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct TrackingBlockerStats {
     pub requests_blocked: u64,
@@ -202,4 +232,6 @@ pub struct TrackingBlockerStats {
     pub blocked_domains_count: usize,
     pub blocked_patterns_count: usize,
     pub blocked_params_count: usize,
+    pub exception_rules_count: usize,
+    pub filter_lists_count: usize,
 }
@@ -42,6 +42,10 @@ impl MerkleTree {
         self.leaves.push(commitment);
     }
 
+    pub fn truncate(&mut self, len: usize) {
+        self.leaves.truncate(len);
+    }
+
     pub fn leaf_count(&self) -> usize {
         self.leaves.len()
     }
@@ -15,6 +15,8 @@ mod tests {
     use ark_bn254::Fr;
     use hash::poseidon_hash_native;
     use merkle::MerkleTree;
+    use crate::storage::NodeStorage;
+    use std::sync::Arc;
 
     #[test]
     fn test_poseidon_native() {
@@ -96,4 +98,31 @@ mod tests {
         let valid2 = system.verify_and_record(&proof).await.unwrap();
         assert!(!valid2, "Double-use should be rejected");
     }
+
+    #[tokio::test]
+    async fn test_state_survives_restart() {
+        let storage = Arc::new(NodeStorage::in_memory().unwrap());
+        let system = ZkCredentialSystem::with_storage(storage.clone()).unwrap();
+
+        let first = system
+            .issue_credential([1u8; 32], ZkCredentialType::Identity, 0)
+            .await
+            .unwrap();
+        system
+            .issue_credential([2u8; 32], ZkCredentialType::RegionVerification, 0)
+            .await
+            .unwrap();
+        let root = system.merkle_root().await;
+        drop(system);
+
+        let restored = ZkCredentialSystem::with_storage(storage).unwrap();
+        assert_eq!(restored.credential_count().await, 2);
+        assert_eq!(restored.merkle_root().await, root);
+
+        let next = restored
+            .issue_credential([3u8; 32], ZkCredentialType::Identity, 0)
+            .await
+            .unwrap();
+        assert_eq!(next.issuer_commitment, first.issuer_commitment);
+    }
 }
@@ -10,11 +10,17 @@ use tracing::{debug, info, warn};
 
 use nonos_types::{NonosError, NonosResult};
 
+use crate::storage::{NodeStorage, PrivacyTree};
+
 use super::circuit::CredentialCircuit;
 use super::hash::{blake3_hash_32, bytes_to_field, field_to_bytes, poseidon_hash_native};
 use super::merkle::MerkleTree;
 use super::types::{ZkCredential, ZkCredentialProof, ZkCredentialType, ZkPublicInputs};
 
+const MAX_ACCEPTED_ROOTS: usize = 256;
+/// Secrets key for the persisted issuer secret.
+const ISSUER_SECRET: &str = "zk_credential_issuer";
+
 pub struct ZkCredentialSystem {
     proving_key: Option<ProvingKey<Bn254>>,
     verifying_key: Option<VerifyingKey<Bn254>>,
@@ -24,6 +30,7 @@ pub struct ZkCredentialSystem {
     _issuer_secret: [u8; 32],
     issuer_commitment: [u8; 32],
     initialized: bool,
+    storage: Option<Arc<NodeStorage>>,
 }
 
 impl ZkCredentialSystem {
@@ -39,9 +46,59 @@ impl ZkCredentialSystem {
             _issuer_secret: issuer_secret,
             issuer_commitment,
             initialized: false,
+            storage: None,
         }
     }
 
+    /// Rebuilds the credential tree and nullifier set from `storage` and
+    /// persists every subsequent issuance and recorded nullifier to it. The
+    /// issuer secret is kept in `storage` too, created on first use.
+    pub fn with_storage(storage: Arc<NodeStorage>) -> NonosResult<Self> {
+        let issuer_secret = match storage.load_secret(ISSUER_SECRET)? {
+            Some(bytes) => <[u8; 32]>::try_from(bytes.as_slice())
+                .map_err(|_| NonosError::Storage("Invalid stored credential issuer secret".into()))?,
+            None => {
+                let secret = ark_std::rand::random::<[u8; 32]>();
+                storage.store_secret(ISSUER_SECRET, &secret)?;
+                secret
+            }
+        };
+
+        let state = storage.load_privacy_state(PrivacyTree::ZkCredentials, MAX_ACCEPTED_ROOTS)?;
+
+        let mut tree = MerkleTree::new();
+        for leaf in &state.leaves {
+            let commitment: [u8; 32] = leaf.as_slice().try_into()
+                .map_err(|_| NonosError::Storage("Invalid stored credential commitment".into()))?;
+            tree.insert(commitment);
+        }
+        state.check_root(PrivacyTree::ZkCredentials, &tree.root())?;
+
+        let mut nullifiers = HashMap::with_capacity(state.nullifiers.len());
+        for bytes in &state.nullifiers {
+            if bytes.len() != 40 {
+                return Err(NonosError::Storage("Invalid stored credential nullifier".into()));
+            }
+            let mut nullifier = [0u8; 32];
+            nullifier.copy_from_slice(&bytes[..32]);
+            let mut used_at = [0u8; 8];
+            used_at.copy_from_slice(&bytes[32..]);
+            nullifiers.insert(nullifier, u64::from_be_bytes(used_at));
+        }
+
+        info!(
+            "ZK credential state restored: {} credentials, {} nullifiers",
+            tree.leaf_count(),
+            nullifiers.len()
+        );
+
+        let mut system = Self::new(issuer_secret);
+        system.merkle_tree = Arc::new(RwLock::new(tree));
+        system.nullifiers = Arc::new(RwLock::new(nullifiers));
+        system.storage = Some(storage);
+        Ok(system)
+    }
+
     pub fn initialize(&mut self) -> NonosResult<()> {
         info!("Generating ZK proving/verifying keys (this may take a while)...");
 
@@ -86,7 +143,14 @@ impl ZkCredentialSystem {
         let commitment = field_to_bytes(&commitment_field);
 
         let mut tree = self.merkle_tree.write().await;
+        let index = tree.leaf_count();
         tree.insert(commitment);
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.append_privacy_leaf(PrivacyTree::ZkCredentials, index as u64, &commitment, &tree.root()) {
+                tree.truncate(index);
+                return Err(e);
+            }
+        }
         drop(tree);
 
         let credential = ZkCredential {
@@ -190,6 +254,13 @@ impl ZkCredentialSystem {
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
+
+        if let Some(storage) = &self.storage {
+            let mut bytes = Vec::with_capacity(40);
+            bytes.extend_from_slice(&proof.public_inputs.nullifier);
+            bytes.extend_from_slice(&now.to_be_bytes());
+            storage.append_privacy_nullifier(PrivacyTree::ZkCredentials, &bytes)?;
+        }
         nullifiers.insert(proof.public_inputs.nullifier, now);
 
         debug!("Proof verified and nullifier recorded");
@@ -1,5 +1,6 @@
 use nonos_crypto::{poseidon_hash, random_bytes, compute_identity_commitment};
 use nonos_crypto::zk_proofs::compute_merkle_root;
+use crate::storage::{NodeStorage, PrivacyTree};
 use nonos_types::{NonosError, NonosResult};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -7,6 +8,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 const MERKLE_DEPTH: usize = 20;
+const MAX_STORED_ROOTS: usize = 1;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ZkSessionProof {
@@ -21,6 +23,7 @@ pub struct ZkSessionManager {
     identity_root: Arc<RwLock<[u8; 32]>>,
     used_nullifiers: Arc<RwLock<HashSet<String>>>,
     session_duration_secs: u64,
+    storage: Option<Arc<NodeStorage>>,
 }
 
 impl ZkSessionManager {
@@ -29,9 +32,30 @@ impl ZkSessionManager {
             identity_root: Arc::new(RwLock::new([0u8; 32])),
             used_nullifiers: Arc::new(RwLock::new(HashSet::new())),
             session_duration_secs: 3600,
+            storage: None,
         }
     }
 
+    /// Restores the identity root and used nullifiers from `storage` and
+    /// persists every subsequent change to them.
+    pub fn with_storage(storage: Arc<NodeStorage>) -> NonosResult<Self> {
+        let state = storage.load_privacy_state(PrivacyTree::ZkSessions, MAX_STORED_ROOTS)?;
+
+        let mut used_nullifiers = HashSet::with_capacity(state.nullifiers.len());
+        for bytes in state.nullifiers {
+            let nullifier = String::from_utf8(bytes)
+                .map_err(|_| NonosError::Storage("Invalid stored session nullifier".into()))?;
+            used_nullifiers.insert(nullifier);
+        }
+
+        Ok(Self {
+            identity_root: Arc::new(RwLock::new(state.root.unwrap_or([0u8; 32]))),
+            used_nullifiers: Arc::new(RwLock::new(used_nullifiers)),
+            session_duration_secs: 3600,
+            storage: Some(storage),
+        })
+    }
+
     pub async fn create_session_proof(
         &self,
         identity_secret: &[u8; 32],
@@ -63,6 +87,12 @@ impl ZkSessionManager {
 
         {
             let mut nullifiers = self.used_nullifiers.write().await;
+            if nullifiers.contains(&nullifier_hex) {
+                return Err(NonosError::Crypto("Nullifier already used".into()));
+            }
+            if let Some(storage) = &self.storage {
+                storage.append_privacy_nullifier(PrivacyTree::ZkSessions, nullifier_hex.as_bytes())?;
+            }
             nullifiers.insert(nullifier_hex);
         }
 
@@ -140,16 +170,26 @@ impl ZkSessionManager {
             .map_err(|e| NonosError::Crypto(format!("Proof verification failed: {}", e)))
     }
 
-    pub async fn update_identity_root(&self, new_root: [u8; 32]) {
-        *self.identity_root.write().await = new_root;
+    pub async fn update_identity_root(&self, new_root: [u8; 32]) -> NonosResult<()> {
+        let mut root = self.identity_root.write().await;
+        if let Some(storage) = &self.storage {
+            storage.store_privacy_root(PrivacyTree::ZkSessions, &new_root)?;
+        }
+        *root = new_root;
+        Ok(())
     }
 
     pub async fn get_identity_root(&self) -> [u8; 32] {
         *self.identity_root.read().await
     }
 
-    pub async fn clear_nullifiers(&self) {
-        self.used_nullifiers.write().await.clear();
+    pub async fn clear_nullifiers(&self) -> NonosResult<()> {
+        let mut nullifiers = self.used_nullifiers.write().await;
+        if let Some(storage) = &self.storage {
+            storage.clear_privacy_nullifiers(PrivacyTree::ZkSessions)?;
+        }
+        nullifiers.clear();
+        Ok(())
     }
 
     pub async fn is_nullifier_used(&self, nullifier: &str) -> bool {
@@ -203,4 +243,26 @@ mod tests {
         let proof2 = manager.create_session_proof(&secret, "other.com").await;
         assert!(proof2.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_state_survives_restart() {
+        let storage = Arc::new(NodeStorage::in_memory().unwrap());
+        let manager = ZkSessionManager::with_storage(storage.clone()).unwrap();
+
+        let root = [0x5a; 32];
+        manager.update_identity_root(root).await.unwrap();
+        storage.append_privacy_nullifier(PrivacyTree::ZkSessions, b"used").unwrap();
+        drop(manager);
+
+        let restored = ZkSessionManager::with_storage(storage.clone()).unwrap();
+        assert_eq!(restored.get_identity_root().await, root);
+        assert!(restored.is_nullifier_used("used").await);
+
+        restored.clear_nullifiers().await.unwrap();
+        drop(restored);
+
+        let cleared = ZkSessionManager::with_storage(storage).unwrap();
+        assert_eq!(cleared.nullifier_count().await, 0);
+        assert_eq!(cleared.get_identity_root().await, root);
+    }
 }
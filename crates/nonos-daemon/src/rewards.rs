@@ -1,4 +1,5 @@
 use crate::contracts::{ContractClient, current_epoch as contract_epoch, EPOCH_DURATION_SECS};
+use crate::services::AttestedQuality;
 use nonos_types::{
     EpochNumber, EpochSummary, EthAddress, NodeTier, NonosError, NonosResult, QualityScore,
     RewardClaim, StakeRecord, TokenAmount, NOX_DECIMALS, EMISSION_DECAY_RATE, Blake3Hash,
@@ -116,12 +117,7 @@ impl RewardTracker {
             info!("Auto-claim triggered: {} NOX >= {} NOX threshold",
                   pending.to_decimal(), threshold.to_decimal());
 
-            let epoch = contract_epoch()
-                .map(EpochNumber)
-                .unwrap_or_else(|| {
-                    warn!("Staking contract not configured, using fallback epoch calculation");
-                    EpochNumber(chrono::Utc::now().timestamp() as u64 / EPOCH_DURATION_SECS)
-                });
+            let epoch = current_epoch();
             match self.claim(epoch).await {
                 Ok(claim) => Ok(Some(claim)),
                 Err(e) => {
@@ -134,6 +130,11 @@ impl RewardTracker {
         }
     }
 
+    /// Reward weight of this node's stake; zero while nothing is staked.
+    pub async fn stake_weight(&self) -> f64 {
+        self.stake.read().await.as_ref().map_or(0.0, StakeRecord::weight)
+    }
+
     pub async fn calculate_epoch_reward(
         &self,
         epoch: &EpochSummary,
@@ -160,8 +161,18 @@ impl RewardTracker {
         TokenAmount::from_raw(reward as u128, NOX_DECIMALS)
     }
 
-    pub async fn process_epoch(&self, epoch: &EpochSummary, quality: &QualityScore) {
-        let reward = self.calculate_epoch_reward(epoch, quality).await;
+    /// Credits the reward for `epoch` using the quality other nodes attested
+    /// for this node. Without enough attestations the epoch earns nothing,
+    /// since self-reported quality cannot be trusted.
+    pub async fn process_epoch(&self, epoch: &EpochSummary, attested: Option<&AttestedQuality>) {
+        let quality = match attested.filter(|attested| attested.epoch == epoch.epoch.0) {
+            Some(attested) => attested.quality.clone(),
+            None => {
+                warn!("No peer-attested quality for epoch {}, scoring it as zero", epoch.epoch.0);
+                QualityScore::zero()
+            }
+        };
+        let reward = self.calculate_epoch_reward(epoch, &quality).await;
 
         let mut pending = self.pending_rewards.write().await;
         if let Some(new_pending) = pending.checked_add(&reward) {
@@ -272,6 +283,17 @@ impl Default for RewardTracker {
     }
 }
 
+/// The current staking epoch, falling back to wall-clock epochs when the
+/// staking contract is not configured.
+pub fn current_epoch() -> EpochNumber {
+    contract_epoch()
+        .map(EpochNumber)
+        .unwrap_or_else(|| {
+            debug!("Staking contract not configured, using fallback epoch calculation");
+            EpochNumber(chrono::Utc::now().timestamp() as u64 / EPOCH_DURATION_SECS)
+        })
+}
+
 pub fn calculate_epoch_emission(year: u32, daily_emission_year_1: u64) -> u64 {
     let decay_factor = (1.0 - EMISSION_DECAY_RATE).powi(year.saturating_sub(1) as i32);
     (daily_emission_year_1 as f64 * decay_factor) as u64
@@ -355,6 +377,49 @@ mod tests {
         assert!(!reward.is_zero());
     }
 
+    #[tokio::test]
+    async fn test_process_epoch_uses_attested_quality() {
+        let tracker = RewardTracker::new();
+        tracker.set_stake(StakeRecord {
+            staker: nonos_types::EthAddress::zero(),
+            node_id: None,
+            amount: TokenAmount::from_raw(10_000_000_000_000_000_000_000, NOX_DECIMALS),
+            tier: NodeTier::Silver,
+            lock_start: chrono::Utc::now(),
+            lock_end: chrono::Utc::now() + chrono::Duration::days(30),
+            is_locked: true,
+        }).await;
+
+        let epoch = EpochSummary {
+            epoch: EpochNumber(7),
+            start_time: chrono::Utc::now(),
+            end_time: chrono::Utc::now() + chrono::Duration::hours(24),
+            total_emission: TokenAmount::from_raw(100_000_000_000_000_000_000_000, NOX_DECIMALS),
+            total_weight: 1000.0,
+            staker_count: 100,
+            avg_quality: 0.9,
+        };
+        let attested = |epoch| AttestedQuality {
+            node_id: nonos_types::NodeId::from_bytes([1u8; 32]),
+            epoch,
+            attesters: 3,
+            availability: 1.0,
+            median_latency_ms: Some(20),
+            quality: QualityScore::perfect(),
+        };
+
+        tracker.process_epoch(&epoch, None).await;
+        assert!(tracker.pending_rewards().await.is_zero());
+        assert_eq!(tracker.current_streak().await, 0);
+
+        tracker.process_epoch(&epoch, Some(&attested(6))).await;
+        assert!(tracker.pending_rewards().await.is_zero());
+
+        tracker.process_epoch(&epoch, Some(&attested(7))).await;
+        assert!(!tracker.pending_rewards().await.is_zero());
+        assert_eq!(tracker.current_streak().await, 1);
+    }
+
     #[test]
     fn test_epoch_emission_decay() {
         let year_1 = calculate_epoch_emission(1, 100_000);
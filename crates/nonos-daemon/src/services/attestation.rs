@@ -0,0 +1,329 @@
+//! Peer-attested node quality.
+//!
+//! Nodes probe each other and gossip signed [`LatencyAttestationData`].
+//! Only attesters bound to a staker count, and every node bound to the same
+//! staker counts as one attester, since the stake behind them is the same.
+//! Each staker's results for a target are summarised on their own, and the
+//! target's score is built from stake-weighted medians across stakers, so
+//! stakers holding a minority of the stake cannot move it in either
+//! direction. Nobody attests to nodes bound to their own stake.
+
+use crate::p2p::LatencyAttestationData;
+use crate::privacy::{StakeLookup, VoterBinding};
+use nonos_types::{EthAddress, NodeId, NonosResult, QualityScore};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Distinct stakers needed before a node's score is trusted. Attesters
+/// without stake are not counted and nodes sharing a staker count once, so
+/// throwaway identities cannot meet it.
+pub const MIN_ATTESTERS: usize = 3;
+pub const MAX_PROBES_PER_ATTESTATION: usize = 32;
+/// Latencies above this are treated as this, which already scores zero.
+pub const MAX_ATTESTED_RTT_MS: u32 = 10_000;
+const MAX_RTT_SAMPLES: usize = 64;
+/// Past epochs kept besides the current one, so the previous epoch can
+/// still be settled.
+const RETAINED_EPOCHS: u64 = 1;
+/// Staker weights cached per epoch; lookups beyond this are still made,
+/// just not cached.
+const MAX_CACHED_STAKERS: usize = 4096;
+
+#[derive(Clone, Debug, Default)]
+struct AttesterRecord {
+    weight: f64,
+    probes: u32,
+    answered: u32,
+    rtts_ms: Vec<u32>,
+}
+
+impl AttesterRecord {
+    fn availability(&self) -> f64 {
+        self.answered as f64 / self.probes.max(1) as f64
+    }
+}
+
+/// A node's quality for one epoch as seen by the nodes that probed it.
+#[derive(Clone, Debug, Serialize)]
+pub struct AttestedQuality {
+    pub node_id: NodeId,
+    pub epoch: u64,
+    pub attesters: usize,
+    /// Stake-weighted median over attesters of the share of probes
+    /// answered.
+    pub availability: f64,
+    /// Stake-weighted median over attesters of their median round-trip
+    /// time.
+    pub median_latency_ms: Option<u32>,
+    pub quality: QualityScore,
+}
+
+#[derive(Default)]
+struct EpochAttestations {
+    /// Records per target and attesting staker.
+    targets: HashMap<NodeId, HashMap<EthAddress, AttesterRecord>>,
+    /// The staker each counted attester is bound to.
+    stakers: HashMap<NodeId, EthAddress>,
+}
+
+impl EpochAttestations {
+    /// Records for `node`, without those of the staker it is bound to.
+    fn attesters(&self, node: &NodeId) -> Vec<&AttesterRecord> {
+        let own = self.stakers.get(node);
+        self.targets.get(node).map_or_else(Vec::new, |attesters| {
+            attesters.iter()
+                .filter(|(staker, _)| Some(*staker) != own)
+                .map(|(_, record)| record)
+                .collect()
+        })
+    }
+}
+
+/// Collects latency attestations per epoch, target and staker.
+pub struct QualityAttestations {
+    epochs: RwLock<BTreeMap<u64, EpochAttestations>>,
+    stake: RwLock<Option<Arc<dyn StakeLookup>>>,
+    binding: RwLock<Option<VoterBinding>>,
+    weights: RwLock<HashMap<(EthAddress, u64), f64>>,
+}
+
+impl QualityAttestations {
+    pub fn new() -> Self {
+        Self {
+            epochs: RwLock::new(BTreeMap::new()),
+            stake: RwLock::new(None),
+            binding: RwLock::new(None),
+            weights: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Sets where attester stake is looked up. Until one is set, no
+    /// attestation is counted.
+    pub fn set_stake_lookup(&self, lookup: Arc<dyn StakeLookup>) {
+        *self.stake.write() = Some(lookup);
+    }
+
+    /// Ties this node's attestations, as `node_id`, to the stake `binding`
+    /// authorises; the same binding the node votes with.
+    pub fn set_binding(&self, node_id: &NodeId, binding: VoterBinding) -> NonosResult<()> {
+        binding.verify(node_id)?;
+        *self.binding.write() = Some(binding);
+        Ok(())
+    }
+
+    pub fn binding(&self) -> Option<VoterBinding> {
+        self.binding.read().clone()
+    }
+
+    /// Stake weight `attestation` counts with: that of the staker its
+    /// binding names, or zero without a valid binding or a stake lookup.
+    pub async fn attester_weight(&self, attestation: &LatencyAttestationData) -> f64 {
+        let Some(binding) = &attestation.binding else {
+            return 0.0;
+        };
+        if binding.verify(&attestation.node_id).is_err() {
+            return 0.0;
+        }
+
+        let key = (binding.staker, attestation.epoch);
+        if let Some(weight) = self.weights.read().get(&key) {
+            return *weight;
+        }
+        let Some(lookup) = self.stake.read().clone() else {
+            return 0.0;
+        };
+        let weight = match lookup.voting_weight(&binding.staker).await {
+            Ok(weight) if weight.is_finite() => weight.max(0.0),
+            Ok(_) => 0.0,
+            Err(e) => {
+                debug!("Stake lookup for {} failed: {}", binding.staker, e);
+                return 0.0;
+            }
+        };
+
+        let mut weights = self.weights.write();
+        if weights.len() < MAX_CACHED_STAKERS {
+            weights.insert(key, weight);
+        }
+        weight
+    }
+
+    /// Records the probe results in `attestation`, whose signer and binding
+    /// have already been checked against `attestation.node_id`, under its
+    /// staker with stake `weight`. Unstaked attesters, probes of nodes bound
+    /// to the same staker, repeated targets and attestations outside the
+    /// retained epochs are ignored. Returns the number of probe results
+    /// recorded.
+    pub fn ingest(&self, attestation: &LatencyAttestationData, weight: f64, current_epoch: u64) -> usize {
+        if attestation.epoch > current_epoch || attestation.epoch + RETAINED_EPOCHS < current_epoch {
+            return 0;
+        }
+        if !(weight.is_finite() && weight > 0.0) {
+            return 0;
+        }
+        let Some(staker) = attestation.binding.as_ref().map(|binding| binding.staker) else {
+            return 0;
+        };
+
+        let live = |epoch: u64| epoch + RETAINED_EPOCHS >= current_epoch;
+        self.weights.write().retain(|(_, epoch), _| live(*epoch));
+        let mut epochs = self.epochs.write();
+        epochs.retain(|epoch, _| live(*epoch));
+        let attestations = epochs.entry(attestation.epoch).or_default();
+        attestations.stakers.insert(attestation.node_id, staker);
+
+        let mut seen = HashSet::new();
+        let mut recorded = 0;
+        for probe in attestation.probes.iter().take(MAX_PROBES_PER_ATTESTATION) {
+            if attestations.stakers.get(&probe.target) == Some(&staker) || !seen.insert(probe.target) {
+                continue;
+            }
+
+            let record = attestations.targets.entry(probe.target)
+                .or_default()
+                .entry(staker)
+                .or_default();
+            record.weight = weight;
+            record.probes += 1;
+            if let Some(rtt_ms) = probe.rtt_ms {
+                record.answered += 1;
+                if record.rtts_ms.len() < MAX_RTT_SAMPLES {
+                    record.rtts_ms.push(rtt_ms.min(MAX_ATTESTED_RTT_MS));
+                }
+            }
+            recorded += 1;
+        }
+        recorded
+    }
+
+    /// The attested quality of `node` in `epoch`, or `None` if fewer than
+    /// [`MIN_ATTESTERS`] other stakers have probed it.
+    pub fn quality(&self, node: &NodeId, epoch: u64) -> Option<AttestedQuality> {
+        let epochs = self.epochs.read();
+        let attesters = epochs.get(&epoch)?.attesters(node);
+        if attesters.len() < MIN_ATTESTERS {
+            return None;
+        }
+
+        let availabilities: Vec<(f64, f64)> = attesters.iter()
+            .map(|record| (record.availability(), record.weight))
+            .collect();
+        let latencies: Vec<(f64, f64)> = attesters.iter()
+            .filter_map(|record| {
+                median(record.rtts_ms.iter().map(|&rtt| rtt as f64).collect())
+                    .map(|rtt| (rtt, record.weight))
+            })
+            .collect();
+
+        let availability = weighted_median(availabilities.clone())?;
+        let median_latency_ms = weighted_median(latencies).map(|ms| ms.round() as u32);
+        let deviation = weighted_median(
+            availabilities.iter().map(|&(a, weight)| ((a - availability).abs(), weight)).collect(),
+        )?;
+        let total_weight: f64 = attesters.iter().map(|record| record.weight).sum();
+        let reached: f64 = attesters.iter()
+            .filter(|record| record.answered > 0)
+            .map(|record| record.weight)
+            .sum();
+
+        Some(AttestedQuality {
+            node_id: *node,
+            epoch,
+            attesters: attesters.len(),
+            availability,
+            median_latency_ms,
+            quality: QualityScore {
+                uptime: availability,
+                success_rate: reached / total_weight,
+                latency_score: median_latency_ms.map_or(0.0, latency_score),
+                reliability: (1.0 - deviation).clamp(0.0, 1.0),
+            },
+        })
+    }
+
+    /// Number of distinct stakers, other than its own, that have probed
+    /// `node` in `epoch`.
+    pub fn attester_count(&self, node: &NodeId, epoch: u64) -> usize {
+        self.epochs.read()
+            .get(&epoch)
+            .map_or(0, |attestations| attestations.attesters(node).len())
+    }
+
+    /// Distinct attesting stakers in `epoch` and their combined weight: the
+    /// stake this node saw taking part in the epoch.
+    pub fn epoch_stake(&self, epoch: u64) -> (usize, f64) {
+        let epochs = self.epochs.read();
+        let Some(attestations) = epochs.get(&epoch) else {
+            return (0, 0.0);
+        };
+        let stakers: HashMap<EthAddress, f64> = attestations.targets.values()
+            .flat_map(|attesters| attesters.iter().map(|(staker, record)| (*staker, record.weight)))
+            .collect();
+        (stakers.len(), stakers.values().sum())
+    }
+
+    /// Stake weight `staker` counted with in `epoch`; zero if none of the
+    /// attestations made under it were counted.
+    pub fn attester_stake(&self, staker: &EthAddress, epoch: u64) -> f64 {
+        self.epochs.read()
+            .get(&epoch)
+            .and_then(|attestations| attestations.targets.values().find_map(|attesters| attesters.get(staker)))
+            .map_or(0.0, |record| record.weight)
+    }
+}
+
+impl Default for QualityAttestations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Same curve as the locally measured latency score: full marks below
+/// 100 ms, nothing above one second.
+pub fn latency_score(latency_ms: u32) -> f64 {
+    let ms = latency_ms as f64;
+    if ms < 100.0 {
+        1.0
+    } else if ms > 1000.0 {
+        0.0
+    } else {
+        1.0 - ((ms - 100.0) / 900.0)
+    }
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+/// Median of `(value, weight)` pairs: the value at which half the weight
+/// lies on either side, averaging the two values straddling an exact
+/// half. With equal weights this is the ordinary median.
+fn weighted_median(mut values: Vec<(f64, f64)>) -> Option<f64> {
+    values.retain(|&(_, weight)| weight > 0.0);
+    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let half = values.iter().map(|&(_, weight)| weight).sum::<f64>() / 2.0;
+
+    let mut cumulative = 0.0;
+    for (i, &(value, weight)) in values.iter().enumerate() {
+        cumulative += weight;
+        if cumulative > half {
+            return Some(value);
+        }
+        if cumulative == half {
+            return Some(values.get(i + 1).map_or(value, |&(next, _)| (value + next) / 2.0));
+        }
+    }
+    None
+}
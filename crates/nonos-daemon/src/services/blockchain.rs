@@ -1,5 +1,7 @@
 use crate::http_client::ProxiedHttpClient;
+use async_trait::async_trait;
 use nonos_types::{NonosError, NonosResult};
+use nonos_wallet::{FeeEstimate, FeeOracle, JsonRpcTransport};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::{debug, warn};
@@ -11,6 +13,8 @@ const RPC_ENDPOINTS: &[&str] = &[
     "https://rpc.payload.de",
 ];
 
+const MAINNET_CHAIN_ID: u64 = 1;
+
 pub const NOX_TOKEN_ADDRESS: &str = "0x0a26c80Be4E060e688d7C23aDdB92cBb5D2C9eCA";
 pub const BALANCE_OF_SELECTOR: &str = "70a08231";
 
@@ -34,14 +38,28 @@ pub struct RpcResponse {
 
 pub struct BlockchainService {
     http: Arc<ProxiedHttpClient>,
+    fees: Arc<FeeOracle>,
 }
 
 impl BlockchainService {
     pub fn new(http: Arc<ProxiedHttpClient>) -> Self {
-        Self { http }
+        Self {
+            http,
+            fees: FeeOracle::shared(),
+        }
     }
 
     async fn rpc_call(&self, method: &str, params: serde_json::Value) -> NonosResult<String> {
+        let result = self.rpc_value(method, params).await?;
+        if result.is_null() {
+            return Err(NonosError::Network("All RPC endpoints failed".into()));
+        }
+        Ok(result.as_str().unwrap_or("").to_string())
+    }
+
+    /// Returns the raw `result` of the first endpoint that answers, or
+    /// `Value::Null` if every endpoint that answered returned null.
+    async fn rpc_value(&self, method: &str, params: serde_json::Value) -> NonosResult<serde_json::Value> {
         if !self.http.is_proxy_configured().await {
             return Err(NonosError::Network(
                 "Proxy not configured. All RPC calls must route through Anyone transport.".into(),
@@ -55,6 +73,9 @@ impl BlockchainService {
             "id": 1
         });
 
+        let mut saw_null = false;
+        let mut last_error = None;
+
         for endpoint in RPC_ENDPOINTS {
             debug!("RPC call to {}: {} {:?}", endpoint, method, params);
 
@@ -70,14 +91,16 @@ impl BlockchainService {
 
                     if let Some(error) = json.get("error") {
                         warn!("RPC error from {}: {}", endpoint, error);
+                        last_error = Some(error.to_string());
                         continue;
                     }
 
                     if let Some(result) = json.get("result") {
                         if result.is_null() {
+                            saw_null = true;
                             continue;
                         }
-                        return Ok(result.as_str().unwrap_or("").to_string());
+                        return Ok(result.clone());
                     }
                 }
                 Err(e) => {
@@ -87,7 +110,14 @@ impl BlockchainService {
             }
         }
 
-        Err(NonosError::Network("All RPC endpoints failed".into()))
+        if saw_null {
+            return Ok(serde_json::Value::Null);
+        }
+
+        match last_error {
+            Some(error) => Err(NonosError::Network(format!("RPC error: {}", error))),
+            None => Err(NonosError::Network("All RPC endpoints failed".into())),
+        }
     }
 
     pub async fn eth_call(&self, to: &str, data: &str) -> NonosResult<String> {
@@ -148,6 +178,11 @@ impl BlockchainService {
             .map_err(|e| NonosError::Internal(format!("Failed to parse gas price: {}", e)))
     }
 
+    /// Slow, normal and fast EIP-1559 fees from `eth_feeHistory`.
+    pub async fn get_fee_estimate(&self) -> NonosResult<FeeEstimate> {
+        self.fees.estimate(MAINNET_CHAIN_ID, self).await
+    }
+
     pub async fn estimate_gas(&self, from: &str, to: &str, data: &str) -> NonosResult<u64> {
         let params = serde_json::json!([{
             "from": from,
@@ -176,6 +211,13 @@ impl BlockchainService {
     }
 }
 
+#[async_trait]
+impl JsonRpcTransport for BlockchainService {
+    async fn request(&self, method: &str, params: serde_json::Value) -> NonosResult<serde_json::Value> {
+        self.rpc_value(method, params).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,52 @@
+use crate::privacy::DistributedCookieVault;
+use crate::P2pNetwork;
+use nonos_types::{NonosError, NonosResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{info, warn};
+
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Checks that the holders of this node's vaulted cookies still answer with
+/// valid shares, and re-shares cookies among live nodes when they don't.
+pub struct CookieVaultMaintenance {
+    network: Arc<RwLock<P2pNetwork>>,
+    vault: Arc<DistributedCookieVault>,
+}
+
+impl CookieVaultMaintenance {
+    pub fn new(network: Arc<RwLock<P2pNetwork>>, vault: Arc<DistributedCookieVault>) -> Self {
+        Self { network, vault }
+    }
+
+    pub async fn run(&self, shutdown: Arc<AtomicBool>) -> NonosResult<()> {
+        let client = self.network.read().await.vault_client()
+            .ok_or_else(|| NonosError::Network("P2P network not running".into()))?;
+
+        let mut ticker = interval(MAINTENANCE_INTERVAL);
+        // Peers are learned from gossip, so the first check waits a round.
+        ticker.tick().await;
+        info!("Cookie vault maintenance running");
+
+        loop {
+            ticker.tick().await;
+            if shutdown.load(Ordering::SeqCst) {
+                info!("Cookie vault maintenance shutting down");
+                break;
+            }
+
+            if self.vault.stored_cookie_count().await == 0 {
+                continue;
+            }
+            let refreshed = self.vault.maintain(&client, &client.known_holders()).await;
+            if refreshed > 0 {
+                warn!("Re-shared {} vaulted cookies after losing holders", refreshed);
+            }
+        }
+
+        Ok(())
+    }
+}
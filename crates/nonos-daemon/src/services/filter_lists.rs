@@ -0,0 +1,86 @@
+use crate::p2p::FilterListChunkData;
+use crate::privacy::{BundleOutcome, FilterListSubscriptions};
+use crate::P2pNetwork;
+use nonos_types::{NonosError, NonosResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{debug, info, warn};
+
+const EXPIRE_INTERVAL: Duration = Duration::from_secs(30);
+/// Pause between republished chunks, as the CLI paces a publication.
+const CHUNK_PACING: Duration = Duration::from_millis(100);
+
+/// Feeds filter-list chunks gossiped on the network into the node's
+/// subscriptions, which verify and install completed bundles, and gossips
+/// the full bundles they keep again when due.
+pub struct FilterListSync {
+    network: Arc<RwLock<P2pNetwork>>,
+    subscriptions: Arc<FilterListSubscriptions>,
+}
+
+impl FilterListSync {
+    pub fn new(network: Arc<RwLock<P2pNetwork>>, subscriptions: Arc<FilterListSubscriptions>) -> Self {
+        Self { network, subscriptions }
+    }
+
+    pub async fn run(&self, shutdown: Arc<AtomicBool>) -> NonosResult<()> {
+        let mut incoming = self.network.read().await.take_filter_list_chunks()
+            .ok_or_else(|| NonosError::Network("Filter list stream already taken".into()))?;
+
+        let mut ticker = interval(EXPIRE_INTERVAL);
+        info!("Filter list sync running");
+
+        loop {
+            if shutdown.load(Ordering::SeqCst) {
+                info!("Filter list sync shutting down");
+                break;
+            }
+
+            tokio::select! {
+                chunk = incoming.recv() => {
+                    let Some(chunk) = chunk else {
+                        warn!("Filter list stream closed");
+                        break;
+                    };
+                    match self.subscriptions.ingest_chunk(chunk).await {
+                        Ok(Some(BundleOutcome::Stale { current })) => {
+                            debug!("Ignoring filter list bundle not newer than version {}", current);
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("Rejected filter list bundle: {}", e),
+                    }
+                }
+
+                _ = ticker.tick() => {
+                    self.subscriptions.expire_pending();
+                    for chunks in self.subscriptions.due_republications(Instant::now()).await {
+                        tokio::spawn(republish(self.network.clone(), chunks));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+async fn republish(network: Arc<RwLock<P2pNetwork>>, chunks: Vec<FilterListChunkData>) {
+    let Some(first) = chunks.first() else {
+        return;
+    };
+    debug!("Republishing filter list {} version {}", first.name, first.version);
+    let (name, version) = (first.name.clone(), first.version);
+
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(CHUNK_PACING).await;
+        }
+        if let Err(e) = network.read().await.publish_filter_list_chunk(chunk).await {
+            warn!("Failed to republish filter list {} version {}: {}", name, version, e);
+            return;
+        }
+    }
+}
@@ -1,5 +1,6 @@
+use crate::p2p::{topics, HealthBeaconData, P2pMessage};
 use crate::{NodeMetricsCollector, P2pNetwork};
-use nonos_types::{NodeId, NonosError, NonosResult};
+use nonos_types::{NodeId, NonosResult};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -45,34 +46,21 @@ impl HealthBeacon {
     async fn broadcast_health(&self) -> NonosResult<()> {
         let quality = self.metrics.quality_score();
         let summary = self.metrics.summary();
+        let network = self.network.read().await;
 
-        let health = HealthStatus {
+        let beacon = HealthBeaconData {
             node_id: self.node_id,
             timestamp: chrono::Utc::now(),
-            quality_score: quality.total(),
-            uptime: quality.uptime,
-            success_rate: quality.success_rate,
-            total_requests: summary.total_requests,
-            peer_count: self.network.read().await.peer_count(),
+            uptime_secs: summary.uptime_secs,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            peer_count: network.peer_count(),
+            cpu_usage: Some(summary.cpu_usage as f32),
+            memory_usage: None,
         };
 
-        let message = serde_json::to_vec(&health)
-            .map_err(|e| NonosError::Internal(format!("Serialize error: {}", e)))?;
+        network.publish_message(topics::HEALTH_BEACON, &P2pMessage::HealthBeacon(beacon.clone())).await?;
 
-        self.network.write().await.publish("nonos/health/v1", &message).await?;
-
-        debug!("Broadcast health: quality={:.2}, peers={}", health.quality_score, health.peer_count);
+        debug!("Broadcast health: quality={:.2}, peers={}", quality.total(), beacon.peer_count);
         Ok(())
     }
 }
-
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
-pub struct HealthStatus {
-    pub node_id: NodeId,
-    pub timestamp: chrono::DateTime<chrono::Utc>,
-    pub quality_score: f64,
-    pub uptime: f64,
-    pub success_rate: f64,
-    pub total_requests: u64,
-    pub peer_count: usize,
-}
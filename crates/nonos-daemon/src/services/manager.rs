@@ -1,17 +1,31 @@
-use super::{HealthBeacon, QualityOracle, BootstrapService, CacheService};
-use crate::{NodeMetricsCollector, P2pNetwork, NodeStorage};
+use super::{
+    HealthBeacon, QualityOracle, QualityAttestations, BootstrapService, CacheService, MixnetRelay,
+    FilterListSync, OracleVoteSync, CookieVaultMaintenance, EpochSettlement, PirServer,
+};
+use crate::config::MixingConfig;
+use crate::privacy::{
+    DistributedCookieVault, FilterListSubscriptions, MixnetKeypair, MixnetProcessor, PrivacyOracle,
+    PrivateContentRetrieval, DEFAULT_PIR_PARAMS,
+};
+use crate::{NodeMetricsCollector, P2pNetwork, NodeStorage, RewardTracker};
 use nonos_types::{NodeId, NonosResult};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 use tracing::{error, info};
 
+/// Exited mix payloads held until drained; later ones are dropped.
+const MIX_DELIVERY_QUEUE: usize = 256;
+
 pub struct ServiceManager {
     states: Arc<RwLock<HashMap<ServiceType, ServiceState>>>,
     shutdown: Arc<AtomicBool>,
     handles: Arc<RwLock<Vec<tokio::task::JoinHandle<()>>>>,
+    mixnet: Option<Arc<MixnetProcessor>>,
+    mix_deliveries: Option<mpsc::Receiver<Vec<u8>>>,
+    attestations: Arc<QualityAttestations>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -20,6 +34,12 @@ pub enum ServiceType {
     QualityOracle,
     Bootstrap,
     Cache,
+    Mixnet,
+    FilterLists,
+    OracleVotes,
+    CookieVault,
+    EpochSettlement,
+    Pir,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -37,11 +57,20 @@ impl ServiceManager {
         states.insert(ServiceType::QualityOracle, ServiceState::Stopped);
         states.insert(ServiceType::Bootstrap, ServiceState::Stopped);
         states.insert(ServiceType::Cache, ServiceState::Stopped);
+        states.insert(ServiceType::Mixnet, ServiceState::Stopped);
+        states.insert(ServiceType::FilterLists, ServiceState::Stopped);
+        states.insert(ServiceType::OracleVotes, ServiceState::Stopped);
+        states.insert(ServiceType::CookieVault, ServiceState::Stopped);
+        states.insert(ServiceType::EpochSettlement, ServiceState::Stopped);
+        states.insert(ServiceType::Pir, ServiceState::Stopped);
 
         Self {
             states: Arc::new(RwLock::new(states)),
             shutdown: Arc::new(AtomicBool::new(false)),
             handles: Arc::new(RwLock::new(Vec::new())),
+            mixnet: None,
+            mix_deliveries: None,
+            attestations: Arc::new(QualityAttestations::new()),
         }
     }
 
@@ -65,7 +94,14 @@ impl ServiceManager {
 
         if config.quality_oracle {
             self.start_service(ServiceType::QualityOracle, {
-                let oracle = QualityOracle::new(node_id, metrics.clone(), storage.clone());
+                let oracle = QualityOracle::new(
+                    node_id,
+                    network.clone(),
+                    metrics.clone(),
+                    storage.clone(),
+                    self.attestations.clone(),
+                    config.quality_interval_secs,
+                );
                 let shutdown = self.shutdown.clone();
                 async move { oracle.run(shutdown).await }
             }).await;
@@ -87,9 +123,96 @@ impl ServiceManager {
             }).await;
         }
 
+        if config.mixnet {
+            let processor = Arc::new(MixnetProcessor::with_mixing(
+                MixnetKeypair::generate(),
+                config.mixnet_mixing.clone(),
+            ));
+            self.mixnet = Some(processor.clone());
+            let (delivery_tx, delivery_rx) = mpsc::channel(MIX_DELIVERY_QUEUE);
+            self.mix_deliveries = Some(delivery_rx);
+            self.start_service(ServiceType::Mixnet, {
+                let relay = MixnetRelay::new(
+                    node_id,
+                    network.clone(),
+                    processor,
+                    config.mixnet_announce_interval_secs,
+                    delivery_tx,
+                );
+                let shutdown = self.shutdown.clone();
+                async move { relay.run(shutdown).await }
+            }).await;
+        }
+
         Ok(())
     }
 
+    /// Starts applying filter-list bundles gossiped on `network` to
+    /// `subscriptions`. Separate from [`ServiceManager::start_all`] as the
+    /// subscriptions belong to the privacy services.
+    pub async fn start_filter_list_sync(
+        &mut self,
+        network: Arc<RwLock<P2pNetwork>>,
+        subscriptions: Arc<FilterListSubscriptions>,
+    ) {
+        self.start_service(ServiceType::FilterLists, {
+            let sync = FilterListSync::new(network, subscriptions);
+            let shutdown = self.shutdown.clone();
+            async move { sync.run(shutdown).await }
+        }).await;
+    }
+
+    /// Starts counting privacy oracle votes gossiped on `network` towards
+    /// `oracle`'s consensus.
+    pub async fn start_oracle_vote_sync(
+        &mut self,
+        network: Arc<RwLock<P2pNetwork>>,
+        oracle: Arc<PrivacyOracle>,
+    ) {
+        self.start_service(ServiceType::OracleVotes, {
+            let sync = OracleVoteSync::new(network, oracle);
+            let shutdown = self.shutdown.clone();
+            async move { sync.run(shutdown).await }
+        }).await;
+    }
+
+    /// Starts keeping `vault`'s cookies shared among live nodes on
+    /// `network`.
+    pub async fn start_cookie_vault_maintenance(
+        &mut self,
+        network: Arc<RwLock<P2pNetwork>>,
+        vault: Arc<DistributedCookieVault>,
+    ) {
+        self.start_service(ServiceType::CookieVault, {
+            let maintenance = CookieVaultMaintenance::new(network, vault);
+            let shutdown = self.shutdown.clone();
+            async move { maintenance.run(shutdown).await }
+        }).await;
+    }
+
+    /// Starts serving `content` to PIR clients on `network`.
+    pub async fn start_pir_server(
+        &mut self,
+        network: Arc<RwLock<P2pNetwork>>,
+        content: Arc<PrivateContentRetrieval>,
+    ) {
+        self.start_service(ServiceType::Pir, {
+            let server = PirServer::new(network, content, DEFAULT_PIR_PARAMS);
+            let shutdown = self.shutdown.clone();
+            async move { server.run(shutdown).await }
+        }).await;
+    }
+
+    /// Starts crediting `rewards` for each finished epoch with the quality
+    /// attested for `node_id` in [`ServiceManager::attestations`].
+    pub async fn start_epoch_settlement(&mut self, node_id: NodeId, rewards: Arc<RewardTracker>) {
+        self.start_service(ServiceType::EpochSettlement, {
+            let settlement = EpochSettlement::new(node_id, self.attestations.clone(), rewards);
+            let shutdown = self.shutdown.clone();
+            async move { settlement.run(shutdown).await }
+        }).await;
+    }
+
     async fn start_service<F>(&mut self, service_type: ServiceType, task: F)
     where
         F: std::future::Future<Output = NonosResult<()>> + Send + 'static,
@@ -118,6 +241,9 @@ impl ServiceManager {
             handle.abort();
         }
 
+        self.mixnet = None;
+        self.mix_deliveries = None;
+
         for state in self.states.write().await.values_mut() {
             *state = ServiceState::Stopped;
         }
@@ -125,6 +251,27 @@ impl ServiceManager {
         info!("All services stopped");
     }
 
+    /// The processor behind the mixnet relay, for building packets that
+    /// leave from this node.
+    pub fn mixnet(&self) -> Option<Arc<MixnetProcessor>> {
+        self.mixnet.clone()
+    }
+
+    /// Takes the payloads whose packets exited the mixnet at this node
+    /// since the last call.
+    pub fn drain_mix_deliveries(&mut self) -> Vec<Vec<u8>> {
+        let Some(deliveries) = self.mix_deliveries.as_mut() else {
+            return Vec::new();
+        };
+        std::iter::from_fn(|| deliveries.try_recv().ok()).collect()
+    }
+
+    /// Latency attestations collected by the quality oracle, from which
+    /// peer-attested quality scores are derived.
+    pub fn attestations(&self) -> Arc<QualityAttestations> {
+        self.attestations.clone()
+    }
+
     pub async fn get_state(&self, service: ServiceType) -> ServiceState {
         *self.states.read().await.get(&service).unwrap_or(&ServiceState::Stopped)
     }
@@ -148,6 +295,9 @@ pub struct ServiceConfig {
     pub cache: bool,
     pub bootstrap_port: u16,
     pub cache_size_mb: u32,
+    pub mixnet: bool,
+    pub mixnet_announce_interval_secs: u64,
+    pub mixnet_mixing: MixingConfig,
     pub beacon_interval_secs: u64,
     pub quality_interval_secs: u64,
 }
@@ -161,6 +311,9 @@ impl Default for ServiceConfig {
             cache: false,
             bootstrap_port: 9735,
             cache_size_mb: 1024,
+            mixnet: false,
+            mixnet_announce_interval_secs: 300,
+            mixnet_mixing: MixingConfig::default(),
             beacon_interval_secs: 60,
             quality_interval_secs: 300,
         }
@@ -0,0 +1,179 @@
+use crate::p2p::{topics, NodeAnnouncementData, P2pMessage};
+use crate::privacy::{poisson_interval, CoverKind, MixnetProcessor};
+use crate::P2pNetwork;
+use nonos_types::{NodeId, NonosError, NonosResult};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+use tokio::time::{interval, sleep_until, Instant};
+use tracing::{debug, info, warn};
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Connects a `MixnetProcessor` to the P2P network: incoming packets are
+/// fed into the processor, relayed packets are sent to their next hop,
+/// payloads exiting here are queued on `deliveries`, and the node's mix key
+/// is rotated and announced so others can route through it. Loop
+/// and drop cover packets are emitted as independent Poisson processes at
+/// the processor's configured rates.
+pub struct MixnetRelay {
+    node_id: NodeId,
+    network: Arc<RwLock<P2pNetwork>>,
+    processor: Arc<MixnetProcessor>,
+    announce_interval: Duration,
+    deliveries: mpsc::Sender<Vec<u8>>,
+}
+
+impl MixnetRelay {
+    pub fn new(
+        node_id: NodeId,
+        network: Arc<RwLock<P2pNetwork>>,
+        processor: Arc<MixnetProcessor>,
+        announce_interval_secs: u64,
+        deliveries: mpsc::Sender<Vec<u8>>,
+    ) -> Self {
+        Self {
+            node_id,
+            network,
+            processor,
+            announce_interval: Duration::from_secs(announce_interval_secs.max(1)),
+            deliveries,
+        }
+    }
+
+    pub async fn run(&self, shutdown: Arc<AtomicBool>) -> NonosResult<()> {
+        let (sender, mut incoming) = {
+            let network = self.network.read().await;
+            let sender = network.mix_packet_sender()
+                .ok_or_else(|| NonosError::Network("P2P network not started".into()))?;
+            let incoming = network.take_mix_packets()
+                .ok_or_else(|| NonosError::Network("Mix packet stream already taken".into()))?;
+            (sender, incoming)
+        };
+
+        self.processor.set_forward_callback(move |next_hop, packet| {
+            if let Err(e) = sender.send(&next_hop, packet) {
+                warn!("Failed to forward mix packet: {}", e);
+            }
+        }).await;
+
+        let deliveries = self.deliveries.clone();
+        self.processor.set_exit_callback(move |payload| {
+            if deliveries.try_send(payload).is_err() {
+                warn!("Dropping exited mix payload: delivery queue full");
+            }
+        }).await;
+
+        self.processor.set_local_node(self.node_id).await;
+
+        let mixing = self.processor.mixing_config().clone();
+        let mut next_loop = next_cover(mixing.loop_cover_rate);
+        let mut next_drop = next_cover(mixing.drop_cover_rate);
+
+        let mut announce_ticker = interval(self.announce_interval);
+        let mut flush_ticker = interval(FLUSH_INTERVAL);
+        info!("Mixnet relay running for node {}", self.node_id);
+
+        loop {
+            if shutdown.load(Ordering::SeqCst) {
+                info!("Mixnet relay shutting down");
+                break;
+            }
+
+            tokio::select! {
+                packet = incoming.recv() => {
+                    let Some(packet) = packet else {
+                        warn!("Mix packet stream closed");
+                        break;
+                    };
+                    if let Err(e) = self.processor.process_packet(packet).await {
+                        debug!("Rejected mix packet: {}", e);
+                    }
+                }
+
+                _ = announce_ticker.tick() => {
+                    if let Err(e) = self.announce().await {
+                        warn!("Failed to announce mix node: {}", e);
+                    }
+                    self.sync_nodes().await;
+                }
+
+                _ = flush_ticker.tick() => {
+                    if let Err(e) = self.processor.flush_expired().await {
+                        warn!("Failed to flush mix pool: {}", e);
+                    }
+                    if self.processor.rotate_keys_if_due().await {
+                        info!("Rotated mix key");
+                        if let Err(e) = self.announce().await {
+                            warn!("Failed to announce rotated mix key: {}", e);
+                        }
+                    }
+                }
+
+                _ = wait_for(next_loop) => {
+                    self.send_cover(CoverKind::Loop).await;
+                    next_loop = next_cover(mixing.loop_cover_rate);
+                }
+
+                _ = wait_for(next_drop) => {
+                    self.send_cover(CoverKind::Drop).await;
+                    next_drop = next_cover(mixing.drop_cover_rate);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn send_cover(&self, kind: CoverKind) {
+        if let Err(e) = self.processor.send_cover_packet(kind).await {
+            debug!("Skipped {:?} cover packet: {}", kind, e);
+        }
+    }
+
+    async fn announce(&self) -> NonosResult<()> {
+        let announcement = P2pMessage::NodeAnnouncement(NodeAnnouncementData {
+            node_id: self.node_id,
+            tier: String::new(),
+            staked_amount: String::new(),
+            services: vec!["mixnet".to_string()],
+            addresses: Vec::new(),
+            mix_public_key: Some(self.processor.public_key().await),
+        });
+
+        self.network.read().await
+            .publish_message(topics::NODE_ANNOUNCEMENTS, &announcement)
+            .await
+    }
+
+    /// Mirrors announced mix nodes into the processor, so paths are built
+    /// only through nodes that still announce themselves.
+    async fn sync_nodes(&self) {
+        let nodes = self.network.read().await.mix_nodes();
+        let announced: HashSet<NodeId> = nodes.iter().map(|node| node.node_id).collect();
+        for known in self.processor.get_nodes().await {
+            if !announced.contains(&known.node_id) {
+                self.processor.remove_node(&known.node_id).await;
+            }
+        }
+        for node in nodes {
+            if node.node_id != self.node_id {
+                self.processor.add_node(node).await;
+            }
+        }
+    }
+}
+
+fn next_cover(rate: f64) -> Option<Instant> {
+    poisson_interval(rate).and_then(|delay| Instant::now().checked_add(delay))
+}
+
+/// Sleeps until `deadline`, or forever if cover traffic is disabled.
+async fn wait_for(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
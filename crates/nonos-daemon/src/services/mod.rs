@@ -1,16 +1,33 @@
 mod manager;
+mod attestation;
 mod health_beacon;
 mod quality_oracle;
 mod bootstrap;
 mod cache;
 mod blockchain;
+mod mixnet;
+mod filter_lists;
+mod oracle_votes;
+mod cookie_vault;
+mod settlement;
+mod pir;
 
 pub use manager::{ServiceManager, ServiceConfig, ServiceType, ServiceState};
-pub use health_beacon::{HealthBeacon, HealthStatus};
-pub use quality_oracle::QualityOracle;
+pub use health_beacon::HealthBeacon;
+pub use attestation::{
+    latency_score, AttestedQuality, QualityAttestations, MAX_ATTESTED_RTT_MS,
+    MAX_PROBES_PER_ATTESTATION, MIN_ATTESTERS,
+};
+pub use quality_oracle::{QualityOracle, PROBE_SAMPLE_SIZE};
 pub use bootstrap::{BootstrapService, BootstrapConfig};
 pub use cache::{CacheService, CacheStats};
 pub use blockchain::BlockchainService;
+pub use mixnet::MixnetRelay;
+pub use filter_lists::FilterListSync;
+pub use oracle_votes::OracleVoteSync;
+pub use cookie_vault::CookieVaultMaintenance;
+pub use settlement::EpochSettlement;
+pub use pir::PirServer;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,67 @@
+use crate::privacy::{PrivacyOracle, VoteOutcome};
+use crate::rewards::current_epoch;
+use crate::P2pNetwork;
+use nonos_types::{NonosError, NonosResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{debug, info, warn};
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Counts privacy oracle votes gossiped on the network towards the local
+/// oracle's consensus.
+pub struct OracleVoteSync {
+    network: Arc<RwLock<P2pNetwork>>,
+    oracle: Arc<PrivacyOracle>,
+}
+
+impl OracleVoteSync {
+    pub fn new(network: Arc<RwLock<P2pNetwork>>, oracle: Arc<PrivacyOracle>) -> Self {
+        Self { network, oracle }
+    }
+
+    pub async fn run(&self, shutdown: Arc<AtomicBool>) -> NonosResult<()> {
+        let mut incoming = self.network.read().await.take_oracle_votes()
+            .ok_or_else(|| NonosError::Network("Oracle vote stream already taken".into()))?;
+
+        let mut ticker = interval(PRUNE_INTERVAL);
+        info!("Privacy oracle vote sync running");
+
+        loop {
+            if shutdown.load(Ordering::SeqCst) {
+                info!("Privacy oracle vote sync shutting down");
+                break;
+            }
+
+            tokio::select! {
+                vote = incoming.recv() => {
+                    let Some(vote) = vote else {
+                        warn!("Oracle vote stream closed");
+                        break;
+                    };
+                    match self.oracle.submit_vote(&vote, current_epoch().0).await {
+                        Ok(VoteOutcome::Counted { weight }) => {
+                            debug!("Counted oracle vote on {} with weight {:.2}", vote.domain, weight);
+                        }
+                        Ok(VoteOutcome::Duplicate) => {
+                            debug!("Ignoring repeat oracle vote on {} from {}", vote.domain, vote.binding.staker);
+                        }
+                        Ok(VoteOutcome::Unweighted) => {
+                            debug!("Ignoring oracle vote from unstaked {}", vote.binding.staker);
+                        }
+                        Err(e) => warn!("Rejected oracle vote from {}: {}", vote.voter, e),
+                    }
+                }
+
+                _ = ticker.tick() => {
+                    self.oracle.prune(current_epoch().0).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
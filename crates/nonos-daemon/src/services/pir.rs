@@ -0,0 +1,63 @@
+use crate::privacy::{PirParams, PrivateContentRetrieval};
+use crate::P2pNetwork;
+use nonos_types::NonosResult;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{debug, info, warn};
+
+const REBUILD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Serves this node's cached content to PIR clients, rebuilding the
+/// database as content is added or expires. Nodes caching the same content
+/// with the same parameters serve databases with the same digest, which is
+/// what lets clients pair them.
+pub struct PirServer {
+    network: Arc<RwLock<P2pNetwork>>,
+    content: Arc<PrivateContentRetrieval>,
+    params: PirParams,
+}
+
+impl PirServer {
+    pub fn new(network: Arc<RwLock<P2pNetwork>>, content: Arc<PrivateContentRetrieval>, params: PirParams) -> Self {
+        Self { network, content, params }
+    }
+
+    pub async fn run(&self, shutdown: Arc<AtomicBool>) -> NonosResult<()> {
+        self.params.validate()?;
+        let mut ticker = interval(REBUILD_INTERVAL);
+        info!("PIR server running");
+
+        loop {
+            ticker.tick().await;
+            if shutdown.load(Ordering::SeqCst) {
+                self.network.read().await.serve_pir_database(None);
+                info!("PIR server shutting down");
+                break;
+            }
+
+            if let Err(e) = self.refresh().await {
+                warn!("Failed to rebuild PIR database: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rebuilds the database and serves it if its digest changed. Returns
+    /// whether it did.
+    pub async fn refresh(&self) -> NonosResult<bool> {
+        self.content.cleanup_expired().await;
+        let database = self.content.pir_database(self.params).await?;
+
+        let network = self.network.read().await;
+        if network.served_pir_database() == Some(database.info()) {
+            return Ok(false);
+        }
+        debug!("Serving PIR database {} with {} items", hex::encode(database.digest()), database.item_count());
+        network.serve_pir_database(Some(database));
+        Ok(true)
+    }
+}
@@ -1,28 +1,63 @@
-use crate::{NodeMetricsCollector, NodeStorage};
-use nonos_types::{NodeId, NonosResult};
+use super::attestation::QualityAttestations;
+use crate::p2p::{topics, LatencyAttestationData, NodeProber, P2pMessage, ProbeResult};
+use crate::rewards::current_epoch;
+use crate::{NodeMetricsCollector, NodeStorage, P2pNetwork};
+use futures::future::join_all;
+use nonos_types::{NodeId, NonosError, NonosResult};
+use rand::seq::SliceRandom;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::RwLock;
 use tokio::time::interval;
 use tracing::{debug, info, warn};
 
+/// Nodes probed per round.
+pub const PROBE_SAMPLE_SIZE: usize = 8;
+
+/// Records local quality metrics and measures other nodes: each round it
+/// probes a random sample of known nodes, gossips the signed results, and
+/// folds attestations from other nodes into the shared
+/// [`QualityAttestations`].
 pub struct QualityOracle {
     node_id: NodeId,
+    network: Arc<RwLock<P2pNetwork>>,
     metrics: Arc<NodeMetricsCollector>,
     storage: Arc<NodeStorage>,
+    attestations: Arc<QualityAttestations>,
+    round_interval: Duration,
 }
 
 impl QualityOracle {
     pub fn new(
         node_id: NodeId,
+        network: Arc<RwLock<P2pNetwork>>,
         metrics: Arc<NodeMetricsCollector>,
         storage: Arc<NodeStorage>,
+        attestations: Arc<QualityAttestations>,
+        round_interval_secs: u64,
     ) -> Self {
-        Self { node_id, metrics, storage }
+        Self {
+            node_id,
+            network,
+            metrics,
+            storage,
+            attestations,
+            round_interval: Duration::from_secs(round_interval_secs.max(1)),
+        }
     }
 
     pub async fn run(&self, shutdown: Arc<AtomicBool>) -> NonosResult<()> {
-        let mut ticker = interval(Duration::from_secs(300));
+        let (prober, mut incoming) = {
+            let network = self.network.read().await;
+            let prober = network.node_prober()
+                .ok_or_else(|| NonosError::Network("P2P network not started".into()))?;
+            let incoming = network.take_attestations()
+                .ok_or_else(|| NonosError::Network("Attestation stream already taken".into()))?;
+            (prober, incoming)
+        };
+
+        let mut ticker = interval(self.round_interval);
         info!("Quality oracle running for node {}", self.node_id);
 
         loop {
@@ -31,10 +66,25 @@ impl QualityOracle {
                 break;
             }
 
-            ticker.tick().await;
+            tokio::select! {
+                attestation = incoming.recv() => {
+                    let Some(attestation) = attestation else {
+                        warn!("Attestation stream closed");
+                        break;
+                    };
+                    let weight = self.attestations.attester_weight(&attestation).await;
+                    let recorded = self.attestations.ingest(&attestation, weight, current_epoch().0);
+                    debug!("Recorded {} probe results from {} with weight {:.2}", recorded, attestation.node_id, weight);
+                }
 
-            if let Err(e) = self.record_quality().await {
-                warn!("Failed to record quality: {}", e);
+                _ = ticker.tick() => {
+                    if let Err(e) = self.record_quality().await {
+                        warn!("Failed to record quality: {}", e);
+                    }
+                    if let Err(e) = self.probe_and_attest(&prober).await {
+                        warn!("Failed to publish latency attestation: {}", e);
+                    }
+                }
             }
         }
 
@@ -54,4 +104,44 @@ impl QualityOracle {
         debug!("Recorded quality: score={:.4}, requests={}", quality.total(), summary.total_requests);
         Ok(())
     }
+
+    async fn probe_and_attest(&self, prober: &NodeProber) -> NonosResult<()> {
+        let mut targets = prober.known_nodes();
+        targets.retain(|node| *node != self.node_id);
+        targets.shuffle(&mut rand::thread_rng());
+        targets.truncate(PROBE_SAMPLE_SIZE);
+
+        if targets.is_empty() {
+            debug!("No known nodes to probe");
+            return Ok(());
+        }
+
+        let probes = join_all(targets.iter().map(|target| async move {
+            ProbeResult {
+                target: *target,
+                rtt_ms: prober.probe(target).await
+                    .map(|rtt| rtt.as_millis().min(u32::MAX as u128) as u32),
+            }
+        })).await;
+
+        let epoch = current_epoch().0;
+        let attestation = LatencyAttestationData {
+            node_id: self.node_id,
+            epoch,
+            probes,
+            binding: self.attestations.binding(),
+        };
+        let answered = attestation.probes.iter().filter(|probe| probe.rtt_ms.is_some()).count();
+
+        // Gossip is not delivered back to its publisher, so our own
+        // measurements are recorded directly, weighted like anyone else's.
+        let weight = self.attestations.attester_weight(&attestation).await;
+        self.attestations.ingest(&attestation, weight, epoch);
+        self.network.read().await
+            .publish_message(topics::QUALITY_REPORTS, &P2pMessage::LatencyAttestation(attestation))
+            .await?;
+
+        debug!("Probed {} nodes, {} answered", targets.len(), answered);
+        Ok(())
+    }
 }
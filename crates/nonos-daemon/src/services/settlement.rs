@@ -0,0 +1,104 @@
+use super::attestation::QualityAttestations;
+use crate::contracts::{current_epoch as contract_epoch, staking_genesis_timestamp, EPOCH_DURATION_SECS};
+use crate::rewards::{current_epoch, RewardTracker};
+use crate::tokenomics::calculate_epoch_emission;
+use chrono::{DateTime, Utc};
+use nonos_types::{EpochNumber, EpochSummary, NodeId, NonosResult, TokenAmount, NOX_DECIMALS};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{info, warn};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Credits this node's reward for each epoch once it ends, scored by the
+/// quality other staked nodes attested for it.
+pub struct EpochSettlement {
+    node_id: NodeId,
+    attestations: Arc<QualityAttestations>,
+    rewards: Arc<RewardTracker>,
+}
+
+impl EpochSettlement {
+    pub fn new(
+        node_id: NodeId,
+        attestations: Arc<QualityAttestations>,
+        rewards: Arc<RewardTracker>,
+    ) -> Self {
+        Self { node_id, attestations, rewards }
+    }
+
+    pub async fn run(&self, shutdown: Arc<AtomicBool>) -> NonosResult<()> {
+        let mut ticker = interval(CHECK_INTERVAL);
+        // Attestations start being collected now, so the epoch already
+        // under way is the first that can be settled.
+        let mut open_epoch = current_epoch().0;
+        info!("Epoch settlement running from epoch {}", open_epoch);
+
+        loop {
+            ticker.tick().await;
+            if shutdown.load(Ordering::SeqCst) {
+                info!("Epoch settlement shutting down");
+                break;
+            }
+
+            let epoch = current_epoch().0;
+            if epoch > open_epoch {
+                self.settle(open_epoch).await;
+                open_epoch = epoch;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Credits the reward for `epoch`. This node's share of the emission is
+    /// its share of the stake seen attesting in the epoch; if none of its
+    /// own attestations counted, the share is unknown and the epoch earns
+    /// nothing.
+    pub async fn settle(&self, epoch: u64) {
+        let (stakers, total_stake) = self.attestations.epoch_stake(epoch);
+        let own_stake = self.attestations.binding()
+            .map_or(0.0, |binding| self.attestations.attester_stake(&binding.staker, epoch));
+        let stake_weight = self.rewards.stake_weight().await;
+
+        let attested = if own_stake > 0.0 {
+            self.attestations.quality(&self.node_id, epoch)
+        } else {
+            warn!("No staked attestations from this node in epoch {}", epoch);
+            None
+        };
+        let total_weight = if own_stake > 0.0 {
+            stake_weight * total_stake / own_stake
+        } else {
+            stake_weight
+        };
+
+        let (start_time, end_time) = epoch_bounds(epoch);
+        let summary = EpochSummary {
+            epoch: EpochNumber(epoch),
+            start_time,
+            end_time,
+            total_emission: TokenAmount::from_raw(
+                calculate_epoch_emission(epoch).saturating_mul(10u128.pow(NOX_DECIMALS as u32)),
+                NOX_DECIMALS,
+            ),
+            total_weight,
+            staker_count: stakers.min(u32::MAX as usize) as u32,
+            avg_quality: attested.as_ref().map_or(0.0, |attested| attested.quality.total()),
+        };
+
+        self.rewards.process_epoch(&summary, attested.as_ref()).await;
+    }
+}
+
+/// Start and end of `epoch`, counted from the staking genesis when the
+/// contract is configured and from the Unix epoch otherwise, as in
+/// [`current_epoch`].
+fn epoch_bounds(epoch: u64) -> (DateTime<Utc>, DateTime<Utc>) {
+    let genesis = if contract_epoch().is_some() { staking_genesis_timestamp() } else { 0 };
+    let start = genesis.saturating_add(epoch.saturating_mul(EPOCH_DURATION_SECS));
+    let at = |secs: u64| DateTime::from_timestamp(secs.min(i64::MAX as u64) as i64, 0).unwrap_or_default();
+    (at(start), at(start.saturating_add(EPOCH_DURATION_SECS)))
+}
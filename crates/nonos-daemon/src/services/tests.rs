@@ -43,3 +43,223 @@ async fn test_cache_eviction() {
     let stats = cache.stats().await;
     assert!(stats.total_size <= 1048576);
 }
+
+fn node(byte: u8) -> nonos_types::NodeId {
+    nonos_types::NodeId::from_bytes([byte; 32])
+}
+
+fn staker(byte: u8) -> nonos_types::EthAddress {
+    nonos_types::EthAddress::from_bytes([byte; 20])
+}
+
+/// An attestation by `attester`, bound to a staker of its own.
+fn attest(attester: u8, epoch: u64, probes: &[(u8, Option<u32>)]) -> crate::p2p::LatencyAttestationData {
+    attest_for(staker(attester), attester, epoch, probes)
+}
+
+/// An attestation by `attester` bound to `staker`; [`QualityAttestations::ingest`]
+/// trusts bindings already checked by `attester_weight`.
+fn attest_for(
+    staker: nonos_types::EthAddress,
+    attester: u8,
+    epoch: u64,
+    probes: &[(u8, Option<u32>)],
+) -> crate::p2p::LatencyAttestationData {
+    crate::p2p::LatencyAttestationData {
+        node_id: node(attester),
+        epoch,
+        probes: probes.iter()
+            .map(|&(target, rtt_ms)| crate::p2p::ProbeResult { target: node(target), rtt_ms })
+            .collect(),
+        binding: Some(crate::privacy::VoterBinding { staker, signature: Vec::new() }),
+    }
+}
+
+#[test]
+fn test_attested_quality_needs_min_attesters() {
+    let attestations = QualityAttestations::new();
+    for attester in 1..MIN_ATTESTERS as u8 {
+        attestations.ingest(&attest(attester, 5, &[(9, Some(50))]), 1.0, 5);
+    }
+    assert!(attestations.quality(&node(9), 5).is_none());
+
+    attestations.ingest(&attest(MIN_ATTESTERS as u8, 5, &[(9, Some(50))]), 1.0, 5);
+    let attested = attestations.quality(&node(9), 5).unwrap();
+    assert_eq!(attested.attesters, MIN_ATTESTERS);
+    assert_eq!(attested.median_latency_ms, Some(50));
+    assert_eq!(attested.availability, 1.0);
+}
+
+#[test]
+fn test_attested_quality_resists_outlier() {
+    let attestations = QualityAttestations::new();
+    attestations.ingest(&attest(1, 5, &[(9, Some(40))]), 1.0, 5);
+    attestations.ingest(&attest(2, 5, &[(9, Some(60))]), 1.0, 5);
+    attestations.ingest(&attest(3, 5, &[(9, Some(50))]), 1.0, 5);
+    attestations.ingest(&attest(4, 5, &[(9, Some(55))]), 1.0, 5);
+    attestations.ingest(&attest(5, 5, &[(9, None)]), 1.0, 5);
+
+    let attested = attestations.quality(&node(9), 5).unwrap();
+    assert_eq!(attested.attesters, 5);
+    assert_eq!(attested.availability, 1.0);
+    assert_eq!(attested.median_latency_ms, Some(53));
+    assert_eq!(attested.quality.latency_score, 1.0);
+    assert_eq!(attested.quality.success_rate, 0.8);
+}
+
+#[test]
+fn test_attestations_ignore_self_probes_and_stale_epochs() {
+    let attestations = QualityAttestations::new();
+    assert_eq!(attestations.ingest(&attest(1, 5, &[(1, Some(1)), (9, Some(30)), (9, Some(1))]), 1.0, 5), 1);
+    assert_eq!(attestations.attester_count(&node(1), 5), 0);
+
+    assert_eq!(attestations.ingest(&attest(2, 3, &[(9, Some(30))]), 1.0, 5), 0);
+    assert_eq!(attestations.ingest(&attest(2, 6, &[(9, Some(30))]), 1.0, 5), 0);
+    assert_eq!(attestations.ingest(&attest(2, 4, &[(9, Some(30))]), 1.0, 5), 1);
+    assert_eq!(attestations.attester_count(&node(9), 4), 1);
+
+    attestations.ingest(&attest(3, 6, &[(9, Some(30))]), 1.0, 6);
+    assert_eq!(attestations.attester_count(&node(9), 4), 0);
+    assert_eq!(attestations.attester_count(&node(9), 5), 1);
+}
+
+#[test]
+fn test_attestations_ignore_unstaked_attesters() {
+    let attestations = QualityAttestations::new();
+    for attester in 1..=MIN_ATTESTERS as u8 {
+        assert_eq!(attestations.ingest(&attest(attester, 5, &[(9, Some(50))]), 0.0, 5), 0);
+    }
+    assert!(attestations.quality(&node(9), 5).is_none());
+    assert_eq!(attestations.epoch_stake(5), (0, 0.0));
+}
+
+#[test]
+fn test_attested_quality_is_weighted_by_stake() {
+    let attestations = QualityAttestations::new();
+    // Two lightly staked attesters cannot outvote one holding most of the
+    // stake.
+    attestations.ingest(&attest(1, 5, &[(9, Some(40))]), 10.0, 5);
+    attestations.ingest(&attest(2, 5, &[(9, None)]), 1.0, 5);
+    attestations.ingest(&attest(3, 5, &[(9, None)]), 1.0, 5);
+
+    let attested = attestations.quality(&node(9), 5).unwrap();
+    assert_eq!(attested.availability, 1.0);
+    assert_eq!(attested.median_latency_ms, Some(40));
+    assert_eq!(attested.quality.success_rate, 10.0 / 12.0);
+    assert_eq!(attestations.epoch_stake(5), (3, 12.0));
+    assert_eq!(attestations.attester_stake(&staker(1), 5), 10.0);
+}
+
+#[test]
+fn test_attesters_sharing_a_staker_count_once() {
+    let attestations = QualityAttestations::new();
+    // One staker binding several nodes is still one attester, and its
+    // stake is counted once.
+    for attester in 1..=MIN_ATTESTERS as u8 {
+        attestations.ingest(&attest_for(staker(1), attester, 5, &[(9, Some(50))]), 10.0, 5);
+    }
+    assert_eq!(attestations.attester_count(&node(9), 5), 1);
+    assert!(attestations.quality(&node(9), 5).is_none());
+    assert_eq!(attestations.epoch_stake(5), (1, 10.0));
+
+    // Nor can it vouch for nodes bound to its own stake, whichever comes
+    // first.
+    attestations.ingest(&attest_for(staker(1), 8, 5, &[(7, Some(50))]), 10.0, 5);
+    assert_eq!(attestations.ingest(&attest_for(staker(1), 1, 5, &[(8, Some(50))]), 10.0, 5), 0);
+    attestations.ingest(&attest_for(staker(1), 7, 5, &[(2, Some(50))]), 10.0, 5);
+    for attester in 10..10 + MIN_ATTESTERS as u8 {
+        attestations.ingest(&attest(attester, 5, &[(7, Some(50))]), 1.0, 5);
+    }
+    let attested = attestations.quality(&node(7), 5).unwrap();
+    assert_eq!(attested.attesters, MIN_ATTESTERS);
+    assert_eq!(attestations.attester_count(&node(8), 5), 0);
+}
+
+struct FixedStakes(std::collections::HashMap<nonos_types::EthAddress, f64>);
+
+#[async_trait::async_trait]
+impl crate::privacy::StakeLookup for FixedStakes {
+    async fn voting_weight(&self, staker: &nonos_types::EthAddress) -> nonos_types::NonosResult<f64> {
+        Ok(self.0.get(staker).copied().unwrap_or(0.0))
+    }
+}
+
+#[tokio::test]
+async fn test_attester_weight_needs_a_valid_binding() {
+    use nonos_crypto::{derive_eth_address_from_private, generate_private_key};
+
+    let key = generate_private_key();
+    let staker = derive_eth_address_from_private(&key).unwrap();
+    let attestations = QualityAttestations::new();
+    attestations.set_stake_lookup(Arc::new(FixedStakes([(staker, 25.0)].into_iter().collect())));
+
+    let mut attestation = attest(1, 5, &[(9, Some(50))]);
+    assert_eq!(attestations.attester_weight(&attestation).await, 0.0);
+
+    attestation.binding = Some(crate::privacy::VoterBinding::sign(&key, &node(1)).unwrap());
+    assert_eq!(attestations.attester_weight(&attestation).await, 25.0);
+
+    // A binding made out to another node does not carry its stake.
+    attestation.node_id = node(2);
+    assert_eq!(attestations.attester_weight(&attestation).await, 0.0);
+}
+
+#[tokio::test]
+async fn test_settlement_credits_attested_epoch() {
+    use nonos_types::{NodeTier, StakeRecord, TokenAmount, NOX_DECIMALS};
+
+    let rewards = Arc::new(crate::RewardTracker::new());
+    rewards.set_stake(StakeRecord {
+        staker: nonos_types::EthAddress::zero(),
+        node_id: None,
+        amount: TokenAmount::from_raw(10_000 * 10u128.pow(NOX_DECIMALS as u32), NOX_DECIMALS),
+        tier: NodeTier::Silver,
+        lock_start: chrono::Utc::now(),
+        lock_end: chrono::Utc::now() + chrono::Duration::days(30),
+        is_locked: true,
+    }).await;
+
+    let key = nonos_crypto::generate_private_key();
+    let binding = crate::privacy::VoterBinding::sign(&key, &node(9)).unwrap();
+    let attestations = Arc::new(QualityAttestations::new());
+    attestations.set_binding(&node(9), binding.clone()).unwrap();
+    for attester in 1..=MIN_ATTESTERS as u8 {
+        attestations.ingest(&attest(attester, 5, &[(9, Some(50))]), 1.0, 5);
+    }
+    let settlement = EpochSettlement::new(node(9), attestations.clone(), rewards.clone());
+
+    // Without staked attestations of its own the node's share is unknown.
+    settlement.settle(5).await;
+    assert!(rewards.pending_rewards().await.is_zero());
+
+    let mut own = attest(9, 5, &[(1, Some(50))]);
+    own.binding = Some(binding);
+    attestations.ingest(&own, 1.0, 5);
+    settlement.settle(5).await;
+    assert!(!rewards.pending_rewards().await.is_zero());
+}
+
+#[tokio::test]
+async fn test_pir_server_serves_cached_content() {
+    use crate::privacy::{PirParams, PrivateContentRetrieval};
+
+    let network = Arc::new(tokio::sync::RwLock::new(crate::P2pNetwork::new(9433, 10)));
+    let content = Arc::new(PrivateContentRetrieval::new(16));
+    let params = PirParams { buckets: 16, slots_per_bucket: 2, max_item_size: 64 };
+    let server = PirServer::new(network.clone(), content.clone(), params);
+
+    assert!(server.refresh().await.unwrap());
+    assert_eq!(network.read().await.served_pir_database().unwrap().items, 0);
+    assert!(!server.refresh().await.unwrap());
+
+    content.store(b"served over pir", 3600).await.unwrap();
+    assert!(server.refresh().await.unwrap());
+    assert_eq!(network.read().await.served_pir_database().unwrap().items, 1);
+}
+
+#[test]
+fn test_latency_score() {
+    assert_eq!(latency_score(50), 1.0);
+    assert_eq!(latency_score(550), 0.5);
+    assert_eq!(latency_score(MAX_ATTESTED_RTT_MS), 0.0);
+}
@@ -0,0 +1,104 @@
+use super::NodeStorage;
+use nonos_types::{NonosError, NonosResult};
+use serde::{Deserialize, Serialize};
+use sled::Tree;
+use std::sync::atomic::Ordering;
+
+/// A cookie this node vaulted: everything but its key, which only exists
+/// as shares held by other nodes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredVaultedCookie {
+    pub cookie_id: String,
+    /// Bumped each time the key is re-shared.
+    pub generation: u32,
+    pub threshold: u8,
+    /// Holder of the share with index `i + 1` at position `i`.
+    pub holders: Vec<[u8; 32]>,
+    /// Feldman commitments the shares verify against.
+    pub commitments: Vec<Vec<u8>>,
+    /// The cookie, encrypted under the shared key.
+    pub sealed: Vec<u8>,
+    pub updated_at: i64,
+}
+
+/// A key share this node holds for another node's cookie.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredVaultShare {
+    /// Peer ID of the node the share belongs to.
+    pub owner: Vec<u8>,
+    pub cookie_id: String,
+    pub generation: u32,
+    pub index: u8,
+    pub value: Vec<u8>,
+    pub commitments: Vec<Vec<u8>>,
+}
+
+fn vault_share_key(owner: &[u8], cookie_id: &str, generation: u32) -> Vec<u8> {
+    let mut key = Vec::with_capacity(2 + owner.len() + cookie_id.len() + 4);
+    key.push(owner.len() as u8);
+    key.extend_from_slice(owner);
+    key.push(cookie_id.len() as u8);
+    key.extend_from_slice(cookie_id.as_bytes());
+    key.extend_from_slice(&generation.to_be_bytes());
+    key
+}
+
+impl NodeStorage {
+    fn store_vault_entry<T: Serialize>(&self, tree: &Tree, key: &[u8], entry: &T) -> NonosResult<()> {
+        let value = bincode::serialize(entry)
+            .map_err(|e| NonosError::Storage(format!("Failed to serialize vault entry: {}", e)))?;
+
+        self.metrics.writes.fetch_add(1, Ordering::Relaxed);
+        self.metrics.write_bytes.fetch_add(value.len() as u64, Ordering::Relaxed);
+
+        tree.insert(key, value).map_err(|e| {
+            self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+            NonosError::Storage(format!("Failed to store vault entry: {}", e))
+        })?;
+
+        self.db.flush().map_err(|e| NonosError::Storage(format!("Flush error: {}", e)))?;
+        Ok(())
+    }
+
+    fn load_vault_entries<T: for<'de> Deserialize<'de>>(&self, tree: &Tree) -> NonosResult<Vec<T>> {
+        self.metrics.reads.fetch_add(1, Ordering::Relaxed);
+
+        tree.iter()
+            .values()
+            .map(|value| {
+                let value = value
+                    .map_err(|e| NonosError::Storage(format!("Failed to iterate vault entries: {}", e)))?;
+                bincode::deserialize(&value)
+                    .map_err(|e| NonosError::Storage(format!("Failed to deserialize vault entry: {}", e)))
+            })
+            .collect()
+    }
+
+    pub fn store_vaulted_cookie(&self, cookie: &StoredVaultedCookie) -> NonosResult<()> {
+        self.store_vault_entry(&self.vault_cookies, cookie.cookie_id.as_bytes(), cookie)
+    }
+
+    pub fn remove_vaulted_cookie(&self, cookie_id: &str) -> NonosResult<bool> {
+        let removed = self.vault_cookies.remove(cookie_id.as_bytes())
+            .map_err(|e| NonosError::Storage(format!("Failed to remove vaulted cookie: {}", e)))?;
+        Ok(removed.is_some())
+    }
+
+    pub fn load_vaulted_cookies(&self) -> NonosResult<Vec<StoredVaultedCookie>> {
+        self.load_vault_entries(&self.vault_cookies)
+    }
+
+    pub fn store_vault_share(&self, share: &StoredVaultShare) -> NonosResult<()> {
+        self.store_vault_entry(&self.vault_shares, &vault_share_key(&share.owner, &share.cookie_id, share.generation), share)
+    }
+
+    pub fn remove_vault_share(&self, owner: &[u8], cookie_id: &str, generation: u32) -> NonosResult<bool> {
+        let removed = self.vault_shares.remove(vault_share_key(owner, cookie_id, generation))
+            .map_err(|e| NonosError::Storage(format!("Failed to remove vault share: {}", e)))?;
+        Ok(removed.is_some())
+    }
+
+    pub fn load_vault_shares(&self) -> NonosResult<Vec<StoredVaultShare>> {
+        self.load_vault_entries(&self.vault_shares)
+    }
+}
@@ -0,0 +1,135 @@
+use super::NodeStorage;
+use nonos_types::{NonosError, NonosResult};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use std::sync::atomic::Ordering;
+use tracing::warn;
+
+const RECORD_PREFIX: &[u8] = b"r";
+const PROVIDER_PREFIX: &[u8] = b"p";
+
+/// A Kademlia value record. Expiry is wall-clock, since the DHT's
+/// monotonic `Instant`s do not survive a restart.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredDhtRecord {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+    pub publisher: Option<Vec<u8>>,
+    pub expires_at_ms: Option<i64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredDhtProvider {
+    pub key: Vec<u8>,
+    pub provider: Vec<u8>,
+    pub addresses: Vec<Vec<u8>>,
+    pub expires_at_ms: Option<i64>,
+}
+
+fn record_key(key: &[u8]) -> Vec<u8> {
+    let mut k = Vec::with_capacity(RECORD_PREFIX.len() + key.len());
+    k.extend_from_slice(RECORD_PREFIX);
+    k.extend_from_slice(key);
+    k
+}
+
+fn provider_key(key: &[u8], provider: &[u8]) -> Vec<u8> {
+    let mut k = Vec::with_capacity(PROVIDER_PREFIX.len() + 4 + key.len() + provider.len());
+    k.extend_from_slice(PROVIDER_PREFIX);
+    k.extend_from_slice(&(key.len() as u32).to_be_bytes());
+    k.extend_from_slice(key);
+    k.extend_from_slice(provider);
+    k
+}
+
+impl NodeStorage {
+    /// Decodes every entry under `prefix`. An entry that fails to decode is
+    /// logged and skipped, so one corrupt entry cannot hide the rest.
+    fn load_dht_entries<T: DeserializeOwned>(&self, prefix: &[u8], what: &str) -> NonosResult<Vec<T>> {
+        self.metrics.reads.fetch_add(1, Ordering::Relaxed);
+
+        let mut entries = Vec::new();
+        for entry in self.dht_records.scan_prefix(prefix) {
+            let (key, value) = entry
+                .map_err(|e| NonosError::Storage(format!("Failed to iterate DHT {}: {}", what, e)))?;
+            match bincode::deserialize(&value) {
+                Ok(decoded) => entries.push(decoded),
+                Err(e) => {
+                    self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+                    warn!("Skipping undecodable DHT {} entry {}: {}", what, hex::encode(&key), e);
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    pub fn store_dht_record(&self, record: &StoredDhtRecord) -> NonosResult<()> {
+        let value = bincode::serialize(record)
+            .map_err(|e| NonosError::Storage(format!("Failed to serialize DHT record: {}", e)))?;
+
+        self.metrics.writes.fetch_add(1, Ordering::Relaxed);
+        self.metrics.write_bytes.fetch_add(value.len() as u64, Ordering::Relaxed);
+
+        self.dht_records.insert(record_key(&record.key), value).map_err(|e| {
+            self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+            NonosError::Storage(format!("Failed to store DHT record: {}", e))
+        })?;
+        Ok(())
+    }
+
+    pub fn remove_dht_record(&self, key: &[u8]) -> NonosResult<()> {
+        self.dht_records.remove(record_key(key))
+            .map_err(|e| NonosError::Storage(format!("Failed to remove DHT record: {}", e)))?;
+        Ok(())
+    }
+
+    pub fn load_dht_records(&self) -> NonosResult<Vec<StoredDhtRecord>> {
+        self.load_dht_entries(RECORD_PREFIX, "records")
+    }
+
+    pub fn store_dht_provider(&self, provider: &StoredDhtProvider) -> NonosResult<()> {
+        let value = bincode::serialize(provider)
+            .map_err(|e| NonosError::Storage(format!("Failed to serialize DHT provider: {}", e)))?;
+
+        self.metrics.writes.fetch_add(1, Ordering::Relaxed);
+        self.metrics.write_bytes.fetch_add(value.len() as u64, Ordering::Relaxed);
+
+        self.dht_records.insert(provider_key(&provider.key, &provider.provider), value).map_err(|e| {
+            self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+            NonosError::Storage(format!("Failed to store DHT provider: {}", e))
+        })?;
+        Ok(())
+    }
+
+    pub fn remove_dht_provider(&self, key: &[u8], provider: &[u8]) -> NonosResult<()> {
+        self.dht_records.remove(provider_key(key, provider))
+            .map_err(|e| NonosError::Storage(format!("Failed to remove DHT provider: {}", e)))?;
+        Ok(())
+    }
+
+    pub fn load_dht_providers(&self) -> NonosResult<Vec<StoredDhtProvider>> {
+        self.load_dht_entries(PROVIDER_PREFIX, "providers")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undecodable_records_are_skipped() {
+        let storage = NodeStorage::in_memory().unwrap();
+        storage.store_dht_record(&StoredDhtRecord {
+            key: b"good".to_vec(),
+            value: vec![1, 2, 3],
+            publisher: None,
+            expires_at_ms: None,
+        }).unwrap();
+        storage.dht_records.insert(record_key(b"bad"), vec![0xff]).unwrap();
+
+        let records = storage.load_dht_records().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].key, b"good");
+        assert_eq!(storage.tree_sizes().unwrap().dht_records, 2);
+    }
+}
@@ -0,0 +1,68 @@
+use super::NodeStorage;
+use nonos_types::{NonosError, NonosResult};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+
+/// A filter-list subscription and the last version of the list applied
+/// from it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredFilterList {
+    pub name: String,
+    /// Ed25519 key the list's bundles must be signed with.
+    pub maintainer: [u8; 32],
+    /// 0 until the first bundle arrives.
+    pub version: u64,
+    /// The list in canonical form.
+    pub rules: String,
+    pub updated_at: Option<i64>,
+    /// A full bundle of `version`, if one has been seen, kept to gossip
+    /// again for nodes that missed it.
+    pub full_bundle: Option<StoredBundle>,
+}
+
+/// An encoded filter-list bundle and the maintainer's signature over each
+/// of its chunks, in order.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredBundle {
+    pub encoded: Vec<u8>,
+    pub chunk_signatures: Vec<Vec<u8>>,
+}
+
+impl NodeStorage {
+    pub fn store_filter_list(&self, list: &StoredFilterList) -> NonosResult<()> {
+        let value = bincode::serialize(list)
+            .map_err(|e| NonosError::Storage(format!("Failed to serialize filter list: {}", e)))?;
+
+        self.metrics.writes.fetch_add(1, Ordering::Relaxed);
+        self.metrics.write_bytes.fetch_add(value.len() as u64, Ordering::Relaxed);
+
+        self.filter_lists.insert(list.name.as_bytes(), value).map_err(|e| {
+            self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+            NonosError::Storage(format!("Failed to store filter list: {}", e))
+        })?;
+
+        self.db.flush().map_err(|e| NonosError::Storage(format!("Flush error: {}", e)))?;
+        Ok(())
+    }
+
+    pub fn remove_filter_list(&self, name: &str) -> NonosResult<bool> {
+        let removed = self.filter_lists.remove(name.as_bytes())
+            .map_err(|e| NonosError::Storage(format!("Failed to remove filter list: {}", e)))?;
+        Ok(removed.is_some())
+    }
+
+    pub fn load_filter_lists(&self) -> NonosResult<Vec<StoredFilterList>> {
+        self.metrics.reads.fetch_add(1, Ordering::Relaxed);
+
+        self.filter_lists
+            .iter()
+            .values()
+            .map(|value| {
+                let value = value
+                    .map_err(|e| NonosError::Storage(format!("Failed to iterate filter lists: {}", e)))?;
+                bincode::deserialize(&value)
+                    .map_err(|e| NonosError::Storage(format!("Failed to deserialize filter list: {}", e)))
+            })
+            .collect()
+    }
+}
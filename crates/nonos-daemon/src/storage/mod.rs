@@ -13,7 +13,7 @@ use std::sync::Arc;
 use std::time::Instant;
 use tracing::{info, warn};
 
-const CURRENT_SCHEMA_VERSION: u32 = 2;
+const CURRENT_SCHEMA_VERSION: u32 = 7;
 const SCHEMA_KEY: &[u8] = b"__schema_version__";
 const MAX_BATCH_SIZE: usize = 1000;
 
@@ -34,6 +34,15 @@ pub struct NodeStorage {
     claims: Tree,
     secrets: Tree,
     audit_log: Tree,
+    mixer_state: Tree,
+    identity_registry: Tree,
+    zk_credentials: Tree,
+    zk_sessions: Tree,
+    stealth_payments: Tree,
+    dht_records: Tree,
+    filter_lists: Tree,
+    vault_cookies: Tree,
+    vault_shares: Tree,
     storage_config: StorageConfig,
     metrics: Arc<StorageMetrics>,
     opened_at: Instant,
@@ -86,6 +95,15 @@ impl NodeStorage {
         let claims = Self::open_tree(&db, "claims")?;
         let secrets = Self::open_tree(&db, "secrets")?;
         let audit_log = Self::open_tree(&db, "audit_log")?;
+        let mixer_state = Self::open_tree(&db, "mixer_state")?;
+        let identity_registry = Self::open_tree(&db, "identity_registry")?;
+        let zk_credentials = Self::open_tree(&db, "zk_credentials")?;
+        let zk_sessions = Self::open_tree(&db, "zk_sessions")?;
+        let stealth_payments = Self::open_tree(&db, "stealth_payments")?;
+        let dht_records = Self::open_tree(&db, "dht_records")?;
+        let filter_lists = Self::open_tree(&db, "filter_lists")?;
+        let vault_cookies = Self::open_tree(&db, "vault_cookies")?;
+        let vault_shares = Self::open_tree(&db, "vault_shares")?;
 
         Ok(Self {
             db,
@@ -98,6 +116,15 @@ impl NodeStorage {
             claims,
             secrets,
             audit_log,
+            mixer_state,
+            identity_registry,
+            zk_credentials,
+            zk_sessions,
+            stealth_payments,
+            dht_records,
+            filter_lists,
+            vault_cookies,
+            vault_shares,
             storage_config: config,
             metrics: Arc::new(StorageMetrics::new()),
             opened_at: Instant::now(),
@@ -206,6 +233,16 @@ impl NodeStorage {
     fn apply_migration(&mut self, from: u32, to: u32) -> NonosResult<()> {
         match (from, to) {
             (1, 2) => self.migrate_v1_to_v2(),
+            // Privacy state trees are created empty when the database is opened.
+            (2, 3) => Ok(()),
+            // So is the stealth payment tree.
+            (3, 4) => Ok(()),
+            // And the Kademlia record tree.
+            (4, 5) => Ok(()),
+            // And the filter-list subscription tree.
+            (5, 6) => Ok(()),
+            // And the cookie vault trees.
+            (6, 7) => Ok(()),
             _ => {
                 warn!("No migration path for {} -> {}", from, to);
                 Ok(())
@@ -347,3 +384,12 @@ mod identity;
 mod peers;
 mod epochs;
 mod operations;
+mod privacy;
+mod stealth;
+mod dht;
+mod filter_lists;
+mod cookie_vault;
+
+pub use dht::{StoredDhtProvider, StoredDhtRecord};
+pub use filter_lists::{StoredBundle, StoredFilterList};
+pub use cookie_vault::{StoredVaultShare, StoredVaultedCookie};
@@ -262,6 +262,15 @@ impl NodeStorage {
             claims: self.claims.len(),
             secrets: self.secrets.len(),
             audit_log: self.audit_log.len(),
+            mixer_state: self.mixer_state.len(),
+            identity_registry: self.identity_registry.len(),
+            zk_credentials: self.zk_credentials.len(),
+            zk_sessions: self.zk_sessions.len(),
+            stealth_payments: self.stealth_payments.len(),
+            filter_lists: self.filter_lists.len(),
+            vault_cookies: self.vault_cookies.len(),
+            vault_shares: self.vault_shares.len(),
+            dht_records: self.dht_records.len(),
         })
     }
 
@@ -311,6 +320,15 @@ impl NodeStorage {
             ("config", &self.config_tree),
             ("claims", &self.claims),
             ("secrets", &self.secrets),
+            ("mixer_state", &self.mixer_state),
+            ("identity_registry", &self.identity_registry),
+            ("zk_credentials", &self.zk_credentials),
+            ("zk_sessions", &self.zk_sessions),
+            ("stealth_payments", &self.stealth_payments),
+            ("filter_lists", &self.filter_lists),
+            ("vault_cookies", &self.vault_cookies),
+            ("vault_shares", &self.vault_shares),
+            ("dht_records", &self.dht_records),
         ];
 
         for (name, tree) in trees {
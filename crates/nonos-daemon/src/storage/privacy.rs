@@ -0,0 +1,222 @@
+use super::{NodeStorage, PrivacyTree, StoredPrivacyState, MAX_BATCH_SIZE};
+use nonos_types::{NonosError, NonosResult};
+use sled::{Batch, Tree};
+use std::sync::atomic::Ordering;
+use tracing::{debug, info};
+
+const LEAF_PREFIX: &[u8] = b"l";
+const ROOT_PREFIX: &[u8] = b"r";
+const NULLIFIER_PREFIX: &[u8] = b"n";
+const SPENT_PREFIX: &[u8] = b"s";
+const CURRENT_ROOT_KEY: &[u8] = b"m:root";
+
+fn prefixed_key(prefix: &[u8], id: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(prefix.len() + 8);
+    key.extend_from_slice(prefix);
+    key.extend_from_slice(&id.to_be_bytes());
+    key
+}
+
+fn spent_key(nullifier: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(SPENT_PREFIX.len() + nullifier.len());
+    key.extend_from_slice(SPENT_PREFIX);
+    key.extend_from_slice(nullifier);
+    key
+}
+
+fn root_from_bytes(bytes: &[u8]) -> NonosResult<[u8; 32]> {
+    bytes.try_into()
+        .map_err(|_| NonosError::Storage(format!("Invalid stored root length: {}", bytes.len())))
+}
+
+impl NodeStorage {
+    fn privacy_tree(&self, tree: PrivacyTree) -> &Tree {
+        match tree {
+            PrivacyTree::NoteMixer => &self.mixer_state,
+            PrivacyTree::IdentityRegistry => &self.identity_registry,
+            PrivacyTree::ZkCredentials => &self.zk_credentials,
+            PrivacyTree::ZkSessions => &self.zk_sessions,
+        }
+    }
+
+    fn next_privacy_seq(&self) -> NonosResult<u64> {
+        self.db.generate_id()
+            .map_err(|e| NonosError::Storage(format!("Failed to allocate sequence id: {}", e)))
+    }
+
+    fn apply_privacy_batch(&self, tree: PrivacyTree, batch: Batch, bytes: usize) -> NonosResult<()> {
+        self.metrics.writes.fetch_add(1, Ordering::Relaxed);
+        self.metrics.write_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+
+        self.privacy_tree(tree).apply_batch(batch).map_err(|e| {
+            self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+            NonosError::Storage(format!("Failed to write {} state: {}", tree.name(), e))
+        })?;
+
+        self.db.flush().map_err(|e| NonosError::Storage(format!("Flush error: {}", e)))?;
+        Ok(())
+    }
+
+    /// Appends a Merkle leaf and the resulting root in a single atomic batch.
+    pub fn append_privacy_leaf(
+        &self,
+        tree: PrivacyTree,
+        index: u64,
+        leaf: &[u8],
+        root: &[u8; 32],
+    ) -> NonosResult<()> {
+        let seq = self.next_privacy_seq()?;
+
+        let mut batch = Batch::default();
+        batch.insert(prefixed_key(LEAF_PREFIX, index), leaf);
+        batch.insert(prefixed_key(ROOT_PREFIX, seq), root.as_slice());
+        batch.insert(CURRENT_ROOT_KEY, root.as_slice());
+
+        self.apply_privacy_batch(tree, batch, leaf.len() + 64)?;
+        debug!("Persisted {} leaf {}", tree.name(), index);
+        Ok(())
+    }
+
+    /// Records a nullifier in insertion order and indexes it by value, so
+    /// it can still be found once dropped from an in-memory set.
+    pub fn append_privacy_nullifier(&self, tree: PrivacyTree, nullifier: &[u8]) -> NonosResult<()> {
+        let seq = self.next_privacy_seq()?;
+
+        let mut batch = Batch::default();
+        batch.insert(prefixed_key(NULLIFIER_PREFIX, seq), nullifier);
+        batch.insert(spent_key(nullifier), &[]);
+
+        self.apply_privacy_batch(tree, batch, nullifier.len() * 2)
+    }
+
+    pub fn has_privacy_nullifier(&self, tree: PrivacyTree, nullifier: &[u8]) -> NonosResult<bool> {
+        self.metrics.reads.fetch_add(1, Ordering::Relaxed);
+        self.privacy_tree(tree).contains_key(spent_key(nullifier)).map_err(|e| {
+            self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+            NonosError::Storage(format!("Failed to look up {} nullifier: {}", tree.name(), e))
+        })
+    }
+
+    pub fn store_privacy_root(&self, tree: PrivacyTree, root: &[u8; 32]) -> NonosResult<()> {
+        let seq = self.next_privacy_seq()?;
+
+        let mut batch = Batch::default();
+        batch.insert(prefixed_key(ROOT_PREFIX, seq), root.as_slice());
+        batch.insert(CURRENT_ROOT_KEY, root.as_slice());
+
+        self.apply_privacy_batch(tree, batch, 64)
+    }
+
+    pub fn clear_privacy_nullifiers(&self, tree: PrivacyTree) -> NonosResult<usize> {
+        let sled_tree = self.privacy_tree(tree);
+        let keys: Vec<sled::IVec> = sled_tree
+            .scan_prefix(NULLIFIER_PREFIX)
+            .keys()
+            .chain(sled_tree.scan_prefix(SPENT_PREFIX).keys())
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut batch = Batch::default();
+        for key in &keys {
+            batch.remove(key);
+        }
+
+        self.apply_privacy_batch(tree, batch, 0)?;
+        self.metrics.deletes.fetch_add(keys.len() as u64, Ordering::Relaxed);
+        Ok(keys.len())
+    }
+
+    /// Loads the persisted state for `tree`, keeping only the newest
+    /// `max_roots` accepted roots and pruning the rest from disk.
+    pub fn load_privacy_state(&self, tree: PrivacyTree, max_roots: usize) -> NonosResult<StoredPrivacyState> {
+        let sled_tree = self.privacy_tree(tree);
+        let mut state = StoredPrivacyState::default();
+
+        for result in sled_tree.scan_prefix(LEAF_PREFIX) {
+            let (key, value) = result.map_err(|e| {
+                self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+                NonosError::Storage(format!("Failed to iterate {} leaves: {}", tree.name(), e))
+            })?;
+
+            let index = u64::from_be_bytes(key[LEAF_PREFIX.len()..].try_into()
+                .map_err(|_| NonosError::Storage("Invalid leaf key".into()))?);
+            if index != state.leaves.len() as u64 {
+                return Err(NonosError::Storage(format!(
+                    "{} leaves are not contiguous: expected index {}, found {}",
+                    tree.name(), state.leaves.len(), index
+                )));
+            }
+
+            self.metrics.reads.fetch_add(1, Ordering::Relaxed);
+            self.metrics.read_bytes.fetch_add(value.len() as u64, Ordering::Relaxed);
+            state.leaves.push(value.to_vec());
+        }
+
+        let mut root_keys = Vec::new();
+        for result in sled_tree.scan_prefix(ROOT_PREFIX) {
+            let (key, value) = result.map_err(|e| {
+                self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+                NonosError::Storage(format!("Failed to iterate {} roots: {}", tree.name(), e))
+            })?;
+            root_keys.push(key);
+            state.accepted_roots.push(root_from_bytes(&value)?);
+        }
+
+        if state.accepted_roots.len() > max_roots {
+            let excess = state.accepted_roots.len() - max_roots;
+            state.accepted_roots.drain(..excess);
+
+            for chunk in root_keys[..excess].chunks(MAX_BATCH_SIZE) {
+                let mut batch = Batch::default();
+                for key in chunk {
+                    batch.remove(key);
+                }
+                sled_tree.apply_batch(batch).map_err(|e| {
+                    self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+                    NonosError::Storage(format!("Failed to prune {} roots: {}", tree.name(), e))
+                })?;
+            }
+            self.metrics.deletes.fetch_add(excess as u64, Ordering::Relaxed);
+        }
+
+        for result in sled_tree.scan_prefix(NULLIFIER_PREFIX) {
+            let (_, value) = result.map_err(|e| {
+                self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+                NonosError::Storage(format!("Failed to iterate {} nullifiers: {}", tree.name(), e))
+            })?;
+            self.metrics.read_bytes.fetch_add(value.len() as u64, Ordering::Relaxed);
+            state.nullifiers.push(value.to_vec());
+        }
+
+        state.root = match sled_tree.get(CURRENT_ROOT_KEY)
+            .map_err(|e| NonosError::Storage(format!("Failed to load {} root: {}", tree.name(), e)))?
+        {
+            Some(bytes) => Some(root_from_bytes(&bytes)?),
+            None => None,
+        };
+
+        info!(
+            "Loaded {} state: {} leaves, {} roots, {} nullifiers",
+            tree.name(), state.leaves.len(), state.accepted_roots.len(), state.nullifiers.len()
+        );
+        Ok(state)
+    }
+}
+
+impl StoredPrivacyState {
+    /// Cross-checks a root recomputed from `leaves` against the stored one.
+    pub fn check_root(&self, tree: PrivacyTree, recomputed: &[u8; 32]) -> NonosResult<()> {
+        match self.root {
+            Some(stored) if stored == *recomputed => Ok(()),
+            Some(stored) => Err(NonosError::Storage(format!(
+                "{} root mismatch: stored {}, recomputed {}",
+                tree.name(), hex::encode(stored), hex::encode(recomputed)
+            ))),
+            None if self.leaves.is_empty() => Ok(()),
+            None => Err(NonosError::Storage(format!(
+                "{} has {} leaves but no stored root",
+                tree.name(), self.leaves.len()
+            ))),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use super::NodeStorage;
+use nonos_types::{NonosError, NonosResult, StealthPayment};
+use sled::Batch;
+use std::sync::atomic::Ordering;
+use tracing::debug;
+
+const PAYMENT_PREFIX: &[u8] = b"p";
+const CHECKPOINT_KEY: &[u8] = b"m:checkpoint";
+
+fn payment_key(block_number: u64, log_index: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(PAYMENT_PREFIX.len() + 16);
+    key.extend_from_slice(PAYMENT_PREFIX);
+    key.extend_from_slice(&block_number.to_be_bytes());
+    key.extend_from_slice(&log_index.to_be_bytes());
+    key
+}
+
+impl NodeStorage {
+    /// Last block whose ERC-5564 announcements have been scanned.
+    pub fn stealth_checkpoint(&self) -> NonosResult<Option<u64>> {
+        self.metrics.reads.fetch_add(1, Ordering::Relaxed);
+
+        match self.stealth_payments.get(CHECKPOINT_KEY)
+            .map_err(|e| NonosError::Storage(format!("Failed to load stealth checkpoint: {}", e)))?
+        {
+            Some(bytes) => {
+                let block = u64::from_be_bytes(bytes.as_ref().try_into()
+                    .map_err(|_| NonosError::Storage("Invalid stealth checkpoint".into()))?);
+                Ok(Some(block))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Stores the payments found in a scanned block range and advances the
+    /// checkpoint to `scanned_to` in a single atomic batch, so a crash never
+    /// skips or double-counts a range.
+    pub fn record_stealth_scan(&self, payments: &[StealthPayment], scanned_to: u64) -> NonosResult<()> {
+        let mut batch = Batch::default();
+        let mut bytes = 8;
+
+        for payment in payments {
+            let value = bincode::serialize(payment)
+                .map_err(|e| NonosError::Storage(format!("Failed to serialize stealth payment: {}", e)))?;
+            bytes += value.len();
+
+            let announcement = &payment.announcement;
+            batch.insert(payment_key(announcement.block_number, announcement.log_index), value);
+        }
+        batch.insert(CHECKPOINT_KEY, &scanned_to.to_be_bytes());
+
+        self.metrics.writes.fetch_add(1, Ordering::Relaxed);
+        self.metrics.write_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+
+        self.stealth_payments.apply_batch(batch).map_err(|e| {
+            self.metrics.errors.fetch_add(1, Ordering::Relaxed);
+            NonosError::Storage(format!("Failed to store stealth scan: {}", e))
+        })?;
+
+        self.db.flush().map_err(|e| NonosError::Storage(format!("Flush error: {}", e)))?;
+        debug!("Stealth scan checkpoint at block {} ({} payments)", scanned_to, payments.len());
+        Ok(())
+    }
+
+    /// All detected stealth payments, oldest first.
+    pub fn load_stealth_payments(&self) -> NonosResult<Vec<StealthPayment>> {
+        self.metrics.reads.fetch_add(1, Ordering::Relaxed);
+
+        self.stealth_payments
+            .scan_prefix(PAYMENT_PREFIX)
+            .values()
+            .map(|value| {
+                let value = value
+                    .map_err(|e| NonosError::Storage(format!("Failed to iterate stealth payments: {}", e)))?;
+                bincode::deserialize(&value)
+                    .map_err(|e| NonosError::Storage(format!("Failed to deserialize stealth payment: {}", e)))
+            })
+            .collect()
+    }
+}
@@ -81,6 +81,15 @@ pub struct TreeSizes {
     pub claims: usize,
     pub secrets: usize,
     pub audit_log: usize,
+    pub mixer_state: usize,
+    pub identity_registry: usize,
+    pub zk_credentials: usize,
+    pub zk_sessions: usize,
+    pub stealth_payments: usize,
+    pub filter_lists: usize,
+    pub vault_cookies: usize,
+    pub vault_shares: usize,
+    pub dht_records: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -107,3 +116,31 @@ pub struct TreeIntegrityReport {
     pub valid: usize,
     pub corrupted: usize,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivacyTree {
+    NoteMixer,
+    IdentityRegistry,
+    ZkCredentials,
+    ZkSessions,
+}
+
+impl PrivacyTree {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PrivacyTree::NoteMixer => "mixer_state",
+            PrivacyTree::IdentityRegistry => "identity_registry",
+            PrivacyTree::ZkCredentials => "zk_credentials",
+            PrivacyTree::ZkSessions => "zk_sessions",
+        }
+    }
+}
+
+/// Privacy state as persisted on disk, in insertion order.
+#[derive(Debug, Clone, Default)]
+pub struct StoredPrivacyState {
+    pub leaves: Vec<Vec<u8>>,
+    pub accepted_roots: Vec<[u8; 32]>,
+    pub root: Option<[u8; 32]>,
+    pub nullifiers: Vec<Vec<u8>>,
+}
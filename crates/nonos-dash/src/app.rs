@@ -69,6 +69,10 @@ pub struct StatusApiResponse {
     pub staked_nox: Option<f64>,
     pub pending_rewards: Option<f64>,
     pub streak_days: Option<u32>,
+    pub reachability: Option<String>,
+    pub public_address: Option<String>,
+    pub relay_reservations: Option<u64>,
+    pub hole_punch_successes: Option<u64>,
 }
 
 
@@ -119,6 +123,12 @@ pub struct AppData {
     pub staked_nox: f64,
     pub streak_days: u32,
 
+    // NAT traversal
+    pub reachability: String,
+    pub public_address: Option<String>,
+    pub relay_reservations: u64,
+    pub hole_punch_successes: u64,
+
     // Request stats
     pub total_requests: u64,
     pub successful_requests: u64,
@@ -199,6 +209,16 @@ impl AppData {
         if let Some(successful) = stats.successful_requests {
             self.successful_requests = successful;
         }
+        if let Some(reachability) = stats.reachability {
+            self.reachability = reachability;
+        }
+        self.public_address = stats.public_address;
+        if let Some(reservations) = stats.relay_reservations {
+            self.relay_reservations = reservations;
+        }
+        if let Some(punched) = stats.hole_punch_successes {
+            self.hole_punch_successes = punched;
+        }
 
         // Calculate failed requests
         self.failed_requests = self.total_requests.saturating_sub(self.successful_requests);
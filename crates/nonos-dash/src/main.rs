@@ -542,8 +542,43 @@ fn render_network(f: &mut Frame, app: &mut App, area: Rect) {
         })
         .collect();
 
+    let right_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(5)])
+        .split(chunks[1]);
+
     let peers_list = List::new(peer_items).block(peers_block);
-    f.render_widget(peers_list, chunks[1]);
+    f.render_widget(peers_list, right_chunks[0]);
+
+    // Bottom right: NAT reachability
+    let reachability_style = match data.reachability.as_str() {
+        "public" => Style::default().fg(theme.success),
+        "private" if data.relay_reservations > 0 => Style::default().fg(theme.warning),
+        "private" => Style::default().fg(theme.error),
+        _ => Style::default().fg(theme.label),
+    };
+    let reachability = if data.reachability.is_empty() { "unknown" } else { data.reachability.as_str() };
+
+    let nat_lines = vec![
+        Line::from(vec![
+            Span::styled(" Reachability: ", Style::default().fg(theme.label)),
+            Span::styled(reachability, reachability_style.add_modifier(Modifier::BOLD)),
+            Span::raw(" "),
+            Span::styled(data.public_address.as_deref().unwrap_or(""), Style::default().fg(theme.highlight)),
+        ]),
+        Line::from(vec![
+            Span::styled(" Relays: ", Style::default().fg(theme.label)),
+            Span::styled(data.relay_reservations.to_string(), Style::default().fg(theme.highlight)),
+            Span::styled("  Hole punches: ", Style::default().fg(theme.label)),
+            Span::styled(data.hole_punch_successes.to_string(), Style::default().fg(theme.highlight)),
+        ]),
+    ];
+
+    let nat_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border))
+        .title(Span::styled(" NAT ", Style::default().fg(theme.title)));
+    f.render_widget(Paragraph::new(nat_lines).block(nat_block), right_chunks[1]);
 }
 
 fn render_identities(f: &mut Frame, app: &mut App, area: Rect) {
@@ -13,7 +13,7 @@ pub fn render_network(f: &mut Frame, app: &mut App, area: Rect) {
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(20), Constraint::Length(8)])
+        .constraints([Constraint::Min(20), Constraint::Length(9)])
         .split(area);
 
     let top_chunks = Layout::default()
@@ -95,6 +95,14 @@ fn render_globe_stats(f: &mut Frame, app: &App, area: Rect) {
         Span::styled("  Tracking Blocked: ", Style::default().fg(theme.label)),
         Span::styled(data.tracking_blocked.to_string(), Style::default().fg(theme.success).add_modifier(Modifier::BOLD)),
     ]));
+    lines.push(Line::from(vec![
+        Span::styled(" Reachability: ", Style::default().fg(theme.label)),
+        Span::styled(data.reachability.clone(), Style::default().fg(theme.highlight)),
+        Span::styled("  Relays: ", Style::default().fg(theme.label)),
+        Span::styled(data.relay_reservations.to_string(), Style::default().fg(theme.highlight)),
+        Span::styled("  Hole Punches: ", Style::default().fg(theme.label)),
+        Span::styled(data.hole_punch_successes.to_string(), Style::default().fg(theme.highlight)),
+    ]));
 
     f.render_widget(Paragraph::new(lines).block(block), area);
 }
@@ -496,6 +496,33 @@ impl fmt::Debug for StealthAddress {
     }
 }
 
+/// An ERC-5564 `Announcement` event as emitted by the announcer contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StealthAnnouncement {
+    pub scheme_id: u64,
+    pub stealth_address: EthAddress,
+    pub caller: EthAddress,
+    pub ephemeral_pubkey: Vec<u8>,
+    /// First byte is the view tag; the rest is scheme-defined.
+    pub metadata: Vec<u8>,
+    pub block_number: u64,
+    pub tx_hash: [u8; 32],
+    pub log_index: u64,
+}
+
+impl StealthAnnouncement {
+    pub fn view_tag(&self) -> Option<u8> {
+        self.metadata.first().copied()
+    }
+}
+
+/// An announcement found to pay one of our stealth addresses.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StealthPayment {
+    pub announcement: StealthAnnouncement,
+    pub detected_at: chrono::DateTime<chrono::Utc>,
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct WalletId(pub uuid::Uuid);
 
@@ -529,6 +556,19 @@ impl fmt::Display for WalletId {
     }
 }
 
+/// How account keys are derived from the wallet's mnemonic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DerivationScheme {
+    /// NONOS BLAKE3 child keys; the original scheme.
+    #[default]
+    Blake3,
+    /// BIP-32 secp256k1 at `m/44'/60'/0'/0/i`, compatible with other wallets.
+    Bip44,
+    /// A single imported secp256k1 key, e.g. from a keystore V3 file.
+    Imported,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WalletMetadata {
     pub id: WalletId,
@@ -537,6 +577,8 @@ pub struct WalletMetadata {
     pub last_accessed: chrono::DateTime<chrono::Utc>,
     pub address: EthAddress,
     pub stealth_count: u32,
+    #[serde(default)]
+    pub derivation: DerivationScheme,
 }
 
 impl WalletMetadata {
@@ -549,6 +591,7 @@ impl WalletMetadata {
             last_accessed: now,
             address,
             stealth_count: 0,
+            derivation: DerivationScheme::Blake3,
         }
     }
 }
@@ -661,6 +704,9 @@ pub enum TransactionStatus {
     Confirmed,
     Failed,
     Dropped,
+    /// Superseded by another transaction with the same nonce (speed-up or
+    /// cancel).
+    Replaced,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
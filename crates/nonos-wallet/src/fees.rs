@@ -0,0 +1,322 @@
+use crate::nonce::JsonRpcTransport;
+use chrono::{DateTime, Duration, Utc};
+use nonos_types::{NonosError, NonosResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use tracing::debug;
+
+/// Number of recent blocks sampled by `eth_feeHistory`.
+pub const FEE_HISTORY_BLOCKS: u64 = 20;
+
+/// Priority-fee percentiles requested for the slow, normal and fast tiers.
+pub const FEE_HISTORY_PERCENTILES: [f64; 3] = [10.0, 25.0, 50.0];
+
+/// How long an estimate is reused before `eth_feeHistory` is queried again
+/// (one slot).
+pub const DEFAULT_FEE_CACHE_TTL_SECS: i64 = 12;
+
+/// Priority fee used when the sampled blocks carry no reward data.
+pub const FALLBACK_PRIORITY_FEE: u128 = 1_000_000_000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeSpeed {
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+}
+
+impl std::str::FromStr for FeeSpeed {
+    type Err = NonosError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "slow" => Ok(FeeSpeed::Slow),
+            "normal" => Ok(FeeSpeed::Normal),
+            "fast" => Ok(FeeSpeed::Fast),
+            other => Err(NonosError::Config(format!("Unknown fee speed: {}", other))),
+        }
+    }
+}
+
+impl FeeSpeed {
+    fn index(self) -> usize {
+        match self {
+            FeeSpeed::Slow => 0,
+            FeeSpeed::Normal => 1,
+            FeeSpeed::Fast => 2,
+        }
+    }
+
+    /// Percentage of the next base fee allowed in `max_fee_per_gas`, i.e.
+    /// how many full blocks of base-fee growth the transaction tolerates.
+    fn base_fee_headroom(self) -> u128 {
+        match self {
+            FeeSpeed::Slow => 125,
+            FeeSpeed::Normal => 200,
+            FeeSpeed::Fast => 250,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeTier {
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// Base fee of the next block.
+    pub base_fee_per_gas: u128,
+    pub slow: FeeTier,
+    pub normal: FeeTier,
+    pub fast: FeeTier,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl FeeEstimate {
+    /// Builds tiers from an `eth_feeHistory` result requested with
+    /// [`FEE_HISTORY_PERCENTILES`]. Each tier's priority fee is the median
+    /// of its percentile across non-empty blocks.
+    pub fn from_fee_history(history: &Value) -> NonosResult<Self> {
+        let base_fees = history["baseFeePerGas"]
+            .as_array()
+            .ok_or_else(|| NonosError::Network("Missing baseFeePerGas in fee history".into()))?;
+        let base_fee_per_gas = parse_wei(
+            base_fees
+                .last()
+                .ok_or_else(|| NonosError::Network("Empty fee history".into()))?,
+        )?;
+
+        let rewards = history["reward"].as_array().cloned().unwrap_or_default();
+        let ratios = history["gasUsedRatio"].as_array().cloned().unwrap_or_default();
+
+        let mut columns: [Vec<u128>; 3] = Default::default();
+        for (block, reward) in rewards.iter().enumerate() {
+            let empty = ratios.get(block).and_then(Value::as_f64).is_some_and(|r| r == 0.0);
+            if empty {
+                continue;
+            }
+            let reward = reward
+                .as_array()
+                .ok_or_else(|| NonosError::Network("Malformed reward in fee history".into()))?;
+            for (column, value) in columns.iter_mut().zip(reward) {
+                column.push(parse_wei(value)?);
+            }
+        }
+
+        let mut priority = columns.map(|mut column| median(&mut column).unwrap_or(FALLBACK_PRIORITY_FEE));
+        priority[1] = priority[1].max(priority[0]);
+        priority[2] = priority[2].max(priority[1]);
+
+        let tier = |speed: FeeSpeed| {
+            let max_priority_fee_per_gas = priority[speed.index()];
+            FeeTier {
+                max_fee_per_gas: base_fee_per_gas
+                    .saturating_mul(speed.base_fee_headroom())
+                    / 100
+                    + max_priority_fee_per_gas,
+                max_priority_fee_per_gas,
+            }
+        };
+
+        Ok(Self {
+            base_fee_per_gas,
+            slow: tier(FeeSpeed::Slow),
+            normal: tier(FeeSpeed::Normal),
+            fast: tier(FeeSpeed::Fast),
+            fetched_at: Utc::now(),
+        })
+    }
+
+    pub fn tier(&self, speed: FeeSpeed) -> FeeTier {
+        match speed {
+            FeeSpeed::Slow => self.slow,
+            FeeSpeed::Normal => self.normal,
+            FeeSpeed::Fast => self.fast,
+        }
+    }
+
+    /// Gas price for legacy and EIP-2930 transactions, which pay their full
+    /// price: the next base fee plus one block of growth, plus the tip.
+    pub fn gas_price(&self, speed: FeeSpeed) -> u128 {
+        self.base_fee_per_gas.saturating_mul(9) / 8 + self.tier(speed).max_priority_fee_per_gas
+    }
+}
+
+/// Caches fee estimates per chain ID.
+pub struct FeeOracle {
+    ttl: Duration,
+    cache: RwLock<HashMap<u64, FeeEstimate>>,
+}
+
+impl FeeOracle {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The oracle shared by every wallet and contract client in this
+    /// process, so each chain is queried once per TTL.
+    pub fn shared() -> Arc<FeeOracle> {
+        static SHARED: OnceLock<Arc<FeeOracle>> = OnceLock::new();
+        SHARED.get_or_init(|| Arc::new(FeeOracle::default())).clone()
+    }
+
+    /// Returns the cached estimate for `chain_id` if it is still fresh.
+    pub fn cached(&self, chain_id: u64) -> Option<FeeEstimate> {
+        let cache = self.cache.read().ok()?;
+        cache
+            .get(&chain_id)
+            .filter(|estimate| Utc::now() - estimate.fetched_at < self.ttl)
+            .cloned()
+    }
+
+    pub fn store(&self, chain_id: u64, estimate: FeeEstimate) {
+        if let Ok(mut cache) = self.cache.write() {
+            cache.insert(chain_id, estimate);
+        }
+    }
+
+    /// Returns a fresh estimate for `chain_id`, querying `rpc` on a cache miss.
+    pub async fn estimate<T: JsonRpcTransport + ?Sized>(&self, chain_id: u64, rpc: &T) -> NonosResult<FeeEstimate> {
+        if let Some(estimate) = self.cached(chain_id) {
+            return Ok(estimate);
+        }
+
+        let history = rpc
+            .request(
+                "eth_feeHistory",
+                json!([format!("0x{:x}", FEE_HISTORY_BLOCKS), "latest", FEE_HISTORY_PERCENTILES]),
+            )
+            .await?;
+        let estimate = FeeEstimate::from_fee_history(&history)?;
+        debug!(
+            "Fee estimate for chain {}: base {} wei, tips {}/{}/{} wei",
+            chain_id,
+            estimate.base_fee_per_gas,
+            estimate.slow.max_priority_fee_per_gas,
+            estimate.normal.max_priority_fee_per_gas,
+            estimate.fast.max_priority_fee_per_gas
+        );
+
+        self.store(chain_id, estimate.clone());
+        Ok(estimate)
+    }
+}
+
+impl Default for FeeOracle {
+    fn default() -> Self {
+        Self::new(Duration::seconds(DEFAULT_FEE_CACHE_TTL_SECS))
+    }
+}
+
+fn median(values: &mut [u128]) -> Option<u128> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 {
+        values[mid - 1] / 2 + values[mid] / 2 + (values[mid - 1] % 2 + values[mid] % 2) / 2
+    } else {
+        values[mid]
+    })
+}
+
+fn parse_wei(value: &Value) -> NonosResult<u128> {
+    let hex = value
+        .as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .ok_or_else(|| NonosError::Network(format!("Invalid quantity in fee history: {}", value)))?;
+    u128::from_str_radix(hex, 16)
+        .map_err(|e| NonosError::Network(format!("Failed to parse fee history: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const GWEI: u128 = 1_000_000_000;
+
+    fn history() -> Value {
+        json!({
+            "oldestBlock": "0x10",
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00", "0x3b9aca00", "0x4a817c800"],
+            "gasUsedRatio": [0.5, 0.0, 0.9],
+            "reward": [
+                ["0x3b9aca00", "0x77359400", "0xb2d05e00"],
+                ["0x0", "0x0", "0x0"],
+                ["0x5f5e100", "0x3b9aca00", "0x12a05f200"],
+            ],
+        })
+    }
+
+    struct CountingNode {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl JsonRpcTransport for CountingNode {
+        async fn request(&self, method: &str, params: Value) -> NonosResult<Value> {
+            assert_eq!(method, "eth_feeHistory");
+            assert_eq!(params[0], "0x14");
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(history())
+        }
+    }
+
+    #[test]
+    fn test_tiers_from_fee_history() {
+        let estimate = FeeEstimate::from_fee_history(&history()).unwrap();
+
+        assert_eq!(estimate.base_fee_per_gas, 20 * GWEI);
+        assert_eq!(estimate.slow.max_priority_fee_per_gas, GWEI / 2 + GWEI / 20);
+        assert_eq!(estimate.normal.max_priority_fee_per_gas, 3 * GWEI / 2);
+        assert_eq!(estimate.fast.max_priority_fee_per_gas, 4 * GWEI);
+
+        assert_eq!(estimate.slow.max_fee_per_gas, 25 * GWEI + estimate.slow.max_priority_fee_per_gas);
+        assert_eq!(estimate.normal.max_fee_per_gas, 40 * GWEI + 3 * GWEI / 2);
+        assert_eq!(estimate.fast.max_fee_per_gas, 50 * GWEI + 4 * GWEI);
+        assert_eq!(estimate.gas_price(FeeSpeed::Normal), 22 * GWEI + GWEI / 2 + 3 * GWEI / 2);
+    }
+
+    #[test]
+    fn test_fee_history_without_rewards() {
+        let estimate = FeeEstimate::from_fee_history(&json!({
+            "baseFeePerGas": ["0x1", "0x2"],
+            "gasUsedRatio": [0.0],
+        }))
+        .unwrap();
+
+        assert_eq!(estimate.base_fee_per_gas, 2);
+        assert_eq!(estimate.tier(FeeSpeed::Fast).max_priority_fee_per_gas, FALLBACK_PRIORITY_FEE);
+        assert!(FeeEstimate::from_fee_history(&json!({ "baseFeePerGas": [] })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_oracle_caches_per_chain() {
+        let node = CountingNode { calls: AtomicUsize::new(0) };
+        let oracle = FeeOracle::default();
+
+        oracle.estimate(1, &node).await.unwrap();
+        oracle.estimate(1, &node).await.unwrap();
+        assert_eq!(node.calls.load(Ordering::SeqCst), 1);
+
+        oracle.estimate(11_155_111, &node).await.unwrap();
+        assert_eq!(node.calls.load(Ordering::SeqCst), 2);
+
+        let expired = FeeOracle::new(Duration::zero());
+        expired.estimate(1, &node).await.unwrap();
+        expired.estimate(1, &node).await.unwrap();
+        assert_eq!(node.calls.load(Ordering::SeqCst), 4);
+    }
+}
@@ -6,8 +6,14 @@ pub mod wallet;
 pub mod storage;
 pub mod transaction;
 pub mod account;
+pub mod rlp;
+pub mod nonce;
+pub mod fees;
 
 pub use wallet::*;
 pub use storage::*;
 pub use transaction::*;
 pub use account::*;
+pub use nonce::*;
+pub use fees::*;
+pub use rlp::RlpItem;
@@ -0,0 +1,884 @@
+use crate::fees::{FeeOracle, FeeSpeed};
+use crate::transaction::{SignedTransaction, TransactionRequest, TransactionType};
+use crate::wallet::Wallet;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use ethers::providers::{JsonRpcClient, Provider};
+use nonos_types::{
+    Blake3Hash, EthAddress, NonosError, NonosResult, TokenAmount, TransactionRecord,
+    TransactionStatus,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Minimum fee increase nodes accept for a same-nonce replacement (geth's
+/// default `txpool.pricebump`).
+pub const DEFAULT_FEE_BUMP_PERCENT: u64 = 10;
+
+/// How long a transaction may be unknown to the node before it is treated as
+/// dropped from the mempool.
+pub const DEFAULT_DROP_TIMEOUT_SECS: i64 = 30 * 60;
+
+const CANCEL_GAS_LIMIT: u64 = 21_000;
+
+/// The subset of Ethereum JSON-RPC the nonce manager needs.
+#[async_trait]
+pub trait JsonRpcTransport: Send + Sync {
+    /// Sends a request and returns its `result`, which is `Value::Null` when
+    /// the node has nothing to report (e.g. no receipt yet).
+    async fn request(&self, method: &str, params: Value) -> NonosResult<Value>;
+}
+
+#[async_trait]
+impl<P: JsonRpcClient> JsonRpcTransport for Provider<P> {
+    async fn request(&self, method: &str, params: Value) -> NonosResult<Value> {
+        Provider::request(self, method, params)
+            .await
+            .map_err(|e| NonosError::Network(format!("RPC error: {}", e)))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NonceManagerConfig {
+    pub fee_bump_percent: u64,
+    pub drop_timeout: Duration,
+    /// Tier used to price requests submitted without fees.
+    pub fee_speed: FeeSpeed,
+}
+
+impl Default for NonceManagerConfig {
+    fn default() -> Self {
+        Self {
+            fee_bump_percent: DEFAULT_FEE_BUMP_PERCENT,
+            drop_timeout: Duration::seconds(DEFAULT_DROP_TIMEOUT_SECS),
+            fee_speed: FeeSpeed::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PendingTransaction {
+    pub hash: [u8; 32],
+    pub from: EthAddress,
+    pub account_index: u32,
+    pub nonce: u64,
+    pub request: TransactionRequest,
+    pub submitted_at: DateTime<Utc>,
+    pub status: TransactionStatus,
+    pub block_number: Option<u64>,
+    pub replaces: Option<[u8; 32]>,
+    pub replaced_by: Option<[u8; 32]>,
+}
+
+impl PendingTransaction {
+    fn new(signed: &SignedTransaction, from: EthAddress, account_index: u32) -> Self {
+        Self {
+            hash: signed.hash,
+            from,
+            account_index,
+            nonce: signed.nonce,
+            request: signed.request.clone(),
+            submitted_at: Utc::now(),
+            status: TransactionStatus::Pending,
+            block_number: None,
+            replaces: None,
+            replaced_by: None,
+        }
+    }
+
+    pub fn hash_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.hash))
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == TransactionStatus::Pending
+    }
+
+    fn to_record(&self) -> TransactionRecord {
+        TransactionRecord {
+            hash: Blake3Hash::from_bytes(self.hash),
+            from: self.from,
+            to: self.request.to,
+            amount: TokenAmount::from_raw(self.request.value, 18),
+            gas_price: self.request.gas_price(),
+            gas_limit: self.request.gas_limit,
+            nonce: self.nonce,
+            timestamp: self.submitted_at,
+            status: self.status,
+            block_number: self.block_number,
+            is_private: false,
+        }
+    }
+}
+
+/// A status change observed by [`NonceManager::poll`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionUpdate {
+    pub hash: [u8; 32],
+    pub nonce: u64,
+    pub status: TransactionStatus,
+    pub block_number: Option<u64>,
+    pub replaced_by: Option<[u8; 32]>,
+}
+
+#[derive(Default)]
+struct AccountNonces {
+    next: Option<u64>,
+    released: BTreeSet<u64>,
+}
+
+/// Hands out nonces ahead of the node's `pending` count and follows every
+/// broadcast transaction until it is mined, dropped or replaced.
+pub struct NonceManager<T: JsonRpcTransport> {
+    transport: T,
+    config: NonceManagerConfig,
+    fee_oracle: Arc<FeeOracle>,
+    accounts: HashMap<EthAddress, AccountNonces>,
+    transactions: Vec<PendingTransaction>,
+}
+
+impl<T: JsonRpcTransport> NonceManager<T> {
+    pub fn new(transport: T) -> Self {
+        Self::with_config(transport, NonceManagerConfig::default())
+    }
+
+    pub fn with_config(transport: T, config: NonceManagerConfig) -> Self {
+        Self {
+            transport,
+            config,
+            fee_oracle: FeeOracle::shared(),
+            accounts: HashMap::new(),
+            transactions: Vec::new(),
+        }
+    }
+
+    /// Prices requests submitted with `max_fee_per_gas` left at zero from
+    /// `oracle` instead of the process-wide [`FeeOracle::shared`].
+    pub fn with_fee_oracle(mut self, oracle: Arc<FeeOracle>) -> Self {
+        self.fee_oracle = oracle;
+        self
+    }
+
+    pub fn fee_oracle(&self) -> &Arc<FeeOracle> {
+        &self.fee_oracle
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn transactions(&self) -> &[PendingTransaction] {
+        &self.transactions
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &PendingTransaction> {
+        self.transactions.iter().filter(|tx| tx.is_pending())
+    }
+
+    pub fn get(&self, hash: &[u8; 32]) -> Option<&PendingTransaction> {
+        self.transactions.iter().find(|tx| &tx.hash == hash)
+    }
+
+    /// Pending transactions broadcast more than `older_than` ago that have
+    /// not been replaced yet; candidates for [`Self::speed_up`] or
+    /// [`Self::cancel`].
+    pub fn stuck(&self, older_than: Duration) -> Vec<&PendingTransaction> {
+        let cutoff = Utc::now() - older_than;
+        self.pending()
+            .filter(|tx| tx.submitted_at <= cutoff)
+            .filter(|tx| !self.pending().any(|other| other.replaces == Some(tx.hash)))
+            .collect()
+    }
+
+    /// Forgets transactions that are no longer pending.
+    pub fn prune(&mut self) {
+        self.transactions.retain(|tx| tx.is_pending());
+    }
+
+    pub async fn transaction_count(&self, address: &EthAddress, block: &str) -> NonosResult<u64> {
+        let result = self
+            .transport
+            .request("eth_getTransactionCount", json!([address.to_hex(), block]))
+            .await?;
+        parse_quantity(&result, "transaction count")
+    }
+
+    /// Reserves the next nonce for `address`. Released nonces are reused
+    /// first so that a failed broadcast does not leave a gap.
+    pub async fn reserve_nonce(&mut self, address: &EthAddress) -> NonosResult<u64> {
+        let chain_next = self.transaction_count(address, "pending").await?;
+        let account = self.accounts.entry(*address).or_default();
+
+        account.released.retain(|nonce| *nonce >= chain_next);
+        if let Some(nonce) = account.released.pop_first() {
+            debug!("Reusing released nonce {} for {}", nonce, address);
+            return Ok(nonce);
+        }
+
+        let nonce = account.next.map_or(chain_next, |next| next.max(chain_next));
+        account.next = Some(nonce + 1);
+        debug!("Reserved nonce {} for {}", nonce, address);
+
+        Ok(nonce)
+    }
+
+    /// Returns an unused nonce so the next reservation can take it.
+    pub fn release_nonce(&mut self, address: &EthAddress, nonce: u64) {
+        let Some(account) = self.accounts.get_mut(address) else {
+            return;
+        };
+
+        if account.next == Some(nonce + 1) {
+            let mut next = nonce;
+            while next > 0 && account.released.remove(&(next - 1)) {
+                next -= 1;
+            }
+            account.next = Some(next);
+        } else if account.next.is_some_and(|next| nonce < next) {
+            account.released.insert(nonce);
+        }
+    }
+
+    /// Discards local nonce state for `address`; the next reservation starts
+    /// again from the node's `pending` count.
+    pub fn resync(&mut self, address: &EthAddress) {
+        self.accounts.remove(address);
+    }
+
+    /// Signs `request` with `account_index`, broadcasts it and starts
+    /// tracking it. A nonce is reserved unless the request carries one, and
+    /// zero fees are priced at the configured [`FeeSpeed`] from the oracle.
+    pub async fn submit(
+        &mut self,
+        wallet: &mut Wallet,
+        account_index: u32,
+        mut request: TransactionRequest,
+    ) -> NonosResult<PendingTransaction> {
+        let from = wallet.derive_account(account_index)?;
+        if request.max_fee_per_gas == 0 {
+            let estimate = self.fee_oracle.estimate(request.chain_id, &self.transport).await?;
+            request = request.with_fee_estimate(&estimate, self.config.fee_speed);
+        }
+        let reserved = request.nonce.is_none();
+        let nonce = match request.nonce {
+            Some(nonce) => nonce,
+            None => self.reserve_nonce(&from).await?,
+        };
+
+        let signed = match wallet.sign_transaction(account_index, request, nonce) {
+            Ok(signed) => signed,
+            Err(e) => {
+                if reserved {
+                    self.release_nonce(&from, nonce);
+                }
+                return Err(e);
+            }
+        };
+
+        if let Err(e) = self.broadcast(&signed).await {
+            // A nonce the node calls too low is taken on chain, so it is
+            // re-read rather than handed out again.
+            if is_nonce_too_low(&e) {
+                self.resync(&from);
+            } else if reserved {
+                self.release_nonce(&from, nonce);
+            }
+            return Err(e);
+        }
+
+        if let Some(tracked) = self.get(&signed.hash) {
+            return Ok(tracked.clone());
+        }
+
+        let pending = PendingTransaction::new(&signed, from, account_index);
+        info!("Submitted transaction {} with nonce {}", pending.hash_hex(), nonce);
+        self.track(wallet, pending.clone());
+
+        Ok(pending)
+    }
+
+    /// Rebroadcasts a pending transaction with fees raised by the configured
+    /// bump percentage.
+    pub async fn speed_up(&mut self, wallet: &mut Wallet, hash: &[u8; 32]) -> NonosResult<PendingTransaction> {
+        let original = self.pending_by_hash(hash)?;
+        let mut request = original.request.clone();
+        let (max_fee, max_priority_fee) = self.min_replacement_fees(&original.request);
+        request.max_fee_per_gas = max_fee;
+        request.max_priority_fee_per_gas = max_priority_fee;
+
+        self.replace(wallet, hash, request).await
+    }
+
+    /// Replaces a pending transaction with a zero-value transfer to the
+    /// sender at the same nonce.
+    pub async fn cancel(&mut self, wallet: &mut Wallet, hash: &[u8; 32]) -> NonosResult<PendingTransaction> {
+        let original = self.pending_by_hash(hash)?;
+        let (max_fee, max_priority_fee) = self.min_replacement_fees(&original.request);
+        let request = TransactionRequest {
+            chain_id: original.request.chain_id,
+            to: original.from,
+            value: 0,
+            data: Vec::new(),
+            gas_limit: CANCEL_GAS_LIMIT,
+            max_fee_per_gas: max_fee,
+            max_priority_fee_per_gas: max_priority_fee,
+            nonce: None,
+            tx_type: original.request.tx_type,
+            access_list: Vec::new(),
+        };
+
+        self.replace(wallet, hash, request).await
+    }
+
+    /// Broadcasts `request` at the nonce of pending transaction `hash`. Its
+    /// fees must clear the node's replacement threshold.
+    pub async fn replace(
+        &mut self,
+        wallet: &mut Wallet,
+        hash: &[u8; 32],
+        mut request: TransactionRequest,
+    ) -> NonosResult<PendingTransaction> {
+        let original = self.pending_by_hash(hash)?.clone();
+        let (min_fee, min_priority_fee) = self.min_replacement_fees(&original.request);
+        if request.max_fee_per_gas < min_fee || request.max_priority_fee_per_gas < min_priority_fee {
+            return Err(NonosError::Transaction(format!(
+                "Replacement fees must be at least {}% above the original",
+                self.config.fee_bump_percent
+            )));
+        }
+
+        request.nonce = Some(original.nonce);
+        let signed = wallet.sign_transaction(original.account_index, request, original.nonce)?;
+        self.broadcast(&signed).await?;
+
+        let mut pending = PendingTransaction::new(&signed, original.from, original.account_index);
+        pending.replaces = Some(original.hash);
+        info!(
+            "Replaced transaction {} with {} at nonce {}",
+            original.hash_hex(),
+            pending.hash_hex(),
+            original.nonce
+        );
+        self.track(wallet, pending.clone());
+
+        Ok(pending)
+    }
+
+    /// Checks every pending transaction against the node and records the
+    /// outcome in `wallet`.
+    ///
+    /// Transactions sharing a nonce resolve together: the mined one becomes
+    /// confirmed or failed and the rest replaced. If the nonce was consumed
+    /// by a transaction we did not send, or the node forgot the transaction
+    /// for longer than the drop timeout, it is marked dropped.
+    pub async fn poll(&mut self, wallet: &mut Wallet) -> NonosResult<Vec<TransactionUpdate>> {
+        let mut groups: BTreeMap<([u8; 20], u64), Vec<usize>> = BTreeMap::new();
+        for (index, tx) in self.transactions.iter().enumerate() {
+            if tx.is_pending() {
+                groups.entry((tx.from.0, tx.nonce)).or_default().push(index);
+            }
+        }
+
+        let mut latest_nonces: HashMap<EthAddress, u64> = HashMap::new();
+        let mut updates = Vec::new();
+        let now = Utc::now();
+
+        for ((from, nonce), indices) in groups {
+            let from = EthAddress::from_bytes(from);
+            let mut mined = self.mined(&indices).await?;
+
+            if mined.is_none() {
+                let latest = match latest_nonces.get(&from) {
+                    Some(latest) => *latest,
+                    None => {
+                        let latest = self.transaction_count(&from, "latest").await?;
+                        latest_nonces.insert(from, latest);
+                        latest
+                    }
+                };
+
+                if latest > nonce {
+                    // One of ours may have been mined after the receipt check.
+                    mined = self.mined(&indices).await?;
+                    if mined.is_none() {
+                        warn!("Nonce {} of {} was consumed by an unknown transaction", nonce, from);
+                        for &index in &indices {
+                            let tx = &mut self.transactions[index];
+                            tx.status = TransactionStatus::Dropped;
+                            updates.push(update_for(tx));
+                        }
+                        continue;
+                    }
+                }
+            }
+
+            if let Some((mined_index, (success, block_number))) = mined {
+                let mined_hash = self.transactions[mined_index].hash;
+                for &index in &indices {
+                    let tx = &mut self.transactions[index];
+                    if index == mined_index {
+                        tx.status = if success { TransactionStatus::Confirmed } else { TransactionStatus::Failed };
+                        tx.block_number = Some(block_number);
+                    } else {
+                        tx.status = TransactionStatus::Replaced;
+                        tx.replaced_by = Some(mined_hash);
+                    }
+                    updates.push(update_for(tx));
+                }
+                continue;
+            }
+
+            let mut dropped = 0;
+            for &index in &indices {
+                let tx = &self.transactions[index];
+                if now - tx.submitted_at < self.config.drop_timeout || self.is_known(&tx.hash).await? {
+                    continue;
+                }
+
+                let tx = &mut self.transactions[index];
+                warn!("Transaction {} is no longer known to the node", tx.hash_hex());
+                tx.status = TransactionStatus::Dropped;
+                updates.push(update_for(tx));
+                dropped += 1;
+            }
+
+            if dropped == indices.len() {
+                self.release_nonce(&from, nonce);
+            }
+        }
+
+        for update in &updates {
+            wallet.record_transaction_outcome(
+                &Blake3Hash::from_bytes(update.hash),
+                update.status,
+                update.block_number,
+            );
+        }
+
+        Ok(updates)
+    }
+
+    /// Minimum `(max_fee_per_gas, max_priority_fee_per_gas)` a node accepts
+    /// to replace `request`.
+    pub fn min_replacement_fees(&self, request: &TransactionRequest) -> (u128, u128) {
+        let max_fee = bump_fee(request.max_fee_per_gas, self.config.fee_bump_percent);
+        match request.tx_type {
+            TransactionType::Eip1559 => (
+                max_fee,
+                bump_fee(request.max_priority_fee_per_gas, self.config.fee_bump_percent),
+            ),
+            _ => (max_fee, max_fee),
+        }
+    }
+
+    fn pending_by_hash(&self, hash: &[u8; 32]) -> NonosResult<&PendingTransaction> {
+        let tx = self
+            .get(hash)
+            .ok_or_else(|| NonosError::Transaction("Unknown transaction".into()))?;
+        if !tx.is_pending() {
+            return Err(NonosError::Transaction(format!(
+                "Transaction {} is no longer pending",
+                tx.hash_hex()
+            )));
+        }
+        Ok(tx)
+    }
+
+    fn track(&mut self, wallet: &mut Wallet, pending: PendingTransaction) {
+        wallet.add_transaction(pending.to_record());
+        self.transactions.push(pending);
+    }
+
+    /// Sends `signed` to the node. A transaction the node already holds
+    /// counts as sent.
+    async fn broadcast(&self, signed: &SignedTransaction) -> NonosResult<()> {
+        let expected = signed.hash_hex();
+        let result = match self
+            .transport
+            .request("eth_sendRawTransaction", json!([signed.raw_hex()]))
+            .await
+        {
+            Ok(result) => result,
+            Err(e) if is_already_known(&e) => {
+                debug!("Transaction {} is already known to the node", expected);
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+
+        match result.as_str() {
+            Some(hash) if hash.eq_ignore_ascii_case(&expected) => {}
+            Some(hash) => warn!("Node returned hash {} for transaction {}", hash, expected),
+            None => warn!("Node returned no hash for transaction {}", expected),
+        }
+
+        Ok(())
+    }
+
+    /// The first of the transactions at `indices` with a receipt.
+    async fn mined(&self, indices: &[usize]) -> NonosResult<Option<(usize, (bool, u64))>> {
+        for &index in indices {
+            if let Some(receipt) = self.receipt(&self.transactions[index].hash).await? {
+                return Ok(Some((index, receipt)));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn receipt(&self, hash: &[u8; 32]) -> NonosResult<Option<(bool, u64)>> {
+        let receipt = self
+            .transport
+            .request("eth_getTransactionReceipt", json!([format!("0x{}", hex::encode(hash))]))
+            .await?;
+        if receipt.is_null() {
+            return Ok(None);
+        }
+
+        let block_number = parse_quantity(&receipt["blockNumber"], "receipt block number")?;
+        let success = parse_quantity(&receipt["status"], "receipt status")? == 1;
+
+        Ok(Some((success, block_number)))
+    }
+
+    async fn is_known(&self, hash: &[u8; 32]) -> NonosResult<bool> {
+        let tx = self
+            .transport
+            .request("eth_getTransactionByHash", json!([format!("0x{}", hex::encode(hash))]))
+            .await?;
+        Ok(!tx.is_null())
+    }
+}
+
+fn update_for(tx: &PendingTransaction) -> TransactionUpdate {
+    TransactionUpdate {
+        hash: tx.hash,
+        nonce: tx.nonce,
+        status: tx.status,
+        block_number: tx.block_number,
+        replaced_by: tx.replaced_by,
+    }
+}
+
+fn bump_fee(fee: u128, percent: u64) -> u128 {
+    let bumped = fee.saturating_mul(100 + percent as u128).div_ceil(100);
+    bumped.max(fee.saturating_add(1))
+}
+
+fn is_nonce_too_low(error: &NonosError) -> bool {
+    error.to_string().to_lowercase().contains("nonce too low")
+}
+
+fn is_already_known(error: &NonosError) -> bool {
+    let message = error.to_string().to_lowercase();
+    message.contains("already known") || message.contains("known transaction")
+}
+
+fn parse_quantity(value: &Value, what: &str) -> NonosResult<u64> {
+    let hex = value
+        .as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .ok_or_else(|| NonosError::Network(format!("Missing {} in RPC response", what)))?;
+    u64::from_str_radix(hex, 16)
+        .map_err(|e| NonosError::Network(format!("Failed to parse {}: {}", what, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::SignedTransaction;
+    use std::sync::Mutex;
+
+    /// In-process stand-in for an Ethereum node's JSON-RPC endpoint.
+    #[derive(Default)]
+    struct MockNode {
+        chain: Mutex<MockChain>,
+    }
+
+    #[derive(Default)]
+    struct MockChain {
+        latest: HashMap<EthAddress, u64>,
+        mempool: HashMap<String, (EthAddress, u64)>,
+        receipts: HashMap<String, Value>,
+        block: u64,
+        fail_next_send: bool,
+        /// Mined when the latest nonce is next read.
+        mine_on_count: Option<String>,
+    }
+
+    impl MockChain {
+        fn mine(&mut self, hash: &str, success: bool) {
+            let (from, nonce) = self.mempool[hash];
+            self.mempool.retain(|_, (f, n)| !(*f == from && *n == nonce));
+            self.block += 1;
+            let receipt = json!({
+                "transactionHash": hash,
+                "blockNumber": format!("0x{:x}", self.block),
+                "status": if success { "0x1" } else { "0x0" },
+            });
+            self.receipts.insert(hash.to_string(), receipt);
+            self.latest.insert(from, nonce + 1);
+        }
+    }
+
+    impl MockNode {
+        fn mine(&self, hash: &str, success: bool) {
+            self.chain.lock().unwrap().mine(hash, success);
+        }
+
+        fn consume_nonce(&self, from: EthAddress, nonce: u64) {
+            let mut chain = self.chain.lock().unwrap();
+            chain.mempool.retain(|_, (f, n)| !(*f == from && *n == nonce));
+            chain.latest.insert(from, nonce + 1);
+        }
+
+        fn evict(&self, hash: &str) {
+            self.chain.lock().unwrap().mempool.remove(hash);
+        }
+    }
+
+    #[async_trait]
+    impl JsonRpcTransport for MockNode {
+        async fn request(&self, method: &str, params: Value) -> NonosResult<Value> {
+            let mut chain = self.chain.lock().unwrap();
+            match method {
+                "eth_getTransactionCount" => {
+                    let address = EthAddress::from_hex(params[0].as_str().unwrap())?;
+                    if params[1] == "latest" {
+                        if let Some(hash) = chain.mine_on_count.take() {
+                            chain.mine(&hash, true);
+                        }
+                    }
+                    let latest = chain.latest.get(&address).copied().unwrap_or(0);
+                    let count = if params[1] == "pending" {
+                        chain.mempool.values()
+                            .filter(|(from, _)| *from == address)
+                            .map(|(_, nonce)| nonce + 1)
+                            .fold(latest, u64::max)
+                    } else {
+                        latest
+                    };
+                    Ok(json!(format!("0x{:x}", count)))
+                }
+                "eth_sendRawTransaction" => {
+                    if std::mem::take(&mut chain.fail_next_send) {
+                        return Err(NonosError::Network("RPC error: insufficient funds".into()));
+                    }
+                    let signed = SignedTransaction::from_hex(params[0].as_str().unwrap())?;
+                    let from = signed.recover_sender()?;
+                    if signed.nonce < chain.latest.get(&from).copied().unwrap_or(0) {
+                        return Err(NonosError::Network("RPC error: nonce too low".into()));
+                    }
+                    if chain.mempool.contains_key(&signed.hash_hex()) {
+                        return Err(NonosError::Network("RPC error: already known".into()));
+                    }
+                    chain.mempool.insert(signed.hash_hex(), (from, signed.nonce));
+                    Ok(json!(signed.hash_hex()))
+                }
+                "eth_getTransactionReceipt" => {
+                    Ok(chain.receipts.get(params[0].as_str().unwrap()).cloned().unwrap_or(Value::Null))
+                }
+                "eth_getTransactionByHash" => {
+                    let hash = params[0].as_str().unwrap();
+                    Ok(if chain.mempool.contains_key(hash) { json!({ "hash": hash }) } else { Value::Null })
+                }
+                other => Err(NonosError::Network(format!("Unsupported method {}", other))),
+            }
+        }
+    }
+
+    fn test_wallet() -> Wallet {
+        let (wallet, _, _) = Wallet::create("Nonce Test".into()).unwrap();
+        wallet
+    }
+
+    fn transfer(fee: u128) -> TransactionRequest {
+        TransactionRequest::transfer(EthAddress::from_bytes([0x42; 20]), TokenAmount::from_raw(1, 18), 1)
+            .with_gas(21_000, fee, fee / 10)
+    }
+
+    #[tokio::test]
+    async fn test_reserve_and_release_nonces() {
+        let mut manager = NonceManager::new(MockNode::default());
+        let address = EthAddress::from_bytes([7; 20]);
+        manager.transport().chain.lock().unwrap().latest.insert(address, 5);
+
+        assert_eq!(manager.reserve_nonce(&address).await.unwrap(), 5);
+        assert_eq!(manager.reserve_nonce(&address).await.unwrap(), 6);
+        assert_eq!(manager.reserve_nonce(&address).await.unwrap(), 7);
+
+        manager.release_nonce(&address, 6);
+        assert_eq!(manager.reserve_nonce(&address).await.unwrap(), 6);
+        assert_eq!(manager.reserve_nonce(&address).await.unwrap(), 8);
+
+        manager.release_nonce(&address, 8);
+        manager.release_nonce(&address, 7);
+        assert_eq!(manager.reserve_nonce(&address).await.unwrap(), 7);
+
+        manager.transport().chain.lock().unwrap().latest.insert(address, 20);
+        assert_eq!(manager.reserve_nonce(&address).await.unwrap(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_confirm() {
+        let mut wallet = test_wallet();
+        let mut manager = NonceManager::new(MockNode::default());
+
+        let first = manager.submit(&mut wallet, 0, transfer(1_000)).await.unwrap();
+        let second = manager.submit(&mut wallet, 0, transfer(1_000)).await.unwrap();
+        assert_eq!(first.nonce, 0);
+        assert_eq!(second.nonce, 1);
+        assert_eq!(wallet.transactions().len(), 2);
+
+        assert!(manager.poll(&mut wallet).await.unwrap().is_empty());
+
+        manager.transport().mine(&first.hash_hex(), true);
+        manager.transport().mine(&second.hash_hex(), false);
+        let updates = manager.poll(&mut wallet).await.unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(manager.get(&first.hash).unwrap().status, TransactionStatus::Confirmed);
+        assert_eq!(manager.get(&second.hash).unwrap().status, TransactionStatus::Failed);
+
+        let record = &wallet.transactions()[0];
+        assert_eq!(record.status, TransactionStatus::Confirmed);
+        assert_eq!(record.block_number, Some(1));
+        assert_eq!(manager.pending().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_broadcast_releases_nonce() {
+        let mut wallet = test_wallet();
+        let mut manager = NonceManager::new(MockNode::default());
+
+        manager.transport().chain.lock().unwrap().fail_next_send = true;
+        assert!(manager.submit(&mut wallet, 0, transfer(1_000)).await.is_err());
+        assert!(wallet.transactions().is_empty());
+
+        let tx = manager.submit(&mut wallet, 0, transfer(1_000)).await.unwrap();
+        assert_eq!(tx.nonce, 0);
+    }
+
+    #[tokio::test]
+    async fn test_already_known_counts_as_sent() {
+        let mut wallet = test_wallet();
+        let mut manager = NonceManager::new(MockNode::default());
+
+        let first = manager.submit(&mut wallet, 0, transfer(1_000)).await.unwrap();
+        let resent = manager
+            .submit(&mut wallet, 0, transfer(1_000).with_nonce(first.nonce))
+            .await
+            .unwrap();
+        assert_eq!(resent.hash, first.hash);
+        assert_eq!(manager.transactions().len(), 1);
+        assert_eq!(wallet.transactions().len(), 1);
+
+        let next = manager.submit(&mut wallet, 0, transfer(1_000)).await.unwrap();
+        assert_eq!(next.nonce, 1);
+    }
+
+    #[tokio::test]
+    async fn test_speed_up_replaces_original() {
+        let mut wallet = test_wallet();
+        let mut manager = NonceManager::new(MockNode::default());
+
+        let original = manager.submit(&mut wallet, 0, transfer(1_000)).await.unwrap();
+        assert_eq!(manager.stuck(Duration::zero()).len(), 1);
+
+        let faster = manager.speed_up(&mut wallet, &original.hash).await.unwrap();
+        assert_eq!(faster.nonce, original.nonce);
+        assert_eq!(faster.replaces, Some(original.hash));
+        assert_eq!(faster.request.max_fee_per_gas, 1_100);
+        assert_eq!(faster.request.max_priority_fee_per_gas, 110);
+
+        let stuck = manager.stuck(Duration::zero());
+        assert_eq!(stuck.len(), 1);
+        assert_eq!(stuck[0].hash, faster.hash);
+
+        let underpriced = transfer(1_050);
+        assert!(manager.replace(&mut wallet, &faster.hash, underpriced).await.is_err());
+
+        manager.transport().mine(&faster.hash_hex(), true);
+        manager.poll(&mut wallet).await.unwrap();
+
+        let original = manager.get(&original.hash).unwrap();
+        assert_eq!(original.status, TransactionStatus::Replaced);
+        assert_eq!(original.replaced_by, Some(faster.hash));
+        assert_eq!(manager.get(&faster.hash).unwrap().status, TransactionStatus::Confirmed);
+        assert_eq!(wallet.transactions()[0].status, TransactionStatus::Replaced);
+        assert!(manager.speed_up(&mut wallet, &faster.hash).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_sends_zero_value_self_transfer() {
+        let mut wallet = test_wallet();
+        let mut manager = NonceManager::new(MockNode::default());
+
+        let original = manager.submit(&mut wallet, 0, transfer(1_000)).await.unwrap();
+        let cancel = manager.cancel(&mut wallet, &original.hash).await.unwrap();
+
+        assert_eq!(cancel.nonce, original.nonce);
+        assert_eq!(cancel.request.to, original.from);
+        assert_eq!(cancel.request.value, 0);
+        assert!(cancel.request.data.is_empty());
+
+        manager.transport().mine(&cancel.hash_hex(), true);
+        manager.poll(&mut wallet).await.unwrap();
+        assert_eq!(manager.get(&original.hash).unwrap().status, TransactionStatus::Replaced);
+    }
+
+    #[tokio::test]
+    async fn test_submit_prices_zero_fees_from_oracle() {
+        let mut wallet = test_wallet();
+        let oracle = Arc::new(FeeOracle::default());
+        oracle.store(1, crate::fees::FeeEstimate {
+            base_fee_per_gas: 100,
+            slow: crate::fees::FeeTier { max_fee_per_gas: 130, max_priority_fee_per_gas: 5 },
+            normal: crate::fees::FeeTier { max_fee_per_gas: 210, max_priority_fee_per_gas: 10 },
+            fast: crate::fees::FeeTier { max_fee_per_gas: 270, max_priority_fee_per_gas: 20 },
+            fetched_at: Utc::now(),
+        });
+        let mut manager = NonceManager::new(MockNode::default()).with_fee_oracle(oracle);
+
+        let request = TransactionRequest::transfer(EthAddress::from_bytes([0x42; 20]), TokenAmount::from_raw(1, 18), 1);
+        let tx = manager.submit(&mut wallet, 0, request).await.unwrap();
+        assert_eq!(tx.request.max_fee_per_gas, 210);
+        assert_eq!(tx.request.max_priority_fee_per_gas, 10);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_transactions() {
+        let mut wallet = test_wallet();
+        let config = NonceManagerConfig { drop_timeout: Duration::zero(), ..Default::default() };
+        let mut manager = NonceManager::with_config(MockNode::default(), config);
+
+        let consumed = manager.submit(&mut wallet, 0, transfer(1_000)).await.unwrap();
+        manager.transport().consume_nonce(consumed.from, consumed.nonce);
+
+        let evicted = manager.submit(&mut wallet, 0, transfer(1_000)).await.unwrap();
+        assert_eq!(evicted.nonce, 1);
+        manager.transport().evict(&evicted.hash_hex());
+
+        let updates = manager.poll(&mut wallet).await.unwrap();
+        assert_eq!(updates.len(), 2);
+        assert!(updates.iter().all(|u| u.status == TransactionStatus::Dropped));
+
+        let retry = manager.submit(&mut wallet, 0, transfer(1_000)).await.unwrap();
+        assert_eq!(retry.nonce, 1);
+    }
+
+    #[tokio::test]
+    async fn test_mined_during_poll_is_not_dropped() {
+        let mut wallet = test_wallet();
+        let mut manager = NonceManager::new(MockNode::default());
+
+        let tx = manager.submit(&mut wallet, 0, transfer(1_000)).await.unwrap();
+        manager.transport().chain.lock().unwrap().mine_on_count = Some(tx.hash_hex());
+
+        let updates = manager.poll(&mut wallet).await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].status, TransactionStatus::Confirmed);
+        assert_eq!(manager.get(&tx.hash).unwrap().status, TransactionStatus::Confirmed);
+    }
+}
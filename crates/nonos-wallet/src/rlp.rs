@@ -0,0 +1,231 @@
+use nonos_types::{NonosError, NonosResult};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RlpItem {
+    Bytes(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+impl RlpItem {
+    pub fn bytes(data: impl Into<Vec<u8>>) -> Self {
+        RlpItem::Bytes(data.into())
+    }
+
+    pub fn uint(value: u128) -> Self {
+        let bytes = value.to_be_bytes();
+        let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+        RlpItem::Bytes(bytes[start..].to_vec())
+    }
+
+    pub fn uint_bytes(value: &[u8]) -> Self {
+        let start = value.iter().position(|&b| b != 0).unwrap_or(value.len());
+        RlpItem::Bytes(value[start..].to_vec())
+    }
+
+    pub fn as_bytes(&self) -> NonosResult<&[u8]> {
+        match self {
+            RlpItem::Bytes(bytes) => Ok(bytes),
+            RlpItem::List(_) => Err(NonosError::Serialization("Expected RLP string, found list".into())),
+        }
+    }
+
+    pub fn as_list(&self) -> NonosResult<&[RlpItem]> {
+        match self {
+            RlpItem::List(items) => Ok(items),
+            RlpItem::Bytes(_) => Err(NonosError::Serialization("Expected RLP list, found string".into())),
+        }
+    }
+
+    pub fn as_u128(&self) -> NonosResult<u128> {
+        let bytes = self.as_bytes()?;
+        if bytes.len() > 16 {
+            return Err(NonosError::Serialization("RLP integer exceeds 128 bits".into()));
+        }
+        if bytes.first() == Some(&0) {
+            return Err(NonosError::Serialization("RLP integer has leading zero".into()));
+        }
+        Ok(bytes.iter().fold(0u128, |acc, &b| (acc << 8) | b as u128))
+    }
+
+    pub fn as_u64(&self) -> NonosResult<u64> {
+        let value = self.as_u128()?;
+        u64::try_from(value)
+            .map_err(|_| NonosError::Serialization("RLP integer exceeds 64 bits".into()))
+    }
+
+    pub fn as_word(&self) -> NonosResult<[u8; 32]> {
+        let bytes = self.as_bytes()?;
+        if bytes.len() > 32 {
+            return Err(NonosError::Serialization("RLP word exceeds 32 bytes".into()));
+        }
+        let mut word = [0u8; 32];
+        word[32 - bytes.len()..].copy_from_slice(bytes);
+        Ok(word)
+    }
+
+    pub fn as_fixed<const N: usize>(&self) -> NonosResult<[u8; N]> {
+        let bytes = self.as_bytes()?;
+        bytes.try_into().map_err(|_| {
+            NonosError::Serialization(format!("Expected {} byte RLP string, got {}", N, bytes.len()))
+        })
+    }
+}
+
+pub fn encode(item: &RlpItem) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_into(item, &mut out);
+    out
+}
+
+fn encode_into(item: &RlpItem, out: &mut Vec<u8>) {
+    match item {
+        RlpItem::Bytes(bytes) => {
+            if bytes.len() == 1 && bytes[0] < 0x80 {
+                out.push(bytes[0]);
+            } else {
+                encode_length(bytes.len(), 0x80, out);
+                out.extend_from_slice(bytes);
+            }
+        }
+        RlpItem::List(items) => {
+            let mut payload = Vec::new();
+            for item in items {
+                encode_into(item, &mut payload);
+            }
+            encode_length(payload.len(), 0xc0, out);
+            out.extend(payload);
+        }
+    }
+}
+
+fn encode_length(len: usize, offset: u8, out: &mut Vec<u8>) {
+    if len < 56 {
+        out.push(offset + len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let start = len_bytes.iter().position(|&b| b != 0).unwrap_or(len_bytes.len());
+        out.push(offset + 55 + (len_bytes.len() - start) as u8);
+        out.extend_from_slice(&len_bytes[start..]);
+    }
+}
+
+pub fn decode(data: &[u8]) -> NonosResult<RlpItem> {
+    let (item, consumed) = decode_item(data)?;
+    if consumed != data.len() {
+        return Err(NonosError::Serialization(format!(
+            "Trailing bytes after RLP item: {}",
+            data.len() - consumed
+        )));
+    }
+    Ok(item)
+}
+
+fn decode_item(data: &[u8]) -> NonosResult<(RlpItem, usize)> {
+    let prefix = *data
+        .first()
+        .ok_or_else(|| NonosError::Serialization("Unexpected end of RLP input".into()))?;
+
+    match prefix {
+        0x00..=0x7f => Ok((RlpItem::Bytes(vec![prefix]), 1)),
+        0x80..=0xbf => {
+            let (offset, len) = decode_length(data, 0x80)?;
+            let bytes = &data[offset..offset + len];
+            if len == 1 && bytes[0] < 0x80 {
+                return Err(NonosError::Serialization("Non-canonical RLP single byte".into()));
+            }
+            Ok((RlpItem::Bytes(bytes.to_vec()), offset + len))
+        }
+        0xc0..=0xff => {
+            let (offset, len) = decode_length(data, 0xc0)?;
+            let mut payload = &data[offset..offset + len];
+            let mut items = Vec::new();
+            while !payload.is_empty() {
+                let (item, consumed) = decode_item(payload)?;
+                items.push(item);
+                payload = &payload[consumed..];
+            }
+            Ok((RlpItem::List(items), offset + len))
+        }
+    }
+}
+
+fn decode_length(data: &[u8], offset: u8) -> NonosResult<(usize, usize)> {
+    let short_len = data[0] - offset;
+
+    let (header, len) = if short_len < 56 {
+        (1, short_len as usize)
+    } else {
+        let len_of_len = (short_len - 55) as usize;
+        if len_of_len > std::mem::size_of::<usize>() || data.len() < 1 + len_of_len {
+            return Err(NonosError::Serialization("Invalid RLP length prefix".into()));
+        }
+        let len_bytes = &data[1..1 + len_of_len];
+        if len_bytes[0] == 0 {
+            return Err(NonosError::Serialization("RLP length has leading zero".into()));
+        }
+        let len = len_bytes.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        if len < 56 {
+            return Err(NonosError::Serialization("Non-canonical RLP long length".into()));
+        }
+        (1 + len_of_len, len)
+    };
+
+    if data.len() - header < len {
+        return Err(NonosError::Serialization("RLP item exceeds input length".into()));
+    }
+
+    Ok((header, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_canonical_examples() {
+        assert_eq!(encode(&RlpItem::bytes(b"dog".to_vec())), vec![0x83, b'd', b'o', b'g']);
+        assert_eq!(encode(&RlpItem::List(vec![])), vec![0xc0]);
+        assert_eq!(encode(&RlpItem::uint(0)), vec![0x80]);
+        assert_eq!(encode(&RlpItem::uint(15)), vec![0x0f]);
+        assert_eq!(encode(&RlpItem::uint(1024)), vec![0x82, 0x04, 0x00]);
+
+        let cat_dog = RlpItem::List(vec![
+            RlpItem::bytes(b"cat".to_vec()),
+            RlpItem::bytes(b"dog".to_vec()),
+        ]);
+        assert_eq!(
+            encode(&cat_dog),
+            vec![0xc8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g']
+        );
+
+        let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit".to_vec();
+        let encoded = encode(&RlpItem::bytes(lorem.clone()));
+        assert_eq!(&encoded[..2], &[0xb8, 0x38]);
+        assert_eq!(&encoded[2..], &lorem[..]);
+    }
+
+    #[test]
+    fn test_roundtrip_nested() {
+        let item = RlpItem::List(vec![
+            RlpItem::List(vec![]),
+            RlpItem::List(vec![RlpItem::List(vec![])]),
+            RlpItem::List(vec![
+                RlpItem::List(vec![]),
+                RlpItem::List(vec![RlpItem::List(vec![])]),
+            ]),
+            RlpItem::bytes(vec![0xab; 300]),
+        ]);
+
+        let encoded = encode(&item);
+        assert_eq!(decode(&encoded).unwrap(), item);
+    }
+
+    #[test]
+    fn test_decode_rejects_non_canonical() {
+        assert!(decode(&[0x81, 0x05]).is_err());
+        assert!(decode(&[0xb8, 0x05, 1, 2, 3, 4, 5]).is_err());
+        assert!(decode(&[0x83, b'd', b'o']).is_err());
+        assert!(decode(&[0x0f, 0x0f]).is_err());
+        assert!(RlpItem::Bytes(vec![0x00, 0x01]).as_u128().is_err());
+    }
+}
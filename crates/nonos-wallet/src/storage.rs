@@ -1,7 +1,11 @@
-use nonos_crypto::{decrypt_wallet, encrypt_wallet, EncryptedWallet};
-use nonos_types::{EthAddress, NonosError, NonosResult, WalletId, WalletMetadata};
+use crate::wallet::Wallet;
+use nonos_crypto::{
+    decrypt_keystore, decrypt_wallet, encrypt_wallet, EncryptedWallet, KeystoreKdf, KeystoreV3,
+};
+use nonos_types::{DerivationScheme, EthAddress, NonosError, NonosResult, WalletId, WalletMetadata};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use zeroize::Zeroize;
 
 const WALLET_FILE_PERMS: u32 = 0o600;
 
@@ -13,6 +17,8 @@ pub struct WalletFile {
     pub address: String,
     pub encrypted: EncryptedWallet,
     pub created_at: String,
+    #[serde(default)]
+    pub derivation: DerivationScheme,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -20,16 +26,119 @@ struct WalletSecrets {
     master_key: String,
     accounts: Vec<(u32, String)>,
     stealth_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    extended_key: Option<String>,
+}
+
+fn seal_wallet_file(
+    metadata: &WalletMetadata,
+    master_key: &[u8; 32],
+    extended_key: Option<&str>,
+    password: &str,
+) -> NonosResult<WalletFile> {
+    let secrets = WalletSecrets {
+        master_key: hex::encode(master_key),
+        accounts: vec![(0, metadata.address.to_hex())],
+        stealth_count: metadata.stealth_count,
+        extended_key: extended_key.map(str::to_string),
+    };
+
+    let plaintext = serde_json::to_vec(&secrets)
+        .map_err(|e| NonosError::Serialization(e.to_string()))?;
+
+    let encrypted = encrypt_wallet(password.as_bytes(), &plaintext)?;
+
+    Ok(WalletFile {
+        version: 2,
+        id: metadata.id.to_string(),
+        name: metadata.name.clone(),
+        address: metadata.address.to_hex(),
+        encrypted,
+        created_at: metadata.created_at.to_rfc3339(),
+        derivation: metadata.derivation,
+    })
+}
+
+fn open_wallet_file(file: &WalletFile, password: &str) -> NonosResult<WalletSecrets> {
+    let plaintext = decrypt_wallet(password.as_bytes(), &file.encrypted)?;
+
+    serde_json::from_slice(&plaintext)
+        .map_err(|e| NonosError::Storage(format!("Failed to parse secrets: {}", e)))
 }
 
 pub trait WalletStorage: Send + Sync {
     fn list_wallets(&self) -> NonosResult<Vec<WalletId>>;
     fn load_metadata(&self, id: &WalletId) -> NonosResult<WalletMetadata>;
-    fn save_wallet(&self, metadata: &WalletMetadata, master_key: &[u8; 32], password: &str) -> NonosResult<()>;
+    fn save_wallet(&self, metadata: &WalletMetadata, master_key: &[u8; 32], password: &str) -> NonosResult<()> {
+        self.save_wallet_with_extended_key(metadata, master_key, None, password)
+    }
+    /// Saves a wallet together with its BIP-44 account `xprv`, which
+    /// BIP-44 wallets need to unlock.
+    fn save_wallet_with_extended_key(
+        &self,
+        metadata: &WalletMetadata,
+        master_key: &[u8; 32],
+        extended_key: Option<&str>,
+        password: &str,
+    ) -> NonosResult<()>;
     fn load_secrets(&self, id: &WalletId, password: &str) -> NonosResult<[u8; 32]>;
+    fn load_extended_key(&self, id: &WalletId, password: &str) -> NonosResult<Option<String>>;
     fn delete_wallet(&self, id: &WalletId) -> NonosResult<()>;
     fn wallet_exists(&self, id: &WalletId) -> bool;
     fn change_password(&self, id: &WalletId, old_password: &str, new_password: &str) -> NonosResult<()>;
+
+    /// Persists an unlocked wallet with whichever secrets its derivation
+    /// scheme needs to be restored by [`WalletStorage::load_wallet`].
+    fn store_wallet(&self, wallet: &Wallet, password: &str) -> NonosResult<()> {
+        let (mut secret, extended_key) = wallet.storage_secrets()?;
+        let result = self.save_wallet_with_extended_key(
+            wallet.metadata(),
+            &secret,
+            extended_key.as_deref(),
+            password,
+        );
+        secret.zeroize();
+        result
+    }
+
+    fn load_wallet(&self, id: &WalletId, password: &str) -> NonosResult<Wallet> {
+        let metadata = self.load_metadata(id)?;
+        let mut secret = self.load_secrets(id, password)?;
+        let extended_key = self.load_extended_key(id, password)?;
+
+        let wallet = Wallet::from_stored(metadata, &secret, extended_key.as_deref());
+        secret.zeroize();
+        wallet
+    }
+
+    /// Imports a keystore V3 file as a new single-key wallet encrypted under
+    /// `password`.
+    fn import_keystore(
+        &self,
+        name: String,
+        keystore: &KeystoreV3,
+        keystore_password: &str,
+        password: &str,
+    ) -> NonosResult<WalletMetadata> {
+        let private_key = decrypt_keystore(keystore, keystore_password.as_bytes())?;
+        let wallet = Wallet::import_from_private_key(name, private_key)?;
+        self.store_wallet(&wallet, password)?;
+        Ok(wallet.metadata().clone())
+    }
+
+    /// Exports the key for `account_index` as a keystore V3 file encrypted
+    /// under `keystore_password`.
+    fn export_keystore(
+        &self,
+        id: &WalletId,
+        password: &str,
+        account_index: u32,
+        keystore_password: &str,
+        kdf: KeystoreKdf,
+    ) -> NonosResult<KeystoreV3> {
+        self.load_wallet(id, password)?
+            .export_keystore(account_index, keystore_password, kdf)
+    }
 }
 
 pub struct FileWalletStorage {
@@ -134,40 +243,24 @@ impl WalletStorage for FileWalletStorage {
             last_accessed: chrono::Utc::now(),
             address,
             stealth_count: 0,
+            derivation: file.derivation,
         })
     }
 
-    fn save_wallet(&self, metadata: &WalletMetadata, master_key: &[u8; 32], password: &str) -> NonosResult<()> {
-        let secrets = WalletSecrets {
-            master_key: hex::encode(master_key),
-            accounts: vec![(0, metadata.address.to_hex())],
-            stealth_count: metadata.stealth_count,
-        };
-
-        let plaintext = serde_json::to_vec(&secrets)
-            .map_err(|e| NonosError::Serialization(e.to_string()))?;
-
-        let encrypted = encrypt_wallet(password.as_bytes(), &plaintext)?;
-
-        let file = WalletFile {
-            version: 2,
-            id: metadata.id.to_string(),
-            name: metadata.name.clone(),
-            address: metadata.address.to_hex(),
-            encrypted,
-            created_at: metadata.created_at.to_rfc3339(),
-        };
-
+    fn save_wallet_with_extended_key(
+        &self,
+        metadata: &WalletMetadata,
+        master_key: &[u8; 32],
+        extended_key: Option<&str>,
+        password: &str,
+    ) -> NonosResult<()> {
+        let file = seal_wallet_file(metadata, master_key, extended_key, password)?;
         self.save_wallet_file(&file)
     }
 
     fn load_secrets(&self, id: &WalletId, password: &str) -> NonosResult<[u8; 32]> {
         let file = self.load_wallet_file(id)?;
-
-        let plaintext = decrypt_wallet(password.as_bytes(), &file.encrypted)?;
-
-        let secrets: WalletSecrets = serde_json::from_slice(&plaintext)
-            .map_err(|e| NonosError::Storage(format!("Failed to parse secrets: {}", e)))?;
+        let secrets = open_wallet_file(&file, password)?;
 
         let key_bytes = hex::decode(&secrets.master_key)
             .map_err(|e| NonosError::Storage(format!("Invalid key encoding: {}", e)))?;
@@ -181,6 +274,11 @@ impl WalletStorage for FileWalletStorage {
         Ok(key)
     }
 
+    fn load_extended_key(&self, id: &WalletId, password: &str) -> NonosResult<Option<String>> {
+        let file = self.load_wallet_file(id)?;
+        Ok(open_wallet_file(&file, password)?.extended_key)
+    }
+
     fn delete_wallet(&self, id: &WalletId) -> NonosResult<()> {
         let path = self.wallet_path(id);
 
@@ -198,13 +296,16 @@ impl WalletStorage for FileWalletStorage {
 
     fn change_password(&self, id: &WalletId, old_password: &str, new_password: &str) -> NonosResult<()> {
         let master_key = self.load_secrets(id, old_password)?;
+        let extended_key = self.load_extended_key(id, old_password)?;
         let metadata = self.load_metadata(id)?;
-        self.save_wallet(&metadata, &master_key, new_password)
+        self.save_wallet_with_extended_key(&metadata, &master_key, extended_key.as_deref(), new_password)
     }
 }
 
+type MemoryWalletEntry = (WalletFile, [u8; 32], Option<String>);
+
 pub struct MemoryWalletStorage {
-    wallets: std::sync::RwLock<std::collections::HashMap<String, (WalletFile, [u8; 32])>>,
+    wallets: std::sync::RwLock<std::collections::HashMap<String, MemoryWalletEntry>>,
 }
 
 impl MemoryWalletStorage {
@@ -235,7 +336,7 @@ impl WalletStorage for MemoryWalletStorage {
         let wallets = self.wallets.read()
             .map_err(|_| NonosError::Storage("Lock poisoned".into()))?;
 
-        let (file, _) = wallets.get(&id.to_string())
+        let (file, _, _) = wallets.get(&id.to_string())
             .ok_or_else(|| NonosError::Storage("Wallet not found".into()))?;
 
         let address = EthAddress::from_hex(&file.address)?;
@@ -250,34 +351,26 @@ impl WalletStorage for MemoryWalletStorage {
             last_accessed: chrono::Utc::now(),
             address,
             stealth_count: 0,
+            derivation: file.derivation,
         })
     }
 
-    fn save_wallet(&self, metadata: &WalletMetadata, master_key: &[u8; 32], password: &str) -> NonosResult<()> {
-        let secrets = WalletSecrets {
-            master_key: hex::encode(master_key),
-            accounts: vec![(0, metadata.address.to_hex())],
-            stealth_count: metadata.stealth_count,
-        };
-
-        let plaintext = serde_json::to_vec(&secrets)
-            .map_err(|e| NonosError::Serialization(e.to_string()))?;
-
-        let encrypted = encrypt_wallet(password.as_bytes(), &plaintext)?;
-
-        let file = WalletFile {
-            version: 2,
-            id: metadata.id.to_string(),
-            name: metadata.name.clone(),
-            address: metadata.address.to_hex(),
-            encrypted,
-            created_at: metadata.created_at.to_rfc3339(),
-        };
+    fn save_wallet_with_extended_key(
+        &self,
+        metadata: &WalletMetadata,
+        master_key: &[u8; 32],
+        extended_key: Option<&str>,
+        password: &str,
+    ) -> NonosResult<()> {
+        let file = seal_wallet_file(metadata, master_key, extended_key, password)?;
 
         let mut wallets = self.wallets.write()
             .map_err(|_| NonosError::Storage("Lock poisoned".into()))?;
 
-        wallets.insert(metadata.id.to_string(), (file, *master_key));
+        wallets.insert(
+            metadata.id.to_string(),
+            (file, *master_key, extended_key.map(str::to_string)),
+        );
         Ok(())
     }
 
@@ -285,12 +378,22 @@ impl WalletStorage for MemoryWalletStorage {
         let wallets = self.wallets.read()
             .map_err(|_| NonosError::Storage("Lock poisoned".into()))?;
 
-        let (_, key) = wallets.get(&id.to_string())
+        let (_, key, _) = wallets.get(&id.to_string())
             .ok_or_else(|| NonosError::Storage("Wallet not found".into()))?;
 
         Ok(*key)
     }
 
+    fn load_extended_key(&self, id: &WalletId, _password: &str) -> NonosResult<Option<String>> {
+        let wallets = self.wallets.read()
+            .map_err(|_| NonosError::Storage("Lock poisoned".into()))?;
+
+        let (_, _, extended_key) = wallets.get(&id.to_string())
+            .ok_or_else(|| NonosError::Storage("Wallet not found".into()))?;
+
+        Ok(extended_key.clone())
+    }
+
     fn delete_wallet(&self, id: &WalletId) -> NonosResult<()> {
         let mut wallets = self.wallets.write()
             .map_err(|_| NonosError::Storage("Lock poisoned".into()))?;
@@ -307,8 +410,9 @@ impl WalletStorage for MemoryWalletStorage {
 
     fn change_password(&self, id: &WalletId, old_password: &str, new_password: &str) -> NonosResult<()> {
         let master_key = self.load_secrets(id, old_password)?;
+        let extended_key = self.load_extended_key(id, old_password)?;
         let metadata = self.load_metadata(id)?;
-        self.save_wallet(&metadata, &master_key, new_password)
+        self.save_wallet_with_extended_key(&metadata, &master_key, extended_key.as_deref(), new_password)
     }
 }
 
@@ -363,4 +467,61 @@ mod tests {
         let loaded_key = storage.load_secrets(&metadata.id, new_password).unwrap();
         assert_eq!(loaded_key, master_key);
     }
+
+    #[test]
+    fn test_keystore_import_export() {
+        let storage = MemoryWalletStorage::new();
+
+        let private_key = nonos_types::Secp256k1PrivateKey::from_bytes([0x42; 32]);
+        let keystore = nonos_crypto::encrypt_keystore(
+            &private_key,
+            b"keystore-pass",
+            KeystoreKdf::Pbkdf2 { iterations: 1024 },
+        ).unwrap();
+
+        let metadata = storage
+            .import_keystore("Reward Key".to_string(), &keystore, "keystore-pass", "walletpass")
+            .unwrap();
+        assert_eq!(metadata.derivation, DerivationScheme::Imported);
+        assert_eq!(hex::encode(metadata.address.as_bytes()), keystore.address.clone().unwrap());
+
+        let wallet = storage.load_wallet(&metadata.id, "walletpass").unwrap();
+        assert_eq!(wallet.id(), &metadata.id);
+        assert_eq!(wallet.get_account_private_key(0).unwrap(), hex::encode([0x42; 32]));
+
+        let exported = storage
+            .export_keystore(&metadata.id, "walletpass", 0, "new-pass", KeystoreKdf::Scrypt { log_n: 10, r: 8, p: 1 })
+            .unwrap();
+        let decrypted = nonos_crypto::decrypt_keystore(&exported, b"new-pass").unwrap();
+        assert_eq!(decrypted.0, [0x42; 32]);
+
+        assert!(storage.import_keystore("Bad".to_string(), &keystore, "wrong", "walletpass").is_err());
+    }
+
+    #[test]
+    fn test_bip44_wallet_persistence() {
+        let dir = std::env::temp_dir().join(format!("nonos-wallet-test-{}", uuid::Uuid::new_v4()));
+        let storage = FileWalletStorage::new(&dir).unwrap();
+
+        let mut metadata = WalletMetadata::new(
+            "BIP-44 Wallet".to_string(),
+            EthAddress::from_bytes([0xab; 20]),
+        );
+        metadata.derivation = DerivationScheme::Bip44;
+
+        let master_key = [0xcd; 32];
+        let xprv = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+
+        storage.save_wallet_with_extended_key(&metadata, &master_key, Some(xprv), "oldpass123").unwrap();
+        storage.change_password(&metadata.id, "oldpass123", "newpass456").unwrap();
+
+        let loaded = storage.load_metadata(&metadata.id).unwrap();
+        assert_eq!(loaded.derivation, DerivationScheme::Bip44);
+        assert_eq!(
+            storage.load_extended_key(&metadata.id, "newpass456").unwrap().as_deref(),
+            Some(xprv)
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,17 +1,83 @@
-use nonos_crypto::{keccak256, sign_message};
+use crate::fees::{FeeEstimate, FeeSpeed};
+use crate::rlp::{self, RlpItem};
+use nonos_crypto::{
+    derive_eth_address, keccak256, recover_public_key, sign_message, StealthMetaAddress,
+};
 use nonos_types::{
-    Blake3Hash, EcdsaSignature, EthAddress, NonosResult, Secp256k1PrivateKey,
-    TokenAmount,
+    EcdsaSignature, EthAddress, NonosError, NonosResult, Secp256k1PrivateKey, TokenAmount,
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Covers a first-time `registerKeys`: four fresh storage slots for the
+/// 66-byte meta-address plus the event.
+const REGISTER_KEYS_GAS_LIMIT: u64 = 150_000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TransactionType {
     Legacy,
     AccessList,
+    #[default]
     Eip1559,
 }
 
+impl TransactionType {
+    pub fn envelope_byte(&self) -> Option<u8> {
+        match self {
+            TransactionType::Legacy => None,
+            TransactionType::AccessList => Some(0x01),
+            TransactionType::Eip1559 => Some(0x02),
+        }
+    }
+
+    pub fn from_envelope_byte(byte: u8) -> NonosResult<Self> {
+        match byte {
+            0x01 => Ok(TransactionType::AccessList),
+            0x02 => Ok(TransactionType::Eip1559),
+            0xc0..=0xff => Ok(TransactionType::Legacy),
+            other => Err(NonosError::Transaction(format!(
+                "Unsupported transaction type: 0x{:02x}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessListItem {
+    pub address: EthAddress,
+    pub storage_keys: Vec<[u8; 32]>,
+}
+
+impl AccessListItem {
+    fn to_rlp(&self) -> RlpItem {
+        RlpItem::List(vec![
+            RlpItem::bytes(self.address.0.to_vec()),
+            RlpItem::List(
+                self.storage_keys
+                    .iter()
+                    .map(|key| RlpItem::bytes(key.to_vec()))
+                    .collect(),
+            ),
+        ])
+    }
+
+    fn from_rlp(item: &RlpItem) -> NonosResult<Self> {
+        let fields = item.as_list()?;
+        if fields.len() != 2 {
+            return Err(NonosError::Transaction("Malformed access list entry".into()));
+        }
+
+        let address = EthAddress::from_bytes(fields[0].as_fixed::<20>()?);
+        let storage_keys = fields[1]
+            .as_list()?
+            .iter()
+            .map(|key| key.as_fixed::<32>())
+            .collect::<NonosResult<Vec<_>>>()?;
+
+        Ok(Self { address, storage_keys })
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TransactionRequest {
     pub chain_id: u64,
@@ -22,6 +88,10 @@ pub struct TransactionRequest {
     pub max_fee_per_gas: u128,
     pub max_priority_fee_per_gas: u128,
     pub nonce: Option<u64>,
+    #[serde(default)]
+    pub tx_type: TransactionType,
+    #[serde(default)]
+    pub access_list: Vec<AccessListItem>,
 }
 
 impl TransactionRequest {
@@ -35,9 +105,25 @@ impl TransactionRequest {
             max_fee_per_gas: 0,
             max_priority_fee_per_gas: 0,
             nonce: None,
+            tx_type: TransactionType::Eip1559,
+            access_list: Vec::new(),
         }
     }
 
+    /// Registers `meta` for `registrant` (the signer) in an ERC-6538
+    /// registry so senders can look it up by address.
+    pub fn register_stealth_keys(
+        registry: EthAddress,
+        scheme_id: u64,
+        meta: &StealthMetaAddress,
+        chain_id: u64,
+    ) -> Self {
+        let data = Erc6538Encoder::register_keys(scheme_id, meta);
+        let mut request = Self::contract_call(registry, data, chain_id);
+        request.gas_limit = REGISTER_KEYS_GAS_LIMIT;
+        request
+    }
+
     pub fn contract_call(to: EthAddress, data: Vec<u8>, chain_id: u64) -> Self {
         Self {
             chain_id,
@@ -48,6 +134,8 @@ impl TransactionRequest {
             max_fee_per_gas: 0,
             max_priority_fee_per_gas: 0,
             nonce: None,
+            tx_type: TransactionType::Eip1559,
+            access_list: Vec::new(),
         }
     }
 
@@ -63,30 +151,119 @@ impl TransactionRequest {
         self
     }
 
+    /// Legacy and EIP-2930 transactions carry a single gas price, stored in
+    /// `max_fee_per_gas`.
+    pub fn with_gas_price(mut self, gas_limit: u64, gas_price: u128) -> Self {
+        self.gas_limit = gas_limit;
+        self.max_fee_per_gas = gas_price;
+        self.max_priority_fee_per_gas = gas_price;
+        self
+    }
+
+    /// Applies the fees of `speed` from `estimate`: EIP-1559 caps, or a
+    /// single gas price for the other transaction types.
+    pub fn with_fee_estimate(mut self, estimate: &FeeEstimate, speed: FeeSpeed) -> Self {
+        match self.tx_type {
+            TransactionType::Eip1559 => {
+                let tier = estimate.tier(speed);
+                self.max_fee_per_gas = tier.max_fee_per_gas;
+                self.max_priority_fee_per_gas = tier.max_priority_fee_per_gas;
+            }
+            _ => {
+                let gas_price = estimate.gas_price(speed);
+                self.max_fee_per_gas = gas_price;
+                self.max_priority_fee_per_gas = gas_price;
+            }
+        }
+        self
+    }
+
     pub fn with_nonce(mut self, nonce: u64) -> Self {
         self.nonce = Some(nonce);
         self
     }
 
-    pub fn signing_hash(&self, nonce: u64) -> [u8; 32] {
-        let mut rlp_data = Vec::new();
+    pub fn with_type(mut self, tx_type: TransactionType) -> Self {
+        self.tx_type = tx_type;
+        self
+    }
 
-        rlp_data.push(0x02);
+    pub fn with_access_list(mut self, access_list: Vec<AccessListItem>) -> Self {
+        self.access_list = access_list;
+        self
+    }
 
-        let mut fields = Vec::new();
+    pub fn gas_price(&self) -> u128 {
+        self.max_fee_per_gas
+    }
 
-        fields.extend(&self.chain_id.to_be_bytes());
-        fields.extend(&nonce.to_be_bytes());
-        fields.extend(&self.max_priority_fee_per_gas.to_be_bytes());
-        fields.extend(&self.max_fee_per_gas.to_be_bytes());
-        fields.extend(&self.gas_limit.to_be_bytes());
-        fields.extend(&self.to.0);
-        fields.extend(&self.value.to_be_bytes());
-        fields.extend(&self.data);
+    fn payload_fields(&self, nonce: u64) -> Vec<RlpItem> {
+        let to = RlpItem::bytes(self.to.0.to_vec());
+        let data = RlpItem::bytes(self.data.clone());
+        let access_list = || {
+            RlpItem::List(self.access_list.iter().map(AccessListItem::to_rlp).collect())
+        };
+
+        match self.tx_type {
+            TransactionType::Legacy => vec![
+                RlpItem::uint(nonce as u128),
+                RlpItem::uint(self.max_fee_per_gas),
+                RlpItem::uint(self.gas_limit as u128),
+                to,
+                RlpItem::uint(self.value),
+                data,
+            ],
+            TransactionType::AccessList => vec![
+                RlpItem::uint(self.chain_id as u128),
+                RlpItem::uint(nonce as u128),
+                RlpItem::uint(self.max_fee_per_gas),
+                RlpItem::uint(self.gas_limit as u128),
+                to,
+                RlpItem::uint(self.value),
+                data,
+                access_list(),
+            ],
+            TransactionType::Eip1559 => vec![
+                RlpItem::uint(self.chain_id as u128),
+                RlpItem::uint(nonce as u128),
+                RlpItem::uint(self.max_priority_fee_per_gas),
+                RlpItem::uint(self.max_fee_per_gas),
+                RlpItem::uint(self.gas_limit as u128),
+                to,
+                RlpItem::uint(self.value),
+                data,
+                access_list(),
+            ],
+        }
+    }
 
-        rlp_data.extend(&fields);
+    pub fn signing_payload(&self, nonce: u64) -> Vec<u8> {
+        let mut fields = self.payload_fields(nonce);
 
-        keccak256(&rlp_data)
+        if self.tx_type == TransactionType::Legacy && self.chain_id != 0 {
+            fields.push(RlpItem::uint(self.chain_id as u128));
+            fields.push(RlpItem::uint(0));
+            fields.push(RlpItem::uint(0));
+        }
+
+        envelope(self.tx_type, &fields)
+    }
+
+    pub fn signing_hash(&self, nonce: u64) -> [u8; 32] {
+        keccak256(&self.signing_payload(nonce))
+    }
+}
+
+fn envelope(tx_type: TransactionType, fields: &[RlpItem]) -> Vec<u8> {
+    let body = rlp::encode(&RlpItem::List(fields.to_vec()));
+    match tx_type.envelope_byte() {
+        Some(byte) => {
+            let mut out = Vec::with_capacity(body.len() + 1);
+            out.push(byte);
+            out.extend(body);
+            out
+        }
+        None => body,
     }
 }
 
@@ -95,34 +272,172 @@ pub struct SignedTransaction {
     pub request: TransactionRequest,
     pub nonce: u64,
     pub signature: EcdsaSignature,
-    pub hash: Blake3Hash,
+    pub hash: [u8; 32],
 }
 
 impl SignedTransaction {
-    pub fn raw_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
+    fn y_parity(&self) -> u8 {
+        if self.signature.v >= 27 {
+            self.signature.v - 27
+        } else {
+            self.signature.v
+        }
+    }
 
-        bytes.push(0x02);
+    pub fn raw_bytes(&self) -> Vec<u8> {
+        let mut fields = self.request.payload_fields(self.nonce);
 
-        bytes.extend(&self.request.chain_id.to_be_bytes());
-        bytes.extend(&self.nonce.to_be_bytes());
-        bytes.extend(&self.request.max_priority_fee_per_gas.to_be_bytes());
-        bytes.extend(&self.request.max_fee_per_gas.to_be_bytes());
-        bytes.extend(&self.request.gas_limit.to_be_bytes());
-        bytes.extend(&self.request.to.0);
-        bytes.extend(&self.request.value.to_be_bytes());
-        bytes.extend(&self.request.data);
+        let v = match self.request.tx_type {
+            TransactionType::Legacy if self.request.chain_id != 0 => {
+                self.request.chain_id as u128 * 2 + 35 + self.y_parity() as u128
+            }
+            TransactionType::Legacy => 27 + self.y_parity() as u128,
+            _ => self.y_parity() as u128,
+        };
 
-        bytes.extend(&self.signature.r);
-        bytes.extend(&self.signature.s);
-        bytes.push(self.signature.v);
+        fields.push(RlpItem::uint(v));
+        fields.push(RlpItem::uint_bytes(&self.signature.r));
+        fields.push(RlpItem::uint_bytes(&self.signature.s));
 
-        bytes
+        envelope(self.request.tx_type, &fields)
     }
 
     pub fn raw_hex(&self) -> String {
         format!("0x{}", hex::encode(self.raw_bytes()))
     }
+
+    pub fn hash_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.hash))
+    }
+
+    pub fn decode(raw: &[u8]) -> NonosResult<Self> {
+        let first = *raw
+            .first()
+            .ok_or_else(|| NonosError::Transaction("Empty transaction".into()))?;
+        let tx_type = TransactionType::from_envelope_byte(first)?;
+
+        let body = match tx_type {
+            TransactionType::Legacy => raw,
+            _ => &raw[1..],
+        };
+        let decoded = rlp::decode(body)?;
+        let fields = decoded.as_list()?;
+
+        let expected = match tx_type {
+            TransactionType::Legacy => 9,
+            TransactionType::AccessList => 11,
+            TransactionType::Eip1559 => 12,
+        };
+        if fields.len() != expected {
+            return Err(NonosError::Transaction(format!(
+                "Expected {} transaction fields, got {}",
+                expected,
+                fields.len()
+            )));
+        }
+
+        let to_field = |item: &RlpItem| -> NonosResult<EthAddress> {
+            if item.as_bytes()?.is_empty() {
+                return Err(NonosError::Transaction(
+                    "Contract creation transactions are not supported".into(),
+                ));
+            }
+            Ok(EthAddress::from_bytes(item.as_fixed::<20>()?))
+        };
+        let access_list = |item: &RlpItem| -> NonosResult<Vec<AccessListItem>> {
+            item.as_list()?.iter().map(AccessListItem::from_rlp).collect()
+        };
+
+        let (request, nonce, v, sig_start) = match tx_type {
+            TransactionType::Legacy => {
+                let v = fields[6].as_u64()?;
+                let (chain_id, parity) = match v {
+                    27 | 28 => (0, v - 27),
+                    v if v >= 35 => ((v - 35) / 2, (v - 35) % 2),
+                    _ => {
+                        return Err(NonosError::Transaction(format!(
+                            "Invalid legacy signature v: {}",
+                            v
+                        )))
+                    }
+                };
+                let gas_price = fields[1].as_u128()?;
+                let request = TransactionRequest {
+                    chain_id,
+                    to: to_field(&fields[3])?,
+                    value: fields[4].as_u128()?,
+                    data: fields[5].as_bytes()?.to_vec(),
+                    gas_limit: fields[2].as_u64()?,
+                    max_fee_per_gas: gas_price,
+                    max_priority_fee_per_gas: gas_price,
+                    nonce: None,
+                    tx_type,
+                    access_list: Vec::new(),
+                };
+                (request, fields[0].as_u64()?, parity, 7)
+            }
+            TransactionType::AccessList => {
+                let gas_price = fields[2].as_u128()?;
+                let request = TransactionRequest {
+                    chain_id: fields[0].as_u64()?,
+                    to: to_field(&fields[4])?,
+                    value: fields[5].as_u128()?,
+                    data: fields[6].as_bytes()?.to_vec(),
+                    gas_limit: fields[3].as_u64()?,
+                    max_fee_per_gas: gas_price,
+                    max_priority_fee_per_gas: gas_price,
+                    nonce: None,
+                    tx_type,
+                    access_list: access_list(&fields[7])?,
+                };
+                (request, fields[1].as_u64()?, fields[8].as_u64()?, 9)
+            }
+            TransactionType::Eip1559 => {
+                let request = TransactionRequest {
+                    chain_id: fields[0].as_u64()?,
+                    to: to_field(&fields[5])?,
+                    value: fields[6].as_u128()?,
+                    data: fields[7].as_bytes()?.to_vec(),
+                    gas_limit: fields[4].as_u64()?,
+                    max_fee_per_gas: fields[3].as_u128()?,
+                    max_priority_fee_per_gas: fields[2].as_u128()?,
+                    nonce: None,
+                    tx_type,
+                    access_list: access_list(&fields[8])?,
+                };
+                (request, fields[1].as_u64()?, fields[9].as_u64()?, 10)
+            }
+        };
+
+        if v > 1 {
+            return Err(NonosError::Transaction(format!("Invalid signature parity: {}", v)));
+        }
+
+        let signature = EcdsaSignature::new(
+            fields[sig_start].as_word()?,
+            fields[sig_start + 1].as_word()?,
+            v as u8 + 27,
+        );
+
+        Ok(Self {
+            request: request.with_nonce(nonce),
+            nonce,
+            signature,
+            hash: keccak256(raw),
+        })
+    }
+
+    pub fn from_hex(raw: &str) -> NonosResult<Self> {
+        let bytes = hex::decode(raw.trim_start_matches("0x"))
+            .map_err(|e| NonosError::Transaction(format!("Invalid transaction hex: {}", e)))?;
+        Self::decode(&bytes)
+    }
+
+    pub fn recover_sender(&self) -> NonosResult<EthAddress> {
+        let signing_hash = self.request.signing_hash(self.nonce);
+        let public_key = recover_public_key(&self.signature, &signing_hash)?;
+        derive_eth_address(&public_key)
+    }
 }
 
 pub struct TransactionSigner;
@@ -136,23 +451,15 @@ impl TransactionSigner {
         let signing_hash = request.signing_hash(nonce);
         let signature = sign_message(private_key, &signing_hash)?;
 
-        let mut tx_data = Vec::new();
-        tx_data.push(0x02);
-        tx_data.extend(&request.chain_id.to_be_bytes());
-        tx_data.extend(&nonce.to_be_bytes());
-        tx_data.extend(&request.to.0);
-        tx_data.extend(&request.value.to_be_bytes());
-        tx_data.extend(&signature.r);
-        tx_data.extend(&signature.s);
-
-        let tx_hash = nonos_crypto::blake3_hash(&tx_data);
-
-        Ok(SignedTransaction {
+        let mut signed = SignedTransaction {
             request,
             nonce,
             signature,
-            hash: tx_hash,
-        })
+            hash: [0u8; 32],
+        };
+        signed.hash = keccak256(&signed.raw_bytes());
+
+        Ok(signed)
     }
 }
 
@@ -244,6 +551,77 @@ impl StakingEncoder {
     }
 }
 
+/// Calldata for the ERC-6538 stealth meta-address registry.
+pub struct Erc6538Encoder;
+
+impl Erc6538Encoder {
+    /// `registerKeys(uint256 schemeId, bytes stealthMetaAddress)`
+    pub fn register_keys(scheme_id: u64, meta: &StealthMetaAddress) -> Vec<u8> {
+        let keys = meta.to_bytes();
+        let padded_len = keys.len().div_ceil(32) * 32;
+        let mut data = Vec::with_capacity(4 + 96 + padded_len);
+
+        data.extend(&[0x04, 0x2c, 0x7a, 0xa3]);
+
+        data.extend(&[0u8; 24]);
+        data.extend(&scheme_id.to_be_bytes());
+
+        data.extend(&[0u8; 24]);
+        data.extend(&64u64.to_be_bytes());
+
+        data.extend(&[0u8; 24]);
+        data.extend(&(keys.len() as u64).to_be_bytes());
+
+        data.extend(&keys);
+        data.resize(4 + 96 + padded_len, 0);
+
+        data
+    }
+
+    /// `stealthMetaAddressOf(address registrant, uint256 schemeId)`
+    pub fn stealth_meta_address_of(registrant: &EthAddress, scheme_id: u64) -> Vec<u8> {
+        let mut data = Vec::with_capacity(68);
+
+        data.extend(&[0x7a, 0xa8, 0xb5, 0xad]);
+
+        data.extend(&[0u8; 12]);
+        data.extend(&registrant.0);
+
+        data.extend(&[0u8; 24]);
+        data.extend(&scheme_id.to_be_bytes());
+
+        data
+    }
+
+    /// Decodes the `bytes` returned by `stealthMetaAddressOf`. Returns
+    /// `None` when the registrant has not registered keys for the scheme.
+    pub fn decode_stealth_meta_address(output: &[u8]) -> NonosResult<Option<StealthMetaAddress>> {
+        let slice = |start: usize, len: usize| -> NonosResult<&[u8]> {
+            start
+                .checked_add(len)
+                .and_then(|end| output.get(start..end))
+                .ok_or_else(|| NonosError::Transaction("Truncated registry response".into()))
+        };
+        let word = |offset: usize| -> NonosResult<usize> {
+            let bytes = slice(offset, 32)?;
+            if bytes[..24].iter().any(|b| *b != 0) {
+                return Err(NonosError::Transaction("Registry response offset out of range".into()));
+            }
+            usize::try_from(u64::from_be_bytes(bytes[24..].try_into().unwrap_or_default()))
+                .map_err(|_| NonosError::Transaction("Registry response offset out of range".into()))
+        };
+
+        let offset = word(0)?;
+        let len = word(offset)?;
+        if len == 0 {
+            return Ok(None);
+        }
+
+        let keys = slice(offset.saturating_add(32), len)?;
+        StealthMetaAddress::from_bytes(keys).map(Some)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -261,6 +639,52 @@ mod tests {
         assert_eq!(request.nonce, Some(5));
     }
 
+    #[test]
+    fn test_erc6538_register_keys_roundtrip() {
+        let meta = nonos_crypto::StealthKeyPair::generate().unwrap().meta_address();
+        let registry = EthAddress::from_bytes([0x65; 20]);
+
+        let request = TransactionRequest::register_stealth_keys(registry, 1, &meta, 1);
+        assert_eq!(request.to, registry);
+        assert_eq!(&request.data[..4], &[0x04, 0x2c, 0x7a, 0xa3]);
+        assert_eq!(request.data.len(), 4 + 96 + 96);
+        assert_eq!(request.data[35], 1);
+        assert_eq!(request.data[67], 64);
+        assert_eq!(request.data[99], 66);
+
+        // The return value is the same `bytes` encoding with the head
+        // offset pointing past a single word.
+        let mut output = vec![0u8; 32];
+        output[31] = 32;
+        output.extend_from_slice(&request.data[68..]);
+
+        let decoded = Erc6538Encoder::decode_stealth_meta_address(&output).unwrap().unwrap();
+        assert_eq!(decoded.encode(), meta.encode());
+
+        let mut empty = vec![0u8; 64];
+        empty[31] = 32;
+        assert!(Erc6538Encoder::decode_stealth_meta_address(&empty).unwrap().is_none());
+        assert!(Erc6538Encoder::decode_stealth_meta_address(&empty[..40]).is_err());
+
+        let mut huge = vec![0u8; 96];
+        huge[24..32].copy_from_slice(&u64::MAX.to_be_bytes());
+        assert!(Erc6538Encoder::decode_stealth_meta_address(&huge).is_err());
+        huge[24..32].copy_from_slice(&32u64.to_be_bytes());
+        huge[56..64].copy_from_slice(&(u64::MAX - 16).to_be_bytes());
+        assert!(Erc6538Encoder::decode_stealth_meta_address(&huge).is_err());
+    }
+
+    #[test]
+    fn test_erc6538_lookup_encoding() {
+        let registrant = EthAddress::from_bytes([0xab; 20]);
+        let data = Erc6538Encoder::stealth_meta_address_of(&registrant, 1);
+
+        assert_eq!(&data[..4], &[0x7a, 0xa8, 0xb5, 0xad]);
+        assert_eq!(&data[16..36], &registrant.0);
+        assert_eq!(data[67], 1);
+        assert_eq!(data.len(), 68);
+    }
+
     #[test]
     fn test_erc20_transfer_encoding() {
         let to = EthAddress::from_bytes([0xab; 20]);
@@ -282,4 +706,158 @@ mod tests {
         let claim_data = StakingEncoder::claim_rewards();
         assert_eq!(claim_data.len(), 4);
     }
+
+    fn eip155_key() -> Secp256k1PrivateKey {
+        Secp256k1PrivateKey::from_bytes([0x46; 32])
+    }
+
+    fn eip155_request() -> TransactionRequest {
+        TransactionRequest::transfer(
+            EthAddress::from_bytes([0x35; 20]),
+            TokenAmount::from_raw(1_000_000_000_000_000_000, 18),
+            1,
+        )
+        .with_type(TransactionType::Legacy)
+        .with_gas_price(21_000, 20_000_000_000)
+    }
+
+    #[test]
+    fn test_eip155_vector() {
+        let request = eip155_request();
+
+        assert_eq!(
+            hex::encode(request.signing_payload(9)),
+            "ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080"
+        );
+        assert_eq!(
+            hex::encode(request.signing_hash(9)),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+
+        let signed = TransactionSigner::sign(request, 9, &eip155_key()).unwrap();
+        assert_eq!(
+            signed.raw_hex(),
+            "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+        assert_eq!(signed.hash, keccak256(&signed.raw_bytes()));
+    }
+
+    #[test]
+    fn test_decode_eip155_vector() {
+        let raw = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+        let decoded = SignedTransaction::from_hex(raw).unwrap();
+
+        assert_eq!(decoded.request.tx_type, TransactionType::Legacy);
+        assert_eq!(decoded.request.chain_id, 1);
+        assert_eq!(decoded.nonce, 9);
+        assert_eq!(decoded.request.gas_price(), 20_000_000_000);
+        assert_eq!(decoded.request.value, 1_000_000_000_000_000_000);
+        assert_eq!(decoded.raw_hex(), raw);
+
+        let expected = nonos_crypto::derive_eth_address_from_private(&eip155_key()).unwrap();
+        assert_eq!(decoded.recover_sender().unwrap(), expected);
+    }
+
+    fn reference_typed(request: &TransactionRequest, nonce: u64) -> ethers::types::transaction::eip2718::TypedTransaction {
+        use ethers::types::transaction::eip2930::{AccessList, AccessListItem as RefItem};
+        use ethers::types::{Address, Bytes, Eip1559TransactionRequest, Eip2930TransactionRequest, H256, U256};
+
+        let access_list = AccessList(
+            request
+                .access_list
+                .iter()
+                .map(|item| RefItem {
+                    address: Address::from(item.address.0),
+                    storage_keys: item.storage_keys.iter().map(|k| H256::from(*k)).collect(),
+                })
+                .collect(),
+        );
+
+        match request.tx_type {
+            TransactionType::Eip1559 => Eip1559TransactionRequest::new()
+                .to(Address::from(request.to.0))
+                .value(U256::from(request.value))
+                .data(Bytes::from(request.data.clone()))
+                .gas(request.gas_limit)
+                .nonce(nonce)
+                .chain_id(request.chain_id)
+                .max_fee_per_gas(U256::from(request.max_fee_per_gas))
+                .max_priority_fee_per_gas(U256::from(request.max_priority_fee_per_gas))
+                .access_list(access_list)
+                .into(),
+            _ => Eip2930TransactionRequest::new(
+                ethers::types::TransactionRequest::new()
+                    .to(Address::from(request.to.0))
+                    .value(U256::from(request.value))
+                    .data(Bytes::from(request.data.clone()))
+                    .gas(request.gas_limit)
+                    .gas_price(U256::from(request.max_fee_per_gas))
+                    .nonce(nonce)
+                    .chain_id(request.chain_id),
+                access_list,
+            )
+            .into(),
+        }
+    }
+
+    fn assert_matches_reference(request: TransactionRequest, nonce: u64) {
+        use ethers::signers::{LocalWallet, Signer};
+
+        let key = Secp256k1PrivateKey::from_bytes([0x4c; 32]);
+        let reference_wallet = LocalWallet::from_bytes(&key.0).unwrap().with_chain_id(request.chain_id);
+        let reference = reference_typed(&request, nonce);
+
+        assert_eq!(request.signing_hash(nonce), reference.sighash().0);
+
+        let reference_sig = reference_wallet.sign_transaction_sync(&reference).unwrap();
+        let reference_raw = reference.rlp_signed(&reference_sig);
+
+        let signed = TransactionSigner::sign(request, nonce, &key).unwrap();
+        assert_eq!(signed.raw_bytes(), reference_raw.to_vec());
+        assert_eq!(signed.hash, keccak256(&reference_raw));
+
+        let decoded = SignedTransaction::decode(&reference_raw).unwrap();
+        assert_eq!(decoded.raw_bytes(), reference_raw.to_vec());
+        assert_eq!(decoded.recover_sender().unwrap(), EthAddress::from_bytes(reference_wallet.address().0));
+    }
+
+    #[test]
+    fn test_eip1559_matches_reference_encoder() {
+        let request = TransactionRequest::contract_call(
+            EthAddress::from_bytes([0x0a; 20]),
+            Erc20Encoder::transfer(
+                &EthAddress::from_bytes([0xab; 20]),
+                &TokenAmount::from_raw(5_000_000_000_000_000_000, 18),
+            ),
+            1,
+        )
+        .with_gas(65_000, 42_000_000_000, 1_500_000_000);
+
+        assert_matches_reference(request, 17);
+    }
+
+    #[test]
+    fn test_access_list_matches_reference_encoder() {
+        let request = TransactionRequest::transfer(
+            EthAddress::from_bytes([0x11; 20]),
+            TokenAmount::from_raw(12_345, 18),
+            11_155_111,
+        )
+        .with_type(TransactionType::AccessList)
+        .with_gas_price(30_000, 3_000_000_000)
+        .with_access_list(vec![AccessListItem {
+            address: EthAddress::from_bytes([0x22; 20]),
+            storage_keys: vec![[0u8; 32], [0x33; 32]],
+        }]);
+
+        assert_matches_reference(request.clone(), 0);
+        assert_matches_reference(request.with_type(TransactionType::Eip1559).with_gas(30_000, 3_000_000_000, 2), 3);
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_envelopes() {
+        assert!(SignedTransaction::decode(&[]).is_err());
+        assert!(SignedTransaction::decode(&[0x03, 0xc0]).is_err());
+        assert!(SignedTransaction::decode(&[0x02, 0xc0]).is_err());
+    }
 }
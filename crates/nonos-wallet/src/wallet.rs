@@ -1,13 +1,16 @@
+use crate::transaction::{SignedTransaction, TransactionRequest, TransactionSigner};
 use nonos_crypto::{
-    derive_blake3_key_from_mnemonic, derive_eth_address_from_private,
-    derive_secp256k1_key, sign_message,
+    blake3_derive_key, derive_blake3_key_from_mnemonic, derive_eth_address_from_private,
+    derive_secp256k1_key, mnemonic_to_seed, sign_message,
     sign_personal_message, SecureMnemonic, StealthKeyPair, StealthMetaAddress,
-    derive_stealth_private_key, check_stealth_address,
+    derive_stealth_private_key, check_stealth_address, encrypt_keystore, ExtendedPrivateKey,
+    KeystoreKdf, KeystoreV3, TypedData, ETH_BIP44_ACCOUNT_PATH, StealthViewingKey,
+    check_erc5564_announcement, derive_erc5564_stealth_private_key, ERC5564_SCHEME_ID,
 };
 use nonos_types::{
-    Blake3Hash, Blake3Key, EcdsaSignature, EthAddress, NonosError, NonosResult,
-    Secp256k1PrivateKey, Secp256k1PublicKey, TransactionRecord, TransactionStatus,
-    WalletId, WalletMetadata,
+    Blake3Hash, Blake3Key, DerivationScheme, EcdsaSignature, EthAddress, NonosError, NonosResult,
+    Secp256k1PrivateKey, Secp256k1PublicKey, StealthAnnouncement, StealthPayment,
+    TransactionRecord, TransactionStatus, WalletId, WalletMetadata,
 };
 use std::collections::HashMap;
 use tracing::{debug, info};
@@ -23,95 +26,153 @@ pub struct Wallet {
     metadata: WalletMetadata,
     state: WalletState,
     master_key: Option<Blake3Key>,
+    bip44_account: Option<ExtendedPrivateKey>,
+    imported_key: Option<Secp256k1PrivateKey>,
     accounts: HashMap<u32, EthAddress>,
     stealth_keypair: Option<StealthKeyPair>,
     transactions: Vec<TransactionRecord>,
+    stealth_payments: Vec<StealthPayment>,
 }
 
+const BIP44_MASTER_CONTEXT: &str = "NONOS-v1-bip44-master";
+const IMPORTED_MASTER_CONTEXT: &str = "NONOS-v1-imported-master";
+
 impl Wallet {
     pub fn create(name: String) -> NonosResult<(Self, String, String)> {
-        info!("Creating new wallet: {}", name);
+        Self::create_with_derivation(name, DerivationScheme::Blake3)
+    }
+
+    /// Creates a wallet with a fresh mnemonic. Returns the wallet, the
+    /// phrase, and the backup key for the scheme: the BLAKE3 master key hex
+    /// for [`DerivationScheme::Blake3`], or the account `xprv` for
+    /// [`DerivationScheme::Bip44`].
+    pub fn create_with_derivation(
+        name: String,
+        derivation: DerivationScheme,
+    ) -> NonosResult<(Self, String, String)> {
+        info!("Creating new wallet: {} ({:?})", name, derivation);
 
         let mnemonic = SecureMnemonic::new()?;
         let phrase = mnemonic.phrase().to_string();
 
-        let master_key = derive_blake3_key_from_mnemonic(&phrase)?;
-        let blake3_hex = master_key.to_hex();
-
-        let account_key = derive_secp256k1_key(&master_key, 0, 0);
-        let private_key = Secp256k1PrivateKey::from_bytes(account_key);
-        let address = derive_eth_address_from_private(&private_key)?;
-
-        let stealth_keypair = StealthKeyPair::derive_from_master(&master_key)?;
-
-        let metadata = WalletMetadata::new(name, address);
-
-        let mut accounts = HashMap::new();
-        accounts.insert(0, address);
-
-        let wallet = Self {
-            metadata,
-            state: WalletState::Unlocked,
-            master_key: Some(master_key),
-            accounts,
-            stealth_keypair: Some(stealth_keypair),
-            transactions: Vec::new(),
-        };
+        let wallet = Self::from_mnemonic(name, &phrase, derivation)?;
+        let backup_key = wallet.backup_key()?;
 
-        Ok((wallet, phrase, blake3_hex))
+        Ok((wallet, phrase, backup_key))
     }
 
     pub fn import_from_mnemonic(name: String, phrase: &str) -> NonosResult<Self> {
-        info!("Importing wallet from mnemonic: {}", name);
-
-        let _mnemonic = SecureMnemonic::from_phrase(phrase.to_string())?;
-        let master_key = derive_blake3_key_from_mnemonic(phrase)?;
-
-        let account_key = derive_secp256k1_key(&master_key, 0, 0);
-        let private_key = Secp256k1PrivateKey::from_bytes(account_key);
-        let address = derive_eth_address_from_private(&private_key)?;
-
-        let stealth_keypair = StealthKeyPair::derive_from_master(&master_key)?;
-
-        let metadata = WalletMetadata::new(name, address);
+        Self::import_from_mnemonic_with_derivation(name, phrase, DerivationScheme::Blake3)
+    }
 
-        let mut accounts = HashMap::new();
-        accounts.insert(0, address);
+    pub fn import_from_mnemonic_with_derivation(
+        name: String,
+        phrase: &str,
+        derivation: DerivationScheme,
+    ) -> NonosResult<Self> {
+        info!("Importing wallet from mnemonic: {} ({:?})", name, derivation);
 
-        Ok(Self {
-            metadata,
-            state: WalletState::Unlocked,
-            master_key: Some(master_key),
-            accounts,
-            stealth_keypair: Some(stealth_keypair),
-            transactions: Vec::new(),
-        })
+        let _mnemonic = SecureMnemonic::from_phrase(phrase.to_string())?;
+        Self::from_mnemonic(name, phrase, derivation)
     }
 
     pub fn import_from_blake3_key(name: String, key_hex: &str) -> NonosResult<Self> {
         info!("Importing wallet from BLAKE3 key: {}", name);
 
         let master_key = Blake3Key::from_hex(key_hex)?;
+        Self::from_keys(name, DerivationScheme::Blake3, master_key, None, None)
+    }
+
+    /// Imports a BIP-44 wallet from an `xprv`, either the master key or the
+    /// `m/44'/60'/0'/0` account node exported by [`Wallet::export_extended_key`].
+    pub fn import_from_extended_key(name: String, xprv: &str) -> NonosResult<Self> {
+        info!("Importing wallet from extended key: {}", name);
+
+        let account = bip44_account_from_xprv(xprv)?;
+        let master_key = bip44_master_key(&account);
+        Self::from_keys(name, DerivationScheme::Bip44, master_key, Some(account), None)
+    }
+
+    /// Imports a single secp256k1 key, e.g. decrypted from a keystore V3
+    /// file. The wallet has only account 0.
+    pub fn import_from_private_key(name: String, private_key: Secp256k1PrivateKey) -> NonosResult<Self> {
+        info!("Importing wallet from private key: {}", name);
+
+        let master_key = imported_master_key(&private_key);
+        Self::from_keys(name, DerivationScheme::Imported, master_key, None, Some(private_key))
+    }
+
+    /// Rebuilds an unlocked wallet from persisted metadata and secrets: the
+    /// BLAKE3 master key, the BIP-44 account `xprv`, or the imported key,
+    /// depending on `metadata.derivation`.
+    pub fn from_stored(
+        metadata: WalletMetadata,
+        secret: &[u8; 32],
+        extended_key: Option<&str>,
+    ) -> NonosResult<Self> {
+        let name = metadata.name.clone();
+        let mut wallet = match metadata.derivation {
+            DerivationScheme::Blake3 => {
+                Self::import_from_blake3_key(name, &hex::encode(secret))?
+            }
+            DerivationScheme::Bip44 => {
+                let xprv = extended_key
+                    .ok_or_else(|| NonosError::Wallet("BIP-44 wallet is missing its extended key".into()))?;
+                Self::import_from_extended_key(name, xprv)?
+            }
+            DerivationScheme::Imported => {
+                Self::import_from_private_key(name, Secp256k1PrivateKey::from_bytes(*secret))?
+            }
+        };
 
-        let account_key = derive_secp256k1_key(&master_key, 0, 0);
-        let private_key = Secp256k1PrivateKey::from_bytes(account_key);
-        let address = derive_eth_address_from_private(&private_key)?;
+        if wallet.metadata.address != metadata.address {
+            return Err(NonosError::Wallet("Stored secrets do not match wallet address".into()));
+        }
+        wallet.metadata = metadata;
+        Ok(wallet)
+    }
+
+    fn from_mnemonic(name: String, phrase: &str, derivation: DerivationScheme) -> NonosResult<Self> {
+        match derivation {
+            DerivationScheme::Blake3 => {
+                let master_key = derive_blake3_key_from_mnemonic(phrase)?;
+                Self::from_keys(name, derivation, master_key, None, None)
+            }
+            DerivationScheme::Bip44 => {
+                let account = bip44_account_from_mnemonic(phrase)?;
+                let master_key = bip44_master_key(&account);
+                Self::from_keys(name, derivation, master_key, Some(account), None)
+            }
+            DerivationScheme::Imported => Err(NonosError::Wallet(
+                "Imported key wallets have no mnemonic".into(),
+            )),
+        }
+    }
 
+    fn from_keys(
+        name: String,
+        derivation: DerivationScheme,
+        master_key: Blake3Key,
+        bip44_account: Option<ExtendedPrivateKey>,
+        imported_key: Option<Secp256k1PrivateKey>,
+    ) -> NonosResult<Self> {
         let stealth_keypair = StealthKeyPair::derive_from_master(&master_key)?;
 
-        let metadata = WalletMetadata::new(name, address);
-
-        let mut accounts = HashMap::new();
-        accounts.insert(0, address);
-
-        Ok(Self {
-            metadata,
+        let mut wallet = Self {
+            metadata: WalletMetadata::new(name, EthAddress::zero()),
             state: WalletState::Unlocked,
             master_key: Some(master_key),
-            accounts,
+            bip44_account,
+            imported_key,
+            accounts: HashMap::new(),
             stealth_keypair: Some(stealth_keypair),
             transactions: Vec::new(),
-        })
+            stealth_payments: Vec::new(),
+        };
+        wallet.metadata.derivation = derivation;
+        wallet.metadata.address = wallet.derive_account(0)?;
+
+        Ok(wallet)
     }
 
     pub fn id(&self) -> &WalletId {
@@ -142,6 +203,59 @@ impl Wallet {
         &self.accounts
     }
 
+    pub fn derivation(&self) -> DerivationScheme {
+        self.metadata.derivation
+    }
+
+    /// Exports the `m/44'/60'/0'/0` account node as an `xprv` so the wallet
+    /// can be recovered in other BIP-32 wallets.
+    pub fn export_extended_key(&self) -> NonosResult<String> {
+        if self.metadata.derivation != DerivationScheme::Bip44 {
+            return Err(NonosError::Wallet("Wallet does not use BIP-44 derivation".into()));
+        }
+
+        let account = self.bip44_account.as_ref()
+            .ok_or_else(|| NonosError::Wallet("Wallet is locked".into()))?;
+        Ok(account.to_base58())
+    }
+
+    fn backup_key(&self) -> NonosResult<String> {
+        match self.metadata.derivation {
+            DerivationScheme::Blake3 => self.master_key.as_ref()
+                .map(|key| key.to_hex())
+                .ok_or_else(|| NonosError::Wallet("Wallet is locked".into())),
+            DerivationScheme::Bip44 => self.export_extended_key(),
+            DerivationScheme::Imported => self.get_account_private_key(0),
+        }
+    }
+
+    /// Secrets to persist: the 32-byte key [`Wallet::from_stored`] expects,
+    /// plus the account `xprv` for BIP-44 wallets.
+    pub fn storage_secrets(&self) -> NonosResult<([u8; 32], Option<String>)> {
+        let locked = || NonosError::Wallet("Wallet is locked".into());
+
+        match self.metadata.derivation {
+            DerivationScheme::Blake3 => Ok((self.master_key.as_ref().ok_or_else(locked)?.0, None)),
+            DerivationScheme::Bip44 => Ok((
+                self.master_key.as_ref().ok_or_else(locked)?.0,
+                Some(self.export_extended_key()?),
+            )),
+            DerivationScheme::Imported => Ok((self.imported_key.as_ref().ok_or_else(locked)?.0, None)),
+        }
+    }
+
+    /// Encrypts the key for `account_index` as a keystore V3 file for geth,
+    /// Foundry or MetaMask.
+    pub fn export_keystore(
+        &self,
+        account_index: u32,
+        password: &str,
+        kdf: KeystoreKdf,
+    ) -> NonosResult<KeystoreV3> {
+        let private_key = Secp256k1PrivateKey::from_bytes(self.account_key(account_index)?);
+        encrypt_keystore(&private_key, password.as_bytes(), kdf)
+    }
+
     pub fn lock(&mut self) {
         info!("Locking wallet: {}", self.metadata.name);
 
@@ -149,6 +263,8 @@ impl Wallet {
             key.0.zeroize();
         }
         self.master_key = None;
+        self.bip44_account = None;
+        self.imported_key = None;
         self.stealth_keypair = None;
         self.state = WalletState::Locked;
     }
@@ -157,41 +273,83 @@ impl Wallet {
         info!("Unlocking wallet with mnemonic: {}", self.metadata.name);
 
         let _mnemonic = SecureMnemonic::from_phrase(phrase.to_string())?;
-        let master_key = derive_blake3_key_from_mnemonic(phrase)?;
+        match self.metadata.derivation {
+            DerivationScheme::Blake3 => {
+                let master_key = derive_blake3_key_from_mnemonic(phrase)?;
+                self.unlock_with_keys(master_key, None, None, "Mnemonic")
+            }
+            DerivationScheme::Bip44 => {
+                let account = bip44_account_from_mnemonic(phrase)?;
+                let master_key = bip44_master_key(&account);
+                self.unlock_with_keys(master_key, Some(account), None, "Mnemonic")
+            }
+            DerivationScheme::Imported => Err(NonosError::Wallet(
+                "Imported key wallets have no mnemonic".into(),
+            )),
+        }
+    }
 
-        let account_key = derive_secp256k1_key(&master_key, 0, 0);
-        let private_key = Secp256k1PrivateKey::from_bytes(account_key);
-        let address = derive_eth_address_from_private(&private_key)?;
+    pub fn unlock_with_blake3_key(&mut self, key_hex: &str) -> NonosResult<()> {
+        info!("Unlocking wallet with BLAKE3 key: {}", self.metadata.name);
 
-        if address != self.metadata.address {
-            return Err(NonosError::Wallet("Mnemonic does not match wallet".into()));
+        if self.metadata.derivation != DerivationScheme::Blake3 {
+            return Err(NonosError::Wallet(
+                "BIP-44 wallets unlock with a mnemonic or extended key".into(),
+            ));
         }
 
-        let stealth_keypair = StealthKeyPair::derive_from_master(&master_key)?;
+        let master_key = Blake3Key::from_hex(key_hex)?;
+        self.unlock_with_keys(master_key, None, None, "BLAKE3 key")
+    }
 
-        self.master_key = Some(master_key);
-        self.stealth_keypair = Some(stealth_keypair);
-        self.state = WalletState::Unlocked;
+    pub fn unlock_with_extended_key(&mut self, xprv: &str) -> NonosResult<()> {
+        info!("Unlocking wallet with extended key: {}", self.metadata.name);
 
-        Ok(())
+        if self.metadata.derivation != DerivationScheme::Bip44 {
+            return Err(NonosError::Wallet("Wallet does not use BIP-44 derivation".into()));
+        }
+
+        let account = bip44_account_from_xprv(xprv)?;
+        let master_key = bip44_master_key(&account);
+        self.unlock_with_keys(master_key, Some(account), None, "Extended key")
     }
 
-    pub fn unlock_with_blake3_key(&mut self, key_hex: &str) -> NonosResult<()> {
-        info!("Unlocking wallet with BLAKE3 key: {}", self.metadata.name);
+    pub fn unlock_with_private_key(&mut self, private_key: Secp256k1PrivateKey) -> NonosResult<()> {
+        info!("Unlocking wallet with private key: {}", self.metadata.name);
 
-        let master_key = Blake3Key::from_hex(key_hex)?;
+        if self.metadata.derivation != DerivationScheme::Imported {
+            return Err(NonosError::Wallet("Wallet is not an imported key wallet".into()));
+        }
 
-        let account_key = derive_secp256k1_key(&master_key, 0, 0);
-        let private_key = Secp256k1PrivateKey::from_bytes(account_key);
-        let address = derive_eth_address_from_private(&private_key)?;
+        let master_key = imported_master_key(&private_key);
+        self.unlock_with_keys(master_key, None, Some(private_key), "Private key")
+    }
+
+    fn unlock_with_keys(
+        &mut self,
+        master_key: Blake3Key,
+        bip44_account: Option<ExtendedPrivateKey>,
+        imported_key: Option<Secp256k1PrivateKey>,
+        source: &str,
+    ) -> NonosResult<()> {
+        let account_key = account_private_key(
+            self.metadata.derivation,
+            Some(&master_key),
+            bip44_account.as_ref(),
+            imported_key.as_ref(),
+            0,
+        )?;
+        let address = derive_eth_address_from_private(&Secp256k1PrivateKey::from_bytes(account_key))?;
 
         if address != self.metadata.address {
-            return Err(NonosError::Wallet("BLAKE3 key does not match wallet".into()));
+            return Err(NonosError::Wallet(format!("{} does not match wallet", source)));
         }
 
         let stealth_keypair = StealthKeyPair::derive_from_master(&master_key)?;
 
         self.master_key = Some(master_key);
+        self.bip44_account = bip44_account;
+        self.imported_key = imported_key;
         self.stealth_keypair = Some(stealth_keypair);
         self.state = WalletState::Unlocked;
 
@@ -199,10 +357,7 @@ impl Wallet {
     }
 
     pub fn derive_account(&mut self, index: u32) -> NonosResult<EthAddress> {
-        let master_key = self.master_key.as_ref()
-            .ok_or_else(|| NonosError::Wallet("Wallet is locked".into()))?;
-
-        let account_key = derive_secp256k1_key(master_key, 0, index);
+        let account_key = self.account_key(index)?;
         let private_key = Secp256k1PrivateKey::from_bytes(account_key);
         let address = derive_eth_address_from_private(&private_key)?;
 
@@ -213,33 +368,52 @@ impl Wallet {
     }
 
     pub fn sign_hash(&self, account_index: u32, hash: &[u8; 32]) -> NonosResult<EcdsaSignature> {
-        let master_key = self.master_key.as_ref()
-            .ok_or_else(|| NonosError::Wallet("Wallet is locked".into()))?;
-
-        let account_key = derive_secp256k1_key(master_key, 0, account_index);
+        let account_key = self.account_key(account_index)?;
         let private_key = Secp256k1PrivateKey::from_bytes(account_key);
 
         sign_message(&private_key, hash)
     }
 
     pub fn get_account_private_key(&self, account_index: u32) -> NonosResult<String> {
-        let master_key = self.master_key.as_ref()
-            .ok_or_else(|| NonosError::Wallet("Wallet is locked".into()))?;
-
-        let account_key = derive_secp256k1_key(master_key, 0, account_index);
+        let account_key = self.account_key(account_index)?;
         Ok(hex::encode(account_key))
     }
 
     pub fn sign_personal(&self, account_index: u32, message: &[u8]) -> NonosResult<EcdsaSignature> {
-        let master_key = self.master_key.as_ref()
-            .ok_or_else(|| NonosError::Wallet("Wallet is locked".into()))?;
-
-        let account_key = derive_secp256k1_key(master_key, 0, account_index);
+        let account_key = self.account_key(account_index)?;
         let private_key = Secp256k1PrivateKey::from_bytes(account_key);
 
         sign_personal_message(&private_key, message)
     }
 
+    pub fn sign_transaction(
+        &self,
+        account_index: u32,
+        request: TransactionRequest,
+        nonce: u64,
+    ) -> NonosResult<SignedTransaction> {
+        let account_key = self.account_key(account_index)?;
+        let private_key = Secp256k1PrivateKey::from_bytes(account_key);
+
+        TransactionSigner::sign(request, nonce, &private_key)
+    }
+
+    /// Signs an EIP-712 typed-data document (`eth_signTypedData_v4`).
+    pub fn sign_typed_data(&self, account_index: u32, typed_data: &TypedData) -> NonosResult<EcdsaSignature> {
+        let hash = typed_data.signing_hash()?;
+        self.sign_hash(account_index, &hash)
+    }
+
+    fn account_key(&self, index: u32) -> NonosResult<[u8; 32]> {
+        account_private_key(
+            self.metadata.derivation,
+            self.master_key.as_ref(),
+            self.bip44_account.as_ref(),
+            self.imported_key.as_ref(),
+            index,
+        )
+    }
+
     pub fn stealth_meta_address(&self) -> NonosResult<StealthMetaAddress> {
         let stealth = self.stealth_keypair.as_ref()
             .ok_or_else(|| NonosError::Wallet("Wallet is locked".into()))?;
@@ -282,6 +456,73 @@ impl Wallet {
         sign_message(&stealth_private, hash)
     }
 
+    /// Keys a scanner needs to detect ERC-5564 payments to this wallet
+    /// without being able to spend them.
+    pub fn stealth_viewing_key(&self) -> NonosResult<StealthViewingKey> {
+        let stealth = self.stealth_keypair.as_ref()
+            .ok_or_else(|| NonosError::Wallet("Wallet is locked".into()))?;
+
+        Ok(stealth.viewing_key())
+    }
+
+    /// Checks an ERC-5564 announcement and records it as a payment if it is
+    /// addressed to this wallet. Returns whether it matched.
+    pub fn scan_stealth_announcement(&mut self, announcement: &StealthAnnouncement) -> NonosResult<bool> {
+        let key = self.stealth_viewing_key()?;
+
+        if announcement.scheme_id != ERC5564_SCHEME_ID
+            || !check_erc5564_announcement(
+                &key,
+                &announcement.ephemeral_pubkey,
+                &announcement.metadata,
+                &announcement.stealth_address,
+            )?
+        {
+            return Ok(false);
+        }
+
+        self.add_stealth_payment(StealthPayment {
+            announcement: announcement.clone(),
+            detected_at: chrono::Utc::now(),
+        });
+        Ok(true)
+    }
+
+    /// Records a payment detected elsewhere, e.g. by the daemon's scanner.
+    /// Payments already recorded are ignored.
+    pub fn add_stealth_payment(&mut self, payment: StealthPayment) {
+        let known = self.stealth_payments.iter().any(|p| {
+            p.announcement.tx_hash == payment.announcement.tx_hash
+                && p.announcement.log_index == payment.announcement.log_index
+        });
+        if !known {
+            self.stealth_payments.push(payment);
+        }
+    }
+
+    pub fn stealth_payments(&self) -> &[StealthPayment] {
+        &self.stealth_payments
+    }
+
+    /// Signs a transaction spending from the stealth address of an ERC-5564
+    /// payment.
+    pub fn sign_stealth_transaction(
+        &self,
+        payment: &StealthPayment,
+        request: TransactionRequest,
+        nonce: u64,
+    ) -> NonosResult<SignedTransaction> {
+        let stealth = self.stealth_keypair.as_ref()
+            .ok_or_else(|| NonosError::Wallet("Wallet is locked".into()))?;
+
+        let private_key = derive_erc5564_stealth_private_key(stealth, &payment.announcement.ephemeral_pubkey)?;
+        if derive_eth_address_from_private(&private_key)? != payment.announcement.stealth_address {
+            return Err(NonosError::Wallet("Stealth payment is not addressed to this wallet".into()));
+        }
+
+        TransactionSigner::sign(request, nonce, &private_key)
+    }
+
     pub fn add_transaction(&mut self, tx: TransactionRecord) {
         self.transactions.push(tx);
     }
@@ -295,6 +536,71 @@ impl Wallet {
             tx.status = status;
         }
     }
+
+    pub fn record_transaction_outcome(
+        &mut self,
+        hash: &Blake3Hash,
+        status: TransactionStatus,
+        block_number: Option<u64>,
+    ) {
+        if let Some(tx) = self.transactions.iter_mut().find(|t| &t.hash == hash) {
+            tx.status = status;
+            tx.block_number = block_number;
+        }
+    }
+}
+
+fn account_private_key(
+    derivation: DerivationScheme,
+    master_key: Option<&Blake3Key>,
+    bip44_account: Option<&ExtendedPrivateKey>,
+    imported_key: Option<&Secp256k1PrivateKey>,
+    index: u32,
+) -> NonosResult<[u8; 32]> {
+    let locked = || NonosError::Wallet("Wallet is locked".into());
+
+    match derivation {
+        DerivationScheme::Blake3 => Ok(derive_secp256k1_key(master_key.ok_or_else(locked)?, 0, index)),
+        DerivationScheme::Bip44 => Ok(bip44_account.ok_or_else(locked)?.derive_child(index)?.private_key()),
+        DerivationScheme::Imported if index == 0 => Ok(imported_key.ok_or_else(locked)?.0),
+        DerivationScheme::Imported => Err(NonosError::Wallet(
+            "Imported key wallets only have account 0".into(),
+        )),
+    }
+}
+
+fn bip44_account_from_mnemonic(phrase: &str) -> NonosResult<ExtendedPrivateKey> {
+    let mut seed = mnemonic_to_seed(phrase, "")?;
+    let account = ExtendedPrivateKey::from_seed(&seed)
+        .and_then(|master| master.derive_path(ETH_BIP44_ACCOUNT_PATH));
+    seed.zeroize();
+    account
+}
+
+fn bip44_account_from_xprv(xprv: &str) -> NonosResult<ExtendedPrivateKey> {
+    let key = ExtendedPrivateKey::from_base58(xprv)?;
+    match key.depth() {
+        0 => key.derive_path(ETH_BIP44_ACCOUNT_PATH),
+        4 => Ok(key),
+        depth => Err(NonosError::Wallet(format!(
+            "Extended key at depth {} is neither a master nor an {} account key",
+            depth, ETH_BIP44_ACCOUNT_PATH
+        ))),
+    }
+}
+
+/// BIP-44 wallets have no BLAKE3 mnemonic key, so the key used for stealth
+/// addresses and storage is bound to the account node instead. Mnemonic and
+/// `xprv` imports therefore agree.
+fn bip44_master_key(account: &ExtendedPrivateKey) -> Blake3Key {
+    let mut private_key = account.private_key();
+    let key = blake3_derive_key(BIP44_MASTER_CONTEXT, &private_key);
+    private_key.zeroize();
+    key
+}
+
+fn imported_master_key(private_key: &Secp256k1PrivateKey) -> Blake3Key {
+    blake3_derive_key(IMPORTED_MASTER_CONTEXT, private_key.as_bytes())
 }
 
 impl Drop for Wallet {
@@ -307,6 +613,8 @@ pub struct WalletBuilder {
     name: Option<String>,
     mnemonic: Option<String>,
     blake3_key: Option<String>,
+    extended_key: Option<String>,
+    derivation: DerivationScheme,
 }
 
 impl WalletBuilder {
@@ -315,6 +623,8 @@ impl WalletBuilder {
             name: None,
             mnemonic: None,
             blake3_key: None,
+            extended_key: None,
+            derivation: DerivationScheme::Blake3,
         }
     }
 
@@ -333,17 +643,30 @@ impl WalletBuilder {
         self
     }
 
+    pub fn extended_key(mut self, xprv: impl Into<String>) -> Self {
+        self.extended_key = Some(xprv.into());
+        self
+    }
+
+    pub fn derivation(mut self, derivation: DerivationScheme) -> Self {
+        self.derivation = derivation;
+        self
+    }
+
     pub fn build(self) -> NonosResult<(Wallet, Option<String>, Option<String>)> {
         let name = self.name.unwrap_or_else(|| "Default Wallet".to_string());
 
         if let Some(mnemonic) = self.mnemonic {
-            let wallet = Wallet::import_from_mnemonic(name, &mnemonic)?;
+            let wallet = Wallet::import_from_mnemonic_with_derivation(name, &mnemonic, self.derivation)?;
+            Ok((wallet, None, None))
+        } else if let Some(xprv) = self.extended_key {
+            let wallet = Wallet::import_from_extended_key(name, &xprv)?;
             Ok((wallet, None, None))
         } else if let Some(key) = self.blake3_key {
             let wallet = Wallet::import_from_blake3_key(name, &key)?;
             Ok((wallet, None, None))
         } else {
-            let (wallet, mnemonic, key) = Wallet::create(name)?;
+            let (wallet, mnemonic, key) = Wallet::create_with_derivation(name, self.derivation)?;
             Ok((wallet, Some(mnemonic), Some(key)))
         }
     }
@@ -403,6 +726,126 @@ mod tests {
         assert!(signature.v == 27 || signature.v == 28);
     }
 
+    #[test]
+    fn test_sign_typed_data() {
+        let typed = TypedData::from_json(r#"{
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "chainId", "type": "uint256"},
+                    {"name": "verifyingContract", "type": "address"}
+                ],
+                "Permit": [
+                    {"name": "owner", "type": "address"},
+                    {"name": "spender", "type": "address"},
+                    {"name": "value", "type": "uint256"},
+                    {"name": "nonce", "type": "uint256"},
+                    {"name": "deadline", "type": "uint256"}
+                ]
+            },
+            "primaryType": "Permit",
+            "domain": {
+                "name": "NONOS",
+                "chainId": 8453,
+                "verifyingContract": "0x0000000000000000000000000000000000000001"
+            },
+            "message": {
+                "owner": "0x0000000000000000000000000000000000000002",
+                "spender": "0x0000000000000000000000000000000000000003",
+                "value": "1000000000000000000",
+                "nonce": 0,
+                "deadline": "0xffffffff"
+            }
+        }"#).unwrap();
+
+        let (wallet, _, _) = Wallet::create("Test Wallet".to_string()).unwrap();
+        let signature = wallet.sign_typed_data(0, &typed).unwrap();
+
+        let hash = typed.signing_hash().unwrap();
+        assert!(nonos_crypto::verify_signature(&signature, &hash, wallet.address()).unwrap());
+    }
+
+    #[test]
+    fn test_typed_data_matches_ethers() {
+        use ethers::types::transaction::eip712::Eip712;
+
+        let json = r#"{
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"}
+                ],
+                "Person": [
+                    {"name": "name", "type": "string"},
+                    {"name": "wallets", "type": "address[]"}
+                ],
+                "Group": [
+                    {"name": "members", "type": "Person[]"},
+                    {"name": "tags", "type": "bytes32[2]"},
+                    {"name": "delta", "type": "int64"},
+                    {"name": "active", "type": "bool"},
+                    {"name": "data", "type": "bytes"}
+                ]
+            },
+            "primaryType": "Group",
+            "domain": {"name": "Nested", "version": "2", "chainId": 1},
+            "message": {
+                "members": [
+                    {"name": "Alice", "wallets": [
+                        "0x0000000000000000000000000000000000000a11",
+                        "0x0000000000000000000000000000000000000a12"
+                    ]},
+                    {"name": "Bob", "wallets": []}
+                ],
+                "tags": [
+                    "0x0101010101010101010101010101010101010101010101010101010101010101",
+                    "0x0202020202020202020202020202020202020202020202020202020202020202"
+                ],
+                "delta": "0x2a",
+                "active": true,
+                "data": "0xdeadbeef"
+            }
+        }"#;
+
+        let ours = TypedData::from_json(json).unwrap().signing_hash().unwrap();
+        let reference: ethers::types::transaction::eip712::TypedData = serde_json::from_str(json).unwrap();
+        assert_eq!(ours, reference.encode_eip712().unwrap());
+    }
+
+    #[test]
+    fn test_keystore_interop_with_ethers() {
+        use ethers::signers::{LocalWallet, Signer};
+
+        let dir = std::env::temp_dir().join(format!("nonos-keystore-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let (wallet, _, _) = Wallet::create("Test Wallet".to_string()).unwrap();
+        let exported = wallet
+            .export_keystore(0, "interop", KeystoreKdf::Scrypt { log_n: 10, r: 8, p: 1 })
+            .unwrap();
+        let exported_path = dir.join("exported.json");
+        std::fs::write(&exported_path, exported.to_json().unwrap()).unwrap();
+
+        let decrypted = LocalWallet::decrypt_keystore(&exported_path, "interop").unwrap();
+        assert_eq!(decrypted.address().0, wallet.address().0);
+
+        let (reference, file_name) =
+            LocalWallet::new_keystore(&dir, &mut ethers::core::rand::thread_rng(), "interop", None).unwrap();
+        let json = std::fs::read_to_string(dir.join(file_name)).unwrap();
+        let keystore = KeystoreV3::from_json(&json).unwrap();
+
+        let imported = Wallet::import_from_private_key(
+            "Imported".to_string(),
+            nonos_crypto::decrypt_keystore(&keystore, b"interop").unwrap(),
+        ).unwrap();
+        assert_eq!(imported.address().0, reference.address().0);
+        assert_eq!(imported.derivation(), DerivationScheme::Imported);
+        assert!(imported.sign_hash(1, &[0; 32]).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_stealth_address() {
         let (wallet, _, _) = Wallet::create("Test Wallet".to_string()).unwrap();
@@ -413,6 +856,36 @@ mod tests {
         assert!(encoded.starts_with("st:eth:0x"));
     }
 
+    #[test]
+    fn test_erc5564_stealth_payment() {
+        use nonos_crypto::generate_erc5564_stealth_address;
+
+        let (mut wallet, _, _) = Wallet::create("Test Wallet".to_string()).unwrap();
+        let announced = generate_erc5564_stealth_address(&wallet.stealth_meta_address().unwrap()).unwrap();
+
+        let mut announcement = StealthAnnouncement {
+            scheme_id: ERC5564_SCHEME_ID,
+            stealth_address: announced.stealth_address,
+            caller: EthAddress::zero(),
+            ephemeral_pubkey: announced.ephemeral_pubkey.0.to_vec(),
+            metadata: vec![announced.view_tag],
+            block_number: 100,
+            tx_hash: [7u8; 32],
+            log_index: 3,
+        };
+
+        assert!(wallet.scan_stealth_announcement(&announcement).unwrap());
+        assert!(wallet.scan_stealth_announcement(&announcement).unwrap());
+        assert_eq!(wallet.stealth_payments().len(), 1);
+
+        let payment = wallet.stealth_payments()[0].clone();
+        let request = TransactionRequest::transfer(EthAddress::zero(), nonos_types::TokenAmount::from_raw(1, 18), 1);
+        assert!(wallet.sign_stealth_transaction(&payment, request, 0).is_ok());
+
+        announcement.stealth_address = *wallet.address();
+        assert!(!wallet.scan_stealth_announcement(&announcement).unwrap());
+    }
+
     #[test]
     fn test_account_derivation() {
         let (mut wallet, _, _) = Wallet::create("Test Wallet".to_string()).unwrap();
@@ -438,6 +911,58 @@ mod tests {
         assert!(key.is_some());
     }
 
+    #[test]
+    fn test_bip44_matches_reference_wallets() {
+        let phrase = "test test test test test test test test test test test junk";
+        let mut wallet = Wallet::import_from_mnemonic_with_derivation(
+            "BIP-44 Wallet".to_string(),
+            phrase,
+            DerivationScheme::Bip44,
+        ).unwrap();
+
+        assert_eq!(wallet.derivation(), DerivationScheme::Bip44);
+        assert_eq!(
+            wallet.address().to_hex().to_lowercase(),
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+        );
+        assert_eq!(
+            wallet.derive_account(1).unwrap().to_hex().to_lowercase(),
+            "0x70997970c51812dc3a010c7d01b50e0d17dc79c8"
+        );
+
+        let blake3 = Wallet::import_from_mnemonic("BLAKE3 Wallet".to_string(), phrase).unwrap();
+        assert_eq!(blake3.derivation(), DerivationScheme::Blake3);
+        assert_ne!(blake3.address(), wallet.address());
+    }
+
+    #[test]
+    fn test_bip44_extended_key_roundtrip() {
+        let (mut wallet, mnemonic, xprv) =
+            Wallet::create_with_derivation("BIP-44 Wallet".to_string(), DerivationScheme::Bip44).unwrap();
+        let address = *wallet.address();
+
+        assert!(xprv.starts_with("xprv"));
+        assert_eq!(wallet.export_extended_key().unwrap(), xprv);
+
+        let imported = Wallet::import_from_extended_key("Imported".to_string(), &xprv).unwrap();
+        assert_eq!(*imported.address(), address);
+        assert_eq!(
+            imported.stealth_meta_address().unwrap().encode(),
+            wallet.stealth_meta_address().unwrap().encode()
+        );
+
+        wallet.lock();
+        assert!(wallet.export_extended_key().is_err());
+        assert!(wallet.unlock_with_blake3_key(&"00".repeat(32)).is_err());
+
+        wallet.unlock_with_extended_key(&xprv).unwrap();
+        assert_eq!(*wallet.address(), address);
+
+        wallet.lock();
+        wallet.unlock_with_mnemonic(&mnemonic).unwrap();
+        assert!(wallet.sign_hash(0, &[1; 32]).is_ok());
+    }
+
     #[test]
     fn test_wrong_key_fails() {
         let (mut wallet, _, _) = Wallet::create("Test Wallet".to_string()).unwrap();
@@ -1,4 +1,5 @@
 use k256::ecdsa::SigningKey;
+use nonos_wallet::{FeeEstimate, FeeOracle, FeeSpeed, FEE_HISTORY_BLOCKS, FEE_HISTORY_PERCENTILES};
 use tiny_keccak::{Hasher, Keccak};
 
 // Network configuration
@@ -41,6 +42,8 @@ pub const BALANCE_OF_SELECTOR: &str = "70a08231";
 
 const SOCKS_PROXY: &str = "socks5h://127.0.0.1:9050";
 
+static FEE_ORACLE: std::sync::OnceLock<FeeOracle> = std::sync::OnceLock::new();
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Network {
     Mainnet,
@@ -219,7 +222,56 @@ pub async fn get_nonce_on_network(network: Network, address: &str) -> Result<u64
     Err(format!("Failed to get nonce on {}", network.name()))
 }
 
+/// Legacy gas price for `network`, derived from `eth_feeHistory` and falling
+/// back to `eth_gasPrice` for nodes without it.
 pub async fn get_gas_price_on_network(network: Network) -> Result<u128, String> {
+    match get_fee_estimate_on_network(network).await {
+        Ok(estimate) => Ok(estimate.gas_price(FeeSpeed::Normal)),
+        Err(_) => get_node_gas_price_on_network(network).await,
+    }
+}
+
+pub async fn get_fee_estimate_on_network(network: Network) -> Result<FeeEstimate, String> {
+    let oracle = FEE_ORACLE.get_or_init(FeeOracle::default);
+    if let Some(estimate) = oracle.cached(network.chain_id()) {
+        return Ok(estimate);
+    }
+
+    let client = build_client()?;
+
+    for endpoint in network.rpc_endpoints() {
+        let payload = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_feeHistory",
+            "params": [format!("0x{:x}", FEE_HISTORY_BLOCKS), "latest", FEE_HISTORY_PERCENTILES],
+            "id": 1
+        });
+
+        match client.post(*endpoint)
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await
+        {
+            Ok(response) => {
+                if let Ok(json) = response.json::<serde_json::Value>().await {
+                    if let Some(history) = json.get("result").filter(|r| r.is_object()) {
+                        if let Ok(estimate) = FeeEstimate::from_fee_history(history) {
+                            oracle.store(network.chain_id(), estimate.clone());
+                            return Ok(estimate);
+                        }
+                    }
+                }
+            }
+            Err(_) => continue,
+        }
+    }
+
+    Err(format!("Failed to get fee history on {}", network.name()))
+}
+
+async fn get_node_gas_price_on_network(network: Network) -> Result<u128, String> {
     let client = build_client()?;
 
     for endpoint in network.rpc_endpoints() {